@@ -0,0 +1,145 @@
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Auto exposure!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 1.6, -4.5),
+        vec3(0.0, 1.2, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(60.0),
+        0.1,
+        100.0,
+    );
+
+    // A dark room with a doorway out to a bright, sunlit exterior
+    let block = |center: Vec3, size: Vec3, color: Color| {
+        let mut block = Gm::new(
+            Mesh::new(&context, &CpuMesh::cube()),
+            PhysicalMaterial::new_opaque(
+                &context,
+                &CpuMaterial {
+                    albedo: color,
+                    roughness: 0.8,
+                    ..Default::default()
+                },
+            ),
+        );
+        block.set_transformation(
+            Mat4::from_translation(center)
+                * Mat4::from_nonuniform_scale(0.5 * size.x, 0.5 * size.y, 0.5 * size.z),
+        );
+        block
+    };
+    let wall = Color::new_opaque(200, 190, 170);
+    let blocks = vec![
+        block(
+            vec3(0.0, -0.1, 5.0),
+            vec3(40.0, 0.2, 40.0),
+            Color::new_opaque(90, 140, 70),
+        ),
+        // Roof, side walls and back wall
+        block(vec3(0.0, 3.1, -3.0), vec3(4.4, 0.2, 6.4), wall),
+        block(vec3(-2.1, 1.5, -3.0), vec3(0.2, 3.0, 6.4), wall),
+        block(vec3(2.1, 1.5, -3.0), vec3(0.2, 3.0, 6.4), wall),
+        block(vec3(0.0, 1.5, -6.1), vec3(4.4, 3.0, 0.2), wall),
+        // Front wall around the doorway
+        block(vec3(-1.55, 1.5, 0.1), vec3(1.1, 3.0, 0.2), wall),
+        block(vec3(1.55, 1.5, 0.1), vec3(1.1, 3.0, 0.2), wall),
+        block(vec3(0.0, 2.6, 0.1), vec3(2.0, 0.8, 0.2), wall),
+        // Something to look at inside and outside
+        block(
+            vec3(-1.2, 0.4, -2.5),
+            vec3(0.8, 0.8, 0.8),
+            Color::new_opaque(180, 60, 40),
+        ),
+        block(
+            vec3(-3.0, 1.0, 9.0),
+            vec3(2.0, 2.0, 2.0),
+            Color::new_opaque(40, 80, 180),
+        ),
+        block(
+            vec3(3.5, 1.5, 12.0),
+            vec3(3.0, 3.0, 3.0),
+            Color::new_opaque(220, 200, 60),
+        ),
+    ];
+
+    let ambient = AmbientLight::new(&context, 0.03, Color::WHITE);
+    let mut sun = DirectionalLight::new(&context, 4.0, Color::WHITE, &vec3(-0.3, -1.0, 0.4));
+    sun.generate_shadow_map(2048, blocks.iter().map(|block| &block.geometry));
+
+    let mut auto_exposure = AutoExposure::new(&context);
+    auto_exposure.metering = MeteringMode::CenterWeighted { falloff: 2.0 };
+
+    let mut color_texture = None;
+    let mut depth_texture = None;
+    window.render_loop(move |frame_input| {
+        let viewport = frame_input.viewport;
+        camera.set_viewport(viewport);
+
+        // Walk from the back of the room out through the doorway and back again
+        let t = 0.5 - 0.5 * (0.0002 * frame_input.accumulated_time as f32).cos();
+        let z = -4.5 + 12.0 * t;
+        camera.set_view(
+            vec3(0.0, 1.6, z),
+            vec3(0.0, 1.2, z + 4.0),
+            vec3(0.0, 1.0, 0.0),
+        );
+
+        // The scene is rendered to a half float texture to keep the precision in the bright parts
+        if color_texture
+            .as_ref()
+            .map(|texture: &Texture2D| {
+                texture.width() != viewport.width || texture.height() != viewport.height
+            })
+            .unwrap_or(true)
+        {
+            color_texture = Some(Texture2D::new_empty::<[f16; 4]>(
+                &context,
+                viewport.width,
+                viewport.height,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ));
+            depth_texture = Some(DepthTexture2D::new::<f32>(
+                &context,
+                viewport.width,
+                viewport.height,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ));
+        }
+        let color_texture = color_texture.as_mut().unwrap();
+        let depth_texture = depth_texture.as_mut().unwrap();
+        RenderTarget::new(
+            color_texture.as_color_target(None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.6, 0.8, 1.0, 1.0, 1.0))
+        .render(&camera, &blocks, &[&ambient, &sun]);
+
+        // The exposure adapts on the GPU, so nothing is read back
+        auto_exposure.update(
+            &context,
+            ColorTexture::Single(color_texture),
+            frame_input.elapsed_time,
+        );
+        frame_input
+            .screen()
+            .clear(ClearState::default())
+            .write(|| auto_exposure.apply(&context, ColorTexture::Single(color_texture)));
+
+        FrameOutput::default()
+    });
+}
@@ -0,0 +1,123 @@
+// Entry point for non-wasm
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main]
+async fn main() {
+    run().await;
+}
+
+use three_d::*;
+
+pub async fn run() {
+    let window = Window::new(WindowSettings {
+        title: "Clipping!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(3.0, 2.0, 4.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        1000.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 100.0);
+    let mut gui = three_d::GUI::new(&context);
+
+    let mut loaded = three_d_asset::io::load_async(&["examples/assets/suzanne.obj"])
+        .await
+        .unwrap();
+
+    let mut monkey =
+        Model::<PhysicalMaterial>::new(&context, &loaded.deserialize("suzanne.obj").unwrap())
+            .unwrap();
+    // Render the back faces as well, otherwise it is possible to look through the cut surface
+    monkey
+        .iter_mut()
+        .for_each(|m| m.material.render_states.cull = Cull::None);
+
+    let mut cpu_plane = CpuMesh::square();
+    cpu_plane
+        .transform(
+            &(Mat4::from_translation(vec3(0.0, -1.2, 0.0))
+                * Mat4::from_scale(10.0)
+                * Mat4::from_angle_x(degrees(-90.0))),
+        )
+        .unwrap();
+    let ground = Gm::new(
+        Mesh::new(&context, &cpu_plane),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(128, 200, 70),
+                ..Default::default()
+            },
+        ),
+    );
+
+    let ambient = AmbientLight::new(&context, 0.4, Color::WHITE);
+    let mut directional =
+        DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(-1.0, -1.0, -1.0));
+
+    // main loop
+    let mut section_position = 0.0;
+    let mut clip_shadows = false;
+    window.render_loop(move |mut frame_input| {
+        let mut panel_width = 0.0;
+        gui.update(
+            &mut frame_input.events,
+            frame_input.accumulated_time,
+            frame_input.viewport,
+            frame_input.device_pixel_ratio,
+            |gui_context| {
+                use three_d::egui::*;
+                SidePanel::left("side_panel").show(gui_context, |ui| {
+                    ui.heading("Debug Panel");
+                    ui.add(
+                        Slider::new::<f32>(&mut section_position, -1.5..=1.5).text("Section plane"),
+                    );
+                    ui.checkbox(&mut clip_shadows, "Clip shadows");
+                });
+                panel_width = gui_context.used_rect().width();
+            },
+        );
+
+        let viewport = Viewport {
+            x: (panel_width * frame_input.device_pixel_ratio) as i32,
+            y: 0,
+            width: frame_input.viewport.width
+                - (panel_width * frame_input.device_pixel_ratio) as u32,
+            height: frame_input.viewport.height,
+        };
+        camera.set_viewport(viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        // Everything on the positive side of the section plane is kept
+        context.set_clip_planes(ClipPlanes {
+            planes: vec![Plane::from_point_normal(
+                vec3(section_position, 0.0, 0.0),
+                vec3(-1.0, 0.0, 0.0),
+            )],
+            clip_shadows,
+        });
+        directional.generate_shadow_map(1024, &monkey);
+
+        let screen = frame_input.screen();
+        screen.clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0));
+        screen.render(
+            &camera,
+            monkey.into_iter().chain(&ground),
+            &[&ambient, &directional],
+        );
+
+        // The GUI should not be clipped
+        context.set_clip_planes(ClipPlanes::default());
+        screen.write(|| gui.render());
+
+        FrameOutput::default()
+    });
+}
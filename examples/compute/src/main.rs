@@ -0,0 +1,61 @@
+use three_d::*;
+
+const COUNT: u32 = 1024;
+const WORKGROUP_SIZE: u32 = 64;
+
+fn main() {
+    // Create a headless graphics context
+    let context = HeadlessContext::new().unwrap();
+
+    // Create a compute program which writes one period of a sine wave into a shader storage buffer
+    let program = match ComputeProgram::new(
+        &context,
+        "
+        layout (local_size_x = 64) in;
+
+        layout (std430, binding = 0) buffer Output {
+            float values[];
+        };
+
+        uniform uint count;
+
+        void main() {
+            uint index = gl_GlobalInvocationID.x;
+            if (index < count) {
+                values[index] = sin(6.2831853 * float(index) / float(count));
+            }
+        }
+        ",
+    ) {
+        Ok(program) => program,
+        Err(CoreError::ComputeShadersNotSupported) => {
+            println!("Compute shaders are not supported on this platform");
+            return;
+        }
+        Err(e) => panic!("{}", e),
+    };
+
+    // Create the output buffer and run the computation
+    let buffer = ShaderStorageBuffer::new(&context, COUNT as usize * std::mem::size_of::<f32>());
+    program.bind_ssbo(0, &buffer);
+    program.use_uniform("count", COUNT);
+    program.dispatch((COUNT + WORKGROUP_SIZE - 1) / WORKGROUP_SIZE, 1, 1);
+
+    // Read back the result and check that it is a sine wave
+    let values = buffer.read::<f32>().unwrap();
+    for (i, value) in values.iter().enumerate() {
+        let expected = (2.0 * std::f32::consts::PI * i as f32 / COUNT as f32).sin();
+        assert!(
+            (value - expected).abs() < 0.001,
+            "value {} is {} but {} was expected",
+            i,
+            value,
+            expected
+        );
+    }
+    println!(
+        "Computed {} values of a sine wave, the first values are {:?}",
+        values.len(),
+        &values[..8]
+    );
+}
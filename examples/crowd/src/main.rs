@@ -0,0 +1,195 @@
+use three_d::*;
+
+// The characters are tubes with a joint at every half unit of height
+const JOINT_COUNT: usize = 4;
+const SEGMENT_HEIGHT: f32 = 0.5;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Crowd!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 12.0, 30.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        200.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 5.0, 100.0);
+
+    let (body, skin) = character();
+    let skeleton = Skeleton {
+        joints: (0..JOINT_COUNT)
+            .map(|i| Joint {
+                parent: if i == 0 { None } else { Some(i - 1) },
+                translation: if i == 0 {
+                    Vec3::zero()
+                } else {
+                    vec3(0.0, SEGMENT_HEIGHT, 0.0)
+                },
+                rotation: Quat::one(),
+                scale: vec3(1.0, 1.0, 1.0),
+                inverse_bind_matrix: Mat4::from_translation(vec3(
+                    0.0,
+                    -SEGMENT_HEIGHT * i as f32,
+                    0.0,
+                )),
+            })
+            .collect(),
+    };
+    let clips = vec![
+        bend_clip("sway", vec3(0.0, 0.0, 1.0), 1.2),
+        bend_clip("bow", vec3(1.0, 0.0, 0.0), 0.8),
+    ];
+
+    // A grid of characters, each playing one of the clips with its own offset and speed
+    let (columns, rows) = (25, 20);
+    let mut instances = Vec::new();
+    let mut transformations = Vec::new();
+    for i in 0..columns * rows {
+        let random = |seed: f32| (seed * (i as f32 + 1.0)).sin().abs();
+        instances.push(CrowdInstance {
+            clip: i % 2,
+            time_offset: 3.0 * random(12.9898),
+            speed: 0.8 + 0.4 * random(78.233),
+            update: true,
+        });
+        transformations.push(
+            Mat4::from_translation(vec3(
+                1.5 * ((i % columns) as f32 - 0.5 * columns as f32) + 0.5 * random(39.425),
+                0.0,
+                1.5 * ((i / columns) as f32 - 0.5 * rows as f32) + 0.5 * random(93.989),
+            )) * Mat4::from_angle_y(degrees(360.0 * random(4.1414))),
+        );
+    }
+    let mut animator = CrowdAnimator::new(skeleton, clips, instances);
+    let mut crowd = InstancedSkinnedModel::new(
+        &context,
+        vec![(
+            &body,
+            &skin,
+            PhysicalMaterial::new_opaque(
+                &context,
+                &CpuMaterial {
+                    albedo: Color::new_opaque(220, 140, 80),
+                    roughness: 0.6,
+                    ..Default::default()
+                },
+            ),
+        )],
+        &transformations,
+        &animator,
+    );
+
+    let mut ground = Gm::new(
+        Mesh::new(&context, &CpuMesh::square()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(120, 150, 110),
+                ..Default::default()
+            },
+        ),
+    );
+    ground.set_transformation(Mat4::from_angle_x(degrees(-90.0)) * Mat4::from_scale(30.0));
+
+    let light = DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(-1.0, -1.0, -0.5));
+    let ambient = AmbientLight::new(&context, 0.3, Color::WHITE);
+
+    window.render_loop(move |mut frame_input| {
+        camera.set_viewport(frame_input.viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        // Distant characters keep their last pose, which is hardly noticeable but saves sampling the clips
+        let eye = *camera.position();
+        for (instance, transformation) in animator
+            .instances_mut()
+            .iter_mut()
+            .zip(transformations.iter())
+        {
+            instance.update = transformation.w.truncate().distance(eye) < 40.0;
+        }
+        animator.update(0.001 * frame_input.elapsed_time as f32);
+        crowd.update_joints(&animator);
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.7, 0.8, 0.9, 1.0, 1.0))
+            .render(
+                &camera,
+                crowd.into_iter().chain(&ground),
+                &[&light, &ambient],
+            );
+
+        FrameOutput::default()
+    });
+}
+
+///
+/// A vertical tube with a ring of vertices at every quarter of a segment, skinned to the joint below and the joint above each ring.
+///
+fn character() -> (CpuMesh, CpuSkin) {
+    let angle_subdivisions = 12;
+    let ring_count = 4 * (JOINT_COUNT - 1) + 1;
+    let mut positions = Vec::new();
+    let mut joints = Vec::new();
+    let mut weights = Vec::new();
+    for ring in 0..ring_count {
+        let height = ring as f32 * SEGMENT_HEIGHT / 4.0;
+        let joint = ((ring / 4) as u16).min(JOINT_COUNT as u16 - 2);
+        let t = height / SEGMENT_HEIGHT - joint as f32;
+        for j in 0..angle_subdivisions {
+            let angle = 2.0 * std::f32::consts::PI * j as f32 / angle_subdivisions as f32;
+            positions.push(vec3(0.25 * angle.cos(), height, 0.25 * angle.sin()));
+            joints.push([joint, joint + 1, 0, 0]);
+            weights.push(vec4(1.0 - t, t, 0.0, 0.0));
+        }
+    }
+    let mut indices = Vec::new();
+    for ring in 0..ring_count as u32 - 1 {
+        for j in 0..angle_subdivisions {
+            let a = ring * angle_subdivisions + j;
+            let b = ring * angle_subdivisions + (j + 1) % angle_subdivisions;
+            let c = a + angle_subdivisions;
+            let d = b + angle_subdivisions;
+            indices.extend_from_slice(&[a, c, b, b, c, d]);
+        }
+    }
+    let mut mesh = CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        ..Default::default()
+    };
+    mesh.compute_normals();
+    (mesh, CpuSkin { joints, weights })
+}
+
+///
+/// A looping clip which bends every joint back and forth around the given axis.
+///
+fn bend_clip(name: &str, axis: Vec3, duration: f32) -> AnimationClip {
+    AnimationClip {
+        name: name.to_string(),
+        tracks: (1..JOINT_COUNT)
+            .map(|joint| {
+                let bend = |angle: f32| Quat::from_axis_angle(axis, degrees(angle));
+                AnimationTrack::Quat(Track {
+                    target: AnimationTarget::Rotation(joint),
+                    interpolation: KeyframeInterpolation::Linear,
+                    keyframes: vec![
+                        Keyframe::new(0.0, bend(-12.0), Quat::zero()),
+                        Keyframe::new(0.5 * duration, bend(12.0), Quat::zero()),
+                        Keyframe::new(duration, bend(-12.0), Quat::zero()),
+                    ],
+                })
+            })
+            .collect(),
+    }
+}
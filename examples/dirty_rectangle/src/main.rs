@@ -0,0 +1,122 @@
+use three_d::*;
+
+/// The number of discs in each row and column of the background.
+const GRID_SIZE: u32 = 24;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Dirty rectangle!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+    let radius = 40.0 * window.device_pixel_ratio();
+
+    let mut spotlight = Gm::new(
+        Circle::new(&context, vec2(0.0, 0.0), radius),
+        ColorMaterial {
+            color: Color::new(255, 255, 255, 160),
+            render_states: RenderStates {
+                blend: Blend::TRANSPARENCY,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let mut background = Vec::new();
+    let mut viewport = Viewport::new_at_origo(1, 1);
+    let mut spotlight_box: Option<ScissorBox> = None;
+    window.render_loop(move |frame_input| {
+        let camera = camera2d(frame_input.viewport);
+        let screen = frame_input.screen();
+
+        if frame_input.first_frame || frame_input.viewport != viewport {
+            // The content of the screen is discarded when the window is resized, so the whole screen is redrawn
+            viewport = frame_input.viewport;
+            background = create_background(&context, viewport);
+            spotlight_box = None;
+            screen
+                .clear(ClearState::color_and_depth(0.1, 0.1, 0.1, 1.0, 1.0))
+                .render(&camera, &background, &[]);
+        }
+
+        for event in frame_input.events.iter() {
+            if let Event::MouseMotion { position, .. } = event {
+                let center = position.to_physical();
+                spotlight.set_center(center);
+                let new_box = ScissorBox {
+                    x: (center.x - radius).floor() as i32,
+                    y: (center.y - radius).floor() as i32,
+                    width: (2.0 * radius).ceil() as u32 + 1,
+                    height: (2.0 * radius).ceil() as u32 + 1,
+                };
+
+                // Only the part of the screen covered by the spotlight before and after the motion is redrawn,
+                // the rest of the screen keeps the content from the previous frame
+                let dirty_box = spotlight_box.map_or(new_box, |old_box| union(old_box, new_box));
+                screen
+                    .clear_partially(
+                        dirty_box,
+                        ClearState::color_and_depth(0.1, 0.1, 0.1, 1.0, 1.0),
+                    )
+                    .render_partially(dirty_box, &camera, &background, &[])
+                    .render_partially(new_box, &camera, &spotlight, &[]);
+                spotlight_box = Some(new_box);
+            }
+        }
+
+        FrameOutput {
+            preserve_screen: true,
+            wait_next_event: true,
+            ..Default::default()
+        }
+    });
+}
+
+///
+/// Creates a grid of colored discs which covers the given viewport.
+///
+fn create_background(context: &Context, viewport: Viewport) -> Vec<Gm<Circle, ColorMaterial>> {
+    let cell_width = viewport.width as f32 / GRID_SIZE as f32;
+    let cell_height = viewport.height as f32 / GRID_SIZE as f32;
+    let mut discs = Vec::new();
+    for i in 0..GRID_SIZE {
+        for j in 0..GRID_SIZE {
+            discs.push(Gm::new(
+                Circle::new(
+                    context,
+                    vec2(
+                        (i as f32 + 0.5) * cell_width,
+                        (j as f32 + 0.5) * cell_height,
+                    ),
+                    0.4 * cell_width.min(cell_height),
+                ),
+                ColorMaterial {
+                    color: Color::new_opaque(
+                        (255 * i / GRID_SIZE) as u8,
+                        (255 * j / GRID_SIZE) as u8,
+                        160,
+                    ),
+                    ..Default::default()
+                },
+            ));
+        }
+    }
+    discs
+}
+
+///
+/// Returns the smallest scissor box which contains both of the given scissor boxes.
+///
+fn union(a: ScissorBox, b: ScissorBox) -> ScissorBox {
+    let x = a.x.min(b.x);
+    let y = a.y.min(b.y);
+    ScissorBox {
+        x,
+        y,
+        width: ((a.x + a.width as i32).max(b.x + b.width as i32) - x) as u32,
+        height: ((a.y + a.height as i32).max(b.y + b.height as i32) - y) as u32,
+    }
+}
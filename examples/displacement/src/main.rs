@@ -0,0 +1,114 @@
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Displacement!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(6.0, 4.0, 6.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        1000.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 100.0);
+
+    // A height map with waves which repeats seamlessly, since all frequencies are whole numbers of periods across the texture
+    let size = 256;
+    let mut heights = Vec::with_capacity(size * size);
+    for y in 0..size {
+        for x in 0..size {
+            let u = x as f32 / size as f32 * std::f32::consts::TAU;
+            let v = y as f32 / size as f32 * std::f32::consts::TAU;
+            let h = 0.5 * (2.0 * u + v).sin() + 0.3 * (3.0 * v - u).sin() + 0.2 * (5.0 * u).cos();
+            heights.push(((0.5 + 0.5 * h) * 255.0) as u8);
+        }
+    }
+    let height_map = Texture2DRef::from(std::sync::Arc::new(Texture2D::new(
+        &context,
+        &CpuTexture {
+            data: TextureData::RU8(heights),
+            width: size as u32,
+            height: size as u32,
+            wrap_s: Wrapping::Repeat,
+            wrap_t: Wrapping::Repeat,
+            ..Default::default()
+        },
+    )));
+
+    // The displacement is applied per vertex, so the water plane needs many vertices
+    let mut water = Gm::new(
+        Mesh::new(&context, &grid(200, 10.0)),
+        PhysicalMaterial {
+            albedo: Color::new_opaque(30, 90, 130),
+            metallic: 0.0,
+            roughness: 0.2,
+            displacement: Some(Displacement {
+                uv_scale: vec2(2.0, 2.0),
+                ..Displacement::new(height_map, 0.4)
+            }),
+            ..Default::default()
+        },
+    );
+
+    let ambient = AmbientLight::new(&context, 0.3, Color::WHITE);
+    let directional = DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(-1.0, -1.0, -0.5));
+
+    window.render_loop(move |mut frame_input| {
+        camera.set_viewport(frame_input.viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        // Scroll the height map to animate the waves
+        let time = frame_input.accumulated_time as f32 * 0.001;
+        if let Some(displacement) = &mut water.material.displacement {
+            displacement.uv_offset = vec2(0.05 * time, 0.02 * time);
+        }
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
+            .render(&camera, &water, &[&ambient, &directional]);
+
+        FrameOutput::default()
+    });
+}
+
+///
+/// Creates a square grid in the xz-plane centered at the origin with the given number of cells along each side and the given side length.
+///
+fn grid(cells: u32, side_length: f32) -> CpuMesh {
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    let mut uvs = Vec::new();
+    for j in 0..=cells {
+        for i in 0..=cells {
+            let u = i as f32 / cells as f32;
+            let v = j as f32 / cells as f32;
+            positions.push(vec3((u - 0.5) * side_length, 0.0, (v - 0.5) * side_length));
+            normals.push(vec3(0.0, 1.0, 0.0));
+            uvs.push(vec2(u, v));
+        }
+    }
+    let mut indices = Vec::new();
+    for j in 0..cells {
+        for i in 0..cells {
+            let a = j * (cells + 1) + i;
+            let b = a + cells + 1;
+            indices.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+        }
+    }
+    CpuMesh {
+        positions: Positions::F32(positions),
+        normals: Some(normals),
+        uvs: Some(uvs),
+        indices: Indices::U32(indices),
+        ..Default::default()
+    }
+}
@@ -40,11 +40,13 @@ pub async fn run() {
         .expect("failed to download the necessary assets, to enable running this example offline, place the relevant assets in a folder called 'assets' next to the three-d source")
     };
 
-    let skybox = Skybox::new_from_equirectangular(
+    let mut skybox = Skybox::new_from_equirectangular(
         &context,
         &loaded.deserialize("chinese_garden_4k").unwrap(),
     );
-    let light = AmbientLight::new_with_environment(&context, 1.0, Color::WHITE, skybox.texture());
+    let mut light =
+        AmbientLight::new_with_environment(&context, 1.0, Color::WHITE, skybox.texture());
+    let mut sun = DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(0.0, -1.0, 0.0));
 
     let mut model = Gm::new(
         Mesh::new(&context, &CpuMesh::sphere(32)),
@@ -57,10 +59,25 @@ pub async fn run() {
             },
         ),
     );
+    model.set_transformation(Mat4::from_translation(vec3(0.0, 1.0, 0.0)));
+    let mut ground = Gm::new(
+        Mesh::new(&context, &CpuMesh::square()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(150, 150, 150),
+                roughness: 0.9,
+                ..Default::default()
+            },
+        ),
+    );
+    ground.set_transformation(Mat4::from_angle_x(degrees(-90.0)) * Mat4::from_scale(5.0));
     let mut gui = three_d::GUI::new(&context);
 
     // main loop
     let mut color = [1.0; 4];
+    let mut rotation = 0.0;
+    let mut intensity = 1.0;
     window.render_loop(move |mut frame_input| {
         let mut panel_width = 0.0;
         gui.update(
@@ -75,12 +92,21 @@ pub async fn run() {
                     ui.add(Slider::new(&mut model.material.metallic, 0.0..=1.0).text("Metallic"));
                     ui.add(Slider::new(&mut model.material.roughness, 0.0..=1.0).text("Roughness"));
                     ui.color_edit_button_rgba_unmultiplied(&mut color);
+                    ui.add(Slider::new(&mut rotation, 0.0..=360.0).text("Environment rotation"));
+                    ui.add(Slider::new(&mut intensity, 0.0..=2.0).text("Environment intensity"));
                 });
                 panel_width = gui_context.used_rect().width();
             },
         );
         model.material.albedo = Color::from_rgba_slice(&color);
 
+        // Rotate and dim the skybox and the image based lighting together and align the sun light and its shadows with the sun in the environment map
+        let environment = light.environment.as_mut().unwrap();
+        skybox.set_rotation_and_intensity(environment, degrees(rotation), intensity);
+        sun.direction = -environment.sample_sun_direction();
+        sun.intensity = 2.0 * intensity;
+        sun.generate_shadow_map(1024, &model);
+
         let viewport = Viewport {
             x: (panel_width * frame_input.device_pixel_ratio) as i32,
             y: 0,
@@ -94,7 +120,11 @@ pub async fn run() {
         frame_input
             .screen()
             .clear(ClearState::color_and_depth(0.5, 0.5, 0.5, 1.0, 1.0))
-            .render(&camera, skybox.into_iter().chain(&model), &[&light])
+            .render(
+                &camera,
+                skybox.into_iter().chain(&model).chain(&ground),
+                &[&light, &sun],
+            )
             .write(|| gui.render());
 
         FrameOutput::default()
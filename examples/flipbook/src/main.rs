@@ -0,0 +1,132 @@
+use std::sync::Arc;
+use three_d::*;
+
+/// The number of explosions.
+const COUNT: usize = 500;
+/// The number of frames in each row and column of the sprite sheet.
+const GRID_SIZE: u32 = 4;
+/// The size in pixels of each frame in the sprite sheet.
+const FRAME_SIZE: u32 = 64;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Flipbook!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 8.0, 30.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        1000.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 200.0);
+
+    // The explosions are spread out in a sphere and start at different times, so they are all on different frames,
+    // but they are still rendered in a single draw call since the frame of each explosion is computed on the GPU
+    let centers = (0..COUNT)
+        .map(|i| {
+            let direction = vec3(
+                random(3 * i) - 0.5,
+                random(3 * i + 1) - 0.5,
+                random(3 * i + 2) - 0.5,
+            );
+            direction.normalize() * 10.0 * random(i + COUNT).cbrt()
+        })
+        .collect::<Vec<_>>();
+    let start_times = (0..COUNT)
+        .map(|i| 2.0 * random(i + 2 * COUNT))
+        .collect::<Vec<_>>();
+    let mut sprites = Sprites::new(&context, &centers, None);
+    sprites.set_start_times(Some(&start_times));
+    sprites.set_transformation(Mat4::from_scale(0.8));
+
+    let mut explosions = Gm::new(
+        sprites,
+        ColorMaterial {
+            texture: Some(Texture2DRef::from(Arc::new(Texture2D::new(
+                &context,
+                &sprite_sheet(),
+            )))),
+            flipbook: Some(Flipbook {
+                // Blending the frames gives a smooth animation even though there are only 16 frames
+                blend_frames: true,
+                ..Flipbook::new(GRID_SIZE, GRID_SIZE, 12.0)
+            }),
+            // Additive blending does not depend on the order of the explosions
+            render_states: RenderStates {
+                write_mask: WriteMask::COLOR,
+                blend: Blend::ADD,
+                ..Default::default()
+            },
+            is_transparent: true,
+            ..Default::default()
+        },
+    );
+
+    window.render_loop(move |mut frame_input| {
+        camera.set_viewport(frame_input.viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+        explosions.animate(0.001 * frame_input.accumulated_time as f32);
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.02, 0.02, 0.05, 1.0, 1.0))
+            .render(&camera, &explosions, &[]);
+
+        FrameOutput::default()
+    });
+}
+
+///
+/// Generates a sprite sheet with an explosion, which expands and fades from a white hot fireball to a dark red cloud.
+/// The frames are ordered from left to right starting in the top row, as expected by [Flipbook].
+///
+fn sprite_sheet() -> CpuTexture {
+    let size = GRID_SIZE * FRAME_SIZE;
+    let mut data = vec![[0u8; 4]; (size * size) as usize];
+    for frame in 0..GRID_SIZE * GRID_SIZE {
+        let t = frame as f32 / (GRID_SIZE * GRID_SIZE - 1) as f32;
+        let radius = 0.3 + 0.6 * t;
+        let (column, row) = (frame % GRID_SIZE, frame / GRID_SIZE);
+        for y in 0..FRAME_SIZE {
+            for x in 0..FRAME_SIZE {
+                let u = 2.0 * (x as f32 + 0.5) / FRAME_SIZE as f32 - 1.0;
+                let v = 2.0 * (y as f32 + 0.5) / FRAME_SIZE as f32 - 1.0;
+                let distance = (u * u + v * v).sqrt() / radius;
+                let intensity = (1.0 - distance).max(0.0) * (1.0 - t);
+                let color = vec3(1.0, 0.9 - 0.6 * t, 0.6 - 0.6 * t) * intensity;
+                let index = (row * FRAME_SIZE + y) * size + column * FRAME_SIZE + x;
+                data[index as usize] = [
+                    (255.0 * color.x) as u8,
+                    (255.0 * color.y) as u8,
+                    (255.0 * color.z) as u8,
+                    (255.0 * intensity) as u8,
+                ];
+            }
+        }
+    }
+    CpuTexture {
+        data: TextureData::RgbaU8(data),
+        width: size,
+        height: size,
+        ..Default::default()
+    }
+}
+
+///
+/// A pseudo random number in the range `[0, 1)` for the given index.
+///
+fn random(index: usize) -> f32 {
+    let mut x = index as u32 ^ 0x9e37_79b9;
+    x = (x ^ (x >> 16)).wrapping_mul(0x7feb_352d);
+    x = (x ^ (x >> 15)).wrapping_mul(0x846c_a68b);
+    x ^= x >> 16;
+    x as f32 / 4_294_967_296.0
+}
@@ -0,0 +1,100 @@
+// Entry point for non-wasm
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main]
+async fn main() {
+    run().await;
+}
+
+use three_d::*;
+
+pub async fn run() {
+    let window = Window::new(WindowSettings {
+        title: "Frame graph!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(4.0, 4.0, 5.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        1000.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 100.0);
+
+    let mut loaded = three_d_asset::io::load_async(&["examples/assets/suzanne.obj"])
+        .await
+        .unwrap();
+
+    let mut monkey =
+        Model::<PhysicalMaterial>::new(&context, &loaded.deserialize("suzanne.obj").unwrap())
+            .unwrap();
+    monkey
+        .iter_mut()
+        .for_each(|m| m.material.render_states.cull = Cull::Back);
+
+    let ambient = AmbientLight::new(&context, 0.4, Color::WHITE);
+    let directional = DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(-1.0, -1.0, -1.0));
+
+    let fog_effect = FogEffect {
+        color: Color::new_opaque(200, 200, 200),
+        density: 0.2,
+        animation: 0.1,
+    };
+    let fxaa_effect = FxaaEffect {};
+    let vignette_effect = VignetteEffect::default();
+
+    // The frame graph is kept between frames so the textures are only allocated when the size of the window changes
+    let mut frame_graph = FrameGraph::new(&context);
+    window.render_loop(move |mut frame_input| {
+        camera.set_viewport(frame_input.viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        // The passes are given in an arbitrary order, the frame graph executes them in the order required by the textures they read and write
+        let screen = frame_input.screen();
+        let viewport = frame_input.viewport;
+        let passes = vec![
+            FramePass::new("present", |resources| {
+                screen.copy_from_color(
+                    ColorTexture::Single(resources.color_texture("final")),
+                    viewport,
+                    WriteMask::default(),
+                );
+            })
+            .read("final"),
+            vignette_effect.frame_pass(&context, "antialiased", "final", FrameTextureFormat::Rgba8),
+            fxaa_effect.frame_pass(&context, "color", "antialiased", FrameTextureFormat::Rgba8),
+            fog_effect.frame_pass(
+                &context,
+                frame_input.accumulated_time,
+                &camera,
+                "depth",
+                "color",
+            ),
+            FramePass::new("opaque", |resources| {
+                resources
+                    .render_target("color", "depth")
+                    .clear(ClearState::default())
+                    .render(&camera, &monkey, &[&ambient, &directional]);
+            })
+            .write(
+                "color",
+                FrameTextureFormat::Rgba8,
+                FrameTextureSize::viewport(),
+            )
+            .write(
+                "depth",
+                FrameTextureFormat::Depth32F,
+                FrameTextureSize::viewport(),
+            ),
+        ];
+        frame_graph.execute(frame_input.viewport, passes);
+
+        FrameOutput::default()
+    });
+}
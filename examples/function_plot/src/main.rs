@@ -0,0 +1,124 @@
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Function plot!".to_string(),
+        max_size: Some((1280, 720)),
+        surface_settings: SurfaceSettings {
+            // The stencil buffer is used to draw the transparent curve only once where it overlaps itself
+            stencil_buffer: 8,
+            ..Default::default()
+        },
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut curve = Gm::new(
+        Polyline::new(&context, &[], 1.0),
+        ColorMaterial {
+            color: Color::new(30, 90, 200, 180),
+            render_states: RenderStates {
+                blend: Blend::TRANSPARENCY,
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+    curve.set_join(LineJoin::Round);
+    curve.set_cap(LineCap::Round);
+    curve.set_draw_once(true);
+    let mut axes = [
+        Gm::new(
+            Polyline::new(&context, &[], 1.0),
+            ColorMaterial {
+                color: Color::new_opaque(120, 120, 120),
+                ..Default::default()
+            },
+        ),
+        Gm::new(
+            Polyline::new(&context, &[], 1.0),
+            ColorMaterial {
+                color: Color::new_opaque(120, 120, 120),
+                ..Default::default()
+            },
+        ),
+    ];
+
+    // The center of the view in plot units and the number of logical pixels per plot unit
+    let mut center = vec2(0.0f32, 0.0);
+    let mut scale = 60.0f32;
+
+    window.render_loop(move |mut frame_input| {
+        let device_pixel_ratio = frame_input.device_pixel_ratio;
+        let viewport = frame_input.viewport;
+        let half_size = 0.5 * vec2(viewport.width as f32, viewport.height as f32);
+        for event in frame_input.events.iter_mut() {
+            match event {
+                Event::MouseWheel {
+                    delta,
+                    position,
+                    handled,
+                    ..
+                } => {
+                    // Zoom towards the cursor, so the point under the cursor stays under the cursor
+                    let pixel = Vec2::from(position.to_physical()) - half_size;
+                    let before = center + pixel / (scale * device_pixel_ratio);
+                    scale = (scale * (1.0 + 0.002 * delta.1)).clamp(1.0, 10000.0);
+                    center = before - pixel / (scale * device_pixel_ratio);
+                    *handled = true;
+                }
+                Event::MouseMotion {
+                    button: Some(MouseButton::Left),
+                    delta,
+                    handled,
+                    ..
+                } => {
+                    center -= vec2(delta.0, -delta.1) / scale;
+                    *handled = true;
+                }
+                _ => {}
+            }
+        }
+
+        // The plot is mapped to physical pixels, so the thickness of the lines is the same at all zoom levels
+        let to_pixels = |point: Vec2| half_size + (point - center) * scale * device_pixel_ratio;
+        let step = 2.0 / (scale * device_pixel_ratio);
+        let left = center.x - half_size.x / (scale * device_pixel_ratio);
+        let points: Vec<Vec2> = (0..=(viewport.width / 2 + 1))
+            .map(|i| {
+                let x = left + i as f32 * step;
+                to_pixels(vec2(x, function(x)))
+            })
+            .collect();
+        curve.update_points(&points);
+        curve.set_thickness(6.0 * device_pixel_ratio);
+
+        let origin = to_pixels(vec2(0.0, 0.0));
+        axes[0].update_points(&[vec2(0.0, origin.y), vec2(viewport.width as f32, origin.y)]);
+        axes[1].update_points(&[vec2(origin.x, 0.0), vec2(origin.x, viewport.height as f32)]);
+        for axis in axes.iter_mut() {
+            axis.set_thickness(device_pixel_ratio);
+        }
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(1.0, 1.0, 1.0, 1.0, 1.0))
+            .render(
+                &camera2d(viewport),
+                axes.iter().chain(std::iter::once(&curve)),
+                &[],
+            );
+
+        FrameOutput::default()
+    });
+}
+
+///
+/// The plotted function, which has both slow and fast changes, so sharp turns are visible when zooming out.
+///
+fn function(x: f32) -> f32 {
+    (3.0 * x).sin() * (-0.05 * x * x).exp() + 0.3 * (17.0 * x).sin()
+}
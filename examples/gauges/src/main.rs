@@ -0,0 +1,133 @@
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Gauges!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    // The shapes overlap, so they are drawn on top of each other in the order they are given (see the render call) instead of using the depth test
+    let material = |color: Color| ColorMaterial {
+        color,
+        render_states: RenderStates {
+            depth_test: DepthTest::Always,
+            write_mask: WriteMask::COLOR,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    let mut gauges = Vec::new();
+    for color in [
+        Color::new_opaque(80, 200, 120),
+        Color::new_opaque(250, 180, 50),
+        Color::new_opaque(230, 70, 70),
+    ] {
+        let background = Gm::new(
+            Ring::new(&context, vec2(0.0, 0.0), 1.0, 0.0),
+            material(Color::new_opaque(40, 40, 50)),
+        );
+        let track = Gm::new(
+            Arc::new(
+                &context,
+                vec2(0.0, 0.0),
+                1.0,
+                degrees(-45.0),
+                degrees(225.0),
+                1.0,
+            ),
+            material(Color::new_opaque(70, 70, 80)),
+        );
+        let value = Gm::new(
+            Arc::new(
+                &context,
+                vec2(0.0, 0.0),
+                1.0,
+                degrees(0.0),
+                degrees(225.0),
+                1.0,
+            ),
+            material(color),
+        );
+        let needle = Gm::new(
+            Line::new(&context, vec2(0.0, 0.0), vec2(0.0, 0.0), 1.0),
+            material(Color::WHITE),
+        );
+        let hub = Gm::new(
+            Circle::new(&context, vec2(0.0, 0.0), 1.0),
+            material(Color::new_opaque(40, 40, 50)),
+        );
+        let glare = Gm::new(
+            Ellipse::new(&context, vec2(0.0, 0.0), degrees(0.0), 1.0, 1.0),
+            material(Color::new(255, 255, 255, 20)),
+        );
+        gauges.push((background, track, value, needle, hub, glare));
+    }
+
+    window.render_loop(move |frame_input| {
+        // Place the gauges next to each other and scale them with the window, all sizes are in physical pixels
+        let viewport = frame_input.viewport;
+        let scale = frame_input.device_pixel_ratio;
+        let radius = (viewport.width as f32 / 7.0).min(viewport.height as f32 * 0.4);
+        let time = frame_input.accumulated_time as f32 * 0.001;
+
+        for (i, (background, track, value, needle, hub, glare)) in gauges.iter_mut().enumerate() {
+            let center = vec2(
+                viewport.width as f32 * (i as f32 + 0.5) / 3.0,
+                viewport.height as f32 * 0.5,
+            );
+            let level = 0.5 + 0.5 * (time * (0.7 + 0.4 * i as f32)).sin();
+            let angle = (225.0 - 270.0 * level).to_radians();
+
+            background.set_center(center);
+            background.set_radius(radius);
+            background.set_inner_radius(0.0);
+            background.set_border(3.0 * scale, Color::new_opaque(120, 120, 130));
+
+            track.set_center(center);
+            track.set_radius(0.85 * radius);
+            track.set_thickness(0.12 * radius);
+
+            value.set_center(center);
+            value.set_radius(0.85 * radius);
+            value.set_thickness(0.12 * radius);
+            value.set_angles(radians(angle), degrees(225.0));
+
+            let direction = vec2(angle.cos(), angle.sin());
+            needle.set_endpoints(center, center + 0.8 * radius * direction);
+            needle.set_thickness(4.0 * scale);
+
+            hub.set_center(center);
+            hub.set_radius(0.1 * radius);
+            hub.set_border(2.0 * scale, Color::WHITE);
+
+            glare.set_center(center + vec2(0.0, 0.45 * radius));
+            glare.set_radii(0.6 * radius, 0.3 * radius);
+        }
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.1, 0.1, 0.12, 1.0, 1.0))
+            .render_with_order(
+                &camera2d(viewport),
+                gauges
+                    .iter()
+                    .flat_map(|(background, track, value, needle, hub, glare)| {
+                        background
+                            .into_iter()
+                            .chain(track)
+                            .chain(value)
+                            .chain(needle)
+                            .chain(hub)
+                            .chain(glare)
+                    }),
+                &[],
+                |a, b| a.index.cmp(&b.index),
+            );
+
+        FrameOutput::default()
+    });
+}
@@ -0,0 +1,138 @@
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Gizmo!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(5.0, 4.0, 8.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        1000.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 100.0);
+
+    // The object which the gizmo is attached to and a wall in front of it
+    let mut cube = Gm::new(
+        Mesh::new(&context, &CpuMesh::cube()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(200, 200, 200),
+                ..Default::default()
+            },
+        ),
+    );
+    let mut wall = Gm::new(
+        Mesh::new(&context, &CpuMesh::square()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(100, 100, 150),
+                ..Default::default()
+            },
+        ),
+    );
+    wall.set_transformation(Mat4::from_translation(vec3(0.0, 0.0, 2.0)));
+
+    // A gizmo consisting of three arrows, which is always 100 pixels long, rendered on top of everything and pickable
+    let mut arrow = CpuMesh::arrow(0.8, 0.6, 16);
+    arrow
+        .transform(&Mat4::from_nonuniform_scale(1.0, 0.04, 0.04))
+        .unwrap();
+    let rotations = [
+        Mat4::identity(),
+        Mat4::from_angle_z(degrees(90.0)),
+        Mat4::from_angle_y(degrees(-90.0)),
+    ];
+    let mut arrows = [Color::RED, Color::GREEN, Color::BLUE].map(|color| {
+        GmWithOptions::new(Gm::new(
+            Mesh::new(&context, &arrow),
+            ColorMaterial {
+                color,
+                ..Default::default()
+            },
+        ))
+        .with_screen_space_scale(100.0)
+        .with_depth_test(DepthTest::Always)
+        .with_render_order(1)
+    });
+
+    let ambient = AmbientLight::new(&context, 0.4, Color::WHITE);
+    let directional = DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(-1.0, -1.0, -1.0));
+
+    let mut position = vec3(0.0, 0.0, 0.0);
+    let mut dragged_axis = None;
+    window.render_loop(move |mut frame_input| {
+        camera.set_viewport(frame_input.viewport);
+
+        // Drag the cube along the axis of the picked arrow
+        for event in frame_input.events.iter_mut() {
+            match event {
+                Event::MousePress {
+                    button,
+                    position: pixel,
+                    handled,
+                    ..
+                } if *button == MouseButton::Left => {
+                    dragged_axis = arrows
+                        .iter()
+                        .zip([Vec3::unit_x(), Vec3::unit_y(), Vec3::unit_z()])
+                        .filter_map(|(arrow, axis)| {
+                            pick(&context, &camera, *pixel, arrow)
+                                .map(|p| (p.distance2(camera.position()), axis))
+                        })
+                        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+                        .map(|(_, axis)| axis);
+                    *handled = dragged_axis.is_some();
+                }
+                Event::MouseMotion { delta, handled, .. } => {
+                    if let Some(axis) = dragged_axis {
+                        let screen_axis =
+                            camera.projection() * camera.view() * (position + axis).extend(1.0)
+                                - camera.projection() * camera.view() * position.extend(1.0);
+                        let screen_axis = vec2(screen_axis.x, screen_axis.y);
+                        if screen_axis.magnitude2() > 0.0 {
+                            let movement =
+                                vec2(delta.0, -delta.1).dot(screen_axis.normalize()) * 0.01;
+                            position += axis * movement;
+                        }
+                        *handled = true;
+                    }
+                }
+                Event::MouseRelease { button, .. } if *button == MouseButton::Left => {
+                    dragged_axis = None;
+                }
+                _ => {}
+            }
+        }
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        cube.set_transformation(Mat4::from_translation(position) * Mat4::from_scale(0.5));
+        for (arrow, rotation) in arrows.iter_mut().zip(rotations) {
+            arrow.set_transformation(Mat4::from_translation(position) * rotation);
+            arrow.set_screen_space_origin(position);
+        }
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
+            .render(
+                &camera,
+                cube.into_iter()
+                    .chain(&wall)
+                    .chain(arrows.iter().flat_map(|arrow| arrow.into_iter())),
+                &[&ambient, &directional],
+            );
+
+        FrameOutput::default()
+    });
+}
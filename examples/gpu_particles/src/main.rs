@@ -0,0 +1,110 @@
+use three_d::core::*;
+use three_d::window::{FrameOutput, Window, WindowSettings};
+
+const PARTICLE_COUNT: usize = 100_000;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "GPU Particles!".to_string(),
+        #[cfg(not(target_arch = "wasm32"))]
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context: Context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 2.0, 8.0),
+        vec3(0.0, 1.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        100.0,
+    );
+
+    // The state of the particles, which is updated on the GPU by swapping between two sets of buffers each frame.
+    // All particles start below the respawn height, so they are spawned at different times during the first frames.
+    let initial_positions = (0..PARTICLE_COUNT)
+        .map(|i| vec3(0.0, -2.0 - i as f32 * 0.0001, 0.0))
+        .collect::<Vec<_>>();
+    let initial_velocities = vec![vec3(0.0, 0.0, 0.0); PARTICLE_COUNT];
+    let mut positions = [
+        VertexBuffer::new_with_data(&context, &initial_positions),
+        VertexBuffer::new_with_data(&context, &initial_positions),
+    ];
+    let mut velocities = [
+        VertexBuffer::new_with_data(&context, &initial_velocities),
+        VertexBuffer::new_with_data(&context, &initial_velocities),
+    ];
+    let mut instance_positions = InstanceBuffer::new_with_data(&context, &initial_positions);
+    let mut update_program = TransformFeedbackProgram::new(
+        &context,
+        include_str!("update.vert"),
+        &["outPosition", "outVelocity", "outInstancePosition"],
+    )
+    .unwrap();
+
+    // A quad which is rendered for each particle
+    let quad = VertexBuffer::new_with_data(
+        &context,
+        &[
+            vec3(-1.0, -1.0, 0.0),
+            vec3(1.0, -1.0, 0.0),
+            vec3(1.0, 1.0, 0.0),
+            vec3(1.0, 1.0, 0.0),
+            vec3(-1.0, 1.0, 0.0),
+            vec3(-1.0, -1.0, 0.0),
+        ],
+    );
+    let render_program = Program::from_source(
+        &context,
+        include_str!("particle.vert"),
+        include_str!("particle.frag"),
+    )
+    .unwrap();
+
+    let mut current = 0;
+    window.render_loop(move |frame_input| {
+        camera.set_viewport(frame_input.viewport);
+
+        // Update the particles without any data transfer between the CPU and GPU
+        let next = 1 - current;
+        update_program.use_uniform(
+            "deltaTime",
+            (frame_input.elapsed_time as f32 * 0.001).min(0.05),
+        );
+        update_program.use_uniform("time", frame_input.accumulated_time as f32 * 0.001);
+        update_program.use_vertex_attribute("position", &positions[current]);
+        update_program.use_vertex_attribute("velocity", &velocities[current]);
+        update_program.use_output_buffer(0, &mut positions[next]);
+        update_program.use_output_buffer(1, &mut velocities[next]);
+        update_program.use_output_instance_buffer(2, &mut instance_positions);
+        update_program.begin(TransformFeedbackMode::Points);
+        update_program.draw_arrays(PARTICLE_COUNT as u32);
+        update_program.end();
+        current = next;
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.1, 0.1, 0.1, 1.0, 1.0))
+            .write(|| {
+                render_program.use_uniform("viewProjection", camera.projection() * camera.view());
+                render_program.use_uniform("cameraRight", camera.right_direction());
+                render_program.use_uniform(
+                    "cameraUp",
+                    camera.right_direction().cross(camera.view_direction()),
+                );
+                render_program.use_vertex_attribute("position", &quad);
+                render_program.use_instance_attribute("particlePosition", &instance_positions);
+                render_program.draw_arrays_instanced(
+                    RenderStates::default(),
+                    frame_input.viewport,
+                    quad.vertex_count(),
+                    PARTICLE_COUNT as u32,
+                );
+            });
+
+        FrameOutput::default()
+    });
+}
@@ -0,0 +1,11 @@
+in vec2 uv;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    if (dot(uv, uv) > 1.0) {
+        discard;
+    }
+    outColor = vec4(0.2, 0.5, 1.0, 1.0);
+}
@@ -0,0 +1,15 @@
+uniform mat4 viewProjection;
+uniform vec3 cameraRight;
+uniform vec3 cameraUp;
+
+in vec3 position;
+in vec3 particlePosition;
+
+out vec2 uv;
+
+void main()
+{
+    uv = position.xy;
+    vec3 p = particlePosition + 0.02 * (position.x * cameraRight + position.y * cameraUp);
+    gl_Position = viewProjection * vec4(p, 1.0);
+}
@@ -0,0 +1,31 @@
+uniform float deltaTime;
+uniform float time;
+
+in vec3 position;
+in vec3 velocity;
+
+out vec3 outPosition;
+out vec3 outVelocity;
+out vec3 outInstancePosition;
+
+float random(float seed)
+{
+    return fract(sin(seed * 12.9898) * 43758.5453);
+}
+
+void main()
+{
+    vec3 v = velocity + vec3(0.0, -9.82, 0.0) * deltaTime;
+    vec3 p = position + v * deltaTime;
+    if (p.y < -2.0) {
+        // Respawn the particle at the fountain with a new random velocity
+        float seed = float(gl_VertexID) + time;
+        float angle = 6.2831853 * random(seed);
+        float speed = 0.5 + random(seed + 1.0);
+        p = vec3(0.0, 0.0, 0.0);
+        v = vec3(speed * cos(angle), 5.0 + 2.0 * random(seed + 2.0), speed * sin(angle));
+    }
+    outPosition = p;
+    outVelocity = v;
+    outInstancePosition = p;
+}
@@ -0,0 +1,88 @@
+use three_d::*;
+
+const TEXTURE_PATH: &str = "examples/assets/test_texture.jpg";
+const MODEL_PATH: &str = "examples/assets/suzanne.obj";
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Hot reload!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 1.0, 6.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        100.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 100.0);
+
+    let mut loaded = three_d_asset::io::load(&[TEXTURE_PATH, MODEL_PATH]).unwrap();
+    let mut cube = Gm::new(
+        Mesh::new(&context, &CpuMesh::cube()),
+        ColorMaterial {
+            texture: Some(
+                std::sync::Arc::new(Texture2D::new(
+                    &context,
+                    &loaded.deserialize(TEXTURE_PATH).unwrap(),
+                ))
+                .into(),
+            ),
+            ..Default::default()
+        },
+    );
+    cube.set_transformation(Mat4::from_translation(vec3(-1.5, 0.0, 0.0)));
+    let mut model =
+        Model::<PhysicalMaterial>::new(&context, &loaded.deserialize(MODEL_PATH).unwrap()).unwrap();
+    model
+        .iter_mut()
+        .for_each(|part| part.set_transformation(Mat4::from_translation(vec3(1.5, 0.0, 0.0))));
+
+    // Edit and save the files while the example is running to see the changes
+    let mut watcher = AssetWatcher::new().unwrap();
+    watcher.watch_texture(TEXTURE_PATH).unwrap();
+    watcher.watch_model(MODEL_PATH).unwrap();
+
+    let ambient = AmbientLight::new(&context, 0.4, Color::WHITE);
+    let directional = DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(0.0, -1.0, -1.0));
+
+    window.render_loop(move |mut frame_input| {
+        camera.set_viewport(frame_input.viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        for (path, asset) in watcher.poll() {
+            match asset {
+                ReloadedAsset::Texture(texture) => {
+                    if let Some(texture_ref) = cube.material.texture.as_mut() {
+                        texture_ref.reload(&context, &texture);
+                    }
+                }
+                ReloadedAsset::Model(cpu_model) => {
+                    if let Err(e) = model.reload(&context, &cpu_model) {
+                        println!("Failed to reload {}: {}", path.display(), e);
+                    }
+                    model.iter_mut().for_each(|part| {
+                        part.set_transformation(Mat4::from_translation(vec3(1.5, 0.0, 0.0)))
+                    });
+                }
+            }
+        }
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
+            .render(
+                &camera,
+                cube.into_iter().chain(&model),
+                &[&ambient, &directional],
+            );
+
+        FrameOutput::default()
+    });
+}
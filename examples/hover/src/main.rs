@@ -0,0 +1,155 @@
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Hover!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 6.0, 12.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        100.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 2.0, 50.0);
+
+    let base_color = Color::new_opaque(100, 130, 180);
+    let highlight_color = Color::new_opaque(255, 200, 60);
+
+    // A row of spheres, each with its own id
+    let mut spheres = (0..5)
+        .map(|i| {
+            let mut sphere = GmWithOptions::new(Gm::new(
+                Mesh::new(&context, &CpuMesh::sphere(32)),
+                PhysicalMaterial::new_opaque(
+                    &context,
+                    &CpuMaterial {
+                        albedo: base_color,
+                        ..Default::default()
+                    },
+                ),
+            ))
+            .with_object_id(i + 1);
+            sphere.set_transformation(
+                Mat4::from_translation(vec3(2.5 * i as f32 - 5.0, 1.0, -2.0))
+                    * Mat4::from_scale(0.8),
+            );
+            sphere
+        })
+        .collect::<Vec<_>>();
+
+    // A grid of cubes in one instanced mesh, where the instance index tells the cubes apart
+    let mut instances = Instances {
+        transformations: (0..64)
+            .map(|i| {
+                Mat4::from_translation(vec3(
+                    (i % 16) as f32 * 0.7 - 5.25,
+                    0.3,
+                    (i / 16) as f32 * 0.7 + 0.5,
+                )) * Mat4::from_scale(0.25)
+            })
+            .collect(),
+        colors: Some(vec![base_color; 64]),
+        ..Default::default()
+    };
+    let mut cubes = GmWithOptions::new(Gm::new(
+        InstancedMesh::new(&context, &instances, &CpuMesh::cube()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::WHITE,
+                ..Default::default()
+            },
+        ),
+    ))
+    .with_object_id(100);
+
+    let light = DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(-1.0, -1.0, -1.0));
+    let ambient = AmbientLight::new(&context, 0.3, Color::WHITE);
+
+    // The highest 8 bits of each id is the instance index
+    let mut object_ids = ObjectIds::new(&context, 1, 1, 8);
+    let mut cursor = None;
+    let mut highlighted = None;
+    window.render_loop(move |mut frame_input| {
+        let viewport = frame_input.viewport;
+        camera.set_viewport(viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+        for event in frame_input.events.iter() {
+            if let Event::MouseMotion { position, .. } = event {
+                cursor = Some(PhysicalPoint::from(position));
+            }
+        }
+
+        // The hovered object is found from the ids written in one of the previous frames
+        let hovered = cursor.and_then(|cursor| object_ids.hovered_id(cursor));
+        if hovered != highlighted {
+            highlighted = hovered;
+            for (i, sphere) in spheres.iter_mut().enumerate() {
+                sphere.material.albedo = match hovered {
+                    Some(ObjectId { object, .. }) if object == i as u32 + 1 => highlight_color,
+                    _ => base_color,
+                };
+            }
+            for (i, color) in instances.colors.as_mut().unwrap().iter_mut().enumerate() {
+                *color = match hovered {
+                    Some(ObjectId {
+                        object: 100,
+                        instance,
+                    }) if instance == i as u32 => highlight_color,
+                    _ => base_color,
+                };
+            }
+            cubes.set_instances(&instances);
+        }
+
+        // Render the color and the object ids in the same pass and then copy the color to the screen
+        object_ids.set_size(viewport.width, viewport.height);
+        let mut color_texture = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            viewport.width,
+            viewport.height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &context,
+            viewport.width,
+            viewport.height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        object_ids
+            .render_target(
+                color_texture.as_color_target(None),
+                depth_texture.as_depth_target(),
+            )
+            .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
+            .render(
+                &camera,
+                spheres
+                    .iter()
+                    .flat_map(|sphere| sphere.into_iter())
+                    .chain(&cubes),
+                &[&light, &ambient],
+            );
+
+        frame_input.screen().copy_from_color(
+            ColorTexture::Single(&color_texture),
+            viewport,
+            WriteMask::default(),
+        );
+
+        FrameOutput::default()
+    });
+}
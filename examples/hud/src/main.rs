@@ -0,0 +1,141 @@
+use std::cell::RefCell;
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "HUD!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let rectangle = |color: Color| {
+        Gm::new(
+            Rectangle::new(&context, vec2(0.0, 0.0), degrees(0.0), 1.0, 1.0),
+            ColorMaterial {
+                color,
+                ..Default::default()
+            },
+        )
+    };
+
+    // The sizes and margins are in logical pixels, so the HUD has the same size on screens with different device pixel ratios
+    let mut minimap = HudElement::new(
+        rectangle(Color::new_opaque(40, 90, 40)),
+        Anchor::with_margin(AnchorPosition::TopRight, 16.0),
+        200.0,
+        150.0,
+    );
+    let mut crosshair = [
+        HudElement::new(rectangle(Color::WHITE), Anchor::default(), 24.0, 2.0),
+        HudElement::new(rectangle(Color::WHITE), Anchor::default(), 2.0, 24.0),
+    ];
+    let colors = [
+        ("Red", Color::new_opaque(120, 30, 30)),
+        ("Green", Color::new_opaque(30, 120, 30)),
+        ("Blue", Color::new_opaque(30, 30, 120)),
+        ("Gray", Color::new_opaque(60, 60, 60)),
+    ];
+    let mut buttons = HudStack {
+        spacing: 8.0,
+        ..HudStack::new(
+            colors
+                .iter()
+                .map(|(label, _)| Button::new(&context, *label, vec2(0.0, 0.0), 1.0, 1.0))
+                .collect(),
+            Anchor::with_margin(AnchorPosition::Bottom, 16.0),
+            StackDirection::Row,
+            120.0,
+            40.0,
+        )
+    };
+    let mut ui = UiLayer::new(BlockFont::new(&context));
+    let mut background = colors[3].1;
+
+    window.render_loop(move |mut frame_input| {
+        // Place the elements in the current viewport before handling the events, so the hit testing uses the same positions as the rendering
+        minimap.update(frame_input.viewport, frame_input.device_pixel_ratio);
+        for line in crosshair.iter_mut() {
+            line.update(frame_input.viewport, frame_input.device_pixel_ratio);
+        }
+        buttons.update(frame_input.viewport, frame_input.device_pixel_ratio);
+
+        ui.handle_events(
+            &mut frame_input.events,
+            frame_input.viewport,
+            frame_input.accumulated_time,
+            &mut buttons.widgets(),
+        );
+        for (button, (_, color)) in buttons.objects.iter_mut().zip(colors.iter()) {
+            if button.clicked() {
+                background = *color;
+            }
+        }
+
+        let camera = camera2d(frame_input.viewport);
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(
+                background.r as f32 / 255.0,
+                background.g as f32 / 255.0,
+                background.b as f32 / 255.0,
+                1.0,
+                1.0,
+            ))
+            .render(&camera, [&*minimap, &*crosshair[0], &*crosshair[1]], &[])
+            .write(|| ui.render(&mut buttons.widgets()));
+
+        FrameOutput::default()
+    });
+}
+
+///
+/// A placeholder font which renders each character as a block, since the widgets do not contain any font rendering.
+///
+struct BlockFont {
+    block: RefCell<Rectangle>,
+}
+
+impl BlockFont {
+    const ADVANCE: f32 = 10.0;
+
+    fn new(context: &Context) -> Self {
+        Self {
+            block: RefCell::new(Rectangle::new(
+                context,
+                vec2(0.0, 0.0),
+                degrees(0.0),
+                7.0,
+                12.0,
+            )),
+        }
+    }
+}
+
+impl UiFont for BlockFont {
+    fn text_width(&self, text: &str) -> f32 {
+        text.chars().count() as f32 * Self::ADVANCE
+    }
+
+    fn render_text(&self, text: &str, position: PhysicalPoint, color: Color, camera: &Camera) {
+        let material = ColorMaterial {
+            color,
+            render_states: RenderStates {
+                depth_test: DepthTest::Always,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let mut block = self.block.borrow_mut();
+        for (i, c) in text.chars().enumerate() {
+            if !c.is_whitespace() {
+                block.set_center(vec2(
+                    position.x + (i as f32 + 0.5) * Self::ADVANCE,
+                    position.y,
+                ));
+                block.render_with_material(&material, camera, &[]);
+            }
+        }
+    }
+}
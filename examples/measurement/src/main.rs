@@ -0,0 +1,127 @@
+// Entry point for non-wasm
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main]
+async fn main() {
+    run().await;
+}
+
+use three_d::*;
+
+pub async fn run() {
+    let window = Window::new(WindowSettings {
+        title: "Measurement!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(3.0, 2.0, 4.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        100.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 50.0);
+
+    // Retain the vertex positions so measurements can snap to the corners of the cube
+    let mut cube = Gm::new(
+        Mesh::new_with_cpu_data(&context, &CpuMesh::cube()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(100, 150, 200),
+                ..Default::default()
+            },
+        ),
+    );
+    let mut scale = 1.0;
+
+    let ambient = AmbientLight::new(&context, 0.3, Color::WHITE);
+    let directional = DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(-1.0, -1.0, -1.0));
+
+    // Measure the width, height and diagonal of the cube
+    let mut width = Measurement::new(vec3(-1.0, -1.0, 1.2), vec3(1.0, -1.0, 1.2));
+    width.set_format(|d| format!("{:.0} mm", d * 1000.0));
+    let mut height = Measurement::new(vec3(1.2, -1.0, 1.0), vec3(1.2, 1.0, 1.0));
+    height.set_format(|d| format!("{:.0} mm", d * 1000.0));
+    let mut diagonal = Measurement::new(vec3(-1.0, -1.0, -1.0), vec3(1.0, 1.0, 1.0));
+    diagonal.color = Color::new_opaque(255, 200, 0);
+
+    // A custom measurement between two corners picked with ctrl + left click
+    let mut custom = Measurement::new(vec3(0.0, 0.0, 0.0), vec3(0.0, 0.0, 0.0));
+    custom.color = Color::new_opaque(255, 100, 100);
+    let mut picked_corners = Vec::new();
+    let mut custom_corners = None;
+
+    let mut gui = GUI::new(&context);
+    window.render_loop(move |mut frame_input| {
+        camera.set_viewport(frame_input.viewport);
+        for event in frame_input.events.iter_mut() {
+            if let Event::MousePress {
+                button,
+                position,
+                modifiers,
+                handled,
+            } = event
+            {
+                if *button == MouseButton::Left && modifiers.ctrl && !*handled {
+                    if let Some(corner) = pick(&context, &camera, *position, &cube)
+                        .and_then(|pick| cube.closest_vertex(pick))
+                    {
+                        picked_corners.push(corner / scale);
+                        if picked_corners.len() == 2 {
+                            custom_corners = Some((picked_corners[0], picked_corners[1]));
+                            picked_corners.clear();
+                        }
+                        *handled = true;
+                    }
+                }
+            }
+        }
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        gui.update(
+            &mut frame_input.events,
+            frame_input.accumulated_time,
+            frame_input.viewport,
+            frame_input.device_pixel_ratio,
+            |gui_context| {
+                use three_d::egui::*;
+                Window::new("Measurement").show(gui_context, |ui| {
+                    ui.add(Slider::new(&mut scale, 0.1..=2.0).text("Scale"));
+                    ui.label("Ctrl + click on two corners of the cube to measure between them");
+                    if custom_corners.is_some() {
+                        ui.label(format!("Custom: {:.2}", custom.distance()));
+                    }
+                });
+                width.start = scale * vec3(-1.0, -1.0, 1.2);
+                width.end = scale * vec3(1.0, -1.0, 1.2);
+                height.start = scale * vec3(1.2, -1.0, 1.0);
+                height.end = scale * vec3(1.2, 1.0, 1.0);
+                diagonal.start = scale * vec3(-1.0, -1.0, -1.0);
+                diagonal.end = scale * vec3(1.0, 1.0, 1.0);
+                for measurement in [&width, &height, &diagonal] {
+                    measurement.paint(gui_context, &camera);
+                }
+                if let Some((start, end)) = custom_corners {
+                    custom.start = scale * start;
+                    custom.end = scale * end;
+                    custom.paint(gui_context, &camera);
+                }
+            },
+        );
+        cube.set_transformation(Mat4::from_scale(scale));
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.2, 0.2, 0.2, 1.0, 1.0))
+            .render(&camera, &cube, &[&ambient, &directional])
+            .write(|| gui.render());
+
+        FrameOutput::default()
+    });
+}
@@ -0,0 +1,169 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use three_d::*;
+use tokio::sync::{mpsc, oneshot};
+
+/// The number of cubes in the scene.
+const COUNT: usize = 8;
+
+#[tokio::main]
+async fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Network updates!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 8.0, 12.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        1000.0,
+    );
+    let control = OrbitControl::new(*camera.target(), 1.0, 100.0);
+
+    let (scene_sender, scene_receiver) = oneshot::channel();
+    let (update_sender, update_receiver) = mpsc::unbounded_channel();
+    simulate_server(scene_sender, update_sender, window.redraw_signal());
+
+    let state = Rc::new(RefCell::new(State {
+        camera,
+        control,
+        scene_receiver: Some(scene_receiver),
+        update_receiver,
+        transformations: Vec::new(),
+        cubes: None,
+    }));
+    let ambient = Rc::new(AmbientLight::new(&context, 0.4, Color::WHITE));
+    let directional = Rc::new(DirectionalLight::new(
+        &context,
+        2.0,
+        Color::WHITE,
+        &vec3(-1.0, -1.0, -1.0),
+    ));
+
+    window.render_loop_async(move |mut frame_input| {
+        let state = state.clone();
+        let ambient = ambient.clone();
+        let directional = directional.clone();
+        async move {
+            // The first frame waits for the scene. The window still handles events in the meantime, which are given to the next frame.
+            let scene_receiver = state.borrow_mut().scene_receiver.take();
+            if let Some(scene_receiver) = scene_receiver {
+                let transformations = scene_receiver.await.expect("the server stopped");
+                let mut state = state.borrow_mut();
+                state.cubes = Some(Gm::new(
+                    InstancedMesh::new(
+                        &frame_input.context,
+                        &Instances {
+                            transformations: transformations.clone(),
+                            ..Default::default()
+                        },
+                        &CpuMesh::cube(),
+                    ),
+                    PhysicalMaterial::new_opaque(
+                        &frame_input.context,
+                        &CpuMaterial {
+                            albedo: Color::new_opaque(200, 80, 50),
+                            ..Default::default()
+                        },
+                    ),
+                ));
+                state.transformations = transformations;
+            }
+
+            let mut state = state.borrow_mut();
+            let state = &mut *state;
+
+            // Apply the updates received since the last frame.
+            // A frame is rendered whenever the server sends an update, since the server signals the render loop after each update.
+            let mut changed = false;
+            while let Ok((index, transformation)) = state.update_receiver.try_recv() {
+                state.transformations[index] = transformation;
+                changed = true;
+            }
+            let cubes = state.cubes.as_mut().unwrap();
+            if changed {
+                cubes.set_instances(&Instances {
+                    transformations: state.transformations.clone(),
+                    ..Default::default()
+                });
+            }
+
+            state.camera.set_viewport(frame_input.viewport);
+            state
+                .control
+                .handle_events(&mut state.camera, &mut frame_input.events);
+
+            frame_input
+                .screen()
+                .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
+                .render(&state.camera, &*cubes, &[&*ambient, &*directional]);
+
+            // Wait for the next event or update instead of rendering continuously
+            FrameOutput {
+                wait_next_event: true,
+                ..Default::default()
+            }
+        }
+    });
+}
+
+struct State {
+    camera: Camera,
+    control: OrbitControl,
+    scene_receiver: Option<oneshot::Receiver<Vec<Mat4>>>,
+    update_receiver: mpsc::UnboundedReceiver<(usize, Mat4)>,
+    transformations: Vec<Mat4>,
+    cubes: Option<Gm<InstancedMesh, PhysicalMaterial>>,
+}
+
+///
+/// Simulates a server which sends the scene after a delay and then sends a new transformation for one of the cubes at a fixed rate.
+/// The server runs on the tokio runtime and signals the render loop each time it has sent an update.
+///
+fn simulate_server(
+    scene_sender: oneshot::Sender<Vec<Mat4>>,
+    update_sender: mpsc::UnboundedSender<(usize, Mat4)>,
+    redraw_signal: RedrawSignal,
+) {
+    tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let scene = (0..COUNT).map(|i| transformation(i, 0.0)).collect();
+        if scene_sender.send(scene).is_err() {
+            return;
+        }
+
+        let mut interval = tokio::time::interval(Duration::from_millis(20));
+        let mut time = 0.0;
+        for index in (0..COUNT).cycle() {
+            interval.tick().await;
+            time += 0.02;
+            if update_sender
+                .send((index, transformation(index, time)))
+                .is_err()
+            {
+                // The render loop has stopped
+                return;
+            }
+            redraw_signal.request_redraw();
+        }
+    });
+}
+
+///
+/// The transformation of the cube with the given index at the given time, the cubes are placed in a ring and bounce up and down.
+///
+fn transformation(index: usize, time: f32) -> Mat4 {
+    let angle = index as f32 / COUNT as f32 * std::f32::consts::TAU;
+    let height = (2.0 * time + angle).sin().abs() * 2.0;
+    Mat4::from_translation(vec3(4.0 * angle.cos(), height, 4.0 * angle.sin()))
+        * Mat4::from_angle_y(radians(time + angle))
+        * Mat4::from_scale(0.5)
+}
@@ -0,0 +1,72 @@
+use three_d::*;
+
+pub fn main() {
+    // Create a transparent, borderless window which stays on top of all other windows
+    let window = Window::new(WindowSettings {
+        title: "Overlay!".to_string(),
+        max_size: Some((400, 400)),
+        decorations: false,
+        always_on_top: true,
+        transparent: true,
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(2.0, 1.5, 2.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        10.0,
+    );
+
+    let mut axes = Axes::new(&context, 0.05, 1.0);
+
+    // A half transparent sphere which is blended with the desktop behind the window
+    let mut sphere_material = ColorMaterial {
+        color: Color::new(255, 255, 255, 100),
+        is_transparent: true,
+        ..Default::default()
+    };
+    // The window compositor expects premultiplied colors and uses the alpha channel of the window to blend
+    sphere_material.render_states.blend = Blend::PREMULTIPLIED_TRANSPARENCY;
+    sphere_material.render_states.write_mask = WriteMask::COLOR;
+    let mut sphere = Gm::new(Mesh::new(&context, &CpuMesh::sphere(32)), sphere_material);
+    sphere.set_transformation(Mat4::from_scale(0.3));
+
+    // Press C to let the mouse events pass through the window to the application behind it
+    let mut click_through = false;
+    window.render_loop(move |frame_input| {
+        camera.set_viewport(frame_input.viewport);
+        let mut exit = false;
+        for event in frame_input.events.iter() {
+            if let Event::KeyPress { kind, .. } = event {
+                match kind {
+                    Key::C => click_through = !click_through,
+                    Key::Escape => exit = true,
+                    _ => {}
+                }
+            }
+        }
+
+        axes.set_transformation(Mat4::from_angle_y(radians(
+            frame_input.accumulated_time as f32 * 0.001,
+        )));
+
+        // Clearing with an alpha value of 0 makes the desktop behind the window visible
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+            .render(&camera, &axes, &[])
+            .render(&camera, &sphere, &[]);
+
+        FrameOutput {
+            exit,
+            cursor_hittest: !click_through,
+            ..Default::default()
+        }
+    });
+}
@@ -143,6 +143,11 @@ pub async fn run() {
                         NormalDistributionFunction::TrowbridgeReitzGGX,
                         GeometryFunction::SmithSchlickGGX,
                     ),
+                    alpha_cutout: model.material.alpha_cutout,
+                    anisotropy: model.material.anisotropy,
+                    anisotropy_rotation: model.material.anisotropy_rotation,
+                    anisotropy_texture: model.material.anisotropy_texture.clone(),
+                    ..Default::default()
                 };
                 model.render_with_material(&material, &camera, &[&light]);
                 gui.render();
@@ -0,0 +1,102 @@
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Pressure brush!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, -2.0, 4.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        100.0,
+    );
+
+    let mut canvas = CpuMesh::square();
+    canvas.transform(&Mat4::from_scale(1.5)).unwrap();
+    let canvas = Gm::new(
+        Mesh::new(&context, &canvas),
+        ColorMaterial {
+            color: Color::new_opaque(240, 235, 220),
+            ..Default::default()
+        },
+    );
+
+    // Each stamp of the brush is a disc lying on the canvas
+    let mut stamps = Instances::default();
+    let mut brush = Gm::new(
+        InstancedMesh::new(&context, &stamps, &CpuMesh::circle(32)),
+        ColorMaterial {
+            color: Color::new_opaque(30, 60, 150),
+            ..Default::default()
+        },
+    );
+
+    let mut pressure = None;
+    let mut last_stamp: Option<Vec3> = None;
+    let mut drawing = false;
+    window.render_loop(move |frame_input| {
+        camera.set_viewport(frame_input.viewport);
+
+        for event in frame_input.events.iter() {
+            match event {
+                // Always received before the press, motion or release it belongs to
+                Event::PointerPressure { pressure: p, .. } => {
+                    pressure = *p;
+                }
+                Event::MousePress {
+                    button: MouseButton::Left,
+                    ..
+                } => {
+                    drawing = true;
+                    last_stamp = None;
+                }
+                Event::MouseRelease {
+                    button: MouseButton::Left,
+                    ..
+                } => {
+                    drawing = false;
+                    pressure = None;
+                }
+                _ => {}
+            }
+            if let Event::MousePress { position, .. } | Event::MouseMotion { position, .. } = event
+            {
+                if drawing {
+                    if let Some(point) = pick(&context, &camera, position, &canvas) {
+                        // A mouse doesn't report any pressure, so use a medium brush size
+                        let radius = 0.01 + 0.05 * pressure.unwrap_or(0.5);
+                        // Fill the gap since the last stamp to get a continuous stroke
+                        let from = last_stamp.unwrap_or(point);
+                        let steps = ((point - from).magnitude() / (0.5 * radius))
+                            .ceil()
+                            .max(1.0);
+                        for i in 1..=steps as u32 {
+                            let p = from.lerp(point, i as f32 / steps);
+                            stamps.transformations.push(
+                                Mat4::from_translation(p + vec3(0.0, 0.0, 0.001))
+                                    * Mat4::from_scale(radius),
+                            );
+                        }
+                        last_stamp = Some(point);
+                        brush.set_instances(&stamps);
+                    }
+                }
+            }
+        }
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.3, 0.3, 0.3, 1.0, 1.0))
+            .render(&camera, canvas.into_iter().chain(&brush), &[]);
+
+        FrameOutput::default()
+    });
+}
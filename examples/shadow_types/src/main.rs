@@ -0,0 +1,134 @@
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Shadow types!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(6.0, 8.0, 12.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        100.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 50.0);
+
+    let material = |color: Color| {
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: color,
+                roughness: 0.8,
+                ..Default::default()
+            },
+        )
+    };
+    let mut ground = Gm::new(
+        Mesh::new(&context, &CpuMesh::square()),
+        material(Color::new_opaque(200, 200, 200)),
+    );
+    ground.set_transformation(Mat4::from_angle_x(degrees(-90.0)) * Mat4::from_scale(8.0));
+
+    // A low and a high box on top of each other as seen from the lights, which shows the light bleeding of the variance shadow map
+    let mut cube = CpuMesh::cube();
+    cube.transform(&Mat4::from_scale(0.5)).unwrap();
+    let boxes = Gm::new(
+        InstancedMesh::new(
+            &context,
+            &Instances {
+                transformations: vec![
+                    Mat4::from_translation(vec3(-2.0, 0.5, 0.0)),
+                    Mat4::from_translation(vec3(-1.5, 3.0, 0.5)) * Mat4::from_scale(2.0),
+                    Mat4::from_translation(vec3(2.5, 0.5, -1.0)),
+                ],
+                ..Default::default()
+            },
+            &cube,
+        ),
+        material(Color::new_opaque(200, 80, 80)),
+    );
+    let mut sphere = Gm::new(
+        Mesh::new(&context, &CpuMesh::sphere(32)),
+        material(Color::new_opaque(80, 120, 200)),
+    );
+    sphere.set_transformation(Mat4::from_translation(vec3(1.0, 1.0, 2.0)));
+
+    let ambient = AmbientLight::new(&context, 0.2, Color::WHITE);
+    let mut directional =
+        DirectionalLight::new(&context, 1.5, Color::WHITE, &vec3(-0.5, -1.0, -0.3));
+    let mut spot = SpotLight::new(
+        &context,
+        2.0,
+        Color::new_opaque(255, 230, 180),
+        &vec3(5.0, 6.0, 5.0),
+        &vec3(-1.0, -1.2, -1.0),
+        degrees(35.0),
+        Attenuation {
+            constant: 1.0,
+            linear: 0.01,
+            quadratic: 0.001,
+        },
+    );
+
+    let mut mode = 0;
+    let mut bleed_reduction = 0.2;
+    let mut gui = GUI::new(&context);
+    window.render_loop(move |mut frame_input| {
+        let mut panel_width = 0.0;
+        gui.update(
+            &mut frame_input.events,
+            frame_input.accumulated_time,
+            frame_input.viewport,
+            frame_input.device_pixel_ratio,
+            |gui_context| {
+                use three_d::egui::*;
+                SidePanel::left("side_panel").show(gui_context, |ui| {
+                    ui.heading("Shadow map type");
+                    ui.radio_value(&mut mode, 0, "Standard");
+                    ui.radio_value(&mut mode, 1, "Variance");
+                    ui.radio_value(&mut mode, 2, "Exponential");
+                    ui.add(Slider::new(&mut bleed_reduction, 0.0..=0.9).text("Bleed reduction"));
+                });
+                panel_width = gui_context.used_rect().width();
+            },
+        );
+        let viewport = Viewport {
+            x: (panel_width * frame_input.device_pixel_ratio) as i32,
+            y: 0,
+            width: frame_input.viewport.width
+                - (panel_width * frame_input.device_pixel_ratio) as u32,
+            height: frame_input.viewport.height,
+        };
+        camera.set_viewport(viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        let shadow_map_type = match mode {
+            1 => ShadowMapType::Variance { bleed_reduction },
+            2 => ShadowMapType::Exponential,
+            _ => ShadowMapType::Standard,
+        };
+        directional.shadow_map_type = shadow_map_type;
+        spot.shadow_map_type = shadow_map_type;
+        directional.generate_shadow_map(1024, ground.into_iter().chain(&boxes).chain(&sphere));
+        spot.generate_shadow_map(1024, ground.into_iter().chain(&boxes).chain(&sphere));
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.5, 0.5, 0.55, 1.0, 1.0))
+            .render(
+                &camera,
+                ground.into_iter().chain(&boxes).chain(&sphere),
+                &[&ambient, &directional, &spot],
+            )
+            .write(|| gui.render());
+
+        FrameOutput::default()
+    });
+}
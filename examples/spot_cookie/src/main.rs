@@ -0,0 +1,154 @@
+// Entry point for non-wasm
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main]
+async fn main() {
+    run().await;
+}
+
+use three_d::*;
+
+pub async fn run() {
+    let window = Window::new(WindowSettings {
+        title: "Spot light cookie!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 2.0, 8.0),
+        vec3(0.0, 1.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        100.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 50.0);
+
+    let mut loaded = if let Ok(loaded) =
+        three_d_asset::io::load_async(&["../assets/rust_logo.png"]).await
+    {
+        loaded
+    } else {
+        three_d_asset::io::load_async(&[
+            "https://asny.github.io/three-d/assets/rust_logo.png",
+        ])
+        .await
+        .expect("failed to download the necessary assets, to enable running this example offline, place the relevant assets in a folder called 'assets' next to the three-d source")
+    };
+    let cookie: Texture2DRef =
+        std::sync::Arc::new(Texture2D::new(&context, &loaded.deserialize("").unwrap())).into();
+
+    // A wall and a floor which the logo is projected onto
+    let mut wall = Gm::new(
+        Mesh::new(&context, &CpuMesh::square()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::WHITE,
+                roughness: 0.8,
+                ..Default::default()
+            },
+        ),
+    );
+    wall.set_transformation(
+        Mat4::from_translation(vec3(0.0, 2.0, -2.0)) * Mat4::from_nonuniform_scale(5.0, 3.0, 1.0),
+    );
+    let mut floor = Gm::new(
+        Mesh::new(&context, &CpuMesh::square()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(150, 150, 150),
+                roughness: 0.8,
+                ..Default::default()
+            },
+        ),
+    );
+    floor.set_transformation(
+        Mat4::from_translation(vec3(0.0, -1.0, 1.0))
+            * Mat4::from_angle_x(degrees(-90.0))
+            * Mat4::from_scale(5.0),
+    );
+    let mut cube = Gm::new(
+        Mesh::new(&context, &CpuMesh::cube()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(100, 150, 200),
+                ..Default::default()
+            },
+        ),
+    );
+    cube.set_transformation(Mat4::from_translation(vec3(1.5, 0.0, 0.0)) * Mat4::from_scale(0.5));
+
+    let ambient = AmbientLight::new(&context, 0.1, Color::WHITE);
+    let mut spot = SpotLight::new(
+        &context,
+        3.0,
+        Color::WHITE,
+        &vec3(0.0, 2.0, 6.0),
+        &vec3(0.0, 0.0, -1.0),
+        degrees(25.0),
+        Attenuation {
+            constant: 1.0,
+            linear: 0.0,
+            quadratic: 0.0,
+        },
+    );
+    spot.cookie = Some(cookie.clone());
+
+    let mut gui = GUI::new(&context);
+    window.render_loop(move |mut frame_input| {
+        let mut use_cookie = spot.cookie.is_some();
+        let mut use_shadow = spot.shadow_map().is_some();
+        gui.update(
+            &mut frame_input.events,
+            frame_input.accumulated_time,
+            frame_input.viewport,
+            frame_input.device_pixel_ratio,
+            |gui_context| {
+                use three_d::egui::*;
+                Window::new("Spot light").show(gui_context, |ui| {
+                    ui.checkbox(&mut use_cookie, "Cookie");
+                    ui.checkbox(&mut use_shadow, "Shadow");
+                });
+            },
+        );
+        camera.set_viewport(frame_input.viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        // Move the spot light around and aim it at the wall
+        let time = frame_input.accumulated_time as f32 * 0.001;
+        spot.position = vec3(2.0 * time.sin(), 2.0 + time.cos(), 6.0);
+        spot.direction = vec3(0.0, 2.0, -2.0) - spot.position;
+
+        // The cookie can be set and cleared at runtime
+        if use_cookie != spot.cookie.is_some() {
+            spot.cookie = if use_cookie {
+                Some(cookie.clone())
+            } else {
+                None
+            };
+        }
+        if use_shadow {
+            spot.generate_shadow_map(1024, wall.into_iter().chain(&floor).chain(&cube));
+        } else {
+            spot.clear_shadow_map();
+        }
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+            .render(
+                &camera,
+                wall.into_iter().chain(&floor).chain(&cube),
+                &[&ambient, &spot],
+            )
+            .write(|| gui.render());
+
+        FrameOutput::default()
+    });
+}
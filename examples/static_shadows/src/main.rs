@@ -0,0 +1,152 @@
+// Entry point for non-wasm
+#[cfg(not(target_arch = "wasm32"))]
+#[tokio::main]
+async fn main() {
+    run().await;
+}
+
+use three_d::*;
+
+pub async fn run() {
+    let window = Window::new(WindowSettings {
+        title: "Static shadows!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 15.0, 25.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        1000.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 100.0);
+
+    // A static scene of roughly one million triangles
+    let mut transformations = Vec::new();
+    for x in -5..=5 {
+        for z in -5..=5 {
+            transformations.push(Mat4::from_translation(vec3(
+                2.0 * x as f32,
+                0.0,
+                2.0 * z as f32,
+            )));
+        }
+    }
+    let spheres = Gm::new(
+        InstancedMesh::new(
+            &context,
+            &Instances {
+                transformations,
+                ..Default::default()
+            },
+            &CpuMesh::sphere(64),
+        ),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(100, 150, 200),
+                ..Default::default()
+            },
+        ),
+    );
+    let mut ground = Gm::new(
+        Mesh::new(&context, &CpuMesh::square()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(150, 150, 150),
+                roughness: 0.8,
+                ..Default::default()
+            },
+        ),
+    );
+    ground.set_transformation(
+        Mat4::from_translation(vec3(0.0, -1.0, 0.0))
+            * Mat4::from_angle_x(degrees(-90.0))
+            * Mat4::from_scale(15.0),
+    );
+
+    // A dynamic character moving between the spheres
+    let mut character = Gm::new(
+        Mesh::new(&context, &CpuMesh::cube()),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(200, 80, 80),
+                ..Default::default()
+            },
+        ),
+    );
+
+    let ambient = AmbientLight::new(&context, 0.2, Color::WHITE);
+    let mut directional =
+        DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(-1.0, -1.0, -0.5));
+
+    let mut cache_static_shadows = true;
+    let mut gui = GUI::new(&context);
+    window.render_loop(move |mut frame_input| {
+        let mut panel_width = 0.0;
+        gui.update(
+            &mut frame_input.events,
+            frame_input.accumulated_time,
+            frame_input.viewport,
+            frame_input.device_pixel_ratio,
+            |gui_context| {
+                use three_d::egui::*;
+                SidePanel::left("side_panel").show(gui_context, |ui| {
+                    ui.heading("Shadows");
+                    ui.checkbox(&mut cache_static_shadows, "Cache static shadows");
+                    ui.label(format!("Frame time: {:.2} ms", frame_input.elapsed_time));
+                });
+                panel_width = gui_context.used_rect().width();
+            },
+        );
+        let viewport = Viewport {
+            x: (panel_width * frame_input.device_pixel_ratio) as i32,
+            y: 0,
+            width: frame_input.viewport.width
+                - (panel_width * frame_input.device_pixel_ratio) as u32,
+            height: frame_input.viewport.height,
+        };
+        camera.set_viewport(viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        let time = frame_input.accumulated_time as f32 * 0.0005;
+        character.set_transformation(
+            Mat4::from_translation(vec3(7.0 * time.cos(), 0.5, 7.0 * time.sin()))
+                * Mat4::from_angle_y(radians(-time))
+                * Mat4::from_nonuniform_scale(0.3, 1.5, 0.3),
+        );
+
+        // Either render the depth of the entire scene every frame or only the moving character on top of the cached depth of the static scene
+        if cache_static_shadows {
+            directional.generate_shadow_map_with_static(
+                2048,
+                spheres.into_iter().chain(&ground),
+                &character,
+            );
+        } else {
+            directional.invalidate_static_shadows();
+            directional
+                .generate_shadow_map(2048, spheres.into_iter().chain(&ground).chain(&character));
+        }
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
+            .render(
+                &camera,
+                spheres.into_iter().chain(&ground).chain(&character),
+                &[&ambient, &directional],
+            )
+            .write(|| gui.render());
+
+        FrameOutput::default()
+    });
+}
@@ -0,0 +1,102 @@
+use three_d::*;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Trails!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    let mut camera = Camera::new_perspective(
+        window.viewport(),
+        vec3(0.0, 4.0, 12.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        100.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 2.0, 50.0);
+
+    let planet = Gm::new(
+        Mesh::new(&context, &CpuMesh::sphere(32)),
+        PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::new_opaque(60, 90, 160),
+                ..Default::default()
+            },
+        ),
+    );
+    let light = DirectionalLight::new(&context, 2.0, Color::WHITE, &vec3(-1.0, -1.0, -1.0));
+    let ambient = AmbientLight::new(&context, 0.2, Color::WHITE);
+
+    // The trails are added onto the scene and fade to black, so they don't need to be sorted
+    let material = ColorMaterial {
+        color: Color::WHITE,
+        render_states: RenderStates {
+            blend: Blend::ADD,
+            write_mask: WriteMask::COLOR,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let colors = [
+        Color::new_opaque(255, 140, 40),
+        Color::new_opaque(80, 255, 120),
+        Color::new_opaque(120, 160, 255),
+    ];
+    let mut trails = colors
+        .iter()
+        .map(|color| {
+            let mut trail = Trail::new(&context, 256, 1500.0, 0.3);
+            trail.set_colors(*color, Color::BLACK);
+            Gm::new(trail, material.clone())
+        })
+        .collect::<Vec<_>>();
+
+    // A thin trail with a constant width on the screen
+    let mut comet = Trail::new(&context, 512, 4000.0, 3.0);
+    comet.set_screen_space(true);
+    comet.set_widths(3.0, 1.0);
+    comet.set_colors(Color::WHITE, Color::BLACK);
+    let mut comet = Gm::new(comet, material.clone());
+
+    window.render_loop(move |mut frame_input| {
+        camera.set_viewport(frame_input.viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        let time = frame_input.accumulated_time;
+        let t = time as f32 * 0.001;
+        for (i, trail) in trails.iter_mut().enumerate() {
+            // Each projectile orbits the planet in its own tilted plane and pauses now and then to show how the trail fades out
+            let phase = i as f32 * 2.1;
+            let angle = t * 1.5 + phase;
+            let orbit = Mat3::from_angle_x(radians(0.6 * i as f32 - 0.6))
+                * vec3(2.5 * angle.cos(), 0.0, 2.5 * angle.sin());
+            if ((t + phase) * 0.3).sin() > -0.8 {
+                trail.push(orbit, time);
+            } else {
+                trail.set_time(time);
+            }
+        }
+        comet.push(
+            vec3(
+                6.0 * (0.3 * t).cos(),
+                2.0 * (0.9 * t).sin(),
+                4.0 * (0.3 * t).sin(),
+            ),
+            time,
+        );
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.02, 1.0, 1.0))
+            .render(&camera, &planet, &[&light, &ambient])
+            .render(&camera, trails.iter().chain(std::iter::once(&comet)), &[]);
+
+        FrameOutput::default()
+    });
+}
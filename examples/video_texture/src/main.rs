@@ -0,0 +1,72 @@
+use three_d::*;
+
+const WIDTH: u32 = 640;
+const HEIGHT: u32 = 360;
+
+pub fn main() {
+    let window = Window::new(WindowSettings {
+        title: "Video texture!".to_string(),
+        max_size: Some((1280, 720)),
+        ..Default::default()
+    })
+    .unwrap();
+    let context = window.gl();
+
+    // The frames are in the NV12 format, ie. a full resolution luma plane and a half resolution plane with interleaved chroma values
+    let mut luma = StreamingTexture2D::new(&context, WIDTH, HEIGHT, PixelFormat::R8);
+    let mut chroma = StreamingTexture2D::new(&context, WIDTH / 2, HEIGHT / 2, PixelFormat::Rg8);
+    let effect = YuvToRgbEffect::new(YuvColorMatrix::Bt709, false);
+
+    // The frame buffers are allocated once and overwritten with each new frame, like the output buffers of a video decoder
+    let mut luma_data = vec![0u8; (WIDTH * HEIGHT) as usize];
+    let mut chroma_data = vec![0u8; (WIDTH * HEIGHT / 2) as usize];
+
+    window.render_loop(move |frame_input| {
+        let time = frame_input.accumulated_time as f32 * 0.001;
+        generate_frame(&mut luma_data, &mut chroma_data, time);
+        luma.update(&luma_data);
+        chroma.update(&chroma_data);
+
+        // Fit the video inside the window while keeping the aspect ratio
+        let viewport = frame_input.viewport;
+        let scale =
+            (viewport.width as f32 / WIDTH as f32).min(viewport.height as f32 / HEIGHT as f32);
+        let width = (WIDTH as f32 * scale) as u32;
+        let height = (HEIGHT as f32 * scale) as u32;
+        let video_viewport = Viewport {
+            x: viewport.x + (viewport.width - width) as i32 / 2,
+            y: viewport.y + (viewport.height - height) as i32 / 2,
+            width,
+            height,
+        };
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+            .write(|| effect.apply(&context, &luma, &chroma, video_viewport));
+
+        FrameOutput::default()
+    });
+}
+
+///
+/// Generates an animated frame in place, where the rows are ordered from the bottom to the top as expected by [StreamingTexture2D::update].
+/// The values use the limited range, ie. luma in `[16..235]` and chroma in `[16..240]`.
+///
+fn generate_frame(luma: &mut [u8], chroma: &mut [u8], time: f32) {
+    for (i, value) in luma.iter_mut().enumerate() {
+        let x = (i as u32 % WIDTH) as f32 / WIDTH as f32;
+        let y = (i as u32 / WIDTH) as f32 / HEIGHT as f32;
+        let plasma = (10.0 * x + time).sin()
+            + (8.0 * y - 1.3 * time).sin()
+            + (12.0 * (x + y) + 0.7 * time).sin();
+        *value = (16.0 + 219.0 * (0.5 + plasma / 6.0)) as u8;
+    }
+    let chroma_width = WIDTH / 2;
+    for (i, uv) in chroma.chunks_exact_mut(2).enumerate() {
+        let x = (i as u32 % chroma_width) as f32 / chroma_width as f32;
+        let y = (i as u32 / chroma_width) as f32 / (HEIGHT / 2) as f32;
+        uv[0] = (128.0 + 100.0 * (6.0 * x + 0.9 * time).sin()) as u8;
+        uv[1] = (128.0 + 100.0 * (5.0 * y - 1.1 * time).cos()) as u8;
+    }
+}
@@ -0,0 +1,153 @@
+// This example uses the DOM events of the canvas directly and therefore only runs on web
+#[cfg(not(target_arch = "wasm32"))]
+pub fn main() {
+    println!("The web_canvas example only runs on web");
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn main() {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use three_d::*;
+    use wasm_bindgen::prelude::*;
+    use wasm_bindgen::JsCast;
+
+    // Get the canvas and the WebGL2 context, these would usually be managed by another framework
+    let browser_window = web_sys::window().unwrap();
+    let canvas = browser_window
+        .document()
+        .unwrap()
+        .get_elements_by_tag_name("canvas")
+        .item(0)
+        .expect("the DOM doesn't have a canvas element")
+        .dyn_into::<web_sys::HtmlCanvasElement>()
+        .unwrap();
+    let webgl_context = canvas
+        .get_context("webgl2")
+        .unwrap()
+        .expect("the browser doesn't support WebGL2")
+        .dyn_into::<web_sys::WebGl2RenderingContext>()
+        .unwrap();
+    let context = Context::from_webgl2_context(webgl_context).unwrap();
+    let frame_input_generator = Rc::new(RefCell::new(WebFrameInputGenerator::new(&canvas)));
+
+    // Forward the DOM events to the frame input generator using plain event listeners
+    for event_type in [
+        "mousedown",
+        "mouseup",
+        "mousemove",
+        "mouseenter",
+        "mouseleave",
+    ] {
+        let generator = frame_input_generator.clone();
+        let closure = Closure::wrap(Box::new(move |event: web_sys::MouseEvent| {
+            if event.type_() == "mousedown" {
+                event.prevent_default();
+            }
+            generator.borrow_mut().handle_mouse_event(&event);
+        }) as Box<dyn FnMut(_)>);
+        canvas
+            .add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+    {
+        let generator = frame_input_generator.clone();
+        let closure = Closure::wrap(Box::new(move |event: web_sys::WheelEvent| {
+            event.prevent_default();
+            generator.borrow_mut().handle_wheel_event(&event);
+        }) as Box<dyn FnMut(_)>);
+        canvas
+            .add_event_listener_with_callback("wheel", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+    for event_type in ["touchstart", "touchend", "touchcancel", "touchmove"] {
+        let generator = frame_input_generator.clone();
+        let closure = Closure::wrap(Box::new(move |event: web_sys::TouchEvent| {
+            event.prevent_default();
+            generator.borrow_mut().handle_touch_event(&event);
+        }) as Box<dyn FnMut(_)>);
+        canvas
+            .add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+    for event_type in ["keydown", "keyup"] {
+        let generator = frame_input_generator.clone();
+        let closure = Closure::wrap(Box::new(move |event: web_sys::KeyboardEvent| {
+            generator.borrow_mut().handle_keyboard_event(&event);
+        }) as Box<dyn FnMut(_)>);
+        browser_window
+            .add_event_listener_with_callback(event_type, closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+    {
+        let closure = Closure::wrap(Box::new(move |event: web_sys::Event| {
+            event.prevent_default();
+        }) as Box<dyn FnMut(_)>);
+        canvas
+            .add_event_listener_with_callback("contextmenu", closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+
+    let mut camera = Camera::new_perspective(
+        Viewport::new_at_origo(1, 1),
+        vec3(0.0, 0.0, 2.0),
+        vec3(0.0, 0.0, 0.0),
+        vec3(0.0, 1.0, 0.0),
+        degrees(45.0),
+        0.1,
+        10.0,
+    );
+    let mut control = OrbitControl::new(*camera.target(), 1.0, 10.0);
+
+    let cpu_mesh = CpuMesh {
+        positions: Positions::F32(vec![
+            vec3(0.5, -0.5, 0.0),
+            vec3(-0.5, -0.5, 0.0),
+            vec3(0.0, 0.5, 0.0),
+        ]),
+        colors: Some(vec![Color::RED, Color::GREEN, Color::BLUE]),
+        ..Default::default()
+    };
+    let model = Gm::new(Mesh::new(&context, &cpu_mesh), ColorMaterial::default());
+
+    // A render loop based on requestAnimationFrame
+    let render_loop = Rc::new(RefCell::new(None::<Closure<dyn FnMut()>>));
+    let next_frame = render_loop.clone();
+    *render_loop.borrow_mut() = Some(Closure::wrap(Box::new(move || {
+        let mut frame_input = frame_input_generator.borrow_mut().generate(&context);
+        camera.set_viewport(frame_input.viewport);
+        control.handle_events(&mut camera, &mut frame_input.events);
+
+        frame_input
+            .screen()
+            .clear(ClearState::color_and_depth(0.8, 0.8, 0.8, 1.0, 1.0))
+            .render(&camera, &model, &[]);
+
+        web_sys::window()
+            .unwrap()
+            .request_animation_frame(
+                next_frame
+                    .borrow()
+                    .as_ref()
+                    .unwrap()
+                    .as_ref()
+                    .unchecked_ref(),
+            )
+            .unwrap();
+    }) as Box<dyn FnMut()>));
+    browser_window
+        .request_animation_frame(
+            render_loop
+                .borrow()
+                .as_ref()
+                .unwrap()
+                .as_ref()
+                .unchecked_ref(),
+        )
+        .unwrap();
+}
@@ -28,10 +28,50 @@ mod program;
 #[doc(inline)]
 pub use program::*;
 
+mod compute_program;
+#[doc(inline)]
+pub use compute_program::*;
+
+mod transform_feedback_program;
+#[doc(inline)]
+pub use transform_feedback_program::*;
+
+mod program_library;
+#[doc(inline)]
+pub use program_library::*;
+
 mod scissor_box;
 #[doc(inline)]
 pub use scissor_box::*;
 
+mod viewport;
+#[doc(inline)]
+pub use viewport::*;
+
+mod plane;
+#[doc(inline)]
+pub use plane::*;
+
+mod segment;
+#[doc(inline)]
+pub use segment::*;
+
+mod camera;
+#[doc(inline)]
+pub use camera::*;
+
+mod clip_planes;
+#[doc(inline)]
+pub use clip_planes::*;
+
+mod render_stats;
+#[doc(inline)]
+pub use render_stats::*;
+
+mod state_guard;
+#[doc(inline)]
+pub use state_guard::*;
+
 pub mod prelude {
 
     //!
@@ -40,7 +80,7 @@ pub mod prelude {
     pub use three_d_asset::prelude::*;
 }
 pub use prelude::*;
-pub use three_d_asset::{Camera, Viewport};
+pub use three_d_asset::{Camera, PixelPoint, ProjectionType, Viewport};
 
 /// A result for this crate.
 use thiserror::Error;
@@ -59,6 +99,24 @@ pub enum CoreError {
     ShaderCompilation(String, String, String),
     #[error("failed to link shader program: {0}")]
     ShaderLink(String),
+    #[error("the shader include {0} is not among the given includes")]
+    UnknownShaderInclude(String),
+    #[error("invalid include directive in shader: {0}")]
+    InvalidShaderInclude(String),
+    #[error("compute shaders are not supported, they require OpenGL 4.3 or OpenGL ES 3.1")]
+    ComputeShadersNotSupported,
+    #[error("a multi render target needs between 1 and {1} color textures, got {0}")]
+    ColorTextureCount(usize, u32),
+    #[error(
+        "all textures of a multi render target must have the same size, got {0}x{1} and {2}x{3}"
+    )]
+    TextureSizeMismatch(u32, u32, u32, u32),
+    #[error("cannot create a texture of size {0}x{1}x{2}, the width and height must be between 1 and {3} and the depth must be at least 1, which for example is not the case for viewport sized textures when the window is minimized")]
+    InvalidTextureSize(u32, u32, u32, u32),
+    #[error("the number of indices must be divisable by 3, but it is {0}")]
+    InvalidIndexCount(usize),
+    #[error("failed mapping {0} bytes of a buffer into client memory")]
+    BufferMapping(usize),
 }
 
 ///
@@ -166,15 +224,47 @@ fn from_byte_slice<T: DataType>(data: &[u8]) -> &[T] {
 }
 
 fn format_from_data_type<T: DataType>() -> u32 {
-    match T::size() {
-        1 => crate::context::RED,
-        2 => crate::context::RG,
-        3 => crate::context::RGB,
-        4 => crate::context::RGBA,
-        _ => unreachable!(),
+    if is_integer_data_type(T::data_type()) {
+        match T::size() {
+            1 => crate::context::RED_INTEGER,
+            2 => crate::context::RG_INTEGER,
+            3 => crate::context::RGB_INTEGER,
+            4 => crate::context::RGBA_INTEGER,
+            _ => unreachable!(),
+        }
+    } else {
+        match T::size() {
+            1 => crate::context::RED,
+            2 => crate::context::RG,
+            3 => crate::context::RGB,
+            4 => crate::context::RGBA,
+            _ => unreachable!(),
+        }
     }
 }
 
+///
+/// Returns true if the given data type is stored as unnormalized integers on the GPU, ie. if it should be accessed
+/// using an integer sampler (`usampler2D`/`isampler2D`) in a shader.
+///
+fn is_integer_data_type(data_type: u32) -> bool {
+    matches!(
+        data_type,
+        crate::context::BYTE
+            | crate::context::SHORT
+            | crate::context::UNSIGNED_SHORT
+            | crate::context::INT
+            | crate::context::UNSIGNED_INT
+    )
+}
+
+fn is_signed_data_type(data_type: u32) -> bool {
+    matches!(
+        data_type,
+        crate::context::BYTE | crate::context::SHORT | crate::context::INT
+    )
+}
+
 fn flip_y<T: TextureDataType>(pixels: &mut [T], width: usize, height: usize) {
     for row in 0..height / 2 {
         for col in 0..width {
@@ -17,6 +17,14 @@ mod uniform_buffer;
 #[doc(inline)]
 pub use uniform_buffer::*;
 
+mod shader_storage_buffer;
+#[doc(inline)]
+pub use shader_storage_buffer::*;
+
+mod draw_indirect_buffer;
+#[doc(inline)]
+pub use draw_indirect_buffer::*;
+
 use crate::core::*;
 use data_type::*;
 
@@ -46,7 +54,10 @@ impl<T: BufferDataType + ?Sized> BufferDataType for &T {}
 struct Buffer {
     context: Context,
     id: crate::context::Buffer,
+    memory: TrackedMemory,
     attribute_count: u32,
+    capacity: u32,
+    element_size: usize,
     data_type: u32,
     data_size: u32,
     normalized: bool,
@@ -57,7 +68,10 @@ impl Buffer {
         Self {
             context: context.clone(),
             id: unsafe { context.create_buffer().expect("Failed creating buffer") },
+            memory: context.track_buffer_memory(0),
             attribute_count: 0,
+            capacity: 0,
+            element_size: 0,
             data_type: 0,
             data_size: 0,
             normalized: false,
@@ -75,23 +89,119 @@ impl Buffer {
     pub fn fill<T: BufferDataType>(&mut self, data: &[T]) {
         self.bind();
         unsafe {
-            self.context.buffer_data_u8_slice(
-                crate::context::ARRAY_BUFFER,
-                to_byte_slice(data),
-                if self.attribute_count > 0 {
-                    crate::context::DYNAMIC_DRAW
-                } else {
-                    crate::context::STATIC_DRAW
-                },
-            );
+            if self.has_layout_of::<T>() && data.len() as u32 <= self.capacity {
+                // Reuse the already allocated memory
+                self.context.buffer_sub_data_u8_slice(
+                    crate::context::ARRAY_BUFFER,
+                    0,
+                    to_byte_slice(data),
+                );
+            } else {
+                self.context.buffer_data_u8_slice(
+                    crate::context::ARRAY_BUFFER,
+                    to_byte_slice(data),
+                    if self.attribute_count > 0 {
+                        crate::context::DYNAMIC_DRAW
+                    } else {
+                        crate::context::STATIC_DRAW
+                    },
+                );
+                self.capacity = data.len() as u32;
+                self.memory.set_bytes(std::mem::size_of_val(data) as u64);
+            }
             self.context.bind_buffer(crate::context::ARRAY_BUFFER, None);
         }
         self.attribute_count = data.len() as u32;
+        self.element_size = std::mem::size_of::<T>();
         self.data_type = T::data_type();
         self.data_size = T::size();
         self.normalized = T::normalized();
     }
 
+    pub fn update_partial<T: BufferDataType>(&mut self, data: &[T], offset_elements: usize) {
+        if !self.has_layout_of::<T>() {
+            panic!("the data type of the data used for a partial update must match the data type of the buffer");
+        }
+        if offset_elements + data.len() > self.capacity as usize {
+            panic!(
+                "cannot update elements {}..{} of a buffer with capacity for {} elements",
+                offset_elements,
+                offset_elements + data.len(),
+                self.capacity
+            );
+        }
+        self.bind();
+        unsafe {
+            self.context.buffer_sub_data_u8_slice(
+                crate::context::ARRAY_BUFFER,
+                (offset_elements * self.element_size) as i32,
+                to_byte_slice(data),
+            );
+            self.context.bind_buffer(crate::context::ARRAY_BUFFER, None);
+        }
+        self.attribute_count = self
+            .attribute_count
+            .max((offset_elements + data.len()) as u32);
+    }
+
+    pub fn resize_if_needed(&mut self, new_len: usize) {
+        if self.element_size == 0 {
+            panic!("cannot resize a buffer before its data type is known, fill the buffer with data first");
+        }
+        let new_len = new_len as u32;
+        if new_len > self.capacity {
+            let capacity = new_len.max(2 * self.capacity);
+            unsafe {
+                let id = self
+                    .context
+                    .create_buffer()
+                    .expect("Failed creating buffer");
+                self.context
+                    .bind_buffer(crate::context::COPY_WRITE_BUFFER, Some(id));
+                self.context.buffer_data_size(
+                    crate::context::COPY_WRITE_BUFFER,
+                    (capacity as usize * self.element_size) as i32,
+                    crate::context::DYNAMIC_DRAW,
+                );
+                if self.attribute_count > 0 {
+                    self.context
+                        .bind_buffer(crate::context::COPY_READ_BUFFER, Some(self.id));
+                    self.context.copy_buffer_sub_data(
+                        crate::context::COPY_READ_BUFFER,
+                        crate::context::COPY_WRITE_BUFFER,
+                        0,
+                        0,
+                        (self.attribute_count as usize * self.element_size) as i32,
+                    );
+                    self.context
+                        .bind_buffer(crate::context::COPY_READ_BUFFER, None);
+                }
+                self.context
+                    .bind_buffer(crate::context::COPY_WRITE_BUFFER, None);
+                self.context.delete_buffer(self.id);
+                self.id = id;
+            }
+            self.capacity = capacity;
+            self.memory
+                .set_bytes(capacity as u64 * self.element_size as u64);
+        }
+        self.attribute_count = new_len;
+    }
+
+    pub fn read<T: BufferDataType>(&self) -> Result<Vec<T>, CoreError> {
+        if !self.has_layout_of::<T>() {
+            panic!("the data type used for reading must match the data type of the buffer");
+        }
+        read_buffer(&self.context, self.id, self.attribute_count as usize)
+    }
+
+    fn has_layout_of<T: BufferDataType>(&self) -> bool {
+        self.element_size == std::mem::size_of::<T>()
+            && self.data_type == T::data_type()
+            && self.data_size == T::size()
+            && self.normalized == T::normalized()
+    }
+
     pub fn attribute_count(&self) -> u32 {
         self.attribute_count
     }
@@ -102,6 +212,60 @@ impl Buffer {
                 .bind_buffer(crate::context::ARRAY_BUFFER, Some(self.id));
         }
     }
+
+    pub fn bind_transform_feedback(&self, index: u32) {
+        unsafe {
+            self.context.bind_buffer_base(
+                crate::context::TRANSFORM_FEEDBACK_BUFFER,
+                index,
+                Some(self.id),
+            );
+        }
+    }
+}
+
+///
+/// Reads the first `count` values of the given type from the given buffer.
+/// The buffer is bound to the copy read target, which is allowed for all types of buffers, so the read does not interfere with other bindings.
+/// Returns an error if the buffer could not be mapped into client memory.
+///
+fn read_buffer<T: DataType>(
+    context: &Context,
+    id: crate::context::Buffer,
+    count: usize,
+) -> Result<Vec<T>, CoreError> {
+    let mut data = Vec::<T>::with_capacity(count);
+    if count > 0 {
+        let byte_size = count * std::mem::size_of::<T>();
+        unsafe {
+            context.bind_buffer(crate::context::COPY_READ_BUFFER, Some(id));
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                let pointer = context.map_buffer_range(
+                    crate::context::COPY_READ_BUFFER,
+                    0,
+                    byte_size as i32,
+                    crate::context::MAP_READ_BIT,
+                );
+                if pointer.is_null() {
+                    context.bind_buffer(crate::context::COPY_READ_BUFFER, None);
+                    return Err(CoreError::BufferMapping(byte_size));
+                }
+                std::ptr::copy_nonoverlapping(pointer, data.as_mut_ptr() as *mut u8, byte_size);
+                context.unmap_buffer(crate::context::COPY_READ_BUFFER);
+            }
+            // Mapping buffers is not supported on web
+            #[cfg(target_arch = "wasm32")]
+            context.get_buffer_sub_data(
+                crate::context::COPY_READ_BUFFER,
+                0,
+                std::slice::from_raw_parts_mut(data.as_mut_ptr() as *mut u8, byte_size),
+            );
+            data.set_len(count);
+            context.bind_buffer(crate::context::COPY_READ_BUFFER, None);
+        }
+    }
+    Ok(data)
 }
 
 impl Drop for Buffer {
@@ -111,3 +275,84 @@ impl Drop for Buffer {
         }
     }
 }
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_update_partial() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut buffer = Buffer::new_with_data(&context, &[0.0f32, 1.0, 2.0, 3.0, 4.0]);
+        let id = buffer.id;
+        buffer.update_partial(&[10.0f32, 11.0], 2);
+        assert_eq!(buffer.id, id);
+        assert_eq!(buffer.attribute_count(), 5);
+        assert_eq!(
+            buffer.read::<f32>().unwrap(),
+            vec![0.0, 1.0, 10.0, 11.0, 4.0]
+        );
+
+        buffer.update_partial(&[20.0f32], 4);
+        assert_eq!(
+            buffer.read::<f32>().unwrap(),
+            vec![0.0, 1.0, 10.0, 11.0, 20.0]
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "cannot update elements 4..6 of a buffer with capacity for 5 elements"
+    )]
+    fn test_update_partial_out_of_range() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut buffer = Buffer::new_with_data(&context, &[0.0f32, 1.0, 2.0, 3.0, 4.0]);
+        buffer.update_partial(&[10.0f32, 11.0], 4);
+    }
+
+    #[test]
+    fn test_resize_if_needed() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut buffer = Buffer::new_with_data(&context, &[vec2(0.0f32, 1.0), vec2(2.0, 3.0)]);
+
+        // Growing beyond the capacity reallocates and keeps the existing content
+        buffer.resize_if_needed(3);
+        assert_eq!(buffer.capacity, 4);
+        assert_eq!(buffer.attribute_count(), 3);
+        buffer.update_partial(&[vec2(4.0f32, 5.0)], 2);
+        assert_eq!(
+            buffer.read::<Vec2>().unwrap(),
+            vec![vec2(0.0, 1.0), vec2(2.0, 3.0), vec2(4.0, 5.0)]
+        );
+
+        // Growing within the capacity reuses the buffer
+        let id = buffer.id;
+        buffer.resize_if_needed(4);
+        assert_eq!(buffer.id, id);
+        assert_eq!(buffer.capacity, 4);
+        buffer.update_partial(&[vec2(6.0f32, 7.0)], 3);
+        assert_eq!(
+            buffer.read::<Vec2>().unwrap(),
+            vec![
+                vec2(0.0, 1.0),
+                vec2(2.0, 3.0),
+                vec2(4.0, 5.0),
+                vec2(6.0, 7.0)
+            ]
+        );
+
+        // Shrinking keeps the capacity and the first elements
+        buffer.resize_if_needed(1);
+        assert_eq!(buffer.id, id);
+        assert_eq!(buffer.capacity, 4);
+        assert_eq!(buffer.read::<Vec2>().unwrap(), vec![vec2(0.0, 1.0)]);
+
+        // Filling with fewer elements than the capacity reuses the buffer
+        buffer.fill(&[vec2(8.0f32, 9.0), vec2(10.0, 11.0)]);
+        assert_eq!(buffer.id, id);
+        assert_eq!(
+            buffer.read::<Vec2>().unwrap(),
+            vec![vec2(8.0, 9.0), vec2(10.0, 11.0)]
+        );
+    }
+}
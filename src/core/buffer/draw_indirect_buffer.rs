@@ -0,0 +1,170 @@
+use crate::core::*;
+
+///
+/// The parameters of a draw call of a mesh without an index buffer which are read from a [DrawIndirectBuffer] when drawing,
+/// see for example [Program::draw_arrays_indirect].
+/// The layout matches the layout expected by OpenGL, so the commands can also be written by a [ComputeProgram],
+/// in which case each command is four consecutive `uint` values in the shader.
+///
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DrawIndirectCommand {
+    /// The number of vertices to draw.
+    pub count: u32,
+    /// The number of instances to draw.
+    pub instance_count: u32,
+    /// The index of the first vertex to draw.
+    pub first: u32,
+    /// The index of the first instance, must be zero on OpenGL ES.
+    pub base_instance: u32,
+}
+
+///
+/// The parameters of a draw call of a mesh with an index buffer which are read from a [DrawIndirectBuffer] when drawing,
+/// see for example [Program::draw_elements_indirect].
+/// The layout matches the layout expected by OpenGL, so the commands can also be written by a [ComputeProgram],
+/// in which case each command is five consecutive `uint` values in the shader.
+///
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DrawElementsIndirectCommand {
+    /// The number of indices to draw.
+    pub count: u32,
+    /// The number of instances to draw.
+    pub instance_count: u32,
+    /// The index of the first index to draw.
+    pub first_index: u32,
+    /// The value added to each index before reading the vertex data.
+    pub base_vertex: i32,
+    /// The index of the first instance, must be zero on OpenGL ES.
+    pub base_instance: u32,
+}
+
+///
+/// A buffer containing the parameters of one or more draw calls, ie. [DrawIndirectCommand]s or [DrawElementsIndirectCommand]s.
+/// The commands can either be written from the CPU using [DrawIndirectBuffer::fill] and [DrawIndirectBuffer::fill_elements]
+/// or on the GPU by a [ComputeProgram] (see [ComputeProgram::bind_draw_indirect_buffer]), which makes it possible to for example cull objects
+/// without reading any data back to the CPU.
+///
+/// **Note:** Indirect draw calls are only supported on OpenGL 4.0 and OpenGL ES 3.1 or newer, not on WebGL2 (see [Context::supports_draw_indirect]).
+///
+pub struct DrawIndirectBuffer {
+    context: Context,
+    id: crate::context::Buffer,
+    _memory: TrackedMemory,
+    capacity: usize,
+}
+
+impl DrawIndirectBuffer {
+    ///
+    /// Creates a new draw indirect buffer with room for the given number of commands of either type. The content is undefined until it is written to.
+    ///
+    pub fn new(context: &Context, capacity: usize) -> Self {
+        let id = unsafe { context.create_buffer().expect("Failed creating buffer") };
+        unsafe {
+            context.bind_buffer(crate::context::DRAW_INDIRECT_BUFFER, Some(id));
+            context.buffer_data_size(
+                crate::context::DRAW_INDIRECT_BUFFER,
+                (capacity * std::mem::size_of::<DrawElementsIndirectCommand>()) as i32,
+                crate::context::DYNAMIC_DRAW,
+            );
+            context.bind_buffer(crate::context::DRAW_INDIRECT_BUFFER, None);
+        }
+        Self {
+            context: context.clone(),
+            id,
+            _memory: context.track_buffer_memory(
+                (capacity * std::mem::size_of::<DrawElementsIndirectCommand>()) as u64,
+            ),
+            capacity,
+        }
+    }
+
+    ///
+    /// The number of commands this buffer has room for.
+    ///
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    ///
+    /// Writes the given commands, used for meshes without an index buffer, to the beginning of this buffer.
+    ///
+    /// # Panic
+    /// Will panic if the number of commands is larger than the capacity of this buffer.
+    ///
+    pub fn fill(&mut self, commands: &[DrawIndirectCommand]) {
+        let data = commands
+            .iter()
+            .flat_map(|c| [c.count, c.instance_count, c.first, c.base_instance])
+            .collect::<Vec<_>>();
+        self.write(commands.len(), &data);
+    }
+
+    ///
+    /// Writes the given commands, used for meshes with an index buffer, to the beginning of this buffer.
+    ///
+    /// # Panic
+    /// Will panic if the number of commands is larger than the capacity of this buffer.
+    ///
+    pub fn fill_elements(&mut self, commands: &[DrawElementsIndirectCommand]) {
+        let data = commands
+            .iter()
+            .flat_map(|c| {
+                [
+                    c.count,
+                    c.instance_count,
+                    c.first_index,
+                    c.base_vertex as u32,
+                    c.base_instance,
+                ]
+            })
+            .collect::<Vec<_>>();
+        self.write(commands.len(), &data);
+    }
+
+    fn write(&mut self, count: usize, data: &[u32]) {
+        if count > self.capacity {
+            panic!(
+                "cannot write {} commands to a draw indirect buffer with capacity for {} commands",
+                count, self.capacity
+            );
+        }
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::DRAW_INDIRECT_BUFFER, Some(self.id));
+            self.context.buffer_sub_data_u8_slice(
+                crate::context::DRAW_INDIRECT_BUFFER,
+                0,
+                to_byte_slice(data),
+            );
+            self.context
+                .bind_buffer(crate::context::DRAW_INDIRECT_BUFFER, None);
+        }
+    }
+
+    pub(in crate::core) fn bind(&self) {
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::DRAW_INDIRECT_BUFFER, Some(self.id));
+        }
+    }
+
+    pub(in crate::core) fn bind_storage(&self, binding: u32) {
+        unsafe {
+            self.context.bind_buffer_base(
+                crate::context::SHADER_STORAGE_BUFFER,
+                binding,
+                Some(self.id),
+            )
+        };
+    }
+}
+
+impl Drop for DrawIndirectBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.context.delete_buffer(self.id);
+        }
+    }
+}
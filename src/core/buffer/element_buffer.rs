@@ -31,10 +31,17 @@ impl ElementBufferDataType for u32 {
 pub struct ElementBuffer {
     context: Context,
     id: crate::context::Buffer,
+    memory: TrackedMemory,
     count: usize,
+    capacity: usize,
     data_type: u32,
 }
 
+///
+/// Another name for an [ElementBuffer].
+///
+pub type IndexBuffer = ElementBuffer;
+
 impl ElementBuffer {
     ///
     /// Creates a new empty element buffer.
@@ -44,7 +51,9 @@ impl ElementBuffer {
         Self {
             context: context.clone(),
             id,
+            memory: context.track_buffer_memory(0),
             count: 0,
+            capacity: 0,
             data_type: 0,
         }
     }
@@ -60,6 +69,21 @@ impl ElementBuffer {
         buffer
     }
 
+    ///
+    /// Creates a new element buffer and fills it with the given indices of type `u8`, `u16` or `u32`,
+    /// for example `IndexBuffer::try_new_with_data(&context, &[0u32, 1, 2])`.
+    /// Returns an error if the number of indices is not divisable by 3.
+    ///
+    pub fn try_new_with_data<T: ElementBufferDataType>(
+        context: &Context,
+        data: &[T],
+    ) -> Result<Self, CoreError> {
+        if data.len() % 3 != 0 {
+            return Err(CoreError::InvalidIndexCount(data.len()));
+        }
+        Ok(Self::new_with_data(context, data))
+    }
+
     ///
     /// Fills the buffer with the given indices which must be divisable by 3.
     ///
@@ -75,7 +99,51 @@ impl ElementBuffer {
                 .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
         }
         self.count = data.len();
+        self.capacity = data.len();
         self.data_type = T::data_type();
+        self.memory.set_bytes(std::mem::size_of_val(data) as u64);
+    }
+
+    ///
+    /// Updates the buffer with the given indices which must be divisable by 3.
+    /// In contrast to [ElementBuffer::fill], the existing memory is reused (using `glBufferSubData`) if the buffer has capacity for the indices and the index type is unchanged,
+    /// which makes it suitable for geometry that changes topology each frame.
+    ///
+    pub fn update<T: ElementBufferDataType>(&mut self, data: &[T]) {
+        if data.len() > self.capacity || self.data_type != T::data_type() {
+            self.bind();
+            unsafe {
+                self.context.buffer_data_u8_slice(
+                    crate::context::ELEMENT_ARRAY_BUFFER,
+                    to_byte_slice(data),
+                    crate::context::DYNAMIC_DRAW,
+                );
+                self.context
+                    .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
+            }
+            self.capacity = data.len();
+            self.data_type = T::data_type();
+            self.memory.set_bytes(std::mem::size_of_val(data) as u64);
+        } else {
+            self.bind();
+            unsafe {
+                self.context.buffer_sub_data_u8_slice(
+                    crate::context::ELEMENT_ARRAY_BUFFER,
+                    0,
+                    to_byte_slice(data),
+                );
+                self.context
+                    .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
+            }
+        }
+        self.count = data.len();
+    }
+
+    ///
+    /// Returns true if the buffer contains no indices.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.count == 0
     }
 
     ///
@@ -92,6 +160,29 @@ impl ElementBuffer {
         self.count / 3
     }
 
+    ///
+    /// Reads the indices in the buffer back from the GPU, converted to `u32` regardless of the index type the buffer was filled with.
+    /// This waits for all previously issued work using this buffer to finish, so it should be used sparingly.
+    /// Returns an error if the buffer could not be mapped into client memory.
+    ///
+    pub fn read(&self) -> Result<Vec<u32>, CoreError> {
+        Ok(match self.data_type {
+            crate::context::UNSIGNED_BYTE => {
+                super::read_buffer::<u8>(&self.context, self.id, self.count)?
+                    .iter()
+                    .map(|i| i.as_u32())
+                    .collect()
+            }
+            crate::context::UNSIGNED_SHORT => {
+                super::read_buffer::<u16>(&self.context, self.id, self.count)?
+                    .iter()
+                    .map(|i| i.as_u32())
+                    .collect()
+            }
+            _ => super::read_buffer::<u32>(&self.context, self.id, self.count)?,
+        })
+    }
+
     pub(crate) fn bind(&self) {
         unsafe {
             self.context
@@ -102,6 +193,14 @@ impl ElementBuffer {
     pub(crate) fn data_type(&self) -> u32 {
         self.data_type
     }
+
+    pub(crate) fn data_size(&self) -> u32 {
+        match self.data_type {
+            crate::context::UNSIGNED_BYTE => 1,
+            crate::context::UNSIGNED_SHORT => 2,
+            _ => 4,
+        }
+    }
 }
 
 impl Drop for ElementBuffer {
@@ -111,3 +210,101 @@ impl Drop for ElementBuffer {
         }
     }
 }
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use crate::core::*;
+
+    #[test]
+    fn test_draw_subset_of_elements() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let program = Program::from_source(
+            &context,
+            "in vec3 position;
+            void main() {
+                gl_Position = vec4(position, 1.0);
+            }",
+            "layout (location = 0) out vec4 color;
+            void main() {
+                color = vec4(1.0);
+            }",
+        )
+        .unwrap();
+        // One triangle covering the left pixel and one covering the right pixel
+        let positions = VertexBuffer::new_with_data(
+            &context,
+            &[
+                vec3(-1.0, -1.0, 0.0),
+                vec3(0.0, -1.0, 0.0),
+                vec3(-1.0, 3.0, 0.0),
+                vec3(0.0, -1.0, 0.0),
+                vec3(1.0, -1.0, 0.0),
+                vec3(1.0, 3.0, 0.0),
+            ],
+        );
+        let render = |elements: &ElementBuffer| {
+            let mut texture = Texture2D::new_empty::<[u8; 4]>(
+                &context,
+                2,
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            texture
+                .as_color_target(None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+                .write(|| {
+                    program.use_vertex_attribute("position", &positions);
+                    program.draw_subset_of_elements(
+                        RenderStates::default(),
+                        Viewport::new_at_origo(2, 1),
+                        elements,
+                        3,
+                        3,
+                    );
+                })
+                .read::<[u8; 4]>()
+        };
+
+        // The first element is given in elements, not in bytes, so only the second triangle is drawn
+        let expected = vec![[0, 0, 0, 255], [255, 255, 255, 255]];
+        assert_eq!(
+            render(&ElementBuffer::new_with_data(
+                &context,
+                &[0u8, 1, 2, 3, 4, 5]
+            )),
+            expected
+        );
+        assert_eq!(
+            render(&ElementBuffer::new_with_data(
+                &context,
+                &[0u16, 1, 2, 3, 4, 5]
+            )),
+            expected
+        );
+        assert_eq!(
+            render(&ElementBuffer::new_with_data(
+                &context,
+                &[0u32, 1, 2, 3, 4, 5]
+            )),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_try_new_with_data() {
+        let context = crate::HeadlessContext::new().unwrap();
+        assert!(matches!(
+            IndexBuffer::try_new_with_data(&context, &[0u16, 1, 2, 3]),
+            Err(CoreError::InvalidIndexCount(4))
+        ));
+        let mut buffer = IndexBuffer::try_new_with_data(&context, &[0u32, 1, 2]).unwrap();
+        assert_eq!(buffer.count(), 3);
+        buffer.update(&[0u32, 1, 2, 2, 1, 3]);
+        assert_eq!(buffer.count(), 6);
+        assert_eq!(buffer.read().unwrap(), vec![0, 1, 2, 2, 1, 3]);
+    }
+}
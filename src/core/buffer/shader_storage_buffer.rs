@@ -0,0 +1,125 @@
+use crate::core::*;
+
+///
+/// A buffer of a fixed size in bytes which can be read and written by a [ComputeProgram]
+/// (see also [bind_ssbo](crate::core::ComputeProgram::bind_ssbo)).
+/// The layout of the data is defined by the `std430` layout of the buffer block in the shader.
+///
+/// **Note:** Shader storage buffers are only supported on OpenGL 4.3 and OpenGL ES 3.1 or newer, not on WebGL2 and macOS.
+///
+pub struct ShaderStorageBuffer {
+    context: Context,
+    id: crate::context::Buffer,
+    _memory: TrackedMemory,
+    size: usize,
+}
+
+impl ShaderStorageBuffer {
+    ///
+    /// Creates a new shader storage buffer with room for the given number of bytes. The content is undefined until it is written to.
+    ///
+    pub fn new(context: &Context, size_bytes: usize) -> Self {
+        let id = unsafe { context.create_buffer().expect("Failed creating buffer") };
+        unsafe {
+            context.bind_buffer(crate::context::SHADER_STORAGE_BUFFER, Some(id));
+            context.buffer_data_size(
+                crate::context::SHADER_STORAGE_BUFFER,
+                size_bytes as i32,
+                crate::context::DYNAMIC_COPY,
+            );
+            context.bind_buffer(crate::context::SHADER_STORAGE_BUFFER, None);
+        }
+        Self {
+            context: context.clone(),
+            id,
+            _memory: context.track_buffer_memory(size_bytes as u64),
+            size: size_bytes,
+        }
+    }
+
+    ///
+    /// Writes the given data to the beginning of this buffer.
+    ///
+    /// # Panic
+    /// Will panic if the size of the data in bytes is larger than the size of this buffer.
+    ///
+    pub fn fill<T: BufferDataType>(&mut self, data: &[T]) {
+        let bytes = to_byte_slice(data);
+        if bytes.len() > self.size {
+            panic!(
+                "cannot write {} bytes to a shader storage buffer of {} bytes",
+                bytes.len(),
+                self.size
+            );
+        }
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::SHADER_STORAGE_BUFFER, Some(self.id));
+            self.context
+                .buffer_sub_data_u8_slice(crate::context::SHADER_STORAGE_BUFFER, 0, bytes);
+            self.context
+                .bind_buffer(crate::context::SHADER_STORAGE_BUFFER, None);
+        }
+    }
+
+    ///
+    /// Reads the entire content of this buffer as values of the given type.
+    /// Any remaining bytes that do not make up a whole value are ignored.
+    /// This waits for all previously dispatched work writing to this buffer to finish, so it should be used sparingly.
+    /// Returns an error if the buffer could not be mapped into client memory.
+    ///
+    pub fn read<T: BufferDataType>(&self) -> Result<Vec<T>, CoreError> {
+        let count = self.size / std::mem::size_of::<T>();
+        let mut data = Vec::<T>::with_capacity(count);
+        if count > 0 {
+            let byte_size = count * std::mem::size_of::<T>();
+            unsafe {
+                self.context
+                    .bind_buffer(crate::context::SHADER_STORAGE_BUFFER, Some(self.id));
+                let ptr = self.context.map_buffer_range(
+                    crate::context::SHADER_STORAGE_BUFFER,
+                    0,
+                    byte_size as i32,
+                    crate::context::MAP_READ_BIT,
+                );
+                if ptr.is_null() {
+                    self.context
+                        .bind_buffer(crate::context::SHADER_STORAGE_BUFFER, None);
+                    return Err(CoreError::BufferMapping(byte_size));
+                }
+                std::ptr::copy_nonoverlapping(ptr, data.as_mut_ptr() as *mut u8, byte_size);
+                data.set_len(count);
+                self.context
+                    .unmap_buffer(crate::context::SHADER_STORAGE_BUFFER);
+                self.context
+                    .bind_buffer(crate::context::SHADER_STORAGE_BUFFER, None);
+            }
+        }
+        Ok(data)
+    }
+
+    ///
+    /// The size of this buffer in bytes.
+    ///
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub(in crate::core) fn bind(&self, binding: u32) {
+        unsafe {
+            self.context.bind_buffer_base(
+                crate::context::SHADER_STORAGE_BUFFER,
+                binding,
+                Some(self.id),
+            )
+        };
+    }
+}
+
+impl Drop for ShaderStorageBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            self.context.delete_buffer(self.id);
+        }
+    }
+}
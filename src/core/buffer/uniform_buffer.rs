@@ -7,6 +7,7 @@ use crate::core::*;
 pub struct UniformBuffer {
     context: Context,
     id: crate::context::Buffer,
+    _memory: TrackedMemory,
     offsets: Vec<usize>,
     data: Vec<f32>,
 }
@@ -30,6 +31,7 @@ impl UniformBuffer {
         let buffer = UniformBuffer {
             context: context.clone(),
             id,
+            _memory: context.track_buffer_memory(length as u64 * 4),
             offsets,
             data: vec![0.0; length],
         };
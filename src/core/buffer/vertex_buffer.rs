@@ -32,11 +32,48 @@ impl VertexBuffer {
     ///
     /// Fills the vertex buffer with the given data. The data should be in the same format as specified in the shader.
     /// As an example, if specified as `vec3` in the shader it needs to be specified as an array of `Vector3<T>` where `T` is a primitive type that implements [BufferDataType], for example can be f16 or f32.
+    /// The buffer is only reallocated if it does not have capacity for the data or if the data type has changed.
     ///
     pub fn fill<T: BufferDataType>(&mut self, data: &[T]) {
         self.buffer.fill(data);
     }
 
+    ///
+    /// Updates the vertex attributes starting at the given offset with the given data without reallocating the buffer, which is much faster than [VertexBuffer::fill] for large buffers.
+    /// The data must have the same type as the data the buffer was filled with.
+    /// Use [VertexBuffer::resize_if_needed] first if the buffer might not be large enough.
+    ///
+    /// # Panic
+    /// Will panic if the data type does not match the data type of the buffer or if the buffer does not have capacity for the data at the given offset.
+    ///
+    pub fn update_partial<T: BufferDataType>(&mut self, data: &[T], offset_elements: usize) {
+        self.buffer.update_partial(data, offset_elements)
+    }
+
+    ///
+    /// Resizes the buffer to contain the given number of vertex attributes.
+    /// The buffer is only reallocated if the current capacity is insufficient, in which case the capacity is at least doubled and the existing data is kept.
+    /// The content of new vertex attributes is undefined until they are updated using [VertexBuffer::update_partial].
+    ///
+    /// # Panic
+    /// Will panic if the buffer has never been filled with data, since the data type is then unknown.
+    ///
+    pub fn resize_if_needed(&mut self, new_len: usize) {
+        self.buffer.resize_if_needed(new_len)
+    }
+
+    ///
+    /// Reads the vertex attributes in the buffer back from the GPU, for example to get the current state after updates.
+    /// This waits for all previously issued work using this buffer to finish, so it should be used sparingly.
+    /// Returns an error if the buffer could not be mapped into client memory.
+    ///
+    /// # Panic
+    /// Will panic if the data type does not match the data type the buffer was filled with.
+    ///
+    pub fn read<T: BufferDataType>(&self) -> Result<Vec<T>, CoreError> {
+        self.buffer.read()
+    }
+
     ///
     /// The number of values in the buffer.
     ///
@@ -55,6 +92,10 @@ impl VertexBuffer {
         self.buffer.bind();
     }
 
+    pub(in crate::core) fn bind_transform_feedback(&self, index: u32) {
+        self.buffer.bind_transform_feedback(index);
+    }
+
     pub(in crate::core) fn data_type(&self) -> u32 {
         self.buffer.data_type
     }
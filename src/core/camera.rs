@@ -0,0 +1,502 @@
+use crate::core::*;
+
+///
+/// Additional functionality for a [Camera], mainly conversions between world space and screen space which are needed for annotations, measurements and similar.
+///
+pub trait CameraExt {
+    ///
+    /// Returns the pixel coordinate, in physical pixels relative to the bottom left corner of the screen, that the given world space position projects to.
+    /// Returns `None` if the position is behind the camera, since the projection of such a position is meaningless.
+    /// Note that the returned pixel can be outside the viewport, for example if the position is closer to the camera than the near plane.
+    ///
+    fn world_to_pixel(&self, position: Vec3) -> Option<PixelPoint>;
+
+    ///
+    /// Returns the distance from the camera to the given world space position measured along the view direction, ie. the view space depth.
+    ///
+    fn view_depth(&self, position: Vec3) -> f32;
+
+    ///
+    /// Clips the line segment between the two given world space positions against the near plane of the camera,
+    /// such that the part of the segment which is behind the camera is removed and the rest can be projected using [CameraExt::world_to_pixel].
+    /// Returns `None` if the entire segment is behind the camera.
+    ///
+    fn clip_line_to_near_plane(&self, start: Vec3, end: Vec3) -> Option<(Vec3, Vec3)>;
+
+    ///
+    /// Returns the height of the view in world units if the camera uses an orthographic projection and `None` if it uses a perspective projection.
+    ///
+    fn orthographic_height(&self) -> Option<f32>;
+
+    ///
+    /// Returns the size in world units of a physical pixel at the given distance from the camera along the view direction.
+    /// For an orthographic projection, the size is the same at all distances.
+    ///
+    fn world_units_per_pixel(&self, distance: f32) -> f32;
+
+    ///
+    /// Returns the six planes bounding the view frustum in world space, in the order left, right, bottom, top, near and far.
+    /// The normal of each plane points into the frustum, so a position is on the inside of the plane if [Plane::signed_distance] is non-negative.
+    ///
+    fn frustum_planes(&self) -> [Plane; 6];
+
+    ///
+    /// Moves the camera to the given position while keeping the target and the up direction.
+    ///
+    fn set_position(&mut self, position: Vec3);
+
+    ///
+    /// Turns the camera towards the given target while keeping the position and the up direction.
+    ///
+    fn set_target(&mut self, target: Vec3);
+
+    ///
+    /// Sets the up direction of the camera while keeping the position and the target.
+    ///
+    fn set_up(&mut self, up: Vec3);
+
+    ///
+    /// Sets the vertical field of view while keeping the near and far planes.
+    /// If the camera uses an orthographic projection, it is changed to a perspective projection.
+    ///
+    fn set_fov(&mut self, field_of_view_y: Radians);
+
+    ///
+    /// Sets the height of the orthographic view in world units while keeping the near and far planes, the width is given by the aspect ratio of the viewport.
+    /// If the camera uses a perspective projection, it is changed to an orthographic projection.
+    /// Use [CameraExt::set_fov] to change it back to a perspective projection.
+    ///
+    /// # Panic
+    /// Will panic if the height is not positive.
+    ///
+    fn set_orthographic_height(&mut self, height: f32);
+
+    ///
+    /// Returns whether the camera uses a perspective projection, otherwise it uses an orthographic projection.
+    ///
+    fn is_perspective(&self) -> bool;
+
+    ///
+    /// Returns a copy of the given camera, with the same view, viewport and near and far planes, which uses an orthographic projection with the given height in world units.
+    /// To keep the objects at the target the same size on screen when switching from a perspective projection,
+    /// use `camera.world_units_per_pixel(camera.position().distance(*camera.target())) * camera.viewport().height as f32` as the height,
+    /// see [CameraExt::world_units_per_pixel].
+    ///
+    /// # Panic
+    /// Will panic if the height is not positive.
+    ///
+    fn orthographic_from_perspective(camera: &Camera, extent_y: f32) -> Camera;
+
+    ///
+    /// Returns the view-projection matrix, ie. the projection matrix multiplied by the view matrix, which transforms from world space to clip space.
+    /// The view and projection matrices are available separately from [Camera::view] and [Camera::projection].
+    ///
+    fn view_projection(&self) -> Mat4;
+
+    ///
+    /// Returns the inverse of the view-projection matrix, see [CameraExt::view_projection], which transforms from clip space to world space.
+    /// This is for example needed to reconstruct the world space position of a pixel from the depth.
+    ///
+    fn inverse_view_projection(&self) -> Mat4;
+
+    ///
+    /// Places the camera on a sphere with the given radius around the given target and turns it towards the target, while keeping the up direction.
+    /// The position is given in spherical coordinates, where the azimuth is the angle around the y-axis measured from the positive z-axis towards the positive x-axis
+    /// and the elevation is the angle above the xz-plane. This is useful for applications which manage the camera state themselves instead of using an [OrbitControl](crate::OrbitControl).
+    ///
+    /// # Panic
+    /// Will panic if the distance is not positive.
+    ///
+    fn orbit_around(&mut self, target: Vec3, azimuth: Radians, elevation: Radians, distance: f32);
+
+    ///
+    /// Moves the camera along the current view direction such that it looks at the given target from the given distance.
+    ///
+    /// # Panic
+    /// Will panic if the distance is not positive.
+    ///
+    fn dolly_to(&mut self, target: Vec3, distance: f32);
+}
+
+impl CameraExt for Camera {
+    fn world_to_pixel(&self, position: Vec3) -> Option<PixelPoint> {
+        if self.view_depth(position) <= 0.0 {
+            return None;
+        }
+        let clip = self.view_projection() * position.extend(1.0);
+        let ndc = clip.truncate() / clip.w;
+        let viewport = self.viewport();
+        Some(PixelPoint {
+            x: viewport.x as f32 + 0.5 * (ndc.x + 1.0) * viewport.width as f32,
+            y: viewport.y as f32 + 0.5 * (ndc.y + 1.0) * viewport.height as f32,
+        })
+    }
+
+    fn view_depth(&self, position: Vec3) -> f32 {
+        -(self.view() * position.extend(1.0)).z
+    }
+
+    fn clip_line_to_near_plane(&self, start: Vec3, end: Vec3) -> Option<(Vec3, Vec3)> {
+        let near = self.z_near();
+        let start_depth = self.view_depth(start);
+        let end_depth = self.view_depth(end);
+        match (start_depth >= near, end_depth >= near) {
+            (true, true) => Some((start, end)),
+            (false, false) => None,
+            (start_visible, _) => {
+                let t = (near - start_depth) / (end_depth - start_depth);
+                let intersection = start + t * (end - start);
+                if start_visible {
+                    Some((start, intersection))
+                } else {
+                    Some((intersection, end))
+                }
+            }
+        }
+    }
+
+    fn orthographic_height(&self) -> Option<f32> {
+        match self.projection_type() {
+            ProjectionType::Orthographic { height } => Some(*height),
+            ProjectionType::Perspective { .. } => None,
+        }
+    }
+
+    fn world_units_per_pixel(&self, distance: f32) -> f32 {
+        let height = match self.projection_type() {
+            ProjectionType::Orthographic { height } => *height,
+            ProjectionType::Perspective { field_of_view_y } => {
+                2.0 * distance * (0.5 * field_of_view_y.0).tan()
+            }
+        };
+        height / self.viewport().height.max(1) as f32
+    }
+
+    fn frustum_planes(&self) -> [Plane; 6] {
+        let m = self.view_projection();
+        let row = |i: usize| vec4(m.x[i], m.y[i], m.z[i], m.w[i]);
+        let (x, y, z, w) = (row(0), row(1), row(2), row(3));
+        [w + x, w - x, w + y, w - y, w + z, w - z].map(|plane| {
+            let length = plane.truncate().magnitude();
+            Plane {
+                normal: plane.truncate() / length,
+                d: plane.w / length,
+            }
+        })
+    }
+
+    fn set_position(&mut self, position: Vec3) {
+        let (target, up) = (*self.target(), *self.up());
+        self.set_view(position, target, up);
+    }
+
+    fn set_target(&mut self, target: Vec3) {
+        let (position, up) = (*self.position(), *self.up());
+        self.set_view(position, target, up);
+    }
+
+    fn set_up(&mut self, up: Vec3) {
+        let (position, target) = (*self.position(), *self.target());
+        self.set_view(position, target, up);
+    }
+
+    fn set_fov(&mut self, field_of_view_y: Radians) {
+        let (z_near, z_far) = (self.z_near(), self.z_far());
+        self.set_perspective_projection(field_of_view_y, z_near, z_far);
+    }
+
+    fn set_orthographic_height(&mut self, height: f32) {
+        if height <= 0.0 {
+            panic!("the orthographic height must be positive, got {}", height);
+        }
+        let (z_near, z_far) = (self.z_near(), self.z_far());
+        self.set_orthographic_projection(height, z_near, z_far);
+    }
+
+    fn is_perspective(&self) -> bool {
+        matches!(self.projection_type(), ProjectionType::Perspective { .. })
+    }
+
+    fn orthographic_from_perspective(camera: &Camera, extent_y: f32) -> Camera {
+        let mut camera = camera.clone();
+        camera.set_orthographic_height(extent_y);
+        camera
+    }
+
+    fn view_projection(&self) -> Mat4 {
+        self.projection() * self.view()
+    }
+
+    fn inverse_view_projection(&self) -> Mat4 {
+        self.view_projection().invert().unwrap()
+    }
+
+    fn orbit_around(&mut self, target: Vec3, azimuth: Radians, elevation: Radians, distance: f32) {
+        if distance <= 0.0 {
+            panic!("the orbit distance must be positive, got {}", distance);
+        }
+        let direction = vec3(
+            elevation.0.cos() * azimuth.0.sin(),
+            elevation.0.sin(),
+            elevation.0.cos() * azimuth.0.cos(),
+        );
+        let up = *self.up();
+        self.set_view(target + direction * distance, target, up);
+    }
+
+    fn dolly_to(&mut self, target: Vec3, distance: f32) {
+        if distance <= 0.0 {
+            panic!("the dolly distance must be positive, got {}", distance);
+        }
+        let (direction, up) = (self.view_direction(), *self.up());
+        self.set_view(target - direction * distance, target, up);
+    }
+}
+
+///
+/// Keeps the view-projection matrix of a [Camera] from the previous frame, which is needed to compute how far each pixel has moved since the previous frame,
+/// for example to render the velocity texture used by the [MotionBlurEffect](crate::renderer::MotionBlurEffect), ie. the difference between the current clip space position
+/// `camera.view_projection() * position` and the previous clip space position `history.previous_view_projection() * position` of each surface.
+///
+/// Call [CameraHistory::update] once each frame after the camera has been updated, for example by a control, and before rendering.
+///
+#[derive(Clone, Copy, Debug)]
+pub struct CameraHistory {
+    view_projection: Mat4,
+    previous_view_projection: Mat4,
+}
+
+impl CameraHistory {
+    ///
+    /// Creates a new camera history where the previous view-projection matrix is the current view-projection matrix of the given camera, ie. there is no movement in the first frame.
+    ///
+    pub fn new(camera: &Camera) -> Self {
+        let view_projection = camera.view_projection();
+        Self {
+            view_projection,
+            previous_view_projection: view_projection,
+        }
+    }
+
+    ///
+    /// Starts a new frame by storing the view-projection matrix from the last call as the previous view-projection matrix
+    /// and the current view-projection matrix of the given camera as the view-projection matrix of this frame.
+    ///
+    pub fn update(&mut self, camera: &Camera) {
+        self.previous_view_projection = self.view_projection;
+        self.view_projection = camera.view_projection();
+    }
+
+    ///
+    /// Returns the view-projection matrix of the camera in the current frame, ie. at the last call to [CameraHistory::update].
+    ///
+    pub fn view_projection(&self) -> Mat4 {
+        self.view_projection
+    }
+
+    ///
+    /// Returns the view-projection matrix of the camera in the previous frame.
+    ///
+    pub fn previous_view_projection(&self) -> Mat4 {
+        self.previous_view_projection
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn camera() -> Camera {
+        Camera::new_perspective(
+            Viewport::new_at_origo(200, 100),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            degrees(90.0),
+            0.1,
+            100.0,
+        )
+    }
+
+    #[test]
+    fn test_world_to_pixel() {
+        let camera = camera();
+        let center = camera.world_to_pixel(vec3(0.0, 0.0, 0.0)).unwrap();
+        assert!((center.x - 100.0).abs() < 0.001 && (center.y - 50.0).abs() < 0.001);
+
+        // The vertical field of view is 90 degrees, so a point at the same height as the distance is at the top of the viewport
+        let top = camera.world_to_pixel(vec3(0.0, 5.0, 0.0)).unwrap();
+        assert!((top.x - 100.0).abs() < 0.001 && (top.y - 100.0).abs() < 0.001);
+
+        assert!(camera.world_to_pixel(vec3(0.0, 0.0, 6.0)).is_none());
+        assert!(camera.world_to_pixel(vec3(1.0, 0.0, 5.0)).is_none());
+    }
+
+    #[test]
+    fn test_clip_line_to_near_plane() {
+        let camera = camera();
+        let start = vec3(-1.0, 0.0, 0.0);
+        let end = vec3(1.0, 0.0, 0.0);
+        assert_eq!(
+            camera.clip_line_to_near_plane(start, end),
+            Some((start, end))
+        );
+
+        // Both ends behind the camera
+        assert_eq!(
+            camera.clip_line_to_near_plane(vec3(-1.0, 0.0, 6.0), vec3(1.0, 0.0, 7.0)),
+            None
+        );
+
+        // The line passes through the camera plane, the clipped end point is on the near plane
+        let behind = vec3(1.0, 0.0, 10.0);
+        let (clipped_start, clipped_end) = camera.clip_line_to_near_plane(start, behind).unwrap();
+        assert_eq!(clipped_start, start);
+        assert!((camera.view_depth(clipped_end) - 0.1).abs() < 0.0001);
+        assert!(camera.world_to_pixel(clipped_end).is_some());
+
+        let (clipped_start, clipped_end) = camera.clip_line_to_near_plane(behind, start).unwrap();
+        assert_eq!(clipped_end, start);
+        assert!((camera.view_depth(clipped_start) - 0.1).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_world_units_per_pixel() {
+        let mut camera = camera();
+        assert_eq!(camera.orthographic_height(), None);
+        // The vertical field of view is 90 degrees, so the view is 10 units high at a distance of 5
+        assert!((camera.world_units_per_pixel(5.0) - 0.1).abs() < 0.0001);
+
+        camera.set_orthographic_projection(4.0, 0.1, 100.0);
+        assert_eq!(camera.orthographic_height(), Some(4.0));
+        assert!((camera.world_units_per_pixel(5.0) - 0.04).abs() < 0.0001);
+        assert!((camera.world_units_per_pixel(50.0) - 0.04).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_frustum_planes() {
+        let camera = camera();
+        let planes = camera.frustum_planes();
+        let inside = |p: Vec3| planes.iter().all(|plane| plane.signed_distance(p) >= 0.0);
+        assert!(inside(vec3(0.0, 0.0, 0.0)));
+        assert!(inside(vec3(0.0, 4.9, 0.0)));
+        assert!(!inside(vec3(0.0, 5.1, 0.0)));
+        assert!(!inside(vec3(0.0, 0.0, 6.0)));
+        assert!(!inside(vec3(0.0, 0.0, -100.0)));
+
+        // The near plane is at distance 0.1 from the camera
+        let near = planes[4];
+        assert!(near.signed_distance(vec3(0.0, 0.0, 4.9)).abs() < 0.0001);
+        for plane in planes {
+            assert!((plane.normal.magnitude() - 1.0).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn test_setters() {
+        let mut camera = camera();
+        camera.set_position(vec3(0.0, 0.0, 10.0));
+        assert_eq!(*camera.position(), vec3(0.0, 0.0, 10.0));
+        assert_eq!(*camera.target(), vec3(0.0, 0.0, 0.0));
+        assert!((camera.view_depth(vec3(0.0, 0.0, 0.0)) - 10.0).abs() < 0.0001);
+
+        camera.set_target(vec3(1.0, 0.0, 10.0));
+        assert_eq!(*camera.position(), vec3(0.0, 0.0, 10.0));
+        assert!(camera.view_direction().distance(vec3(1.0, 0.0, 0.0)) < 0.0001);
+
+        camera.set_up(vec3(0.0, 0.0, 1.0));
+        assert!(camera.up().distance(vec3(0.0, 0.0, 1.0)) < 0.0001);
+        assert!(camera.view_direction().distance(vec3(1.0, 0.0, 0.0)) < 0.0001);
+
+        camera.set_orthographic_projection(4.0, 0.5, 50.0);
+        camera.set_fov(degrees(60.0).into());
+        assert_eq!(camera.orthographic_height(), None);
+        assert_eq!((camera.z_near(), camera.z_far()), (0.5, 50.0));
+        // The vertical field of view is 60 degrees, so the view is 2 * tan(30) * 10 units high at a distance of 10
+        let expected = 2.0 * (30.0f32.to_radians()).tan() * 10.0 / 100.0;
+        assert!((camera.world_units_per_pixel(10.0) - expected).abs() < 0.0001);
+    }
+
+    #[test]
+    fn test_projection_switch() {
+        let mut camera = camera();
+        assert!(camera.is_perspective());
+        let view = *camera.view();
+
+        // The height that keeps the target the same size on screen
+        let height = camera.world_units_per_pixel(5.0) * 100.0;
+        let orthographic = Camera::orthographic_from_perspective(&camera, height);
+        assert!(!orthographic.is_perspective());
+        assert_eq!(*orthographic.view(), view);
+        assert_eq!((orthographic.z_near(), orthographic.z_far()), (0.1, 100.0));
+        assert!((orthographic.world_units_per_pixel(5.0) - height / 100.0).abs() < 0.0001);
+        let position = vec3(1.0, -2.0, 0.0);
+        let (a, b) = (
+            camera.world_to_pixel(position).unwrap(),
+            orthographic.world_to_pixel(position).unwrap(),
+        );
+        assert!((a.x - b.x).abs() < 0.001 && (a.y - b.y).abs() < 0.001);
+
+        camera.set_orthographic_height(4.0);
+        assert_eq!(camera.orthographic_height(), Some(4.0));
+        assert_eq!(*camera.view(), view);
+        camera.set_fov(degrees(90.0).into());
+        assert!(camera.is_perspective());
+        assert_eq!(*camera.view(), view);
+    }
+
+    #[test]
+    fn test_view_projection() {
+        let camera = camera();
+        let position = vec3(1.0, -2.0, 0.5);
+        let clip = camera.view_projection() * position.extend(1.0);
+        let pixel = camera.world_to_pixel(position).unwrap();
+        assert!((0.5 * (clip.x / clip.w + 1.0) * 200.0 - pixel.x).abs() < 0.001);
+        assert!((0.5 * (clip.y / clip.w + 1.0) * 100.0 - pixel.y).abs() < 0.001);
+
+        let world = camera.inverse_view_projection() * clip;
+        assert!((world.truncate() / world.w).distance(position) < 0.0001);
+    }
+
+    #[test]
+    fn test_orbit_around() {
+        let mut camera = camera();
+        let target = vec3(1.0, 2.0, 3.0);
+        camera.orbit_around(target, degrees(90.0).into(), degrees(0.0).into(), 4.0);
+        assert!(camera.position().distance(vec3(5.0, 2.0, 3.0)) < 0.0001);
+        assert_eq!(*camera.target(), target);
+        assert!(camera.view_direction().distance(vec3(-1.0, 0.0, 0.0)) < 0.0001);
+
+        camera.orbit_around(target, degrees(0.0).into(), degrees(30.0).into(), 2.0);
+        let expected = target + vec3(0.0, 1.0, 3.0f32.sqrt());
+        assert!(camera.position().distance(expected) < 0.0001);
+
+        camera.dolly_to(vec3(0.0, 0.0, 0.0), 10.0);
+        assert_eq!(*camera.target(), vec3(0.0, 0.0, 0.0));
+        assert!((camera.position().magnitude() - 10.0).abs() < 0.0001);
+        // The view direction is the same as before
+        let direction = (target - expected).normalize();
+        assert!(camera.view_direction().distance(direction) < 0.0001);
+    }
+
+    #[test]
+    fn test_camera_history() {
+        let mut camera = camera();
+        let mut history = CameraHistory::new(&camera);
+        assert_eq!(history.previous_view_projection(), camera.view_projection());
+
+        let before = camera.view_projection();
+        camera.set_position(vec3(1.0, 0.0, 5.0));
+        history.update(&camera);
+        assert_eq!(history.previous_view_projection(), before);
+        assert_eq!(history.view_projection(), camera.view_projection());
+
+        // The origin moves to the left on the screen when the camera moves to the right
+        let current = history.view_projection() * vec4(0.0, 0.0, 0.0, 1.0);
+        let previous = history.previous_view_projection() * vec4(0.0, 0.0, 0.0, 1.0);
+        assert!(current.x / current.w < previous.x / previous.w);
+
+        history.update(&camera);
+        assert_eq!(history.previous_view_projection(), camera.view_projection());
+    }
+}
@@ -0,0 +1,39 @@
+use crate::core::*;
+
+///
+/// The maximum number of clip planes that can be used at the same time (see [ClipPlanes]).
+///
+pub const MAX_CLIP_PLANES: usize = 8;
+
+///
+/// A set of planes that all geometry is clipped against, which can for example be used for creating section views.
+/// The part of the geometry that is on the side of a plane that the plane normal points towards is kept, the rest is removed.
+/// Set the clip planes on the context using [Context::set_clip_planes].
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClipPlanes {
+    /// The planes to clip against. At most [MAX_CLIP_PLANES] planes are supported.
+    pub planes: Vec<Plane>,
+    /// Whether or not to also clip the geometry when generating shadow maps.
+    /// If false, the shadows are cast by the entire geometry, including the part that is clipped away.
+    pub clip_shadows: bool,
+}
+
+impl ClipPlanes {
+    ///
+    /// Creates a new set of clip planes which are also used when generating shadow maps.
+    ///
+    pub fn new(planes: &[Plane]) -> Self {
+        Self {
+            planes: planes.to_vec(),
+            clip_shadows: true,
+        }
+    }
+
+    ///
+    /// Returns true if there are no planes to clip against.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.planes.is_empty()
+    }
+}
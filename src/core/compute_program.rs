@@ -0,0 +1,119 @@
+use crate::core::*;
+
+///
+/// A shader program consisting of a single compute shader which is used for general purpose computations on the GPU, for example sorting, physics or FFT.
+/// The input and output of the computation is typically given as [ShaderStorageBuffer]s (see [ComputeProgram::bind_ssbo])
+/// and the computation is executed using [ComputeProgram::dispatch].
+///
+/// **Note:** Compute shaders are only supported on OpenGL 4.3 and OpenGL ES 3.1 or newer, not on WebGL2 and macOS (see [Context::supports_compute_shaders]).
+///
+pub struct ComputeProgram {
+    program: Program,
+}
+
+impl ComputeProgram {
+    ///
+    /// Creates a new compute program from the given glsl compute shader source.
+    /// The source should not contain a `#version` directive since it is added automatically,
+    /// but it should define the workgroup size, for example `layout (local_size_x = 64) in;`.
+    ///
+    /// Returns [CoreError::ComputeShadersNotSupported] if compute shaders are not supported on the current platform.
+    ///
+    pub fn new(context: &Context, compute_shader_source: &str) -> Result<Self, CoreError> {
+        if !context.supports_compute_shaders() {
+            return Err(CoreError::ComputeShadersNotSupported);
+        }
+        let header: &str = if context.version().is_embedded {
+            "#version 310 es
+                precision highp float;
+                precision highp int;\n"
+        } else {
+            "#version 430 core\n"
+        };
+        let program = Program::from_shader_sources(
+            context,
+            &[(
+                crate::context::COMPUTE_SHADER,
+                "compute",
+                format!("{}{}", header, compute_shader_source),
+            )],
+            &[],
+        )?;
+        Ok(Self { program })
+    }
+
+    ///
+    /// Executes the compute shader using the given number of workgroups in each dimension.
+    /// The total number of invocations is therefore the number of workgroups times the workgroup size defined in the shader.
+    /// Waits for the writes to shader storage buffers to be visible before the buffers are used afterwards,
+    /// either by another dispatch, as vertex or instance data or when reading the buffer.
+    ///
+    pub fn dispatch(&self, x: u32, y: u32, z: u32) {
+        self.program.dispatch_compute(x, y, z);
+    }
+
+    ///
+    /// Binds the given [ShaderStorageBuffer] to the given binding point so it can be read and written in the shader by the buffer block with that binding point.
+    /// The binding point must be the same as specified in the shader, for example `layout (std430, binding = 0) buffer Output { float values[]; };`
+    /// should be bound using `bind_ssbo(0, &buffer)`, since it is not possible to change the binding point of a buffer block on OpenGL ES.
+    ///
+    pub fn bind_ssbo(&self, binding: u32, buffer: &ShaderStorageBuffer) {
+        buffer.bind(binding);
+    }
+
+    ///
+    /// Binds the given [DrawIndirectBuffer] to the given binding point so the draw commands can be written in the shader by the buffer block with that binding point,
+    /// for example `layout (std430, binding = 1) buffer Commands { uint commands[]; };` is bound using `bind_draw_indirect_buffer(1, &buffer)`,
+    /// where each command is four or five consecutive `uint` values (see [DrawIndirectCommand] and [DrawElementsIndirectCommand]).
+    ///
+    pub fn bind_draw_indirect_buffer(&self, binding: u32, buffer: &DrawIndirectBuffer) {
+        buffer.bind_storage(binding);
+    }
+
+    ///
+    /// Send the given uniform data to this compute program and associate it with the given named variable (see [Program::use_uniform]).
+    ///
+    /// # Panic
+    /// Will panic if the uniform is not defined or not used in the shader code.
+    /// In the latter case the variable is removed by the shader compiler.
+    ///
+    pub fn use_uniform<T: UniformDataType>(&self, name: &str, data: T) {
+        self.program.use_uniform(name, data);
+    }
+
+    ///
+    /// Send the given array of uniform data to this compute program and associate it with the given named variable (see [Program::use_uniform_array]).
+    ///
+    /// # Panic
+    /// Will panic if the uniform is not defined or not used in the shader code.
+    /// In the latter case the variable is removed by the shader compiler.
+    ///
+    pub fn use_uniform_array<T: UniformDataType>(&self, name: &str, data: &[T]) {
+        self.program.use_uniform_array(name, data);
+    }
+
+    ///
+    /// Use the given [Texture2D] in this compute program and associate it with the given named variable (see [Program::use_texture]).
+    ///
+    /// # Panic
+    /// Will panic if the texture is not defined in the shader code or not used.
+    /// In the latter case the variable is removed by the shader compiler.
+    ///
+    pub fn use_texture(&self, name: &str, texture: &Texture2D) {
+        self.program.use_texture(name, texture);
+    }
+
+    ///
+    /// Use the given [UniformBuffer] in this compute program and associate it with the given named variable (see [Program::use_uniform_block]).
+    ///
+    pub fn use_uniform_block(&self, name: &str, buffer: &UniformBuffer) {
+        self.program.use_uniform_block(name, buffer);
+    }
+
+    ///
+    /// Returns true if this program uses the uniform with the given name.
+    ///
+    pub fn requires_uniform(&self, name: &str) -> bool {
+        self.program.requires_uniform(name)
+    }
+}
@@ -1,5 +1,4 @@
 use super::*;
-use std::collections::HashMap;
 use std::sync::Arc;
 use std::sync::RwLock;
 
@@ -15,7 +14,23 @@ pub use crate::context::HasContext;
 pub struct Context {
     context: Arc<crate::context::Context>,
     pub(super) vao: crate::context::VertexArray,
-    programs: Arc<RwLock<HashMap<(String, String), Program>>>,
+    pub(super) programs: Arc<RwLock<ProgramStorage>>,
+    clip_planes: Arc<RwLock<ClipPlanes>>,
+    stencil_test: Arc<RwLock<StencilTest>>,
+    render_stats: Arc<RenderCounters>,
+    memory_stats: Arc<RwLock<MemoryStats>>,
+    object_ids: Arc<RwLock<ObjectIdState>>,
+    texture_units: Arc<std::sync::atomic::AtomicU32>,
+}
+
+///
+/// The id of the object which is currently rendered and, if rendering into a render target with object ids (see [RenderTarget::new_with_object_ids]),
+/// the number of bits used for the instance index.
+///
+#[derive(Clone, Copy, Debug, Default)]
+struct ObjectIdState {
+    object_id: u32,
+    instance_bits: Option<u32>,
 }
 
 impl Context {
@@ -42,7 +57,14 @@ impl Context {
             Self {
                 context,
                 vao,
-                programs: Arc::new(RwLock::new(HashMap::new())),
+                programs: Arc::new(RwLock::new(ProgramStorage::default())),
+                clip_planes: Arc::new(RwLock::new(ClipPlanes::default())),
+                stencil_test: Arc::new(RwLock::new(StencilTest::Disabled)),
+                render_stats: Arc::new(RenderCounters::default()),
+                memory_stats: Arc::new(RwLock::new(MemoryStats::default())),
+                object_ids: Arc::new(RwLock::new(ObjectIdState::default())),
+                // The first unit is always used, since textures are bound to the active unit when they are created
+                texture_units: Arc::new(std::sync::atomic::AtomicU32::new(1)),
             }
         };
         Ok(c)
@@ -58,16 +80,200 @@ impl Context {
         fragment_shader_source: String,
         callback: impl FnOnce(&Program),
     ) -> Result<(), CoreError> {
-        let key = (vertex_shader_source, fragment_shader_source);
-        let mut programs = self.programs.write().unwrap();
-        if let Some(program) = programs.get(&key) {
-            callback(program);
+        self.program_with_defines(
+            &vertex_shader_source,
+            &fragment_shader_source,
+            &[],
+            callback,
+        )
+    }
+
+    ///
+    /// Compiles a [Program] with the given vertex and fragment shader source and the given defines (see [Program::with_defines]) and stores it for later use.
+    /// If a program with the same source and set of defines (see [ProgramKey]) has already been created, then it is just returned.
+    ///
+    pub fn program_with_defines(
+        &self,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        defines: &[(&str, &str)],
+        callback: impl FnOnce(&Program),
+    ) -> Result<(), CoreError> {
+        let object_ids = *self.object_ids.read().unwrap();
+        if let Some(instance_bits) = object_ids.instance_bits {
+            // Rendering into a render target with object ids, so the shaders are extended to write the id into the second color attachment
+            let key = ProgramKey::new(
+                &object_id_vertex_shader_source(vertex_shader_source),
+                &object_id_fragment_shader_source(fragment_shader_source),
+                defines,
+            );
+            let program = || Program::from_key(self, &key);
+            return self
+                .program_library()
+                .get_or_create_with(key.clone(), program, |program| {
+                    program.use_uniform_if_required("objectId", object_ids.object_id);
+                    program.use_uniform_if_required("objectIdInstanceBits", instance_bits);
+                    callback(program)
+                });
+        }
+        let key = ProgramKey::new(vertex_shader_source, fragment_shader_source, defines);
+        let program = || Program::from_key(self, &key);
+        self.program_library()
+            .get_or_create_with(key.clone(), program, callback)
+    }
+
+    ///
+    /// Sets the id of the objects rendered until the next call to this method, which is written to the object id texture
+    /// when rendering into a render target with object ids (see [RenderTarget::new_with_object_ids]).
+    /// The render functions in the [renderer](crate::renderer) module sets the id of each object automatically, so this is only needed when drawing using a [Program] directly.
+    ///
+    pub fn set_object_id(&self, object_id: u32) {
+        self.object_ids.write().unwrap().object_id = object_id;
+    }
+
+    ///
+    /// Returns the id of the objects currently rendered, see [Context::set_object_id].
+    ///
+    pub fn object_id(&self) -> u32 {
+        self.object_ids.read().unwrap().object_id
+    }
+
+    ///
+    /// Enables writing the object id when the number of instance bits is specified, otherwise disables it, and returns the previous value.
+    ///
+    pub(in crate::core) fn set_object_id_output(&self, instance_bits: Option<u32>) -> Option<u32> {
+        std::mem::replace(
+            &mut self.object_ids.write().unwrap().instance_bits,
+            instance_bits,
+        )
+    }
+
+    ///
+    /// Returns the [ProgramLibrary] which stores all programs compiled using this context, for example by [Context::program].
+    /// Use this to share custom programs between multiple objects or to get statistics about the program cache.
+    ///
+    pub fn program_library(&self) -> ProgramLibrary<'_> {
+        ProgramLibrary::new(self)
+    }
+
+    ///
+    /// Sets the planes that all geometries rendered using this context are clipped against (see [ClipPlanes]).
+    /// The clip planes are applied in world space by the built-in geometries that support it, for example [Mesh](crate::renderer::Mesh) and [InstancedMesh](crate::renderer::InstancedMesh),
+    /// until they are replaced by another call to this method. Use [ClipPlanes::default] to disable clipping.
+    ///
+    /// # Panic
+    /// Will panic if more than [MAX_CLIP_PLANES] planes are specified.
+    ///
+    pub fn set_clip_planes(&self, clip_planes: ClipPlanes) {
+        if clip_planes.planes.len() > MAX_CLIP_PLANES {
+            panic!(
+                "cannot use more than {} clip planes (got {})",
+                MAX_CLIP_PLANES,
+                clip_planes.planes.len()
+            );
+        }
+        *self.clip_planes.write().unwrap() = clip_planes;
+    }
+
+    ///
+    /// Returns the planes that geometries are currently clipped against (see [Context::set_clip_planes]).
+    ///
+    pub fn clip_planes(&self) -> ClipPlanes {
+        self.clip_planes.read().unwrap().clone()
+    }
+
+    ///
+    /// Returns the number of draw calls and triangles drawn since the context was created or since the last call to [Context::reset_render_stats].
+    ///
+    pub fn render_stats(&self) -> RenderStats {
+        self.render_stats.stats()
+    }
+
+    ///
+    /// Resets the number of draw calls and triangles, for example at the beginning of each frame.
+    ///
+    pub fn reset_render_stats(&self) {
+        self.render_stats.reset();
+    }
+
+    ///
+    /// Returns the number of textures and buffers which are currently allocated using this context and an estimate of the GPU memory they use.
+    ///
+    pub fn memory_stats(&self) -> MemoryStats {
+        *self.memory_stats.read().unwrap()
+    }
+
+    pub(in crate::core) fn track_texture_memory(&self, bytes: u64) -> TrackedMemory {
+        TrackedMemory::new(self.memory_stats.clone(), true, bytes)
+    }
+
+    pub(in crate::core) fn track_buffer_memory(&self, bytes: u64) -> TrackedMemory {
+        TrackedMemory::new(self.memory_stats.clone(), false, bytes)
+    }
+
+    ///
+    /// Registers that a program has bound a texture to the texture unit with the given index, see [Context::texture_units].
+    ///
+    pub(in crate::core) fn use_texture_unit(&self, index: u32) {
+        self.texture_units
+            .fetch_max(index + 1, std::sync::atomic::Ordering::Relaxed);
+    }
+
+    ///
+    /// Returns the number of texture units, starting from the first, which three-d has bound textures to.
+    ///
+    pub(in crate::core) fn texture_units(&self) -> u32 {
+        self.texture_units
+            .load(std::sync::atomic::Ordering::Relaxed)
+    }
+
+    pub(in crate::core) fn count_draw_call(&self, triangles: u64) {
+        self.render_stats.count_draw_call(triangles);
+    }
+
+    ///
+    /// Returns whether or not compute shaders (see [ComputeProgram]) and shader storage buffers (see [ShaderStorageBuffer]) are supported,
+    /// which requires OpenGL 4.3 or OpenGL ES 3.1. They are never supported on WebGL2 and macOS.
+    ///
+    pub fn supports_compute_shaders(&self) -> bool {
+        let version = self.version();
+        if version.is_embedded {
+            version.major > 3 || (version.major == 3 && version.minor >= 1)
         } else {
-            let program = Program::from_source(self, &key.0, &key.1)?;
-            callback(&program);
-            programs.insert(key, program);
+            version.major > 4 || (version.major == 4 && version.minor >= 3)
+        }
+    }
+
+    ///
+    /// Returns whether or not indirect draw calls (see [DrawIndirectBuffer]) are supported,
+    /// which requires OpenGL 4.0 or OpenGL ES 3.1. They are never supported on WebGL2.
+    ///
+    pub fn supports_draw_indirect(&self) -> bool {
+        let version = self.version();
+        if version.is_embedded {
+            version.major > 3 || (version.major == 3 && version.minor >= 1)
+        } else {
+            version.major >= 4
+        }
+    }
+
+    ///
+    /// Enables the first `count` clip distances, ie. the values written to `gl_ClipDistance` in the vertex shader, and disables the rest.
+    /// Clip distances are not available on WebGL2 and OpenGL ES, so this method does nothing on those platforms.
+    ///
+    pub fn set_clip_distances(&self, count: u32) {
+        if self.version().is_embedded {
+            return;
+        }
+        unsafe {
+            for i in 0..MAX_CLIP_PLANES as u32 {
+                if i < count {
+                    self.enable(crate::context::CLIP_DISTANCE0 + i);
+                } else {
+                    self.disable(crate::context::CLIP_DISTANCE0 + i);
+                }
+            }
         }
-        Ok(())
     }
 
     ///
@@ -209,6 +415,85 @@ impl Context {
         }
     }
 
+    ///
+    /// Set the stencil test used by the following render calls (see [StencilTest]), which is disabled by default.
+    /// When the stencil test is disabled, the stencil function, operations and write mask are reset to their defaults,
+    /// so for example clearing the stencil buffer is not affected by a previous stencil test.
+    /// The stencil test is not part of the [RenderStates], so remember to disable it again after rendering.
+    ///
+    /// The last stencil test is stored and nothing is changed if it is set again,
+    /// so if the stencil state is changed directly using the low-level context, restore the default stencil state afterwards.
+    ///
+    pub fn set_stencil_test(&self, stencil_test: StencilTest) {
+        {
+            let mut current = self.stencil_test.write().unwrap();
+            if *current == stencil_test {
+                return;
+            }
+            *current = stencil_test;
+        }
+        unsafe {
+            if let StencilTest::Enabled {
+                function,
+                reference,
+                read_mask,
+                write_mask,
+                fail,
+                depth_fail,
+                pass,
+            } = stencil_test
+            {
+                self.enable(crate::context::STENCIL_TEST);
+                self.stencil_func(
+                    Self::compare_const_from_test(function),
+                    reference as i32,
+                    read_mask as u32,
+                );
+                self.stencil_op(
+                    Self::stencil_const_from_operation(fail),
+                    Self::stencil_const_from_operation(depth_fail),
+                    Self::stencil_const_from_operation(pass),
+                );
+                self.stencil_mask(write_mask as u32);
+            } else {
+                self.disable(crate::context::STENCIL_TEST);
+                self.stencil_func(crate::context::ALWAYS, 0, 0xFF);
+                self.stencil_op(
+                    crate::context::KEEP,
+                    crate::context::KEEP,
+                    crate::context::KEEP,
+                );
+                self.stencil_mask(0xFF);
+            }
+        }
+    }
+
+    fn compare_const_from_test(test: DepthTest) -> u32 {
+        match test {
+            DepthTest::Never => crate::context::NEVER,
+            DepthTest::Less => crate::context::LESS,
+            DepthTest::Equal => crate::context::EQUAL,
+            DepthTest::LessOrEqual => crate::context::LEQUAL,
+            DepthTest::Greater => crate::context::GREATER,
+            DepthTest::NotEqual => crate::context::NOTEQUAL,
+            DepthTest::GreaterOrEqual => crate::context::GEQUAL,
+            DepthTest::Always => crate::context::ALWAYS,
+        }
+    }
+
+    fn stencil_const_from_operation(operation: StencilOperation) -> u32 {
+        match operation {
+            StencilOperation::Keep => crate::context::KEEP,
+            StencilOperation::Zero => crate::context::ZERO,
+            StencilOperation::Replace => crate::context::REPLACE,
+            StencilOperation::Increment => crate::context::INCR,
+            StencilOperation::IncrementWrap => crate::context::INCR_WRAP,
+            StencilOperation::Decrement => crate::context::DECR,
+            StencilOperation::DecrementWrap => crate::context::DECR_WRAP,
+            StencilOperation::Invert => crate::context::INVERT,
+        }
+    }
+
     fn blend_const_from_multiplier(multiplier: BlendMultiplierType) -> u32 {
         match multiplier {
             BlendMultiplierType::Zero => crate::context::ZERO,
@@ -248,6 +533,45 @@ impl Context {
         self.set_blend(render_states.blend);
     }
 
+    ///
+    /// Reads the pixels inside the rectangle with the given lower left corner and size from the framebuffer which is currently bound as the read framebuffer
+    /// and returns the raw bytes in the given format. The rows are ordered from the bottom to the top, as given by OpenGL.
+    /// This is a low-level alternative to for example [RenderTarget::read_color], which makes it possible to read from a framebuffer bound by other code,
+    /// for example using `bind_framebuffer(context::READ_FRAMEBUFFER, ..)` from the [context](crate::context) module, or from the screen if no framebuffer is bound.
+    ///
+    /// Note that this method is not named `read_pixels`, since that would hide the `read_pixels` method of the low-level context.
+    ///
+    /// # Panic
+    /// Will panic if the format is [ReadPixelFormat::Depth] on web, where reading depth values is not supported.
+    ///
+    pub fn read_framebuffer_pixels(
+        &self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        format: ReadPixelFormat,
+    ) -> Vec<u8> {
+        if format == ReadPixelFormat::Depth && cfg!(target_arch = "wasm32") {
+            panic!("reading depth values is not supported on web");
+        }
+        let (gl_format, gl_data_type) = format.gl_format_and_data_type();
+        let mut bytes =
+            vec![0u8; width as usize * height as usize * format.bytes_per_pixel() as usize];
+        unsafe {
+            self.context.read_pixels(
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                gl_format,
+                gl_data_type,
+                crate::context::PixelPackData::Slice(&mut bytes),
+            );
+        }
+        bytes
+    }
+
     ///
     /// Returns an error if an GPU-side error has happened while rendering which can be used to check for errors while developing.
     /// Can also be used in production to handle unexpected rendering errors, but do not call it too often to avoid performance problems.
@@ -316,6 +640,46 @@ impl Context {
     }
 }
 
+///
+/// The format of the pixels returned by [Context::read_framebuffer_pixels].
+/// All formats are supported on all platforms, given that the format matches the format of the framebuffer, except [ReadPixelFormat::Depth] which is not supported on web.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadPixelFormat {
+    /// Four 8 bit unsigned normalized channels, used for framebuffers with a normalized fixed-point format, for example the screen.
+    RgbaU8,
+    /// Four 32 bit floating point channels, used for framebuffers with a floating point format.
+    RgbaF32,
+    /// Four 32 bit unsigned integer channels, used for framebuffers with an unsigned integer format.
+    RgbaU32,
+    /// Four 32 bit signed integer channels, used for framebuffers with a signed integer format.
+    RgbaI32,
+    /// One 32 bit floating point depth value.
+    Depth,
+}
+
+impl ReadPixelFormat {
+    ///
+    /// Returns the number of bytes of a single pixel in this format.
+    ///
+    pub fn bytes_per_pixel(&self) -> u32 {
+        match self {
+            Self::RgbaU8 | Self::Depth => 4,
+            Self::RgbaF32 | Self::RgbaU32 | Self::RgbaI32 => 16,
+        }
+    }
+
+    fn gl_format_and_data_type(&self) -> (u32, u32) {
+        match self {
+            Self::RgbaU8 => (crate::context::RGBA, crate::context::UNSIGNED_BYTE),
+            Self::RgbaF32 => (crate::context::RGBA, crate::context::FLOAT),
+            Self::RgbaU32 => (crate::context::RGBA_INTEGER, crate::context::UNSIGNED_INT),
+            Self::RgbaI32 => (crate::context::RGBA_INTEGER, crate::context::INT),
+            Self::Depth => (crate::context::DEPTH_COMPONENT, crate::context::FLOAT),
+        }
+    }
+}
+
 impl std::fmt::Debug for Context {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let mut d = f.debug_struct("Context");
@@ -330,3 +694,77 @@ impl std::ops::Deref for Context {
         &self.context
     }
 }
+
+///
+/// Renames the main function of the vertex shader and adds a new main function which also passes the instance index on to the fragment shader.
+///
+fn object_id_vertex_shader_source(source: &str) -> String {
+    format!(
+        "{}
+        flat out uint objectIdInstance;
+        void main()
+        {{
+            object_id_vertex_main();
+            objectIdInstance = uint(gl_InstanceID);
+        }}",
+        source.replacen("void main()", "void object_id_vertex_main()", 1)
+    )
+}
+
+///
+/// Renames the main function of the fragment shader and adds a new main function which also writes the object id and the instance index,
+/// packed into the high bits, into the second color attachment.
+///
+fn object_id_fragment_shader_source(source: &str) -> String {
+    format!(
+        "{}
+        flat in uint objectIdInstance;
+        uniform uint objectId;
+        uniform uint objectIdInstanceBits;
+        layout (location = 1) out uint outObjectId;
+        void main()
+        {{
+            object_id_fragment_main();
+            if (objectIdInstanceBits == 0u) {{
+                outObjectId = objectId;
+            }} else {{
+                uint objectBits = 32u - objectIdInstanceBits;
+                outObjectId = (objectId & ((1u << objectBits) - 1u)) | (objectIdInstance << objectBits);
+            }}
+        }}",
+        source.replacen("void main()", "void object_id_fragment_main()", 1)
+    )
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_framebuffer_pixels() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let texture = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            4,
+            2,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        unsafe {
+            let framebuffer = context.create_framebuffer().unwrap();
+            context.bind_framebuffer(crate::context::FRAMEBUFFER, Some(framebuffer));
+            texture.bind_as_color_target(0, 0);
+            context.clear_color(1.0, 0.0, 1.0, 1.0);
+            context.clear(crate::context::COLOR_BUFFER_BIT);
+
+            let bytes = context.read_framebuffer_pixels(1, 0, 2, 2, ReadPixelFormat::RgbaU8);
+            assert_eq!(bytes, [255, 0, 255, 255].repeat(4));
+
+            context.bind_framebuffer(crate::context::FRAMEBUFFER, None);
+            context.delete_framebuffer(framebuffer);
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use crate::core::*;
+
+///
+/// A plane in 3D space defined by the equation `normal.dot(point) + d = 0`.
+/// The normal is expected to have unit length, which is ensured if the plane is constructed using [Plane::from_point_normal] or [Plane::from_three_points].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Plane {
+    /// The unit normal of the plane.
+    pub normal: Vec3,
+    /// The negative distance from the origin to the plane along the normal.
+    pub d: f32,
+}
+
+///
+/// The side of a [Plane] that a shape is on, see for example [Plane::classify_sphere] and [Plane::classify_aabb].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PlaneSide {
+    /// The shape is entirely on the side of the plane that the normal points towards.
+    Front,
+    /// The shape is entirely on the side of the plane that the normal points away from.
+    Back,
+    /// The shape intersects the plane.
+    Intersecting,
+}
+
+impl Plane {
+    ///
+    /// Constructs a plane that goes through the given point and has the given normal.
+    /// The normal does not need to have unit length.
+    ///
+    pub fn from_point_normal(point: Vec3, normal: Vec3) -> Self {
+        let normal = normal.normalize();
+        Self {
+            normal,
+            d: -normal.dot(point),
+        }
+    }
+
+    ///
+    /// Constructs a plane that goes through the three given points.
+    /// The normal points towards the side from which the points are in counter clockwise order.
+    ///
+    /// # Panic
+    /// Will panic if the points are on a line.
+    ///
+    pub fn from_three_points(a: Vec3, b: Vec3, c: Vec3) -> Self {
+        let normal = (b - a).cross(c - a);
+        if normal.magnitude2() < f32::EPSILON * f32::EPSILON {
+            panic!("cannot construct a plane from three points on a line");
+        }
+        Self::from_point_normal(a, normal)
+    }
+
+    ///
+    /// Returns the plane with the opposite normal, ie. the same plane seen from the other side.
+    ///
+    pub fn flipped(&self) -> Self {
+        Self {
+            normal: -self.normal,
+            d: -self.d,
+        }
+    }
+
+    ///
+    /// Returns the plane as a vector with the normal in the first three components and `d` in the last,
+    /// so the dot product with a point in homogeneous coordinates is the signed distance.
+    ///
+    pub fn to_vec4(&self) -> Vec4 {
+        self.normal.extend(self.d)
+    }
+
+    ///
+    /// Returns the signed distance from the plane to the given point.
+    /// The distance is positive if the point is on the side of the plane that the normal points towards and negative otherwise.
+    ///
+    pub fn signed_distance(&self, point: Vec3) -> f32 {
+        self.normal.dot(point) + self.d
+    }
+
+    ///
+    /// Returns the point on the plane that is closest to the given point.
+    ///
+    pub fn project_point(&self, point: Vec3) -> Vec3 {
+        point - self.signed_distance(point) * self.normal
+    }
+
+    ///
+    /// Returns the distance along the ray with the given origin and direction to the point where it intersects the plane,
+    /// measured in units of the length of the direction, or `None` if the ray is parallel to the plane or points away from it.
+    /// The intersection point is `origin + distance * direction`.
+    ///
+    pub fn intersect_ray(&self, origin: Vec3, direction: Vec3) -> Option<f32> {
+        let denominator = self.normal.dot(direction);
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+        let distance = -self.signed_distance(origin) / denominator;
+        if distance >= 0.0 {
+            Some(distance)
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Returns this plane transformed by the given transformation, ie. the plane containing the transformed points of this plane.
+    /// The normal is transformed by the inverse transpose of the transformation, so it stays orthogonal to the plane also for non-uniform scaling.
+    ///
+    /// # Panic
+    /// Will panic if the transformation is not invertible.
+    ///
+    pub fn transformed(&self, transformation: &Mat4) -> Self {
+        let inverse = transformation
+            .invert()
+            .expect("a plane can only be transformed by an invertible transformation");
+        let plane = inverse.transpose() * self.to_vec4();
+        let length = plane.truncate().magnitude();
+        Self {
+            normal: plane.truncate() / length,
+            d: plane.w / length,
+        }
+    }
+
+    ///
+    /// Returns which side of the plane the sphere with the given center and radius is on.
+    ///
+    pub fn classify_sphere(&self, center: Vec3, radius: f32) -> PlaneSide {
+        let distance = self.signed_distance(center);
+        if distance > radius {
+            PlaneSide::Front
+        } else if distance < -radius {
+            PlaneSide::Back
+        } else {
+            PlaneSide::Intersecting
+        }
+    }
+
+    ///
+    /// Returns which side of the plane the given axis aligned bounding box is on.
+    /// An empty bounding box is classified as being in front of the plane and an infinite bounding box as intersecting the plane.
+    ///
+    pub fn classify_aabb(&self, aabb: &AxisAlignedBoundingBox) -> PlaneSide {
+        if aabb.is_empty() {
+            return PlaneSide::Front;
+        }
+        if aabb.is_infinite() {
+            return PlaneSide::Intersecting;
+        }
+        let (min, max) = (aabb.min(), aabb.max());
+        let center = 0.5 * (min + max);
+        let extent = 0.5 * (max - min);
+        // The radius of the box projected onto the normal
+        let radius = extent.x * self.normal.x.abs()
+            + extent.y * self.normal.y.abs()
+            + extent.z * self.normal.z.abs();
+        self.classify_sphere(center, radius)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!((a - b).magnitude() < 1.0e-5, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_plane_construction_and_distance() {
+        let plane = Plane::from_three_points(
+            vec3(0.0, 2.0, 0.0),
+            vec3(0.0, 2.0, 1.0),
+            vec3(1.0, 2.0, 0.0),
+        );
+        assert_close(plane.normal, vec3(0.0, 1.0, 0.0));
+        assert_eq!(plane.d, -2.0);
+        assert_eq!(
+            plane,
+            Plane::from_point_normal(vec3(5.0, 2.0, 3.0), vec3(0.0, 3.0, 0.0))
+        );
+        assert_eq!(plane.signed_distance(vec3(1.0, 5.0, -1.0)), 3.0);
+        assert_eq!(plane.flipped().signed_distance(vec3(1.0, 5.0, -1.0)), -3.0);
+        assert_close(
+            plane.project_point(vec3(1.0, 5.0, -1.0)),
+            vec3(1.0, 2.0, -1.0),
+        );
+        assert_eq!(plane.to_vec4().dot(vec4(1.0, 5.0, -1.0, 1.0)), 3.0);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_plane_from_points_on_a_line() {
+        Plane::from_three_points(
+            vec3(0.0, 0.0, 0.0),
+            vec3(1.0, 1.0, 1.0),
+            vec3(2.0, 2.0, 2.0),
+        );
+    }
+
+    #[test]
+    fn test_plane_ray_intersection() {
+        let plane = Plane::from_point_normal(vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0));
+        let origin = vec3(1.0, 4.0, 2.0);
+        let direction = vec3(1.0, -2.0, 0.0);
+        let distance = plane.intersect_ray(origin, direction).unwrap();
+        assert_eq!(distance, 2.0);
+        assert_close(origin + distance * direction, vec3(3.0, 0.0, 2.0));
+        // Also hits the plane from behind
+        assert_eq!(plane.intersect_ray(-origin, -direction), Some(2.0));
+        assert_eq!(plane.intersect_ray(origin, -direction), None);
+        assert_eq!(plane.intersect_ray(origin, vec3(1.0, 0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_plane_transformed() {
+        // A plane with 45 degree slope through the point (1, 0, 0)
+        let plane = Plane::from_point_normal(vec3(1.0, 0.0, 0.0), vec3(1.0, 1.0, 0.0));
+        let transformation = Mat4::from_translation(vec3(0.0, 0.0, 3.0))
+            * Mat4::from_nonuniform_scale(2.0, 1.0, 1.0);
+        let transformed = plane.transformed(&transformation);
+        assert!((transformed.normal.magnitude() - 1.0).abs() < 1.0e-5);
+        // Points on the plane are still on the transformed plane after they are transformed
+        for point in [
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            vec3(3.0, -2.0, 5.0),
+        ] {
+            assert!(plane.signed_distance(point).abs() < 1.0e-5);
+            let transformed_point = (transformation * point.extend(1.0)).truncate();
+            assert!(transformed.signed_distance(transformed_point).abs() < 1.0e-5);
+        }
+        // Transforming the normal directly with the transformation would give a normal along (2, 1, 0) which is not orthogonal to the plane
+        assert_close(transformed.normal, vec3(1.0, 2.0, 0.0).normalize());
+        // A point in front of the plane stays in front
+        assert!(transformed.signed_distance(vec3(10.0, 10.0, 0.0)) > 0.0);
+    }
+
+    #[test]
+    fn test_plane_classification() {
+        let plane = Plane::from_point_normal(vec3(0.0, 1.0, 0.0), vec3(0.0, 1.0, 0.0));
+        assert_eq!(
+            plane.classify_sphere(vec3(0.0, 3.0, 0.0), 1.0),
+            PlaneSide::Front
+        );
+        assert_eq!(
+            plane.classify_sphere(vec3(0.0, -3.0, 0.0), 1.0),
+            PlaneSide::Back
+        );
+        assert_eq!(
+            plane.classify_sphere(vec3(0.0, 1.5, 0.0), 1.0),
+            PlaneSide::Intersecting
+        );
+
+        let aabb = |min: Vec3, max: Vec3| AxisAlignedBoundingBox::new_with_positions(&[min, max]);
+        assert_eq!(
+            plane.classify_aabb(&aabb(vec3(-1.0, 2.0, -1.0), vec3(1.0, 3.0, 1.0))),
+            PlaneSide::Front
+        );
+        assert_eq!(
+            plane.classify_aabb(&aabb(vec3(-1.0, -2.0, -1.0), vec3(1.0, 0.0, 1.0))),
+            PlaneSide::Back
+        );
+        assert_eq!(
+            plane.classify_aabb(&aabb(vec3(-1.0, 0.0, -1.0), vec3(1.0, 2.0, 1.0))),
+            PlaneSide::Intersecting
+        );
+        // The corner of the box is closer to a tilted plane than the sides
+        let tilted = Plane::from_point_normal(vec3(0.0, 0.0, 0.0), vec3(1.0, 1.0, 0.0));
+        assert_eq!(
+            tilted.classify_aabb(&aabb(vec3(0.5, 0.5, 0.0), vec3(2.0, 2.0, 1.0))),
+            PlaneSide::Front
+        );
+        assert_eq!(
+            tilted.classify_aabb(&aabb(vec3(-0.4, 0.5, 0.0), vec3(2.0, 2.0, 1.0))),
+            PlaneSide::Front
+        );
+        assert_eq!(
+            tilted.classify_aabb(&aabb(vec3(-0.6, -0.6, 0.0), vec3(2.0, 2.0, 1.0))),
+            PlaneSide::Intersecting
+        );
+        assert_eq!(
+            plane.classify_aabb(&AxisAlignedBoundingBox::INFINITE),
+            PlaneSide::Intersecting
+        );
+    }
+}
@@ -1,5 +1,7 @@
 use crate::core::*;
 use std::collections::HashMap;
+#[cfg(debug_assertions)]
+use std::collections::HashSet;
 use std::sync::RwLock;
 
 ///
@@ -8,6 +10,9 @@ use std::sync::RwLock;
 /// and transferring uniform data to both shader stages (see the use_uniform and use_texture functionality)
 /// and execute the shader program (see the draw functionality).
 ///
+/// In debug builds, the program checks that a buffer is given for each attribute used by the shaders before each draw call
+/// and logs a warning the first time it is drawn if any of the uniforms used by the shaders have not been set.
+///
 pub struct Program {
     context: Context,
     id: crate::context::Program,
@@ -15,6 +20,12 @@ pub struct Program {
     textures: RwLock<HashMap<String, u32>>,
     uniforms: HashMap<String, crate::context::UniformLocation>,
     uniform_blocks: RwLock<HashMap<String, (u32, u32)>>,
+    #[cfg(debug_assertions)]
+    used_uniforms: RwLock<HashSet<String>>,
+    #[cfg(debug_assertions)]
+    used_attributes: RwLock<HashSet<String>>,
+    #[cfg(debug_assertions)]
+    unset_uniforms_checked: std::sync::atomic::AtomicBool,
 }
 
 impl Program {
@@ -26,59 +37,64 @@ impl Program {
         vertex_shader_source: &str,
         fragment_shader_source: &str,
     ) -> Result<Self, CoreError> {
-        unsafe {
-            let vert_shader = context
-                .create_shader(crate::context::VERTEX_SHADER)
-                .expect("Failed creating vertex shader");
-            let frag_shader = context
-                .create_shader(crate::context::FRAGMENT_SHADER)
-                .expect("Failed creating fragment shader");
-
-            let header: &str = if context.version().is_embedded {
-                "#version 300 es
-                    #ifdef GL_FRAGMENT_PRECISION_HIGH
-                        precision highp float;
-                        precision highp int;
-                        precision highp sampler2DArray;
-                        precision highp sampler3D;
-                    #else
-                        precision mediump float;
-                        precision mediump int;
-                        precision mediump sampler2DArray;
-                        precision mediump sampler3D;
-                    #endif\n"
-            } else {
-                "#version 330 core\n"
-            };
-            let vertex_shader_source = format!("{}{}", header, vertex_shader_source);
-            let fragment_shader_source = format!("{}{}", header, fragment_shader_source);
-
-            context.shader_source(vert_shader, &vertex_shader_source);
-            context.shader_source(frag_shader, &fragment_shader_source);
-            context.compile_shader(vert_shader);
-            context.compile_shader(frag_shader);
+        let header = shader_header(context);
+        Self::from_shader_sources(
+            context,
+            &[
+                (
+                    crate::context::VERTEX_SHADER,
+                    "vertex",
+                    format!("{}{}", header, vertex_shader_source),
+                ),
+                (
+                    crate::context::FRAGMENT_SHADER,
+                    "fragment",
+                    format!("{}{}", header, fragment_shader_source),
+                ),
+            ],
+            &[],
+        )
+    }
 
+    ///
+    /// Compiles and links a shader program consisting of the given shaders, each given as the shader type, the name used in error messages and the full source including the version header.
+    ///
+    pub(in crate::core) fn from_shader_sources(
+        context: &Context,
+        shaders: &[(u32, &str, String)],
+        transform_feedback_varyings: &[&str],
+    ) -> Result<Self, CoreError> {
+        unsafe {
             let id = context.create_program().expect("Failed creating program");
-            context.attach_shader(id, vert_shader);
-            context.attach_shader(id, frag_shader);
+            let mut shader_ids = Vec::new();
+            for (shader_type, name, source) in shaders.iter() {
+                let shader = context
+                    .create_shader(*shader_type)
+                    .unwrap_or_else(|_| panic!("Failed creating {} shader", name));
+                context.shader_source(shader, source);
+                context.compile_shader(shader);
+                context.attach_shader(id, shader);
+                shader_ids.push(shader);
+            }
+            if !transform_feedback_varyings.is_empty() {
+                context.transform_feedback_varyings(
+                    id,
+                    transform_feedback_varyings,
+                    crate::context::SEPARATE_ATTRIBS,
+                );
+            }
             context.link_program(id);
 
             if !context.get_program_link_status(id) {
-                let log = context.get_shader_info_log(vert_shader);
-                if !log.is_empty() {
-                    Err(CoreError::ShaderCompilation(
-                        "vertex".to_string(),
-                        log,
-                        vertex_shader_source,
-                    ))?;
-                }
-                let log = context.get_shader_info_log(frag_shader);
-                if !log.is_empty() {
-                    Err(CoreError::ShaderCompilation(
-                        "fragment".to_string(),
-                        log,
-                        fragment_shader_source,
-                    ))?;
+                for (shader, (_, name, source)) in shader_ids.iter().zip(shaders.iter()) {
+                    let log = context.get_shader_info_log(*shader);
+                    if !log.is_empty() {
+                        Err(CoreError::ShaderCompilation(
+                            name.to_string(),
+                            log,
+                            source.clone(),
+                        ))?;
+                    }
                 }
                 let log = context.get_program_info_log(id);
                 if !log.is_empty() {
@@ -87,10 +103,10 @@ impl Program {
                 unreachable!();
             }
 
-            context.detach_shader(id, vert_shader);
-            context.detach_shader(id, frag_shader);
-            context.delete_shader(vert_shader);
-            context.delete_shader(frag_shader);
+            for shader in shader_ids {
+                context.detach_shader(id, shader);
+                context.delete_shader(shader);
+            }
 
             // Init vertex attributes
             let num_attribs = context.get_active_attributes(id);
@@ -135,10 +151,65 @@ impl Program {
                 uniforms,
                 uniform_blocks: RwLock::new(HashMap::new()),
                 textures: RwLock::new(HashMap::new()),
+                #[cfg(debug_assertions)]
+                used_uniforms: RwLock::new(HashSet::new()),
+                #[cfg(debug_assertions)]
+                used_attributes: RwLock::new(HashSet::new()),
+                #[cfg(debug_assertions)]
+                unset_uniforms_checked: std::sync::atomic::AtomicBool::new(false),
             })
         }
     }
 
+    ///
+    /// Creates a new shader program from the given vertex and fragment glsl shader source with the given preprocessor defines.
+    /// A `#define KEY VALUE` line is inserted at the top of both shaders for each `(KEY, VALUE)` pair,
+    /// which makes it possible to compile different permutations of the same shader source, for example `("USE_SHADOW_MAP", "1")`.
+    /// Use an empty string as value if the define should not have a value.
+    ///
+    pub fn with_defines(
+        context: &Context,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        defines: &[(&str, &str)],
+    ) -> Result<Self, CoreError> {
+        let key = ProgramKey::new(vertex_shader_source, fragment_shader_source, defines);
+        Self::from_key(context, &key)
+    }
+
+    ///
+    /// Creates a new shader program from the given vertex and fragment glsl shader source, where each `#include "name"` line is replaced by the source of the include with that name.
+    /// The includes are given as `(name, source)` pairs and can themselves include other includes,
+    /// which makes it possible to share for example lighting or noise functions between shaders.
+    /// Each include is only inserted the first time it is included in a shader, so an include can safely be included by several other includes.
+    ///
+    /// Returns an error if an include directive is invalid or refers to a name which is not among the given includes.
+    ///
+    pub fn new_with_includes(
+        context: &Context,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        includes: &[(&str, &str)],
+    ) -> Result<Self, CoreError> {
+        Self::from_source(
+            context,
+            &resolve_includes(vertex_shader_source, includes)?,
+            &resolve_includes(fragment_shader_source, includes)?,
+        )
+    }
+
+    pub(in crate::core) fn from_key(
+        context: &Context,
+        key: &ProgramKey,
+    ) -> Result<Self, CoreError> {
+        let defines = key.defines_source();
+        Self::from_source(
+            context,
+            &format!("{}{}", defines, key.vertex_shader_source),
+            &format!("{}{}", defines, key.fragment_shader_source),
+        )
+    }
+
     ///
     /// Send the given uniform data to this shader program and associate it with the given named variable.
     /// The glsl shader variable must be of type `uniform int` if the data is an integer, `uniform vec2` if it is of type [Vec2] etc.
@@ -180,12 +251,15 @@ impl Program {
 
     fn get_uniform_location(&self, name: &str) -> &crate::context::UniformLocation {
         self.use_program();
-        self.uniforms.get(name).unwrap_or_else(|| {
-            panic!(
-                "the uniform {} is sent to the shader but not defined or never used",
-                name
-            )
-        })
+        let location = self
+            .uniforms
+            .get(name)
+            .unwrap_or_else(|| panic!("{}", unknown_uniform_message(name, self.uniforms.keys())));
+        #[cfg(debug_assertions)]
+        if !self.used_uniforms.read().unwrap().contains(name) {
+            self.used_uniforms.write().unwrap().insert(name.to_owned());
+        }
+        location
     }
 
     ///
@@ -287,6 +361,7 @@ impl Program {
         };
         let index = *self.textures.read().unwrap().get(name).unwrap();
         self.use_uniform(name, index as i32);
+        self.context.use_texture_unit(index);
         unsafe {
             self.context
                 .active_texture(crate::context::TEXTURE0 + index);
@@ -328,6 +403,11 @@ impl Program {
     /// In the latter case the variable is removed by the shader compiler.
     ///
     pub fn use_vertex_attribute(&self, name: &str, buffer: &VertexBuffer) {
+        #[cfg(debug_assertions)]
+        self.used_attributes
+            .write()
+            .unwrap()
+            .insert(name.to_owned());
         if buffer.count() > 0 {
             buffer.bind();
             let loc = self.location(name);
@@ -376,6 +456,11 @@ impl Program {
     /// In the latter case the variable is removed by the shader compiler.
     ///
     pub fn use_instance_attribute(&self, name: &str, buffer: &InstanceBuffer) {
+        #[cfg(debug_assertions)]
+        self.used_attributes
+            .write()
+            .unwrap()
+            .insert(name.to_owned());
         if buffer.count() > 0 {
             buffer.bind();
             let loc = self.location(name);
@@ -423,10 +508,13 @@ impl Program {
     pub fn draw_arrays(&self, render_states: RenderStates, viewport: Viewport, count: u32) {
         self.context.set_viewport(viewport);
         self.context.set_render_states(render_states);
+        #[cfg(debug_assertions)]
+        self.validate_draw();
         self.use_program();
         unsafe {
             self.context
                 .draw_arrays(crate::context::TRIANGLES, 0, count as i32);
+            self.context.count_draw_call(count as u64 / 3);
             for location in self.attributes.values() {
                 self.context.disable_vertex_attrib_array(*location);
             }
@@ -453,6 +541,8 @@ impl Program {
     ) {
         self.context.set_viewport(viewport);
         self.context.set_render_states(render_states);
+        #[cfg(debug_assertions)]
+        self.validate_draw();
         self.use_program();
         unsafe {
             self.context.draw_arrays_instanced(
@@ -461,6 +551,8 @@ impl Program {
                 count as i32,
                 instance_count as i32,
             );
+            self.context
+                .count_draw_call(count as u64 / 3 * instance_count as u64);
             self.context
                 .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
             for location in self.attributes.values() {
@@ -511,6 +603,8 @@ impl Program {
     ) {
         self.context.set_viewport(viewport);
         self.context.set_render_states(render_states);
+        #[cfg(debug_assertions)]
+        self.validate_draw();
         self.use_program();
         element_buffer.bind();
         unsafe {
@@ -518,8 +612,9 @@ impl Program {
                 crate::context::TRIANGLES,
                 count as i32,
                 element_buffer.data_type(),
-                first as i32,
+                (first * element_buffer.data_size()) as i32,
             );
+            self.context.count_draw_call(count as u64 / 3);
             self.context
                 .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
 
@@ -572,6 +667,8 @@ impl Program {
     ) {
         self.context.set_viewport(viewport);
         self.context.set_render_states(render_states);
+        #[cfg(debug_assertions)]
+        self.validate_draw();
         self.use_program();
         element_buffer.bind();
         unsafe {
@@ -579,9 +676,104 @@ impl Program {
                 crate::context::TRIANGLES,
                 count as i32,
                 element_buffer.data_type(),
-                first as i32,
+                (first * element_buffer.data_size()) as i32,
                 instance_count as i32,
             );
+            self.context
+                .count_draw_call(count as u64 / 3 * instance_count as u64);
+            self.context
+                .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
+            for location in self.attributes.values() {
+                self.context.disable_vertex_attrib_array(*location);
+            }
+            self.context.bind_vertex_array(None);
+        }
+        self.unuse_program();
+
+        #[cfg(debug_assertions)]
+        self.context
+            .error_check()
+            .expect("Unexpected rendering error occured")
+    }
+
+    ///
+    /// Same as [Program::draw_arrays_instanced] except that the number of vertices, number of instances etc. are read from the [DrawIndirectCommand]
+    /// at the given index in the given [DrawIndirectBuffer], which can for example be written by a [ComputeProgram].
+    ///
+    /// # Panic
+    /// Will panic if the index is not smaller than the capacity of the buffer.
+    /// Indirect draw calls are not supported on all platforms, see [Context::supports_draw_indirect].
+    ///
+    pub fn draw_arrays_indirect(
+        &self,
+        render_states: RenderStates,
+        viewport: Viewport,
+        indirect_buffer: &DrawIndirectBuffer,
+        index: usize,
+    ) {
+        Self::check_indirect_index(indirect_buffer, index);
+        self.context.set_viewport(viewport);
+        self.context.set_render_states(render_states);
+        #[cfg(debug_assertions)]
+        self.validate_draw();
+        self.use_program();
+        indirect_buffer.bind();
+        unsafe {
+            self.context.draw_arrays_indirect_offset(
+                crate::context::TRIANGLES,
+                (index * std::mem::size_of::<DrawIndirectCommand>()) as i32,
+            );
+            // The number of triangles is unknown on the CPU
+            self.context.count_draw_call(0);
+            self.context
+                .bind_buffer(crate::context::DRAW_INDIRECT_BUFFER, None);
+            for location in self.attributes.values() {
+                self.context.disable_vertex_attrib_array(*location);
+            }
+            self.context.bind_vertex_array(None);
+        }
+        self.unuse_program();
+
+        #[cfg(debug_assertions)]
+        self.context
+            .error_check()
+            .expect("Unexpected rendering error occured")
+    }
+
+    ///
+    /// Same as [Program::draw_subset_of_elements_instanced] except that the number of indices, number of instances etc. are read from the [DrawElementsIndirectCommand]
+    /// at the given index in the given [DrawIndirectBuffer], which can for example be written by a [ComputeProgram].
+    ///
+    /// # Panic
+    /// Will panic if the index is not smaller than the capacity of the buffer.
+    /// Indirect draw calls are not supported on all platforms, see [Context::supports_draw_indirect].
+    ///
+    pub fn draw_elements_indirect(
+        &self,
+        render_states: RenderStates,
+        viewport: Viewport,
+        element_buffer: &ElementBuffer,
+        indirect_buffer: &DrawIndirectBuffer,
+        index: usize,
+    ) {
+        Self::check_indirect_index(indirect_buffer, index);
+        self.context.set_viewport(viewport);
+        self.context.set_render_states(render_states);
+        #[cfg(debug_assertions)]
+        self.validate_draw();
+        self.use_program();
+        element_buffer.bind();
+        indirect_buffer.bind();
+        unsafe {
+            self.context.draw_elements_indirect_offset(
+                crate::context::TRIANGLES,
+                element_buffer.data_type(),
+                (index * std::mem::size_of::<DrawElementsIndirectCommand>()) as i32,
+            );
+            // The number of triangles is unknown on the CPU
+            self.context.count_draw_call(0);
+            self.context
+                .bind_buffer(crate::context::DRAW_INDIRECT_BUFFER, None);
             self.context
                 .bind_buffer(crate::context::ELEMENT_ARRAY_BUFFER, None);
             for location in self.attributes.values() {
@@ -597,6 +789,16 @@ impl Program {
             .expect("Unexpected rendering error occured")
     }
 
+    fn check_indirect_index(indirect_buffer: &DrawIndirectBuffer, index: usize) {
+        if index >= indirect_buffer.capacity() {
+            panic!(
+                "the command index {} is outside the expected range [0, {}]",
+                index,
+                indirect_buffer.capacity() as i64 - 1
+            );
+        }
+    }
+
     ///
     /// Returns true if this program uses the uniform with the given name.
     ///
@@ -615,12 +817,99 @@ impl Program {
         self.use_program();
         *self.attributes.get(name).unwrap_or_else(|| {
             panic!(
-                "the attribute {} is sent to the shader but not defined or never used",
-                name
+                "the attribute {} is sent to the shader but not defined or never used, the attributes used by the shader are: {}",
+                name,
+                sorted_names(self.attributes.keys())
             )
         })
     }
 
+    ///
+    /// Panics if a buffer has not been given for each of the attributes used by the shaders since the last draw call
+    /// and logs a warning the first time this program is drawn if any of the uniforms used by the shaders have not been set.
+    ///
+    #[cfg(debug_assertions)]
+    fn validate_draw(&self) {
+        let mut used_attributes = self.used_attributes.write().unwrap();
+        let missing = self
+            .attributes
+            .keys()
+            .filter(|name| !is_built_in(name) && !used_attributes.contains(*name))
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            panic!(
+                "{}",
+                missing_attributes_message(missing.into_iter(), used_attributes.iter())
+            );
+        }
+        // The attributes are disabled after each draw call, so they must be given again before the next
+        used_attributes.clear();
+
+        if !self
+            .unset_uniforms_checked
+            .swap(true, std::sync::atomic::Ordering::Relaxed)
+        {
+            let used_uniforms = self.used_uniforms.read().unwrap();
+            let unset = self
+                .uniforms
+                .keys()
+                .filter(|name| !is_built_in(name) && !used_uniforms.contains(*name))
+                .collect::<Vec<_>>();
+            if !unset.is_empty() {
+                log::warn!(
+                    "the uniforms {} are used by the shader but have not been set, so they have the default value zero",
+                    sorted_names(unset.into_iter())
+                );
+            }
+        }
+    }
+
+    pub(in crate::core) fn dispatch_compute(&self, x: u32, y: u32, z: u32) {
+        self.use_program();
+        unsafe {
+            self.context.dispatch_compute(x, y, z);
+            self.context.memory_barrier(
+                crate::context::SHADER_STORAGE_BARRIER_BIT
+                    | crate::context::BUFFER_UPDATE_BARRIER_BIT
+                    | crate::context::VERTEX_ATTRIB_ARRAY_BARRIER_BIT
+                    | crate::context::COMMAND_BARRIER_BIT,
+            );
+        }
+        self.unuse_program();
+    }
+
+    pub(in crate::core) fn begin_transform_feedback(&self, primitive_mode: u32) {
+        self.use_program();
+        unsafe {
+            self.context.enable(crate::context::RASTERIZER_DISCARD);
+            self.context.begin_transform_feedback(primitive_mode);
+        }
+    }
+
+    pub(in crate::core) fn draw_transform_feedback(&self, primitive_mode: u32, count: u32) {
+        unsafe {
+            self.context.bind_vertex_array(Some(self.context.vao));
+            self.context.draw_arrays(primitive_mode, 0, count as i32);
+        }
+    }
+
+    pub(in crate::core) fn end_transform_feedback(&self) {
+        unsafe {
+            self.context.end_transform_feedback();
+            self.context.disable(crate::context::RASTERIZER_DISCARD);
+            for location in self.attributes.values() {
+                self.context.disable_vertex_attrib_array(*location);
+            }
+            self.context.bind_vertex_array(None);
+        }
+        self.unuse_program();
+
+        #[cfg(debug_assertions)]
+        self.context
+            .error_check()
+            .expect("Unexpected rendering error occured")
+    }
+
     fn use_program(&self) {
         unsafe {
             self.context.use_program(Some(self.id));
@@ -634,6 +923,101 @@ impl Program {
     }
 }
 
+///
+/// Returns true for the variables defined by OpenGL, which are never set by the user.
+///
+#[cfg(debug_assertions)]
+fn is_built_in(name: &str) -> bool {
+    name.starts_with("gl_")
+}
+
+fn sorted_names<'a>(names: impl Iterator<Item = &'a String>) -> String {
+    let mut names = names.map(|n| n.as_str()).collect::<Vec<_>>();
+    names.sort_unstable();
+    names.join(", ")
+}
+
+///
+/// Returns the error message when setting a uniform which is not used by the shaders, including the most similar name if it is probably a typo.
+///
+fn unknown_uniform_message<'a>(
+    name: &str,
+    uniforms: impl Iterator<Item = &'a String> + Clone,
+) -> String {
+    let suggestion = uniforms
+        .clone()
+        .map(|u| (edit_distance(name, u), u))
+        .filter(|(distance, _)| *distance <= 2)
+        .min()
+        .map(|(_, u)| format!(", did you mean {}?", u))
+        .unwrap_or_default();
+    format!(
+        "the uniform {} is sent to the shader but not defined or never used{} The uniforms used by the shader are: {}",
+        name,
+        if suggestion.is_empty() { "." } else { &suggestion },
+        sorted_names(uniforms)
+    )
+}
+
+///
+/// Returns the error message when drawing without giving a buffer for each of the attributes used by the shaders.
+///
+#[cfg(debug_assertions)]
+fn missing_attributes_message<'a>(
+    missing: impl Iterator<Item = &'a String>,
+    given: impl Iterator<Item = &'a String>,
+) -> String {
+    format!(
+        "the attributes {} are used by the shader but no buffers were given for them, the attributes given are: {}",
+        sorted_names(missing),
+        sorted_names(given)
+    )
+}
+
+///
+/// The number of single character insertions, deletions and substitutions needed to change one string into the other.
+///
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, ca) in a.chars().enumerate() {
+        let mut previous = row[0];
+        row[0] = i + 1;
+        for j in 0..b.len() {
+            let current = row[j + 1];
+            row[j + 1] = if ca == b[j] {
+                previous
+            } else {
+                1 + previous.min(row[j]).min(current)
+            };
+            previous = current;
+        }
+    }
+    row[b.len()]
+}
+
+///
+/// The version and precision header which is added to all vertex and fragment shaders.
+///
+pub(in crate::core) fn shader_header(context: &Context) -> &'static str {
+    if context.version().is_embedded {
+        "#version 300 es
+            #ifdef GL_FRAGMENT_PRECISION_HIGH
+                precision highp float;
+                precision highp int;
+                precision highp sampler2DArray;
+                precision highp sampler3D;
+            #else
+                precision mediump float;
+                precision mediump int;
+                precision mediump sampler2DArray;
+                precision mediump sampler3D;
+            #endif\n"
+    } else {
+        "#version 330 core\n"
+    }
+}
+
 impl Drop for Program {
     fn drop(&mut self) {
         unsafe {
@@ -641,3 +1025,184 @@ impl Drop for Program {
         }
     }
 }
+
+///
+/// Replaces each `#include "name"` line in the source by the source of the include with that name, see [Program::new_with_includes].
+///
+fn resolve_includes(source: &str, includes: &[(&str, &str)]) -> Result<String, CoreError> {
+    fn resolve<'a>(
+        source: &str,
+        includes: &[(&'a str, &str)],
+        included: &mut Vec<&'a str>,
+        output: &mut String,
+    ) -> Result<(), CoreError> {
+        for line in source.lines() {
+            if let Some(directive) = line.trim_start().strip_prefix("#include") {
+                let name = directive
+                    .trim()
+                    .strip_prefix('"')
+                    .and_then(|d| d.strip_suffix('"'))
+                    .ok_or_else(|| CoreError::InvalidShaderInclude(line.trim().to_owned()))?;
+                let (name, include) = includes
+                    .iter()
+                    .find(|(n, _)| *n == name)
+                    .ok_or_else(|| CoreError::UnknownShaderInclude(name.to_owned()))?;
+                if !included.contains(name) {
+                    included.push(*name);
+                    resolve(include, includes, included, output)?;
+                }
+            } else {
+                output.push_str(line);
+                output.push('\n');
+            }
+        }
+        Ok(())
+    }
+    let mut output = String::with_capacity(source.len());
+    resolve(source, includes, &mut Vec::new(), &mut output)?;
+    Ok(output)
+}
+
+///
+/// Identifies a [Program] by its vertex and fragment shader source and the set of preprocessor defines it is compiled with (see [Program::with_defines]).
+/// Two keys are equal if the sources are equal and the sets of defines are equal, independent of the order in which the defines are given.
+/// Used as the key when caching programs, see [Context::program_with_defines].
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ProgramKey {
+    vertex_shader_source: String,
+    fragment_shader_source: String,
+    defines: Vec<(String, String)>,
+}
+
+impl ProgramKey {
+    ///
+    /// Creates a new key from the vertex and fragment shader source and the given set of defines.
+    /// If the same define is given more than once, the last value is used.
+    ///
+    pub fn new(
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        defines: &[(&str, &str)],
+    ) -> Self {
+        let mut sorted: Vec<(String, String)> = Vec::with_capacity(defines.len());
+        for (name, value) in defines {
+            if let Some(d) = sorted.iter_mut().find(|(n, _)| n == name) {
+                d.1 = value.to_string();
+            } else {
+                sorted.push((name.to_string(), value.to_string()));
+            }
+        }
+        sorted.sort_by(|a, b| a.0.cmp(&b.0));
+        Self {
+            vertex_shader_source: vertex_shader_source.to_owned(),
+            fragment_shader_source: fragment_shader_source.to_owned(),
+            defines: sorted,
+        }
+    }
+
+    ///
+    /// The defines of this key sorted by name.
+    ///
+    pub fn defines(&self) -> &[(String, String)] {
+        &self.defines
+    }
+
+    fn defines_source(&self) -> String {
+        self.defines
+            .iter()
+            .map(|(name, value)| format!("#define {} {}\n", name, value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resolve_includes() {
+        let includes = [
+            ("math", "float square(float x) { return x * x; }"),
+            (
+                "lighting",
+                "#include \"math\"\nfloat light(float x) { return square(x); }",
+            ),
+        ];
+        assert_eq!(
+            resolve_includes(
+                "#include \"lighting\"\n  #include \"math\"\nvoid main() {}",
+                &includes
+            )
+            .unwrap(),
+            "float square(float x) { return x * x; }\nfloat light(float x) { return square(x); }\nvoid main() {}\n"
+        );
+        assert!(matches!(
+            resolve_includes("#include \"noise\"", &includes),
+            Err(CoreError::UnknownShaderInclude(name)) if name == "noise"
+        ));
+        assert!(matches!(
+            resolve_includes("#include <math>", &includes),
+            Err(CoreError::InvalidShaderInclude(_))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_uniform_message() {
+        let uniforms = ["roughness", "albedo", "metallic"].map(String::from);
+        assert_eq!(
+            unknown_uniform_message("albdeo", uniforms.iter()),
+            "the uniform albdeo is sent to the shader but not defined or never used, did you mean albedo? The uniforms used by the shader are: albedo, metallic, roughness"
+        );
+        assert_eq!(
+            unknown_uniform_message("color", uniforms.iter()),
+            "the uniform color is sent to the shader but not defined or never used. The uniforms used by the shader are: albedo, metallic, roughness"
+        );
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn test_missing_attributes_message() {
+        let missing = ["uv_coordinates", "normal"].map(String::from);
+        let given = ["position".to_string()];
+        assert_eq!(
+            missing_attributes_message(missing.iter(), given.iter()),
+            "the attributes normal, uv_coordinates are used by the shader but no buffers were given for them, the attributes given are: position"
+        );
+    }
+
+    #[cfg(all(debug_assertions, feature = "headless", not(target_arch = "wasm32")))]
+    #[test]
+    #[should_panic(
+        expected = "the attributes normal are used by the shader but no buffers were given for them, the attributes given are: position"
+    )]
+    fn test_draw_with_missing_attribute() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let program = Program::from_source(
+            &context,
+            "in vec3 position;
+            in vec3 normal;
+            out vec3 nor;
+            void main() {
+                nor = normal;
+                gl_Position = vec4(position, 1.0);
+            }",
+            "in vec3 nor;
+            layout (location = 0) out vec4 color;
+            void main() {
+                color = vec4(nor, 1.0);
+            }",
+        )
+        .unwrap();
+        let positions = VertexBuffer::new_with_data(
+            &context,
+            &[
+                vec3(-1.0, -1.0, 0.0),
+                vec3(1.0, -1.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+            ],
+        );
+        program.use_vertex_attribute("position", &positions);
+        program.draw_arrays(RenderStates::default(), Viewport::new_at_origo(1, 1), 3);
+    }
+}
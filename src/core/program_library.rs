@@ -0,0 +1,155 @@
+use crate::core::*;
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+///
+/// Statistics for the [ProgramLibrary] which can be used for debugging, for example to check that programs are not recompiled each frame.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProgramCacheStat {
+    /// The number of times a requested program was already compiled and was returned from the cache.
+    pub hits: u64,
+    /// The number of times a requested program was not in the cache and therefore had to be compiled.
+    pub misses: u64,
+}
+
+#[derive(Default)]
+pub(in crate::core) struct ProgramStorage {
+    programs: HashMap<u64, Vec<(Box<dyn Any + Send + Sync>, Program)>>,
+    stat: ProgramCacheStat,
+}
+
+impl ProgramStorage {
+    pub fn len(&self) -> usize {
+        self.programs.values().map(|b| b.len()).sum()
+    }
+}
+
+///
+/// A library of compiled [Program]s stored in the [Context] which makes it possible to reuse programs across for example multiple instances of the same object.
+/// Each program is identified by a user defined key which can be any type that implements [Hash], [Eq], [Send] and [Sync],
+/// for example a string, an enum or a [ProgramKey].
+/// All built-in geometries, materials and effects use this library (see [Context::program]) so programs are shared between them.
+///
+/// Get access to the library using [Context::program_library].
+///
+pub struct ProgramLibrary<'a> {
+    context: &'a Context,
+}
+
+impl<'a> ProgramLibrary<'a> {
+    pub(in crate::core) fn new(context: &'a Context) -> Self {
+        Self { context }
+    }
+
+    ///
+    /// Calls the callback with the program associated with the given key.
+    /// If no program is associated with the key, a new [Program] is compiled from the given vertex and fragment shader source and stored in the library before calling the callback.
+    /// Note that the shader source is ignored if a program with the given key already exists, so make sure that the key uniquely identifies the shader source.
+    ///
+    pub fn get_or_create<K: Hash + Eq + Send + Sync + 'static>(
+        &self,
+        key: K,
+        vertex_shader_source: &str,
+        fragment_shader_source: &str,
+        callback: impl FnOnce(&Program),
+    ) -> Result<(), CoreError> {
+        self.get_or_create_with(
+            key,
+            || Program::from_source(self.context, vertex_shader_source, fragment_shader_source),
+            callback,
+        )
+    }
+
+    pub(in crate::core) fn get_or_create_with<K: Hash + Eq + Send + Sync + 'static>(
+        &self,
+        key: K,
+        create: impl FnOnce() -> Result<Program, CoreError>,
+        callback: impl FnOnce(&Program),
+    ) -> Result<(), CoreError> {
+        let hash = {
+            let mut hasher = DefaultHasher::new();
+            std::any::TypeId::of::<K>().hash(&mut hasher);
+            key.hash(&mut hasher);
+            hasher.finish()
+        };
+        let mut storage = self.context.programs.write().unwrap();
+        let existing = storage.programs.get(&hash).and_then(|bucket| {
+            bucket
+                .iter()
+                .position(|(k, _)| k.downcast_ref::<K>() == Some(&key))
+        });
+        if let Some(index) = existing {
+            storage.stat.hits += 1;
+            callback(&storage.programs.get(&hash).unwrap()[index].1);
+        } else {
+            storage.stat.misses += 1;
+            let program = create()?;
+            callback(&program);
+            storage
+                .programs
+                .entry(hash)
+                .or_default()
+                .push((Box::new(key), program));
+        }
+        Ok(())
+    }
+
+    ///
+    /// Returns whether or not a program is associated with the given key.
+    ///
+    pub fn contains<K: Hash + Eq + Send + Sync + 'static>(&self, key: &K) -> bool {
+        let mut hasher = DefaultHasher::new();
+        std::any::TypeId::of::<K>().hash(&mut hasher);
+        key.hash(&mut hasher);
+        self.context
+            .programs
+            .read()
+            .unwrap()
+            .programs
+            .get(&hasher.finish())
+            .map(|bucket| {
+                bucket
+                    .iter()
+                    .any(|(k, _)| k.downcast_ref::<K>() == Some(key))
+            })
+            .unwrap_or(false)
+    }
+
+    ///
+    /// The number of programs stored in the library.
+    ///
+    pub fn len(&self) -> usize {
+        self.context.programs.read().unwrap().len()
+    }
+
+    ///
+    /// Returns true if no programs are stored in the library.
+    ///
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    ///
+    /// Returns the number of cache hits and misses since the context was created or since the last call to [Self::reset_stat].
+    ///
+    pub fn stat(&self) -> ProgramCacheStat {
+        self.context.programs.read().unwrap().stat
+    }
+
+    ///
+    /// Resets the cache hit and miss counts.
+    ///
+    pub fn reset_stat(&self) {
+        self.context.programs.write().unwrap().stat = ProgramCacheStat::default();
+    }
+
+    ///
+    /// Removes all programs from the library, which means that they are compiled again the next time they are requested.
+    ///
+    pub fn clear(&self) {
+        self.context.programs.write().unwrap().programs.clear();
+    }
+}
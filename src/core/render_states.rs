@@ -79,6 +79,60 @@ impl Default for DepthTest {
     }
 }
 
+///
+/// Defines the stencil test used by the following render calls, see [Context::set_stencil_test](crate::core::Context::set_stencil_test).
+/// The stencil test compares a reference value with the value in the stencil buffer of the render target for each fragment,
+/// discards the fragment if the test fails and updates the stencil buffer depending on the result of the stencil and depth test.
+///
+/// **Note:** The render target must have a stencil buffer, otherwise the test always passes and nothing is written.
+///
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub enum StencilTest {
+    /// The stencil test is disabled and the stencil buffer is not changed.
+    #[default]
+    Disabled,
+    /// The stencil test is enabled.
+    Enabled {
+        /// The comparison between the reference value and the stencil value, for example [DepthTest::Equal] passes if the reference is equal to the stencil value.
+        function: DepthTest,
+        /// The reference value which is compared to the stencil value.
+        reference: u8,
+        /// The bits of the reference and stencil value which are compared.
+        read_mask: u8,
+        /// The bits of the stencil value which are written.
+        write_mask: u8,
+        /// How the stencil value is updated if the stencil test fails.
+        fail: StencilOperation,
+        /// How the stencil value is updated if the stencil test passes but the depth test fails.
+        depth_fail: StencilOperation,
+        /// How the stencil value is updated if both the stencil and the depth test passes.
+        pass: StencilOperation,
+    },
+}
+
+///
+/// Defines how a stencil value is updated, see [StencilTest].
+///
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum StencilOperation {
+    /// Keeps the current value.
+    Keep,
+    /// Sets the value to zero.
+    Zero,
+    /// Sets the value to the reference value.
+    Replace,
+    /// Increments the value and clamps it to the maximum value.
+    Increment,
+    /// Increments the value and wraps it to zero when exceeding the maximum value.
+    IncrementWrap,
+    /// Decrements the value and clamps it to zero.
+    Decrement,
+    /// Decrements the value and wraps it to the maximum value when decrementing zero.
+    DecrementWrap,
+    /// Inverts the bits of the value.
+    Invert,
+}
+
 ///
 /// Defines which channels (red, green, blue, alpha and depth) to write to in a render call.
 ///
@@ -190,6 +244,21 @@ impl Blend {
         alpha_equation: BlendEquationType::Add,
     };
 
+    ///
+    /// Transparency blending parameters which, given a straight (not premultiplied) alpha output from the render call,
+    /// produces colors premultiplied with alpha and the resulting coverage in the alpha channel.
+    /// Use this when rendering transparent objects to a transparent window, since the window compositor expects premultiplied colors
+    /// and uses the alpha channel to blend the window with the desktop behind it (see [WindowSettings::transparent](crate::WindowSettings::transparent)).
+    ///
+    pub const PREMULTIPLIED_TRANSPARENCY: Self = Self::Enabled {
+        source_rgb_multiplier: BlendMultiplierType::SrcAlpha,
+        source_alpha_multiplier: BlendMultiplierType::One,
+        destination_rgb_multiplier: BlendMultiplierType::OneMinusSrcAlpha,
+        destination_alpha_multiplier: BlendMultiplierType::OneMinusSrcAlpha,
+        rgb_equation: BlendEquationType::Add,
+        alpha_equation: BlendEquationType::Add,
+    };
+
     ///
     /// Adds the color of the render target with the output color of the render call.
     ///
@@ -0,0 +1,154 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+///
+/// Statistics about the render calls issued using a [Context](crate::core::Context) since it was created or since the last call to
+/// [Context::reset_render_stats](crate::core::Context::reset_render_stats), for example used for profiling the rendering of each frame.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RenderStats {
+    /// The number of draw calls, ie. calls to one of the draw methods on [Program](crate::core::Program).
+    pub draw_calls: u64,
+    /// The number of triangles drawn, including all instances of instanced draw calls.
+    pub triangles: u64,
+}
+
+///
+/// The counters behind the [RenderStats] of a context, which are atomic so counting a draw call does not take a lock.
+///
+#[derive(Default)]
+pub(in crate::core) struct RenderCounters {
+    draw_calls: AtomicU64,
+    triangles: AtomicU64,
+}
+
+impl RenderCounters {
+    pub(in crate::core) fn count_draw_call(&self, triangles: u64) {
+        self.draw_calls.fetch_add(1, Ordering::Relaxed);
+        self.triangles.fetch_add(triangles, Ordering::Relaxed);
+    }
+
+    pub(in crate::core) fn stats(&self) -> RenderStats {
+        RenderStats {
+            draw_calls: self.draw_calls.load(Ordering::Relaxed),
+            triangles: self.triangles.load(Ordering::Relaxed),
+        }
+    }
+
+    pub(in crate::core) fn reset(&self) {
+        self.draw_calls.store(0, Ordering::Relaxed);
+        self.triangles.store(0, Ordering::Relaxed);
+    }
+}
+
+///
+/// Statistics about the GPU memory used by the textures and buffers which are currently allocated using a [Context](crate::core::Context),
+/// see [Context::memory_stats](crate::core::Context::memory_stats).
+/// The sizes are estimated from the size and format of the data including mip maps, the actual memory use depends on the graphics driver.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryStats {
+    /// The number of textures, including render buffers used for multisampling.
+    pub textures: u64,
+    /// The estimated number of bytes used by the textures.
+    pub texture_bytes: u64,
+    /// The number of buffers, for example vertex, index and uniform buffers.
+    pub buffers: u64,
+    /// The estimated number of bytes used by the buffers.
+    pub buffer_bytes: u64,
+}
+
+///
+/// The memory of a single texture or buffer which is counted in the [MemoryStats] of the context until it is dropped.
+///
+pub(in crate::core) struct TrackedMemory {
+    stats: std::sync::Arc<std::sync::RwLock<MemoryStats>>,
+    is_texture: bool,
+    bytes: u64,
+}
+
+impl TrackedMemory {
+    pub(in crate::core) fn new(
+        stats: std::sync::Arc<std::sync::RwLock<MemoryStats>>,
+        is_texture: bool,
+        bytes: u64,
+    ) -> Self {
+        {
+            let mut stats = stats.write().unwrap();
+            if is_texture {
+                stats.textures += 1;
+                stats.texture_bytes += bytes;
+            } else {
+                stats.buffers += 1;
+                stats.buffer_bytes += bytes;
+            }
+        }
+        Self {
+            stats,
+            is_texture,
+            bytes,
+        }
+    }
+
+    ///
+    /// Changes the number of bytes, for example when a buffer is reallocated with a different size.
+    ///
+    pub(in crate::core) fn set_bytes(&mut self, bytes: u64) {
+        let mut stats = self.stats.write().unwrap();
+        let total = if self.is_texture {
+            &mut stats.texture_bytes
+        } else {
+            &mut stats.buffer_bytes
+        };
+        *total = total.saturating_sub(self.bytes) + bytes;
+        self.bytes = bytes;
+    }
+}
+
+impl Drop for TrackedMemory {
+    fn drop(&mut self) {
+        self.set_bytes(0);
+        let mut stats = self.stats.write().unwrap();
+        if self.is_texture {
+            stats.textures = stats.textures.saturating_sub(1);
+        } else {
+            stats.buffers = stats.buffers.saturating_sub(1);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use crate::core::*;
+
+    #[test]
+    fn test_memory_stats() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let before = context.memory_stats();
+        let texture = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            4,
+            4,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut buffer = VertexBuffer::new_with_data(&context, &[vec3(0.0f32, 0.0, 0.0); 3]);
+        let stats = context.memory_stats();
+        assert_eq!(stats.textures, before.textures + 1);
+        assert_eq!(stats.texture_bytes, before.texture_bytes + 4 * 4 * 4);
+        assert_eq!(stats.buffers, before.buffers + 1);
+        assert_eq!(stats.buffer_bytes, before.buffer_bytes + 3 * 12);
+
+        buffer.fill(&[vec3(0.0f32, 0.0, 0.0); 6]);
+        assert_eq!(
+            context.memory_stats().buffer_bytes,
+            before.buffer_bytes + 6 * 12
+        );
+
+        drop(texture);
+        drop(buffer);
+        assert_eq!(context.memory_stats(), before);
+    }
+}
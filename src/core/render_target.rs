@@ -26,6 +26,10 @@ mod depth_target_multisample;
 #[doc(inline)]
 pub use depth_target_multisample::*;
 
+mod multi_render_target;
+#[doc(inline)]
+pub use multi_render_target::*;
+
 use crate::core::*;
 
 use crate::context::Framebuffer;
@@ -39,6 +43,7 @@ pub struct RenderTarget<'a> {
     id: Option<Framebuffer>,
     color: Option<ColorTarget<'a>>,
     depth: Option<DepthTarget<'a>>,
+    object_ids: Option<(&'a Texture2D, u32)>,
     pub(crate) context: Context,
     width: u32,
     height: u32,
@@ -55,6 +60,7 @@ impl<'a> RenderTarget<'a> {
             id: None,
             color: None,
             depth: None,
+            object_ids: None,
             width,
             height,
         }
@@ -71,11 +77,54 @@ impl<'a> RenderTarget<'a> {
             id: Some(new_framebuffer(&color.context)),
             color: Some(color),
             depth: Some(depth),
+            object_ids: None,
             width,
             height,
         }
     }
 
+    ///
+    /// Constructs a new render target like [RenderTarget::new], which in addition writes the id of the rendered object (see [Context::set_object_id])
+    /// into the given object id texture for each pixel. This makes it possible to find the object in a pixel, for example the object under the cursor,
+    /// without rendering the scene again.
+    ///
+    /// The object id texture must be a texture with one `u32` channel. It is cleared to zero when the color is cleared.
+    /// If `instance_bits` is larger than zero, the highest `instance_bits` bits of each id contains the index of the instance
+    /// when rendering instanced geometries, for example [InstancedMesh](crate::renderer::InstancedMesh), and the remaining bits contains the object id.
+    /// Note that the object id is written whenever the color is written, so transparent objects overwrite the id of the objects behind them.
+    ///
+    /// # Panic
+    /// Will panic if the object id texture is not an unsigned integer texture, if it is not the same size as the color target or if `instance_bits` is larger than 31.
+    ///
+    pub fn new_with_object_ids(
+        color: ColorTarget<'a>,
+        object_ids: &'a Texture2D,
+        instance_bits: u32,
+        depth: DepthTarget<'a>,
+    ) -> Self {
+        if object_ids.integer_data_type() != Some(crate::context::UNSIGNED_INT) {
+            panic!("the object id texture must be a texture with one u32 channel");
+        }
+        if object_ids.width() != color.width() || object_ids.height() != color.height() {
+            panic!(
+                "the object id texture ({}x{}) must have the same size as the color target ({}x{})",
+                object_ids.width(),
+                object_ids.height(),
+                color.width(),
+                color.height()
+            );
+        }
+        if instance_bits > 31 {
+            panic!(
+                "cannot use more than 31 bits for the instance index (got {})",
+                instance_bits
+            );
+        }
+        let mut target = Self::new(color, depth);
+        target.object_ids = Some((object_ids, instance_bits));
+        target
+    }
+
     /// The width of this target.
     pub fn width(&self) -> u32 {
         self.width
@@ -99,7 +148,31 @@ impl<'a> RenderTarget<'a> {
     pub fn clear_partially(&self, scissor_box: ScissorBox, clear_state: ClearState) -> &Self {
         self.context.set_scissor(scissor_box);
         self.bind(crate::context::DRAW_FRAMEBUFFER);
-        clear_state.apply(&self.context);
+        if let Some(data_type) = self.color.as_ref().and_then(|c| c.integer_data_type()) {
+            clear_state.apply_integer(&self.context, is_signed_data_type(data_type));
+        } else if self.object_ids.is_some() {
+            // The integer object id buffer cannot be cleared together with the color buffer
+            let clear_color = clear_state.red.is_some()
+                || clear_state.green.is_some()
+                || clear_state.blue.is_some()
+                || clear_state.alpha.is_some();
+            unsafe {
+                if clear_color {
+                    self.context.set_write_mask(WriteMask::COLOR);
+                    self.context
+                        .clear_buffer_u32_slice(crate::context::COLOR, 1, &[0; 4]);
+                }
+                self.context
+                    .draw_buffers(&[crate::context::COLOR_ATTACHMENT0, crate::context::NONE]);
+                clear_state.apply(&self.context);
+                self.context.draw_buffers(&[
+                    crate::context::COLOR_ATTACHMENT0,
+                    crate::context::COLOR_ATTACHMENT1,
+                ]);
+            }
+        } else {
+            clear_state.apply(&self.context);
+        }
         self
     }
 
@@ -116,7 +189,11 @@ impl<'a> RenderTarget<'a> {
     pub fn write_partially(&self, scissor_box: ScissorBox, render: impl FnOnce()) -> &Self {
         self.context.set_scissor(scissor_box);
         self.bind(crate::context::DRAW_FRAMEBUFFER);
+        let object_id_output = self
+            .context
+            .set_object_id_output(self.object_ids.map(|(_, instance_bits)| instance_bits));
         render();
+        self.context.set_object_id_output(object_id_output);
         if let Some(ref color) = self.color {
             color.generate_mip_maps();
         }
@@ -128,6 +205,7 @@ impl<'a> RenderTarget<'a> {
     /// The number of channels per pixel and the data format for each channel is specified by the generic parameter.
     ///
     /// **Note:** On web, the data format needs to match the data format of the color texture.
+    /// Use an integer type, for example `read_color::<u32>()`, to read from an integer texture.
     ///
     pub fn read_color<T: TextureDataType>(&self) -> Vec<T> {
         self.read_color_partially(self.scissor_box())
@@ -145,9 +223,12 @@ impl<'a> RenderTarget<'a> {
         }
         self.bind(crate::context::DRAW_FRAMEBUFFER);
         self.bind(crate::context::READ_FRAMEBUFFER);
+        if is_integer_data_type(T::data_type()) && self.context.version().is_embedded {
+            return self.read_integer_color_partially(scissor_box);
+        }
         let mut data_size = std::mem::size_of::<T>();
         // On web, the format needs to be RGBA if the data type is byte.
-        if data_size / T::size() as usize == 1 {
+        if data_size / T::size() as usize == 1 && !is_integer_data_type(T::data_type()) {
             data_size *= 4 / T::size() as usize
         }
         let mut bytes =
@@ -172,6 +253,91 @@ impl<'a> RenderTarget<'a> {
         pixels
     }
 
+    ///
+    /// On WebGL2 and OpenGL ES, integer colors can only be read using the RGBA_INTEGER format and a 32 bit data type,
+    /// so the pixels are read in that format and afterwards converted to the requested format.
+    ///
+    fn read_integer_color_partially<T: TextureDataType>(&self, scissor_box: ScissorBox) -> Vec<T> {
+        let pixel_count = scissor_box.width as usize * scissor_box.height as usize;
+        let mut bytes = vec![0u8; pixel_count * 16];
+        unsafe {
+            self.context.read_pixels(
+                scissor_box.x,
+                scissor_box.y,
+                scissor_box.width as i32,
+                scissor_box.height as i32,
+                crate::context::RGBA_INTEGER,
+                if is_signed_data_type(T::data_type()) {
+                    crate::context::INT
+                } else {
+                    crate::context::UNSIGNED_INT
+                },
+                crate::context::PixelPackData::Slice(&mut bytes),
+            );
+        }
+        let mut pixels = from_integer_rgba_bytes(&bytes);
+        flip_y(
+            &mut pixels,
+            scissor_box.width as usize,
+            scissor_box.height as usize,
+        );
+        pixels
+    }
+
+    ///
+    /// Starts reading the colors of the pixels in this render target inside the given scissor box without waiting for the GPU to finish rendering,
+    /// see [GpuReadback]. This is typically used for reading a few pixels every frame, in which case the result is available a frame or two later.
+    /// The number of channels per pixel and the data format for each channel is specified by the generic parameter.
+    ///
+    /// **Note:** On web, the data format needs to match the data format of the color texture.
+    ///
+    pub fn read_color_partially_async<T: TextureDataType>(
+        &self,
+        scissor_box: ScissorBox,
+    ) -> GpuReadback<T> {
+        if self.id.is_some() && self.color.is_none() {
+            panic!("cannot read color from a render target without a color target");
+        }
+        self.bind(crate::context::DRAW_FRAMEBUFFER);
+        self.bind(crate::context::READ_FRAMEBUFFER);
+        let pixel_count = scissor_box.width as usize * scissor_box.height as usize;
+        let integer = is_integer_data_type(T::data_type()) && self.context.version().is_embedded;
+        let (format, data_type, pixel_size) = if integer {
+            (
+                crate::context::RGBA_INTEGER,
+                if is_signed_data_type(T::data_type()) {
+                    crate::context::INT
+                } else {
+                    crate::context::UNSIGNED_INT
+                },
+                16,
+            )
+        } else {
+            (
+                format_from_data_type::<T>(),
+                T::data_type(),
+                std::mem::size_of::<T>(),
+            )
+        };
+        GpuReadback::new(
+            &self.context,
+            pixel_count * pixel_size,
+            scissor_box,
+            integer,
+            || unsafe {
+                self.context.read_pixels(
+                    scissor_box.x,
+                    scissor_box.y,
+                    scissor_box.width as i32,
+                    scissor_box.height as i32,
+                    format,
+                    data_type,
+                    crate::context::PixelPackData::BufferOffset(0),
+                );
+            },
+        )
+    }
+
     ///
     /// Returns the depth values in this render target.
     ///
@@ -375,6 +541,7 @@ impl<'a> RenderTarget<'a> {
             id: Some(framebuffer),
             color: None,
             depth: None,
+            object_ids: None,
             context: context.clone(),
             width,
             height,
@@ -423,6 +590,32 @@ impl<'a> RenderTarget<'a> {
         }
     }
 
+    ///
+    /// Copies the color of the screen into the color target of this render target, where the screen is assumed to have the same size as this render target.
+    /// Only the color is copied, since the depth format of the screen is not known and a blit requires the depth formats to be the same.
+    ///
+    #[cfg(feature = "window")]
+    pub(in crate::core) fn blit_color_from_screen(&self) {
+        self.context.set_scissor(self.scissor_box());
+        self.bind(crate::context::DRAW_FRAMEBUFFER);
+        unsafe {
+            self.context
+                .bind_framebuffer(crate::context::READ_FRAMEBUFFER, None);
+            self.context.blit_framebuffer(
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                0,
+                0,
+                self.width as i32,
+                self.height as i32,
+                crate::context::COLOR_BUFFER_BIT,
+                crate::context::NEAREST,
+            );
+        }
+    }
+
     fn new_color(color: ColorTarget<'a>) -> Self {
         let width = color.width();
         let height = color.height();
@@ -431,6 +624,7 @@ impl<'a> RenderTarget<'a> {
             id: Some(new_framebuffer(&color.context)),
             color: Some(color),
             depth: None,
+            object_ids: None,
             width,
             height,
         }
@@ -444,6 +638,7 @@ impl<'a> RenderTarget<'a> {
             id: Some(new_framebuffer(&depth.context)),
             depth: Some(depth),
             color: None,
+            object_ids: None,
             width,
             height,
         }
@@ -456,6 +651,15 @@ impl<'a> RenderTarget<'a> {
         if let Some(ref color) = self.color {
             color.bind(&self.context);
         }
+        if let Some((object_ids, _)) = self.object_ids {
+            object_ids.bind_as_color_target(1, 0);
+            unsafe {
+                self.context.draw_buffers(&[
+                    crate::context::COLOR_ATTACHMENT0,
+                    crate::context::COLOR_ATTACHMENT1,
+                ]);
+            }
+        }
         if let Some(ref depth) = self.depth {
             depth.bind();
         }
@@ -472,6 +676,119 @@ impl Drop for RenderTarget<'_> {
     }
 }
 
+///
+/// A read of pixels from a render target which happens asynchronously, see for example [RenderTarget::read_color_partially_async].
+/// The pixels are copied into a pixel buffer object on the GPU and can be fetched without stalling when the GPU has finished rendering.
+/// The pixel buffer object is deleted when this is dropped, which is safe to do before the read is complete.
+///
+pub struct GpuReadback<T: TextureDataType> {
+    context: Context,
+    buffer: crate::context::Buffer,
+    fence: crate::context::Fence,
+    size: usize,
+    scissor_box: ScissorBox,
+    integer: bool,
+    _data_type: std::marker::PhantomData<T>,
+}
+
+impl<T: TextureDataType> GpuReadback<T> {
+    fn new(
+        context: &Context,
+        size: usize,
+        scissor_box: ScissorBox,
+        integer: bool,
+        copy_from_framebuffer: impl FnOnce(),
+    ) -> Self {
+        unsafe {
+            let buffer = context.create_buffer().expect("Failed creating buffer");
+            context.bind_buffer(crate::context::PIXEL_PACK_BUFFER, Some(buffer));
+            context.buffer_data_size(
+                crate::context::PIXEL_PACK_BUFFER,
+                size as i32,
+                crate::context::STREAM_READ,
+            );
+            copy_from_framebuffer();
+            context.bind_buffer(crate::context::PIXEL_PACK_BUFFER, None);
+            let fence = context
+                .fence_sync(crate::context::SYNC_GPU_COMMANDS_COMPLETE, 0)
+                .expect("Failed creating fence");
+            Self {
+                context: context.clone(),
+                buffer,
+                fence,
+                size,
+                scissor_box,
+                integer,
+                _data_type: std::marker::PhantomData,
+            }
+        }
+    }
+
+    ///
+    /// Returns true if the GPU has finished copying the pixels.
+    /// This does not block.
+    ///
+    pub fn is_complete(&self) -> bool {
+        let status = unsafe { self.context.client_wait_sync(self.fence, 0, 0) };
+        status == crate::context::ALREADY_SIGNALED || status == crate::context::CONDITION_SATISFIED
+    }
+
+    ///
+    /// Returns the pixels, in the same order as [RenderTarget::read_color_partially], if the GPU has finished copying them, otherwise `None`.
+    /// This does not block.
+    ///
+    pub fn pixels(&self) -> Option<Vec<T>> {
+        if !self.is_complete() {
+            return None;
+        }
+        let mut bytes = vec![0u8; self.size];
+        unsafe {
+            self.context
+                .bind_buffer(crate::context::PIXEL_PACK_BUFFER, Some(self.buffer));
+            self.context
+                .get_buffer_sub_data(crate::context::PIXEL_PACK_BUFFER, 0, &mut bytes);
+            self.context
+                .bind_buffer(crate::context::PIXEL_PACK_BUFFER, None);
+        }
+        let mut pixels = if self.integer {
+            from_integer_rgba_bytes(&bytes)
+        } else {
+            from_byte_slice(&bytes).to_vec()
+        };
+        flip_y(
+            &mut pixels,
+            self.scissor_box.width as usize,
+            self.scissor_box.height as usize,
+        );
+        Some(pixels)
+    }
+}
+
+impl<T: TextureDataType> Drop for GpuReadback<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.context.delete_sync(self.fence);
+            self.context.delete_buffer(self.buffer);
+        }
+    }
+}
+
+///
+/// Converts integer pixels read in the RGBA_INTEGER format with a 32 bit data type to the requested format.
+///
+fn from_integer_rgba_bytes<T: TextureDataType>(bytes: &[u8]) -> Vec<T> {
+    let channel_count = T::size() as usize;
+    let channel_size = std::mem::size_of::<T>() / channel_count;
+    let mut compact = Vec::with_capacity(bytes.len() / 16 * std::mem::size_of::<T>());
+    for pixel in bytes.chunks_exact(16) {
+        for channel in pixel.chunks_exact(4).take(channel_count) {
+            // Assumes little endian which is the case for all supported platforms
+            compact.extend_from_slice(&channel[..channel_size]);
+        }
+    }
+    from_byte_slice(&compact).to_vec()
+}
+
 fn size_with_mip(size: u32, mip: Option<u32>) -> u32 {
     if let Some(mip) = mip {
         size / 2u32.pow(mip)
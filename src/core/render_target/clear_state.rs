@@ -4,6 +4,10 @@ use crate::core::*;
 /// Defines which channels (red, green, blue, alpha and depth) to clear when starting to write to a [RenderTarget].
 /// If `None` then the channel is not cleared and if `Some(value)` the channel is cleared to that value (the value must be between 0 and 1).
 ///
+/// When clearing the screen of a transparent window (see [WindowSettings::transparent](crate::WindowSettings::transparent)),
+/// the window compositor interprets the color as premultiplied with alpha, so the red, green and blue values should be multiplied with the alpha value.
+/// For example, clearing to `(0.0, 0.0, 0.0, 0.0)` makes the window fully transparent.
+///
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub struct ClearState {
     /// Defines the clear value for the red channel.
@@ -104,6 +108,50 @@ impl ClearState {
             });
         }
     }
+
+    ///
+    /// Clears an integer color buffer which cannot be cleared with [ClearState::apply].
+    /// The color values are truncated to integers.
+    ///
+    pub(in crate::core) fn apply_integer(&self, context: &Context, signed: bool) {
+        context.set_write_mask(WriteMask {
+            red: self.red.is_some(),
+            green: self.green.is_some(),
+            blue: self.blue.is_some(),
+            alpha: self.alpha.is_some(),
+            depth: self.depth.is_some(),
+        });
+        unsafe {
+            if self.red.is_some()
+                || self.green.is_some()
+                || self.blue.is_some()
+                || self.alpha.is_some()
+            {
+                let values = [
+                    self.red.unwrap_or(0.0),
+                    self.green.unwrap_or(0.0),
+                    self.blue.unwrap_or(0.0),
+                    self.alpha.unwrap_or(1.0),
+                ];
+                if signed {
+                    context.clear_buffer_i32_slice(
+                        crate::context::COLOR,
+                        0,
+                        &values.map(|v| v as i32),
+                    );
+                } else {
+                    context.clear_buffer_u32_slice(
+                        crate::context::COLOR,
+                        0,
+                        &values.map(|v| v as u32),
+                    );
+                }
+            }
+            if let Some(depth) = self.depth {
+                context.clear_buffer_f32_slice(crate::context::DEPTH, 0, &[depth]);
+            }
+        }
+    }
 }
 
 impl Default for ClearState {
@@ -14,6 +14,7 @@ pub struct ColorTarget<'a> {
     mip_level: Option<u32>,
     target: Option<ColorTexture<'a>>,
     multisample_target: Option<&'a Texture2DMultisample>,
+    multiple_targets: Option<&'a [Texture2D]>,
 }
 
 impl<'a> ColorTarget<'a> {
@@ -27,6 +28,7 @@ impl<'a> ColorTarget<'a> {
             mip_level,
             target: Some(ColorTexture::Single(texture)),
             multisample_target: None,
+            multiple_targets: None,
         }
     }
 
@@ -41,6 +43,7 @@ impl<'a> ColorTarget<'a> {
             mip_level,
             target: Some(ColorTexture::CubeMap { texture, sides }),
             multisample_target: None,
+            multiple_targets: None,
         }
     }
 
@@ -55,6 +58,7 @@ impl<'a> ColorTarget<'a> {
             mip_level,
             target: Some(ColorTexture::Array { texture, layers }),
             multisample_target: None,
+            multiple_targets: None,
         }
     }
 
@@ -67,6 +71,17 @@ impl<'a> ColorTarget<'a> {
             mip_level: None,
             target: None,
             multisample_target: Some(texture),
+            multiple_targets: None,
+        }
+    }
+
+    pub(in crate::core) fn new_textures2d(context: &Context, textures: &'a [Texture2D]) -> Self {
+        ColorTarget {
+            context: context.clone(),
+            mip_level: None,
+            target: None,
+            multisample_target: None,
+            multiple_targets: Some(textures),
         }
     }
 
@@ -79,6 +94,7 @@ impl<'a> ColorTarget<'a> {
 
     ///
     /// Clears the color of the part of this color target that is inside the given scissor box.
+    /// If the target is an integer texture, the clear values are truncated to integers.
     ///
     pub fn clear_partially(&self, scissor_box: ScissorBox, clear_state: ClearState) -> &Self {
         self.as_render_target().clear_partially(
@@ -126,6 +142,18 @@ impl<'a> ColorTarget<'a> {
         self.as_render_target().read_color_partially(scissor_box)
     }
 
+    ///
+    /// Starts reading the colors of the pixels in this color target inside the given scissor box without waiting for the GPU to finish rendering,
+    /// see [RenderTarget::read_color_partially_async].
+    ///
+    pub fn read_partially_async<T: TextureDataType>(
+        &self,
+        scissor_box: ScissorBox,
+    ) -> GpuReadback<T> {
+        self.as_render_target()
+            .read_color_partially_async(scissor_box)
+    }
+
     ///
     /// Copies the content of the color texture as limited by the [WriteMask]
     /// to the part of this color target specified by the [Viewport].
@@ -174,6 +202,8 @@ impl<'a> ColorTarget<'a> {
                     size_with_mip(texture.width(), self.mip_level)
                 }
             }
+        } else if let Some(textures) = self.multiple_targets {
+            textures[0].width()
         } else {
             self.multisample_target.as_ref().unwrap().width()
         }
@@ -194,11 +224,30 @@ impl<'a> ColorTarget<'a> {
                     size_with_mip(texture.height(), self.mip_level)
                 }
             }
+        } else if let Some(textures) = self.multiple_targets {
+            textures[0].height()
         } else {
             self.multisample_target.as_ref().unwrap().height()
         }
     }
 
+    pub(super) fn integer_data_type(&self) -> Option<u32> {
+        if let Some(ColorTexture::Single(texture)) = self.target {
+            texture.integer_data_type()
+        } else {
+            None
+        }
+    }
+
+    ///
+    /// Copies the color of the screen with the same size as this color target into this color target.
+    /// Used to preserve the content of the screen between frames, see [FrameOutput::preserve_screen](crate::FrameOutput::preserve_screen).
+    ///
+    #[cfg(feature = "window")]
+    pub(crate) fn blit_from_screen(&self) {
+        self.as_render_target().blit_color_from_screen();
+    }
+
     pub(super) fn as_render_target(&self) -> RenderTarget<'a> {
         RenderTarget::new_color(self.clone())
     }
@@ -222,6 +271,10 @@ impl<'a> ColorTarget<'a> {
                     }
                 }
             }
+        } else if let Some(textures) = self.multiple_targets {
+            textures
+                .iter()
+                .for_each(|texture| texture.generate_mip_maps());
         }
     }
 
@@ -261,6 +314,18 @@ impl<'a> ColorTarget<'a> {
                     });
                 },
             }
+        } else if let Some(textures) = self.multiple_targets {
+            unsafe {
+                context.draw_buffers(
+                    &(0..textures.len())
+                        .map(|i| crate::context::COLOR_ATTACHMENT0 + i as u32)
+                        .collect::<Vec<u32>>(),
+                );
+            }
+            textures
+                .iter()
+                .enumerate()
+                .for_each(|(channel, texture)| texture.bind_as_color_target(channel as u32, 0));
         } else {
             unsafe {
                 context.draw_buffers(&[crate::context::COLOR_ATTACHMENT0]);
@@ -0,0 +1,173 @@
+use super::*;
+
+///
+/// A set of color textures, and optionally a depth texture, that are written at the same time by a single draw call,
+/// for example to write the different parts of a G-buffer for deferred rendering.
+/// The fragment shader writes to each texture through an output with the same location as the index of the texture,
+/// for example `layout (location = 1) out vec4 normal;` writes to the texture with index 1.
+///
+/// Unlike [RenderTarget], which only adds functionality to textures that are stored elsewhere, this struct owns the textures.
+/// Use [MultiRenderTarget::render_target] to clear, write or read them.
+///
+pub struct MultiRenderTarget {
+    context: Context,
+    color_textures: Vec<Texture2D>,
+    depth_texture: Option<DepthTexture2D>,
+}
+
+impl MultiRenderTarget {
+    ///
+    /// Creates a new multi render target from the given color textures and optional depth texture.
+    /// The textures can have different formats, but must have the same size.
+    ///
+    /// Returns an error if there are no color textures, more color textures than the number of draw buffers supported by the graphics driver
+    /// (at least 4 and typically 8) or if the textures do not have the same size.
+    ///
+    pub fn new(
+        context: &Context,
+        color_textures: Vec<Texture2D>,
+        depth_texture: Option<DepthTexture2D>,
+    ) -> Result<Self, CoreError> {
+        let max_count = unsafe {
+            context
+                .get_parameter_i32(crate::context::MAX_DRAW_BUFFERS)
+                .min(context.get_parameter_i32(crate::context::MAX_COLOR_ATTACHMENTS))
+                as u32
+        };
+        if color_textures.is_empty() || color_textures.len() > max_count as usize {
+            return Err(CoreError::ColorTextureCount(
+                color_textures.len(),
+                max_count,
+            ));
+        }
+        let (width, height) = (color_textures[0].width(), color_textures[0].height());
+        for (w, h) in color_textures
+            .iter()
+            .map(|t| (t.width(), t.height()))
+            .chain(depth_texture.iter().map(|t| (t.width(), t.height())))
+        {
+            if w != width || h != height {
+                return Err(CoreError::TextureSizeMismatch(width, height, w, h));
+            }
+        }
+        Ok(Self {
+            context: context.clone(),
+            color_textures,
+            depth_texture,
+        })
+    }
+
+    ///
+    /// Returns the number of color textures.
+    ///
+    pub fn color_texture_count(&self) -> usize {
+        self.color_textures.len()
+    }
+
+    ///
+    /// Returns the color texture with the given index, which is written by the fragment shader output with that location.
+    ///
+    /// # Panic
+    /// Will panic if the index is not smaller than the number of color textures.
+    ///
+    pub fn color_texture(&self, index: usize) -> &Texture2D {
+        &self.color_textures[index]
+    }
+
+    ///
+    /// Returns a mutable reference to the color texture with the given index, for example to use it as a [ColorTarget] on its own.
+    ///
+    /// # Panic
+    /// Will panic if the index is not smaller than the number of color textures.
+    ///
+    pub fn color_texture_mut(&mut self, index: usize) -> &mut Texture2D {
+        &mut self.color_textures[index]
+    }
+
+    ///
+    /// Returns the depth texture if this multi render target has one.
+    ///
+    pub fn depth_texture(&self) -> Option<&DepthTexture2D> {
+        self.depth_texture.as_ref()
+    }
+
+    ///
+    /// Returns a [RenderTarget] which clears, writes and reads all of the textures at the same time.
+    /// Reading the color returns the color of the first color texture.
+    ///
+    /// **Note:** [DepthTest] is disabled if this multi render target does not have a depth texture.
+    ///
+    pub fn render_target(&mut self) -> RenderTarget<'_> {
+        let color = ColorTarget::new_textures2d(&self.context, &self.color_textures);
+        if let Some(depth_texture) = self.depth_texture.as_mut() {
+            RenderTarget::new(color, depth_texture.as_depth_target())
+        } else {
+            color.as_render_target()
+        }
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_write_to_multiple_textures() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let texture = |context: &Context| {
+            Texture2D::new_empty::<[u8; 4]>(
+                context,
+                4,
+                4,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        let mut target =
+            MultiRenderTarget::new(&context, vec![texture(&context), texture(&context)], None)
+                .unwrap();
+        target
+            .render_target()
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .write(|| {
+                apply_effect(
+                    &context,
+                    "layout (location = 0) out vec4 first;
+                    layout (location = 1) out vec4 second;
+                    void main() {
+                        first = vec4(1.0, 0.0, 0.0, 1.0);
+                        second = vec4(0.0, 0.0, 1.0, 1.0);
+                    }",
+                    RenderStates::default(),
+                    Viewport::new_at_origo(4, 4),
+                    |_| {},
+                )
+            });
+        let first = target
+            .color_texture_mut(0)
+            .as_color_target(None)
+            .read::<[u8; 4]>();
+        let second = target
+            .color_texture_mut(1)
+            .as_color_target(None)
+            .read::<[u8; 4]>();
+        assert!(first.iter().all(|c| *c == [255, 0, 0, 255]));
+        assert!(second.iter().all(|c| *c == [0, 0, 255, 255]));
+
+        assert!(MultiRenderTarget::new(&context, vec![], None).is_err());
+        let small = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            2,
+            2,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        assert!(MultiRenderTarget::new(&context, vec![texture(&context), small], None).is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::core::*;
+
+///
+/// Returns the point on the line segment from `a` to `b` that is closest to the given point.
+///
+pub fn closest_point_on_segment(point: Vec3, a: Vec3, b: Vec3) -> Vec3 {
+    let ab = b - a;
+    let length2 = ab.magnitude2();
+    if length2 < f32::EPSILON * f32::EPSILON {
+        return a;
+    }
+    a + ((point - a).dot(ab) / length2).clamp(0.0, 1.0) * ab
+}
+
+///
+/// Returns the closest pair of points on the line segment from `a0` to `a1` and the line segment from `b0` to `b1`, respectively.
+/// If the segments are parallel, one of the closest pairs is returned.
+///
+pub fn closest_points_between_segments(a0: Vec3, a1: Vec3, b0: Vec3, b1: Vec3) -> (Vec3, Vec3) {
+    let da = a1 - a0;
+    let db = b1 - b0;
+    let r = a0 - b0;
+    let a = da.magnitude2();
+    let e = db.magnitude2();
+    let f = db.dot(r);
+    const EPSILON: f32 = f32::EPSILON * f32::EPSILON;
+    if a < EPSILON && e < EPSILON {
+        return (a0, b0);
+    }
+    let (s, t) = if a < EPSILON {
+        (0.0, (f / e).clamp(0.0, 1.0))
+    } else {
+        let c = da.dot(r);
+        if e < EPSILON {
+            ((-c / a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = da.dot(db);
+            let denominator = a * e - b * b;
+            // Pick the start of the first segment if the segments are parallel
+            let s = if denominator > EPSILON {
+                ((b * f - c * e) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / e;
+            if t < 0.0 {
+                ((-c / a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (a0 + s * da, b0 + t * db)
+}
+
+///
+/// Returns the shortest distance between the line segment from `a0` to `a1` and the line segment from `b0` to `b1`.
+///
+pub fn segment_segment_distance(a0: Vec3, a1: Vec3, b0: Vec3, b1: Vec3) -> f32 {
+    let (a, b) = closest_points_between_segments(a0, a1, b0, b1);
+    a.distance(b)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_closest_point_on_segment() {
+        let (a, b) = (vec3(0.0, 0.0, 0.0), vec3(2.0, 0.0, 0.0));
+        assert_eq!(
+            closest_point_on_segment(vec3(1.0, 3.0, 0.0), a, b),
+            vec3(1.0, 0.0, 0.0)
+        );
+        assert_eq!(closest_point_on_segment(vec3(-1.0, 3.0, 0.0), a, b), a);
+        assert_eq!(closest_point_on_segment(vec3(5.0, -1.0, 2.0), a, b), b);
+        assert_eq!(closest_point_on_segment(vec3(5.0, -1.0, 2.0), a, a), a);
+    }
+
+    #[test]
+    fn test_segment_segment_distance() {
+        // Crossing segments with a gap of 1 along z
+        let (p, q) = closest_points_between_segments(
+            vec3(-1.0, 0.0, 0.0),
+            vec3(1.0, 0.0, 0.0),
+            vec3(0.5, -1.0, 1.0),
+            vec3(0.5, 1.0, 1.0),
+        );
+        assert_eq!(p, vec3(0.5, 0.0, 0.0));
+        assert_eq!(q, vec3(0.5, 0.0, 1.0));
+
+        // The closest points are end points
+        assert_eq!(
+            segment_segment_distance(
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(4.0, 1.0, 0.0),
+                vec3(4.0, 5.0, 0.0)
+            ),
+            vec3(3.0, 1.0, 0.0).magnitude()
+        );
+
+        // Parallel and overlapping segments
+        assert_eq!(
+            segment_segment_distance(
+                vec3(0.0, 0.0, 0.0),
+                vec3(2.0, 0.0, 0.0),
+                vec3(1.0, 2.0, 0.0),
+                vec3(3.0, 2.0, 0.0)
+            ),
+            2.0
+        );
+
+        // Degenerate segments
+        assert_eq!(
+            segment_segment_distance(
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 0.0),
+                vec3(-1.0, 1.0, 0.0),
+                vec3(1.0, 1.0, 0.0)
+            ),
+            1.0
+        );
+        assert_eq!(
+            segment_segment_distance(
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 0.0),
+                vec3(0.0, 0.0, 3.0),
+                vec3(0.0, 0.0, 3.0)
+            ),
+            3.0
+        );
+    }
+}
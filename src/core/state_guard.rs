@@ -0,0 +1,332 @@
+use crate::core::*;
+
+/// The texture targets and the corresponding binding parameters which are saved and restored for each texture unit.
+const TEXTURE_TARGETS: [(u32, u32); 4] = [
+    (
+        crate::context::TEXTURE_2D,
+        crate::context::TEXTURE_BINDING_2D,
+    ),
+    (
+        crate::context::TEXTURE_2D_ARRAY,
+        crate::context::TEXTURE_BINDING_2D_ARRAY,
+    ),
+    (
+        crate::context::TEXTURE_3D,
+        crate::context::TEXTURE_BINDING_3D,
+    ),
+    (
+        crate::context::TEXTURE_CUBE_MAP,
+        crate::context::TEXTURE_BINDING_CUBE_MAP,
+    ),
+];
+
+/// The capabilities which are saved and restored, the first four are set by three-d before each draw and the rest are expected to be disabled.
+const CAPABILITIES: [u32; 8] = [
+    crate::context::BLEND,
+    crate::context::DEPTH_TEST,
+    crate::context::CULL_FACE,
+    crate::context::SCISSOR_TEST,
+    crate::context::STENCIL_TEST,
+    crate::context::POLYGON_OFFSET_FILL,
+    crate::context::SAMPLE_ALPHA_TO_COVERAGE,
+    crate::context::RASTERIZER_DISCARD,
+];
+
+/// The pixel storage parameters which are saved and restored.
+const PIXEL_STORE: [u32; 5] = [
+    crate::context::UNPACK_ALIGNMENT,
+    crate::context::PACK_ALIGNMENT,
+    crate::context::UNPACK_ROW_LENGTH,
+    crate::context::UNPACK_SKIP_PIXELS,
+    crate::context::UNPACK_SKIP_ROWS,
+];
+
+///
+/// Saves the graphics state when created and restores it when dropped, see [Context::state_guard].
+///
+#[must_use = "the state is restored when the guard is dropped"]
+pub struct StateGuard {
+    context: Context,
+    state: SavedState,
+}
+
+struct SavedState {
+    capabilities: [bool; CAPABILITIES.len()],
+    pixel_store: [i32; PIXEL_STORE.len()],
+    viewport: [i32; 4],
+    scissor_box: [i32; 4],
+    blend_functions: [i32; 4],
+    blend_equations: [i32; 2],
+    depth_function: i32,
+    cull_face: i32,
+    program: Option<crate::context::Program>,
+    vertex_array: Option<crate::context::VertexArray>,
+    draw_framebuffer: Option<crate::context::Framebuffer>,
+    read_framebuffer: Option<crate::context::Framebuffer>,
+    array_buffer: Option<crate::context::Buffer>,
+    pixel_pack_buffer: Option<crate::context::Buffer>,
+    pixel_unpack_buffer: Option<crate::context::Buffer>,
+    active_texture: i32,
+    textures: Vec<Option<crate::context::Texture>>,
+}
+
+impl SavedState {
+    fn save(context: &Context) -> Self {
+        unsafe {
+            let mut viewport = [0; 4];
+            context.get_parameter_i32_slice(crate::context::VIEWPORT, &mut viewport);
+            let mut scissor_box = [0; 4];
+            context.get_parameter_i32_slice(crate::context::SCISSOR_BOX, &mut scissor_box);
+
+            let active_texture = context.get_parameter_i32(crate::context::ACTIVE_TEXTURE);
+            let units = context.texture_units();
+            let mut textures = Vec::with_capacity((units * TEXTURE_TARGETS.len() as u32) as usize);
+            for unit in 0..units {
+                context.active_texture(crate::context::TEXTURE0 + unit);
+                for (_, binding) in TEXTURE_TARGETS {
+                    textures.push(context.get_parameter_texture(binding));
+                }
+            }
+            context.active_texture(active_texture as u32);
+
+            Self {
+                capabilities: CAPABILITIES.map(|capability| context.is_enabled(capability)),
+                pixel_store: PIXEL_STORE.map(|parameter| context.get_parameter_i32(parameter)),
+                viewport,
+                scissor_box,
+                blend_functions: [
+                    crate::context::BLEND_SRC_RGB,
+                    crate::context::BLEND_DST_RGB,
+                    crate::context::BLEND_SRC_ALPHA,
+                    crate::context::BLEND_DST_ALPHA,
+                ]
+                .map(|parameter| context.get_parameter_i32(parameter)),
+                blend_equations: [
+                    crate::context::BLEND_EQUATION_RGB,
+                    crate::context::BLEND_EQUATION_ALPHA,
+                ]
+                .map(|parameter| context.get_parameter_i32(parameter)),
+                depth_function: context.get_parameter_i32(crate::context::DEPTH_FUNC),
+                cull_face: context.get_parameter_i32(crate::context::CULL_FACE_MODE),
+                program: context.get_parameter_program(crate::context::CURRENT_PROGRAM),
+                vertex_array: context
+                    .get_parameter_vertex_array(crate::context::VERTEX_ARRAY_BINDING),
+                draw_framebuffer: context
+                    .get_parameter_framebuffer(crate::context::DRAW_FRAMEBUFFER_BINDING),
+                read_framebuffer: context
+                    .get_parameter_framebuffer(crate::context::READ_FRAMEBUFFER_BINDING),
+                array_buffer: context.get_parameter_buffer(crate::context::ARRAY_BUFFER_BINDING),
+                pixel_pack_buffer: context
+                    .get_parameter_buffer(crate::context::PIXEL_PACK_BUFFER_BINDING),
+                pixel_unpack_buffer: context
+                    .get_parameter_buffer(crate::context::PIXEL_UNPACK_BUFFER_BINDING),
+                active_texture,
+                textures,
+            }
+        }
+    }
+
+    fn restore(&self, context: &Context) {
+        unsafe {
+            let units = self.textures.len() as u32 / TEXTURE_TARGETS.len() as u32;
+            for unit in 0..units {
+                context.active_texture(crate::context::TEXTURE0 + unit);
+                for (i, (target, _)) in TEXTURE_TARGETS.iter().enumerate() {
+                    let texture = self.textures[(unit as usize) * TEXTURE_TARGETS.len() + i];
+                    context.bind_texture(*target, texture);
+                }
+            }
+            context.active_texture(self.active_texture as u32);
+
+            context.bind_buffer(crate::context::ARRAY_BUFFER, self.array_buffer);
+            context.bind_buffer(crate::context::PIXEL_PACK_BUFFER, self.pixel_pack_buffer);
+            context.bind_buffer(
+                crate::context::PIXEL_UNPACK_BUFFER,
+                self.pixel_unpack_buffer,
+            );
+            context.bind_framebuffer(crate::context::DRAW_FRAMEBUFFER, self.draw_framebuffer);
+            context.bind_framebuffer(crate::context::READ_FRAMEBUFFER, self.read_framebuffer);
+            context.bind_vertex_array(self.vertex_array);
+            context.use_program(self.program);
+
+            for (parameter, value) in PIXEL_STORE.iter().zip(self.pixel_store) {
+                context.pixel_store_i32(*parameter, value);
+            }
+            let [x, y, width, height] = self.viewport;
+            context.viewport(x, y, width, height);
+            let [x, y, width, height] = self.scissor_box;
+            context.scissor(x, y, width, height);
+            let [src_rgb, dst_rgb, src_alpha, dst_alpha] = self.blend_functions.map(|v| v as u32);
+            context.blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha);
+            let [rgb, alpha] = self.blend_equations.map(|v| v as u32);
+            context.blend_equation_separate(rgb, alpha);
+            context.depth_func(self.depth_function as u32);
+            context.cull_face(self.cull_face as u32);
+            for (capability, enabled) in CAPABILITIES.iter().zip(self.capabilities) {
+                if enabled {
+                    context.enable(*capability);
+                } else {
+                    context.disable(*capability);
+                }
+            }
+        }
+    }
+}
+
+impl Drop for StateGuard {
+    fn drop(&mut self) {
+        self.state.restore(&self.context);
+    }
+}
+
+impl Context {
+    ///
+    /// Saves the graphics state and returns a guard which restores it when dropped.
+    /// This makes it safe to call another library which renders using the same low-level context, for example a video player,
+    /// while three-d is rendering:
+    /// ```no_run
+    /// # let context: three_d::Context = unimplemented!();
+    /// # fn render_with_other_library() {}
+    /// {
+    ///     let _guard = context.state_guard();
+    ///     render_with_other_library();
+    /// }
+    /// // Continue rendering with three-d
+    /// ```
+    ///
+    /// The saved state is the bound program, vertex array, framebuffers, array and pixel buffers, the textures bound to the texture units three-d has used so far,
+    /// the viewport, scissor box, blend, depth and cull state, the pixel storage parameters and whether the stencil test and a few other rarely used capabilities are enabled.
+    /// three-d does not cache the state but sets most of it before each draw, so restoring the state is enough for three-d to continue as if nothing happened.
+    /// Saving the state takes about 30 queries plus four for each of those texture units and each query can stall the pipeline on some drivers,
+    /// so use one guard around all of the foreign code in a frame instead of one guard for each call.
+    /// Use [Context::assume_dirty] if the foreign code cannot be wrapped by a guard.
+    ///
+    pub fn state_guard(&self) -> StateGuard {
+        StateGuard {
+            context: self.clone(),
+            state: SavedState::save(self),
+        }
+    }
+
+    ///
+    /// Resets the parts of the graphics state which three-d expects to have specific values but does not set before each draw,
+    /// for example the pixel storage parameters used when uploading textures, the bound pixel buffers and the stencil test.
+    /// Call this after other code has rendered using the same low-level context without being wrapped by a [Context::state_guard],
+    /// for example in a callback that three-d does not control.
+    ///
+    pub fn assume_dirty(&self) {
+        unsafe {
+            if !self.version().is_embedded {
+                // Enable seamless cube map textures - not available on OpenGL ES and WebGL
+                self.enable(crate::context::TEXTURE_CUBE_MAP_SEAMLESS);
+            }
+            self.pixel_store_i32(crate::context::UNPACK_ALIGNMENT, 1);
+            self.pixel_store_i32(crate::context::PACK_ALIGNMENT, 1);
+            self.pixel_store_i32(crate::context::UNPACK_ROW_LENGTH, 0);
+            self.pixel_store_i32(crate::context::UNPACK_SKIP_PIXELS, 0);
+            self.pixel_store_i32(crate::context::UNPACK_SKIP_ROWS, 0);
+            self.bind_buffer(crate::context::PIXEL_PACK_BUFFER, None);
+            self.bind_buffer(crate::context::PIXEL_UNPACK_BUFFER, None);
+            for capability in &CAPABILITIES[4..] {
+                self.disable(*capability);
+            }
+            self.active_texture(crate::context::TEXTURE0);
+            self.bind_vertex_array(None);
+            self.use_program(None);
+            self.bind_framebuffer(crate::context::FRAMEBUFFER, None);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    fn texture(context: &Context) -> Texture2D {
+        Texture2D::new_empty::<[u8; 4]>(
+            context,
+            3,
+            3,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        )
+    }
+
+    /// Uploads and reads back a texture, which fails if for example a pixel unpack buffer is bound.
+    fn check_rendering(context: &Context) {
+        let mut texture = texture(context);
+        let data: Vec<[u8; 4]> = (0..9u8).map(|i| [i, 2 * i, 3 * i, 255]).collect();
+        texture.fill(&data);
+        assert_eq!(texture.as_color_target(None).read::<[u8; 4]>(), data);
+        texture
+            .as_color_target(None)
+            .clear(ClearState::color(1.0, 0.0, 0.0, 1.0));
+        assert_eq!(
+            texture.as_color_target(None).read::<[u8; 4]>(),
+            vec![[255, 0, 0, 255]; 9]
+        );
+    }
+
+    /// Changes the state like a foreign library which does not clean up after itself.
+    fn trash_state(context: &Context) {
+        unsafe {
+            let framebuffer = context.create_framebuffer().unwrap();
+            context.bind_framebuffer(crate::context::FRAMEBUFFER, Some(framebuffer));
+            let buffer = context.create_buffer().unwrap();
+            context.bind_buffer(crate::context::PIXEL_UNPACK_BUFFER, Some(buffer));
+            context.buffer_data_size(
+                crate::context::PIXEL_UNPACK_BUFFER,
+                1024,
+                crate::context::STREAM_DRAW,
+            );
+            let vertex_array = context.create_vertex_array().unwrap();
+            context.bind_vertex_array(Some(vertex_array));
+            context.pixel_store_i32(crate::context::UNPACK_ALIGNMENT, 8);
+            context.pixel_store_i32(crate::context::UNPACK_ROW_LENGTH, 7);
+            context.viewport(1, 2, 3, 4);
+            context.enable(crate::context::STENCIL_TEST);
+            context.stencil_func(crate::context::NEVER, 0, 0xFF);
+            context.enable(crate::context::RASTERIZER_DISCARD);
+            let texture = context.create_texture().unwrap();
+            context.active_texture(crate::context::TEXTURE0);
+            context.bind_texture(crate::context::TEXTURE_2D, Some(texture));
+            context.active_texture(crate::context::TEXTURE3);
+        }
+    }
+
+    #[test]
+    fn test_state_guard() {
+        let context = crate::HeadlessContext::new().unwrap();
+        check_rendering(&context);
+        let before = SavedState::save(&context);
+        {
+            let _guard = context.state_guard();
+            trash_state(&context);
+        }
+        let after = SavedState::save(&context);
+        assert_eq!(after.viewport, before.viewport);
+        assert_eq!(after.capabilities, before.capabilities);
+        assert_eq!(after.pixel_store, before.pixel_store);
+        assert_eq!(after.pixel_unpack_buffer, None);
+        assert_eq!(after.draw_framebuffer, before.draw_framebuffer);
+        assert_eq!(after.vertex_array, before.vertex_array);
+        assert_eq!(after.active_texture, before.active_texture);
+        assert_eq!(after.textures, before.textures);
+        check_rendering(&context);
+    }
+
+    #[test]
+    fn test_assume_dirty() {
+        let context = crate::HeadlessContext::new().unwrap();
+        trash_state(&context);
+        context.assume_dirty();
+        let state = SavedState::save(&context);
+        assert_eq!(state.pixel_store, [1, 1, 0, 0, 0]);
+        assert_eq!(state.pixel_unpack_buffer, None);
+        assert!(!state.capabilities[4..].iter().any(|enabled| *enabled));
+        check_rendering(&context);
+    }
+}
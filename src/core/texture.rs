@@ -37,14 +37,47 @@ mod depth_texture2d_multisample;
 #[doc(inline)]
 pub(in crate::core) use depth_texture2d_multisample::*;
 
+mod video_texture;
+#[doc(inline)]
+pub use video_texture::*;
+
+mod streaming_texture2d;
+#[doc(inline)]
+pub use streaming_texture2d::*;
+
 use data_type::*;
 pub use three_d_asset::texture::{
     Interpolation, Texture2D as CpuTexture, Texture3D as CpuTexture3D, TextureData, Wrapping,
 };
 
+///
+/// The color space of the colors in a texture, see [Texture2D::new_with_color_space].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// sRGB encoded colors, which is what most images and artist made color textures contain.
+    #[default]
+    Srgb,
+    /// Linear values, which is what textures containing data, for example normal, metallic, roughness and occlusion textures, contain.
+    Linear,
+}
+
+///
 /// The basic data type used for each channel of each pixel in a texture.
+/// The number of channels (1 to 4) and the data type for each channel determines the internal format of the texture, for example
+/// `f32` corresponds to R32F, `[f16; 2]` to RG16F, `[u8; 4]` to RGBA8 and `u32` to R32UI.
+///
+/// **Note:** `u8` is interpreted as a normalized value between 0 and 1 while `i8`, `u16`, `i16`, `u32` and `i32` results in an integer texture.
+/// Integer textures must be sampled using `usampler2D`/`isampler2D` in the shader and must use [Interpolation::Nearest] without mip maps.
+/// Also, blending is not applied when writing to an integer texture.
+///
 pub trait TextureDataType: DataType {}
 impl TextureDataType for u8 {}
+impl TextureDataType for i8 {}
+impl TextureDataType for u16 {}
+impl TextureDataType for i16 {}
+impl TextureDataType for u32 {}
+impl TextureDataType for i32 {}
 impl TextureDataType for f16 {}
 impl TextureDataType for f32 {}
 
@@ -162,6 +195,85 @@ impl DepthTexture<'_> {
 
 use crate::core::*;
 
+///
+/// An upload of texture data to the GPU which happens asynchronously, see for example [Texture2D::upload_async].
+/// The pixel buffer object used for the transfer is deleted when this is dropped, which is safe to do before the upload is complete.
+///
+pub struct GpuUpload {
+    context: Context,
+    buffer: crate::context::Buffer,
+    fence: crate::context::Fence,
+}
+
+impl GpuUpload {
+    fn new(
+        context: &Context,
+        data: &[u8],
+        copy_to_texture: impl FnOnce(),
+    ) -> Result<Self, CoreError> {
+        unsafe {
+            let buffer = context.create_buffer().expect("Failed creating buffer");
+            context.bind_buffer(crate::context::PIXEL_UNPACK_BUFFER, Some(buffer));
+            #[cfg(not(target_arch = "wasm32"))]
+            {
+                context.buffer_data_size(
+                    crate::context::PIXEL_UNPACK_BUFFER,
+                    data.len() as i32,
+                    crate::context::STREAM_DRAW,
+                );
+                let pointer = context.map_buffer_range(
+                    crate::context::PIXEL_UNPACK_BUFFER,
+                    0,
+                    data.len() as i32,
+                    crate::context::MAP_WRITE_BIT | crate::context::MAP_INVALIDATE_BUFFER_BIT,
+                );
+                if pointer.is_null() {
+                    context.bind_buffer(crate::context::PIXEL_UNPACK_BUFFER, None);
+                    context.delete_buffer(buffer);
+                    return Err(CoreError::BufferMapping(data.len()));
+                }
+                std::ptr::copy_nonoverlapping(data.as_ptr(), pointer, data.len());
+                context.unmap_buffer(crate::context::PIXEL_UNPACK_BUFFER);
+            }
+            // Mapping buffers is not supported on web
+            #[cfg(target_arch = "wasm32")]
+            context.buffer_data_u8_slice(
+                crate::context::PIXEL_UNPACK_BUFFER,
+                data,
+                crate::context::STREAM_DRAW,
+            );
+            copy_to_texture();
+            context.bind_buffer(crate::context::PIXEL_UNPACK_BUFFER, None);
+            let fence = context
+                .fence_sync(crate::context::SYNC_GPU_COMMANDS_COMPLETE, 0)
+                .expect("Failed creating fence");
+            Ok(Self {
+                context: context.clone(),
+                buffer,
+                fence,
+            })
+        }
+    }
+
+    ///
+    /// Returns true if the GPU has finished copying the data into the texture.
+    /// This does not block.
+    ///
+    pub fn is_complete(&self) -> bool {
+        let status = unsafe { self.context.client_wait_sync(self.fence, 0, 0) };
+        status == crate::context::ALREADY_SIGNALED || status == crate::context::CONDITION_SATISFIED
+    }
+}
+
+impl Drop for GpuUpload {
+    fn drop(&mut self) {
+        unsafe {
+            self.context.delete_sync(self.fence);
+            self.context.delete_buffer(self.buffer);
+        }
+    }
+}
+
 // COMMON TEXTURE FUNCTIONS
 
 fn generate(context: &Context) -> crate::context::Texture {
@@ -255,6 +367,35 @@ fn calculate_number_of_mip_maps(
     }
 }
 
+///
+/// The estimated number of bytes used by a texture with the given size, number of mip maps and number of bytes per texel,
+/// where the depth is only used for 3D textures and is reduced for each mip map like the width and height.
+///
+fn texture_memory(
+    width: u32,
+    height: u32,
+    depth: u32,
+    number_of_mip_maps: u32,
+    data_byte_size: usize,
+) -> u64 {
+    (0..number_of_mip_maps)
+        .map(|level| {
+            (width >> level).max(1) as u64
+                * (height >> level).max(1) as u64
+                * (depth >> level).max(1) as u64
+        })
+        .sum::<u64>()
+        * data_byte_size as u64
+}
+
+fn depth_data_byte_size<T: DepthTextureDataType>() -> usize {
+    if T::internal_format() == crate::context::DEPTH_COMPONENT16 {
+        2
+    } else {
+        4
+    }
+}
+
 fn wrapping_from(wrapping: Wrapping) -> i32 {
     (match wrapping {
         Wrapping::Repeat => crate::context::REPEAT,
@@ -288,6 +429,56 @@ fn check_data_length<T: TextureDataType>(
     }
 }
 
+fn check_size(context: &Context, width: u32, height: u32, depth: u32) {
+    if let Err(e) = validate_size(context, width, height, depth) {
+        panic!("{}", e);
+    }
+}
+
+fn validate_size(context: &Context, width: u32, height: u32, depth: u32) -> Result<(), CoreError> {
+    let max_size =
+        unsafe { context.get_parameter_i32(crate::context::MAX_TEXTURE_SIZE) }.max(1) as u32;
+    if width == 0 || height == 0 || depth == 0 || width > max_size || height > max_size {
+        Err(CoreError::InvalidTextureSize(
+            width, height, depth, max_size,
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+fn check_filtering<T: TextureDataType>(
+    min_filter: Interpolation,
+    mag_filter: Interpolation,
+    mip_map_filter: Option<Interpolation>,
+) {
+    if is_integer_data_type(T::data_type())
+        && (min_filter != Interpolation::Nearest
+            || mag_filter != Interpolation::Nearest
+            || mip_map_filter.is_some())
+    {
+        panic!("integer textures are not filterable and must use nearest interpolation without mip maps")
+    }
+}
+
+fn check_color_renderable(context: &Context, internal_format: u32) {
+    if context.version().is_embedded
+        && matches!(
+            internal_format,
+            crate::context::RGB16F
+                | crate::context::RGB32F
+                | crate::context::RGB8I
+                | crate::context::RGB8UI
+                | crate::context::RGB16I
+                | crate::context::RGB16UI
+                | crate::context::RGB32I
+                | crate::context::RGB32UI
+        )
+    {
+        panic!("textures with three float or integer channels cannot be rendered into on WebGL2 and OpenGL ES, use four channels instead")
+    }
+}
+
 fn ru8_data(t: &CpuTexture) -> &[u8] {
     if let TextureData::RU8(data) = &t.data {
         data
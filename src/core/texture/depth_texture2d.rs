@@ -6,6 +6,7 @@ use crate::core::texture::*;
 pub struct DepthTexture2D {
     context: Context,
     id: crate::context::Texture,
+    _memory: TrackedMemory,
     width: u32,
     height: u32,
 }
@@ -14,6 +15,9 @@ impl DepthTexture2D {
     ///
     /// Constructs a new 2D depth texture.
     ///
+    /// # Panic
+    /// Will panic if the width or height is zero or larger than the maximum texture size, see [DepthTexture2D::try_new].
+    ///
     pub fn new<T: DepthTextureDataType>(
         context: &Context,
         width: u32,
@@ -21,10 +25,18 @@ impl DepthTexture2D {
         wrap_s: Wrapping,
         wrap_t: Wrapping,
     ) -> Self {
+        check_size(context, width, height, 1);
         let id = generate(context);
         let texture = Self {
             context: context.clone(),
             id,
+            _memory: context.track_texture_memory(texture_memory(
+                width,
+                height,
+                1,
+                1,
+                depth_data_byte_size::<T>(),
+            )),
             width,
             height,
         };
@@ -51,6 +63,21 @@ impl DepthTexture2D {
         texture
     }
 
+    ///
+    /// Same as [DepthTexture2D::new] except that an error is returned instead of panicking if any of the dimensions is zero
+    /// or if the width or height is larger than the maximum texture size supported by the graphics driver.
+    ///
+    pub fn try_new<T: DepthTextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+    ) -> Result<Self, CoreError> {
+        validate_size(context, width, height, 1)?;
+        Ok(Self::new::<T>(context, width, height, wrap_s, wrap_t))
+    }
+
     ///
     /// Returns a [DepthTarget] which can be used to clear, write to and read from this texture.
     /// Combine this together with a [ColorTarget] with [RenderTarget::new] to be able to write to both a depth and color target at the same time.
@@ -6,6 +6,7 @@ use crate::core::texture::*;
 pub struct DepthTexture2DArray {
     context: Context,
     id: crate::context::Texture,
+    _memory: TrackedMemory,
     width: u32,
     height: u32,
     depth: u32,
@@ -15,6 +16,9 @@ impl DepthTexture2DArray {
     ///
     /// Creates a new array of depth textures.
     ///
+    /// # Panic
+    /// Will panic if any of the dimensions is zero or if the width or height is larger than the maximum texture size, see [DepthTexture2DArray::try_new].
+    ///
     pub fn new<T: DepthTextureDataType>(
         context: &Context,
         width: u32,
@@ -23,10 +27,14 @@ impl DepthTexture2DArray {
         wrap_s: Wrapping,
         wrap_t: Wrapping,
     ) -> Self {
+        check_size(context, width, height, depth);
         let id = generate(context);
         let texture = Self {
             context: context.clone(),
             id,
+            _memory: context.track_texture_memory(
+                texture_memory(width, height, 1, 1, depth_data_byte_size::<T>()) * depth as u64,
+            ),
             width,
             height,
             depth,
@@ -55,6 +63,24 @@ impl DepthTexture2DArray {
         texture
     }
 
+    ///
+    /// Same as [DepthTexture2DArray::new] except that an error is returned instead of panicking if any of the dimensions is zero
+    /// or if the width or height is larger than the maximum texture size supported by the graphics driver.
+    ///
+    pub fn try_new<T: DepthTextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        depth: u32,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+    ) -> Result<Self, CoreError> {
+        validate_size(context, width, height, depth)?;
+        Ok(Self::new::<T>(
+            context, width, height, depth, wrap_s, wrap_t,
+        ))
+    }
+
     ///
     /// Returns a [DepthTarget] which can be used to clear, write to and read from the given layer of this texture.
     /// Combine this together with a [ColorTarget] with [RenderTarget::new] to be able to write to both a depth and color target at the same time.
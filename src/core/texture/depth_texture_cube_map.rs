@@ -6,6 +6,7 @@ use crate::core::texture::*;
 pub struct DepthTextureCubeMap {
     context: Context,
     id: crate::context::Texture,
+    _memory: TrackedMemory,
     width: u32,
     height: u32,
 }
@@ -14,6 +15,9 @@ impl DepthTextureCubeMap {
     ///
     /// Creates a new depth texture cube map.
     ///
+    /// # Panic
+    /// Will panic if the width or height is zero or larger than the maximum texture size, see [DepthTextureCubeMap::try_new].
+    ///
     pub fn new<T: DepthTextureDataType>(
         context: &Context,
         width: u32,
@@ -22,10 +26,14 @@ impl DepthTextureCubeMap {
         wrap_t: Wrapping,
         wrap_r: Wrapping,
     ) -> Self {
+        check_size(context, width, height, 1);
         let id = generate(context);
         let texture = Self {
             context: context.clone(),
             id,
+            _memory: context.track_texture_memory(
+                6 * texture_memory(width, height, 1, 1, depth_data_byte_size::<T>()),
+            ),
             width,
             height,
         };
@@ -52,6 +60,24 @@ impl DepthTextureCubeMap {
         texture
     }
 
+    ///
+    /// Same as [DepthTextureCubeMap::new] except that an error is returned instead of panicking if any of the dimensions is zero
+    /// or if the width or height is larger than the maximum texture size supported by the graphics driver.
+    ///
+    pub fn try_new<T: DepthTextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+        wrap_r: Wrapping,
+    ) -> Result<Self, CoreError> {
+        validate_size(context, width, height, 1)?;
+        Ok(Self::new::<T>(
+            context, width, height, wrap_s, wrap_t, wrap_r,
+        ))
+    }
+
     ///
     /// Returns a [DepthTarget] which can be used to clear, write to and read from the given side of this texture.
     /// Combine this together with a [ColorTarget] with [RenderTarget::new] to be able to write to both a depth and color target at the same time.
@@ -0,0 +1,117 @@
+use crate::core::texture::*;
+
+///
+/// Two 2D textures which are used alternately, such that new data is written into one texture while the other is used for rendering.
+/// This avoids that the GPU has to wait for a texture which is being sampled by rendering in progress before it can be updated,
+/// and that a frame is rendered with a partially updated texture, when the content is replaced every frame, for example with video frames or procedurally generated data.
+///
+/// Dereferences to the [Texture2D] which was updated last, so it can be used everywhere a texture is accepted.
+///
+pub struct StreamingTexture2D {
+    textures: [Texture2D; 2],
+    front: usize,
+    format: PixelFormat,
+}
+
+impl StreamingTexture2D {
+    ///
+    /// Constructs a new streaming texture with the given size in pixels and pixel format, linear interpolation and clamp to edge wrapping.
+    /// The content is undefined until the first update.
+    ///
+    pub fn new(context: &Context, width: u32, height: u32, format: PixelFormat) -> Self {
+        Self {
+            textures: [
+                new_texture(context, width, height, format),
+                new_texture(context, width, height, format),
+            ],
+            front: 0,
+            format,
+        }
+    }
+
+    ///
+    /// Writes the given bytes into the texture which is not currently used for rendering and then makes it the current texture.
+    /// The data is not copied before it is transferred to the GPU, see [Texture2D::fill_from_slice] for the expected data layout.
+    ///
+    /// # Panic
+    /// Will panic if the length of the data does not correspond to the size and the format given at construction.
+    ///
+    pub fn update(&mut self, data: &[u8]) {
+        let back = 1 - self.front;
+        self.textures[back].fill_from_slice(data);
+        self.front = back;
+    }
+
+    ///
+    /// Writes the given bytes, where the rows are `stride` bytes apart, into the texture which is not currently used for rendering and then makes it the current texture.
+    /// See [Texture2D::fill_subregion_with_stride].
+    ///
+    /// # Panic
+    /// Will panic if the stride is not a whole number of pixels or is smaller than a row, or if the data is too short.
+    ///
+    pub fn update_with_stride(&mut self, data: &[u8], stride: usize) {
+        let back = 1 - self.front;
+        let texture = &mut self.textures[back];
+        let (width, height) = (texture.width(), texture.height());
+        texture.fill_subregion_with_stride(0, 0, width, height, data, stride);
+        self.front = back;
+    }
+
+    ///
+    /// Returns the texture which was updated last.
+    ///
+    pub fn texture(&self) -> &Texture2D {
+        &self.textures[self.front]
+    }
+
+    ///
+    /// The pixel format given at construction.
+    ///
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+}
+
+impl std::ops::Deref for StreamingTexture2D {
+    type Target = Texture2D;
+    fn deref(&self) -> &Self::Target {
+        self.texture()
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    fn read(texture: &mut Texture2D) -> Vec<[u8; 4]> {
+        texture.as_color_target(None).read::<[u8; 4]>()
+    }
+
+    #[test]
+    fn test_update_with_stride() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut texture = StreamingTexture2D::new(&context, 2, 2, PixelFormat::Rgba8);
+
+        // The rows are given from the bottom to the top and each row is padded with 4 bytes
+        let data = [
+            1, 1, 1, 1, 2, 2, 2, 2, 0, 0, 0, 0, //
+            3, 3, 3, 3, 4, 4, 4, 4, 0, 0, 0, 0,
+        ];
+        texture.update_with_stride(&data, 12);
+        let front = texture.front;
+        // Read back gives the rows from the top to the bottom
+        assert_eq!(
+            read(&mut texture.textures[front]),
+            vec![[3; 4], [4; 4], [1; 4], [2; 4]]
+        );
+
+        texture.update(&[5; 16]);
+        assert_ne!(texture.front, front);
+        let back = texture.front;
+        texture.textures[back].fill_subregion(1, 1, 1, 1, &[6; 4]);
+        assert_eq!(
+            read(&mut texture.textures[back]),
+            vec![[5; 4], [6; 4], [5; 4], [5; 4]]
+        );
+    }
+}
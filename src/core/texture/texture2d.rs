@@ -6,15 +6,21 @@ use crate::core::texture::*;
 pub struct Texture2D {
     context: Context,
     id: crate::context::Texture,
+    _memory: TrackedMemory,
     width: u32,
     height: u32,
     number_of_mip_maps: u32,
     data_byte_size: usize,
+    internal_format: u32,
+    format: u32,
+    data_type: u32,
 }
 
 impl Texture2D {
     ///
     /// Construcs a new texture with the given data.
+    /// The data is stored as it is and, if it is a color, it is interpreted as sRGB by the built-in materials,
+    /// use [Texture2D::new_with_color_space] to have the GPU convert sRGB colors to linear colors when sampling the texture instead.
     ///
     pub fn new(context: &Context, cpu_texture: &CpuTexture) -> Self {
         match cpu_texture.data {
@@ -33,12 +39,78 @@ impl Texture2D {
         }
     }
 
+    ///
+    /// Constructs a new texture with the given data in the given color space.
+    /// 8-bit RGB and RGBA data in the [ColorSpace::Srgb] color space is stored in the SRGB8_ALPHA8 format, which the GPU converts to linear colors when sampling the texture,
+    /// and all other data is stored as it is, like with [Texture2D::new].
+    /// Use [ColorSpace::Srgb] for color textures, for example albedo and emissive textures, and [ColorSpace::Linear] for textures which contain data,
+    /// for example normal, metallic, roughness and occlusion textures.
+    ///
+    pub fn new_with_color_space(
+        context: &Context,
+        cpu_texture: &CpuTexture,
+        color_space: ColorSpace,
+    ) -> Self {
+        match (color_space, &cpu_texture.data) {
+            (ColorSpace::Srgb, TextureData::RgbU8(ref data)) => {
+                // There is no renderable 8-bit sRGB format without alpha, which is needed for generating mip maps
+                let data = data
+                    .iter()
+                    .map(|c| [c[0], c[1], c[2], u8::MAX])
+                    .collect::<Vec<_>>();
+                Self::new_with_data_and_format(
+                    context,
+                    cpu_texture,
+                    &data,
+                    crate::context::SRGB8_ALPHA8,
+                )
+            }
+            (ColorSpace::Srgb, TextureData::RgbaU8(ref data)) => Self::new_with_data_and_format(
+                context,
+                cpu_texture,
+                data,
+                crate::context::SRGB8_ALPHA8,
+            ),
+            _ => Self::new(context, cpu_texture),
+        }
+    }
+
+    ///
+    /// Returns [ColorSpace::Srgb] if the texture is stored in an sRGB format, in which case the GPU converts the colors to linear colors when sampling the texture,
+    /// and [ColorSpace::Linear] otherwise.
+    ///
+    pub fn color_space(&self) -> ColorSpace {
+        if self.internal_format == crate::context::SRGB8_ALPHA8 {
+            ColorSpace::Srgb
+        } else {
+            ColorSpace::Linear
+        }
+    }
+
+    ///
+    /// Replaces the content of this texture with the given data, for example when the image file it was loaded from has changed.
+    /// The texture is reallocated internally, so the size, format and sampling parameters can change, but this texture itself stays the same,
+    /// so everything owning it does not need to be updated. The new data is stored in the same [ColorSpace] as the old data.
+    ///
+    pub fn reload(&mut self, cpu_texture: &CpuTexture) {
+        *self = Self::new_with_color_space(&self.context, cpu_texture, self.color_space());
+    }
+
     fn new_with_data<T: TextureDataType>(
         context: &Context,
         cpu_texture: &CpuTexture,
         data: &[T],
     ) -> Self {
-        let mut texture = Self::new_empty::<T>(
+        Self::new_with_data_and_format(context, cpu_texture, data, T::internal_format())
+    }
+
+    fn new_with_data_and_format<T: TextureDataType>(
+        context: &Context,
+        cpu_texture: &CpuTexture,
+        data: &[T],
+        internal_format: u32,
+    ) -> Self {
+        let mut texture = Self::new_empty_with_format::<T>(
             context,
             cpu_texture.width,
             cpu_texture.height,
@@ -47,6 +119,7 @@ impl Texture2D {
             cpu_texture.mip_map_filter,
             cpu_texture.wrap_s,
             cpu_texture.wrap_t,
+            internal_format,
         );
         texture.fill(data);
         texture
@@ -57,6 +130,10 @@ impl Texture2D {
     /// The format is determined by the generic [TextureDataType] parameter
     /// (for example, if [u8; 4] is specified, the format is RGBA and the data type is byte).
     ///
+    /// # Panic
+    /// Will panic if the data type is an integer type (see [TextureDataType]) and the texture is not using [Interpolation::Nearest] without mip maps.
+    /// Will panic if the width or height is zero or larger than the maximum texture size, see [Texture2D::try_new_empty].
+    ///
     pub fn new_empty<T: TextureDataType>(
         context: &Context,
         width: u32,
@@ -67,15 +144,140 @@ impl Texture2D {
         wrap_s: Wrapping,
         wrap_t: Wrapping,
     ) -> Self {
+        Self::new_empty_with_format::<T>(
+            context,
+            width,
+            height,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+            T::internal_format(),
+        )
+    }
+
+    ///
+    /// Same as [Texture2D::new_empty] except that an error is returned instead of panicking if any of the dimensions is zero
+    /// or if the width or height is larger than the maximum texture size supported by the graphics driver.
+    ///
+    pub fn try_new_empty<T: TextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+    ) -> Result<Self, CoreError> {
+        validate_size(context, width, height, 1)?;
+        Ok(Self::new_empty::<T>(
+            context,
+            width,
+            height,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+        ))
+    }
+
+    fn new_empty_with_format<T: TextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+        internal_format: u32,
+    ) -> Self {
+        check_filtering::<T>(min_filter, mag_filter, mip_map_filter);
+        Self::new_empty_with_parameters(
+            context,
+            width,
+            height,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+            internal_format,
+            format_from_data_type::<T>(),
+            std::mem::size_of::<T>(),
+            T::data_type(),
+        )
+    }
+
+    ///
+    /// Returns whether this texture has the same format as the given texture.
+    ///
+    pub(crate) fn has_same_format(&self, other: &Texture2D) -> bool {
+        self.internal_format == other.internal_format
+            && self.format == other.format
+            && self.data_type == other.data_type
+    }
+
+    ///
+    /// Constructs a new empty 2D texture with the same format as this texture, the given size, linear interpolation without mip maps and clamp to edge wrapping.
+    ///
+    pub(crate) fn new_empty_with_same_format(&self, width: u32, height: u32) -> Self {
+        if is_integer_data_type(self.data_type) {
+            panic!("integer textures are not filterable and must use nearest interpolation without mip maps")
+        }
+        Self::new_empty_with_parameters(
+            &self.context,
+            width,
+            height,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            self.internal_format,
+            self.format,
+            self.data_byte_size,
+            self.data_type,
+        )
+    }
+
+    fn new_empty_with_parameters(
+        context: &Context,
+        width: u32,
+        height: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+        internal_format: u32,
+        format: u32,
+        data_byte_size: usize,
+        data_type: u32,
+    ) -> Self {
+        check_size(context, width, height, 1);
         let id = generate(context);
         let number_of_mip_maps = calculate_number_of_mip_maps(mip_map_filter, width, height, None);
         let texture = Self {
             context: context.clone(),
             id,
+            _memory: context.track_texture_memory(texture_memory(
+                width,
+                height,
+                1,
+                number_of_mip_maps,
+                data_byte_size,
+            )),
             width,
             height,
             number_of_mip_maps,
-            data_byte_size: std::mem::size_of::<T>(),
+            data_byte_size,
+            internal_format,
+            format,
+            data_type,
         };
         texture.bind();
         set_parameters(
@@ -96,7 +298,7 @@ impl Texture2D {
             context.tex_storage_2d(
                 crate::context::TEXTURE_2D,
                 number_of_mip_maps as i32,
-                T::internal_format(),
+                internal_format,
                 width as i32,
                 height as i32,
             );
@@ -113,6 +315,53 @@ impl Texture2D {
     /// It is therefore necessary to create a new texture if the texture size or format has changed.
     ///
     pub fn fill<T: TextureDataType>(&mut self, data: &[T]) {
+        self.fill_shared(data);
+    }
+
+    ///
+    /// Uploads the data of the given [CpuTexture] into this texture without reallocating it and returns `true`,
+    /// if the data has the same size and format as this texture, otherwise the texture is unchanged and `false` is returned.
+    /// The sampling parameters of this texture are kept.
+    /// Contrary to [Texture2D::reload], this only needs a shared reference, so it also updates a texture which is shared, see [crate::renderer::Texture2DRef::reload].
+    ///
+    pub(crate) fn refill(&self, cpu_texture: &CpuTexture) -> bool {
+        if cpu_texture.width != self.width || cpu_texture.height != self.height {
+            return false;
+        }
+        match cpu_texture.data {
+            TextureData::RgbU8(ref data)
+                if self.internal_format == crate::context::SRGB8_ALPHA8 =>
+            {
+                let data = data
+                    .iter()
+                    .map(|c| [c[0], c[1], c[2], u8::MAX])
+                    .collect::<Vec<_>>();
+                self.refill_with_data(&data)
+            }
+            TextureData::RU8(ref data) => self.refill_with_data(data),
+            TextureData::RgU8(ref data) => self.refill_with_data(data),
+            TextureData::RgbU8(ref data) => self.refill_with_data(data),
+            TextureData::RgbaU8(ref data) => self.refill_with_data(data),
+            TextureData::RF16(ref data) => self.refill_with_data(data),
+            TextureData::RgF16(ref data) => self.refill_with_data(data),
+            TextureData::RgbF16(ref data) => self.refill_with_data(data),
+            TextureData::RgbaF16(ref data) => self.refill_with_data(data),
+            TextureData::RF32(ref data) => self.refill_with_data(data),
+            TextureData::RgF32(ref data) => self.refill_with_data(data),
+            TextureData::RgbF32(ref data) => self.refill_with_data(data),
+            TextureData::RgbaF32(ref data) => self.refill_with_data(data),
+        }
+    }
+
+    fn refill_with_data<T: TextureDataType>(&self, data: &[T]) -> bool {
+        if format_from_data_type::<T>() != self.format || T::data_type() != self.data_type {
+            return false;
+        }
+        self.fill_shared(data);
+        true
+    }
+
+    fn fill_shared<T: TextureDataType>(&self, data: &[T]) {
         check_data_length::<T>(self.width, self.height, 1, self.data_byte_size, data.len());
         self.bind();
         let mut data = data.to_owned();
@@ -133,6 +382,148 @@ impl Texture2D {
         self.generate_mip_maps();
     }
 
+    ///
+    /// Fills this texture with the given bytes without copying or converting them first, which avoids an allocation when the texture is updated often,
+    /// for example with frames from a video decoder or procedurally generated data.
+    /// The bytes must be tightly packed in the format of this texture (for example 4 bytes per pixel for a `[u8; 4]` texture)
+    /// and, unlike [Texture2D::fill], the rows are ordered from the bottom to the top, since the rows are not flipped.
+    ///
+    /// # Panic
+    /// Will panic if the length of the data does not correspond to the width, height and format specified at construction.
+    ///
+    pub fn fill_from_slice(&mut self, data: &[u8]) {
+        check_data_length::<u8>(self.width, self.height, 1, self.data_byte_size, data.len());
+        self.fill_subregion_with_stride(
+            0,
+            0,
+            self.width,
+            self.height,
+            data,
+            self.width as usize * self.data_byte_size,
+        );
+    }
+
+    ///
+    /// Fills the region of this texture with the given lower left corner and size with the given tightly packed bytes, without changing the rest of the texture.
+    /// See [Texture2D::fill_from_slice] for the expected data layout.
+    ///
+    /// # Panic
+    /// Will panic if the region is not inside the texture or if the length of the data does not correspond to the size of the region.
+    ///
+    pub fn fill_subregion(&mut self, x: u32, y: u32, width: u32, height: u32, data: &[u8]) {
+        let row_size = width as usize * self.data_byte_size;
+        if data.len() != row_size * height as usize {
+            panic!(
+                "invalid size of texture data (expected {} bytes but got {} bytes)",
+                row_size * height as usize,
+                data.len()
+            );
+        }
+        self.fill_subregion_with_stride(x, y, width, height, data, row_size);
+    }
+
+    ///
+    /// Fills the region of this texture with the given lower left corner and size with the given bytes like [Texture2D::fill_subregion],
+    /// except that the rows in the data are `stride` bytes apart, which is useful when the rows are padded,
+    /// for example by a video decoder, or when the region is part of a larger image.
+    ///
+    /// # Panic
+    /// Will panic if the region is not inside the texture, if the stride is not a whole number of pixels
+    /// or is smaller than a row of the region, or if the data is too short.
+    ///
+    pub fn fill_subregion_with_stride(
+        &mut self,
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+        data: &[u8],
+        stride: usize,
+    ) {
+        if x + width > self.width || y + height > self.height {
+            panic!(
+                "the region at ({}, {}) with size {}x{} is outside the {}x{} texture",
+                x, y, width, height, self.width, self.height
+            );
+        }
+        let row_size = width as usize * self.data_byte_size;
+        if stride % self.data_byte_size != 0 || stride < row_size {
+            panic!(
+                "the stride ({} bytes) must be a whole number of pixels of {} bytes and at least the size of a row ({} bytes)",
+                stride, self.data_byte_size, row_size
+            );
+        }
+        let expected_bytes = if height > 0 {
+            stride * (height as usize - 1) + row_size
+        } else {
+            0
+        };
+        if data.len() < expected_bytes {
+            panic!(
+                "invalid size of texture data (expected at least {} bytes but got {} bytes)",
+                expected_bytes,
+                data.len()
+            );
+        }
+        if width == 0 || height == 0 {
+            return;
+        }
+        self.bind();
+        unsafe {
+            self.context.pixel_store_i32(
+                crate::context::UNPACK_ROW_LENGTH,
+                (stride / self.data_byte_size) as i32,
+            );
+            self.context.tex_sub_image_2d(
+                crate::context::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                self.format,
+                self.data_type,
+                crate::context::PixelUnpackData::Slice(&data[..expected_bytes]),
+            );
+            self.context
+                .pixel_store_i32(crate::context::UNPACK_ROW_LENGTH, 0);
+        }
+        self.generate_mip_maps();
+    }
+
+    ///
+    /// Fills this texture with the given data like [Texture2D::fill], except that the data is first copied into a pixel buffer object
+    /// from which the GPU copies the data into the texture asynchronously, so the CPU does not have to wait for the transfer to finish.
+    /// Use the returned [GpuUpload] to check if the upload is complete. The texture can be used right away, but using it before the upload is complete might stall the pipeline.
+    /// Returns an error if the pixel buffer object could not be mapped into client memory, in which case the texture is not changed.
+    ///
+    /// # Panic
+    /// Will panic if the length of the data does not correspond to the width, height and format specified at construction.
+    ///
+    pub fn upload_async<T: TextureDataType>(&mut self, data: &[T]) -> Result<GpuUpload, CoreError> {
+        check_data_length::<T>(self.width, self.height, 1, self.data_byte_size, data.len());
+        let mut data = data.to_owned();
+        flip_y(&mut data, self.width as usize, self.height as usize);
+        let upload = GpuUpload::new(&self.context, to_byte_slice(&data), || {
+            self.bind();
+            unsafe {
+                self.context.tex_sub_image_2d(
+                    crate::context::TEXTURE_2D,
+                    0,
+                    0,
+                    0,
+                    self.width as i32,
+                    self.height as i32,
+                    format_from_data_type::<T>(),
+                    T::data_type(),
+                    crate::context::PixelUnpackData::BufferOffset(0),
+                );
+            }
+        })?;
+        self.generate_mip_maps();
+        Ok(upload)
+    }
+
     ///
     /// Returns a [ColorTarget] which can be used to clear, write to and read from the given mip level of this texture.
     /// Combine this together with a [DepthTarget] with [RenderTarget::new] to be able to write to both a depth and color target at the same time.
@@ -141,7 +532,12 @@ impl Texture2D {
     ///
     /// **Note:** [DepthTest] is disabled if not also writing to a depth texture.
     ///
+    /// # Panic
+    /// Will panic if the format of this texture cannot be rendered into on the current platform,
+    /// for example a texture with three float or integer channels on WebGL2 and OpenGL ES.
+    ///
     pub fn as_color_target(&mut self, mip_level: Option<u32>) -> ColorTarget<'_> {
+        check_color_renderable(&self.context, self.internal_format);
         ColorTarget::new_texture2d(&self.context, self, mip_level)
     }
 
@@ -155,6 +551,17 @@ impl Texture2D {
         self.height
     }
 
+    ///
+    /// Returns the GL data type of each channel if this is an integer texture (see [TextureDataType]), otherwise `None`.
+    ///
+    pub(in crate::core) fn integer_data_type(&self) -> Option<u32> {
+        if is_integer_data_type(self.data_type) {
+            Some(self.data_type)
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn generate_mip_maps(&self) {
         if self.number_of_mip_maps > 1 {
             self.bind();
@@ -190,3 +597,35 @@ impl Drop for Texture2D {
         }
     }
 }
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_try_new_empty() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let new = |width, height| {
+            Texture2D::try_new_empty::<[u8; 4]>(
+                &context,
+                width,
+                height,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        assert!(matches!(
+            new(0, 16),
+            Err(CoreError::InvalidTextureSize(0, 16, 1, _))
+        ));
+        assert!(matches!(
+            new(1 << 20, 1),
+            Err(CoreError::InvalidTextureSize(..))
+        ));
+        let texture = new(16, 8).unwrap();
+        assert_eq!((texture.width(), texture.height()), (16, 8));
+    }
+}
@@ -9,6 +9,7 @@ use crate::core::texture::*;
 pub struct Texture2DArray {
     context: Context,
     id: crate::context::Texture,
+    _memory: TrackedMemory,
     width: u32,
     height: u32,
     depth: u32,
@@ -145,6 +146,9 @@ impl Texture2DArray {
     ///
     /// Creates a new array of 2D textures.
     ///
+    /// # Panic
+    /// Will panic if any of the dimensions is zero or if the width or height is larger than the maximum texture size, see [Texture2DArray::try_new_empty].
+    ///
     pub fn new_empty<T: TextureDataType>(
         context: &Context,
         width: u32,
@@ -156,11 +160,21 @@ impl Texture2DArray {
         wrap_s: Wrapping,
         wrap_t: Wrapping,
     ) -> Self {
+        check_size(context, width, height, depth);
         let id = generate(context);
         let number_of_mip_maps = calculate_number_of_mip_maps(mip_map_filter, width, height, None);
         let texture = Self {
             context: context.clone(),
             id,
+            _memory: context.track_texture_memory(
+                texture_memory(
+                    width,
+                    height,
+                    1,
+                    number_of_mip_maps,
+                    std::mem::size_of::<T>(),
+                ) * depth as u64,
+            ),
             width,
             height,
             depth,
@@ -196,6 +210,35 @@ impl Texture2DArray {
         texture
     }
 
+    ///
+    /// Same as [Texture2DArray::new_empty] except that an error is returned instead of panicking if any of the dimensions is zero
+    /// or if the width or height is larger than the maximum texture size supported by the graphics driver.
+    ///
+    pub fn try_new_empty<T: TextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        depth: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+    ) -> Result<Self, CoreError> {
+        validate_size(context, width, height, depth)?;
+        Ok(Self::new_empty::<T>(
+            context,
+            width,
+            height,
+            depth,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+        ))
+    }
+
     ///
     /// Fills the texture array with the given pixel data.
     ///
@@ -3,6 +3,7 @@ use crate::core::texture::*;
 pub struct Texture2DMultisample {
     context: Context,
     id: crate::context::Renderbuffer,
+    _memory: TrackedMemory,
     width: u32,
     height: u32,
     number_of_samples: u32,
@@ -15,6 +16,7 @@ impl Texture2DMultisample {
         height: u32,
         number_of_samples: u32,
     ) -> Self {
+        check_size(context, width, height, 1);
         let id = unsafe {
             context
                 .create_renderbuffer()
@@ -23,6 +25,10 @@ impl Texture2DMultisample {
         let texture = Self {
             context: context.clone(),
             id,
+            _memory: context.track_texture_memory(
+                texture_memory(width, height, 1, 1, std::mem::size_of::<T>())
+                    * number_of_samples as u64,
+            ),
             width,
             height,
             number_of_samples,
@@ -5,6 +5,7 @@ use crate::core::texture::*;
 pub struct Texture3D {
     context: Context,
     id: crate::context::Texture,
+    _memory: TrackedMemory,
     width: u32,
     height: u32,
     depth: u32,
@@ -57,6 +58,9 @@ impl Texture3D {
     ///
     /// Creates a new empty 3D color texture.
     ///
+    /// # Panic
+    /// Will panic if any of the dimensions is zero or if the width or height is larger than the maximum texture size, see [Texture3D::try_new_empty].
+    ///
     pub fn new_empty<T: TextureDataType>(
         context: &Context,
         width: u32,
@@ -69,12 +73,20 @@ impl Texture3D {
         wrap_t: Wrapping,
         wrap_r: Wrapping,
     ) -> Self {
+        check_size(context, width, height, depth);
         let id = generate(context);
         let number_of_mip_maps =
             calculate_number_of_mip_maps(mip_map_filter, width, height, Some(depth));
         let texture = Self {
             context: context.clone(),
             id,
+            _memory: context.track_texture_memory(texture_memory(
+                width,
+                height,
+                depth,
+                number_of_mip_maps,
+                std::mem::size_of::<T>(),
+            )),
             width,
             height,
             depth,
@@ -110,6 +122,37 @@ impl Texture3D {
         texture
     }
 
+    ///
+    /// Same as [Texture3D::new_empty] except that an error is returned instead of panicking if any of the dimensions is zero
+    /// or if the width or height is larger than the maximum texture size supported by the graphics driver.
+    ///
+    pub fn try_new_empty<T: TextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        depth: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+        wrap_r: Wrapping,
+    ) -> Result<Self, CoreError> {
+        validate_size(context, width, height, depth)?;
+        Ok(Self::new_empty::<T>(
+            context,
+            width,
+            height,
+            depth,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+            wrap_r,
+        ))
+    }
+
     ///
     /// Fills this texture with the given data.
     ///
@@ -107,6 +107,7 @@ impl CubeMapSide {
 pub struct TextureCubeMap {
     context: Context,
     id: crate::context::Texture,
+    _memory: TrackedMemory,
     width: u32,
     height: u32,
     number_of_mip_maps: u32,
@@ -300,6 +301,9 @@ impl TextureCubeMap {
     ///
     /// Creates a new texture cube map.
     ///
+    /// # Panic
+    /// Will panic if the width or height is zero or larger than the maximum texture size, see [TextureCubeMap::try_new_empty].
+    ///
     pub fn new_empty<T: TextureDataType>(
         context: &Context,
         width: u32,
@@ -311,11 +315,21 @@ impl TextureCubeMap {
         wrap_t: Wrapping,
         wrap_r: Wrapping,
     ) -> Self {
+        check_size(context, width, height, 1);
         let id = generate(context);
         let number_of_mip_maps = calculate_number_of_mip_maps(mip_map_filter, width, height, None);
         let texture = Self {
             context: context.clone(),
             id,
+            _memory: context.track_texture_memory(
+                6 * texture_memory(
+                    width,
+                    height,
+                    1,
+                    number_of_mip_maps,
+                    std::mem::size_of::<T>(),
+                ),
+            ),
             width,
             height,
             number_of_mip_maps,
@@ -350,6 +364,35 @@ impl TextureCubeMap {
         texture
     }
 
+    ///
+    /// Same as [TextureCubeMap::new_empty] except that an error is returned instead of panicking if any of the dimensions is zero
+    /// or if the width or height is larger than the maximum texture size supported by the graphics driver.
+    ///
+    pub fn try_new_empty<T: TextureDataType>(
+        context: &Context,
+        width: u32,
+        height: u32,
+        min_filter: Interpolation,
+        mag_filter: Interpolation,
+        mip_map_filter: Option<Interpolation>,
+        wrap_s: Wrapping,
+        wrap_t: Wrapping,
+        wrap_r: Wrapping,
+    ) -> Result<Self, CoreError> {
+        validate_size(context, width, height, 1)?;
+        Ok(Self::new_empty::<T>(
+            context,
+            width,
+            height,
+            min_filter,
+            mag_filter,
+            mip_map_filter,
+            wrap_s,
+            wrap_t,
+            wrap_r,
+        ))
+    }
+
     ///
     /// Fills the cube map texture with the given pixel data for the 6 images.
     ///
@@ -508,6 +551,80 @@ impl TextureCubeMap {
         ColorTarget::new_texture_cube_map(&self.context, self, sides, mip_level)
     }
 
+    ///
+    /// Clears the given side of this texture and a temporary depth buffer using the given clear state and then calls the `render` closure
+    /// in which everything rendered, for example using [Object::render](crate::renderer::Object::render), ends up on that side of the cube map.
+    /// Mip maps are generated afterwards if a mip map filter is specified.
+    /// See [TextureCubeMap::render_probe] for rendering all six sides from a given position.
+    ///
+    pub fn render_to_face(
+        &mut self,
+        side: CubeMapSide,
+        clear_state: ClearState,
+        render: impl FnOnce(),
+    ) {
+        let mut depth_texture = self.new_depth_texture();
+        self.render_to_face_with_depth(side, &mut depth_texture, clear_state, render);
+    }
+
+    ///
+    /// Renders the scene surrounding the given position into all six sides of this texture, for example to make a dynamic reflection probe
+    /// which can be used as an environment map.
+    /// For each side, the side is cleared using the given clear state and the `render` closure is called with a camera placed at the given position,
+    /// looking in the direction of that side with a 90 degrees field of view and a viewport matching the size of the texture.
+    /// Objects should be rendered with the given camera inside the closure, for example using [Object::render](crate::renderer::Object::render).
+    ///
+    pub fn render_probe(
+        &mut self,
+        position: Vec3,
+        z_near: f32,
+        z_far: f32,
+        clear_state: ClearState,
+        render: impl Fn(&Camera),
+    ) {
+        let mut depth_texture = self.new_depth_texture();
+        let viewport = Viewport::new_at_origo(self.width, self.height);
+        for side in CubeMapSide::iter() {
+            let camera = Camera::new_perspective(
+                viewport,
+                position,
+                position + side.direction(),
+                side.up(),
+                degrees(90.0),
+                z_near,
+                z_far,
+            );
+            self.render_to_face_with_depth(side, &mut depth_texture, clear_state, || {
+                render(&camera)
+            });
+        }
+    }
+
+    fn render_to_face_with_depth(
+        &mut self,
+        side: CubeMapSide,
+        depth_texture: &mut DepthTexture2D,
+        clear_state: ClearState,
+        render: impl FnOnce(),
+    ) {
+        RenderTarget::new(
+            self.as_color_target(&[side], None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(clear_state)
+        .write(render);
+    }
+
+    fn new_depth_texture(&self) -> DepthTexture2D {
+        DepthTexture2D::new::<f32>(
+            &self.context,
+            self.width,
+            self.height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        )
+    }
+
     /// The width of this texture.
     pub fn width(&self) -> u32 {
         self.width
@@ -0,0 +1,158 @@
+use crate::core::texture::*;
+
+///
+/// The format of the pixel data of a video frame given to [VideoTexture::upload_frame].
+/// Each channel is one byte.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelFormat {
+    /// One channel, for example a luminance (Y) plane. Note that the value is sampled as the red channel in a shader.
+    R8,
+    /// Two channels, for example the interleaved chroma (UV) plane of a video frame in the NV12 format. Note that the values are sampled as the red and green channels in a shader.
+    Rg8,
+    /// Three channels; red, green and blue.
+    Rgb8,
+    /// Four channels; red, green, blue and alpha.
+    Rgba8,
+}
+
+impl PixelFormat {
+    ///
+    /// The number of bytes per pixel.
+    ///
+    pub fn bytes_per_pixel(&self) -> usize {
+        match self {
+            Self::R8 => 1,
+            Self::Rg8 => 2,
+            Self::Rgb8 => 3,
+            Self::Rgba8 => 4,
+        }
+    }
+}
+
+///
+/// A 2D texture which content is replaced each frame with a new video frame, for example from a video file or a camera.
+/// The decoding of the video is the responsibility of the caller, this texture only transfers the decoded frames to the GPU
+/// using an asynchronous upload through a pixel buffer object (see [Texture2D::upload_async]) so the CPU does not have to wait for the transfer.
+///
+/// Dereferences to a [Texture2D] so it can be used everywhere a texture is accepted.
+///
+pub struct VideoTexture {
+    context: Context,
+    texture: Texture2D,
+    format: PixelFormat,
+    upload: Option<GpuUpload>,
+}
+
+impl VideoTexture {
+    ///
+    /// Constructs a new video texture with the given size in pixels. The content is black until the first frame is uploaded.
+    ///
+    pub fn new(context: &Context, width: u32, height: u32) -> Self {
+        let format = PixelFormat::Rgba8;
+        let mut texture = new_texture(context, width, height, format);
+        texture.fill(&vec![[0u8, 0, 0, 255]; (width * height) as usize]);
+        Self {
+            context: context.clone(),
+            texture,
+            format,
+            upload: None,
+        }
+    }
+
+    ///
+    /// Uploads a new decoded video frame with the size given at construction.
+    /// The rows of the frame are expected to be ordered from top to bottom without any padding between rows.
+    /// The upload happens asynchronously, use [VideoTexture::is_frame_uploaded] to check if the GPU has finished copying the frame into the texture.
+    /// Returns an error if the frame could not be copied into the pixel buffer object used for the upload, see [Texture2D::upload_async].
+    ///
+    /// # Panic
+    /// Will panic if the length of the data does not correspond to the size given at construction and the given format.
+    ///
+    pub fn upload_frame(&mut self, data: &[u8], format: PixelFormat) -> Result<(), CoreError> {
+        let width = self.texture.width();
+        let height = self.texture.height();
+        if data.len() != width as usize * height as usize * format.bytes_per_pixel() {
+            panic!(
+                "the length of the frame data ({}) does not correspond to a {}x{} frame with the format {:?}",
+                data.len(),
+                width,
+                height,
+                format
+            );
+        }
+        if format != self.format {
+            self.upload = None;
+            self.texture = new_texture(&self.context, width, height, format);
+            self.format = format;
+        }
+        self.upload = Some(match format {
+            PixelFormat::R8 => self.texture.upload_async::<u8>(data)?,
+            PixelFormat::Rg8 => self
+                .texture
+                .upload_async::<[u8; 2]>(from_byte_slice(data))?,
+            PixelFormat::Rgb8 => self
+                .texture
+                .upload_async::<[u8; 3]>(from_byte_slice(data))?,
+            PixelFormat::Rgba8 => self
+                .texture
+                .upload_async::<[u8; 4]>(from_byte_slice(data))?,
+        });
+        Ok(())
+    }
+
+    ///
+    /// Returns true if the GPU has finished copying the last uploaded frame into the texture or if no frame has been uploaded yet.
+    ///
+    pub fn is_frame_uploaded(&self) -> bool {
+        self.upload
+            .as_ref()
+            .map(|upload| upload.is_complete())
+            .unwrap_or(true)
+    }
+
+    ///
+    /// The format of the last uploaded frame.
+    ///
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+}
+
+impl std::ops::Deref for VideoTexture {
+    type Target = Texture2D;
+    fn deref(&self) -> &Self::Target {
+        &self.texture
+    }
+}
+
+pub(super) fn new_texture(
+    context: &Context,
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+) -> Texture2D {
+    match format {
+        PixelFormat::R8 => new_texture_with_type::<u8>(context, width, height),
+        PixelFormat::Rg8 => new_texture_with_type::<[u8; 2]>(context, width, height),
+        PixelFormat::Rgb8 => new_texture_with_type::<[u8; 3]>(context, width, height),
+        PixelFormat::Rgba8 => new_texture_with_type::<[u8; 4]>(context, width, height),
+    }
+}
+
+fn new_texture_with_type<T: TextureDataType>(
+    context: &Context,
+    width: u32,
+    height: u32,
+) -> Texture2D {
+    Texture2D::new_empty::<T>(
+        context,
+        width,
+        height,
+        Interpolation::Linear,
+        Interpolation::Linear,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
@@ -0,0 +1,221 @@
+use crate::core::*;
+
+///
+/// The type of primitives which are processed by a [TransformFeedbackProgram].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TransformFeedbackMode {
+    /// Each vertex is processed separately, which is the most common mode, for example when updating particles.
+    Points,
+    /// Each pair of vertices form a line.
+    Lines,
+    /// Each triplet of vertices form a triangle.
+    Triangles,
+}
+
+impl TransformFeedbackMode {
+    fn primitive_mode(&self) -> u32 {
+        match self {
+            Self::Points => crate::context::POINTS,
+            Self::Lines => crate::context::LINES,
+            Self::Triangles => crate::context::TRIANGLES,
+        }
+    }
+}
+
+///
+/// A shader program consisting of only a vertex shader, where the output variables of the vertex shader are captured into buffers instead of being rasterized.
+/// This makes it possible to update for example particles on the GPU each frame without transferring the data to and from the CPU
+/// and is a lighter alternative to a [ComputeProgram] which is also available on WebGL2 and OpenGL ES 3.0.
+///
+/// The captured output is typically used as input to the next update, by swapping the input and output buffers each frame,
+/// and/or as input for rendering, for example as instance attributes.
+///
+/// The following steps are needed to run the program:
+/// - Send uniforms and attributes using for example [TransformFeedbackProgram::use_uniform] and [TransformFeedbackProgram::use_vertex_attribute].
+/// - Specify where to capture the output using [TransformFeedbackProgram::use_output_buffer] or [TransformFeedbackProgram::use_output_instance_buffer].
+/// - Call [TransformFeedbackProgram::begin], one or more calls to [TransformFeedbackProgram::draw_arrays] and then [TransformFeedbackProgram::end].
+///
+pub struct TransformFeedbackProgram {
+    context: Context,
+    program: Program,
+    output_count: u32,
+    mode: Option<TransformFeedbackMode>,
+}
+
+impl TransformFeedbackProgram {
+    ///
+    /// Creates a new transform feedback program from the given glsl vertex shader source.
+    /// The output variables which should be captured are given by name, for example `&["outPosition", "outVelocity"]`,
+    /// and the output variable at index `i` is captured into the buffer given to [TransformFeedbackProgram::use_output_buffer] with index `i`.
+    ///
+    pub fn new(
+        context: &Context,
+        vertex_shader_source: &str,
+        output_varyings: &[&str],
+    ) -> Result<Self, CoreError> {
+        let header = shader_header(context);
+        let program = Program::from_shader_sources(
+            context,
+            &[
+                (
+                    crate::context::VERTEX_SHADER,
+                    "vertex",
+                    format!("{}{}", header, vertex_shader_source),
+                ),
+                (
+                    crate::context::FRAGMENT_SHADER,
+                    "fragment",
+                    format!(
+                        "{}layout (location = 0) out vec4 outColor;\nvoid main() {{ outColor = vec4(0.0); }}",
+                        header
+                    ),
+                ),
+            ],
+            output_varyings,
+        )?;
+        Ok(Self {
+            context: context.clone(),
+            program,
+            output_count: output_varyings.len() as u32,
+            mode: None,
+        })
+    }
+
+    ///
+    /// Send the given uniform data to this program and associate it with the given named variable (see [Program::use_uniform]).
+    ///
+    /// # Panic
+    /// Will panic if the uniform is not defined or not used in the shader code.
+    /// In the latter case the variable is removed by the shader compiler.
+    /// Will also panic if called between [TransformFeedbackProgram::begin] and [TransformFeedbackProgram::end].
+    ///
+    pub fn use_uniform<T: UniformDataType>(&self, name: &str, data: T) {
+        self.check_not_active();
+        self.program.use_uniform(name, data);
+    }
+
+    ///
+    /// Calls [TransformFeedbackProgram::use_uniform] if [TransformFeedbackProgram::requires_uniform] returns true.
+    ///
+    pub fn use_uniform_if_required<T: UniformDataType>(&self, name: &str, data: T) {
+        if self.requires_uniform(name) {
+            self.use_uniform(name, data);
+        }
+    }
+
+    ///
+    /// Returns true if this program uses the uniform with the given name.
+    ///
+    pub fn requires_uniform(&self, name: &str) -> bool {
+        self.program.requires_uniform(name)
+    }
+
+    ///
+    /// Uses the given [VertexBuffer] data in this program and associates it with the given named variable (see [Program::use_vertex_attribute]).
+    ///
+    /// # Panic
+    /// Will panic if the attribute is not defined in the shader code or not used.
+    /// In the latter case the variable is removed by the shader compiler.
+    /// Will also panic if called between [TransformFeedbackProgram::begin] and [TransformFeedbackProgram::end].
+    ///
+    pub fn use_vertex_attribute(&self, name: &str, buffer: &VertexBuffer) {
+        self.check_not_active();
+        self.program.use_vertex_attribute(name, buffer);
+    }
+
+    ///
+    /// Captures the output variable with the given index, as specified at construction, into the given vertex buffer.
+    /// The buffer must have been filled with data of the same type as the output variable and must have room for the output of all vertices,
+    /// output that does not fit into the buffer is discarded.
+    ///
+    /// # Panic
+    /// Will panic if the index is not smaller than the number of output variables
+    /// or if called between [TransformFeedbackProgram::begin] and [TransformFeedbackProgram::end].
+    ///
+    pub fn use_output_buffer(&self, index: u32, buffer: &mut VertexBuffer) {
+        self.check_output_index(index);
+        buffer.bind_transform_feedback(index);
+    }
+
+    ///
+    /// Same as [TransformFeedbackProgram::use_output_buffer] except that the output is captured into an [InstanceBuffer],
+    /// so it can be used directly as per instance data when rendering, for example the positions of particles.
+    ///
+    /// # Panic
+    /// Will panic if the index is not smaller than the number of output variables
+    /// or if called between [TransformFeedbackProgram::begin] and [TransformFeedbackProgram::end].
+    ///
+    pub fn use_output_instance_buffer(&self, index: u32, buffer: &mut InstanceBuffer) {
+        self.check_output_index(index);
+        buffer.bind_transform_feedback(index);
+    }
+
+    ///
+    /// Begins capturing the output of the vertex shader. The primitives drawn by [TransformFeedbackProgram::draw_arrays] are of the given type.
+    /// Nothing is rasterized until [TransformFeedbackProgram::end] is called.
+    ///
+    /// # Panic
+    /// Will panic if [TransformFeedbackProgram::begin] has already been called without a call to [TransformFeedbackProgram::end].
+    ///
+    pub fn begin(&mut self, mode: TransformFeedbackMode) {
+        self.check_not_active();
+        self.program.begin_transform_feedback(mode.primitive_mode());
+        self.mode = Some(mode);
+    }
+
+    ///
+    /// Runs the vertex shader for the given number of vertices and captures the output.
+    ///
+    /// # Panic
+    /// Will panic if not called between [TransformFeedbackProgram::begin] and [TransformFeedbackProgram::end].
+    ///
+    pub fn draw_arrays(&self, count: u32) {
+        let mode = self
+            .mode
+            .expect("transform feedback must begin before drawing");
+        self.program
+            .draw_transform_feedback(mode.primitive_mode(), count);
+    }
+
+    ///
+    /// Ends capturing the output of the vertex shader, after which the output buffers can be used for rendering or as input to the next update.
+    ///
+    /// # Panic
+    /// Will panic if [TransformFeedbackProgram::begin] has not been called.
+    ///
+    pub fn end(&mut self) {
+        if self.mode.take().is_none() {
+            panic!("transform feedback must begin before it can end");
+        }
+        self.program.end_transform_feedback();
+        unsafe {
+            for index in 0..self.output_count {
+                self.context.bind_buffer_base(
+                    crate::context::TRANSFORM_FEEDBACK_BUFFER,
+                    index,
+                    None,
+                );
+            }
+            self.context
+                .bind_buffer(crate::context::TRANSFORM_FEEDBACK_BUFFER, None);
+        }
+    }
+
+    fn check_not_active(&self) {
+        if self.mode.is_some() {
+            panic!("cannot change the state of a transform feedback program between begin and end");
+        }
+    }
+
+    fn check_output_index(&self, index: u32) {
+        self.check_not_active();
+        if index >= self.output_count {
+            panic!(
+                "the output index {} is outside the expected range [0, {}]",
+                index,
+                self.output_count as i32 - 1
+            );
+        }
+    }
+}
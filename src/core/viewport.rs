@@ -0,0 +1,79 @@
+use crate::core::*;
+
+///
+/// Additional functionality for a [Viewport], mainly conversions between physical pixels, which is what render targets, textures and scissor boxes use,
+/// and logical pixels, which is what the window size and events use.
+/// The number of physical pixels for each logical pixel is given by the device pixel ratio, see for example `FrameInput::device_pixel_ratio`.
+///
+/// The edges of the viewport, rather than the position and size, are rounded to whole pixels, so viewports which are adjacent before the conversion
+/// are also adjacent after the conversion, even at fractional device pixel ratios like 1.25 and 1.5.
+///
+pub trait ViewportExt: Sized {
+    ///
+    /// Converts this viewport from physical pixels to logical pixels.
+    ///
+    fn to_logical(&self, device_pixel_ratio: f32) -> Self;
+
+    ///
+    /// Converts the given viewport from logical pixels to physical pixels.
+    ///
+    fn from_logical(logical: &Self, device_pixel_ratio: f32) -> Self;
+}
+
+impl ViewportExt for Viewport {
+    fn to_logical(&self, device_pixel_ratio: f32) -> Self {
+        scale_edges(self, 1.0 / device_pixel_ratio)
+    }
+
+    fn from_logical(logical: &Self, device_pixel_ratio: f32) -> Self {
+        scale_edges(logical, device_pixel_ratio)
+    }
+}
+
+fn scale_edges(viewport: &Viewport, scale: f32) -> Viewport {
+    let edge = |value: i32| (value as f32 * scale).round() as i32;
+    let x = edge(viewport.x);
+    let y = edge(viewport.y);
+    Viewport {
+        x,
+        y,
+        width: (edge(viewport.x + viewport.width as i32) - x).max(0) as u32,
+        height: (edge(viewport.y + viewport.height as i32) - y).max(0) as u32,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_adjacent_viewports_at_fractional_device_pixel_ratio() {
+        for device_pixel_ratio in [1.0, 1.25, 1.5, 1.75, 2.0] {
+            // Three logical viewports side by side which together cover the screen
+            let mut x = 0;
+            for width in [101, 57, 233] {
+                let logical = Viewport {
+                    x,
+                    y: 7,
+                    width,
+                    height: 99,
+                };
+                let physical = Viewport::from_logical(&logical, device_pixel_ratio);
+                let next = Viewport::from_logical(
+                    &Viewport {
+                        x: x + width as i32,
+                        ..logical
+                    },
+                    device_pixel_ratio,
+                );
+                // No gap or overlap between the physical viewports
+                assert_eq!(physical.x + physical.width as i32, next.x);
+                assert_eq!(physical.to_logical(device_pixel_ratio), logical);
+                x += width as i32;
+            }
+            let screen =
+                Viewport::from_logical(&Viewport::new_at_origo(391, 99), device_pixel_ratio);
+            assert_eq!(screen.width, (391.0 * device_pixel_ratio).round() as u32);
+        }
+    }
+}
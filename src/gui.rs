@@ -8,3 +8,17 @@ mod egui_gui;
 #[doc(inline)]
 #[cfg(feature = "egui-gui")]
 pub use egui_gui::*;
+
+#[cfg(feature = "egui-gui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "egui-gui")))]
+mod profiler_overlay;
+#[doc(inline)]
+#[cfg(feature = "egui-gui")]
+pub use profiler_overlay::*;
+
+#[cfg(feature = "egui-gui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "egui-gui")))]
+mod measurement;
+#[doc(inline)]
+#[cfg(feature = "egui-gui")]
+pub use measurement::*;
@@ -14,6 +14,7 @@ pub struct GUI {
     egui_context: egui::Context,
     output: RefCell<Option<egui::FullOutput>>,
     viewport: Viewport,
+    device_pixel_ratio: f32,
     modifiers: Modifiers,
 }
 
@@ -35,6 +36,7 @@ impl GUI {
             painter: RefCell::new(Painter::new(context, "", None).unwrap()),
             output: RefCell::new(None),
             viewport: Viewport::new_at_origo(1, 1),
+            device_pixel_ratio: 1.0,
             modifiers: Modifiers::default(),
         }
     }
@@ -53,6 +55,7 @@ impl GUI {
         callback: impl FnOnce(&egui::Context),
     ) -> bool {
         self.viewport = viewport;
+        self.device_pixel_ratio = device_pixel_ratio;
         let egui_input = egui::RawInput {
             screen_rect: Some(egui::Rect {
                 min: egui::Pos2 {
@@ -204,6 +207,11 @@ impl GUI {
                     } => {
                         *handled = true;
                     }
+                    Event::PointerPressure {
+                        ref mut handled, ..
+                    } => {
+                        *handled = true;
+                    }
                     _ => {}
                 }
             }
@@ -227,6 +235,28 @@ impl GUI {
         self.egui_context.wants_pointer_input() || self.egui_context.wants_keyboard_input()
     }
 
+    ///
+    /// Converts the given rectangle in egui points (logical pixels with the origin at the top left corner), for example the area of a panel,
+    /// to a viewport in physical pixels with the origin at the bottom left corner, for example for rendering a 3D view into that area.
+    /// Uses the viewport and device pixel ratio given to the last call to [update](Self::update) and rounds the edges to whole pixels,
+    /// so there is no gap between adjacent areas at fractional device pixel ratios.
+    ///
+    pub fn physical_viewport(&self, rect: egui::Rect) -> Viewport {
+        let edge = |value: f32| (value * self.device_pixel_ratio).round() as i32;
+        // The top of the egui screen is the top of the viewport
+        let top = self.viewport.y + self.viewport.height as i32;
+        let x0 = edge(rect.min.x);
+        let x1 = edge(rect.max.x);
+        let y0 = top - (edge(rect.max.y) - self.viewport.y);
+        let y1 = top - (edge(rect.min.y) - self.viewport.y);
+        Viewport {
+            x: x0,
+            y: y0,
+            width: (x1 - x0).max(0) as u32,
+            height: (y1 - y0).max(0) as u32,
+        }
+    }
+
     ///
     /// Render the GUI defined in the [update](Self::update) function.
     /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
@@ -310,6 +340,18 @@ impl From<&Key> for egui::Key {
             X => Key::X,
             Y => Key::Y,
             Z => Key::Z,
+            F1 => Key::F1,
+            F2 => Key::F2,
+            F3 => Key::F3,
+            F4 => Key::F4,
+            F5 => Key::F5,
+            F6 => Key::F6,
+            F7 => Key::F7,
+            F8 => Key::F8,
+            F9 => Key::F9,
+            F10 => Key::F10,
+            F11 => Key::F11,
+            F12 => Key::F12,
         }
     }
 }
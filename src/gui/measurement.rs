@@ -0,0 +1,130 @@
+use crate::core::*;
+
+///
+/// A dimension line between two world space positions which is painted on top of the rendered scene using [egui],
+/// with arrowheads and perpendicular ticks at the ends and a label showing the distance between the two positions.
+/// The line is clipped against the near plane of the camera, so it is drawn correctly even if one of the positions is behind the camera,
+/// and the label keeps the same size on screen and is aligned with the line but flipped such that it is never upside down.
+///
+/// Call [Measurement::paint] with the egui context given in the callback of [GUI::update](crate::GUI::update).
+///
+pub struct Measurement {
+    /// The world space position where the measurement starts.
+    pub start: Vec3,
+    /// The world space position where the measurement ends.
+    pub end: Vec3,
+    /// The color of the line and label.
+    pub color: Color,
+    /// The width of the line in points.
+    pub line_width: f32,
+    /// The length of the ticks and arrowheads in points.
+    pub tick_size: f32,
+    /// The font size of the label in points.
+    pub font_size: f32,
+    format: Box<dyn Fn(f32) -> String>,
+}
+
+impl Measurement {
+    ///
+    /// Creates a new measurement between the two given world space positions.
+    /// The distance is shown with two decimals, use [Measurement::set_format] to change that.
+    ///
+    pub fn new(start: Vec3, end: Vec3) -> Self {
+        Self {
+            start,
+            end,
+            color: Color::WHITE,
+            line_width: 1.5,
+            tick_size: 8.0,
+            font_size: 14.0,
+            format: Box::new(|distance| format!("{:.2}", distance)),
+        }
+    }
+
+    ///
+    /// Sets the function which formats the distance in world space units into the text shown in the label, for example `|d| format!("{:.1} mm", d * 1000.0)`.
+    ///
+    pub fn set_format(&mut self, format: impl Fn(f32) -> String + 'static) {
+        self.format = Box::new(format);
+    }
+
+    ///
+    /// Returns the distance between the start and end position.
+    ///
+    pub fn distance(&self) -> f32 {
+        self.start.distance(self.end)
+    }
+
+    ///
+    /// Paints the measurement as seen from the given camera. Nothing is painted if the measurement is entirely behind the camera.
+    ///
+    pub fn paint(&self, gui_context: &egui::Context, camera: &Camera) {
+        use egui::*;
+        let (start, end) = match camera.clip_line_to_near_plane(self.start, self.end) {
+            Some(line) => line,
+            None => return,
+        };
+        let pixels_per_point = gui_context.pixels_per_point();
+        let screen_height = gui_context.screen_rect().height();
+        let to_screen = |position: Vec3| {
+            camera.world_to_pixel(position).map(|p| {
+                pos2(
+                    p.x / pixels_per_point,
+                    screen_height - p.y / pixels_per_point,
+                )
+            })
+        };
+        let (a, b) = match (to_screen(start), to_screen(end)) {
+            (Some(a), Some(b)) => (a, b),
+            _ => return,
+        };
+        let color =
+            Color32::from_rgba_unmultiplied(self.color.r, self.color.g, self.color.b, self.color.a);
+        let stroke = Stroke::new(self.line_width, color);
+        let painter = gui_context.layer_painter(LayerId::new(
+            Order::Background,
+            Id::new("three_d_measurement"),
+        ));
+        painter.line_segment([a, b], stroke);
+
+        let direction = (b - a).normalized();
+        if direction.x.is_nan() {
+            return;
+        }
+        let normal = direction.rot90();
+        let half_tick = 0.5 * self.tick_size * normal;
+        let arrow_size = 0.5 * self.tick_size;
+        // Ticks and arrowheads are only drawn at the original end points, not where the line is clipped
+        if start == self.start {
+            painter.line_segment([a - half_tick, a + half_tick], stroke);
+            painter.line_segment([a, a + arrow_size * (direction + 0.5 * normal)], stroke);
+            painter.line_segment([a, a + arrow_size * (direction - 0.5 * normal)], stroke);
+        }
+        if end == self.end {
+            painter.line_segment([b - half_tick, b + half_tick], stroke);
+            painter.line_segment([b, b - arrow_size * (direction + 0.5 * normal)], stroke);
+            painter.line_segment([b, b - arrow_size * (direction - 0.5 * normal)], stroke);
+        }
+
+        // The label is aligned with the line, but flipped if it would otherwise be upside down
+        let mut angle = direction.y.atan2(direction.x);
+        if angle > std::f32::consts::FRAC_PI_2 {
+            angle -= std::f32::consts::PI;
+        } else if angle < -std::f32::consts::FRAC_PI_2 {
+            angle += std::f32::consts::PI;
+        }
+        let galley = painter.layout_no_wrap(
+            (self.format)(self.distance()),
+            FontId::proportional(self.font_size),
+            color,
+        );
+        let rotation = emath::Rot2::from_angle(angle);
+        // Place the label centered just above the middle of the line
+        let center = a + 0.5 * (b - a) + rotation * vec2(0.0, -0.5 * galley.size().y - 2.0);
+        let position = center - rotation * (0.5 * galley.size());
+        painter.add(TextShape {
+            angle,
+            ..TextShape::new(position, galley)
+        });
+    }
+}
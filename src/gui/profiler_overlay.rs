@@ -0,0 +1,232 @@
+use super::GUI;
+use crate::control::*;
+use crate::core::*;
+#[cfg(target_arch = "wasm32")]
+use instant::Instant;
+#[cfg(not(target_arch = "wasm32"))]
+use std::time::Instant;
+
+const FRAME_HISTORY: usize = 120;
+
+///
+/// An overlay showing profiling information in the top right corner of the screen;
+/// a graph of the frame times of the last 120 frames, the CPU time spent in each named scope of the last frame, the number of draw calls and triangles
+/// (see [Context::render_stats]) of the last frame and the memory used by textures and buffers (see [Context::memory_stats]).
+///
+/// Call [ProfilerOverlay::begin_frame] at the beginning of each frame, [ProfilerOverlay::scope] to mark the beginning of each part of the frame
+/// that should be measured, for example `overlay.scope("shadows")` before generating shadow maps, and [ProfilerOverlay::render] at the end of the frame.
+/// The overlay is shown or hidden by pressing the toggle key (P by default).
+///
+/// Apart from the GUI itself, the overlay does not allocate memory each frame once the number of scopes is stable.
+///
+pub struct ProfilerOverlay {
+    context: Context,
+    gui: GUI,
+    visible: bool,
+    toggle_key: Key,
+    frame_times: [f32; FRAME_HISTORY],
+    next_frame_time: usize,
+    accumulated_time: f64,
+    viewport: Viewport,
+    device_pixel_ratio: f32,
+    frame_start: Instant,
+    scope_starts: Vec<(&'static str, f32)>,
+    scope_durations: Vec<(&'static str, f32)>,
+    cpu_time: f32,
+    render_stats: RenderStats,
+    memory_stats: MemoryStats,
+}
+
+impl ProfilerOverlay {
+    ///
+    /// Creates a new profiler overlay which is visible from the start.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            gui: GUI::new(context),
+            visible: true,
+            toggle_key: Key::P,
+            frame_times: [0.0; FRAME_HISTORY],
+            next_frame_time: 0,
+            accumulated_time: 0.0,
+            viewport: Viewport::new_at_origo(1, 1),
+            device_pixel_ratio: 1.0,
+            frame_start: Instant::now(),
+            scope_starts: Vec::new(),
+            scope_durations: Vec::new(),
+            cpu_time: 0.0,
+            render_stats: RenderStats::default(),
+            memory_stats: MemoryStats::default(),
+        }
+    }
+
+    ///
+    /// Sets the key that shows or hides the overlay.
+    ///
+    pub fn set_toggle_key(&mut self, key: Key) {
+        self.toggle_key = key;
+    }
+
+    ///
+    /// Shows or hides the overlay.
+    ///
+    pub fn set_visible(&mut self, visible: bool) {
+        self.visible = visible;
+    }
+
+    ///
+    /// Returns whether or not the overlay is visible.
+    ///
+    pub fn is_visible(&self) -> bool {
+        self.visible
+    }
+
+    ///
+    /// Begins measuring a new frame. Should be called at the beginning of each frame with the events, elapsed time (in milliseconds), viewport and
+    /// device pixel ratio of the frame, for example from the [FrameInput](crate::FrameInput).
+    /// A key press event matching the toggle key is marked as handled.
+    /// Also resets the render statistics of the context (see [Context::reset_render_stats]).
+    ///
+    pub fn begin_frame(
+        &mut self,
+        events: &mut [Event],
+        elapsed_time_in_ms: f64,
+        viewport: Viewport,
+        device_pixel_ratio: f32,
+    ) {
+        for event in events.iter_mut() {
+            if let Event::KeyPress { kind, handled, .. } = event {
+                if *kind == self.toggle_key && !*handled {
+                    self.visible = !self.visible;
+                    *handled = true;
+                }
+            }
+        }
+        self.frame_times[self.next_frame_time] = elapsed_time_in_ms as f32;
+        self.next_frame_time = (self.next_frame_time + 1) % FRAME_HISTORY;
+        self.accumulated_time += elapsed_time_in_ms;
+        self.viewport = viewport;
+        self.device_pixel_ratio = device_pixel_ratio;
+        self.scope_starts.clear();
+        self.context.reset_render_stats();
+        self.frame_start = Instant::now();
+    }
+
+    ///
+    /// Marks the beginning of a new named scope, which also ends the previous scope.
+    /// The last scope ends when [ProfilerOverlay::render] is called.
+    ///
+    pub fn scope(&mut self, name: &'static str) {
+        let start = self.elapsed_since_frame_start();
+        self.scope_starts.push((name, start));
+    }
+
+    ///
+    /// Ends the measurement of the current frame and renders the overlay if it is visible.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method,
+    /// and should be called after everything else is rendered. The draw calls issued by the overlay itself are not counted.
+    ///
+    pub fn render(&mut self) {
+        self.cpu_time = self.elapsed_since_frame_start();
+        self.render_stats = self.context.render_stats();
+        self.memory_stats = self.context.memory_stats();
+        self.scope_durations.clear();
+        for (i, (name, start)) in self.scope_starts.iter().enumerate() {
+            let end = self
+                .scope_starts
+                .get(i + 1)
+                .map(|(_, end)| *end)
+                .unwrap_or(self.cpu_time);
+            self.scope_durations.push((*name, end - start));
+        }
+
+        if !self.visible {
+            return;
+        }
+        let frame_times = &self.frame_times;
+        let next_frame_time = self.next_frame_time;
+        let scope_durations = &self.scope_durations;
+        let cpu_time = self.cpu_time;
+        let render_stats = self.render_stats;
+        let memory_stats = self.memory_stats;
+        self.gui.update(
+            &mut [],
+            self.accumulated_time,
+            self.viewport,
+            self.device_pixel_ratio,
+            |gui_context| {
+                use egui::*;
+                Area::new("profiler_overlay")
+                    .anchor(Align2::RIGHT_TOP, [-10.0, 10.0])
+                    .interactable(false)
+                    .show(gui_context, |ui| {
+                        Frame::popup(ui.style()).show(ui, |ui| {
+                            let last_frame_time =
+                                frame_times[(next_frame_time + FRAME_HISTORY - 1) % FRAME_HISTORY];
+                            ui.label(format!(
+                                "Frame: {:.2} ms ({:.0} fps)",
+                                last_frame_time,
+                                1000.0 / last_frame_time.max(0.001)
+                            ));
+
+                            // Frame time graph, from the oldest to the newest frame
+                            let (rect, _) =
+                                ui.allocate_exact_size(vec2(240.0, 60.0), Sense::hover());
+                            let painter = ui.painter_at(rect);
+                            painter.rect_filled(rect, 0.0, Color32::from_black_alpha(150));
+                            let max_time = frame_times.iter().fold(33.3f32, |a, b| a.max(*b));
+                            let point = |i: usize| {
+                                let time = frame_times[(next_frame_time + i) % FRAME_HISTORY];
+                                pos2(
+                                    rect.left()
+                                        + rect.width() * i as f32 / (FRAME_HISTORY - 1) as f32,
+                                    rect.bottom() - rect.height() * time / max_time,
+                                )
+                            };
+                            let target_y = rect.bottom() - rect.height() * 16.7 / max_time;
+                            painter.line_segment(
+                                [pos2(rect.left(), target_y), pos2(rect.right(), target_y)],
+                                Stroke::new(1.0, Color32::DARK_GRAY),
+                            );
+                            for i in 1..FRAME_HISTORY {
+                                painter.line_segment(
+                                    [point(i - 1), point(i)],
+                                    Stroke::new(1.0, Color32::GREEN),
+                                );
+                            }
+
+                            ui.label(format!("CPU: {:.2} ms", cpu_time));
+                            for (name, duration) in scope_durations.iter() {
+                                ui.horizontal(|ui| {
+                                    ui.add(
+                                        ProgressBar::new(duration / cpu_time.max(0.001))
+                                            .desired_width(100.0),
+                                    );
+                                    ui.label(format!("{}: {:.2} ms", name, duration));
+                                });
+                            }
+                            ui.label(format!("Draw calls: {}", render_stats.draw_calls));
+                            ui.label(format!("Triangles: {}", render_stats.triangles));
+                            ui.label(format!(
+                                "Textures: {} ({:.1} MB)",
+                                memory_stats.textures,
+                                memory_stats.texture_bytes as f64 / (1024.0 * 1024.0)
+                            ));
+                            ui.label(format!(
+                                "Buffers: {} ({:.1} MB)",
+                                memory_stats.buffers,
+                                memory_stats.buffer_bytes as f64 / (1024.0 * 1024.0)
+                            ));
+                        });
+                    });
+            },
+        );
+        self.gui.render();
+    }
+
+    fn elapsed_since_frame_start(&self) -> f32 {
+        let duration = Instant::now().duration_since(self.frame_start);
+        duration.as_secs() as f32 * 1000.0 + duration.subsec_nanos() as f32 * 1e-6
+    }
+}
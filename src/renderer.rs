@@ -29,6 +29,20 @@ pub enum RendererError {
     InvalidBufferLength(String, usize, usize),
     #[error("the material {0} is required by the geometry {1} but could not be found")]
     MissingMaterial(String, String),
+    #[error("invalid half-edge mesh: {0}")]
+    InvalidHalfEdgeMesh(String),
+    #[error("the edge cannot be collapsed: {0}")]
+    InvalidEdgeCollapse(String),
+    #[error("mesh boolean operation failed: {0}")]
+    MeshBoolean(String),
+    #[error("failed to load the model {0}: {1}")]
+    ModelLoading(String, String),
+    #[error("failed to watch {0} for changes: {1}")]
+    AssetWatcher(String, String),
+    #[error("invalid shortcut: {0}")]
+    InvalidShortcut(String),
+    #[error("the shortcut {0} conflicts with the already registered shortcut {1}")]
+    ShortcutConflict(String, String),
 }
 
 pub mod material;
@@ -49,12 +63,36 @@ pub use object::*;
 pub mod control;
 pub use control::*;
 
+pub mod animation;
+pub use animation::*;
+
+pub mod frame_graph;
+pub use frame_graph::*;
+
+pub mod panorama;
+pub use panorama::*;
+
+pub mod object_ids;
+pub use object_ids::*;
+
+#[cfg(feature = "ui")]
+#[cfg_attr(docsrs, doc(cfg(feature = "ui")))]
+pub mod ui;
+#[cfg(feature = "ui")]
+pub use ui::*;
+
+#[cfg(all(feature = "asset-hot-reload", not(target_arch = "wasm32")))]
+#[cfg_attr(docsrs, doc(cfg(feature = "asset-hot-reload")))]
+pub mod hot_reload;
+#[cfg(all(feature = "asset-hot-reload", not(target_arch = "wasm32")))]
+pub use hot_reload::*;
+
 macro_rules! impl_render_target_extensions_body {
     () => {
         ///
         /// Render the objects using the given camera and lights into this render target.
         /// Use an empty array for the `lights` argument, if the objects does not require lights to be rendered.
-        /// Also, objects outside the camera frustum are not rendered and the objects are rendered in the order given by [cmp_render_order].
+        /// Also, objects outside the camera frustum are not rendered and the objects are rendered in the order given by [SortInfo::cmp_default].
         ///
         pub fn render(
             &self,
@@ -68,7 +106,7 @@ macro_rules! impl_render_target_extensions_body {
         ///
         /// Render the objects using the given camera and lights into the part of this render target defined by the scissor box.
         /// Use an empty array for the `lights` argument, if the objects does not require lights to be rendered.
-        /// Also, objects outside the camera frustum are not rendered and the objects are rendered in the order given by [cmp_render_order].
+        /// Also, objects outside the camera frustum are not rendered and the objects are rendered in the order given by [SortInfo::cmp_default].
         ///
         pub fn render_partially(
             &self,
@@ -76,11 +114,60 @@ macro_rules! impl_render_target_extensions_body {
             camera: &Camera,
             objects: impl IntoIterator<Item = impl Object>,
             lights: &[&dyn Light],
+        ) -> &Self {
+            self.render_partially_with_order(
+                scissor_box,
+                camera,
+                objects,
+                lights,
+                SortInfo::cmp_default,
+            )
+        }
+
+        ///
+        /// Render the objects using the given camera and lights into this render target like [Self::render],
+        /// except that the objects are rendered in the order given by the comparator instead of [SortInfo::cmp_default].
+        /// The comparator is given the [SortInfo] of two objects, which among other things contains the index of the objects in the given list,
+        /// and should return [std::cmp::Ordering::Less] if the first object should be rendered before the second.
+        /// The sort is stable, so objects which compare as equal are rendered in the given order, but ties should preferably be broken using the index
+        /// to make the order explicit, for example by ending the comparator with `.then_with(|| a.index.cmp(&b.index))`.
+        ///
+        /// Note that deferred objects are always rendered before the forward objects and are only sorted among themselves.
+        ///
+        pub fn render_with_order(
+            &self,
+            camera: &Camera,
+            objects: impl IntoIterator<Item = impl Object>,
+            lights: &[&dyn Light],
+            comparator: impl FnMut(&SortInfo, &SortInfo) -> std::cmp::Ordering,
+        ) -> &Self {
+            self.render_partially_with_order(
+                self.scissor_box(),
+                camera,
+                objects,
+                lights,
+                comparator,
+            )
+        }
+
+        ///
+        /// Render the objects using the given camera and lights into the part of this render target defined by the scissor box
+        /// in the order given by the comparator, see [Self::render_with_order].
+        ///
+        pub fn render_partially_with_order(
+            &self,
+            scissor_box: ScissorBox,
+            camera: &Camera,
+            objects: impl IntoIterator<Item = impl Object>,
+            lights: &[&dyn Light],
+            mut comparator: impl FnMut(&SortInfo, &SortInfo) -> std::cmp::Ordering,
         ) -> &Self {
             let (mut deferred_objects, mut forward_objects): (Vec<_>, Vec<_>) = objects
                 .into_iter()
-                .filter(|o| camera.in_frustum(&o.aabb()))
-                .partition(|o| o.material_type() == MaterialType::Deferred);
+                .enumerate()
+                .filter(|(_, o)| camera.in_frustum(&o.aabb()))
+                .map(|(index, o)| (SortInfo::new(camera, &o, index), o))
+                .partition(|(info, _)| info.material_type == MaterialType::Deferred);
 
             // Deferred
             if deferred_objects.len() > 0 {
@@ -89,7 +176,7 @@ macro_rules! impl_render_target_extensions_body {
                 let viewport =
                     Viewport::new_at_origo(camera.viewport().width, camera.viewport().height);
                 geometry_pass_camera.set_viewport(viewport);
-                deferred_objects.sort_by(|a, b| cmp_render_order(&geometry_pass_camera, a, b));
+                deferred_objects.sort_by(|(a, _), (b, _)| comparator(a, b));
                 let mut geometry_pass_texture = Texture2DArray::new_empty::<[u8; 4]>(
                     &self.context,
                     viewport.width,
@@ -115,12 +202,13 @@ macro_rules! impl_render_target_extensions_body {
                 )
                 .clear(ClearState::default())
                 .write(|| {
-                    for object in deferred_objects {
+                    for (_, object) in deferred_objects {
                         object.render(&geometry_pass_camera, lights);
                     }
                 });
 
-                // Lighting pass
+                // Lighting pass, which does not know which object is in each pixel
+                self.context.set_object_id(0);
                 self.write_partially(scissor_box, || {
                     DeferredPhysicalMaterial::lighting_pass(
                         &self.context,
@@ -136,9 +224,11 @@ macro_rules! impl_render_target_extensions_body {
             }
 
             // Forward
-            forward_objects.sort_by(|a, b| cmp_render_order(camera, a, b));
+            forward_objects.sort_by(|(a, _), (b, _)| comparator(a, b));
             self.write_partially(scissor_box, || {
-                for object in forward_objects {
+                for (info, object) in forward_objects {
+                    self.context
+                        .set_object_id(object.object_id().unwrap_or(info.index as u32 + 1));
                     object.render(camera, lights);
                 }
             });
@@ -178,10 +268,12 @@ macro_rules! impl_render_target_extensions_body {
             lights: &[&dyn Light],
         ) -> &Self {
             self.write_partially(scissor_box, || {
-                for object in geometries
+                for (index, object) in geometries
                     .into_iter()
-                    .filter(|o| camera.in_frustum(&o.aabb()))
+                    .enumerate()
+                    .filter(|(_, o)| camera.in_frustum(&o.aabb()))
                 {
+                    self.context.set_object_id(index as u32 + 1);
                     object.render_with_material(material, camera, lights);
                 }
             });
@@ -227,10 +319,12 @@ macro_rules! impl_render_target_extensions_body {
             depth_texture: Option<DepthTexture>,
         ) -> &Self {
             self.write_partially(scissor_box, || {
-                for object in geometries
+                for (index, object) in geometries
                     .into_iter()
-                    .filter(|o| camera.in_frustum(&o.aabb()))
+                    .enumerate()
+                    .filter(|(_, o)| camera.in_frustum(&o.aabb()))
                 {
+                    self.context.set_object_id(index as u32 + 1);
                     object.render_with_post_material(
                         material,
                         camera,
@@ -309,31 +403,74 @@ pub fn camera2d(viewport: Viewport) -> Camera {
 /// Compare function for sorting objects based on distance from the camera.
 /// The order is opaque objects from nearest to farthest away from the camera,
 /// then transparent objects from farthest away to closest to the camera.
+/// Objects with a higher [Object::render_order] are always rendered after objects with a lower render order.
+/// This is the same order as [SortInfo::cmp_default], except that objects which are equal in all other respects compare as equal.
 ///
 pub fn cmp_render_order(
     camera: &Camera,
     obj0: impl Object,
     obj1: impl Object,
 ) -> std::cmp::Ordering {
-    if obj0.material_type() == MaterialType::Transparent
-        && obj1.material_type() != MaterialType::Transparent
-    {
-        std::cmp::Ordering::Greater
-    } else if obj0.material_type() != MaterialType::Transparent
-        && obj1.material_type() == MaterialType::Transparent
-    {
-        std::cmp::Ordering::Less
-    } else {
-        let distance_a = camera.position().distance2(obj0.aabb().center());
-        let distance_b = camera.position().distance2(obj1.aabb().center());
-        if distance_a.is_nan() || distance_b.is_nan() {
-            distance_a.is_nan().cmp(&distance_b.is_nan()) // whatever - just save us from panicing on unwrap below
-        } else if obj0.material_type() == MaterialType::Transparent {
-            distance_b.partial_cmp(&distance_a).unwrap()
-        } else {
-            distance_a.partial_cmp(&distance_b).unwrap()
+    SortInfo::new(camera, &obj0, 0).cmp_default(&SortInfo::new(camera, &obj1, 0))
+}
+
+///
+/// The information about an object which is used to decide the order in which objects are rendered,
+/// see [RenderTarget::render_with_order] for how to define a custom order.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SortInfo {
+    /// The distance from the camera position to the center of the bounding box of the object.
+    pub distance: f32,
+    /// The type of material applied to the object.
+    pub material_type: MaterialType,
+    /// An id of the instance of the material applied to the object, which is the same for objects sharing the same material (see [Object::material_id]).
+    /// It is not used by [SortInfo::cmp_default], but can be used in a custom order to render objects with the same material after each other,
+    /// for example `a.material_id.cmp(&b.material_id).then_with(|| a.index.cmp(&b.index))` for opaque objects.
+    pub material_id: usize,
+    /// The render order of the object, see [Object::render_order].
+    pub render_order: i32,
+    /// The index of the object in the list of objects given to the render call.
+    pub index: usize,
+}
+
+impl SortInfo {
+    ///
+    /// Constructs the sort information for the given object as seen from the given camera.
+    ///
+    pub fn new(camera: &Camera, object: &dyn Object, index: usize) -> Self {
+        Self {
+            distance: camera.position().distance(object.aabb().center()),
+            material_type: object.material_type(),
+            material_id: object.material_id(),
+            render_order: object.render_order(),
+            index,
         }
     }
+
+    ///
+    /// The default render order which is used by [RenderTarget::render].
+    /// Objects with a lower render order are rendered first, then opaque objects from nearest to farthest away from the camera,
+    /// then transparent objects from farthest away to closest to the camera.
+    /// Ties are broken by the index, so the order never changes from frame to frame because of objects with the same distance.
+    ///
+    pub fn cmp_default(&self, other: &Self) -> std::cmp::Ordering {
+        let is_transparent = self.material_type == MaterialType::Transparent;
+        self.render_order
+            .cmp(&other.render_order)
+            .then_with(|| is_transparent.cmp(&(other.material_type == MaterialType::Transparent)))
+            .then_with(|| {
+                // Objects without a valid distance, for example with an infinite bounding box, are rendered last
+                match (self.distance.is_nan(), other.distance.is_nan()) {
+                    (false, false) if is_transparent => {
+                        other.distance.partial_cmp(&self.distance).unwrap()
+                    }
+                    (false, false) => self.distance.partial_cmp(&other.distance).unwrap(),
+                    (a, b) => a.cmp(&b),
+                }
+            })
+            .then_with(|| self.index.cmp(&other.index))
+    }
 }
 
 ///
@@ -341,6 +478,7 @@ pub fn cmp_render_order(
 /// The pixel coordinate must be in physical pixels, where (viewport.x, viewport.y) indicate the bottom left corner of the viewport
 /// and (viewport.x + viewport.width, viewport.y + viewport.height) indicate the top right corner.
 /// Returns ```None``` if no geometry was hit between the near (`z_near`) and far (`z_far`) plane for this camera.
+/// Geometries are rendered as seen from the given camera, so for example objects with a screen space scale (see [GmWithOptions::with_screen_space_scale]) are picked with the size they have on screen.
 ///
 pub fn pick(
     context: &Context,
@@ -348,15 +486,37 @@ pub fn pick(
     pixel: impl Into<PhysicalPoint> + Copy,
     geometries: impl IntoIterator<Item = impl Geometry>,
 ) -> Option<Vec3> {
+    // A camera which sees exactly the given pixel of the given camera, so that geometries that depend on the size of a pixel,
+    // for example when using screen space scale (see GmWithOptions::with_screen_space_scale), are rendered the same way as with the given camera.
     let pos = camera.position_at_pixel(pixel);
     let dir = camera.view_direction_at_pixel(pixel);
-    ray_intersect(
-        context,
-        pos + dir * camera.z_near(),
-        dir,
-        camera.z_far() - camera.z_near(),
-        geometries,
-    )
+    let viewport = Viewport::new_at_origo(1, 1);
+    let up = *camera.up();
+    let pixel_camera = match camera.projection_type() {
+        ProjectionType::Perspective { field_of_view_y } => Camera::new_perspective(
+            viewport,
+            pos,
+            pos + dir,
+            up,
+            radians(
+                2.0 * ((0.5 * field_of_view_y.0).tan() / camera.viewport().height.max(1) as f32)
+                    .atan(),
+            ),
+            camera.z_near(),
+            camera.z_far(),
+        ),
+        ProjectionType::Orthographic { height } => Camera::new_orthographic(
+            viewport,
+            pos,
+            pos + dir,
+            up,
+            height / camera.viewport().height.max(1) as f32,
+            camera.z_near(),
+            camera.z_far(),
+        ),
+    };
+    let depth = render_depth(context, &pixel_camera, geometries)?;
+    Some(pos + dir * (camera.z_near() + depth * (camera.z_far() - camera.z_near())))
 }
 
 ///
@@ -386,6 +546,20 @@ pub fn ray_intersect(
         0.0,
         max_depth,
     );
+    let depth = render_depth(context, &camera, geometries)?;
+    Some(position + direction * depth * max_depth)
+}
+
+///
+/// Renders the distance from the camera to the geometries, in the range between the near and far plane of the camera mapped to `[0..1]`,
+/// into a single pixel and returns the closest distance if any geometry was hit.
+///
+fn render_depth(
+    context: &Context,
+    camera: &Camera,
+    geometries: impl IntoIterator<Item = impl Geometry>,
+) -> Option<f32> {
+    let viewport = camera.viewport();
     let mut texture = Texture2D::new_empty::<f32>(
         context,
         viewport.width,
@@ -420,13 +594,104 @@ pub fn ray_intersect(
     .clear(ClearState::color_and_depth(1.0, 1.0, 1.0, 1.0, 1.0))
     .write(|| {
         for geometry in geometries {
-            geometry.render_with_material(&depth_material, &camera, &[]);
+            geometry.render_with_material(&depth_material, camera, &[]);
         }
     })
     .read_color()[0];
     if depth < 1.0 {
-        Some(position + direction * depth * max_depth)
+        Some(depth)
     } else {
         None
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn info(
+        distance: f32,
+        material_type: MaterialType,
+        render_order: i32,
+        index: usize,
+    ) -> SortInfo {
+        SortInfo {
+            distance,
+            material_type,
+            material_id: 0,
+            render_order,
+            index,
+        }
+    }
+
+    #[test]
+    fn test_default_render_order() {
+        let mut infos = vec![
+            // A transparent overlay close to the camera which must be rendered after the particles
+            info(1.0, MaterialType::Transparent, 1, 0),
+            info(5.0, MaterialType::Transparent, 0, 1),
+            info(2.0, MaterialType::Transparent, 0, 2),
+            info(3.0, MaterialType::Opaque, 0, 3),
+            info(1.0, MaterialType::Opaque, 0, 4),
+            info(f32::NAN, MaterialType::Opaque, 0, 5),
+            // Same distance, broken by the index
+            info(2.0, MaterialType::Transparent, 0, 6),
+        ];
+        infos.reverse();
+        infos.sort_by(SortInfo::cmp_default);
+        assert_eq!(
+            infos.iter().map(|i| i.index).collect::<Vec<_>>(),
+            vec![4, 3, 5, 1, 2, 6, 0]
+        );
+    }
+
+    #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_render_order_of_transparent_objects() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let quad = |color: Color, z: f32, render_order: i32| {
+            let mut quad = GmWithOptions::new(Gm::new(
+                Mesh::new(&context, &CpuMesh::square()),
+                ColorMaterial::new_transparent(
+                    &context,
+                    &CpuMaterial {
+                        albedo: color,
+                        ..Default::default()
+                    },
+                ),
+            ))
+            .with_render_order(render_order);
+            quad.set_transformation(Mat4::from_translation(vec3(0.0, 0.0, z)));
+            quad
+        };
+        // The red quad is closer to the camera, but the green quad has a higher render order
+        let near = quad(Color::new(255, 0, 0, 128), 1.0, 0);
+        let far = quad(Color::new(0, 255, 0, 128), -1.0, 1);
+        let camera = Camera::new_orthographic(
+            Viewport::new_at_origo(1, 1),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            1.0,
+            0.1,
+            10.0,
+        );
+        let mut texture = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            1,
+            1,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let pixel = texture
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+            .render(&camera, near.into_iter().chain(&far), &[])
+            .read::<[u8; 4]>()[0];
+        // The green quad is blended on top of the red quad, so it contributes the most
+        assert!(pixel[1] > pixel[0], "unexpected pixel {:?}", pixel);
+    }
+}
@@ -0,0 +1,784 @@
+//!
+//! Keyframed animation of object transformations, light intensities, material parameters or any other property.
+//! An [AnimationClip] contains a set of [AnimationTrack]s, each animating one [AnimationTarget] using a list of [Keyframe]s.
+//! Use an [Animator] to play, pause, loop and blend clips and to sample the animated values each frame.
+//! A [Spline] defines a smooth curve through or near a list of keyframes, for example for camera paths.
+//! A [Skeleton] is animated by clips targeting its joints and a [CrowdAnimator] samples a clip per instance for a crowd of skinned characters.
+//!
+//! Clips are not yet created when loading a glTF file. The node animations of a loaded [CpuModel](crate::CpuModel) are played using [Model::choose_animation](crate::Model::choose_animation),
+//! since the loader flattens the node hierarchy into a chain of transformations per primitive and does not keep the node names,
+//! which are needed to target the translation, rotation and scale of a node with an [AnimationTrack].
+//!
+
+use crate::core::*;
+
+mod spline;
+#[doc(inline)]
+pub use spline::*;
+
+mod skeleton;
+#[doc(inline)]
+pub use skeleton::*;
+
+///
+/// Defines how to interpolate between two keyframes.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyframeInterpolation {
+    /// The value of the previous keyframe is used until the next keyframe is reached.
+    Step,
+    /// Linear interpolation between the values of the two keyframes (spherical linear interpolation for rotations).
+    Linear,
+    /// Cubic Hermite spline interpolation using the tangents of the two keyframes, the same as the glTF cubic spline interpolation.
+    CubicSpline,
+}
+
+impl Default for KeyframeInterpolation {
+    fn default() -> Self {
+        Self::Linear
+    }
+}
+
+///
+/// A value that can be animated using keyframes.
+///
+pub trait Animatable: Copy {
+    ///
+    /// Linear interpolation between `self` and `other` where a `t` value of 0 returns `self` and 1 returns `other`.
+    ///
+    fn interpolate(&self, other: &Self, t: f32) -> Self;
+
+    ///
+    /// Returns the weighted sum of the given values.
+    ///
+    fn weighted_sum(values: &[(Self, f32)]) -> Self;
+
+    ///
+    /// Cubic Hermite spline interpolation between `self` and `other` where a `t` value of 0 returns `self` and 1 returns `other`.
+    /// The tangents are the derivatives per unit time at `self` and `other` and `duration` is the time between the two values.
+    ///
+    fn cubic_spline(
+        &self,
+        out_tangent: &Self,
+        other: &Self,
+        in_tangent: &Self,
+        t: f32,
+        duration: f32,
+    ) -> Self {
+        let [h00, h10, h01, h11] = hermite_weights(t);
+        Self::weighted_sum(&[
+            (*self, h00),
+            (*out_tangent, h10 * duration),
+            (*other, h01),
+            (*in_tangent, h11 * duration),
+        ])
+    }
+}
+
+///
+/// Returns the weights of the start value, start tangent, end value and end tangent of a cubic Hermite spline at the given `t` value.
+///
+fn hermite_weights(t: f32) -> [f32; 4] {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    [
+        2.0 * t3 - 3.0 * t2 + 1.0,
+        t3 - 2.0 * t2 + t,
+        -2.0 * t3 + 3.0 * t2,
+        t3 - t2,
+    ]
+}
+
+impl Animatable for f32 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    fn weighted_sum(values: &[(Self, f32)]) -> Self {
+        values.iter().map(|(v, w)| v * w).sum()
+    }
+}
+
+impl Animatable for Vec3 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    fn weighted_sum(values: &[(Self, f32)]) -> Self {
+        values.iter().fold(Vec3::zero(), |sum, (v, w)| sum + v * *w)
+    }
+}
+
+impl Animatable for Vec4 {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        self + (other - self) * t
+    }
+
+    fn weighted_sum(values: &[(Self, f32)]) -> Self {
+        values.iter().fold(Vec4::zero(), |sum, (v, w)| sum + v * *w)
+    }
+}
+
+impl Animatable for Quat {
+    fn interpolate(&self, other: &Self, t: f32) -> Self {
+        // Take the shortest path
+        let other = if self.dot(*other) < 0.0 {
+            -*other
+        } else {
+            *other
+        };
+        self.slerp(other, t)
+    }
+
+    fn weighted_sum(values: &[(Self, f32)]) -> Self {
+        let first = values.first().map(|(v, _)| *v).unwrap_or(Quat::one());
+        values
+            .iter()
+            .fold(Quat::new(0.0, 0.0, 0.0, 0.0), |sum, (v, w)| {
+                // Take the shortest path
+                let v = if first.dot(*v) < 0.0 { -*v } else { *v };
+                sum + v * *w
+            })
+            .normalize()
+    }
+
+    fn cubic_spline(
+        &self,
+        out_tangent: &Self,
+        other: &Self,
+        in_tangent: &Self,
+        t: f32,
+        duration: f32,
+    ) -> Self {
+        // Only the values are moved to the same hemisphere, the tangents are derivatives and must keep their sign
+        let other = if self.dot(*other) < 0.0 {
+            -*other
+        } else {
+            *other
+        };
+        let [h00, h10, h01, h11] = hermite_weights(t);
+        (*self * h00
+            + *out_tangent * (h10 * duration)
+            + other * h01
+            + *in_tangent * (h11 * duration))
+            .normalize()
+    }
+}
+
+///
+/// Converts the given color to a vector with the red, green, blue and alpha channels in the range `[0..1]`.
+///
+fn color_to_vec4(color: &Color) -> Vec4 {
+    vec4(
+        color.r as f32,
+        color.g as f32,
+        color.b as f32,
+        color.a as f32,
+    ) / 255.0
+}
+
+///
+/// Converts the given vector with the red, green, blue and alpha channels in the range `[0..1]` to a color, clamping channels outside the range.
+///
+fn vec4_to_color(v: Vec4) -> Color {
+    let channel = |c: f32| (c * 255.0).round().clamp(0.0, 255.0) as u8;
+    Color::new(channel(v.x), channel(v.y), channel(v.z), channel(v.w))
+}
+
+///
+/// A keyframe specifying the value at a specific time.
+/// The tangents are only used with [KeyframeInterpolation::CubicSpline] and are specified per unit time.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Keyframe<T: Animatable> {
+    /// The time of this keyframe.
+    pub time: f32,
+    /// The value at the time of this keyframe.
+    pub value: T,
+    /// The tangent used when interpolating from the previous keyframe to this keyframe.
+    pub in_tangent: T,
+    /// The tangent used when interpolating from this keyframe to the next keyframe.
+    pub out_tangent: T,
+}
+
+impl<T: Animatable> Keyframe<T> {
+    ///
+    /// Creates a new keyframe with the given time and value and with both tangents equal to the given tangent.
+    ///
+    pub fn new(time: f32, value: T, tangent: T) -> Self {
+        Self {
+            time,
+            value,
+            in_tangent: tangent,
+            out_tangent: tangent,
+        }
+    }
+}
+
+///
+/// The property animated by an [AnimationTrack].
+///
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AnimationTarget {
+    /// The translation of the object with the given index, for example the index of a part of a [Model].
+    Translation(usize),
+    /// The rotation of the object with the given index, for example the index of a part of a [Model].
+    Rotation(usize),
+    /// The scale of the object with the given index, for example the index of a part of a [Model].
+    Scale(usize),
+    /// The intensity of the light with the given index.
+    LightIntensity(usize),
+    /// The color of the light with the given index.
+    LightColor(usize),
+    /// A factor of the material with the given index, for example the emissive strength.
+    MaterialFactor(usize),
+    /// A color of the material with the given index, for example the albedo.
+    MaterialColor(usize),
+    /// Any other property identified by name.
+    Custom(String),
+}
+
+///
+/// A value sampled from an [AnimationTrack].
+///
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnimationValue {
+    F32(f32),
+    Vec3(Vec3),
+    Quat(Quat),
+    Color(Color),
+}
+
+///
+/// A list of keyframes of a specific type animating an [AnimationTarget].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T: Animatable> {
+    /// The animated property.
+    pub target: AnimationTarget,
+    /// How to interpolate between the keyframes.
+    pub interpolation: KeyframeInterpolation,
+    /// The keyframes sorted by time.
+    pub keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Animatable> Track<T> {
+    ///
+    /// Returns the value at the given time or `None` if the track has no keyframes.
+    /// Before the first keyframe, the value of the first keyframe is returned and after the last keyframe, the value of the last keyframe is returned.
+    ///
+    pub fn sample(&self, time: f32) -> Option<T> {
+        let first = self.keyframes.first()?;
+        let last = self.keyframes.last()?;
+        if time <= first.time {
+            return Some(first.value);
+        }
+        if time >= last.time {
+            return Some(last.value);
+        }
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        let k0 = &self.keyframes[next - 1];
+        let k1 = &self.keyframes[next];
+        let dt = k1.time - k0.time;
+        let t = (time - k0.time) / dt;
+        Some(match self.interpolation {
+            KeyframeInterpolation::Step => k0.value,
+            KeyframeInterpolation::Linear => k0.value.interpolate(&k1.value, t),
+            KeyframeInterpolation::CubicSpline => {
+                k0.value
+                    .cubic_spline(&k0.out_tangent, &k1.value, &k1.in_tangent, t, dt)
+            }
+        })
+    }
+
+    ///
+    /// Returns the time of the last keyframe or zero if the track has no keyframes.
+    ///
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map(|k| k.time).unwrap_or(0.0)
+    }
+}
+
+///
+/// A track of keyframes of one of the supported types.
+///
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq)]
+pub enum AnimationTrack {
+    F32(Track<f32>),
+    Vec3(Track<Vec3>),
+    Quat(Track<Quat>),
+    /// A color track where the keyframe values and tangents are the red, green, blue and alpha channels in the range `[0..1]`,
+    /// so the tangents can be negative, which is sampled as an [AnimationValue::Color].
+    Color(Track<Vec4>),
+}
+
+impl AnimationTrack {
+    ///
+    /// Returns the animated property.
+    ///
+    pub fn target(&self) -> &AnimationTarget {
+        match self {
+            Self::F32(track) => &track.target,
+            Self::Vec3(track) => &track.target,
+            Self::Quat(track) => &track.target,
+            Self::Color(track) => &track.target,
+        }
+    }
+
+    ///
+    /// Returns the value at the given time or `None` if the track has no keyframes, see [Track::sample].
+    ///
+    pub fn sample(&self, time: f32) -> Option<AnimationValue> {
+        match self {
+            Self::F32(track) => track.sample(time).map(AnimationValue::F32),
+            Self::Vec3(track) => track.sample(time).map(AnimationValue::Vec3),
+            Self::Quat(track) => track.sample(time).map(AnimationValue::Quat),
+            Self::Color(track) => track
+                .sample(time)
+                .map(|v| AnimationValue::Color(vec4_to_color(v))),
+        }
+    }
+
+    ///
+    /// Returns the time of the last keyframe.
+    ///
+    pub fn duration(&self) -> f32 {
+        match self {
+            Self::F32(track) => track.duration(),
+            Self::Vec3(track) => track.duration(),
+            Self::Quat(track) => track.duration(),
+            Self::Color(track) => track.duration(),
+        }
+    }
+}
+
+///
+/// A named set of [AnimationTrack]s which are played together, for example a walk cycle or a pulsing light.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnimationClip {
+    /// The name of the clip, used to play the clip with an [Animator].
+    pub name: String,
+    /// The tracks of the clip.
+    pub tracks: Vec<AnimationTrack>,
+}
+
+impl AnimationClip {
+    ///
+    /// Returns the duration of the clip, ie. the time of the last keyframe in any of the tracks.
+    ///
+    pub fn duration(&self) -> f32 {
+        self.tracks
+            .iter()
+            .map(|track| track.duration())
+            .fold(0.0, f32::max)
+    }
+
+    ///
+    /// Returns the value of each animated property at the given time.
+    ///
+    pub fn sample(&self, time: f32) -> Vec<(AnimationTarget, AnimationValue)> {
+        self.tracks
+            .iter()
+            .filter_map(|track| track.sample(time).map(|v| (track.target().clone(), v)))
+            .collect()
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Playback {
+    clip: usize,
+    time: f32,
+    weight: f32,
+}
+
+///
+/// Plays a set of [AnimationClip]s.
+/// One clip can be played at a time or two clips can be blended together, for example to make a smooth transition between a walk and a run cycle.
+/// Call [Animator::update] each frame to advance the time and then use [Animator::sample] or [Animator::transformation] to get the animated values
+/// and apply them to the targeted objects, lights or materials.
+///
+#[derive(Clone, Debug)]
+pub struct Animator {
+    clips: Vec<AnimationClip>,
+    playing: Vec<Playback>,
+    paused: bool,
+    looping: bool,
+    speed: f32,
+}
+
+impl Animator {
+    ///
+    /// Creates a new animator with the given clips. No clip is playing until [Animator::play] or [Animator::blend] is called.
+    /// The animator is looping with a speed of 1 by default.
+    ///
+    pub fn new(clips: Vec<AnimationClip>) -> Self {
+        Self {
+            clips,
+            playing: Vec::new(),
+            paused: false,
+            looping: true,
+            speed: 1.0,
+        }
+    }
+
+    ///
+    /// Returns the clips of this animator.
+    ///
+    pub fn clips(&self) -> &[AnimationClip] {
+        &self.clips
+    }
+
+    ///
+    /// Starts playing the clip with the given name from the beginning and stops any other clip.
+    /// Returns false if no clip with the given name exists.
+    ///
+    pub fn play(&mut self, name: &str) -> bool {
+        if let Some(clip) = self.clip_index(name) {
+            self.playing = vec![Playback {
+                clip,
+                time: 0.0,
+                weight: 1.0,
+            }];
+            self.paused = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Plays the two clips with the given names at the same time, blending the values such that a weight of 0 gives the values of the `from` clip
+    /// and a weight of 1 gives the values of the `to` clip. Clips that are already playing continue from their current time while other clips start from the beginning.
+    /// Call this each frame with an increasing weight to make a smooth transition between two clips.
+    /// Returns false if one of the clips does not exist.
+    ///
+    pub fn blend(&mut self, from: &str, to: &str, weight: f32) -> bool {
+        let (from, to) = match (self.clip_index(from), self.clip_index(to)) {
+            (Some(from), Some(to)) => (from, to),
+            _ => return false,
+        };
+        let time = |clip| {
+            self.playing
+                .iter()
+                .find(|p| p.clip == clip)
+                .map(|p| p.time)
+                .unwrap_or(0.0)
+        };
+        let weight = weight.clamp(0.0, 1.0);
+        self.playing = vec![
+            Playback {
+                clip: from,
+                time: time(from),
+                weight: 1.0 - weight,
+            },
+            Playback {
+                clip: to,
+                time: time(to),
+                weight,
+            },
+        ];
+        true
+    }
+
+    ///
+    /// Stops all clips.
+    ///
+    pub fn stop(&mut self) {
+        self.playing.clear();
+    }
+
+    ///
+    /// Pauses the playing clips, ie. [Animator::update] does not advance the time.
+    ///
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    ///
+    /// Resumes the playing clips after a call to [Animator::pause].
+    ///
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+
+    ///
+    /// Returns true if a clip is playing and the animator is not paused.
+    ///
+    pub fn is_playing(&self) -> bool {
+        !self.paused && !self.playing.is_empty()
+    }
+
+    ///
+    /// Sets whether the clips should start over when reaching the end. Otherwise, the clips stay at the last keyframe.
+    ///
+    pub fn set_looping(&mut self, looping: bool) {
+        self.looping = looping;
+    }
+
+    ///
+    /// Sets the playback speed, for example 2 plays the clips twice as fast and a negative value plays the clips backwards.
+    ///
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+
+    ///
+    /// Returns the current time of the clip with the given name if it is playing.
+    ///
+    pub fn time(&self, name: &str) -> Option<f32> {
+        let clip = self.clip_index(name)?;
+        self.playing.iter().find(|p| p.clip == clip).map(|p| p.time)
+    }
+
+    ///
+    /// Advances the time of the playing clips by the given elapsed time times the speed.
+    /// The elapsed time must be in the same unit as the keyframe times, for example `0.001 * frame_input.elapsed_time` if the keyframe times are in seconds.
+    ///
+    pub fn update(&mut self, elapsed_time: f32) {
+        if self.paused {
+            return;
+        }
+        for playback in self.playing.iter_mut() {
+            let duration = self.clips[playback.clip].duration();
+            let time = playback.time + elapsed_time * self.speed;
+            playback.time = if duration <= 0.0 {
+                0.0
+            } else if self.looping {
+                time.rem_euclid(duration)
+            } else {
+                time.clamp(0.0, duration)
+            };
+        }
+    }
+
+    ///
+    /// Returns the current value of each property animated by the playing clips.
+    /// If more than one clip is playing, the values of the properties animated by several clips are blended using the clip weights.
+    ///
+    pub fn sample(&self) -> Vec<(AnimationTarget, AnimationValue)> {
+        let mut result: Vec<(AnimationTarget, AnimationValue, f32)> = Vec::new();
+        for playback in self.playing.iter() {
+            for (target, value) in self.clips[playback.clip].sample(playback.time) {
+                if let Some((_, existing, weight)) =
+                    result.iter_mut().find(|(t, _, _)| *t == target)
+                {
+                    let total_weight = *weight + playback.weight;
+                    if total_weight > 0.0 {
+                        *existing = blend_values(existing, &value, playback.weight / total_weight);
+                    }
+                    *weight = total_weight;
+                } else {
+                    result.push((target, value, playback.weight));
+                }
+            }
+        }
+        result
+            .into_iter()
+            .map(|(target, value, _)| (target, value))
+            .collect()
+    }
+
+    ///
+    /// Returns the current transformation of the object with the given index, composed of the animated translation, rotation and scale,
+    /// or `None` if none of these properties are animated for the object.
+    ///
+    pub fn transformation(&self, index: usize) -> Option<Mat4> {
+        let mut translation = None;
+        let mut rotation = None;
+        let mut scale = None;
+        for (target, value) in self.sample() {
+            match (target, value) {
+                (AnimationTarget::Translation(i), AnimationValue::Vec3(v)) if i == index => {
+                    translation = Some(v)
+                }
+                (AnimationTarget::Rotation(i), AnimationValue::Quat(q)) if i == index => {
+                    rotation = Some(q)
+                }
+                (AnimationTarget::Scale(i), AnimationValue::Vec3(v)) if i == index => {
+                    scale = Some(v)
+                }
+                _ => {}
+            }
+        }
+        if translation.is_none() && rotation.is_none() && scale.is_none() {
+            return None;
+        }
+        let scale = scale.unwrap_or(vec3(1.0, 1.0, 1.0));
+        Some(
+            Mat4::from_translation(translation.unwrap_or(Vec3::zero()))
+                * Mat4::from(rotation.unwrap_or(Quat::one()))
+                * Mat4::from_nonuniform_scale(scale.x, scale.y, scale.z),
+        )
+    }
+
+    fn clip_index(&self, name: &str) -> Option<usize> {
+        self.clips.iter().position(|clip| clip.name == name)
+    }
+}
+
+fn blend_values(a: &AnimationValue, b: &AnimationValue, t: f32) -> AnimationValue {
+    match (a, b) {
+        (AnimationValue::F32(a), AnimationValue::F32(b)) => {
+            AnimationValue::F32(a.interpolate(b, t))
+        }
+        (AnimationValue::Vec3(a), AnimationValue::Vec3(b)) => {
+            AnimationValue::Vec3(a.interpolate(b, t))
+        }
+        (AnimationValue::Quat(a), AnimationValue::Quat(b)) => {
+            AnimationValue::Quat(a.interpolate(b, t))
+        }
+        (AnimationValue::Color(a), AnimationValue::Color(b)) => AnimationValue::Color(
+            vec4_to_color(color_to_vec4(a).interpolate(&color_to_vec4(b), t)),
+        ),
+        _ => *a,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn clip(name: &str, interpolation: KeyframeInterpolation, values: [f32; 2]) -> AnimationClip {
+        AnimationClip {
+            name: name.to_string(),
+            tracks: vec![AnimationTrack::F32(Track {
+                target: AnimationTarget::LightIntensity(0),
+                interpolation,
+                keyframes: vec![
+                    Keyframe::new(0.0, values[0], 0.0),
+                    Keyframe::new(2.0, values[1], 0.0),
+                ],
+            })],
+        }
+    }
+
+    #[test]
+    fn test_linear_and_step() {
+        let track = Track {
+            target: AnimationTarget::Translation(0),
+            interpolation: KeyframeInterpolation::Linear,
+            keyframes: vec![
+                Keyframe::new(0.0, vec3(0.0, 0.0, 0.0), Vec3::zero()),
+                Keyframe::new(1.0, vec3(2.0, 4.0, 6.0), Vec3::zero()),
+            ],
+        };
+        assert_eq!(track.sample(-1.0), Some(vec3(0.0, 0.0, 0.0)));
+        assert_eq!(track.sample(0.25), Some(vec3(0.5, 1.0, 1.5)));
+        assert_eq!(track.sample(2.0), Some(vec3(2.0, 4.0, 6.0)));
+
+        let track = Track {
+            interpolation: KeyframeInterpolation::Step,
+            ..track
+        };
+        assert_eq!(track.sample(0.99), Some(vec3(0.0, 0.0, 0.0)));
+        assert_eq!(track.sample(1.0), Some(vec3(2.0, 4.0, 6.0)));
+    }
+
+    #[test]
+    fn test_cubic_spline() {
+        let track = Track {
+            target: AnimationTarget::MaterialFactor(0),
+            interpolation: KeyframeInterpolation::CubicSpline,
+            keyframes: vec![
+                Keyframe {
+                    time: 0.0,
+                    value: 0.0,
+                    in_tangent: 0.0,
+                    out_tangent: 1.0,
+                },
+                Keyframe {
+                    time: 2.0,
+                    value: 1.0,
+                    in_tangent: 0.0,
+                    out_tangent: 0.0,
+                },
+            ],
+        };
+        // 0.5 * 0.0 + 0.125 * (2.0 * 1.0) + 0.5 * 1.0 - 0.125 * (2.0 * 0.0)
+        assert_eq!(track.sample(1.0), Some(0.75));
+        assert_eq!(track.sample(2.0), Some(1.0));
+    }
+
+    #[test]
+    fn test_cubic_spline_rotation_keeps_tangent_sign() {
+        let end = Quat::from_angle_z(degrees(90.0));
+        let out_tangent = Quat::new(-0.5, 0.0, 0.0, 0.0);
+        let track = Track {
+            target: AnimationTarget::Rotation(0),
+            interpolation: KeyframeInterpolation::CubicSpline,
+            keyframes: vec![
+                Keyframe {
+                    time: 0.0,
+                    value: Quat::one(),
+                    in_tangent: Quat::zero(),
+                    out_tangent,
+                },
+                Keyframe::new(1.0, end, Quat::zero()),
+            ],
+        };
+        // The out tangent points away from the first value, but must not be flipped
+        let expected = (Quat::one() * 0.5 + out_tangent * 0.125 + end * 0.5).normalize();
+        let rotation = track.sample(0.5).unwrap();
+        assert!((rotation - expected).magnitude() < 0.000001);
+    }
+
+    #[test]
+    fn test_cubic_spline_color_with_negative_tangent() {
+        let track = AnimationTrack::Color(Track {
+            target: AnimationTarget::LightColor(0),
+            interpolation: KeyframeInterpolation::CubicSpline,
+            keyframes: vec![
+                Keyframe {
+                    time: 0.0,
+                    value: vec4(1.0, 0.0, 0.0, 1.0),
+                    in_tangent: Vec4::zero(),
+                    out_tangent: vec4(-1.0, 0.0, 0.0, 0.0),
+                },
+                Keyframe::new(2.0, vec4(0.0, 0.0, 0.0, 1.0), Vec4::zero()),
+            ],
+        });
+        // 0.5 * 1.0 + 0.125 * (2.0 * -1.0) + 0.5 * 0.0 - 0.125 * (2.0 * 0.0) = 0.25
+        assert_eq!(
+            track.sample(1.0),
+            Some(AnimationValue::Color(Color::new(64, 0, 0, 255)))
+        );
+    }
+
+    #[test]
+    fn test_animator() {
+        let mut animator = Animator::new(vec![
+            clip("a", KeyframeInterpolation::Linear, [0.0, 4.0]),
+            clip("b", KeyframeInterpolation::Linear, [8.0, 8.0]),
+        ]);
+        assert!(animator.play("a"));
+        animator.update(0.5);
+        assert_eq!(
+            animator.sample(),
+            vec![(AnimationTarget::LightIntensity(0), AnimationValue::F32(1.0))]
+        );
+
+        // Loops back to the start
+        animator.update(2.0);
+        assert_eq!(animator.time("a"), Some(0.5));
+
+        animator.pause();
+        animator.update(1.0);
+        assert_eq!(animator.time("a"), Some(0.5));
+        animator.resume();
+
+        animator.set_looping(false);
+        animator.set_speed(2.0);
+        animator.update(1.0);
+        assert_eq!(animator.time("a"), Some(2.0));
+
+        assert!(animator.blend("a", "b", 0.25));
+        assert_eq!(
+            animator.sample(),
+            vec![(AnimationTarget::LightIntensity(0), AnimationValue::F32(5.0))]
+        );
+        assert!(!animator.play("c"));
+    }
+}
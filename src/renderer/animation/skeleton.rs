@@ -0,0 +1,382 @@
+use super::*;
+
+///
+/// A joint (or bone) in a [Skeleton].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Joint {
+    /// The index of the parent joint, which must be smaller than the index of this joint, or `None` if this is a root joint.
+    pub parent: Option<usize>,
+    /// The translation relative to the parent joint when not animated.
+    pub translation: Vec3,
+    /// The rotation relative to the parent joint when not animated.
+    pub rotation: Quat,
+    /// The scale relative to the parent joint when not animated.
+    pub scale: Vec3,
+    /// The inverse of the global transformation of the joint in the pose the mesh was modelled in, which transforms the vertices into the space of the joint.
+    pub inverse_bind_matrix: Mat4,
+}
+
+///
+/// Memory which is reused between calls to [Skeleton::skinning_matrices], so sampling the pose of many instances each frame does not allocate.
+///
+#[derive(Clone, Debug, Default)]
+pub struct SkinningScratch {
+    locals: Vec<(Vec3, Quat, Vec3)>,
+}
+
+///
+/// A hierarchy of [Joint]s which deforms a skinned mesh, for example an [InstancedSkinnedMesh].
+/// The joints are animated by an [AnimationClip] where [AnimationTarget::Translation], [AnimationTarget::Rotation] and [AnimationTarget::Scale]
+/// with the index of a joint replace the corresponding property of that joint.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Skeleton {
+    /// The joints ordered so that the parent of a joint comes before the joint itself.
+    pub joints: Vec<Joint>,
+}
+
+impl Skeleton {
+    ///
+    /// Returns the number of joints.
+    ///
+    pub fn joint_count(&self) -> usize {
+        self.joints.len()
+    }
+
+    ///
+    /// Computes the skinning matrix of each joint, ie. the global transformation of the joint multiplied by the inverse bind matrix,
+    /// for the pose given by the clip at the given time or for the rest pose if no clip is given.
+    /// The scratch memory is used for the local transformations of the joints and can be reused for any skeleton.
+    ///
+    /// # Panic
+    /// Will panic if the length of `result` is not the number of joints or if the parent of a joint does not come before the joint.
+    ///
+    pub fn skinning_matrices(
+        &self,
+        clip: Option<&AnimationClip>,
+        time: f32,
+        result: &mut [Mat4],
+        scratch: &mut SkinningScratch,
+    ) {
+        assert_eq!(
+            result.len(),
+            self.joints.len(),
+            "the number of skinning matrices must match the number of joints"
+        );
+        let locals = &mut scratch.locals;
+        locals.clear();
+        locals.extend(
+            self.joints
+                .iter()
+                .map(|joint| (joint.translation, joint.rotation, joint.scale)),
+        );
+        for track in clip.iter().flat_map(|clip| clip.tracks.iter()) {
+            match (track.target(), track.sample(time)) {
+                (AnimationTarget::Translation(i), Some(AnimationValue::Vec3(v)))
+                    if *i < locals.len() =>
+                {
+                    locals[*i].0 = v
+                }
+                (AnimationTarget::Rotation(i), Some(AnimationValue::Quat(q)))
+                    if *i < locals.len() =>
+                {
+                    locals[*i].1 = q
+                }
+                (AnimationTarget::Scale(i), Some(AnimationValue::Vec3(v))) if *i < locals.len() => {
+                    locals[*i].2 = v
+                }
+                _ => {}
+            }
+        }
+
+        for (i, (joint, (translation, rotation, scale))) in
+            self.joints.iter().zip(locals.iter()).enumerate()
+        {
+            let local = Mat4::from_translation(*translation)
+                * Mat4::from(*rotation)
+                * Mat4::from_nonuniform_scale(scale.x, scale.y, scale.z);
+            result[i] = match joint.parent {
+                Some(parent) if parent < i => result[parent] * local,
+                Some(parent) => panic!(
+                    "the parent ({}) of joint {} must come before the joint",
+                    parent, i
+                ),
+                None => local,
+            };
+        }
+        for (matrix, joint) in result.iter_mut().zip(self.joints.iter()) {
+            *matrix = *matrix * joint.inverse_bind_matrix;
+        }
+    }
+}
+
+///
+/// The animation state of one instance in a [CrowdAnimator].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CrowdInstance {
+    /// The index of the clip played by this instance.
+    pub clip: usize,
+    /// The time added to the time of the animator before sampling the clip, so instances playing the same clip are not in sync.
+    pub time_offset: f32,
+    /// The playback speed of this instance.
+    pub speed: f32,
+    /// Whether to update the pose of this instance in [CrowdAnimator::update]. Set this to false, for example for instances far from the camera,
+    /// to reuse the last pose and save the cost of sampling the clip.
+    pub update: bool,
+}
+
+impl Default for CrowdInstance {
+    fn default() -> Self {
+        Self {
+            clip: 0,
+            time_offset: 0.0,
+            speed: 1.0,
+            update: true,
+        }
+    }
+}
+
+///
+/// Samples a looping [AnimationClip] for each of many instances of the same [Skeleton], for example a crowd of animated characters,
+/// into one list of skinning matrices which is uploaded to the GPU using [InstancedSkinnedMesh::update_joints].
+/// With the `rayon` feature enabled, the instances are sampled in parallel.
+///
+pub struct CrowdAnimator {
+    skeleton: Skeleton,
+    clips: Vec<AnimationClip>,
+    instances: Vec<CrowdInstance>,
+    matrices: Vec<Mat4>,
+    time: f32,
+}
+
+impl CrowdAnimator {
+    ///
+    /// Constructs a new animator for the given instances, where each instance plays one of the given clips,
+    /// and samples the initial pose of all instances.
+    ///
+    /// # Panic
+    /// Will panic if an instance refers to a clip that does not exist.
+    ///
+    pub fn new(
+        skeleton: Skeleton,
+        clips: Vec<AnimationClip>,
+        instances: Vec<CrowdInstance>,
+    ) -> Self {
+        let mut animator = Self {
+            matrices: Vec::new(),
+            skeleton,
+            clips,
+            instances: Vec::new(),
+            time: 0.0,
+        };
+        animator.set_instances(instances);
+        animator
+    }
+
+    ///
+    /// Returns the skeleton.
+    ///
+    pub fn skeleton(&self) -> &Skeleton {
+        &self.skeleton
+    }
+
+    ///
+    /// Returns the clips.
+    ///
+    pub fn clips(&self) -> &[AnimationClip] {
+        &self.clips
+    }
+
+    ///
+    /// Returns the animation state of the instances.
+    ///
+    pub fn instances(&self) -> &[CrowdInstance] {
+        &self.instances
+    }
+
+    ///
+    /// Returns the animation state of the instances mutably, for example to change the clip of an instance or to stop updating distant instances.
+    /// An instance must not refer to a clip that does not exist.
+    ///
+    pub fn instances_mut(&mut self) -> &mut [CrowdInstance] {
+        &mut self.instances
+    }
+
+    ///
+    /// Replaces the instances and samples the pose of all the new instances.
+    ///
+    /// # Panic
+    /// Will panic if an instance refers to a clip that does not exist.
+    ///
+    pub fn set_instances(&mut self, instances: Vec<CrowdInstance>) {
+        if let Some(instance) = instances.iter().find(|i| i.clip >= self.clips.len()) {
+            panic!(
+                "an instance plays clip {} but there are only {} clips",
+                instance.clip,
+                self.clips.len()
+            );
+        }
+        self.instances = instances;
+        self.matrices = vec![Mat4::identity(); self.instances.len() * self.skeleton.joint_count()];
+        self.sample(true);
+    }
+
+    ///
+    /// Returns the number of instances.
+    ///
+    pub fn instance_count(&self) -> usize {
+        self.instances.len()
+    }
+
+    ///
+    /// Returns the current time, ie. the sum of the elapsed times given to [CrowdAnimator::update].
+    ///
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    ///
+    /// Advances the time by the given elapsed time and samples the pose of each instance which should be updated (see [CrowdInstance::update]).
+    /// The clip of an instance is sampled at `(time + time_offset) * speed` and loops after the duration of the clip.
+    ///
+    pub fn update(&mut self, elapsed_time: f32) {
+        self.time += elapsed_time;
+        self.sample(false);
+    }
+
+    ///
+    /// Returns the skinning matrices (see [Skeleton::skinning_matrices]) for all instances after each other.
+    ///
+    pub fn joint_matrices(&self) -> &[Mat4] {
+        &self.matrices
+    }
+
+    ///
+    /// Returns the skinning matrices of the instance with the given index.
+    ///
+    pub fn instance_joint_matrices(&self, index: usize) -> &[Mat4] {
+        let joint_count = self.skeleton.joint_count();
+        &self.matrices[index * joint_count..(index + 1) * joint_count]
+    }
+
+    fn sample(&mut self, all: bool) {
+        let joint_count = self.skeleton.joint_count();
+        if joint_count == 0 {
+            return;
+        }
+        let skeleton = &self.skeleton;
+        let clips = &self.clips;
+        let time = self.time;
+        let sample_instance =
+            |scratch: &mut SkinningScratch, (matrices, instance): (&mut [Mat4], &CrowdInstance)| {
+                if all || instance.update {
+                    let clip = &clips[instance.clip];
+                    let duration = clip.duration();
+                    let t = (time + instance.time_offset) * instance.speed;
+                    let t = if duration > 0.0 {
+                        t.rem_euclid(duration)
+                    } else {
+                        0.0
+                    };
+                    skeleton.skinning_matrices(Some(clip), t, matrices, scratch);
+                }
+            };
+
+        #[cfg(feature = "rayon")]
+        {
+            use rayon::prelude::*;
+            self.matrices
+                .par_chunks_mut(joint_count)
+                .zip(self.instances.par_iter())
+                .for_each_init(SkinningScratch::default, sample_instance);
+        }
+        #[cfg(not(feature = "rayon"))]
+        {
+            let mut scratch = SkinningScratch::default();
+            self.matrices
+                .chunks_mut(joint_count)
+                .zip(self.instances.iter())
+                .for_each(|item| sample_instance(&mut scratch, item));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn arm() -> Skeleton {
+        // A shoulder at the origin and an elbow one unit along the x-axis
+        Skeleton {
+            joints: vec![
+                Joint {
+                    parent: None,
+                    translation: Vec3::zero(),
+                    rotation: Quat::one(),
+                    scale: vec3(1.0, 1.0, 1.0),
+                    inverse_bind_matrix: Mat4::identity(),
+                },
+                Joint {
+                    parent: Some(0),
+                    translation: vec3(1.0, 0.0, 0.0),
+                    rotation: Quat::one(),
+                    scale: vec3(1.0, 1.0, 1.0),
+                    inverse_bind_matrix: Mat4::from_translation(vec3(-1.0, 0.0, 0.0)),
+                },
+            ],
+        }
+    }
+
+    fn raise_arm() -> AnimationClip {
+        AnimationClip {
+            name: "raise".to_string(),
+            tracks: vec![AnimationTrack::Quat(Track {
+                target: AnimationTarget::Rotation(0),
+                interpolation: KeyframeInterpolation::Linear,
+                keyframes: vec![
+                    Keyframe::new(0.0, Quat::one(), Quat::zero()),
+                    Keyframe::new(2.0, Quat::from_angle_z(degrees(90.0)), Quat::zero()),
+                ],
+            })],
+        }
+    }
+
+    #[test]
+    fn test_rest_pose_is_identity() {
+        let mut matrices = vec![Mat4::zero(); 2];
+        arm().skinning_matrices(None, 0.0, &mut matrices, &mut SkinningScratch::default());
+        for matrix in matrices {
+            assert!((matrix - Mat4::identity()).x.magnitude() < 1.0e-6);
+            assert!((matrix - Mat4::identity()).w.magnitude() < 1.0e-6);
+        }
+    }
+
+    #[test]
+    fn test_crowd_instances() {
+        let mut animator = CrowdAnimator::new(
+            arm(),
+            vec![raise_arm()],
+            vec![
+                CrowdInstance::default(),
+                CrowdInstance {
+                    time_offset: 2.0,
+                    update: false,
+                    ..Default::default()
+                },
+            ],
+        );
+        assert_eq!(animator.joint_matrices().len(), 4);
+        animator.update(1.0);
+
+        // The tip of the forearm, which is at (2, 0, 0) in the bind pose, is rotated 45 degrees around the shoulder
+        let tip = animator.instance_joint_matrices(0)[1] * vec4(2.0, 0.0, 0.0, 1.0);
+        let expected = 2.0 * std::f32::consts::FRAC_1_SQRT_2;
+        assert!((tip.truncate() - vec3(expected, expected, 0.0)).magnitude() < 1.0e-5);
+
+        // The second instance is not updated, so it keeps the pose sampled at construction, ie. at time 2 which wraps around to 0
+        let tip = animator.instance_joint_matrices(1)[1] * vec4(2.0, 0.0, 0.0, 1.0);
+        assert!((tip.truncate() - vec3(2.0, 0.0, 0.0)).magnitude() < 1.0e-5);
+    }
+}
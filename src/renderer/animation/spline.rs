@@ -0,0 +1,255 @@
+use super::Animatable;
+use crate::core::*;
+
+///
+/// The type of curve used by a [Spline].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum SplineType<T: Animatable> {
+    ///
+    /// A Catmull-Rom spline which passes through all keyframes with the tangent at each keyframe determined by the neighbouring keyframes.
+    /// The first and last keyframes are repeated to define the tangents at the ends.
+    ///
+    CatmullRom,
+    ///
+    /// A cubic Bezier curve between each two consecutive keyframes which passes through all keyframes.
+    /// The tangents contain the two control points, given as absolute values and not relative to the keyframe value, for each keyframe,
+    /// where the first is used when interpolating from the previous keyframe and the second when interpolating to the next keyframe.
+    /// There must be exactly one pair of control points per keyframe.
+    ///
+    Bezier {
+        /// The incoming and outgoing control point for each keyframe.
+        tangents: Vec<(T, T)>,
+    },
+    ///
+    /// A clamped B-spline of the given degree which uses the keyframe values as control points,
+    /// so it only passes through the first and last keyframe but is smoother than the other spline types.
+    /// The knots are placed by averaging the keyframe times, so the curve follows the timing of the keyframes.
+    /// A degree of 1 is the same as linear interpolation and the degree is limited to the number of keyframes minus one.
+    ///
+    BSpline {
+        /// The degree of the polynomial pieces, for example 3 for a cubic B-spline.
+        degree: u32,
+    },
+}
+
+///
+/// A curve through or near a list of keyframes, which for example can be used for camera paths, animation curves or for generating geometry along a path.
+/// The keyframes are given as a parameter value, for example a time, and a value at that parameter.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spline<T: Animatable> {
+    /// The type of curve.
+    pub spline_type: SplineType<T>,
+    keyframes: Vec<(f32, T)>,
+}
+
+impl<T: Animatable> Spline<T> {
+    ///
+    /// Creates a new spline of the given type without keyframes.
+    ///
+    pub fn new(spline_type: SplineType<T>) -> Self {
+        Self {
+            spline_type,
+            keyframes: Vec::new(),
+        }
+    }
+
+    ///
+    /// Adds a keyframe with the given value at the given parameter value.
+    /// The keyframes are kept sorted by the parameter value, a keyframe with the same parameter value as an existing keyframe is inserted after the existing keyframe.
+    /// Note that for [SplineType::Bezier], the control points must be given in the same order as the sorted keyframes.
+    ///
+    pub fn add_keyframe(&mut self, t: f32, value: T) {
+        let index = self.keyframes.partition_point(|(k, _)| *k <= t);
+        self.keyframes.insert(index, (t, value));
+    }
+
+    ///
+    /// Returns the keyframes sorted by the parameter value.
+    ///
+    pub fn keyframes(&self) -> &[(f32, T)] {
+        &self.keyframes
+    }
+
+    ///
+    /// Returns the value of the spline at the given parameter value or `None` if the spline has no keyframes.
+    /// Before the first keyframe, the value at the first keyframe is returned and after the last keyframe, the value at the last keyframe is returned.
+    ///
+    /// # Panic
+    /// Will panic if the spline is a [SplineType::Bezier] and the number of control point pairs does not match the number of keyframes.
+    ///
+    pub fn sample(&self, t: f32) -> Option<T> {
+        let (first, last) = (self.keyframes.first()?, self.keyframes.last()?);
+        let t = t.clamp(first.0, last.0);
+        if let SplineType::BSpline { degree } = self.spline_type {
+            return Some(self.sample_b_spline(degree as usize, t));
+        }
+
+        let next = self
+            .keyframes
+            .partition_point(|(k, _)| *k <= t)
+            .clamp(1, self.keyframes.len());
+        if next == self.keyframes.len() {
+            return Some(last.1);
+        }
+        let i = next - 1;
+        let (t0, p1) = self.keyframes[i];
+        let (t1, p2) = self.keyframes[next];
+        let u = if t1 > t0 { (t - t0) / (t1 - t0) } else { 0.0 };
+        let (u2, u3) = (u * u, u * u * u);
+        Some(match &self.spline_type {
+            SplineType::CatmullRom => {
+                let p0 = self.keyframes[i.saturating_sub(1)].1;
+                let p3 = self.keyframes[(next + 1).min(self.keyframes.len() - 1)].1;
+                T::weighted_sum(&[
+                    (p0, -0.5 * u3 + u2 - 0.5 * u),
+                    (p1, 1.5 * u3 - 2.5 * u2 + 1.0),
+                    (p2, -1.5 * u3 + 2.0 * u2 + 0.5 * u),
+                    (p3, 0.5 * u3 - 0.5 * u2),
+                ])
+            }
+            SplineType::Bezier { tangents } => {
+                if tangents.len() != self.keyframes.len() {
+                    panic!(
+                        "a bezier spline needs a pair of control points for each keyframe, got {} pairs for {} keyframes",
+                        tangents.len(),
+                        self.keyframes.len()
+                    );
+                }
+                let v = 1.0 - u;
+                T::weighted_sum(&[
+                    (p1, v * v * v),
+                    (tangents[i].1, 3.0 * v * v * u),
+                    (tangents[next].0, 3.0 * v * u2),
+                    (p2, u3),
+                ])
+            }
+            SplineType::BSpline { .. } => unreachable!(),
+        })
+    }
+
+    ///
+    /// Evaluates the clamped B-spline using de Boor's algorithm.
+    ///
+    fn sample_b_spline(&self, degree: usize, t: f32) -> T {
+        let n = self.keyframes.len();
+        let p = degree.min(n - 1);
+        if p == 0 {
+            let index = self.keyframes.partition_point(|(k, _)| *k <= t);
+            return self.keyframes[index.clamp(1, n) - 1].1;
+        }
+        let mut knots = vec![self.keyframes[0].0; p + 1];
+        for j in 1..n - p {
+            knots.push(self.keyframes[j..j + p].iter().map(|(k, _)| k).sum::<f32>() / p as f32);
+        }
+        knots.extend(std::iter::repeat(self.keyframes[n - 1].0).take(p + 1));
+
+        // The knot span containing t
+        let k = (p..n)
+            .rev()
+            .find(|k| knots[*k] <= t && knots[*k] < knots[*k + 1])
+            .unwrap_or(p);
+        let mut d = (0..=p)
+            .map(|j| self.keyframes[j + k - p].1)
+            .collect::<Vec<_>>();
+        for r in 1..=p {
+            for j in (r..=p).rev() {
+                let left = knots[j + k - p];
+                let right = knots[j + 1 + k - r];
+                let alpha = if right > left {
+                    (t - left) / (right - left)
+                } else {
+                    0.0
+                };
+                d[j] = d[j - 1].interpolate(&d[j], alpha);
+            }
+        }
+        d[p]
+    }
+}
+
+impl Spline<Vec3> {
+    ///
+    /// Divides the parameter range between the first and the last keyframe into the given number of uniform steps and samples the spline at the ends of each step,
+    /// which results in `steps + 1` positions that for example can be used to visualize the spline as a line strip.
+    /// Returns an empty list if the spline has no keyframes.
+    ///
+    pub fn to_polyline(&self, steps: u32) -> Vec<Vec3> {
+        let (first, last) = match (self.keyframes.first(), self.keyframes.last()) {
+            (Some(first), Some(last)) => (first.0, last.0),
+            _ => return Vec::new(),
+        };
+        let steps = steps.max(1);
+        (0..=steps)
+            .filter_map(|i| self.sample(first + (last - first) * i as f32 / steps as f32))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn spline(spline_type: SplineType<Vec3>) -> Spline<Vec3> {
+        let mut spline = Spline::new(spline_type);
+        spline.add_keyframe(2.0, vec3(2.0, 0.0, 0.0));
+        spline.add_keyframe(0.0, vec3(0.0, 0.0, 0.0));
+        spline.add_keyframe(1.0, vec3(1.0, 1.0, 0.0));
+        spline.add_keyframe(3.0, vec3(3.0, 1.0, 0.0));
+        spline
+    }
+
+    fn assert_close(a: Vec3, b: Vec3) {
+        assert!(a.distance(b) < 0.0001, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_catmull_rom() {
+        let spline = spline(SplineType::CatmullRom);
+        assert!(Spline::<Vec3>::new(SplineType::CatmullRom)
+            .sample(0.0)
+            .is_none());
+        for (t, value) in spline.keyframes() {
+            assert_close(spline.sample(*t).unwrap(), *value);
+        }
+        assert_close(spline.sample(-1.0).unwrap(), vec3(0.0, 0.0, 0.0));
+        assert_close(spline.sample(4.0).unwrap(), vec3(3.0, 1.0, 0.0));
+        // Symmetric keyframes around t = 1.5
+        assert_close(spline.sample(1.5).unwrap(), vec3(1.5, 0.5, 0.0));
+    }
+
+    #[test]
+    fn test_bezier() {
+        let mut spline = Spline::new(SplineType::Bezier {
+            tangents: vec![
+                (vec3(0.0, 0.0, 0.0), vec3(0.0, 1.0, 0.0)),
+                (vec3(1.0, 1.0, 0.0), vec3(1.0, 0.0, 0.0)),
+            ],
+        });
+        spline.add_keyframe(0.0, vec3(0.0, 0.0, 0.0));
+        spline.add_keyframe(1.0, vec3(1.0, 0.0, 0.0));
+        assert_close(spline.sample(0.0).unwrap(), vec3(0.0, 0.0, 0.0));
+        assert_close(spline.sample(0.5).unwrap(), vec3(0.5, 0.75, 0.0));
+        assert_close(spline.sample(1.0).unwrap(), vec3(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_b_spline() {
+        // A B-spline of degree one is linear interpolation
+        let linear = spline(SplineType::BSpline { degree: 1 });
+        assert_close(linear.sample(0.5).unwrap(), vec3(0.5, 0.5, 0.0));
+        assert_close(linear.sample(2.25).unwrap(), vec3(2.25, 0.25, 0.0));
+
+        let cubic = spline(SplineType::BSpline { degree: 3 });
+        assert_close(cubic.sample(0.0).unwrap(), vec3(0.0, 0.0, 0.0));
+        assert_close(cubic.sample(3.0).unwrap(), vec3(3.0, 1.0, 0.0));
+        // With four control points, the cubic B-spline is a Bezier curve
+        assert_close(cubic.sample(1.5).unwrap(), vec3(1.5, 0.5, 0.0));
+
+        let polyline = cubic.to_polyline(10);
+        assert_eq!(polyline.len(), 11);
+        assert_close(polyline[0], vec3(0.0, 0.0, 0.0));
+        assert_close(polyline[10], vec3(3.0, 1.0, 0.0));
+    }
+}
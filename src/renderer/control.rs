@@ -18,6 +18,14 @@ mod fly_control;
 #[doc(inline)]
 pub use fly_control::*;
 
+mod shortcuts;
+#[doc(inline)]
+pub use shortcuts::*;
+
+mod touch_overlay;
+#[doc(inline)]
+pub use touch_overlay::*;
+
 pub use three_d_asset::PixelPoint as PhysicalPoint;
 
 ///
@@ -34,6 +42,23 @@ pub struct LogicalPoint {
     pub(crate) height: f32,
 }
 
+impl LogicalPoint {
+    ///
+    /// Returns the position in physical pixels with the origin at the bottom left corner of the screen,
+    /// which is what for example [camera2d](crate::renderer::camera2d) and [CameraExt::world_to_pixel](crate::core::CameraExt::world_to_pixel) use.
+    ///
+    pub fn to_physical(&self) -> PhysicalPoint {
+        self.into()
+    }
+
+    ///
+    /// Returns the number of physical pixels for each logical pixel at the time this position was generated.
+    ///
+    pub fn device_pixel_ratio(&self) -> f32 {
+        self.device_pixel_ratio
+    }
+}
+
 impl From<LogicalPoint> for (f32, f32) {
     fn from(value: LogicalPoint) -> Self {
         Self::from(&value)
@@ -130,12 +155,28 @@ pub enum Event {
         /// The relative scrolling since the last [Event::MouseWheel] event.
         delta: (f32, f32),
         /// The screen position in logical pixels.
+        /// If the cursor position is not known, for example if the wheel is the first input after the window is created, this is the center of the window,
+        /// so controls zooming towards the cursor zoom towards the center.
         position: LogicalPoint,
         /// The state of modifiers.
         modifiers: Modifiers,
         /// Whether or not this event already have been handled.
         handled: bool,
     },
+    ///
+    /// Fired for pen and touch input directly before each [Event::MousePress], [Event::MouseMotion] and [Event::MouseRelease] event generated by the pen or finger,
+    /// so the pressure and tilt are known when handling the following event. Not fired for mouse input.
+    ///
+    PointerPressure {
+        /// The screen position in logical pixels.
+        position: LogicalPoint,
+        /// The pressure normalized to the range `[0, 1]` or `None` if the device or platform doesn't report the pressure.
+        pressure: Option<f32>,
+        /// The tilt of the pen in degrees in the range `[-90, 90]` in the x and y direction or `None` if the device or platform doesn't report the tilt.
+        tilt: Option<(f32, f32)>,
+        /// Whether or not this event already have been handled.
+        handled: bool,
+    },
     /// Fired when the mouse enters the window.
     MouseEnter,
     /// Fired when the mouse leaves the window.
@@ -236,6 +277,19 @@ pub enum Key {
     X,
     Y,
     Z,
+
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
 }
 
 /// State of modifiers (alt, ctrl, shift and command).
@@ -3,9 +3,18 @@ use crate::core::*;
 
 ///
 /// A control that makes the camera orbit around a target.
+/// Dragging with the left mouse button orbits, dragging with the right mouse button pans and scrolling zooms.
+///
+/// The control works with both perspective and orthographic cameras.
+/// For a perspective camera, zooming changes the distance to the target.
+/// For an orthographic camera, where the distance does not change the size of the scene, zooming instead scales the height of the view
+/// around the cursor position, such that the point under the cursor stays fixed. The minimum and maximum distance then limit the height of the view.
 ///
 pub struct OrbitControl {
     control: CameraControl,
+    target: Vec3,
+    min_distance: f32,
+    max_distance: f32,
 }
 
 impl OrbitControl {
@@ -23,23 +32,173 @@ impl OrbitControl {
                 },
                 ..Default::default()
             },
+            target,
+            min_distance,
+            max_distance,
         }
     }
 
+    /// Returns the target which the camera orbits around, which is moved when panning.
+    pub fn target(&self) -> Vec3 {
+        self.target
+    }
+
     /// Handles the events. Must be called each frame.
     pub fn handle_events(&mut self, camera: &mut Camera, events: &mut [Event]) -> bool {
-        if let CameraAction::Zoom { speed, target, .. } = &mut self.control.scroll_vertical {
-            let x = target.distance(*camera.position());
+        let mut change = false;
+        for event in events.iter_mut() {
+            match event {
+                Event::MouseMotion {
+                    delta,
+                    button: Some(MouseButton::Right),
+                    position,
+                    handled,
+                    ..
+                } if !*handled => {
+                    self.pan(camera, *delta, position.device_pixel_ratio());
+                    *handled = true;
+                    change = true;
+                }
+                Event::MouseWheel {
+                    delta,
+                    position,
+                    handled,
+                    ..
+                } if !*handled && camera.orthographic_height().is_some() => {
+                    self.zoom_orthographic(camera, delta.1, position.to_physical());
+                    *handled = true;
+                    change = true;
+                }
+                _ => {}
+            }
+        }
+
+        let target = self.target;
+        let x = target.distance(*camera.position());
+        if let CameraAction::Zoom {
+            speed, target: t, ..
+        } = &mut self.control.scroll_vertical
+        {
             *speed = 0.01 * x + 0.001;
+            *t = target;
         }
-        if let CameraAction::OrbitLeft { speed, target } = &mut self.control.left_drag_horizontal {
-            let x = target.distance(*camera.position());
+        if let CameraAction::OrbitLeft { speed, target: t } = &mut self.control.left_drag_horizontal
+        {
             *speed = 0.01 * x + 0.001;
+            *t = target;
         }
-        if let CameraAction::OrbitUp { speed, target } = &mut self.control.left_drag_vertical {
-            let x = target.distance(*camera.position());
+        if let CameraAction::OrbitUp { speed, target: t } = &mut self.control.left_drag_vertical {
             *speed = 0.01 * x + 0.001;
+            *t = target;
+        }
+        change | self.control.handle_events(camera, events)
+    }
+
+    ///
+    /// Moves the camera and the target such that the point at the distance of the target follows the cursor.
+    ///
+    fn pan(&mut self, camera: &mut Camera, delta: (f32, f32), device_pixel_ratio: f32) {
+        let distance = camera.view_depth(self.target);
+        let scale = camera.world_units_per_pixel(distance) * device_pixel_ratio;
+        let right = camera.right_direction();
+        let up = right.cross(camera.view_direction());
+        let change = (-right * delta.0 + up * delta.1) * scale;
+        camera.translate(&change);
+        self.target += change;
+    }
+
+    ///
+    /// Scales the height of the orthographic view around the given pixel, such that the point under the pixel stays fixed.
+    ///
+    fn zoom_orthographic(&mut self, camera: &mut Camera, delta: f32, pixel: PhysicalPoint) {
+        let height = camera.orthographic_height().unwrap();
+        let new_height =
+            (height * (-0.01 * delta).exp()).clamp(self.min_distance, self.max_distance);
+        let view_direction = camera.view_direction();
+        let mut offset = camera.position_at_pixel(pixel) - *camera.position();
+        offset -= view_direction * offset.dot(view_direction);
+        camera.set_orthographic_projection(new_height, camera.z_near(), camera.z_far());
+        let change = offset * (1.0 - new_height / height);
+        camera.translate(&change);
+        self.target += change;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cameras() -> [Camera; 2] {
+        let viewport = Viewport::new_at_origo(200, 100);
+        let position = vec3(0.0, 0.0, 5.0);
+        [
+            Camera::new_perspective(
+                viewport,
+                position,
+                Vec3::zero(),
+                Vec3::unit_y(),
+                degrees(45.0),
+                0.1,
+                100.0,
+            ),
+            Camera::new_orthographic(
+                viewport,
+                position,
+                Vec3::zero(),
+                Vec3::unit_y(),
+                4.0,
+                0.1,
+                100.0,
+            ),
+        ]
+    }
+
+    fn cursor(x: f32, y: f32) -> LogicalPoint {
+        LogicalPoint {
+            x,
+            y,
+            device_pixel_ratio: 1.0,
+            height: 100.0,
+        }
+    }
+
+    #[test]
+    fn test_orthographic_zoom_keeps_point_under_cursor() {
+        let mut camera = cameras()[1].clone();
+        let mut control = OrbitControl::new(Vec3::zero(), 0.1, 100.0);
+        let position = cursor(150.0, 20.0);
+        let point = camera.position_at_pixel(position.to_physical()) - 5.0 * Vec3::unit_z();
+        for delta in [10.0, 30.0, -20.0] {
+            let mut events = [Event::MouseWheel {
+                delta: (0.0, delta),
+                position,
+                modifiers: Modifiers::default(),
+                handled: false,
+            }];
+            assert!(control.handle_events(&mut camera, &mut events));
+            let pixel = camera.world_to_pixel(point).unwrap();
+            assert!((pixel.x - 150.0).abs() < 0.01 && (pixel.y - 80.0).abs() < 0.01);
+        }
+        assert!(camera.orthographic_height().unwrap() < 4.0);
+        assert_eq!(camera.position().z, 5.0);
+    }
+
+    #[test]
+    fn test_pan_follows_cursor() {
+        for mut camera in cameras() {
+            let mut control = OrbitControl::new(Vec3::zero(), 0.1, 100.0);
+            let mut events = [Event::MouseMotion {
+                button: Some(MouseButton::Right),
+                delta: (20.0, -10.0),
+                position: cursor(120.0, 40.0),
+                modifiers: Modifiers::default(),
+                handled: false,
+            }];
+            assert!(control.handle_events(&mut camera, &mut events));
+            // The original target is moved the same number of pixels as the cursor, the y axis of logical pixels points down
+            let pixel = camera.world_to_pixel(Vec3::zero()).unwrap();
+            assert!((pixel.x - 120.0).abs() < 0.01 && (pixel.y - 60.0).abs() < 0.01);
+            assert!((control.target().distance(*camera.position()) - 5.0).abs() < 0.0001);
         }
-        self.control.handle_events(camera, events)
     }
 }
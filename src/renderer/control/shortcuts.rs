@@ -0,0 +1,552 @@
+use super::*;
+use crate::renderer::RendererError;
+use std::collections::HashSet;
+
+///
+/// A keyboard shortcut, ie. a key combined with a set of modifiers, for example `CmdOrCtrl+Shift+Z`.
+/// Use [Shortcut::new] and the struct update syntax or parse it from a string, see [Shortcut::from_str](std::str::FromStr::from_str).
+///
+/// The [Shortcut::command] modifier uses the [Modifiers::command] abstraction, ie. it is the ⌘ Command key on Mac and the Control key on other platforms,
+/// which is what most shortcuts should use, while [Shortcut::ctrl] is always the Control key.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Shortcut {
+    /// The key which triggers the shortcut.
+    pub key: Key,
+    /// Whether the ⌘ Command key on Mac and the Control key on other platforms must be down.
+    pub command: bool,
+    /// Whether the Control key must be down. On other platforms than Mac, this is the same as [Shortcut::command].
+    pub ctrl: bool,
+    /// Whether the Alt key (⌥ Option on Mac) must be down.
+    pub alt: bool,
+    /// Whether the Shift key must be down.
+    pub shift: bool,
+    /// Whether the shortcut fires again when the key is held down and the key press is repeated. Defaults to false.
+    pub repeat: bool,
+}
+
+impl Shortcut {
+    ///
+    /// Creates a new shortcut which is triggered by pressing the given key without any modifiers.
+    ///
+    pub fn new(key: Key) -> Self {
+        Self {
+            key,
+            command: false,
+            ctrl: false,
+            alt: false,
+            shift: false,
+            repeat: false,
+        }
+    }
+
+    ///
+    /// Returns whether the given modifiers are exactly the modifiers of this shortcut, where `mac_keyboard` specifies
+    /// whether the ⌘ Command key and the Control key are different keys, see [Shortcuts::set_mac_keyboard].
+    ///
+    pub fn matches(&self, modifiers: &Modifiers, mac_keyboard: bool) -> bool {
+        let (command, ctrl) = self.physical_modifiers(mac_keyboard);
+        let modifiers_match = if mac_keyboard {
+            modifiers.command == command && modifiers.ctrl == ctrl
+        } else {
+            // The command modifier mirrors the Control key, so only the Control key is compared
+            modifiers.ctrl == ctrl
+        };
+        modifiers_match && modifiers.alt == self.alt && modifiers.shift == self.shift
+    }
+
+    ///
+    /// Returns whether this and the other shortcut are triggered by the same keys, where `mac_keyboard` specifies
+    /// whether the ⌘ Command key and the Control key are different keys, see [Shortcuts::set_mac_keyboard].
+    ///
+    pub fn conflicts_with(&self, other: &Shortcut, mac_keyboard: bool) -> bool {
+        self.key == other.key
+            && self.alt == other.alt
+            && self.shift == other.shift
+            && self.physical_modifiers(mac_keyboard) == other.physical_modifiers(mac_keyboard)
+    }
+
+    /// Returns whether the ⌘ Command key and the Control key must be down.
+    fn physical_modifiers(&self, mac_keyboard: bool) -> (bool, bool) {
+        if mac_keyboard {
+            (self.command, self.ctrl)
+        } else {
+            let ctrl = self.command || self.ctrl;
+            (ctrl, ctrl)
+        }
+    }
+}
+
+impl std::str::FromStr for Shortcut {
+    type Err = RendererError;
+
+    ///
+    /// Parses a shortcut from a string of modifiers and a key separated by `+`, for example `CmdOrCtrl+Shift+Z`, `Ctrl+Tab` or `Shift+F5`.
+    /// The modifiers are `CmdOrCtrl`, `Cmd` or `Command` for [Shortcut::command], `Ctrl` or `Control` for [Shortcut::ctrl],
+    /// `Alt` or `Option` for [Shortcut::alt] and `Shift` for [Shortcut::shift]. The Mac symbols `⌘`, `⌃`, `⌥` and `⇧` can also be used
+    /// and they do not need to be separated by `+`, for example `⌘⇧Z`. The names are not case sensitive.
+    ///
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || RendererError::InvalidShortcut(s.to_string());
+        let mut tokens = Vec::new();
+        for part in s.split('+').map(|part| part.trim()) {
+            // The Mac symbols can be written without separators, for example ⌘⇧Z
+            let mut rest = part;
+            while let Some(symbol) = rest.chars().next().filter(|c| "⌘⌃⌥⇧".contains(*c)) {
+                tokens.push(&rest[..symbol.len_utf8()]);
+                rest = rest[symbol.len_utf8()..].trim_start();
+            }
+            if !rest.is_empty() || tokens.is_empty() {
+                tokens.push(rest);
+            }
+        }
+        let (key, modifiers) = tokens.split_last().ok_or_else(error)?;
+        let mut shortcut = Shortcut::new(parse_key(key).ok_or_else(error)?);
+        for modifier in modifiers {
+            let flag = match modifier.to_lowercase().as_str() {
+                "cmdorctrl" | "commandorcontrol" | "cmd" | "command" | "⌘" => {
+                    &mut shortcut.command
+                }
+                "ctrl" | "control" | "⌃" => &mut shortcut.ctrl,
+                "alt" | "option" | "⌥" => &mut shortcut.alt,
+                "shift" | "⇧" => &mut shortcut.shift,
+                _ => return Err(error()),
+            };
+            if *flag {
+                // The same modifier is given twice
+                return Err(error());
+            }
+            *flag = true;
+        }
+        Ok(shortcut)
+    }
+}
+
+impl std::fmt::Display for Shortcut {
+    ///
+    /// Writes the shortcut in the same format as it is parsed from, for example `CmdOrCtrl+Shift+Z`.
+    ///
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (enabled, name) in [
+            (self.command, "CmdOrCtrl+"),
+            (self.ctrl, "Ctrl+"),
+            (self.alt, "Alt+"),
+            (self.shift, "Shift+"),
+        ] {
+            if enabled {
+                write!(f, "{}", name)?;
+            }
+        }
+        let key = format!("{:?}", self.key);
+        write!(f, "{}", key.strip_prefix("Num").unwrap_or(&key))
+    }
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    let name = name.to_lowercase();
+    if let Some(number) = name.strip_prefix('f').and_then(|n| n.parse::<u32>().ok()) {
+        return [
+            Key::F1,
+            Key::F2,
+            Key::F3,
+            Key::F4,
+            Key::F5,
+            Key::F6,
+            Key::F7,
+            Key::F8,
+            Key::F9,
+            Key::F10,
+            Key::F11,
+            Key::F12,
+        ]
+        .get((number as usize).wrapping_sub(1))
+        .copied();
+    }
+    Some(match name.as_str() {
+        "down" | "arrowdown" => Key::ArrowDown,
+        "left" | "arrowleft" => Key::ArrowLeft,
+        "right" | "arrowright" => Key::ArrowRight,
+        "up" | "arrowup" => Key::ArrowUp,
+        "esc" | "escape" => Key::Escape,
+        "tab" => Key::Tab,
+        "backspace" => Key::Backspace,
+        "enter" | "return" => Key::Enter,
+        "space" => Key::Space,
+        "insert" => Key::Insert,
+        "delete" | "del" => Key::Delete,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        _ => {
+            let mut chars = name.chars();
+            let c = chars.next()?;
+            if chars.next().is_some() {
+                return None;
+            }
+            let keys = match c {
+                'a'..='z' => [
+                    Key::A,
+                    Key::B,
+                    Key::C,
+                    Key::D,
+                    Key::E,
+                    Key::F,
+                    Key::G,
+                    Key::H,
+                    Key::I,
+                    Key::J,
+                    Key::K,
+                    Key::L,
+                    Key::M,
+                    Key::N,
+                    Key::O,
+                    Key::P,
+                    Key::Q,
+                    Key::R,
+                    Key::S,
+                    Key::T,
+                    Key::U,
+                    Key::V,
+                    Key::W,
+                    Key::X,
+                    Key::Y,
+                    Key::Z,
+                ]
+                .as_slice(),
+                '0'..='9' => [
+                    Key::Num0,
+                    Key::Num1,
+                    Key::Num2,
+                    Key::Num3,
+                    Key::Num4,
+                    Key::Num5,
+                    Key::Num6,
+                    Key::Num7,
+                    Key::Num8,
+                    Key::Num9,
+                ]
+                .as_slice(),
+                _ => return None,
+            };
+            let first = if c.is_ascii_digit() { '0' } else { 'a' };
+            keys[(c as u32 - first as u32) as usize]
+        }
+    })
+}
+
+///
+/// A registry of keyboard [Shortcut]s, which each triggers an action of the given type, for example an enum or a string.
+/// Feed it the events each frame using [Shortcuts::handle_events], which returns the actions that were triggered
+/// and marks the key presses that triggered them as handled, so they are not also used by for example a text field or a camera control.
+///
+/// ```
+/// # use three_d::*;
+/// let mut shortcuts = Shortcuts::new();
+/// shortcuts.register("CmdOrCtrl+Z", "undo").unwrap();
+/// shortcuts.register("CmdOrCtrl+Shift+Z", "redo").unwrap();
+/// // Registering the same keys twice is an error
+/// assert!(shortcuts.register("Shift+CmdOrCtrl+Z", "other").is_err());
+/// ```
+///
+pub struct Shortcuts<A> {
+    shortcuts: Vec<(Shortcut, A)>,
+    mac_keyboard: bool,
+    pressed_keys: HashSet<Key>,
+}
+
+impl<A: Clone> Shortcuts<A> {
+    ///
+    /// Creates a new empty registry of shortcuts, where the ⌘ Command key and the Control key are different keys if the target is Mac,
+    /// see [Shortcuts::set_mac_keyboard].
+    ///
+    pub fn new() -> Self {
+        Self {
+            shortcuts: Vec::new(),
+            mac_keyboard: cfg!(any(target_os = "macos", target_os = "ios")),
+            pressed_keys: HashSet::new(),
+        }
+    }
+
+    ///
+    /// Returns whether the ⌘ Command key and the Control key are different keys.
+    ///
+    pub fn mac_keyboard(&self) -> bool {
+        self.mac_keyboard
+    }
+
+    ///
+    /// Sets whether the ⌘ Command key and the Control key are different keys, which is the case on Mac.
+    /// This is detected from the target, except on web where it should be set if the browser runs on a Mac.
+    ///
+    pub fn set_mac_keyboard(&mut self, mac_keyboard: bool) {
+        self.mac_keyboard = mac_keyboard;
+    }
+
+    ///
+    /// Registers the shortcut given as a string, see [Shortcut::from_str](std::str::FromStr::from_str), which triggers the given action.
+    /// Returns an error if the string cannot be parsed or if the shortcut conflicts with an already registered shortcut.
+    ///
+    pub fn register(&mut self, shortcut: &str, action: A) -> Result<(), RendererError> {
+        self.register_shortcut(shortcut.parse()?, action)
+    }
+
+    ///
+    /// Registers the shortcut which triggers the given action.
+    /// Returns an error if the shortcut conflicts with an already registered shortcut, ie. if they are triggered by the same keys.
+    ///
+    pub fn register_shortcut(
+        &mut self,
+        shortcut: Shortcut,
+        action: A,
+    ) -> Result<(), RendererError> {
+        if let Some((existing, _)) = self
+            .shortcuts
+            .iter()
+            .find(|(existing, _)| existing.conflicts_with(&shortcut, self.mac_keyboard))
+        {
+            return Err(RendererError::ShortcutConflict(
+                shortcut.to_string(),
+                existing.to_string(),
+            ));
+        }
+        self.shortcuts.push((shortcut, action));
+        Ok(())
+    }
+
+    ///
+    /// Removes all shortcuts which trigger an action for which the given predicate returns true.
+    ///
+    pub fn unregister(&mut self, mut predicate: impl FnMut(&A) -> bool) {
+        self.shortcuts.retain(|(_, action)| !predicate(action));
+    }
+
+    ///
+    /// Returns the registered shortcuts and their actions.
+    ///
+    pub fn shortcuts(&self) -> impl Iterator<Item = (&Shortcut, &A)> {
+        self.shortcuts
+            .iter()
+            .map(|(shortcut, action)| (shortcut, action))
+    }
+
+    ///
+    /// Handles the given events and returns the actions of the shortcuts that were triggered in the order of the events.
+    /// The key presses that match a shortcut are marked as handled, also if they are repeated key presses and the shortcut does not allow repeats,
+    /// and key presses that are already handled are ignored.
+    /// A key press is a repeated key press if the key has been pressed and not yet released.
+    ///
+    pub fn handle_events(&mut self, events: &mut [Event]) -> Vec<A> {
+        let mut actions = Vec::new();
+        for event in events.iter_mut() {
+            match event {
+                Event::KeyPress {
+                    kind,
+                    modifiers,
+                    handled,
+                } => {
+                    let repeated = !self.pressed_keys.insert(*kind);
+                    if *handled {
+                        continue;
+                    }
+                    if let Some((shortcut, action)) = self.shortcuts.iter().find(|(shortcut, _)| {
+                        shortcut.key == *kind && shortcut.matches(modifiers, self.mac_keyboard)
+                    }) {
+                        *handled = true;
+                        if !repeated || shortcut.repeat {
+                            actions.push(action.clone());
+                        }
+                    }
+                }
+                Event::KeyRelease { kind, .. } => {
+                    self.pressed_keys.remove(kind);
+                }
+                _ => {}
+            }
+        }
+        actions
+    }
+}
+
+impl<A: Clone> Default for Shortcuts<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn key_press(kind: Key, modifiers: Modifiers) -> Event {
+        Event::KeyPress {
+            kind,
+            modifiers,
+            handled: false,
+        }
+    }
+
+    fn key_release(kind: Key) -> Event {
+        Event::KeyRelease {
+            kind,
+            modifiers: Modifiers::default(),
+            handled: false,
+        }
+    }
+
+    const WINDOWS_CTRL: Modifiers = Modifiers {
+        alt: false,
+        ctrl: true,
+        shift: false,
+        command: true,
+    };
+    const MAC_COMMAND: Modifiers = Modifiers {
+        alt: false,
+        ctrl: false,
+        shift: false,
+        command: true,
+    };
+    const MAC_CTRL: Modifiers = Modifiers {
+        alt: false,
+        ctrl: true,
+        shift: false,
+        command: false,
+    };
+
+    #[test]
+    fn test_parse() {
+        let shortcut: Shortcut = "CmdOrCtrl+Shift+Z".parse().unwrap();
+        assert_eq!(
+            shortcut,
+            Shortcut {
+                command: true,
+                shift: true,
+                ..Shortcut::new(Key::Z)
+            }
+        );
+        assert_eq!(
+            "cmdorctrl + shift + z".parse::<Shortcut>().unwrap(),
+            shortcut
+        );
+        assert_eq!("⌘⇧Z".parse::<Shortcut>().unwrap(), shortcut);
+        assert_eq!("⌘+⇧+Z".parse::<Shortcut>().unwrap(), shortcut);
+        assert_eq!(shortcut.to_string(), "CmdOrCtrl+Shift+Z");
+
+        assert_eq!(
+            "Shift+F5".parse::<Shortcut>().unwrap(),
+            Shortcut {
+                shift: true,
+                ..Shortcut::new(Key::F5)
+            }
+        );
+        assert_eq!(
+            "Ctrl+Alt+Delete".parse::<Shortcut>().unwrap(),
+            Shortcut {
+                ctrl: true,
+                alt: true,
+                ..Shortcut::new(Key::Delete)
+            }
+        );
+        assert_eq!("1".parse::<Shortcut>().unwrap(), Shortcut::new(Key::Num1));
+        for shortcut in ["Ctrl+Alt+Delete", "Shift+F12", "CmdOrCtrl+1", "PageUp"] {
+            assert_eq!(shortcut.parse::<Shortcut>().unwrap().to_string(), shortcut);
+        }
+
+        for invalid in [
+            "",
+            "Ctrl+",
+            "Ctrl",
+            "Hyper+A",
+            "Shift+Shift+A",
+            "F13",
+            "F0",
+            "AB",
+            "⌘",
+        ] {
+            assert!(invalid.parse::<Shortcut>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_windows_mapping() {
+        let mut shortcuts = Shortcuts::new();
+        shortcuts.set_mac_keyboard(false);
+        shortcuts.register("CmdOrCtrl+S", "save").unwrap();
+        // The command modifier and the Control key are the same on Windows
+        assert!(shortcuts.register("Ctrl+S", "other").is_err());
+
+        let mut events = vec![
+            key_press(Key::S, WINDOWS_CTRL),
+            key_press(Key::S, Modifiers::default()),
+        ];
+        assert_eq!(shortcuts.handle_events(&mut events), vec!["save"]);
+        assert!(matches!(events[0], Event::KeyPress { handled: true, .. }));
+        assert!(matches!(events[1], Event::KeyPress { handled: false, .. }));
+    }
+
+    #[test]
+    fn test_mac_mapping() {
+        let mut shortcuts = Shortcuts::new();
+        shortcuts.set_mac_keyboard(true);
+        shortcuts.register("CmdOrCtrl+S", "save").unwrap();
+        // The ⌘ Command key and the Control key are different keys on Mac
+        shortcuts.register("Ctrl+S", "other").unwrap();
+
+        let mut events = vec![
+            key_press(Key::S, MAC_COMMAND),
+            key_release(Key::S),
+            key_press(Key::S, MAC_CTRL),
+            key_release(Key::S),
+            // ⌘ and Control together is neither of the shortcuts
+            key_press(
+                Key::S,
+                Modifiers {
+                    ctrl: true,
+                    ..MAC_COMMAND
+                },
+            ),
+        ];
+        assert_eq!(shortcuts.handle_events(&mut events), vec!["save", "other"]);
+        assert!(matches!(events[4], Event::KeyPress { handled: false, .. }));
+    }
+
+    #[test]
+    fn test_repeat_and_handled() {
+        let mut shortcuts = Shortcuts::new();
+        shortcuts.set_mac_keyboard(false);
+        shortcuts.register("CmdOrCtrl+Z", "undo").unwrap();
+        shortcuts
+            .register_shortcut(
+                Shortcut {
+                    repeat: true,
+                    ..Shortcut::new(Key::ArrowUp)
+                },
+                "up",
+            )
+            .unwrap();
+
+        // Holding down the keys repeats the key presses until the keys are released
+        let mut events = vec![
+            key_press(Key::Z, WINDOWS_CTRL),
+            key_press(Key::ArrowUp, Modifiers::default()),
+            key_press(Key::Z, WINDOWS_CTRL),
+            key_press(Key::ArrowUp, Modifiers::default()),
+            key_release(Key::Z),
+            key_press(Key::Z, WINDOWS_CTRL),
+        ];
+        assert_eq!(
+            shortcuts.handle_events(&mut events),
+            vec!["undo", "up", "up", "undo"]
+        );
+        // The repeated key press is handled even though it did not trigger the action
+        assert!(matches!(events[2], Event::KeyPress { handled: true, .. }));
+
+        // Key presses handled by something else are ignored
+        let mut events = vec![Event::KeyPress {
+            kind: Key::ArrowUp,
+            modifiers: Modifiers::default(),
+            handled: true,
+        }];
+        assert!(shortcuts.handle_events(&mut events).is_empty());
+    }
+}
@@ -0,0 +1,72 @@
+use crate::renderer::*;
+
+///
+/// Renders a circle at the position of each finger touching the screen and a small circle at an optional anchor,
+/// for example to show what the touch emulation of the `FrameInputGenerator` in the window module generates,
+/// using the positions returned by `FrameInputGenerator::emulated_touches` and `FrameInputGenerator::emulated_touch_anchor`.
+///
+/// The circles are only created the first time they are needed and afterwards moved to the given positions each time the overlay is rendered.
+///
+pub struct TouchOverlay {
+    fingers: Vec<Circle>,
+    anchor: Circle,
+    material: ColorMaterial,
+}
+
+impl TouchOverlay {
+    ///
+    /// Creates a new touch overlay.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            fingers: Vec::new(),
+            anchor: Circle::new(context, PhysicalPoint { x: 0.0, y: 0.0 }, 4.0),
+            material: ColorMaterial {
+                color: Color::new(255, 255, 255, 160),
+                render_states: RenderStates {
+                    blend: Blend::TRANSPARENCY,
+                    depth_test: DepthTest::Always,
+                    write_mask: WriteMask::COLOR,
+                    ..Default::default()
+                },
+                is_transparent: true,
+                ..Default::default()
+            },
+        }
+    }
+
+    ///
+    /// Renders a circle at each of the given touch positions and a small circle at the anchor, if any, on top of the given render target.
+    /// Call this after rendering the frame, for example with the screen render target.
+    ///
+    pub fn render(
+        &mut self,
+        target: &RenderTarget,
+        touches: &[LogicalPoint],
+        anchor: Option<LogicalPoint>,
+    ) {
+        let context = &target.context;
+        while self.fingers.len() < touches.len() {
+            self.fingers
+                .push(Circle::new(context, PhysicalPoint { x: 0.0, y: 0.0 }, 12.0));
+        }
+        for (circle, touch) in self.fingers.iter_mut().zip(touches) {
+            circle.set_center(touch.to_physical());
+            circle.set_radius(12.0 * touch.device_pixel_ratio());
+        }
+        if let Some(anchor) = anchor {
+            self.anchor.set_center(anchor.to_physical());
+            self.anchor.set_radius(4.0 * anchor.device_pixel_ratio());
+        }
+
+        let camera = camera2d(target.viewport());
+        let circles = self
+            .fingers
+            .iter()
+            .take(touches.len())
+            .chain(anchor.map(|_| &self.anchor));
+        for circle in circles {
+            target.render_with_material(&self.material, &camera, circle, &[]);
+        }
+    }
+}
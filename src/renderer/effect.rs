@@ -9,3 +9,85 @@ pub use fog::*;
 mod fxaa;
 #[doc(inline)]
 pub use fxaa::*;
+
+mod auto_exposure;
+#[doc(inline)]
+pub use auto_exposure::*;
+
+mod chromatic_aberration;
+#[doc(inline)]
+pub use chromatic_aberration::*;
+
+mod color_grading;
+#[doc(inline)]
+pub use color_grading::*;
+
+mod depth_of_field;
+#[doc(inline)]
+pub use depth_of_field::*;
+
+mod gaussian_blur;
+#[doc(inline)]
+pub use gaussian_blur::*;
+
+mod motion_blur;
+#[doc(inline)]
+pub use motion_blur::*;
+
+mod screen_space_reflections;
+#[doc(inline)]
+pub use screen_space_reflections::*;
+
+mod vignette;
+#[doc(inline)]
+pub use vignette::*;
+
+mod yuv_to_rgb;
+#[doc(inline)]
+pub use yuv_to_rgb::*;
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use crate::renderer::*;
+
+    ///
+    /// Applies an effect, given as a closure which renders the effect with the given color texture as input, to an 8x8 image where all pixels are different.
+    /// Returns the pixels of the image and the pixels of the result.
+    ///
+    pub(super) fn apply_to_test_image(
+        context: &Context,
+        apply: impl FnOnce(ColorTexture),
+    ) -> (Vec<[u8; 4]>, Vec<[u8; 4]>) {
+        let pixels = (0..64u8)
+            .map(|i| [i * 4, 255 - i * 4, i.wrapping_mul(37), 255])
+            .collect::<Vec<_>>();
+        let source = Texture2D::new(
+            context,
+            &CpuTexture {
+                data: TextureData::RgbaU8(pixels.clone()),
+                width: 8,
+                height: 8,
+                min_filter: Interpolation::Nearest,
+                mag_filter: Interpolation::Nearest,
+                mip_map_filter: None,
+                ..Default::default()
+            },
+        );
+        let mut target = Texture2D::new_empty::<[u8; 4]>(
+            context,
+            8,
+            8,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let result = target
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+            .write(|| apply(ColorTexture::Single(&source)))
+            .read::<[u8; 4]>();
+        (pixels, result)
+    }
+}
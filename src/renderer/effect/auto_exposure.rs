@@ -0,0 +1,323 @@
+use crate::renderer::*;
+
+/// The size of the texture which the luminance is reduced in, the last mip level is 1x1.
+const LUMINANCE_SIZE: u32 = 128;
+
+///
+/// Defines how the luminance of the pixels is weighted when measuring the brightness of an image, see [AutoExposure].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MeteringMode {
+    /// All pixels are weighted equally.
+    Average,
+    /// The pixels are weighted by `exp(-falloff * d * d)`, where `d` is the distance to the center of the image relative to the distance to the middle of the edges,
+    /// so a higher falloff measures a smaller area around the center.
+    CenterWeighted {
+        /// How fast the weight falls off away from the center.
+        falloff: f32,
+    },
+}
+
+impl MeteringMode {
+    fn falloff(&self) -> f32 {
+        match self {
+            Self::Average => 0.0,
+            Self::CenterWeighted { falloff } => falloff.max(0.0),
+        }
+    }
+}
+
+///
+/// Adjusts the exposure of a rendered image to the brightness of the scene, like a camera or the eye adapting when moving from a dark room to a bright exterior.
+///
+/// Each frame, [AutoExposure::update] measures the average log luminance of the image by reducing it to a single pixel on the GPU
+/// and moves the exposure towards the exposure which maps the average luminance to [AutoExposure::target_luminance] with a limited speed.
+/// The exposure is kept in a 1x1 texture on the GPU (see [AutoExposure::exposure_texture]), so nothing is read back and the rendering is not stalled.
+/// Then [AutoExposure::apply] applies the exposure to the image.
+///
+/// The image is expected to be rendered with the built-in materials, which apply Reinhard tone mapping and sRGB encoding,
+/// so the luminance of the scene is recovered by inverting the tone mapping before the exposure is applied and the image is tone mapped again.
+/// Render the scene to a [f16] color texture to avoid losing precision in the bright parts of the image.
+/// The exposure is given in EV, ie. the luminance of the scene is multiplied by `2^exposure`.
+///
+pub struct AutoExposure {
+    /// The lowest exposure in EV, which limits how much a bright scene is darkened.
+    pub min_exposure: f32,
+    /// The highest exposure in EV, which limits how much a dark scene is brightened.
+    pub max_exposure: f32,
+    /// How the luminance of the pixels is weighted when measuring the brightness of the image.
+    pub metering: MeteringMode,
+    /// The luminance which the average luminance of the scene is mapped to, the default is the middle gray `0.18`.
+    pub target_luminance: f32,
+    /// The maximum increase of the exposure in EV per second, ie. how fast the exposure adapts to a darker scene.
+    pub speed_up: f32,
+    /// The maximum decrease of the exposure in EV per second, ie. how fast the exposure adapts to a brighter scene.
+    pub speed_down: f32,
+    luminance: Texture2D,
+    exposure: [Texture2D; 2],
+    current: usize,
+    reset: bool,
+}
+
+impl AutoExposure {
+    ///
+    /// Creates a new auto exposure with an exposure between -8 and 8 EV, average metering
+    /// and an adaptation which is faster towards a brighter scene (3 EV per second) than towards a darker scene (1 EV per second).
+    ///
+    pub fn new(context: &Context) -> Self {
+        let exposure = || {
+            Texture2D::new_empty::<f32>(
+                context,
+                1,
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )
+        };
+        Self {
+            min_exposure: -8.0,
+            max_exposure: 8.0,
+            metering: MeteringMode::Average,
+            target_luminance: 0.18,
+            speed_up: 1.0,
+            speed_down: 3.0,
+            luminance: Texture2D::new_empty::<[f16; 2]>(
+                context,
+                LUMINANCE_SIZE,
+                LUMINANCE_SIZE,
+                Interpolation::Linear,
+                Interpolation::Linear,
+                Some(Interpolation::Nearest),
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ),
+            exposure: [exposure(), exposure()],
+            current: 0,
+            reset: true,
+        }
+    }
+
+    ///
+    /// Makes the next [AutoExposure::update] jump directly to the exposure of the image instead of adapting, for example after a camera cut.
+    /// This is also the case for the first update.
+    ///
+    pub fn reset(&mut self) {
+        self.reset = true;
+    }
+
+    ///
+    /// Measures the luminance of the given color texture and adapts the exposure to it, given the elapsed time in milliseconds since the last update,
+    /// for example `frame_input.elapsed_time`.
+    /// Everything happens on the GPU, so this does not wait for the rendering to finish.
+    ///
+    pub fn update(&mut self, context: &Context, color_texture: ColorTexture, elapsed_time: f64) {
+        let falloff = self.metering.falloff();
+        self.luminance
+            .as_color_target(None)
+            .clear(ClearState::default())
+            .write(|| {
+                apply_effect(
+                    context,
+                    &format!(
+                        "{}{}\n{}",
+                        include_str!("../../core/shared.frag"),
+                        color_texture.fragment_shader_source(),
+                        include_str!("shaders/auto_exposure_luminance.frag")
+                    ),
+                    RenderStates {
+                        write_mask: WriteMask::COLOR,
+                        depth_test: DepthTest::Always,
+                        cull: Cull::Back,
+                        ..Default::default()
+                    },
+                    Viewport::new_at_origo(LUMINANCE_SIZE, LUMINANCE_SIZE),
+                    |program| {
+                        color_texture.use_uniforms(program);
+                        program.use_uniform("falloff", falloff);
+                    },
+                )
+            });
+
+        let [first, second] = &mut self.exposure;
+        let (previous, next) = if self.current == 0 {
+            (&*first, second)
+        } else {
+            (&*second, first)
+        };
+        let luminance = &self.luminance;
+        let reset = self.reset;
+        let (min_exposure, max_exposure) =
+            (self.min_exposure, self.max_exposure.max(self.min_exposure));
+        let (target_luminance, speed_up, speed_down) =
+            (self.target_luminance, self.speed_up, self.speed_down);
+        next.as_color_target(None).write(|| {
+            apply_effect(
+                context,
+                include_str!("shaders/auto_exposure_adaptation.frag"),
+                RenderStates {
+                    write_mask: WriteMask::COLOR,
+                    depth_test: DepthTest::Always,
+                    cull: Cull::Back,
+                    ..Default::default()
+                },
+                Viewport::new_at_origo(1, 1),
+                |program| {
+                    program.use_texture("luminanceTexture", luminance);
+                    program.use_uniform("luminanceLod", (LUMINANCE_SIZE as f32).log2());
+                    program.use_texture("previousExposure", previous);
+                    program.use_uniform("targetLuminance", target_luminance);
+                    program.use_uniform("minExposure", min_exposure);
+                    program.use_uniform("maxExposure", max_exposure);
+                    program.use_uniform("speedUp", speed_up.max(0.0));
+                    program.use_uniform("speedDown", speed_down.max(0.0));
+                    program.use_uniform("elapsedTime", 0.001 * elapsed_time as f32);
+                    program.use_uniform("reset", if reset { 1.0f32 } else { 0.0 });
+                },
+            )
+        });
+        self.current = 1 - self.current;
+        self.reset = false;
+    }
+
+    ///
+    /// Returns the 1x1 texture containing the current exposure in EV in the red channel, which is updated by [AutoExposure::update].
+    /// Use this to apply the exposure in a custom shader without reading it back from the GPU, for example `exp2(texelFetch(exposureTexture, ivec2(0, 0), 0).r)`.
+    ///
+    pub fn exposure_texture(&self) -> &Texture2D {
+        &self.exposure[self.current]
+    }
+
+    ///
+    /// Reads the current exposure in EV back from the GPU.
+    /// Note that this waits for the GPU to finish all rendering, so it should only be used for debugging or tests.
+    ///
+    pub fn read_exposure(&mut self) -> f32 {
+        self.exposure[self.current]
+            .as_color_target(None)
+            .read::<f32>()[0]
+    }
+
+    ///
+    /// Applies the current exposure to the given color texture and writes the result to the current render target.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn apply(&self, context: &Context, color_texture: ColorTexture) {
+        apply_effect(
+            context,
+            &format!(
+                "{}{}\n{}",
+                include_str!("../../core/shared.frag"),
+                color_texture.fragment_shader_source(),
+                include_str!("shaders/auto_exposure_effect.frag")
+            ),
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            Viewport::new_at_origo(color_texture.width(), color_texture.height()),
+            |program| {
+                color_texture.use_uniforms(program);
+                program.use_texture("exposureTexture", self.exposure_texture());
+            },
+        )
+    }
+
+    ///
+    /// Returns a [FramePass] which updates the exposure from the color texture with the given input name (see [AutoExposure::update]),
+    /// applies it and writes the result to a color texture with the given output name, format and the size of the viewport.
+    ///
+    pub fn frame_pass<'a>(
+        &'a mut self,
+        context: &'a Context,
+        elapsed_time: f64,
+        input: &str,
+        output: &str,
+        format: FrameTextureFormat,
+    ) -> FramePass<'a> {
+        let input_name = input.to_string();
+        let output_name = output.to_string();
+        FramePass::new("auto_exposure", move |resources| {
+            let color_texture = resources.color_texture(&input_name);
+            self.update(context, ColorTexture::Single(color_texture), elapsed_time);
+            resources
+                .color_target(&output_name)
+                .clear(ClearState::default())
+                .write(|| self.apply(context, ColorTexture::Single(color_texture)));
+        })
+        .read(input)
+        .write(output, format, FrameTextureSize::viewport())
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    fn image(context: &Context, value: u8) -> Texture2D {
+        let mut texture = Texture2D::new_empty::<[u8; 4]>(
+            context,
+            16,
+            16,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        texture.fill(&vec![[value, value, value, 255]; 256]);
+        texture
+    }
+
+    /// The exposure which maps the gray sRGB value to middle gray.
+    fn expected_exposure(value: u8) -> f32 {
+        let linear = (value as f32 / 255.0 + 0.055) / 1.055;
+        let linear = linear.powf(2.4);
+        let luminance = linear / (1.0 - linear);
+        (0.18 / luminance).log2()
+    }
+
+    #[test]
+    fn test_auto_exposure_adaptation() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let dark = image(&context, 30);
+        let bright = image(&context, 250);
+        let mut auto_exposure = AutoExposure::new(&context);
+
+        // The first update jumps directly to the exposure of the image
+        auto_exposure.update(&context, ColorTexture::Single(&dark), 16.0);
+        let exposure = auto_exposure.read_exposure();
+        assert!((exposure - expected_exposure(30)).abs() < 0.05);
+
+        // Afterwards, the exposure adapts with the given speed
+        auto_exposure.update(&context, ColorTexture::Single(&bright), 100.0);
+        assert!((auto_exposure.read_exposure() - (exposure - 0.3)).abs() < 0.01);
+        auto_exposure.update(&context, ColorTexture::Single(&dark), 100.0);
+        assert!((auto_exposure.read_exposure() - (exposure - 0.2)).abs() < 0.01);
+
+        auto_exposure.max_exposure = 1.0;
+        auto_exposure.reset();
+        auto_exposure.update(&context, ColorTexture::Single(&dark), 16.0);
+        assert!((auto_exposure.read_exposure() - 1.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_auto_exposure_apply() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let dark = image(&context, 30);
+        let mut auto_exposure = AutoExposure::new(&context);
+        auto_exposure.update(&context, ColorTexture::Single(&dark), 16.0);
+
+        let mut output = image(&context, 0);
+        let pixels = output
+            .as_color_target(None)
+            .write(|| auto_exposure.apply(&context, ColorTexture::Single(&dark)))
+            .read::<[u8; 4]>();
+        // Middle gray is mapped to about 0.15 by the tone mapping, which is 109 after sRGB encoding
+        assert!(pixels.iter().all(|p| (p[0] as i32 - 109).abs() <= 2));
+    }
+}
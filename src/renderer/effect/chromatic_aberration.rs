@@ -0,0 +1,94 @@
+use crate::renderer::*;
+
+///
+/// An effect that simulates the chromatic aberration of a real camera lens, ie. that the lens does not focus all colors at the same point
+/// which gives colored fringes along edges, especially towards the edges of the image.
+/// The red and blue channels are sampled at positions which are offset radially from the center of the image in opposite directions.
+/// The effect is typically applied as the last pass, after tone mapping.
+///
+#[derive(Clone, Debug)]
+pub struct ChromaticAberrationEffect {
+    /// The offset of the red and blue channels relative to the distance from the center of the image.
+    /// A value of `0.0` leaves the image unchanged and a value of `0.01` offsets the channels by half a percent of the image size at the edges.
+    pub strength: f32,
+}
+
+impl Default for ChromaticAberrationEffect {
+    fn default() -> Self {
+        Self { strength: 0.005 }
+    }
+}
+
+impl ChromaticAberrationEffect {
+    ///
+    /// Creates a new chromatic aberration effect with the given strength, see [ChromaticAberrationEffect::strength].
+    ///
+    pub fn new(strength: f32) -> Self {
+        Self { strength }
+    }
+
+    ///
+    /// Applies the chromatic aberration effect to the given color texture and writes the result to the current render target.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn apply(&self, context: &Context, color_texture: ColorTexture) {
+        apply_effect(
+            context,
+            &format!(
+                "{}\n{}",
+                color_texture.fragment_shader_source(),
+                include_str!("shaders/chromatic_aberration_effect.frag")
+            ),
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            Viewport::new_at_origo(color_texture.width(), color_texture.height()),
+            |program| {
+                color_texture.use_uniforms(program);
+                program.use_uniform("strength", self.strength);
+            },
+        )
+    }
+
+    ///
+    /// Returns a [FramePass] which applies the chromatic aberration effect to the color texture with the given input name
+    /// and writes the result to a color texture with the given output name, format and the size of the viewport.
+    ///
+    pub fn frame_pass<'a>(
+        &'a self,
+        context: &'a Context,
+        input: &str,
+        output: &str,
+        format: FrameTextureFormat,
+    ) -> FramePass<'a> {
+        let input_name = input.to_string();
+        let output_name = output.to_string();
+        FramePass::new("chromatic_aberration", move |resources| {
+            let color_texture = resources.color_texture(&input_name);
+            resources
+                .color_target(&output_name)
+                .clear(ClearState::default())
+                .write(|| self.apply(context, ColorTexture::Single(color_texture)));
+        })
+        .read(input)
+        .write(output, format, FrameTextureSize::viewport())
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+    use crate::renderer::effect::test::apply_to_test_image;
+
+    #[test]
+    fn test_zero_strength_is_unchanged() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let (pixels, result) = apply_to_test_image(&context, |color_texture| {
+            ChromaticAberrationEffect::new(0.0).apply(&context, color_texture)
+        });
+        assert_eq!(result, pixels);
+    }
+}
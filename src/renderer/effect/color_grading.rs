@@ -0,0 +1,209 @@
+use crate::renderer::*;
+use std::sync::Arc;
+
+///
+/// A look-up table (LUT) which maps each color to a new color, see [ColorGradingEffect].
+///
+#[derive(Clone)]
+pub enum ColorLookupTable {
+    /// A 3D texture with the same size in all three dimensions, where the red, green and blue values of the input color are the coordinates in the texture.
+    Texture3D(Arc<Texture3D>),
+    /// A 2D texture which contains the slices of a 3D look-up table side by side in a horizontal strip,
+    /// ie. a texture with a width of `size * size` and a height of `size`.
+    /// The slices are ordered by the blue value of the input color and within each slice, the red value increases along the width and the green value along the height.
+    Strip {
+        /// The strip texture.
+        texture: Arc<Texture2D>,
+        /// The number of entries along each of the three dimensions.
+        size: u32,
+    },
+}
+
+impl ColorLookupTable {
+    ///
+    /// Returns the number of entries along each of the three dimensions of the look-up table.
+    ///
+    pub fn size(&self) -> u32 {
+        match self {
+            Self::Texture3D(texture) => texture.width(),
+            Self::Strip { size, .. } => *size,
+        }
+    }
+}
+
+///
+/// An effect that changes the colors of an image using a 3D look-up table (LUT), which is the common way to apply color grading
+/// created in an image editing or color grading tool.
+/// Each color is clamped to the range `[0, 1]` and then replaced by the value in the look-up table, interpolated between the nearest entries.
+/// The effect is typically applied after tone mapping.
+///
+#[derive(Clone)]
+pub struct ColorGradingEffect {
+    /// The look-up table which maps the colors.
+    pub lut: ColorLookupTable,
+}
+
+impl ColorGradingEffect {
+    ///
+    /// Creates a new color grading effect using the given 3D texture as the look-up table.
+    /// The texture should use [Interpolation::Linear] and [Wrapping::ClampToEdge].
+    ///
+    /// # Panic
+    /// Will panic if the width, height and depth of the texture are not the same.
+    ///
+    pub fn from_lut(lut: impl Into<Arc<Texture3D>>) -> Self {
+        let lut = lut.into();
+        if lut.width() != lut.height() || lut.width() != lut.depth() {
+            panic!(
+                "a color look-up table must have the same size in all dimensions, got {}x{}x{}",
+                lut.width(),
+                lut.height(),
+                lut.depth()
+            );
+        }
+        Self {
+            lut: ColorLookupTable::Texture3D(lut),
+        }
+    }
+
+    ///
+    /// Creates a new color grading effect using the given 2D texture containing a horizontal strip of slices as the look-up table, see [ColorLookupTable::Strip].
+    /// This is the format that look-up tables are often stored in as images.
+    /// The texture should use [Interpolation::Linear] and [Wrapping::ClampToEdge].
+    ///
+    /// # Panic
+    /// Will panic if the width of the texture is not `lut_size * lut_size` or the height is not `lut_size`.
+    ///
+    pub fn from_lut_strip(strip: impl Into<Arc<Texture2D>>, lut_size: u32) -> Self {
+        let texture = strip.into();
+        if texture.width() != lut_size * lut_size || texture.height() != lut_size {
+            panic!(
+                "a color look-up table strip with size {} must be {}x{}, got {}x{}",
+                lut_size,
+                lut_size * lut_size,
+                lut_size,
+                texture.width(),
+                texture.height()
+            );
+        }
+        Self {
+            lut: ColorLookupTable::Strip {
+                texture,
+                size: lut_size,
+            },
+        }
+    }
+
+    ///
+    /// Creates a new color grading effect with a look-up table of the given size which maps each color to itself.
+    /// This is useful for verifying the setup before any grading is applied or as a starting point for modifying the look-up table.
+    ///
+    /// # Panic
+    /// Will panic if the size is smaller than 2.
+    ///
+    pub fn identity(context: &Context, size: u32) -> Self {
+        if size < 2 {
+            panic!("a color look-up table must have a size of at least 2");
+        }
+        let mut data = Vec::with_capacity((size * size * size) as usize);
+        let value = |i: u32| f16::from_f32(i as f32 / (size - 1) as f32);
+        for b in 0..size {
+            for g in 0..size {
+                for r in 0..size {
+                    data.push([value(r), value(g), value(b), f16::from_f32(1.0)]);
+                }
+            }
+        }
+        let mut texture = Texture3D::new_empty::<[f16; 4]>(
+            context,
+            size,
+            size,
+            size,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        texture.fill(&data);
+        Self::from_lut(texture)
+    }
+
+    ///
+    /// Applies the color grading effect to the given color texture and writes the result to the current render target.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn apply(&self, context: &Context, color_texture: ColorTexture) {
+        apply_effect(
+            context,
+            &format!(
+                "{}{}\n{}",
+                if let ColorLookupTable::Strip { .. } = self.lut {
+                    "#define USE_LUT_STRIP\n"
+                } else {
+                    ""
+                },
+                color_texture.fragment_shader_source(),
+                include_str!("shaders/color_grading_effect.frag")
+            ),
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            Viewport::new_at_origo(color_texture.width(), color_texture.height()),
+            |program| {
+                color_texture.use_uniforms(program);
+                match &self.lut {
+                    ColorLookupTable::Texture3D(texture) => program.use_texture_3d("lut", texture),
+                    ColorLookupTable::Strip { texture, .. } => program.use_texture("lut", texture),
+                }
+                program.use_uniform("lutSize", self.lut.size() as f32);
+            },
+        )
+    }
+
+    ///
+    /// Returns a [FramePass] which applies the color grading effect to the color texture with the given input name
+    /// and writes the result to a color texture with the given output name, format and the size of the viewport.
+    ///
+    pub fn frame_pass<'a>(
+        &'a self,
+        context: &'a Context,
+        input: &str,
+        output: &str,
+        format: FrameTextureFormat,
+    ) -> FramePass<'a> {
+        let input_name = input.to_string();
+        let output_name = output.to_string();
+        FramePass::new("color_grading", move |resources| {
+            let color_texture = resources.color_texture(&input_name);
+            resources
+                .color_target(&output_name)
+                .clear(ClearState::default())
+                .write(|| self.apply(context, ColorTexture::Single(color_texture)));
+        })
+        .read(input)
+        .write(output, format, FrameTextureSize::viewport())
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+    use crate::renderer::effect::test::apply_to_test_image;
+
+    #[test]
+    fn test_identity_is_unchanged() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let effect = ColorGradingEffect::identity(&context, 16);
+        let (pixels, result) = apply_to_test_image(&context, |color_texture| {
+            effect.apply(&context, color_texture)
+        });
+        for (a, b) in result.iter().zip(pixels.iter()) {
+            assert!(a.iter().zip(b.iter()).all(|(x, y)| x.abs_diff(*y) <= 1));
+        }
+    }
+}
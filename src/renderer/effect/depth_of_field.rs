@@ -0,0 +1,121 @@
+use crate::renderer::*;
+
+///
+/// An effect that simulates the depth of field of a real camera lens, ie. objects close to the focal distance are sharp
+/// while objects closer to or further away from the camera are blurred.
+/// The blur is computed from a circle of confusion for each pixel and uses a hexagonal kernel which gives a bokeh look to bright spots.
+///
+#[derive(Clone, Debug)]
+pub struct DepthOfFieldEffect {
+    /// The distance from the camera to the plane in focus.
+    pub focal_distance: f32,
+    /// The distance from the focal plane within which everything is sharp.
+    /// Outside of this range, the blur increases until it reaches the maximum blur radius at twice this distance from the focal plane.
+    pub focal_range: f32,
+    /// The maximum radius of the blur in pixels.
+    pub max_blur_radius: f32,
+}
+
+impl Default for DepthOfFieldEffect {
+    fn default() -> Self {
+        Self {
+            focal_distance: 10.0,
+            focal_range: 2.0,
+            max_blur_radius: 8.0,
+        }
+    }
+}
+
+impl DepthOfFieldEffect {
+    ///
+    /// Creates a new depth of field effect with the given focal distance, focal range and maximum blur radius in pixels.
+    ///
+    pub fn new(focal_distance: f32, focal_range: f32, max_blur_radius: f32) -> Self {
+        Self {
+            focal_distance,
+            focal_range,
+            max_blur_radius,
+        }
+    }
+
+    ///
+    /// Applies the depth of field effect to the given color texture based on the given depth texture and writes the result to the current render target.
+    /// The color texture is typically the HDR color of the scene before tone mapping.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn apply(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        color_texture: ColorTexture,
+        depth_texture: DepthTexture,
+    ) {
+        apply_effect(
+            context,
+            &format!(
+                "{}\n{}\n{}\n{}",
+                include_str!("../../core/shared.frag"),
+                color_texture.fragment_shader_source(),
+                depth_texture.fragment_shader_source(),
+                include_str!("shaders/depth_of_field_effect.frag")
+            ),
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            Viewport::new_at_origo(color_texture.width(), color_texture.height()),
+            |program| {
+                color_texture.use_uniforms(program);
+                depth_texture.use_uniforms(program);
+                let (w, h) = color_texture.resolution();
+                program.use_uniform("resolution", vec2(w as f32, h as f32));
+                program.use_uniform(
+                    "viewProjectionInverse",
+                    (camera.projection() * camera.view()).invert().unwrap(),
+                );
+                program.use_uniform("eyePosition", camera.position());
+                program.use_uniform("focalDistance", self.focal_distance);
+                program.use_uniform("focalRange", self.focal_range);
+                program.use_uniform("maxBlurRadius", self.max_blur_radius);
+            },
+        )
+    }
+
+    ///
+    /// Returns a [FramePass] which applies the depth of field effect to the color texture with the given name based on the depth texture with the given name
+    /// and writes the result to a color texture with the given output name, format and the size of the viewport.
+    ///
+    pub fn frame_pass<'a>(
+        &'a self,
+        context: &'a Context,
+        camera: &'a Camera,
+        color: &str,
+        depth: &str,
+        output: &str,
+        format: FrameTextureFormat,
+    ) -> FramePass<'a> {
+        let color_name = color.to_string();
+        let depth_name = depth.to_string();
+        let output_name = output.to_string();
+        FramePass::new("depth_of_field", move |resources| {
+            let color_texture = resources.color_texture(&color_name);
+            let depth_texture = resources.depth_texture(&depth_name);
+            resources
+                .color_target(&output_name)
+                .clear(ClearState::default())
+                .write(|| {
+                    self.apply(
+                        context,
+                        camera,
+                        ColorTexture::Single(color_texture),
+                        DepthTexture::Single(depth_texture),
+                    )
+                });
+        })
+        .read(color)
+        .read(depth)
+        .write(output, format, FrameTextureSize::viewport())
+    }
+}
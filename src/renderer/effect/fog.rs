@@ -64,4 +64,27 @@ impl FogEffect {
             },
         )
     }
+
+    ///
+    /// Returns a [FramePass] which applies the fog effect on top of the color texture with the given name based on the depth texture with the given name.
+    ///
+    pub fn frame_pass<'a>(
+        &'a self,
+        context: &'a Context,
+        time: f64,
+        camera: &'a Camera,
+        depth: &str,
+        color: &str,
+    ) -> FramePass<'a> {
+        let depth_name = depth.to_string();
+        let color_name = color.to_string();
+        FramePass::new("fog", move |resources| {
+            let depth_texture = resources.depth_texture(&depth_name);
+            resources
+                .color_target(&color_name)
+                .write(|| self.apply(context, time, camera, DepthTexture::Single(depth_texture)));
+        })
+        .read(depth)
+        .modify(color)
+    }
 }
@@ -34,4 +34,28 @@ impl FxaaEffect {
             },
         )
     }
+
+    ///
+    /// Returns a [FramePass] which applies the FXAA effect to the color texture with the given input name
+    /// and writes the result to a color texture with the given output name, format and the size of the viewport.
+    ///
+    pub fn frame_pass<'a>(
+        &'a self,
+        context: &'a Context,
+        input: &str,
+        output: &str,
+        format: FrameTextureFormat,
+    ) -> FramePass<'a> {
+        let input_name = input.to_string();
+        let output_name = output.to_string();
+        FramePass::new("fxaa", move |resources| {
+            let color_texture = resources.color_texture(&input_name);
+            resources
+                .color_target(&output_name)
+                .clear(ClearState::default())
+                .write(|| self.apply(context, ColorTexture::Single(color_texture)));
+        })
+        .read(input)
+        .write(output, format, FrameTextureSize::viewport())
+    }
 }
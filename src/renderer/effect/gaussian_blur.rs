@@ -0,0 +1,321 @@
+use crate::renderer::*;
+use std::sync::RwLock;
+
+/// The maximum radius of the kernel in texels after downsampling.
+const MAX_RADIUS: u32 = 64;
+
+///
+/// A separable Gaussian blur which blurs a texture horizontally into an intermediate texture with the same format as the source and then vertically into a render target.
+/// The weights of the kernel are computed on the CPU and each tap, except the center, samples between two texels using linear interpolation
+/// which halves the number of texture lookups, so the source texture must use [Interpolation::Linear].
+///
+/// For large radii, set [GaussianBlur::downsample] to blur a texture which is smaller by that factor and then upsample the result,
+/// which is a lot faster and usually not noticeable since the result is blurry anyway.
+///
+/// The intermediate texture is kept and reused by the next blur of a source with the same size and format,
+/// so keep the blur between frames instead of creating a new one each frame.
+///
+pub struct GaussianBlur {
+    /// The standard deviation of the Gaussian in texels.
+    pub sigma: f32,
+    /// The radius of the kernel in texels, ie. the number of texels on each side of the center texel that contributes to the result.
+    pub radius: u32,
+    /// The factor the size of the intermediate texture is reduced by. A value of `1` means no downsampling.
+    pub downsample: u32,
+    intermediate: RwLock<Option<Texture2D>>,
+}
+
+impl Clone for GaussianBlur {
+    fn clone(&self) -> Self {
+        Self {
+            sigma: self.sigma,
+            radius: self.radius,
+            downsample: self.downsample,
+            intermediate: RwLock::new(None),
+        }
+    }
+}
+
+impl std::fmt::Debug for GaussianBlur {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("GaussianBlur")
+            .field("sigma", &self.sigma)
+            .field("radius", &self.radius)
+            .field("downsample", &self.downsample)
+            .finish()
+    }
+}
+
+impl GaussianBlur {
+    ///
+    /// Creates a new Gaussian blur with the given standard deviation in texels and a radius of three times the standard deviation.
+    ///
+    pub fn new(sigma: f32) -> Self {
+        Self::new_with_radius(sigma, (3.0 * sigma).ceil().max(0.0) as u32)
+    }
+
+    ///
+    /// Creates a new Gaussian blur with the given standard deviation and radius in texels.
+    ///
+    pub fn new_with_radius(sigma: f32, radius: u32) -> Self {
+        Self {
+            sigma,
+            radius,
+            downsample: 1,
+            intermediate: RwLock::new(None),
+        }
+    }
+
+    ///
+    /// Blurs the source texture and writes the result to the target, which should have the same size as the source.
+    /// An intermediate texture with the same format as the source is used between the horizontal and vertical pass, which is reused by the next call if possible.
+    ///
+    /// # Panic
+    /// Will panic if the radius divided by the downsample factor is larger than 64 or if the source texture has an integer format.
+    ///
+    pub fn apply(&self, context: &Context, source: &Texture2D, target: &RenderTarget) {
+        self.apply_partially(context, source, target, target.scissor_box())
+    }
+
+    ///
+    /// Blurs the part of the source texture inside the scissor box and writes the result to the same part of the target, the rest of the target is left untouched.
+    /// The target should have the same size as the source.
+    ///
+    /// # Panic
+    /// Will panic if the radius divided by the downsample factor is larger than 64 or if the source texture has an integer format.
+    ///
+    pub fn apply_partially(
+        &self,
+        context: &Context,
+        source: &Texture2D,
+        target: &RenderTarget,
+        scissor_box: ScissorBox,
+    ) {
+        let downsample = self.downsample.max(1);
+        let width = (source.width() + downsample - 1) / downsample;
+        let height = (source.height() + downsample - 1) / downsample;
+        let mut cache = self.intermediate.write().unwrap();
+        let reuse = cache.as_ref().map_or(false, |texture| {
+            texture.width() == width
+                && texture.height() == height
+                && texture.has_same_format(source)
+        });
+        if !reuse {
+            *cache = Some(source.new_empty_with_same_format(width, height));
+        }
+        let intermediate = cache.as_mut().unwrap();
+
+        // The vertical pass needs the rows of the intermediate texture within the radius of the scissor box
+        let scale = downsample as i32;
+        let margin = (self.radius / downsample) as i32 + 2;
+        let x0 = (scissor_box.x / scale - 1).max(0);
+        let y0 = (scissor_box.y / scale - margin).max(0);
+        let x1 = ((scissor_box.x + scissor_box.width as i32) / scale + 1).min(width as i32);
+        let y1 = ((scissor_box.y + scissor_box.height as i32) / scale + margin).min(height as i32);
+        let intermediate_scissor_box = ScissorBox {
+            x: x0,
+            y: y0,
+            width: (x1 - x0).max(0) as u32,
+            height: (y1 - y0).max(0) as u32,
+        };
+
+        intermediate
+            .as_color_target(None)
+            .write_partially(intermediate_scissor_box, || {
+                self.blur(
+                    context,
+                    source,
+                    vec2(downsample as f32 / source.width() as f32, 0.0),
+                    downsample,
+                    Viewport::new_at_origo(width, height),
+                )
+            });
+        target.write_partially(scissor_box, || {
+            self.blur(
+                context,
+                intermediate,
+                vec2(0.0, 1.0 / height as f32),
+                downsample,
+                target.viewport(),
+            )
+        });
+    }
+
+    ///
+    /// Applies a single one-dimensional pass of the blur to the source texture, which is useful when the intermediate texture should be reused between blurs.
+    /// The direction is given in texels of the source texture, so `vec2(1.0, 0.0)` gives a horizontal blur and `vec2(0.0, 1.0)` a vertical blur.
+    /// The downsample factor is not used.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    /// # Panic
+    /// Will panic if the radius is larger than 64.
+    ///
+    pub fn apply_pass(
+        &self,
+        context: &Context,
+        source: &Texture2D,
+        direction: Vec2,
+        viewport: Viewport,
+    ) {
+        self.blur(
+            context,
+            source,
+            vec2(
+                direction.x / source.width() as f32,
+                direction.y / source.height() as f32,
+            ),
+            1,
+            viewport,
+        )
+    }
+
+    ///
+    /// Returns the two [FramePass]es which blur the color texture with the given input name and write the result to a color texture with the given output name, format and the size of the viewport.
+    /// The first pass blurs horizontally into an intermediate texture, which is downsampled by [GaussianBlur::downsample] and named after the output with a `.horizontal_blur` suffix,
+    /// and the second pass blurs the intermediate texture vertically into the output.
+    /// The input texture should have the size of the viewport.
+    ///
+    /// # Panic
+    /// Will panic if the radius divided by the downsample factor is larger than 64.
+    ///
+    pub fn frame_passes<'a>(
+        &'a self,
+        context: &'a Context,
+        input: &str,
+        output: &str,
+        format: FrameTextureFormat,
+    ) -> [FramePass<'a>; 2] {
+        let downsample = self.downsample.max(1);
+        let intermediate = format!("{}.horizontal_blur", output);
+        let input_name = input.to_string();
+        let intermediate_name = intermediate.clone();
+        let horizontal = FramePass::new("gaussian_blur_horizontal", move |resources| {
+            let source = resources.color_texture(&input_name);
+            let target = resources.color_target(&intermediate_name);
+            let viewport = Viewport::new_at_origo(target.width(), target.height());
+            target.clear(ClearState::default()).write(|| {
+                self.blur(
+                    context,
+                    source,
+                    vec2(downsample as f32 / source.width() as f32, 0.0),
+                    downsample,
+                    viewport,
+                )
+            });
+        })
+        .read(input)
+        .write(
+            &intermediate,
+            format,
+            FrameTextureSize::ViewportRelative(1.0 / downsample as f32),
+        );
+
+        let intermediate_name = intermediate.clone();
+        let output_name = output.to_string();
+        let vertical = FramePass::new("gaussian_blur_vertical", move |resources| {
+            let source = resources.color_texture(&intermediate_name);
+            let viewport = resources.viewport();
+            resources
+                .color_target(&output_name)
+                .clear(ClearState::default())
+                .write(|| {
+                    self.blur(
+                        context,
+                        source,
+                        vec2(0.0, 1.0 / source.height() as f32),
+                        downsample,
+                        viewport,
+                    )
+                });
+        })
+        .read(&intermediate)
+        .write(output, format, FrameTextureSize::viewport());
+        [horizontal, vertical]
+    }
+
+    fn blur(
+        &self,
+        context: &Context,
+        source: &Texture2D,
+        step: Vec2,
+        downsample: u32,
+        viewport: Viewport,
+    ) {
+        let (offsets, weights) = self.kernel(downsample);
+        apply_effect(
+            context,
+            include_str!("shaders/gaussian_blur.frag"),
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            viewport,
+            |program| {
+                program.use_texture("image", source);
+                program.use_uniform("blurStep", step);
+                program.use_uniform_array("offsets", &offsets);
+                program.use_uniform_array("weights", &weights);
+                program.use_uniform("tapCount", offsets.len() as i32);
+            },
+        )
+    }
+
+    ///
+    /// Returns the offsets in texels and the weights of the taps on one side of the center, including the center,
+    /// where two neighbouring texels are combined into one tap sampled between them.
+    ///
+    fn kernel(&self, downsample: u32) -> (Vec<f32>, Vec<f32>) {
+        let radius = (self.radius + downsample - 1) / downsample;
+        if radius > MAX_RADIUS {
+            panic!(
+                "the radius of the Gaussian blur ({}) divided by the downsample factor ({}) must not be larger than {}",
+                self.radius, downsample, MAX_RADIUS
+            );
+        }
+        let sigma = (self.sigma / downsample as f32).max(0.0001);
+        let texel_weights = (0..=radius)
+            .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+            .collect::<Vec<_>>();
+        let sum = texel_weights[0] + 2.0 * texel_weights[1..].iter().sum::<f32>();
+
+        let mut offsets = vec![0.0];
+        let mut weights = vec![texel_weights[0] / sum];
+        for i in (1..=radius as usize).step_by(2) {
+            let w0 = texel_weights[i];
+            let w1 = texel_weights.get(i + 1).copied().unwrap_or(0.0);
+            let weight = w0 + w1;
+            offsets.push(if weight > 0.0 {
+                (i as f32 * w0 + (i + 1) as f32 * w1) / weight
+            } else {
+                i as f32
+            });
+            weights.push(weight / sum);
+        }
+        (offsets, weights)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_kernel() {
+        for (sigma, radius, downsample) in [(2.0, 4, 1), (5.0, 15, 1), (20.0, 60, 4), (0.0, 0, 1)] {
+            let blur = GaussianBlur {
+                downsample,
+                ..GaussianBlur::new_with_radius(sigma, radius)
+            };
+            let (offsets, weights) = blur.kernel(downsample);
+            let radius = (radius + downsample - 1) / downsample;
+            assert_eq!(offsets.len(), 1 + (radius as usize + 1) / 2);
+            let sum = weights[0] + 2.0 * weights[1..].iter().sum::<f32>();
+            assert!((sum - 1.0).abs() < 0.0001);
+            assert!(offsets
+                .windows(2)
+                .all(|w| w[0] < w[1] && w[1] <= radius as f32));
+        }
+    }
+}
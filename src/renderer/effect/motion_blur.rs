@@ -0,0 +1,105 @@
+use crate::renderer::*;
+
+///
+/// An effect that blurs moving objects along the direction they move, so they leave a trail like objects filmed with a real camera.
+/// The movement is given by a velocity texture, typically with the [f16] RG format, which contains the screen space velocity of each pixel
+/// in texture coordinates per frame, ie. the difference between the current and the previous position of the surface in texture coordinates.
+/// The velocity texture must be rendered in a preceding pass using a [VelocityMaterial], for example with [VelocityMaterial::frame_pass],
+/// and a [CameraHistory] to keep track of the view-projection matrix of the camera from the previous frame.
+///
+#[derive(Clone, Debug)]
+pub struct MotionBlurEffect {
+    /// The fraction of the frame time that the shutter is open, which scales the length of the blur.
+    /// A value of `1.0` blurs over the entire movement since the previous frame.
+    pub shutter_speed: f32,
+    /// The number of samples along the velocity which are averaged for each pixel.
+    pub sample_count: u32,
+}
+
+impl Default for MotionBlurEffect {
+    fn default() -> Self {
+        Self {
+            shutter_speed: 0.5,
+            sample_count: 8,
+        }
+    }
+}
+
+impl MotionBlurEffect {
+    ///
+    /// Creates a new motion blur effect with the given shutter speed and number of samples.
+    ///
+    pub fn new(shutter_speed: f32, sample_count: u32) -> Self {
+        Self {
+            shutter_speed,
+            sample_count,
+        }
+    }
+
+    ///
+    /// Applies the motion blur effect to the given color texture based on the given velocity texture and writes the result to the current render target.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn apply(
+        &self,
+        context: &Context,
+        color_texture: ColorTexture,
+        velocity_texture: &Texture2D,
+    ) {
+        apply_effect(
+            context,
+            &format!(
+                "{}\n{}",
+                color_texture.fragment_shader_source(),
+                include_str!("shaders/motion_blur_effect.frag")
+            ),
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            Viewport::new_at_origo(color_texture.width(), color_texture.height()),
+            |program| {
+                color_texture.use_uniforms(program);
+                program.use_texture("velocityMap", velocity_texture);
+                program.use_uniform("shutterSpeed", self.shutter_speed);
+                program.use_uniform("sampleCount", self.sample_count.max(1) as i32);
+            },
+        )
+    }
+
+    ///
+    /// Returns a [FramePass] which applies the motion blur effect to the color texture with the given name based on the velocity texture with the given name
+    /// and writes the result to a color texture with the given output name, format and the size of the viewport.
+    ///
+    pub fn frame_pass<'a>(
+        &'a self,
+        context: &'a Context,
+        color: &str,
+        velocity: &str,
+        output: &str,
+        format: FrameTextureFormat,
+    ) -> FramePass<'a> {
+        let color_name = color.to_string();
+        let velocity_name = velocity.to_string();
+        let output_name = output.to_string();
+        FramePass::new("motion_blur", move |resources| {
+            let color_texture = resources.color_texture(&color_name);
+            let velocity_texture = resources.color_texture(&velocity_name);
+            resources
+                .color_target(&output_name)
+                .clear(ClearState::default())
+                .write(|| {
+                    self.apply(
+                        context,
+                        ColorTexture::Single(color_texture),
+                        velocity_texture,
+                    )
+                });
+        })
+        .read(color)
+        .read(velocity)
+        .write(output, format, FrameTextureSize::viewport())
+    }
+}
@@ -0,0 +1,248 @@
+use crate::renderer::*;
+
+///
+/// The textures that describe the surfaces seen in the image, which are needed by the [ScreenSpaceReflectionsEffect] in addition to the depth texture.
+///
+#[derive(Clone, Copy)]
+pub enum ReflectionSurfaces<'a> {
+    /// The color texture array written in the geometry pass of a deferred render, ie. when rendering objects with a [DeferredPhysicalMaterial],
+    /// where the first layer contains the albedo and metallic and the second layer contains the normal and roughness.
+    GeometryPass(&'a Texture2DArray),
+    /// A texture containing the world space normals encoded as `0.5 + 0.5 * normal`, for example rendered in a prepass with a [NormalMaterial],
+    /// and the roughness of all surfaces. The surfaces are assumed to be dielectric when computing the Fresnel term in [ScreenSpaceReflectionsEffect::apply].
+    Normals {
+        /// The normal texture.
+        texture: &'a Texture2D,
+        /// The roughness of all surfaces.
+        roughness: f32,
+    },
+}
+
+///
+/// An effect that adds approximate reflections to glossy surfaces of any shape by tracing the reflected rays in the rendered image.
+/// It uses the depth texture and the normals and roughness of the surfaces, see [ReflectionSurfaces], to find the position, normal and roughness of each surface.
+///
+/// Each reflected ray is marched in steps until it is behind the surface seen at that position in the image, then the intersection is refined using a binary search.
+/// Only what is visible in the image can be reflected, so rays leaving the screen are faded out,
+/// and if an environment map is given, the environment is reflected instead.
+/// The reflected color is blurred based on the roughness of the surface and the distance to the reflected surface.
+/// The rays and the blur use fixed patterns instead of random jitter, so the reflections do not flicker when the camera moves.
+///
+#[derive(Clone, Debug)]
+pub struct ScreenSpaceReflectionsEffect {
+    /// The maximum number of steps along each reflected ray.
+    pub max_steps: u32,
+    /// The length of each step along the reflected ray in world units.
+    pub step_size: f32,
+    /// How far a ray can be behind a surface in world units and still be considered to hit the surface.
+    pub thickness: f32,
+    /// Surfaces with a roughness above this value do not get reflections and the reflections fade out as the roughness approaches this value.
+    pub max_roughness: f32,
+    /// The radius in pixels of the blur of the reflected color for surfaces with the maximum roughness.
+    pub blur_radius: f32,
+}
+
+impl Default for ScreenSpaceReflectionsEffect {
+    fn default() -> Self {
+        Self {
+            max_steps: 64,
+            step_size: 0.1,
+            thickness: 0.2,
+            max_roughness: 0.6,
+            blur_radius: 8.0,
+        }
+    }
+}
+
+impl ScreenSpaceReflectionsEffect {
+    ///
+    /// Creates a new screen space reflections effect with the given maximum number of steps, step size, thickness, maximum roughness and blur radius.
+    ///
+    pub fn new(
+        max_steps: u32,
+        step_size: f32,
+        thickness: f32,
+        max_roughness: f32,
+        blur_radius: f32,
+    ) -> Self {
+        Self {
+            max_steps,
+            step_size,
+            thickness,
+            max_roughness,
+            blur_radius,
+        }
+    }
+
+    ///
+    /// Adds reflections to the given color texture, typically the result of [DeferredPhysicalMaterial::lighting_pass], and writes the result to the current render target.
+    /// The reflections are blended with the color based on the Fresnel term and the roughness of the surfaces.
+    /// The environment map is reflected where a ray does not hit anything visible and should contain colors in the same color space as the color texture.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn apply(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        color_texture: ColorTexture,
+        surfaces: ReflectionSurfaces,
+        depth_texture: DepthTexture,
+        environment_map: Option<&TextureCubeMap>,
+    ) {
+        self.render(
+            context,
+            camera,
+            color_texture,
+            surfaces,
+            depth_texture,
+            environment_map,
+            false,
+        )
+    }
+
+    ///
+    /// Writes only the reflections to the current render target, where the reflected color is in the rgb channels
+    /// and the weight of the reflection, which is zero where nothing is reflected and decreases with the roughness, is in the alpha channel.
+    /// This can then be blended with the color in a custom composite pass, for example using a Fresnel term based on the material of the surfaces.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn apply_reflections(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        color_texture: ColorTexture,
+        surfaces: ReflectionSurfaces,
+        depth_texture: DepthTexture,
+        environment_map: Option<&TextureCubeMap>,
+    ) {
+        self.render(
+            context,
+            camera,
+            color_texture,
+            surfaces,
+            depth_texture,
+            environment_map,
+            true,
+        )
+    }
+
+    ///
+    /// Returns a [FramePass] which adds reflections to the color texture with the given name (see [ScreenSpaceReflectionsEffect::apply])
+    /// using the normal texture and depth texture with the given names and the given roughness of all surfaces, see [ReflectionSurfaces::Normals],
+    /// and writes the result to a color texture with the given output name, format and the size of the viewport.
+    /// The camera should use the viewport given to [FrameGraph::execute].
+    ///
+    pub fn frame_pass<'a>(
+        &'a self,
+        context: &'a Context,
+        camera: &'a Camera,
+        color: &str,
+        normal: &str,
+        roughness: f32,
+        depth: &str,
+        environment_map: Option<&'a TextureCubeMap>,
+        output: &str,
+        format: FrameTextureFormat,
+    ) -> FramePass<'a> {
+        let color_name = color.to_string();
+        let normal_name = normal.to_string();
+        let depth_name = depth.to_string();
+        let output_name = output.to_string();
+        FramePass::new("screen_space_reflections", move |resources| {
+            let color_texture = resources.color_texture(&color_name);
+            let normal_texture = resources.color_texture(&normal_name);
+            let depth_texture = resources.depth_texture(&depth_name);
+            resources
+                .color_target(&output_name)
+                .clear(ClearState::default())
+                .write(|| {
+                    self.apply(
+                        context,
+                        camera,
+                        ColorTexture::Single(color_texture),
+                        ReflectionSurfaces::Normals {
+                            texture: normal_texture,
+                            roughness,
+                        },
+                        DepthTexture::Single(depth_texture),
+                        environment_map,
+                    )
+                });
+        })
+        .read(color)
+        .read(normal)
+        .read(depth)
+        .write(output, format, FrameTextureSize::viewport())
+    }
+
+    fn render(
+        &self,
+        context: &Context,
+        camera: &Camera,
+        color_texture: ColorTexture,
+        surfaces: ReflectionSurfaces,
+        depth_texture: DepthTexture,
+        environment_map: Option<&TextureCubeMap>,
+        reflections_only: bool,
+    ) {
+        apply_effect(
+            context,
+            &format!(
+                "{}{}{}{}\n{}\n{}\n{}",
+                if environment_map.is_some() {
+                    "#define USE_ENVIRONMENT\n"
+                } else {
+                    ""
+                },
+                if let ReflectionSurfaces::GeometryPass(_) = surfaces {
+                    "#define USE_GEOMETRY_PASS\n"
+                } else {
+                    ""
+                },
+                if reflections_only {
+                    "#define REFLECTIONS_ONLY\n"
+                } else {
+                    ""
+                },
+                include_str!("../../core/shared.frag"),
+                color_texture.fragment_shader_source(),
+                depth_texture.fragment_shader_source(),
+                include_str!("shaders/screen_space_reflections_effect.frag")
+            ),
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            camera.viewport(),
+            |program| {
+                color_texture.use_uniforms(program);
+                depth_texture.use_uniforms(program);
+                match surfaces {
+                    ReflectionSurfaces::GeometryPass(texture) => {
+                        program.use_texture_array("geometryPassTexture", texture);
+                    }
+                    ReflectionSurfaces::Normals { texture, roughness } => {
+                        program.use_texture("normalTexture", texture);
+                        program.use_uniform("surfaceRoughness", roughness);
+                    }
+                }
+                if let Some(environment_map) = environment_map {
+                    program.use_texture_cube("environmentMap", environment_map);
+                }
+                let (w, h) = color_texture.resolution();
+                program.use_uniform("resolution", vec2(w as f32, h as f32));
+                let view_projection = camera.projection() * camera.view();
+                program.use_uniform("viewProjection", view_projection);
+                program.use_uniform("viewProjectionInverse", view_projection.invert().unwrap());
+                program.use_uniform("cameraPosition", camera.position());
+                program.use_uniform("maxSteps", self.max_steps as i32);
+                program.use_uniform("stepSize", self.step_size);
+                program.use_uniform("thickness", self.thickness);
+                program.use_uniform("maxRoughness", self.max_roughness);
+                program.use_uniform("blurRadius", self.blur_radius);
+            },
+        )
+    }
+}
@@ -0,0 +1,24 @@
+
+uniform sampler2D luminanceTexture;
+uniform float luminanceLod;
+uniform sampler2D previousExposure;
+uniform float targetLuminance;
+uniform float minExposure;
+uniform float maxExposure;
+uniform float speedUp;
+uniform float speedDown;
+uniform float elapsedTime;
+uniform float reset;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // The last mip level contains the weighted average of the log luminance and the average weight
+    vec2 l = textureLod(luminanceTexture, vec2(0.5), luminanceLod).rg;
+    float average = l.x / max(l.y, 0.0001);
+    float target = clamp(log2(targetLuminance) - average, minExposure, maxExposure);
+    float previous = texelFetch(previousExposure, ivec2(0, 0), 0).r;
+    float exposure = reset > 0.5 ? target : previous + clamp(target - previous, -speedDown * elapsedTime, speedUp * elapsedTime);
+    outColor = vec4(clamp(exposure, minExposure, maxExposure), 0.0, 0.0, 1.0);
+}
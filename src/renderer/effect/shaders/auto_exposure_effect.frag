@@ -0,0 +1,14 @@
+
+uniform sampler2D exposureTexture;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    vec4 c = sample_color(uvs);
+    float exposure = exp2(texelFetch(exposureTexture, ivec2(0, 0), 0).r);
+    vec3 hdr = inverse_reinhard_tone_mapping(rgb_from_srgb(c.rgb));
+    color = vec4(srgb_from_rgb(reinhard_tone_mapping(exposure * hdr)), c.a);
+}
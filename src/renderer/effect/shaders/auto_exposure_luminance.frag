@@ -0,0 +1,17 @@
+
+uniform float falloff;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // The colors are tone mapped and sRGB encoded by the materials, so the scene luminance is recovered first
+    vec3 hdr = inverse_reinhard_tone_mapping(rgb_from_srgb(sample_color(uvs).rgb));
+    float luminance = dot(hdr, vec3(0.2126, 0.7152, 0.0722));
+    // The distance is 1.0 at the middle of the edges
+    vec2 d = 2.0 * (uvs - 0.5);
+    float weight = exp(-falloff * dot(d, d));
+    outColor = vec4(weight * log2(max(luminance, 0.0001)), weight, 0.0, 1.0);
+}
@@ -0,0 +1,14 @@
+
+uniform float strength;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    // The red and blue channels are shifted in opposite directions away from the center, more so towards the edges
+    vec2 offset = (uvs - 0.5) * strength;
+    vec4 c = sample_color(uvs);
+    color = vec4(sample_color(uvs + offset).r, c.g, sample_color(uvs - offset).b, c.a);
+}
@@ -0,0 +1,36 @@
+
+uniform float lutSize;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 color;
+
+#ifdef USE_LUT_STRIP
+uniform sampler2D lut;
+
+// The strip contains one slice for each value of blue from left to right, where red increases to the right and green increases upwards within a slice
+vec3 lookup(vec3 c)
+{
+    float b = c.b * (lutSize - 1.0);
+    float b0 = floor(b);
+    float b1 = min(b0 + 1.0, lutSize - 1.0);
+    vec2 uv = vec2((c.r * (lutSize - 1.0) + 0.5) / (lutSize * lutSize), (c.g * (lutSize - 1.0) + 0.5) / lutSize);
+    vec3 c0 = texture(lut, uv + vec2(b0 / lutSize, 0.0)).rgb;
+    vec3 c1 = texture(lut, uv + vec2(b1 / lutSize, 0.0)).rgb;
+    return mix(c0, c1, b - b0);
+}
+#else
+uniform sampler3D lut;
+
+vec3 lookup(vec3 c)
+{
+    // Sample at the center of the first and last texels for the values 0 and 1
+    return texture(lut, (c * (lutSize - 1.0) + 0.5) / lutSize).rgb;
+}
+#endif
+
+void main()
+{
+    vec4 c = sample_color(uvs);
+    color = vec4(lookup(clamp(c.rgb, 0.0, 1.0)), c.a);
+}
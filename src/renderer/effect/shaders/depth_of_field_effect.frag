@@ -0,0 +1,54 @@
+
+uniform mat4 viewProjectionInverse;
+uniform vec3 eyePosition;
+uniform float focalDistance;
+uniform float focalRange;
+uniform float maxBlurRadius;
+uniform vec2 resolution;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 color;
+
+const int RINGS = 4;
+
+// The radius of the circle of confusion in pixels at the given uv coordinates.
+float circle_of_confusion(vec2 uv) {
+    float depth = sample_depth(uv);
+    vec3 position = world_pos_from_depth(viewProjectionInverse, depth, uv);
+    float dist = depth > 0.99999 ? 1.0e10 : distance(position, eyePosition);
+    float blur = (abs(dist - focalDistance) - focalRange) / max(focalRange, 0.0001);
+    return maxBlurRadius * clamp(blur, 0.0, 1.0);
+}
+
+void main()
+{
+    vec4 center = sample_color(uvs);
+    float coc = circle_of_confusion(uvs);
+    if (coc < 0.5) {
+        color = center;
+        return;
+    }
+
+    // Gather samples on hexagonal rings scaled to the circle of confusion of this pixel.
+    // A sample only contributes if its own circle of confusion covers this pixel,
+    // which prevents sharp objects from bleeding into the blurred background.
+    vec4 sum = center;
+    float weightSum = 1.0;
+    for (int ring = 1; ring <= RINGS; ring++) {
+        float radius = coc * float(ring) / float(RINGS);
+        for (int side = 0; side < 6; side++) {
+            float angle = float(side) * PI / 3.0;
+            vec2 corner0 = vec2(cos(angle), sin(angle));
+            vec2 corner1 = vec2(cos(angle + PI / 3.0), sin(angle + PI / 3.0));
+            for (int i = 0; i < ring; i++) {
+                vec2 offset = radius * mix(corner0, corner1, float(i) / float(ring));
+                vec2 uv = uvs + offset / resolution;
+                float weight = clamp(circle_of_confusion(uv) - length(offset) + 1.0, 0.0, 1.0);
+                sum += weight * sample_color(uv);
+                weightSum += weight;
+            }
+        }
+    }
+    color = sum / weightSum;
+}
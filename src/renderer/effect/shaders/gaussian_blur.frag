@@ -0,0 +1,21 @@
+
+uniform sampler2D image;
+uniform vec2 blurStep;
+uniform float offsets[33];
+uniform float weights[33];
+uniform int tapCount;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // A Gaussian blur in the given direction where each tap, except the center, samples between two texels using linear interpolation
+    vec4 result = texture(image, uvs) * weights[0];
+    for (int i = 1; i < tapCount; i++) {
+        result += texture(image, uvs + offsets[i] * blurStep) * weights[i];
+        result += texture(image, uvs - offsets[i] * blurStep) * weights[i];
+    }
+    outColor = result;
+}
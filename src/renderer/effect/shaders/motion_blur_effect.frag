@@ -0,0 +1,20 @@
+
+uniform sampler2D velocityMap;
+uniform float shutterSpeed;
+uniform int sampleCount;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    // Average samples along the velocity, centered around the current pixel
+    vec2 velocity = texture(velocityMap, uvs).xy * shutterSpeed;
+    vec4 result = vec4(0.0);
+    for (int i = 0; i < sampleCount; i++) {
+        float t = sampleCount > 1 ? float(i) / float(sampleCount - 1) - 0.5 : 0.0;
+        result += sample_color(uvs + t * velocity);
+    }
+    color = result / float(sampleCount);
+}
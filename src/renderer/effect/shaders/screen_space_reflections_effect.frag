@@ -0,0 +1,142 @@
+
+#ifdef USE_GEOMETRY_PASS
+uniform sampler2DArray geometryPassTexture;
+#else
+uniform sampler2D normalTexture;
+uniform float surfaceRoughness;
+#endif
+uniform vec2 resolution;
+uniform mat4 viewProjection;
+uniform mat4 viewProjectionInverse;
+uniform vec3 cameraPosition;
+uniform int maxSteps;
+uniform float stepSize;
+uniform float thickness;
+uniform float maxRoughness;
+uniform float blurRadius;
+#ifdef USE_ENVIRONMENT
+uniform samplerCube environmentMap;
+#endif
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+bool is_inside_screen(vec2 uv) {
+    return uv.x >= 0.0 && uv.x <= 1.0 && uv.y >= 0.0 && uv.y <= 1.0;
+}
+
+// Returns how far the given position is behind the surface seen at the screen position of the given position and returns that screen position in uv.
+float depth_difference(vec3 position, out vec2 uv) {
+    vec4 clip = viewProjection * vec4(position, 1.0);
+    uv = 0.5 * clip.xy / clip.w + 0.5;
+    vec3 surface = world_pos_from_depth(viewProjectionInverse, sample_depth(uv), uv);
+    return distance(position, cameraPosition) - distance(surface, cameraPosition);
+}
+
+// Averages the color in a disc with the given radius in pixels around the given position, using a fixed pattern to avoid flickering
+vec3 blurred_color(vec2 uv, float radius) {
+    vec3 result = sample_color(uv).rgb;
+    if (radius < 0.5) {
+        return result;
+    }
+    vec2 scale = radius / resolution;
+    for (int i = 0; i < 8; i++) {
+        float angle = float(i) * 0.785398;
+        float r = i % 2 == 0 ? 1.0 : 0.5;
+        result += sample_color(uv + r * scale * vec2(cos(angle), sin(angle))).rgb;
+    }
+    return result / 9.0;
+}
+
+// Returns the reflected color in rgb and the weight of the reflection in alpha, which is zero if the ray leaves the screen without hitting anything.
+// The reflected color is blurred more for rough surfaces and when the hit is further away, since the reflection of a rough surface spreads out with the distance.
+vec4 trace_reflection(vec3 position, vec3 direction, float roughness) {
+    vec3 previous = position;
+    for (int i = 1; i <= maxSteps; i++) {
+        vec3 current = position + float(i) * stepSize * direction;
+        vec2 uv;
+        if (depth_difference(current, uv) > 0.0) {
+            if (!is_inside_screen(uv)) {
+                break;
+            }
+            // Binary search for the intersection between the previous and current step
+            vec3 front = previous;
+            vec3 back = current;
+            for (int j = 0; j < 8; j++) {
+                vec3 middle = 0.5 * (front + back);
+                if (depth_difference(middle, uv) > 0.0) {
+                    back = middle;
+                } else {
+                    front = middle;
+                }
+            }
+            if (depth_difference(back, uv) < thickness) {
+                // Fade out towards the edges of the screen and the end of the ray to avoid a visible cut
+                vec2 edge = min(uv, 1.0 - uv);
+                float fade = clamp(10.0 * min(edge.x, edge.y), 0.0, 1.0) * (1.0 - float(i - 1) / float(maxSteps));
+                float spread = min(distance(back, position) / (float(maxSteps) * stepSize) * 4.0, 1.0);
+                float radius = blurRadius * roughness / max(maxRoughness, 0.0001) * spread;
+                return vec4(blurred_color(uv, radius), fade);
+            }
+        } else if (!is_inside_screen(uv)) {
+            break;
+        }
+        previous = current;
+    }
+    return vec4(0.0);
+}
+
+// Returns the normal, roughness and the specular color at normal incidence of the surface seen at the given position
+void surface_properties(vec2 uv, out vec3 normal, out float roughness, out vec3 f0) {
+#ifdef USE_GEOMETRY_PASS
+    vec4 n = texture(geometryPassTexture, vec3(uv, 1));
+    roughness = n.w;
+    vec4 c = texture(geometryPassTexture, vec3(uv, 0));
+    f0 = mix(vec3(0.04), c.rgb, c.w);
+    vec2 n2 = n.xy * 2.0 - 1.0;
+    float z = sqrt(max(1.0 - n2.x * n2.x - n2.y * n2.y, 0.0));
+    normal = normalize(vec3(n2.x, n2.y, (int(floor(n.z * 255.0)) & 128) == 128 ? z: -z));
+#else
+    normal = normalize(texture(normalTexture, uv).xyz * 2.0 - 1.0);
+    roughness = surfaceRoughness;
+    f0 = vec3(0.04);
+#endif
+}
+
+void main()
+{
+    vec4 color = sample_color(uvs);
+    float depth = sample_depth(uvs);
+    vec3 normal;
+    float roughness;
+    vec3 f0;
+    surface_properties(uvs, normal, roughness, f0);
+    if (depth > 0.99999 || roughness > maxRoughness) {
+#ifdef REFLECTIONS_ONLY
+        outColor = vec4(0.0);
+#else
+        outColor = color;
+#endif
+        return;
+    }
+
+    vec3 position = world_pos_from_depth(viewProjectionInverse, depth, uvs);
+    vec3 view = normalize(position - cameraPosition);
+    vec3 direction = reflect(view, normal);
+    vec4 reflection = trace_reflection(position, direction, roughness);
+#ifdef USE_ENVIRONMENT
+    reflection.rgb = mix(texture(environmentMap, direction).rgb, reflection.rgb, reflection.a);
+    reflection.a = 1.0;
+#endif
+    // Less reflection for rough surfaces
+    reflection.a *= 1.0 - roughness / max(maxRoughness, 0.0001);
+
+#ifdef REFLECTIONS_ONLY
+    outColor = reflection;
+#else
+    // Replace the part of the color given by the Fresnel term of the specular reflection
+    vec3 fresnel = f0 + (1.0 - f0) * pow(1.0 - max(dot(-view, normal), 0.0), 5.0);
+    outColor = vec4(mix(color.rgb, reflection.rgb, fresnel * reflection.a), color.a);
+#endif
+}
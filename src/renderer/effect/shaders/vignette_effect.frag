@@ -0,0 +1,18 @@
+
+uniform float radius;
+uniform float softness;
+uniform float strength;
+uniform vec4 vignetteColor;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    // The distance is 0.5 at the middle of the edges and about 0.7 in the corners
+    float dist = length(uvs - 0.5);
+    float amount = strength * smoothstep(radius, radius + softness, dist);
+    vec4 c = sample_color(uvs);
+    color = vec4(mix(c.rgb, vignetteColor.rgb, amount), c.a);
+}
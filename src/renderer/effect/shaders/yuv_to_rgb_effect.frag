@@ -0,0 +1,15 @@
+uniform sampler2D lumaTexture;
+uniform sampler2D chromaTexture;
+uniform mat3 yuvToRgb;
+uniform vec3 yuvOffset;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 color;
+
+void main()
+{
+    // The chroma texture usually has half the resolution of the luma texture, which is handled by sampling both using the same uv coordinates
+    vec3 yuv = vec3(texture(lumaTexture, uvs).r, texture(chromaTexture, uvs).rg);
+    color = vec4(clamp(yuvToRgb * (yuv - yuvOffset), 0.0, 1.0), 1.0);
+}
@@ -0,0 +1,95 @@
+use crate::renderer::*;
+
+///
+/// An effect that darkens the border of the image, or more generally fades it towards a color, like the vignetting of a real camera lens.
+/// The effect is typically applied as the last pass, after tone mapping, and can be combined with other effects using a [FrameGraph].
+///
+#[derive(Clone, Debug)]
+pub struct VignetteEffect {
+    /// The distance from the center of the image, where the edges of the image are at a distance of `0.5`, within which the image is unchanged.
+    pub radius: f32,
+    /// The distance beyond the radius over which the image fades towards the color.
+    pub softness: f32,
+    /// How much the image fades towards the color outside the radius plus the softness. A value of `0.0` leaves the image unchanged.
+    pub strength: f32,
+    /// The color that the border fades towards.
+    pub color: Color,
+}
+
+impl Default for VignetteEffect {
+    fn default() -> Self {
+        Self {
+            radius: 0.4,
+            softness: 0.35,
+            strength: 0.8,
+            color: Color::BLACK,
+        }
+    }
+}
+
+impl VignetteEffect {
+    ///
+    /// Creates a new vignette effect with the given radius, softness, strength and color, see the fields of [VignetteEffect].
+    ///
+    pub fn new(radius: f32, softness: f32, strength: f32, color: Color) -> Self {
+        Self {
+            radius,
+            softness,
+            strength,
+            color,
+        }
+    }
+
+    ///
+    /// Applies the vignette effect to the given color texture and writes the result to the current render target.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn apply(&self, context: &Context, color_texture: ColorTexture) {
+        apply_effect(
+            context,
+            &format!(
+                "{}\n{}",
+                color_texture.fragment_shader_source(),
+                include_str!("shaders/vignette_effect.frag")
+            ),
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            Viewport::new_at_origo(color_texture.width(), color_texture.height()),
+            |program| {
+                color_texture.use_uniforms(program);
+                program.use_uniform("radius", self.radius);
+                program.use_uniform("softness", self.softness.max(0.0001));
+                program.use_uniform("strength", self.strength);
+                program.use_uniform("vignetteColor", self.color);
+            },
+        )
+    }
+
+    ///
+    /// Returns a [FramePass] which applies the vignette effect to the color texture with the given input name
+    /// and writes the result to a color texture with the given output name, format and the size of the viewport.
+    ///
+    pub fn frame_pass<'a>(
+        &'a self,
+        context: &'a Context,
+        input: &str,
+        output: &str,
+        format: FrameTextureFormat,
+    ) -> FramePass<'a> {
+        let input_name = input.to_string();
+        let output_name = output.to_string();
+        FramePass::new("vignette", move |resources| {
+            let color_texture = resources.color_texture(&input_name);
+            resources
+                .color_target(&output_name)
+                .clear(ClearState::default())
+                .write(|| self.apply(context, ColorTexture::Single(color_texture)));
+        })
+        .read(input)
+        .write(output, format, FrameTextureSize::viewport())
+    }
+}
@@ -0,0 +1,142 @@
+use crate::renderer::*;
+
+///
+/// The standard which defines how the YUV values of a video frame are converted to RGB, see [YuvToRgbEffect].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum YuvColorMatrix {
+    /// ITU-R BT.601, used by standard definition video.
+    Bt601,
+    /// ITU-R BT.709, used by most high definition video.
+    Bt709,
+}
+
+///
+/// An effect that converts a video frame with separate luma (Y) and chroma (UV) planes, for example decoded from the NV12 format, to RGB.
+/// The luma plane is a texture with one channel, for example a [StreamingTexture2D] with [PixelFormat::R8],
+/// and the chroma plane is a texture with two channels, usually with half the width and height, for example a [StreamingTexture2D] with [PixelFormat::Rg8].
+///
+/// The resulting colors are encoded in the same way as the video, which is close to sRGB, so they can be written directly to the screen,
+/// but should be treated as sRGB if they are written to a texture which is used as input to other materials or effects.
+///
+#[derive(Clone, Debug)]
+pub struct YuvToRgbEffect {
+    /// The standard used for converting YUV to RGB.
+    pub color_matrix: YuvColorMatrix,
+    /// Whether the YUV values use the full range of values, otherwise the luma is in the range `[16..235]` and the chroma in the range `[16..240]`,
+    /// which is the case for most video.
+    pub full_range: bool,
+}
+
+impl Default for YuvToRgbEffect {
+    fn default() -> Self {
+        Self {
+            color_matrix: YuvColorMatrix::Bt709,
+            full_range: false,
+        }
+    }
+}
+
+impl YuvToRgbEffect {
+    ///
+    /// Creates a new YUV to RGB conversion effect with the given color matrix and range.
+    ///
+    pub fn new(color_matrix: YuvColorMatrix, full_range: bool) -> Self {
+        Self {
+            color_matrix,
+            full_range,
+        }
+    }
+
+    ///
+    /// Converts the given luma and chroma textures to RGB and writes the result to the given viewport of the current render target.
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    pub fn apply(
+        &self,
+        context: &Context,
+        luma: &Texture2D,
+        chroma: &Texture2D,
+        viewport: Viewport,
+    ) {
+        let (yuv_to_rgb, yuv_offset) = self.conversion();
+        apply_effect(
+            context,
+            include_str!("shaders/yuv_to_rgb_effect.frag"),
+            RenderStates {
+                write_mask: WriteMask::COLOR,
+                depth_test: DepthTest::Always,
+                cull: Cull::Back,
+                ..Default::default()
+            },
+            viewport,
+            |program| {
+                program.use_texture("lumaTexture", luma);
+                program.use_texture("chromaTexture", chroma);
+                program.use_uniform("yuvToRgb", yuv_to_rgb);
+                program.use_uniform("yuvOffset", yuv_offset);
+            },
+        )
+    }
+
+    ///
+    /// Returns the matrix and the offset which converts YUV values in the range `[0..1]` to RGB using `rgb = matrix * (yuv - offset)`.
+    ///
+    fn conversion(&self) -> (Mat3, Vec3) {
+        let (kr, kb) = match self.color_matrix {
+            YuvColorMatrix::Bt601 => (0.299, 0.114),
+            YuvColorMatrix::Bt709 => (0.2126, 0.0722),
+        };
+        let kg = 1.0 - kr - kb;
+        // The columns are the contributions of Y, U and V to red, green and blue
+        let matrix = Mat3::new(
+            1.0,
+            1.0,
+            1.0,
+            0.0,
+            -2.0 * (1.0 - kb) * kb / kg,
+            2.0 * (1.0 - kb),
+            2.0 * (1.0 - kr),
+            -2.0 * (1.0 - kr) * kr / kg,
+            0.0,
+        );
+        if self.full_range {
+            (matrix, vec3(0.0, 0.5, 0.5))
+        } else {
+            let scale = Mat3::from_diagonal(vec3(255.0 / 219.0, 255.0 / 224.0, 255.0 / 224.0));
+            (
+                matrix * scale,
+                vec3(16.0 / 255.0, 128.0 / 255.0, 128.0 / 255.0),
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_conversion() {
+        let rgb = |effect: &YuvToRgbEffect, y: f32, u: f32, v: f32| {
+            let (matrix, offset) = effect.conversion();
+            matrix * (vec3(y, u, v) - offset)
+        };
+        for color_matrix in [YuvColorMatrix::Bt601, YuvColorMatrix::Bt709] {
+            let full = YuvToRgbEffect::new(color_matrix, true);
+            assert!(rgb(&full, 1.0, 0.5, 0.5).distance(vec3(1.0, 1.0, 1.0)) < 0.0001);
+            assert!(rgb(&full, 0.0, 0.5, 0.5).distance(vec3(0.0, 0.0, 0.0)) < 0.0001);
+
+            let limited = YuvToRgbEffect::new(color_matrix, false);
+            let white = rgb(&limited, 235.0 / 255.0, 128.0 / 255.0, 128.0 / 255.0);
+            assert!(white.distance(vec3(1.0, 1.0, 1.0)) < 0.0001);
+            let black = rgb(&limited, 16.0 / 255.0, 128.0 / 255.0, 128.0 / 255.0);
+            assert!(black.distance(vec3(0.0, 0.0, 0.0)) < 0.0001);
+        }
+
+        // Pure red in BT.709 with full range
+        let effect = YuvToRgbEffect::new(YuvColorMatrix::Bt709, true);
+        let red = rgb(&effect, 0.2126, 0.5 - 0.1146, 1.0);
+        assert!(red.distance(vec3(1.0, 0.0, 0.0)) < 0.001);
+    }
+}
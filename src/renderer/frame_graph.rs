@@ -0,0 +1,556 @@
+//!
+//! A lightweight frame graph which automates the allocation of the intermediate textures used when rendering a frame in several passes,
+//! for example shadow maps, an opaque pass, a transparent pass and a number of post processing effects.
+//!
+//! Each pass declares the textures it reads and writes by name and the [FrameGraph] orders the passes based on these dependencies,
+//! allocates the textures and reuses them across frames and within a frame when their content is no longer needed.
+//! Textures are only reallocated when the viewport size or the format changes.
+//!
+
+use crate::renderer::*;
+use std::collections::HashMap;
+
+///
+/// The format of a texture in a [FrameGraph].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FrameTextureFormat {
+    /// A color texture with four 8 bit channels.
+    Rgba8,
+    /// A color texture with four 16 bit float channels, for example for high dynamic range rendering.
+    Rgba16F,
+    /// A color texture with four 32 bit float channels.
+    Rgba32F,
+    /// A color texture with one 32 bit float channel.
+    R32F,
+    /// A color texture with two 16 bit float channels, for example for screen space velocities used by the [MotionBlurEffect].
+    Rg16F,
+    /// A depth texture with 32 bit float precision.
+    Depth32F,
+}
+
+impl FrameTextureFormat {
+    ///
+    /// Returns true if this is a depth format.
+    ///
+    pub fn is_depth(&self) -> bool {
+        *self == Self::Depth32F
+    }
+}
+
+///
+/// The size of a texture in a [FrameGraph].
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FrameTextureSize {
+    /// A fixed size in pixels, for example for shadow maps.
+    Absolute {
+        /// The width in pixels.
+        width: u32,
+        /// The height in pixels.
+        height: u32,
+    },
+    /// The size of the viewport given to [FrameGraph::execute] multiplied by the given factor, for example `0.5` for a texture of half the size of the viewport.
+    ViewportRelative(f32),
+}
+
+impl FrameTextureSize {
+    ///
+    /// The size of the viewport.
+    ///
+    pub fn viewport() -> Self {
+        Self::ViewportRelative(1.0)
+    }
+
+    fn resolve(&self, viewport: Viewport) -> (u32, u32) {
+        match *self {
+            Self::Absolute { width, height } => (width.max(1), height.max(1)),
+            Self::ViewportRelative(factor) => (
+                ((viewport.width as f32 * factor).round() as u32).max(1),
+                ((viewport.height as f32 * factor).round() as u32).max(1),
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Access {
+    Read,
+    Write(FrameTextureFormat, FrameTextureSize),
+    Modify,
+}
+
+///
+/// A pass in a [FrameGraph] which reads and/or writes a set of named textures.
+/// The closure given at construction is called with the resolved textures when the graph is executed.
+///
+pub struct FramePass<'a> {
+    name: String,
+    accesses: Vec<(String, Access)>,
+    execute: Box<dyn FnOnce(&mut PassResources) + 'a>,
+}
+
+impl<'a> FramePass<'a> {
+    ///
+    /// Creates a new pass with the given name, used in error messages, and the closure which is called with the resolved textures when the graph is executed.
+    ///
+    pub fn new(name: &str, execute: impl FnOnce(&mut PassResources) + 'a) -> Self {
+        Self {
+            name: name.to_string(),
+            accesses: Vec::new(),
+            execute: Box::new(execute),
+        }
+    }
+
+    ///
+    /// Declares that this pass reads the texture with the given name, which must be written by another pass.
+    /// The pass is executed after all passes writing to the texture.
+    ///
+    pub fn read(mut self, name: &str) -> Self {
+        self.accesses.push((name.to_string(), Access::Read));
+        self
+    }
+
+    ///
+    /// Declares that this pass writes the texture with the given name, format and size.
+    /// The content of the texture is undefined before this pass, so it should be cleared or completely overwritten.
+    ///
+    pub fn write(mut self, name: &str, format: FrameTextureFormat, size: FrameTextureSize) -> Self {
+        self.accesses
+            .push((name.to_string(), Access::Write(format, size)));
+        self
+    }
+
+    ///
+    /// Declares that this pass writes on top of the existing content of the texture with the given name, for example when rendering transparent objects or applying fog.
+    /// The pass is executed after the pass that [writes](FramePass::write) the texture and after other passes modifying the texture that are added before this pass.
+    ///
+    pub fn modify(mut self, name: &str) -> Self {
+        self.accesses.push((name.to_string(), Access::Modify));
+        self
+    }
+}
+
+///
+/// The textures available to a [FramePass] when it is executed.
+///
+pub struct PassResources<'r> {
+    viewport: Viewport,
+    color_textures: HashMap<String, &'r Texture2D>,
+    depth_textures: HashMap<String, &'r DepthTexture2D>,
+    color_targets: HashMap<String, &'r mut Texture2D>,
+    depth_targets: HashMap<String, &'r mut DepthTexture2D>,
+}
+
+impl<'r> PassResources<'r> {
+    ///
+    /// The viewport given to [FrameGraph::execute].
+    ///
+    pub fn viewport(&self) -> Viewport {
+        self.viewport
+    }
+
+    ///
+    /// Returns the color texture with the given name.
+    ///
+    /// # Panic
+    /// Will panic if the pass has not declared that it reads a color texture with the given name.
+    ///
+    pub fn color_texture(&self, name: &str) -> &'r Texture2D {
+        *self
+            .color_textures
+            .get(name)
+            .unwrap_or_else(|| panic!("the pass does not read the color texture {}", name))
+    }
+
+    ///
+    /// Returns the depth texture with the given name.
+    ///
+    /// # Panic
+    /// Will panic if the pass has not declared that it reads a depth texture with the given name.
+    ///
+    pub fn depth_texture(&self, name: &str) -> &'r DepthTexture2D {
+        *self
+            .depth_textures
+            .get(name)
+            .unwrap_or_else(|| panic!("the pass does not read the depth texture {}", name))
+    }
+
+    ///
+    /// Returns a [ColorTarget] for writing to the color texture with the given name.
+    ///
+    /// # Panic
+    /// Will panic if the pass has not declared that it writes or modifies a color texture with the given name.
+    ///
+    pub fn color_target(&mut self, name: &str) -> ColorTarget<'_> {
+        self.color_targets
+            .get_mut(name)
+            .unwrap_or_else(|| panic!("the pass does not write the color texture {}", name))
+            .as_color_target(None)
+    }
+
+    ///
+    /// Returns a [DepthTarget] for writing to the depth texture with the given name.
+    ///
+    /// # Panic
+    /// Will panic if the pass has not declared that it writes or modifies a depth texture with the given name.
+    ///
+    pub fn depth_target(&mut self, name: &str) -> DepthTarget<'_> {
+        self.depth_targets
+            .get_mut(name)
+            .unwrap_or_else(|| panic!("the pass does not write the depth texture {}", name))
+            .as_depth_target()
+    }
+
+    ///
+    /// Returns a [RenderTarget] for writing to the color texture and depth texture with the given names at the same time.
+    ///
+    /// # Panic
+    /// Will panic if the pass has not declared that it writes or modifies a color and depth texture with the given names.
+    ///
+    pub fn render_target(&mut self, color_name: &str, depth_name: &str) -> RenderTarget<'_> {
+        let color_target = self
+            .color_targets
+            .get_mut(color_name)
+            .unwrap_or_else(|| panic!("the pass does not write the color texture {}", color_name))
+            .as_color_target(None);
+        let depth_target = self
+            .depth_targets
+            .get_mut(depth_name)
+            .unwrap_or_else(|| panic!("the pass does not write the depth texture {}", depth_name))
+            .as_depth_target();
+        RenderTarget::new(color_target, depth_target)
+    }
+}
+
+enum PooledTexture {
+    Color(Texture2D),
+    Depth(DepthTexture2D),
+}
+
+type TextureKey = (FrameTextureFormat, u32, u32);
+
+///
+/// A frame graph which allocates the textures used by a set of [FramePass]es, orders the passes based on the textures they read and write
+/// and executes them, see the [module](crate::renderer::frame_graph) documentation.
+/// Keep the frame graph between frames, so the textures can be reused.
+///
+pub struct FrameGraph {
+    context: Context,
+    textures: Vec<(TextureKey, PooledTexture)>,
+}
+
+impl FrameGraph {
+    ///
+    /// Creates a new frame graph without any allocated textures.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            textures: Vec::new(),
+        }
+    }
+
+    ///
+    /// Executes the given passes in an order that respects their dependencies, passes without dependencies between them are executed in the given order.
+    /// Textures with the same format and size are reused when the content of one of them is no longer needed by any of the remaining passes.
+    /// Textures allocated in the previous frame are reused if possible, the rest are deallocated.
+    /// Nothing is executed if the viewport has zero width or height, for example when the window is minimized.
+    ///
+    /// # Panic
+    /// Will panic if a texture is read or modified but never written, if a texture is written by more than one pass, if a pass both reads and writes the same texture
+    /// or if the dependencies between the passes are cyclic.
+    ///
+    pub fn execute<'a>(&mut self, viewport: Viewport, passes: Vec<FramePass<'a>>) {
+        if viewport.width == 0 || viewport.height == 0 {
+            return;
+        }
+        let declarations = passes
+            .iter()
+            .map(|pass| (pass.name.clone(), pass.accesses.clone()))
+            .collect::<Vec<_>>();
+        let schedule = Schedule::new(&declarations, viewport);
+
+        // Reuse the textures from the previous frame if possible
+        let mut old_textures = std::mem::take(&mut self.textures);
+        for key in schedule.slots.iter() {
+            let texture = if let Some(index) = old_textures.iter().position(|(k, _)| k == key) {
+                old_textures.swap_remove(index).1
+            } else {
+                self.new_texture(*key)
+            };
+            self.textures.push((*key, texture));
+        }
+
+        let mut passes = passes.into_iter().map(Some).collect::<Vec<_>>();
+        for pass_index in schedule.order.iter() {
+            let pass = passes[*pass_index].take().unwrap();
+            let mut resources = PassResources {
+                viewport: Viewport::new_at_origo(viewport.width, viewport.height),
+                color_textures: HashMap::new(),
+                depth_textures: HashMap::new(),
+                color_targets: HashMap::new(),
+                depth_targets: HashMap::new(),
+            };
+            let mut slot_accesses = HashMap::new();
+            for (name, access) in pass.accesses.iter() {
+                slot_accesses.insert(schedule.assignments[name], (name.clone(), *access));
+            }
+            for (slot, (_, texture)) in self.textures.iter_mut().enumerate() {
+                if let Some((name, access)) = slot_accesses.remove(&slot) {
+                    match (texture, access) {
+                        (PooledTexture::Color(texture), Access::Read) => {
+                            resources.color_textures.insert(name, &*texture);
+                        }
+                        (PooledTexture::Depth(texture), Access::Read) => {
+                            resources.depth_textures.insert(name, &*texture);
+                        }
+                        (PooledTexture::Color(texture), _) => {
+                            resources.color_targets.insert(name, texture);
+                        }
+                        (PooledTexture::Depth(texture), _) => {
+                            resources.depth_targets.insert(name, texture);
+                        }
+                    }
+                }
+            }
+            (pass.execute)(&mut resources);
+        }
+    }
+
+    ///
+    /// Returns the number of textures currently allocated by this frame graph.
+    ///
+    pub fn texture_count(&self) -> usize {
+        self.textures.len()
+    }
+
+    fn new_texture(&self, key: TextureKey) -> PooledTexture {
+        let (format, width, height) = key;
+        match format {
+            FrameTextureFormat::Rgba8 => {
+                PooledTexture::Color(new_color_texture::<[u8; 4]>(&self.context, width, height))
+            }
+            FrameTextureFormat::Rgba16F => {
+                PooledTexture::Color(new_color_texture::<[f16; 4]>(&self.context, width, height))
+            }
+            FrameTextureFormat::Rgba32F => {
+                PooledTexture::Color(new_color_texture::<[f32; 4]>(&self.context, width, height))
+            }
+            FrameTextureFormat::R32F => {
+                PooledTexture::Color(new_color_texture::<f32>(&self.context, width, height))
+            }
+            FrameTextureFormat::Rg16F => {
+                PooledTexture::Color(new_color_texture::<[f16; 2]>(&self.context, width, height))
+            }
+            FrameTextureFormat::Depth32F => PooledTexture::Depth(DepthTexture2D::new::<f32>(
+                &self.context,
+                width,
+                height,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            )),
+        }
+    }
+}
+
+fn new_color_texture<T: TextureDataType>(context: &Context, width: u32, height: u32) -> Texture2D {
+    Texture2D::new_empty::<T>(
+        context,
+        width,
+        height,
+        Interpolation::Linear,
+        Interpolation::Linear,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+///
+/// The order in which the passes are executed and the assignment of the named textures to the allocated textures (slots).
+///
+#[derive(Debug)]
+struct Schedule {
+    order: Vec<usize>,
+    slots: Vec<TextureKey>,
+    assignments: HashMap<String, usize>,
+}
+
+impl Schedule {
+    fn new(passes: &[(String, Vec<(String, Access)>)], viewport: Viewport) -> Self {
+        // Find the pass that writes each texture and the passes that modifies each texture in the order they are given
+        let mut writers: HashMap<&str, (usize, FrameTextureFormat, FrameTextureSize)> =
+            HashMap::new();
+        let mut modifiers: HashMap<&str, Vec<usize>> = HashMap::new();
+        for (index, (pass_name, accesses)) in passes.iter().enumerate() {
+            for (name, access) in accesses.iter() {
+                if accesses
+                    .iter()
+                    .any(|(n, a)| n == name && *a != Access::Read && *access == Access::Read)
+                {
+                    panic!("the pass {} both reads and writes {}", pass_name, name);
+                }
+                match access {
+                    Access::Write(format, size) => {
+                        if writers
+                            .insert(name.as_str(), (index, *format, *size))
+                            .is_some()
+                        {
+                            panic!("{} is written by more than one pass", name);
+                        }
+                    }
+                    Access::Modify => modifiers.entry(name.as_str()).or_default().push(index),
+                    Access::Read => {}
+                }
+            }
+        }
+
+        // Find the dependencies between the passes
+        let mut dependencies = vec![Vec::new(); passes.len()];
+        for (index, (pass_name, accesses)) in passes.iter().enumerate() {
+            for (name, access) in accesses.iter() {
+                let writer = writers.get(name.as_str()).unwrap_or_else(|| {
+                    panic!(
+                        "{} is used by the pass {} but never written",
+                        name, pass_name
+                    )
+                });
+                let modifiers = modifiers.get(name.as_str()).cloned().unwrap_or_default();
+                match access {
+                    Access::Read => {
+                        dependencies[index].push(writer.0);
+                        dependencies[index].extend(modifiers);
+                    }
+                    Access::Modify => {
+                        dependencies[index].push(writer.0);
+                        dependencies[index].extend(modifiers.into_iter().filter(|i| *i < index));
+                    }
+                    Access::Write(..) => {}
+                }
+            }
+        }
+
+        // Order the passes such that all dependencies are executed first, otherwise keep the given order
+        let mut order = Vec::with_capacity(passes.len());
+        let mut done = vec![false; passes.len()];
+        while order.len() < passes.len() {
+            let next = (0..passes.len())
+                .find(|i| !done[*i] && dependencies[*i].iter().all(|d| done[*d]))
+                .unwrap_or_else(|| panic!("the dependencies between the passes are cyclic"));
+            done[next] = true;
+            order.push(next);
+        }
+
+        // Find the first and last use of each texture
+        let mut position = vec![0; passes.len()];
+        for (i, pass_index) in order.iter().enumerate() {
+            position[*pass_index] = i;
+        }
+        let mut lifetimes: Vec<(&str, usize, usize)> = Vec::new();
+        for (name, (writer, ..)) in writers.iter() {
+            let mut last = position[*writer];
+            for (index, (_, accesses)) in passes.iter().enumerate() {
+                if accesses.iter().any(|(n, _)| n == name) {
+                    last = last.max(position[index]);
+                }
+            }
+            lifetimes.push((name, position[*writer], last));
+        }
+        lifetimes.sort_by(|a, b| (a.1, a.0).cmp(&(b.1, b.0)));
+
+        // Assign the textures to slots, reusing a slot with the same format and size if it is not used anymore
+        let mut slots: Vec<TextureKey> = Vec::new();
+        let mut slot_last_use: Vec<usize> = Vec::new();
+        let mut assignments = HashMap::new();
+        for (name, first, last) in lifetimes {
+            let (_, format, size) = writers[name];
+            let (width, height) = size.resolve(viewport);
+            let key = (format, width, height);
+            let slot = if let Some(slot) =
+                (0..slots.len()).find(|s| slots[*s] == key && slot_last_use[*s] < first)
+            {
+                slot_last_use[slot] = last;
+                slot
+            } else {
+                slots.push(key);
+                slot_last_use.push(last);
+                slots.len() - 1
+            };
+            assignments.insert(name.to_string(), slot);
+        }
+        Self {
+            order,
+            slots,
+            assignments,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn color() -> Access {
+        Access::Write(FrameTextureFormat::Rgba8, FrameTextureSize::viewport())
+    }
+
+    fn pass(name: &str, accesses: &[(&str, Access)]) -> (String, Vec<(String, Access)>) {
+        (
+            name.to_string(),
+            accesses.iter().map(|(n, a)| (n.to_string(), *a)).collect(),
+        )
+    }
+
+    #[test]
+    fn test_order() {
+        let passes = [
+            pass("tone map", &[("hdr", Access::Read), ("ldr", color())]),
+            pass("transparent", &[("hdr", Access::Modify)]),
+            pass("opaque", &[("hdr", color()), ("shadow", Access::Read)]),
+            pass("shadow", &[("shadow", color())]),
+        ];
+        let schedule = Schedule::new(&passes, Viewport::new_at_origo(100, 100));
+        assert_eq!(schedule.order, vec![3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn test_aliasing() {
+        let passes = [
+            pass("a", &[("a", color())]),
+            pass("b", &[("a", Access::Read), ("b", color())]),
+            pass("c", &[("b", Access::Read), ("c", color())]),
+            pass(
+                "d",
+                &[
+                    ("c", Access::Read),
+                    (
+                        "d",
+                        Access::Write(
+                            FrameTextureFormat::Rgba8,
+                            FrameTextureSize::ViewportRelative(0.5),
+                        ),
+                    ),
+                ],
+            ),
+        ];
+        let schedule = Schedule::new(&passes, Viewport::new_at_origo(100, 100));
+        assert_eq!(schedule.slots.len(), 3);
+        assert_eq!(schedule.assignments["a"], schedule.assignments["c"]);
+        assert_ne!(schedule.assignments["a"], schedule.assignments["b"]);
+        assert_eq!(
+            schedule.slots[schedule.assignments["d"]],
+            (FrameTextureFormat::Rgba8, 50, 50)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_cycle() {
+        let passes = [
+            pass("a", &[("a", color()), ("b", Access::Read)]),
+            pass("b", &[("b", color()), ("a", Access::Read)]),
+        ];
+        Schedule::new(&passes, Viewport::new_at_origo(100, 100));
+    }
+}
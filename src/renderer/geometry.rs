@@ -12,6 +12,10 @@ mod instanced_mesh;
 #[doc(inline)]
 pub use instanced_mesh::*;
 
+mod instanced_skinned_mesh;
+#[doc(inline)]
+pub use instanced_skinned_mesh::*;
+
 mod sprites;
 #[doc(inline)]
 pub use sprites::*;
@@ -20,6 +24,14 @@ mod particles;
 #[doc(inline)]
 pub use particles::*;
 
+mod aabb;
+#[doc(inline)]
+pub use aabb::*;
+
+mod bvh;
+#[doc(inline)]
+pub use bvh::*;
+
 mod bounding_box;
 #[doc(inline)]
 pub use bounding_box::*;
@@ -28,14 +40,54 @@ mod line;
 #[doc(inline)]
 pub use line::*;
 
+mod polyline;
+#[doc(inline)]
+pub use polyline::*;
+
 mod rectangle;
 #[doc(inline)]
 pub use rectangle::*;
 
+mod shape2d;
+
 mod circle;
 #[doc(inline)]
 pub use circle::*;
 
+mod ellipse;
+#[doc(inline)]
+pub use ellipse::*;
+
+mod ring;
+#[doc(inline)]
+pub use ring::*;
+
+mod circular_arc;
+#[doc(inline)]
+pub use circular_arc::*;
+
+mod trail;
+#[doc(inline)]
+pub use trail::*;
+
+mod half_edge_mesh;
+#[doc(inline)]
+pub use half_edge_mesh::*;
+
+mod cpu_mesh;
+#[doc(inline)]
+pub use cpu_mesh::*;
+
+mod csg;
+#[doc(inline)]
+pub use csg::BooleanOp;
+
+mod marching_cubes;
+#[doc(inline)]
+pub use marching_cubes::*;
+
+mod procedural;
+
 use crate::core::*;
 use crate::renderer::*;
 
@@ -305,6 +357,86 @@ impl<T: Geometry> Geometry for std::sync::RwLock<T> {
     }
 }
 
+///
+/// Adds support for the clip planes set on the context (see [Context::set_clip_planes]) to the given vertex and fragment shader source.
+/// On desktop, the clipping is done using `gl_ClipDistance`. On WebGL2 and OpenGL ES where that is not available,
+/// the fragment shader main function is wrapped in a function which discards the fragments that are clipped away.
+/// Returns the modified vertex and fragment shader source together with the planes to clip against
+/// which should be passed to [draw_with_clip_planes] when drawing.
+///
+fn clip_shader_source(
+    context: &Context,
+    vertex_shader_source: String,
+    fragment_shader_source: String,
+) -> (String, String, Vec<Plane>) {
+    let planes = context.clip_planes().planes;
+    if planes.is_empty() {
+        return (vertex_shader_source, fragment_shader_source, planes);
+    }
+    if context.version().is_embedded {
+        (
+            format!(
+                "#define CLIP_PLANE_COUNT {}\n{}",
+                planes.len(),
+                vertex_shader_source
+            ),
+            format!(
+                "#define main material_main\n{1}\n#undef main\n
+                uniform vec4 clipPlanes[{0}];
+                in vec3 clipPosition;
+                void main()
+                {{
+                    for (int i = 0; i < {0}; i++) {{
+                        if (dot(clipPlanes[i], vec4(clipPosition, 1.0)) < 0.0) {{
+                            discard;
+                        }}
+                    }}
+                    material_main();
+                }}",
+                planes.len(),
+                fragment_shader_source
+            ),
+            planes,
+        )
+    } else {
+        (
+            format!(
+                "#define CLIP_PLANE_COUNT {}\n#define USE_CLIP_DISTANCE\n{}",
+                planes.len(),
+                vertex_shader_source
+            ),
+            fragment_shader_source,
+            planes,
+        )
+    }
+}
+
+///
+/// Sends the given clip planes to the program and enables the corresponding clip distances while calling the `draw` closure.
+///
+fn draw_with_clip_planes(
+    context: &Context,
+    program: &Program,
+    planes: &[Plane],
+    draw: impl FnOnce(),
+) {
+    if planes.is_empty() {
+        draw();
+        return;
+    }
+    program.use_uniform_array(
+        "clipPlanes",
+        &planes.iter().map(|p| p.to_vec4()).collect::<Vec<_>>(),
+    );
+    if context.version().is_embedded {
+        draw();
+    } else {
+        context.set_clip_distances(planes.len() as u32);
+        draw();
+        context.set_clip_distances(0);
+    }
+}
+
 struct BaseMesh {
     indices: Option<ElementBuffer>,
     positions: VertexBuffer,
@@ -351,6 +483,56 @@ impl BaseMesh {
         }
     }
 
+    pub fn vertex_count(&self) -> u32 {
+        self.positions.vertex_count()
+    }
+
+    pub fn triangle_count(&self) -> u32 {
+        if let Some(index_buffer) = &self.indices {
+            index_buffer.triangle_count() as u32
+        } else {
+            self.positions.vertex_count() / 3
+        }
+    }
+
+    pub fn read_back(&self) -> Result<CpuMesh, CoreError> {
+        Ok(CpuMesh {
+            positions: Positions::F32(self.positions.read()?),
+            indices: self
+                .indices
+                .as_ref()
+                .map(|index_buffer| index_buffer.read().map(Indices::U32))
+                .transpose()?
+                .unwrap_or(Indices::None),
+            normals: self
+                .normals
+                .as_ref()
+                .map(|buffer| buffer.read())
+                .transpose()?,
+            tangents: self
+                .tangents
+                .as_ref()
+                .map(|buffer| buffer.read())
+                .transpose()?,
+            // The uv coordinates are flipped when they are sent to the GPU
+            uvs: self
+                .uvs
+                .as_ref()
+                .map(|buffer| {
+                    buffer
+                        .read::<Vec2>()
+                        .map(|uvs| uvs.into_iter().map(|uv| vec2(uv.x, 1.0 - uv.y)).collect())
+                })
+                .transpose()?,
+            colors: self
+                .colors
+                .as_ref()
+                .map(|buffer| buffer.read())
+                .transpose()?,
+            ..Default::default()
+        })
+    }
+
     pub fn draw(
         &self,
         program: &Program,
@@ -370,6 +552,29 @@ impl BaseMesh {
         }
     }
 
+    pub fn draw_indirect(
+        &self,
+        program: &Program,
+        render_states: RenderStates,
+        viewport: Viewport,
+        attributes: FragmentAttributes,
+        indirect_buffer: &DrawIndirectBuffer,
+        index: usize,
+    ) {
+        self.use_attributes(program, attributes);
+        if let Some(index_buffer) = &self.indices {
+            program.draw_elements_indirect(
+                render_states,
+                viewport,
+                index_buffer,
+                indirect_buffer,
+                index,
+            )
+        } else {
+            program.draw_arrays_indirect(render_states, viewport, indirect_buffer, index)
+        }
+    }
+
     pub fn draw_instanced(
         &self,
         program: &Program,
@@ -434,7 +639,10 @@ impl BaseMesh {
         }
 
         if let Some(colors) = &self.colors {
-            program.use_vertex_attribute("color", colors);
+            // A program given by the user, see Mesh::render_indirect, does not necessarily use the vertex colors
+            if program.requires_attribute("color") {
+                program.use_vertex_attribute("color", colors);
+            }
         }
     }
 }
@@ -0,0 +1,124 @@
+use crate::core::*;
+
+///
+/// Additional functionality for an [AxisAlignedBoundingBox], mainly for computing bounds directly from positions or other bounds,
+/// for example for culling a group of meshes or building a bounding volume hierarchy.
+///
+pub trait AxisAlignedBoundingBoxExt: Sized {
+    ///
+    /// Constructs the smallest bounding box which contains all the given points or an empty bounding box if no points are given.
+    ///
+    fn from_points(points: &[Vec3]) -> Self;
+
+    ///
+    /// Returns the smallest bounding box which contains both this and the other bounding box.
+    ///
+    fn union(&self, other: &Self) -> Self;
+
+    ///
+    /// Returns this bounding box expanded by the given margin in all directions.
+    /// A negative margin shrinks the bounding box, but never beyond its center.
+    /// An empty or infinite bounding box is returned unchanged.
+    ///
+    fn expanded_by(&self, margin: f32) -> Self;
+
+    ///
+    /// Returns the smallest axis aligned bounding box which contains the eight corners of this bounding box transformed by the given transformation,
+    /// for example to get the world space bounds from bounds in the local space of an object.
+    /// An empty or infinite bounding box is returned unchanged.
+    ///
+    fn transformed(&self, transformation: &Mat4) -> Self;
+}
+
+impl AxisAlignedBoundingBoxExt for AxisAlignedBoundingBox {
+    fn from_points(points: &[Vec3]) -> Self {
+        if points.is_empty() {
+            Self::EMPTY
+        } else {
+            Self::new_with_positions(points)
+        }
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        let mut aabb = *self;
+        aabb.expand_with_aabb(other);
+        aabb
+    }
+
+    fn expanded_by(&self, margin: f32) -> Self {
+        if self.is_empty() || self.is_infinite() {
+            return *self;
+        }
+        let (min, max) = (self.min(), self.max());
+        let center = 0.5 * (min + max);
+        let expand = |min: f32, max: f32, center: f32| {
+            ((min - margin).min(center), (max + margin).max(center))
+        };
+        let (x0, x1) = expand(min.x, max.x, center.x);
+        let (y0, y1) = expand(min.y, max.y, center.y);
+        let (z0, z1) = expand(min.z, max.z, center.z);
+        Self::new_with_positions(&[vec3(x0, y0, z0), vec3(x1, y1, z1)])
+    }
+
+    fn transformed(&self, transformation: &Mat4) -> Self {
+        if self.is_empty() || self.is_infinite() {
+            return *self;
+        }
+        let (min, max) = (self.min(), self.max());
+        let corners = (0..8)
+            .map(|i| {
+                let corner = vec3(
+                    if i & 1 == 0 { min.x } else { max.x },
+                    if i & 2 == 0 { min.y } else { max.y },
+                    if i & 4 == 0 { min.z } else { max.z },
+                );
+                (transformation * corner.extend(1.0)).truncate()
+            })
+            .collect::<Vec<_>>();
+        Self::new_with_positions(&corners)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_aabb_helpers() {
+        let a = AxisAlignedBoundingBox::from_points(&[vec3(0.0, 1.0, 2.0), vec3(-1.0, 3.0, 0.0)]);
+        assert_eq!(a.min(), vec3(-1.0, 1.0, 0.0));
+        assert_eq!(a.max(), vec3(0.0, 3.0, 2.0));
+        assert!(AxisAlignedBoundingBox::from_points(&[]).is_empty());
+
+        let b = a.union(&AxisAlignedBoundingBox::from_points(&[vec3(4.0, 2.0, 1.0)]));
+        assert_eq!(b.min(), vec3(-1.0, 1.0, 0.0));
+        assert_eq!(b.max(), vec3(4.0, 3.0, 2.0));
+        assert_eq!(a.union(&AxisAlignedBoundingBox::EMPTY), a);
+
+        let c = a.expanded_by(0.5);
+        assert_eq!(c.min(), vec3(-1.5, 0.5, -0.5));
+        assert_eq!(c.max(), vec3(0.5, 3.5, 2.5));
+        let d = a.expanded_by(-5.0);
+        assert_eq!(d.min(), d.max());
+        assert!(AxisAlignedBoundingBox::EMPTY.expanded_by(1.0).is_empty());
+    }
+
+    #[test]
+    fn test_aabb_transformed() {
+        let aabb = AxisAlignedBoundingBox::from_points(&[vec3(0.0, 0.0, 0.0), vec3(1.0, 2.0, 3.0)]);
+        let transformed = aabb.transformed(
+            &(Mat4::from_translation(vec3(10.0, 0.0, 0.0))
+                * Mat4::from_nonuniform_scale(2.0, 1.0, 1.0)),
+        );
+        assert_eq!(transformed.min(), vec3(10.0, 0.0, 0.0));
+        assert_eq!(transformed.max(), vec3(12.0, 2.0, 3.0));
+
+        // A rotation of 90 degrees around the z-axis maps x to y and y to -x
+        let rotated = aabb.transformed(&Mat4::from_angle_z(degrees(90.0)));
+        assert!((rotated.min() - vec3(-2.0, 0.0, 0.0)).magnitude() < 1.0e-5);
+        assert!((rotated.max() - vec3(0.0, 1.0, 3.0)).magnitude() < 1.0e-5);
+        assert!(AxisAlignedBoundingBox::EMPTY
+            .transformed(&Mat4::from_scale(2.0))
+            .is_empty());
+    }
+}
@@ -72,6 +72,27 @@ impl BoundingBox {
         );
         Self { mesh, aabb }
     }
+
+    ///
+    /// Returns the number of vertices in the geometry of a single edge of the box, which is rendered as 12 instances.
+    ///
+    pub fn vertex_count(&self) -> u32 {
+        self.mesh.vertex_count()
+    }
+
+    ///
+    /// Returns the number of triangles in the geometry of a single edge of the box, which is rendered as 12 instances.
+    ///
+    pub fn triangle_count(&self) -> u32 {
+        self.mesh.triangle_count()
+    }
+
+    ///
+    /// Returns the number of instances, ie. edges, that is rendered.
+    ///
+    pub fn instance_count(&self) -> u32 {
+        self.mesh.instance_count()
+    }
 }
 
 impl<'a> IntoIterator for &'a BoundingBox {
@@ -0,0 +1,356 @@
+use crate::core::*;
+
+///
+/// A ray starting at `origin` and going in the `direction`, which does not need to be normalized.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    /// The start of the ray.
+    pub origin: Vec3,
+    /// The direction of the ray.
+    pub direction: Vec3,
+}
+
+impl Ray {
+    ///
+    /// Constructs a new ray.
+    ///
+    pub fn new(origin: Vec3, direction: Vec3) -> Self {
+        Self { origin, direction }
+    }
+
+    ///
+    /// Returns the position at the given distance along the ray, ie. `origin + distance * direction`.
+    ///
+    pub fn at(&self, distance: f32) -> Vec3 {
+        self.origin + distance * self.direction
+    }
+
+    ///
+    /// Returns this ray transformed by the given transformation.
+    /// The distance to a point along the ray is the same before and after the transformation.
+    ///
+    pub fn transform(&self, transformation: &Mat4) -> Self {
+        Self {
+            origin: (transformation * self.origin.extend(1.0)).truncate(),
+            direction: (transformation * self.direction.extend(0.0)).truncate(),
+        }
+    }
+}
+
+///
+/// The closest intersection between a [Ray] and a triangle mesh.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RayHit {
+    /// The distance along the ray in units of the ray direction, ie. the hit position is [Ray::at] this distance.
+    pub distance: f32,
+    /// The hit position.
+    pub position: Vec3,
+    /// The index of the hit triangle.
+    pub triangle: usize,
+    /// The barycentric coordinates of the hit position with respect to the second and third vertex of the triangle.
+    pub barycentric: Vec2,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct BvhNode {
+    min: Vec3,
+    max: Vec3,
+    // The index of the first triangle for a leaf or the index of the second child for an inner node, where the first child is the next node
+    index: u32,
+    // The number of triangles in a leaf or zero for an inner node
+    count: u32,
+}
+
+const MAX_LEAF_SIZE: usize = 4;
+
+///
+/// A bounding volume hierarchy over the triangles of a mesh, which makes it possible to find the intersection between a ray and the mesh
+/// in logarithmic instead of linear time in the number of triangles, for example for picking on the CPU.
+/// The hierarchy is built by recursively splitting the triangles at the median along the longest axis.
+///
+#[derive(Clone, Debug)]
+pub struct BvhMesh {
+    nodes: Vec<BvhNode>,
+    triangles: Vec<[Vec3; 3]>,
+    triangle_indices: Vec<u32>,
+}
+
+impl BvhMesh {
+    ///
+    /// Builds a bounding volume hierarchy over the triangles given by the positions and the indices, where each three consecutive indices define a triangle,
+    /// or each three consecutive positions if no indices are given.
+    ///
+    pub fn new(positions: &[Vec3], indices: Option<&[u32]>) -> Self {
+        let triangles = if let Some(indices) = indices {
+            indices
+                .chunks_exact(3)
+                .map(|t| {
+                    [
+                        positions[t[0] as usize],
+                        positions[t[1] as usize],
+                        positions[t[2] as usize],
+                    ]
+                })
+                .collect::<Vec<_>>()
+        } else {
+            positions
+                .chunks_exact(3)
+                .map(|t| [t[0], t[1], t[2]])
+                .collect::<Vec<_>>()
+        };
+        let mut bvh = Self {
+            nodes: Vec::with_capacity(2 * triangles.len() / MAX_LEAF_SIZE + 1),
+            triangle_indices: (0..triangles.len() as u32).collect(),
+            triangles,
+        };
+        let centroids = bvh
+            .triangles
+            .iter()
+            .map(|t| (t[0] + t[1] + t[2]) / 3.0)
+            .collect::<Vec<_>>();
+        bvh.build(&centroids, 0, bvh.triangles.len());
+        bvh
+    }
+
+    ///
+    /// Returns the number of triangles.
+    ///
+    pub fn triangle_count(&self) -> usize {
+        self.triangles.len()
+    }
+
+    ///
+    /// Returns the closest intersection between the ray and the triangles or `None` if the ray does not hit any triangle.
+    /// Both sides of the triangles are hit and only intersections in the direction of the ray, ie. with a positive distance, are returned.
+    ///
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<RayHit> {
+        if self.triangles.is_empty() {
+            return None;
+        }
+        let inverse_direction = vec3(
+            1.0 / ray.direction.x,
+            1.0 / ray.direction.y,
+            1.0 / ray.direction.z,
+        );
+        let mut closest: Option<RayHit> = None;
+        let mut stack = vec![0usize];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            let max_distance = closest.map(|hit| hit.distance).unwrap_or(f32::INFINITY);
+            if node.count > 0 {
+                for i in node.index..node.index + node.count {
+                    let triangle = self.triangle_indices[i as usize] as usize;
+                    if let Some((distance, barycentric)) =
+                        intersect_triangle(ray, &self.triangles[triangle])
+                    {
+                        if distance < max_distance
+                            && closest.map(|hit| distance < hit.distance).unwrap_or(true)
+                        {
+                            closest = Some(RayHit {
+                                distance,
+                                position: ray.at(distance),
+                                triangle,
+                                barycentric,
+                            });
+                        }
+                    }
+                }
+            } else {
+                // Visit the closest child first, so more of the other child can be skipped
+                let first = node_index + 1;
+                let second = node.index as usize;
+                let first_distance = self.intersect_node(first, ray, inverse_direction);
+                let second_distance = self.intersect_node(second, ray, inverse_direction);
+                let mut children = [(first, first_distance), (second, second_distance)];
+                if second_distance < first_distance {
+                    children.swap(0, 1);
+                }
+                for (child, distance) in children.into_iter().rev() {
+                    if distance < max_distance {
+                        stack.push(child);
+                    }
+                }
+            }
+        }
+        closest
+    }
+
+    fn intersect_node(&self, index: usize, ray: &Ray, inverse_direction: Vec3) -> f32 {
+        let node = &self.nodes[index];
+        let mut t_min = 0.0f32;
+        let mut t_max = f32::INFINITY;
+        for axis in 0..3 {
+            let t0 = (node.min[axis] - ray.origin[axis]) * inverse_direction[axis];
+            let t1 = (node.max[axis] - ray.origin[axis]) * inverse_direction[axis];
+            // NaN happens when the ray is parallel to and in the plane of a side, which is not a miss
+            if !t0.is_nan() && !t1.is_nan() {
+                t_min = t_min.max(t0.min(t1));
+                t_max = t_max.min(t0.max(t1));
+            }
+        }
+        if t_min <= t_max {
+            t_min
+        } else {
+            f32::INFINITY
+        }
+    }
+
+    fn build(&mut self, centroids: &[Vec3], start: usize, end: usize) -> usize {
+        let node_index = self.nodes.len();
+        let mut min = vec3(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = vec3(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        let mut centroid_min = min;
+        let mut centroid_max = max;
+        for i in self.triangle_indices[start..end].iter() {
+            for p in self.triangles[*i as usize].iter() {
+                min = vec3(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+                max = vec3(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+            }
+            let c = centroids[*i as usize];
+            centroid_min = vec3(
+                centroid_min.x.min(c.x),
+                centroid_min.y.min(c.y),
+                centroid_min.z.min(c.z),
+            );
+            centroid_max = vec3(
+                centroid_max.x.max(c.x),
+                centroid_max.y.max(c.y),
+                centroid_max.z.max(c.z),
+            );
+        }
+        self.nodes.push(BvhNode {
+            min,
+            max,
+            index: start as u32,
+            count: (end - start) as u32,
+        });
+        if end - start <= MAX_LEAF_SIZE {
+            return node_index;
+        }
+
+        let extent = centroid_max - centroid_min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        let middle = (start + end) / 2;
+        self.triangle_indices[start..end].select_nth_unstable_by(middle - start, |a, b| {
+            centroids[*a as usize][axis]
+                .partial_cmp(&centroids[*b as usize][axis])
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.build(centroids, start, middle);
+        let second = self.build(centroids, middle, end);
+        self.nodes[node_index].index = second as u32;
+        self.nodes[node_index].count = 0;
+        node_index
+    }
+}
+
+///
+/// The Möller-Trumbore ray triangle intersection, which returns the distance along the ray and the barycentric coordinates of the intersection.
+///
+fn intersect_triangle(ray: &Ray, triangle: &[Vec3; 3]) -> Option<(f32, Vec2)> {
+    let edge1 = triangle[1] - triangle[0];
+    let edge2 = triangle[2] - triangle[0];
+    let p = ray.direction.cross(edge2);
+    let determinant = edge1.dot(p);
+    if determinant.abs() < f32::EPSILON * edge1.magnitude() * edge2.magnitude() {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+    let s = ray.origin - triangle[0];
+    let u = s.dot(p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(edge1);
+    let v = ray.direction.dot(q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = edge2.dot(q) * inverse_determinant;
+    if distance > 0.0 {
+        Some((distance, vec2(u, v)))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn sphere() -> (Vec<Vec3>, Vec<u32>) {
+        let mesh = CpuMesh::sphere(32);
+        let indices = match &mesh.indices {
+            Indices::U8(ind) => ind.iter().map(|i| *i as u32).collect(),
+            Indices::U16(ind) => ind.iter().map(|i| *i as u32).collect(),
+            Indices::U32(ind) => ind.clone(),
+            Indices::None => (0..mesh.positions.len() as u32).collect(),
+        };
+        (mesh.positions.to_f32(), indices)
+    }
+
+    fn brute_force(positions: &[Vec3], indices: &[u32], ray: &Ray) -> Option<f32> {
+        indices
+            .chunks_exact(3)
+            .filter_map(|t| {
+                intersect_triangle(
+                    ray,
+                    &[
+                        positions[t[0] as usize],
+                        positions[t[1] as usize],
+                        positions[t[2] as usize],
+                    ],
+                )
+            })
+            .map(|(distance, _)| distance)
+            .min_by(|a, b| a.partial_cmp(b).unwrap())
+    }
+
+    #[test]
+    fn test_same_as_brute_force() {
+        let (positions, indices) = sphere();
+        let bvh = BvhMesh::new(&positions, Some(&indices));
+        assert_eq!(bvh.triangle_count(), indices.len() / 3);
+        for i in 0..200 {
+            let angle = i as f32 * 0.7;
+            let origin = vec3(
+                3.0 * angle.cos(),
+                (i as f32 * 0.37).sin() * 2.0,
+                3.0 * angle.sin(),
+            );
+            let target = vec3((i as f32 * 1.3).sin(), (i as f32 * 2.1).cos(), 0.0) * 1.2;
+            let ray = Ray::new(origin, target - origin);
+            let expected = brute_force(&positions, &indices, &ray);
+            let hit = bvh.intersect_ray(&ray);
+            assert_eq!(hit.is_some(), expected.is_some());
+            if let (Some(hit), Some(expected)) = (hit, expected) {
+                assert!((hit.distance - expected).abs() < 1.0e-5);
+                assert!((hit.position.magnitude() - 1.0).abs() < 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn test_ray_from_inside_and_behind() {
+        let (positions, indices) = sphere();
+        let bvh = BvhMesh::new(&positions, Some(&indices));
+        let hit = bvh
+            .intersect_ray(&Ray::new(Vec3::zero(), vec3(0.0, 0.0, 2.0)))
+            .unwrap();
+        assert!((hit.distance - 0.5).abs() < 0.01);
+        assert!(bvh
+            .intersect_ray(&Ray::new(vec3(0.0, 0.0, 2.0), vec3(0.0, 0.0, 1.0)))
+            .is_none());
+        assert!(BvhMesh::new(&[], None)
+            .intersect_ray(&Ray::new(Vec3::zero(), vec3(1.0, 0.0, 0.0)))
+            .is_none());
+    }
+}
@@ -1,10 +1,12 @@
+use super::shape2d::Shape2D;
 use crate::renderer::*;
 
 ///
 /// A circle 2D geometry which can be rendered using the [camera2d] camera.
+/// The circle is cut out of a quad in the fragment shader, so the edge is smooth and anti-aliased no matter the size of the circle.
 ///
 pub struct Circle {
-    mesh: Mesh,
+    shape: Shape2D,
     radius: f32,
     center: PhysicalPoint,
 }
@@ -14,9 +16,8 @@ impl Circle {
     /// Constructs a new circle geometry.
     ///
     pub fn new(context: &Context, center: impl Into<PhysicalPoint>, radius: f32) -> Self {
-        let mesh = CpuMesh::circle(64);
         let mut circle = Self {
-            mesh: Mesh::new(context, &mesh),
+            shape: Shape2D::new(context),
             center: center.into(),
             radius,
         };
@@ -46,13 +47,50 @@ impl Circle {
         self.center
     }
 
+    ///
+    /// Set a border with the given width in physical pixels and color along the inside of the edge of the circle.
+    /// The inside of the border is colored by the material. A width of zero removes the border.
+    ///
+    pub fn set_border(&mut self, width: f32, color: Color) {
+        self.shape.set_border(width, color);
+    }
+
+    /// Get the width and color of the border if any.
+    pub fn border(&self) -> Option<(f32, Color)> {
+        self.shape.border()
+    }
+
+    /// Get the number of vertices in the circle.
+    pub fn vertex_count(&self) -> u32 {
+        self.shape.vertex_count()
+    }
+
+    /// Get the number of triangles in the circle.
+    pub fn triangle_count(&self) -> u32 {
+        self.shape.triangle_count()
+    }
+
     fn update(&mut self) {
-        self.mesh.set_transformation_2d(
-            Mat3::from_translation(self.center.into()) * Mat3::from_scale(self.radius),
+        self.shape.update(
+            self.center.into(),
+            radians(0.0),
+            2.0 * self.radius,
+            2.0 * self.radius,
         );
     }
+
+    fn use_uniforms(&self, program: &Program) {
+        program.use_uniform("circleRadius", self.radius);
+    }
 }
 
+const CIRCLE_DISTANCE: &str = "
+    uniform float circleRadius;
+    float shape_distance(vec2 p)
+    {
+        return length(p) - circleRadius;
+    }";
+
 impl Geometry for Circle {
     fn render_with_material(
         &self,
@@ -60,7 +98,10 @@ impl Geometry for Circle {
         camera: &Camera,
         lights: &[&dyn Light],
     ) {
-        self.mesh.render_with_material(material, camera, lights)
+        self.shape
+            .render_with_material(material, camera, lights, CIRCLE_DISTANCE, |program| {
+                self.use_uniforms(program)
+            })
     }
 
     fn render_with_post_material(
@@ -71,8 +112,15 @@ impl Geometry for Circle {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        self.mesh
-            .render_with_post_material(material, camera, lights, color_texture, depth_texture)
+        self.shape.render_with_post_material(
+            material,
+            camera,
+            lights,
+            color_texture,
+            depth_texture,
+            CIRCLE_DISTANCE,
+            |program| self.use_uniforms(program),
+        )
     }
 
     ///
@@ -0,0 +1,271 @@
+use super::shape2d::Shape2D;
+use crate::renderer::*;
+use std::f32::consts::PI;
+
+///
+/// An arc 2D geometry, ie. a part of a [Ring] between two angles, for example for drawing gauges, which can be rendered using the [camera2d] camera.
+/// The arc is cut out of a quad in the fragment shader, so the edges are smooth and anti-aliased no matter the size of the arc.
+///
+pub struct CircularArc {
+    shape: Shape2D,
+    radius: f32,
+    thickness: f32,
+    start_angle: Radians,
+    end_angle: Radians,
+    center: PhysicalPoint,
+}
+
+impl CircularArc {
+    ///
+    /// Constructs a new arc geometry with the given outer radius and thickness going from the start angle to the end angle.
+    /// The angles are measured counterclockwise from the positive x-axis and the arc is a full ring if the angles are a full revolution or more apart.
+    ///
+    pub fn new(
+        context: &Context,
+        center: impl Into<PhysicalPoint>,
+        radius: f32,
+        start_angle: impl Into<Radians>,
+        end_angle: impl Into<Radians>,
+        thickness: f32,
+    ) -> Self {
+        let mut arc = Self {
+            shape: Shape2D::new(context),
+            radius,
+            thickness,
+            start_angle: start_angle.into(),
+            end_angle: end_angle.into(),
+            center: center.into(),
+        };
+        arc.update();
+        arc
+    }
+
+    /// Set the outer radius of the arc.
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius;
+        self.update();
+    }
+
+    /// Get the outer radius of the arc.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Set the thickness of the arc, ie. the distance between the outer and the inner edge.
+    pub fn set_thickness(&mut self, thickness: f32) {
+        self.thickness = thickness;
+    }
+
+    /// Get the thickness of the arc, ie. the distance between the outer and the inner edge.
+    pub fn thickness(&self) -> f32 {
+        self.thickness
+    }
+
+    /// Set the start and end angle of the arc, see [CircularArc::new].
+    pub fn set_angles(&mut self, start_angle: impl Into<Radians>, end_angle: impl Into<Radians>) {
+        self.start_angle = start_angle.into();
+        self.end_angle = end_angle.into();
+    }
+
+    /// Get the start and end angle of the arc.
+    pub fn angles(&self) -> (Radians, Radians) {
+        (self.start_angle, self.end_angle)
+    }
+
+    /// Set the center of the arc, ie. the center of the circle the arc is a part of.
+    pub fn set_center(&mut self, center: impl Into<PhysicalPoint>) {
+        self.center = center.into();
+        self.update();
+    }
+
+    /// Get the center of the arc, ie. the center of the circle the arc is a part of.
+    pub fn center(&self) -> PhysicalPoint {
+        self.center
+    }
+
+    ///
+    /// Set a border with the given width in physical pixels and color along the inside of the edges of the arc.
+    /// The inside of the border is colored by the material. A width of zero removes the border.
+    ///
+    pub fn set_border(&mut self, width: f32, color: Color) {
+        self.shape.set_border(width, color);
+    }
+
+    /// Get the width and color of the border if any.
+    pub fn border(&self) -> Option<(f32, Color)> {
+        self.shape.border()
+    }
+
+    /// Get the number of vertices in the arc.
+    pub fn vertex_count(&self) -> u32 {
+        self.shape.vertex_count()
+    }
+
+    /// Get the number of triangles in the arc.
+    pub fn triangle_count(&self) -> u32 {
+        self.shape.triangle_count()
+    }
+
+    fn update(&mut self) {
+        self.shape.update(
+            self.center.into(),
+            radians(0.0),
+            2.0 * self.radius,
+            2.0 * self.radius,
+        );
+    }
+
+    fn use_uniforms(&self, program: &Program) {
+        let middle = 0.5 * (self.start_angle.0 + self.end_angle.0);
+        let half_sweep = (0.5 * (self.end_angle.0 - self.start_angle.0).abs()).min(PI);
+        program.use_uniform("arcRadius", self.radius);
+        program.use_uniform("arcThickness", self.thickness.min(self.radius));
+        program.use_uniform("arcMiddle", vec2(middle.cos(), middle.sin()));
+        program.use_uniform("arcHalfSweep", vec2(half_sweep.sin(), half_sweep.cos()));
+    }
+}
+
+const ARC_DISTANCE: &str = "
+    uniform float arcRadius;
+    uniform float arcThickness;
+    uniform vec2 arcMiddle;
+    uniform vec2 arcHalfSweep;
+    float shape_distance(vec2 p)
+    {
+        float ring = abs(length(p) - arcRadius + 0.5 * arcThickness) - 0.5 * arcThickness;
+
+        // The distance to a pie slice which is symmetric around the y-axis after rotating the middle of the arc to the y-axis
+        vec2 q = vec2(abs(dot(p, vec2(arcMiddle.y, -arcMiddle.x))), dot(p, arcMiddle));
+        float m = length(q - arcHalfSweep * clamp(dot(q, arcHalfSweep), 0.0, arcRadius));
+        float pie = max(length(q) - arcRadius, m * sign(arcHalfSweep.y * q.x - arcHalfSweep.x * q.y));
+        return max(ring, pie);
+    }";
+
+impl Geometry for CircularArc {
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        self.shape
+            .render_with_material(material, camera, lights, ARC_DISTANCE, |program| {
+                self.use_uniforms(program)
+            })
+    }
+
+    fn render_with_post_material(
+        &self,
+        material: &dyn PostMaterial,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        self.shape.render_with_post_material(
+            material,
+            camera,
+            lights,
+            color_texture,
+            depth_texture,
+            ARC_DISTANCE,
+            |program| self.use_uniforms(program),
+        )
+    }
+
+    ///
+    /// Returns the [AxisAlignedBoundingBox] for this geometry in the global coordinate system,
+    /// which only covers the part of the circle the arc sweeps.
+    ///
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let center: Vec2 = self.center.into();
+        AxisAlignedBoundingBox::new_with_positions(
+            &arc_extremes(
+                self.radius,
+                self.radius - self.thickness.min(self.radius),
+                self.start_angle.0,
+                self.end_angle.0,
+            )
+            .into_iter()
+            .map(|p| (center + p).extend(0.0))
+            .collect::<Vec<_>>(),
+        )
+    }
+}
+
+impl<'a> IntoIterator for &'a CircularArc {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+///
+/// Returns the points, relative to the center, which together span the bounding box of the arc,
+/// that is the end points on the inner and outer edge and the points on the outer edge where the arc crosses an axis.
+///
+fn arc_extremes(radius: f32, inner_radius: f32, start_angle: f32, end_angle: f32) -> Vec<Vec2> {
+    let from = start_angle.min(end_angle);
+    let to = from + (end_angle - start_angle).abs().min(2.0 * PI);
+    let point = |r: f32, angle: f32| vec2(r * angle.cos(), r * angle.sin());
+    let mut points = vec![
+        point(radius, from),
+        point(radius, to),
+        point(inner_radius, from),
+        point(inner_radius, to),
+    ];
+    let quarter = 0.5 * PI;
+    let mut k = (from / quarter).ceil();
+    while k * quarter <= to {
+        points.push(point(radius, k * quarter));
+        k += 1.0;
+    }
+    points
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bounds(points: Vec<Vec2>) -> (Vec2, Vec2) {
+        points.iter().fold(
+            (vec2(f32::MAX, f32::MAX), vec2(f32::MIN, f32::MIN)),
+            |(min, max), p| {
+                (
+                    vec2(min.x.min(p.x), min.y.min(p.y)),
+                    vec2(max.x.max(p.x), max.y.max(p.y)),
+                )
+            },
+        )
+    }
+
+    fn assert_close(a: Vec2, b: Vec2) {
+        assert!(a.distance(b) < 1.0e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_arc_extremes() {
+        // A quarter in the first quadrant
+        let (min, max) = bounds(arc_extremes(10.0, 8.0, 0.0, 0.5 * PI));
+        assert_close(min, vec2(0.0, 0.0));
+        assert_close(max, vec2(10.0, 10.0));
+
+        // From 45 to 135 degrees crosses the positive y-axis, the end points are reversed
+        let (min, max) = bounds(arc_extremes(10.0, 8.0, 0.75 * PI, 0.25 * PI));
+        let d = 0.5f32.sqrt();
+        assert_close(min, vec2(-10.0 * d, 8.0 * d));
+        assert_close(max, vec2(10.0 * d, 10.0));
+
+        // A gauge from -135 to 135 degrees which crosses all axes except the negative x-axis
+        let (min, max) = bounds(arc_extremes(10.0, 0.0, -0.75 * PI, 0.75 * PI));
+        assert_close(min, vec2(-10.0 * d, -10.0));
+        assert_close(max, vec2(10.0, 10.0));
+
+        // More than a full revolution
+        let (min, max) = bounds(arc_extremes(10.0, 5.0, 1.0, 10.0));
+        assert_close(min, vec2(-10.0, -10.0));
+        assert_close(max, vec2(10.0, 10.0));
+    }
+}
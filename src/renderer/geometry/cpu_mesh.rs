@@ -0,0 +1,127 @@
+use super::{csg, procedural};
+use crate::renderer::*;
+
+///
+/// Additional functionality for a [CpuMesh], mainly procedural generation and modification of meshes on the CPU.
+///
+pub trait CpuMeshExt: Sized {
+    ///
+    /// Computes the union, intersection or difference (see [BooleanOp]) of the two given meshes, also known as constructive solid geometry (CSG).
+    /// The meshes must be closed, ie. the boundary of a solid, with the triangles oriented counter clockwise when seen from the outside.
+    /// The resulting mesh has flat normals and no other attributes.
+    ///
+    /// Returns an error with a description of the problem if one of the meshes contains degenerate triangles
+    /// or if the two meshes have overlapping co-planar faces, in which case it is ambiguous which of the faces should be kept.
+    /// Moving one of the meshes slightly is usually enough to avoid the latter.
+    ///
+    fn boolean(a: &Self, b: &Self, op: BooleanOp) -> Result<Self, RendererError>;
+
+    ///
+    /// Creates a surface of revolution, for example a vase or a column, by rotating the given profile around the y-axis.
+    /// The profile is given as (radius, height) points and is rotated the given angle, which is clamped to the range from zero to a full revolution, in the given number of segments.
+    /// The normals are computed from the profile and the uv coordinates are the angle divided by 2π and the height normalized to the range of heights in the profile.
+    /// If the first and last point of the profile are the same, for example for a torus, the profile is considered closed and the normals are continuous across the first and last point.
+    /// Triangles where the profile touches the axis, ie. where the radius is zero, are left out.
+    ///
+    /// # Panic
+    /// Will panic if the profile contains less than two points or if the number of segments is zero.
+    ///
+    fn lathe(profile: &[(f32, f32)], segments: u32, angle: Radians) -> Self;
+
+    ///
+    /// Creates a mesh, for example a tube, a pipe or a molding, by extruding the given 2D cross section along the given path.
+    /// At each point on the path, the cross section is placed perpendicular to the tangent of the path with the x and y axes of the cross section
+    /// following a parallel transport frame, which avoids the cross section twisting around the path.
+    /// The normals are computed from the cross section, smooth at corners less sharp than roughly 30 degrees and flat otherwise.
+    /// The u coordinate is the distance around the cross section and the v coordinate the distance along the path, both normalized.
+    ///
+    /// If `closed` is true, both ends are capped with the triangulated cross section, which must then be a simple polygon, so that the mesh is closed.
+    /// If the first and last point of the path are the same, the path is considered a loop, no caps are added and the twist of the frames is distributed along the path so the ends meet.
+    ///
+    /// # Panic
+    /// Will panic if the cross section contains less than three points or if the path contains less than two points.
+    ///
+    fn extrude_along_path(cross_section: &[Vec2], path: &[Vec3], closed: bool) -> Self;
+
+    ///
+    /// Returns this mesh transformed by the given transformation, which is the same as `transform` but consumes the mesh, so it can be used for method chaining.
+    /// The positions are transformed by the transformation, the normals by the inverse transpose of the upper left 3x3 part of the transformation
+    /// and the tangents by the upper left 3x3 part, so both stay correct for non-uniform scaling, and the normals and tangents are normalized afterwards.
+    /// The handedness of the tangents is flipped if the transformation mirrors the mesh.
+    ///
+    /// # Panic
+    /// Will panic if the transformation is not invertible.
+    ///
+    fn transformed(self, transformation: &Mat4) -> Self;
+}
+
+impl CpuMeshExt for CpuMesh {
+    fn boolean(a: &Self, b: &Self, op: BooleanOp) -> Result<Self, RendererError> {
+        csg::boolean(a, b, op)
+    }
+
+    fn lathe(profile: &[(f32, f32)], segments: u32, angle: Radians) -> Self {
+        procedural::lathe(profile, segments, angle)
+    }
+
+    fn extrude_along_path(cross_section: &[Vec2], path: &[Vec3], closed: bool) -> Self {
+        procedural::extrude_along_path(cross_section, path, closed)
+    }
+
+    fn transformed(mut self, transformation: &Mat4) -> Self {
+        let linear = Mat3::from_cols(
+            transformation.x.truncate(),
+            transformation.y.truncate(),
+            transformation.z.truncate(),
+        );
+        let normal_transformation = linear
+            .invert()
+            .expect("the transformation of a mesh must be invertible")
+            .transpose();
+        self.positions = Positions::F32(
+            self.positions
+                .to_f32()
+                .into_iter()
+                .map(|p| (transformation * p.extend(1.0)).truncate())
+                .collect(),
+        );
+        if let Some(normals) = self.normals.as_mut() {
+            for normal in normals.iter_mut() {
+                *normal = (normal_transformation * *normal).normalize();
+            }
+        }
+        if let Some(tangents) = self.tangents.as_mut() {
+            let handedness = linear.determinant().signum();
+            for tangent in tangents.iter_mut() {
+                *tangent = (linear * tangent.truncate())
+                    .normalize()
+                    .extend(tangent.w * handedness);
+            }
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transformed_normals_with_non_uniform_scale() {
+        let mut mesh = CpuMesh::sphere(16);
+        // The normals of a unit sphere are the positions
+        mesh.normals = Some(mesh.positions.to_f32());
+        let transformation = Mat4::from_translation(vec3(1.0, 2.0, 3.0))
+            * Mat4::from_nonuniform_scale(4.0, 1.0, 0.5);
+        let mesh = mesh.transformed(&transformation);
+        let positions = mesh.positions.to_f32();
+        let normals = mesh.normals.as_ref().unwrap();
+
+        // The transformed sphere is an ellipsoid whose gradient gives the exact normal
+        for (p, n) in positions.iter().zip(normals.iter()) {
+            let q = p - vec3(1.0, 2.0, 3.0);
+            let exact = vec3(q.x / 16.0, q.y, q.z / 0.25).normalize();
+            assert!((n - exact).magnitude() < 1.0e-4);
+        }
+    }
+}
@@ -0,0 +1,466 @@
+use crate::renderer::*;
+
+///
+/// A boolean operation between two solids, see [CpuMeshExt::boolean].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BooleanOp {
+    /// The volume inside either of the two solids.
+    Union,
+    /// The volume inside both of the two solids.
+    Intersection,
+    /// The volume inside the first solid but not inside the second.
+    Difference,
+}
+
+const EPSILON: f32 = 1.0e-5;
+
+///
+/// Computes the given boolean operation between the two closed meshes using a binary space partitioning (BSP) tree.
+///
+pub(super) fn boolean(a: &CpuMesh, b: &CpuMesh, op: BooleanOp) -> Result<CpuMesh, RendererError> {
+    let a_polygons = polygons(a, "first")?;
+    let b_polygons = polygons(b, "second")?;
+    let mut a = Node::new(a_polygons.clone());
+    let mut b = Node::new(b_polygons.clone());
+    if a.has_coplanar_overlap(b_polygons) || b.has_coplanar_overlap(a_polygons) {
+        return Err(RendererError::MeshBoolean(
+            "the two meshes have overlapping co-planar faces".to_string(),
+        ));
+    }
+
+    match op {
+        BooleanOp::Union => {
+            a.clip_to(&b);
+            b.clip_to(&a);
+            b.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.build(b.all_polygons());
+        }
+        BooleanOp::Intersection => {
+            a.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.clip_to(&b);
+            b.clip_to(&a);
+            a.build(b.all_polygons());
+            a.invert();
+        }
+        BooleanOp::Difference => {
+            a.invert();
+            a.clip_to(&b);
+            b.clip_to(&a);
+            b.invert();
+            b.clip_to(&a);
+            b.invert();
+            a.build(b.all_polygons());
+            a.invert();
+        }
+    }
+
+    let mut positions = Vec::new();
+    let mut normals = Vec::new();
+    for polygon in a.all_polygons() {
+        for i in 1..polygon.vertices.len() - 1 {
+            positions.extend([
+                polygon.vertices[0],
+                polygon.vertices[i],
+                polygon.vertices[i + 1],
+            ]);
+            normals.extend([polygon.plane.normal; 3]);
+        }
+    }
+    Ok(CpuMesh {
+        indices: Indices::U32((0..positions.len() as u32).collect()),
+        positions: Positions::F32(positions),
+        normals: Some(normals),
+        ..Default::default()
+    })
+}
+
+fn polygons(mesh: &CpuMesh, name: &str) -> Result<Vec<Polygon>, RendererError> {
+    let positions = mesh.positions.to_f32();
+    let indices = match &mesh.indices {
+        Indices::U8(ind) => ind.iter().map(|i| *i as usize).collect::<Vec<_>>(),
+        Indices::U16(ind) => ind.iter().map(|i| *i as usize).collect::<Vec<_>>(),
+        Indices::U32(ind) => ind.iter().map(|i| *i as usize).collect::<Vec<_>>(),
+        Indices::None => (0..positions.len()).collect::<Vec<_>>(),
+    };
+    indices
+        .chunks_exact(3)
+        .enumerate()
+        .map(|(triangle, face)| {
+            let vertices = face
+                .iter()
+                .map(|i| {
+                    positions.get(*i).copied().ok_or_else(|| {
+                        RendererError::MeshBoolean(format!(
+                            "triangle {} of the {} mesh refers to vertex {} which does not exist",
+                            triangle, name, i
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let normal = (vertices[1] - vertices[0]).cross(vertices[2] - vertices[0]);
+            if normal.magnitude() < EPSILON * EPSILON {
+                return Err(RendererError::MeshBoolean(format!(
+                    "triangle {} of the {} mesh is degenerate",
+                    triangle, name
+                )));
+            }
+            Ok(Polygon {
+                plane: Plane::from_point_normal(vertices[0], normal),
+                vertices,
+            })
+        })
+        .collect()
+}
+
+///
+/// A convex polygon and the plane it lies in.
+///
+#[derive(Clone, Debug)]
+struct Polygon {
+    vertices: Vec<Vec3>,
+    plane: Plane,
+}
+
+impl Polygon {
+    fn flip(&mut self) {
+        self.vertices.reverse();
+        self.plane = self.plane.flipped();
+    }
+
+    ///
+    /// Returns whether or not this polygon and the other polygon, which are assumed to be co-planar, overlap in an area larger than zero.
+    ///
+    fn overlaps(&self, other: &Polygon) -> bool {
+        let normal = self.plane.normal;
+        let u = if normal.x.abs() < 0.9 {
+            normal.cross(vec3(1.0, 0.0, 0.0))
+        } else {
+            normal.cross(vec3(0.0, 1.0, 0.0))
+        }
+        .normalize();
+        let v = normal.cross(u);
+        let project = |polygon: &Polygon| {
+            polygon
+                .vertices
+                .iter()
+                .map(|p| vec2(p.dot(u), p.dot(v)))
+                .collect::<Vec<_>>()
+        };
+        let a = project(self);
+        let b = project(other);
+        // Separating axis test using the edge normals of both polygons
+        for polygon in [&a, &b] {
+            for i in 0..polygon.len() {
+                let edge = polygon[(i + 1) % polygon.len()] - polygon[i];
+                let axis = vec2(-edge.y, edge.x);
+                let range = |points: &[Vec2]| {
+                    points
+                        .iter()
+                        .map(|p| p.dot(axis))
+                        .fold((f32::MAX, f32::MIN), |(min, max), d| {
+                            (min.min(d), max.max(d))
+                        })
+                };
+                let (a_min, a_max) = range(&a);
+                let (b_min, b_max) = range(&b);
+                let tolerance = EPSILON * axis.magnitude();
+                if a_max <= b_min + tolerance || b_max <= a_min + tolerance {
+                    return false;
+                }
+            }
+        }
+        true
+    }
+}
+
+#[derive(Default)]
+struct Split {
+    coplanar_front: Vec<Polygon>,
+    coplanar_back: Vec<Polygon>,
+    front: Vec<Polygon>,
+    back: Vec<Polygon>,
+}
+
+///
+/// Splits the polygon by the given plane and adds the resulting polygons to the relevant lists.
+///
+fn split_polygon(plane: &Plane, polygon: Polygon, split: &mut Split) {
+    const COPLANAR: u8 = 0;
+    const FRONT: u8 = 1;
+    const BACK: u8 = 2;
+    const SPANNING: u8 = 3;
+
+    let mut polygon_type = COPLANAR;
+    let types = polygon
+        .vertices
+        .iter()
+        .map(|v| {
+            let t = plane.signed_distance(*v);
+            let vertex_type = if t < -EPSILON {
+                BACK
+            } else if t > EPSILON {
+                FRONT
+            } else {
+                COPLANAR
+            };
+            polygon_type |= vertex_type;
+            vertex_type
+        })
+        .collect::<Vec<_>>();
+
+    match polygon_type {
+        COPLANAR => {
+            if plane.normal.dot(polygon.plane.normal) > 0.0 {
+                split.coplanar_front.push(polygon);
+            } else {
+                split.coplanar_back.push(polygon);
+            }
+        }
+        FRONT => split.front.push(polygon),
+        BACK => split.back.push(polygon),
+        _ => {
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+            let count = polygon.vertices.len();
+            for i in 0..count {
+                let j = (i + 1) % count;
+                let (ti, tj) = (types[i], types[j]);
+                let (vi, vj) = (polygon.vertices[i], polygon.vertices[j]);
+                if ti != BACK {
+                    front.push(vi);
+                }
+                if ti != FRONT {
+                    back.push(vi);
+                }
+                if ti | tj == SPANNING {
+                    let t = -plane.signed_distance(vi) / plane.normal.dot(vj - vi);
+                    let v = vi + t * (vj - vi);
+                    front.push(v);
+                    back.push(v);
+                }
+            }
+            if front.len() >= 3 {
+                split.front.push(Polygon {
+                    vertices: front,
+                    plane: polygon.plane,
+                });
+            }
+            if back.len() >= 3 {
+                split.back.push(Polygon {
+                    vertices: back,
+                    plane: polygon.plane,
+                });
+            }
+        }
+    }
+}
+
+///
+/// A node in a BSP tree. The polygons in the node are co-planar with the plane of the node,
+/// the polygons in front of the plane are in the front sub-tree and the polygons behind the plane are in the back sub-tree.
+///
+#[derive(Default)]
+struct Node {
+    plane: Option<Plane>,
+    front: Option<Box<Node>>,
+    back: Option<Box<Node>>,
+    polygons: Vec<Polygon>,
+}
+
+impl Node {
+    fn new(polygons: Vec<Polygon>) -> Self {
+        let mut node = Self::default();
+        node.build(polygons);
+        node
+    }
+
+    fn build(&mut self, polygons: Vec<Polygon>) {
+        if polygons.is_empty() {
+            return;
+        }
+        let plane = *self.plane.get_or_insert(polygons[0].plane);
+        let mut split = Split::default();
+        for polygon in polygons {
+            split_polygon(&plane, polygon, &mut split);
+        }
+        self.polygons.extend(split.coplanar_front);
+        self.polygons.extend(split.coplanar_back);
+        if !split.front.is_empty() {
+            self.front
+                .get_or_insert_with(Default::default)
+                .build(split.front);
+        }
+        if !split.back.is_empty() {
+            self.back
+                .get_or_insert_with(Default::default)
+                .build(split.back);
+        }
+    }
+
+    ///
+    /// Converts solid space to empty space and empty space to solid space.
+    ///
+    fn invert(&mut self) {
+        for polygon in self.polygons.iter_mut() {
+            polygon.flip();
+        }
+        if let Some(plane) = self.plane.as_mut() {
+            *plane = plane.flipped();
+        }
+        if let Some(front) = self.front.as_mut() {
+            front.invert();
+        }
+        if let Some(back) = self.back.as_mut() {
+            back.invert();
+        }
+        std::mem::swap(&mut self.front, &mut self.back);
+    }
+
+    ///
+    /// Removes the parts of the given polygons that are inside the solid represented by this tree.
+    ///
+    fn clip_polygons(&self, polygons: Vec<Polygon>) -> Vec<Polygon> {
+        let plane = match self.plane {
+            Some(plane) => plane,
+            None => return polygons,
+        };
+        let mut split = Split::default();
+        for polygon in polygons {
+            split_polygon(&plane, polygon, &mut split);
+        }
+        let mut front = split.front;
+        front.extend(split.coplanar_front);
+        let mut back = split.back;
+        back.extend(split.coplanar_back);
+        let mut result = match &self.front {
+            Some(node) => node.clip_polygons(front),
+            None => front,
+        };
+        if let Some(node) = &self.back {
+            result.extend(node.clip_polygons(back));
+        }
+        result
+    }
+
+    ///
+    /// Removes the parts of the polygons in this tree that are inside the solid represented by the other tree.
+    ///
+    fn clip_to(&mut self, other: &Node) {
+        self.polygons = other.clip_polygons(std::mem::take(&mut self.polygons));
+        if let Some(front) = self.front.as_mut() {
+            front.clip_to(other);
+        }
+        if let Some(back) = self.back.as_mut() {
+            back.clip_to(other);
+        }
+    }
+
+    ///
+    /// Returns whether or not any of the given polygons overlaps a co-planar polygon in this tree.
+    ///
+    fn has_coplanar_overlap(&self, polygons: Vec<Polygon>) -> bool {
+        let plane = match self.plane {
+            Some(plane) => plane,
+            None => return false,
+        };
+        let mut split = Split::default();
+        for polygon in polygons {
+            split_polygon(&plane, polygon, &mut split);
+        }
+        if split
+            .coplanar_front
+            .iter()
+            .chain(split.coplanar_back.iter())
+            .any(|p| self.polygons.iter().any(|q| q.overlaps(p)))
+        {
+            return true;
+        }
+        self.front
+            .as_ref()
+            .map(|node| node.has_coplanar_overlap(split.front))
+            .unwrap_or(false)
+            || self
+                .back
+                .as_ref()
+                .map(|node| node.has_coplanar_overlap(split.back))
+                .unwrap_or(false)
+    }
+
+    fn all_polygons(&self) -> Vec<Polygon> {
+        let mut polygons = self.polygons.clone();
+        if let Some(front) = &self.front {
+            polygons.extend(front.all_polygons());
+        }
+        if let Some(back) = &self.back {
+            polygons.extend(back.all_polygons());
+        }
+        polygons
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn cube(offset: Vec3) -> CpuMesh {
+        let mut cube = CpuMesh::cube();
+        cube.positions = Positions::F32(
+            cube.positions
+                .to_f32()
+                .into_iter()
+                .map(|p| p + offset)
+                .collect(),
+        );
+        cube
+    }
+
+    fn volume(mesh: &CpuMesh) -> f32 {
+        let positions = mesh.positions.to_f32();
+        positions
+            .chunks(3)
+            .map(|t| t[0].dot(t[1].cross(t[2])) / 6.0)
+            .sum()
+    }
+
+    #[test]
+    fn test_boolean() {
+        let a = cube(vec3(0.0, 0.0, 0.0));
+        let b = cube(vec3(1.0, 0.5, 0.25));
+        let overlap = 1.0 * 1.5 * 1.75;
+        let union = boolean(&a, &b, BooleanOp::Union).unwrap();
+        assert!((volume(&union) - (16.0 - overlap)).abs() < 0.001);
+        let intersection = boolean(&a, &b, BooleanOp::Intersection).unwrap();
+        assert!((volume(&intersection) - overlap).abs() < 0.001);
+        let difference = boolean(&a, &b, BooleanOp::Difference).unwrap();
+        assert!((volume(&difference) - (8.0 - overlap)).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_separate() {
+        let a = cube(vec3(0.0, 0.0, 0.0));
+        let b = cube(vec3(3.0, 0.5, 0.5));
+        let union = boolean(&a, &b, BooleanOp::Union).unwrap();
+        assert!((volume(&union) - 16.0).abs() < 0.001);
+        let intersection = boolean(&a, &b, BooleanOp::Intersection).unwrap();
+        assert!(intersection.positions.to_f32().is_empty());
+    }
+
+    #[test]
+    fn test_coplanar() {
+        let a = cube(vec3(0.0, 0.0, 0.0));
+        let b = cube(vec3(1.0, 0.0, 0.5));
+        assert!(boolean(&a, &b, BooleanOp::Union).is_err());
+    }
+
+    #[test]
+    fn test_degenerate() {
+        let mut a = cube(vec3(0.0, 0.0, 0.0));
+        a.indices = Indices::U32(vec![0, 0, 1]);
+        assert!(boolean(&a, &cube(vec3(0.5, 0.5, 0.5)), BooleanOp::Union).is_err());
+    }
+}
@@ -0,0 +1,175 @@
+use super::shape2d::Shape2D;
+use crate::renderer::*;
+
+///
+/// An ellipse 2D geometry which can be rendered using the [camera2d] camera.
+/// The ellipse is cut out of a quad in the fragment shader, so the edge is smooth and anti-aliased no matter the size of the ellipse.
+///
+pub struct Ellipse {
+    shape: Shape2D,
+    radius_x: f32,
+    radius_y: f32,
+    center: PhysicalPoint,
+    rotation: Radians,
+}
+
+impl Ellipse {
+    ///
+    /// Constructs a new ellipse geometry with the given radius along the x-axis and y-axis before the rotation is applied.
+    ///
+    pub fn new(
+        context: &Context,
+        center: impl Into<PhysicalPoint>,
+        rotation: impl Into<Radians>,
+        radius_x: f32,
+        radius_y: f32,
+    ) -> Self {
+        let mut ellipse = Self {
+            shape: Shape2D::new(context),
+            radius_x,
+            radius_y,
+            center: center.into(),
+            rotation: rotation.into(),
+        };
+        ellipse.update();
+        ellipse
+    }
+
+    /// Set the radius along the x-axis and y-axis of the ellipse.
+    pub fn set_radii(&mut self, radius_x: f32, radius_y: f32) {
+        self.radius_x = radius_x;
+        self.radius_y = radius_y;
+        self.update();
+    }
+
+    /// Get the radius along the x-axis and y-axis of the ellipse.
+    pub fn radii(&self) -> (f32, f32) {
+        (self.radius_x, self.radius_y)
+    }
+
+    /// Set the center of the ellipse.
+    pub fn set_center(&mut self, center: impl Into<PhysicalPoint>) {
+        self.center = center.into();
+        self.update();
+    }
+
+    /// Get the center of the ellipse.
+    pub fn center(&self) -> PhysicalPoint {
+        self.center
+    }
+
+    /// Set the rotation of the ellipse.
+    pub fn set_rotation(&mut self, rotation: impl Into<Radians>) {
+        self.rotation = rotation.into();
+        self.update();
+    }
+
+    /// Get the rotation of the ellipse.
+    pub fn rotation(&self) -> Radians {
+        self.rotation
+    }
+
+    ///
+    /// Set a border with the given width in physical pixels and color along the inside of the edge of the ellipse.
+    /// The inside of the border is colored by the material. A width of zero removes the border.
+    ///
+    pub fn set_border(&mut self, width: f32, color: Color) {
+        self.shape.set_border(width, color);
+    }
+
+    /// Get the width and color of the border if any.
+    pub fn border(&self) -> Option<(f32, Color)> {
+        self.shape.border()
+    }
+
+    /// Get the number of vertices in the ellipse.
+    pub fn vertex_count(&self) -> u32 {
+        self.shape.vertex_count()
+    }
+
+    /// Get the number of triangles in the ellipse.
+    pub fn triangle_count(&self) -> u32 {
+        self.shape.triangle_count()
+    }
+
+    fn update(&mut self) {
+        self.shape.update(
+            self.center.into(),
+            self.rotation,
+            2.0 * self.radius_x,
+            2.0 * self.radius_y,
+        );
+    }
+
+    fn use_uniforms(&self, program: &Program) {
+        program.use_uniform("ellipseRadii", vec2(self.radius_x, self.radius_y));
+    }
+}
+
+const ELLIPSE_DISTANCE: &str = "
+    uniform vec2 ellipseRadii;
+    float shape_distance(vec2 p)
+    {
+        // An approximation of the distance which is exact on the edge
+        float k0 = length(p / ellipseRadii);
+        float k1 = length(p / (ellipseRadii * ellipseRadii));
+        return k1 > 0.0 ? k0 * (k0 - 1.0) / k1 : -min(ellipseRadii.x, ellipseRadii.y);
+    }";
+
+impl Geometry for Ellipse {
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        self.shape
+            .render_with_material(material, camera, lights, ELLIPSE_DISTANCE, |program| {
+                self.use_uniforms(program)
+            })
+    }
+
+    fn render_with_post_material(
+        &self,
+        material: &dyn PostMaterial,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        self.shape.render_with_post_material(
+            material,
+            camera,
+            lights,
+            color_texture,
+            depth_texture,
+            ELLIPSE_DISTANCE,
+            |program| self.use_uniforms(program),
+        )
+    }
+
+    ///
+    /// Returns the [AxisAlignedBoundingBox] for this geometry in the global coordinate system.
+    ///
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let center: Vec2 = self.center.into();
+        let (sin, cos) = self.rotation.0.sin_cos();
+        let half_size = vec2(
+            ((self.radius_x * cos).powi(2) + (self.radius_y * sin).powi(2)).sqrt(),
+            ((self.radius_x * sin).powi(2) + (self.radius_y * cos).powi(2)).sqrt(),
+        );
+        AxisAlignedBoundingBox::new_with_positions(&[
+            (center - half_size).extend(0.0),
+            (center + half_size).extend(0.0),
+        ])
+    }
+}
+
+impl<'a> IntoIterator for &'a Ellipse {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
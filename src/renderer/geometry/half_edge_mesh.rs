@@ -0,0 +1,670 @@
+use crate::renderer::*;
+use std::collections::{HashMap, HashSet};
+
+///
+/// A handle to a vertex in a [HalfEdgeMesh].
+/// The handle stays valid until the vertex is removed, for example by [HalfEdgeMesh::collapse_edge].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VertexHandle(usize);
+
+///
+/// A handle to a half-edge in a [HalfEdgeMesh], ie. one of the two directed edges that make up an edge between two faces.
+/// The handle stays valid until the face it belongs to is removed, which happens for all faces that are changed by an editing operation.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct HalfEdgeHandle(usize);
+
+///
+/// A handle to a (triangle) face in a [HalfEdgeMesh].
+/// The handle stays valid until the face is removed, which happens for all faces that are changed by an editing operation.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FaceHandle(usize);
+
+#[derive(Clone, Copy, Debug)]
+struct HalfEdge {
+    vertex: VertexHandle,
+    next: HalfEdgeHandle,
+    face: FaceHandle,
+}
+
+///
+/// A triangle mesh represented as a half-edge data structure which, contrary to a [CpuMesh], supports topological editing operations,
+/// for example [split_edge](HalfEdgeMesh::split_edge), [collapse_edge](HalfEdgeMesh::collapse_edge), [flip_edge](HalfEdgeMesh::flip_edge) and [extrude_face](HalfEdgeMesh::extrude_face).
+/// Use [HalfEdgeMesh::from_cpu_mesh] to construct it and [HalfEdgeMesh::to_cpu_mesh] to convert it back to a [CpuMesh] that can be rendered.
+///
+/// Each edge consists of two half-edges pointing in opposite directions, one for each of the two adjacent faces.
+/// An edge on the boundary of the mesh only has one half-edge, ie. the half-edge does not have a [twin](HalfEdgeMesh::twin).
+///
+#[derive(Clone, Debug, Default)]
+pub struct HalfEdgeMesh {
+    positions: Vec<Option<Vec3>>,
+    half_edges: Vec<Option<HalfEdge>>,
+    faces: Vec<Option<HalfEdgeHandle>>,
+    directed_edges: HashMap<(VertexHandle, VertexHandle), HalfEdgeHandle>,
+    vertex_count: usize,
+    face_count: usize,
+}
+
+impl HalfEdgeMesh {
+    ///
+    /// Constructs a half-edge mesh from the positions and triangles of the given [CpuMesh], all other attributes are ignored.
+    /// The vertices are not merged, so the triangles of a mesh without indices are not connected.
+    ///
+    /// Returns an error if a triangle refers to the same vertex more than once, refers to a vertex that does not exist
+    /// or if the mesh is not manifold, ie. if more than two triangles share an edge or the neighbouring triangles are not consistently oriented.
+    ///
+    pub fn from_cpu_mesh(cpu_mesh: &CpuMesh) -> Result<Self, RendererError> {
+        let positions = cpu_mesh.positions.to_f32();
+        let indices = match &cpu_mesh.indices {
+            Indices::U8(ind) => ind.iter().map(|i| *i as usize).collect::<Vec<_>>(),
+            Indices::U16(ind) => ind.iter().map(|i| *i as usize).collect::<Vec<_>>(),
+            Indices::U32(ind) => ind.iter().map(|i| *i as usize).collect::<Vec<_>>(),
+            Indices::None => (0..positions.len()).collect::<Vec<_>>(),
+        };
+        let mut mesh = Self::default();
+        let vertices = positions
+            .into_iter()
+            .map(|p| mesh.add_vertex(p))
+            .collect::<Vec<_>>();
+        for (triangle, face) in indices.chunks(3).enumerate() {
+            let face = face
+                .iter()
+                .map(|i| {
+                    vertices.get(*i).copied().ok_or_else(|| {
+                        RendererError::InvalidHalfEdgeMesh(format!(
+                            "triangle {} refers to vertex {} which does not exist",
+                            triangle, i
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if face.len() != 3 {
+                return Err(RendererError::InvalidHalfEdgeMesh(
+                    "the number of indices is not divisible by 3".to_string(),
+                ));
+            }
+            if face[0] == face[1] || face[1] == face[2] || face[2] == face[0] {
+                return Err(RendererError::InvalidHalfEdgeMesh(format!(
+                    "triangle {} is degenerate",
+                    triangle
+                )));
+            }
+            for i in 0..3 {
+                if mesh.find_edge(face[i], face[(i + 1) % 3]).is_some() {
+                    return Err(RendererError::InvalidHalfEdgeMesh(format!(
+                        "the edge between vertex {} and {} of triangle {} is shared with more than one triangle or the triangles are not consistently oriented",
+                        face[i].0, face[(i + 1) % 3].0, triangle
+                    )));
+                }
+            }
+            mesh.add_face(face[0], face[1], face[2]);
+        }
+        Ok(mesh)
+    }
+
+    ///
+    /// Converts this half-edge mesh to a [CpuMesh] with positions, indices and normals.
+    /// The vertices are renumbered, so removed vertices do not leave gaps.
+    ///
+    pub fn to_cpu_mesh(&self) -> CpuMesh {
+        let mut mapping = HashMap::new();
+        let mut positions = Vec::with_capacity(self.vertex_count);
+        for vertex in self.vertex_iter() {
+            mapping.insert(vertex, positions.len() as u32);
+            positions.push(self.position(vertex));
+        }
+        let mut indices = Vec::with_capacity(3 * self.face_count);
+        for face in self.face_iter() {
+            indices.extend(self.face_vertices(face).iter().map(|v| mapping[v]));
+        }
+        let mut cpu_mesh = CpuMesh {
+            positions: Positions::F32(positions),
+            indices: Indices::U32(indices),
+            ..Default::default()
+        };
+        cpu_mesh.compute_normals();
+        cpu_mesh
+    }
+
+    ///
+    /// Returns the number of vertices.
+    ///
+    pub fn vertex_count(&self) -> usize {
+        self.vertex_count
+    }
+
+    ///
+    /// Returns the number of faces.
+    ///
+    pub fn face_count(&self) -> usize {
+        self.face_count
+    }
+
+    ///
+    /// Returns the number of edges, where two half-edges between the same two vertices count as one edge.
+    ///
+    pub fn edge_count(&self) -> usize {
+        self.edge_iter().count()
+    }
+
+    ///
+    /// Iterator over all vertices.
+    ///
+    pub fn vertex_iter(&self) -> impl Iterator<Item = VertexHandle> + '_ {
+        self.positions
+            .iter()
+            .enumerate()
+            .filter(|(_, p)| p.is_some())
+            .map(|(i, _)| VertexHandle(i))
+    }
+
+    ///
+    /// Iterator over all faces.
+    ///
+    pub fn face_iter(&self) -> impl Iterator<Item = FaceHandle> + '_ {
+        self.faces
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| f.is_some())
+            .map(|(i, _)| FaceHandle(i))
+    }
+
+    ///
+    /// Iterator over all edges, where only one of the two half-edges of each edge is returned.
+    ///
+    pub fn edge_iter(&self) -> impl Iterator<Item = HalfEdgeHandle> + '_ {
+        self.half_edges
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.is_some())
+            .map(|(i, _)| HalfEdgeHandle(i))
+            .filter(|h| self.twin(*h).map(|t| h.0 < t.0).unwrap_or(true))
+    }
+
+    ///
+    /// Returns the position of the given vertex.
+    ///
+    pub fn position(&self, vertex: VertexHandle) -> Vec3 {
+        self.positions[vertex.0].expect("the vertex has been removed")
+    }
+
+    ///
+    /// Moves the given vertex to the given position.
+    ///
+    pub fn set_position(&mut self, vertex: VertexHandle, position: Vec3) {
+        let p = self.positions[vertex.0]
+            .as_mut()
+            .expect("the vertex has been removed");
+        *p = position;
+    }
+
+    ///
+    /// Returns the half-edge pointing from vertex `a` to vertex `b` if it exists.
+    ///
+    pub fn find_edge(&self, a: VertexHandle, b: VertexHandle) -> Option<HalfEdgeHandle> {
+        self.directed_edges.get(&(a, b)).copied()
+    }
+
+    ///
+    /// Returns the source and target vertex of the given half-edge.
+    ///
+    pub fn edge_vertices(&self, half_edge: HalfEdgeHandle) -> (VertexHandle, VertexHandle) {
+        (
+            self.half_edge(self.prev(half_edge)).vertex,
+            self.half_edge(half_edge).vertex,
+        )
+    }
+
+    ///
+    /// Returns the half-edge pointing in the opposite direction of the given half-edge or `None` if the half-edge is on the boundary of the mesh.
+    ///
+    pub fn twin(&self, half_edge: HalfEdgeHandle) -> Option<HalfEdgeHandle> {
+        let (a, b) = self.edge_vertices(half_edge);
+        self.find_edge(b, a)
+    }
+
+    ///
+    /// Returns the face the given half-edge belongs to.
+    ///
+    pub fn face(&self, half_edge: HalfEdgeHandle) -> FaceHandle {
+        self.half_edge(half_edge).face
+    }
+
+    ///
+    /// Returns the three vertices of the given face in counter clockwise order.
+    ///
+    pub fn face_vertices(&self, face: FaceHandle) -> [VertexHandle; 3] {
+        let h0 = self.faces[face.0].expect("the face has been removed");
+        let h1 = self.next(h0);
+        let h2 = self.next(h1);
+        [
+            self.half_edge(h2).vertex,
+            self.half_edge(h0).vertex,
+            self.half_edge(h1).vertex,
+        ]
+    }
+
+    ///
+    /// Returns the unit normal of the given face.
+    ///
+    pub fn face_normal(&self, face: FaceHandle) -> Vec3 {
+        let [a, b, c] = self.face_vertices(face).map(|v| self.position(v));
+        (b - a).cross(c - a).normalize()
+    }
+
+    ///
+    /// Splits the edge of the given half-edge in two by inserting a new vertex in the middle of the edge,
+    /// thereby also splitting each of the adjacent faces in two. Returns the new vertex.
+    ///
+    pub fn split_edge(&mut self, half_edge: HalfEdgeHandle) -> VertexHandle {
+        let (a, b) = self.edge_vertices(half_edge);
+        let c = self.opposite_vertex(half_edge);
+        let twin = self.twin(half_edge);
+        let m = self.add_vertex(0.5 * (self.position(a) + self.position(b)));
+
+        self.remove_face(self.face(half_edge));
+        self.add_face(a, m, c);
+        self.add_face(m, b, c);
+        if let Some(twin) = twin {
+            let d = self.opposite_vertex(twin);
+            self.remove_face(self.face(twin));
+            self.add_face(b, m, d);
+            self.add_face(m, a, d);
+        }
+        m
+    }
+
+    ///
+    /// Collapses the edge of the given half-edge by merging its two vertices into one vertex positioned in the middle of the edge
+    /// and removing the faces adjacent to the edge. Returns the merged vertex, which is the target vertex of the half-edge.
+    ///
+    /// Returns an error, without changing the mesh, if the collapse would make the mesh non-manifold, which is checked using the link condition,
+    /// ie. if the one-rings of the two vertices share other vertices than the vertices opposite to the edge or share an edge,
+    /// if both vertices are on the boundary while the edge is not or if the two vertices share a neighbour along the boundary.
+    ///
+    pub fn collapse_edge(
+        &mut self,
+        half_edge: HalfEdgeHandle,
+    ) -> Result<VertexHandle, RendererError> {
+        let (a, b) = self.edge_vertices(half_edge);
+        let twin = self.twin(half_edge);
+        let (faces_a, boundary_a) = self.vertex_faces(half_edge);
+        let (faces_b, boundary_b) = self.vertex_faces(self.next(half_edge));
+        if twin.is_some() && boundary_a && boundary_b {
+            return Err(RendererError::InvalidEdgeCollapse(
+                "both vertices are on the boundary but the edge is not".to_string(),
+            ));
+        }
+
+        let link_a = self.vertex_link(a, &faces_a);
+        let link_b = self.vertex_link(b, &faces_b);
+        let mut opposite = HashSet::new();
+        opposite.insert(self.opposite_vertex(half_edge));
+        if let Some(twin) = twin {
+            opposite.insert(self.opposite_vertex(twin));
+        }
+        if link_a
+            .vertices
+            .intersection(&link_b.vertices)
+            .any(|v| !opposite.contains(v))
+        {
+            return Err(RendererError::InvalidEdgeCollapse(
+                "the vertices share neighbours which are not opposite to the edge".to_string(),
+            ));
+        }
+        if link_a.edges.intersection(&link_b.edges).next().is_some() {
+            return Err(RendererError::InvalidEdgeCollapse(
+                "the vertices share an edge in their one-rings, which would collapse a face onto another"
+                    .to_string(),
+            ));
+        }
+        if link_a
+            .boundary_vertices
+            .intersection(&link_b.boundary_vertices)
+            .next()
+            .is_some()
+        {
+            return Err(RendererError::InvalidEdgeCollapse(
+                "the vertices share a neighbour on the boundary, which would collapse the boundary"
+                    .to_string(),
+            ));
+        }
+
+        let triangles = faces_a
+            .iter()
+            .map(|f| self.face_vertices(*f))
+            .collect::<Vec<_>>();
+        for face in faces_a {
+            self.remove_face(face);
+        }
+        for triangle in triangles {
+            if !triangle.contains(&b) {
+                let [v0, v1, v2] = triangle.map(|v| if v == a { b } else { v });
+                self.add_face(v0, v1, v2);
+            }
+        }
+        self.set_position(b, 0.5 * (self.position(a) + self.position(b)));
+        self.positions[a.0] = None;
+        self.vertex_count -= 1;
+        Ok(b)
+    }
+
+    ///
+    /// Flips the edge of the given half-edge, ie. replaces the edge between the two adjacent faces with an edge between the two vertices opposite to the edge.
+    ///
+    /// # Panic
+    /// Will panic if the edge is on the boundary of the mesh or if the two opposite vertices are already connected by an edge.
+    ///
+    pub fn flip_edge(&mut self, half_edge: HalfEdgeHandle) {
+        let twin = self
+            .twin(half_edge)
+            .expect("cannot flip an edge on the boundary of the mesh");
+        let (a, b) = self.edge_vertices(half_edge);
+        let c = self.opposite_vertex(half_edge);
+        let d = self.opposite_vertex(twin);
+        if c == d || self.find_edge(c, d).is_some() {
+            panic!("cannot flip an edge when the opposite vertices are already connected");
+        }
+        self.remove_face(self.face(half_edge));
+        self.remove_face(self.face(twin));
+        self.add_face(a, d, c);
+        self.add_face(d, b, c);
+    }
+
+    ///
+    /// Extrudes the given face by the given offset, ie. moves the face to three new vertices offset from the original vertices
+    /// and connects the edges of the moved face to the original edges with new faces. Returns the moved face.
+    ///
+    pub fn extrude_face(&mut self, face: FaceHandle, offset: Vec3) -> FaceHandle {
+        let vertices = self.face_vertices(face);
+        let moved = vertices.map(|v| self.add_vertex(self.position(v) + offset));
+        self.remove_face(face);
+        for i in 0..3 {
+            let j = (i + 1) % 3;
+            self.add_face(vertices[i], vertices[j], moved[j]);
+            self.add_face(vertices[i], moved[j], moved[i]);
+        }
+        self.add_face(moved[0], moved[1], moved[2])
+    }
+
+    fn half_edge(&self, half_edge: HalfEdgeHandle) -> &HalfEdge {
+        self.half_edges[half_edge.0]
+            .as_ref()
+            .expect("the half-edge has been removed")
+    }
+
+    fn next(&self, half_edge: HalfEdgeHandle) -> HalfEdgeHandle {
+        self.half_edge(half_edge).next
+    }
+
+    fn prev(&self, half_edge: HalfEdgeHandle) -> HalfEdgeHandle {
+        self.next(self.next(half_edge))
+    }
+
+    fn opposite_vertex(&self, half_edge: HalfEdgeHandle) -> VertexHandle {
+        self.half_edge(self.next(half_edge)).vertex
+    }
+
+    ///
+    /// Returns the faces around the source vertex of the given half-edge and whether or not the vertex is on the boundary.
+    ///
+    fn vertex_faces(&self, outgoing: HalfEdgeHandle) -> (Vec<FaceHandle>, bool) {
+        let mut faces = vec![self.face(outgoing)];
+        // Rotate in one direction until reaching the start or the boundary
+        let mut current = outgoing;
+        loop {
+            match self.twin(self.prev(current)) {
+                Some(h) if h == outgoing => return (faces, false),
+                Some(h) => {
+                    faces.push(self.face(h));
+                    current = h;
+                }
+                None => break,
+            }
+        }
+        // Rotate in the other direction until reaching the boundary
+        let mut current = outgoing;
+        while let Some(twin) = self.twin(current) {
+            current = self.next(twin);
+            faces.push(self.face(current));
+        }
+        (faces, true)
+    }
+
+    ///
+    /// Returns the link of the given vertex, ie. the one-ring of vertices and edges opposite to the vertex in the given faces around the vertex,
+    /// together with the neighbours connected to the vertex by an edge on the boundary.
+    ///
+    fn vertex_link(&self, vertex: VertexHandle, faces: &[FaceHandle]) -> VertexLink {
+        let mut link = VertexLink::default();
+        for face in faces {
+            let vertices = self.face_vertices(*face);
+            let i = vertices.iter().position(|v| *v == vertex).unwrap();
+            let (x, y) = (vertices[(i + 1) % 3], vertices[(i + 2) % 3]);
+            link.vertices.insert(x);
+            link.vertices.insert(y);
+            link.edges.insert((x.min(y), x.max(y)));
+            if self.find_edge(x, vertex).is_none() {
+                link.boundary_vertices.insert(x);
+            }
+            if self.find_edge(vertex, y).is_none() {
+                link.boundary_vertices.insert(y);
+            }
+        }
+        link
+    }
+
+    fn add_vertex(&mut self, position: Vec3) -> VertexHandle {
+        self.positions.push(Some(position));
+        self.vertex_count += 1;
+        VertexHandle(self.positions.len() - 1)
+    }
+
+    fn add_face(&mut self, a: VertexHandle, b: VertexHandle, c: VertexHandle) -> FaceHandle {
+        let face = FaceHandle(self.faces.len());
+        let first = self.half_edges.len();
+        for (i, (source, target)) in [(a, b), (b, c), (c, a)].into_iter().enumerate() {
+            let handle = HalfEdgeHandle(first + i);
+            self.half_edges.push(Some(HalfEdge {
+                vertex: target,
+                next: HalfEdgeHandle(first + (i + 1) % 3),
+                face,
+            }));
+            self.directed_edges.insert((source, target), handle);
+        }
+        self.faces.push(Some(HalfEdgeHandle(first)));
+        self.face_count += 1;
+        face
+    }
+
+    fn remove_face(&mut self, face: FaceHandle) {
+        let [a, b, c] = self.face_vertices(face);
+        for (source, target) in [(a, b), (b, c), (c, a)] {
+            let handle = self.directed_edges.remove(&(source, target)).unwrap();
+            self.half_edges[handle.0] = None;
+        }
+        self.faces[face.0] = None;
+        self.face_count -= 1;
+    }
+}
+
+#[derive(Default)]
+struct VertexLink {
+    vertices: HashSet<VertexHandle>,
+    edges: HashSet<(VertexHandle, VertexHandle)>,
+    boundary_vertices: HashSet<VertexHandle>,
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    ///
+    /// A 3x3 grid of vertices in the xy-plane with 8 triangles.
+    ///
+    fn grid() -> HalfEdgeMesh {
+        let mut positions = Vec::new();
+        for y in 0..3 {
+            for x in 0..3 {
+                positions.push(vec3(x as f32, y as f32, 0.0));
+            }
+        }
+        let mut indices = Vec::new();
+        for y in 0..2 {
+            for x in 0..2 {
+                let i = y * 3 + x;
+                indices.extend([i, i + 1, i + 4, i, i + 4, i + 3]);
+            }
+        }
+        HalfEdgeMesh::from_cpu_mesh(&CpuMesh {
+            positions: Positions::F32(positions),
+            indices: Indices::U32(indices),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn tetrahedron() -> HalfEdgeMesh {
+        HalfEdgeMesh::from_cpu_mesh(&CpuMesh {
+            positions: Positions::F32(vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(0.0, 0.0, 1.0),
+            ]),
+            indices: Indices::U8(vec![0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3]),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn assert_closed(mesh: &HalfEdgeMesh) {
+        assert!(mesh
+            .half_edges
+            .iter()
+            .enumerate()
+            .filter(|(_, h)| h.is_some())
+            .all(|(i, _)| mesh.twin(HalfEdgeHandle(i)).is_some()));
+        assert_eq!(
+            mesh.vertex_count() + mesh.face_count(),
+            mesh.edge_count() + 2
+        );
+    }
+
+    #[test]
+    fn test_from_cpu_mesh() {
+        let mesh = grid();
+        assert_eq!(mesh.vertex_count(), 9);
+        assert_eq!(mesh.face_count(), 8);
+        assert_eq!(mesh.edge_count(), 16);
+        assert_closed(&tetrahedron());
+
+        let cpu_mesh = mesh.to_cpu_mesh();
+        assert_eq!(cpu_mesh.vertex_count(), 9);
+        assert_eq!(cpu_mesh.triangle_count(), 8);
+    }
+
+    #[test]
+    fn test_non_manifold() {
+        let result = HalfEdgeMesh::from_cpu_mesh(&CpuMesh {
+            positions: Positions::F32(vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+                vec3(0.0, -1.0, 0.0),
+            ]),
+            indices: Indices::U8(vec![0, 1, 2, 0, 1, 3]),
+            ..Default::default()
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_split_edge() {
+        let mut mesh = tetrahedron();
+        let edge = mesh.edge_iter().next().unwrap();
+        let (a, b) = mesh.edge_vertices(edge);
+        let m = mesh.split_edge(edge);
+        assert_eq!(mesh.vertex_count(), 5);
+        assert_eq!(mesh.face_count(), 6);
+        assert_eq!(
+            mesh.position(m),
+            0.5 * (mesh.position(a) + mesh.position(b))
+        );
+        assert!(mesh.find_edge(a, b).is_none());
+        assert!(mesh.find_edge(a, m).is_some() && mesh.find_edge(m, b).is_some());
+        assert_closed(&mesh);
+    }
+
+    #[test]
+    fn test_flip_edge() {
+        let mut mesh = grid();
+        let center = VertexHandle(4);
+        let edge = mesh.find_edge(VertexHandle(0), center).unwrap();
+        mesh.flip_edge(edge);
+        assert_eq!(mesh.face_count(), 8);
+        assert!(mesh.find_edge(VertexHandle(0), center).is_none());
+        assert!(
+            mesh.find_edge(VertexHandle(1), VertexHandle(3)).is_some()
+                || mesh.find_edge(VertexHandle(3), VertexHandle(1)).is_some()
+        );
+        for face in mesh.face_iter() {
+            assert!(mesh.face_normal(face).z > 0.0);
+        }
+    }
+
+    #[test]
+    fn test_collapse_edge() {
+        let mut mesh = grid();
+        let edge = mesh.find_edge(VertexHandle(4), VertexHandle(5)).unwrap();
+        let vertex = mesh.collapse_edge(edge).unwrap();
+        assert_eq!(vertex, VertexHandle(5));
+        assert_eq!(mesh.vertex_count(), 8);
+        assert_eq!(mesh.face_count(), 6);
+        assert_eq!(mesh.position(vertex), vec3(1.5, 1.0, 0.0));
+        for face in mesh.face_iter() {
+            assert!(mesh.face_normal(face).z > 0.0);
+        }
+
+        // Both vertices are on the boundary, but the edge is not
+        let mut mesh = grid();
+        let edge = mesh.find_edge(VertexHandle(1), VertexHandle(5)).unwrap();
+        assert!(mesh.collapse_edge(edge).is_err());
+        assert_eq!(mesh.face_count(), 8);
+
+        // The one-rings share the edge between the opposite vertices, so the remaining two faces would be on top of each other
+        let mut mesh = tetrahedron();
+        let edge = mesh.edge_iter().next().unwrap();
+        assert!(mesh.collapse_edge(edge).is_err());
+        assert_eq!(mesh.face_count(), 4);
+
+        // The vertices share a neighbour along the boundary, so the boundary would collapse
+        let mut mesh = HalfEdgeMesh::from_cpu_mesh(&CpuMesh {
+            positions: Positions::F32(vec![
+                vec3(0.0, 0.0, 0.0),
+                vec3(1.0, 0.0, 0.0),
+                vec3(0.0, 1.0, 0.0),
+            ]),
+            indices: Indices::U8(vec![0, 1, 2]),
+            ..Default::default()
+        })
+        .unwrap();
+        let edge = mesh.find_edge(VertexHandle(0), VertexHandle(1)).unwrap();
+        assert!(mesh.collapse_edge(edge).is_err());
+        assert_eq!(mesh.face_count(), 1);
+    }
+
+    #[test]
+    fn test_extrude_face() {
+        let mut mesh = tetrahedron();
+        let face = mesh.face_iter().next().unwrap();
+        let normal = mesh.face_normal(face);
+        let extruded = mesh.extrude_face(face, normal);
+        assert_eq!(mesh.vertex_count(), 7);
+        assert_eq!(mesh.face_count(), 10);
+        assert!((mesh.face_normal(extruded) - normal).magnitude() < 0.0001);
+        assert_closed(&mesh);
+    }
+}
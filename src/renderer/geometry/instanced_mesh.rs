@@ -1,9 +1,9 @@
 use crate::core::*;
 use crate::renderer::*;
 use std::collections::HashMap;
-use std::sync::RwLock;
+use std::sync::{Arc, RwLock};
 
-use super::BaseMesh;
+use super::{clip_shader_source, draw_with_clip_planes, BaseMesh};
 
 ///
 /// Similar to [Mesh], except it is possible to render many instances of the same mesh efficiently.
@@ -11,7 +11,7 @@ use super::BaseMesh;
 pub struct InstancedMesh {
     context: Context,
     base_mesh: BaseMesh,
-    instance_buffers: RwLock<(HashMap<String, InstanceBuffer>, Vec3)>,
+    instance_buffers: RwLock<(HashMap<String, InstanceBuffer>, Option<Vec3>)>,
     aabb: AxisAlignedBoundingBox,
     aabb_local: AxisAlignedBoundingBox,
     transformation: Mat4,
@@ -19,6 +19,21 @@ pub struct InstancedMesh {
     animation: Option<Box<dyn Fn(f32) -> Mat4 + Send + Sync>>,
     instance_count: u32,
     instances: Instances,
+    shared_instances: Option<SharedInstances>,
+    shared_aabb: RwLock<Option<(u64, AxisAlignedBoundingBox)>>,
+    bounding_sphere: Option<(Vec3, f32)>,
+    culled_instance_buffers: RwLock<CulledInstanceBuffers>,
+}
+
+///
+/// The instance buffers containing the instances inside the view frustum, see [InstancedMesh::set_bounding_sphere].
+/// The buffers can have capacity for more instances than the number of visible instances, which are the first `count` instances.
+///
+#[derive(Default)]
+struct CulledInstanceBuffers {
+    indices: Vec<usize>,
+    buffers: HashMap<String, InstanceBuffer>,
+    count: u32,
 }
 
 impl InstancedMesh {
@@ -32,7 +47,7 @@ impl InstancedMesh {
         let mut instanced_mesh = Self {
             context: context.clone(),
             base_mesh: BaseMesh::new(context, cpu_mesh),
-            instance_buffers: RwLock::new((Default::default(), vec3(0.0, 0.0, 0.0))),
+            instance_buffers: RwLock::new((Default::default(), None)),
             aabb,
             aabb_local: aabb,
             transformation: Mat4::identity(),
@@ -40,11 +55,51 @@ impl InstancedMesh {
             animation: None,
             instance_count: 0,
             instances: instances.clone(),
+            shared_instances: None,
+            shared_aabb: RwLock::new(None),
+            bounding_sphere: None,
+            culled_instance_buffers: RwLock::new(CulledInstanceBuffers::default()),
         };
         instanced_mesh.set_instances(instances);
         instanced_mesh
     }
 
+    ///
+    /// Creates a new instanced 3D mesh from the given [CpuMesh] which renders the given [SharedInstances].
+    /// The instance buffers are owned by the shared instances, so several instanced meshes, for example the parts of a model or different levels of detail,
+    /// can use the same buffers and all of them are affected by [SharedInstances::update].
+    ///
+    pub fn new_with_shared_instances(
+        context: &Context,
+        shared_instances: &SharedInstances,
+        cpu_mesh: &CpuMesh,
+    ) -> Self {
+        let aabb = cpu_mesh.compute_aabb();
+        Self {
+            context: context.clone(),
+            base_mesh: BaseMesh::new(context, cpu_mesh),
+            instance_buffers: RwLock::new((Default::default(), None)),
+            aabb,
+            aabb_local: aabb,
+            transformation: Mat4::identity(),
+            current_transformation: Mat4::identity(),
+            animation: None,
+            instance_count: u32::MAX,
+            instances: Instances::default(),
+            shared_instances: Some(shared_instances.clone()),
+            shared_aabb: RwLock::new(None),
+            bounding_sphere: None,
+            culled_instance_buffers: RwLock::new(CulledInstanceBuffers::default()),
+        }
+    }
+
+    ///
+    /// Returns the [SharedInstances] rendered by this mesh, if it was constructed with [InstancedMesh::new_with_shared_instances].
+    ///
+    pub fn shared_instances(&self) -> Option<&SharedInstances> {
+        self.shared_instances.as_ref()
+    }
+
     ///
     /// Returns the local to world transformation applied to all instances.
     ///
@@ -70,25 +125,108 @@ impl InstancedMesh {
         self.animation = Some(Box::new(animation));
     }
 
+    ///
+    /// Returns the [IndexBuffer] which defines the triangles of this instanced mesh
+    /// or `None` if each three consecutive vertices defines a triangle.
+    ///
+    pub fn index_buffer(&self) -> Option<&IndexBuffer> {
+        self.base_mesh.indices.as_ref()
+    }
+
+    ///
+    /// Returns a mutable reference to the [IndexBuffer] which defines the triangles of this instanced mesh.
+    /// Use [IndexBuffer::update] to change the topology of the instanced mesh without reallocating the buffer, for example for procedurally generated geometry.
+    ///
+    pub fn index_buffer_mut(&mut self) -> Option<&mut IndexBuffer> {
+        self.base_mesh.indices.as_mut()
+    }
+
+    ///
+    /// Replaces the [IndexBuffer] which defines the triangles of this instanced mesh.
+    /// If `None`, each three consecutive vertices defines a triangle.
+    ///
+    pub fn set_index_buffer(&mut self, index_buffer: Option<IndexBuffer>) {
+        self.base_mesh.indices = index_buffer;
+    }
+
+    ///
+    /// Returns the number of vertices in a single instance of this mesh, use [InstancedMesh::instance_count] to get the number of instances.
+    ///
+    pub fn vertex_count(&self) -> u32 {
+        self.base_mesh.vertex_count()
+    }
+
+    ///
+    /// Returns the number of triangles in a single instance of this mesh, use [InstancedMesh::instance_count] to get the number of instances.
+    ///
+    pub fn triangle_count(&self) -> u32 {
+        self.base_mesh.triangle_count()
+    }
+
+    ///
+    /// Downloads the current content of the GPU buffers of this mesh, excluding the instance attributes, into a [CpuMesh].
+    /// The positions are in the local coordinate system of this mesh and the indices are always `u32`.
+    /// This waits for all previously issued work using the buffers to finish, so it should be used sparingly.
+    /// Returns an error if one of the buffers could not be mapped into client memory.
+    ///
+    pub fn read_back(&self) -> Result<CpuMesh, CoreError> {
+        self.base_mesh.read_back()
+    }
+
     /// Returns the number of instances that is rendered.
     pub fn instance_count(&self) -> u32 {
-        self.instance_count
+        if let Some(shared_instances) = &self.shared_instances {
+            self.instance_count.min(shared_instances.count())
+        } else {
+            self.instance_count
+        }
     }
 
     /// Use this if you only want to render instance 0 through to instance `instance_count`.
     /// This is the same as changing the instances using `set_instances`, except that it is faster since it doesn't update any buffers.
     /// `instance_count` will be set to the number of instances when they are defined by `set_instances`, so all instanced are rendered by default.
     pub fn set_instance_count(&mut self, instance_count: u32) {
-        self.instance_count = instance_count.min(self.instances.transformations.len() as u32);
+        self.instance_count = if self.shared_instances.is_some() {
+            instance_count
+        } else {
+            instance_count.min(self.instances.transformations.len() as u32)
+        };
         self.update_aabb();
     }
 
+    ///
+    /// Returns the bounding sphere used for culling the instances, if any, see [InstancedMesh::set_bounding_sphere].
+    ///
+    pub fn bounding_sphere(&self) -> Option<(Vec3, f32)> {
+        self.bounding_sphere
+    }
+
+    ///
+    /// Enables culling of the instances against the view frustum of the camera using a sphere, given in the local space of the mesh, which contains the entire mesh.
+    /// When rendering, the sphere is transformed by the transformation of each instance and the instances whose sphere is completely outside
+    /// one of the [CameraExt::frustum_planes] are not rendered. The test is conservative, so an instance can be rendered even though it is not visible.
+    /// Culling is disabled by default and does not apply when this mesh renders [SharedInstances].
+    ///
+    pub fn set_bounding_sphere(&mut self, center: Vec3, radius: f32) {
+        self.bounding_sphere = Some((center, radius));
+    }
+
+    ///
+    /// Disables the culling enabled by [InstancedMesh::set_bounding_sphere], so all instances are rendered.
+    ///
+    pub fn clear_bounding_sphere(&mut self) {
+        self.bounding_sphere = None;
+        *self.culled_instance_buffers.write().unwrap() = CulledInstanceBuffers::default();
+    }
+
     ///
     /// Update the instances.
+    /// If this mesh renders [SharedInstances], it stops doing so and renders the given instances instead.
     ///
     pub fn set_instances(&mut self, instances: &Instances) {
         #[cfg(debug_assertions)]
         instances.validate().expect("invalid instances");
+        self.shared_instances = None;
         self.instance_count = instances.count();
         self.instances = instances.clone();
         self.update_aabb();
@@ -100,16 +238,249 @@ impl InstancedMesh {
                 .expect("failed acquiring write accesss");
             s.0.clear();
         }
+        self.culled_instance_buffers
+            .write()
+            .unwrap()
+            .indices
+            .clear();
+    }
+
+    ///
+    /// Updates the transformations of the instances starting at the given offset, see [Instances::transformations], and leaves the other instance attributes unchanged.
+    /// The existing instance buffers are updated in place using [InstanceBuffer::update_partial], which is much faster than [InstancedMesh::set_instances]
+    /// when only a few of many instances change, unless the instances are ordered by distance to the camera or an instance changes from a pure translation to a general transformation,
+    /// in which case the buffers are recreated before the next render.
+    /// If this mesh renders [SharedInstances], the shared instances are updated, which affects all meshes using them.
+    ///
+    /// # Panic
+    /// Will panic if the offset plus the number of transformations is larger than the number of instances.
+    ///
+    pub fn update_instance_transformations(&mut self, offset: usize, transformations: &[Mat4]) {
+        let count = if let Some(shared_instances) = &self.shared_instances {
+            shared_instances.count() as usize
+        } else {
+            self.instances.transformations.len()
+        };
+        let range = offset..offset + transformations.len();
+        if range.end > count {
+            panic!(
+                "cannot update the transformations of instances {}..{} when there are {} instances",
+                range.start, range.end, count
+            );
+        }
+        if let Some(shared_instances) = &self.shared_instances {
+            let mut instances = shared_instances.instances();
+            instances.transformations[range].copy_from_slice(transformations);
+            shared_instances.update(&instances);
+            return;
+        }
+        self.instances.transformations[range].copy_from_slice(transformations);
+        self.update_aabb();
+        self.culled_instance_buffers
+            .write()
+            .unwrap()
+            .indices
+            .clear();
+
+        let mut s = self
+            .instance_buffers
+            .write()
+            .expect("failed acquiring write accesss");
+        if s.1.is_some() {
+            // The buffers are ordered by distance to the camera
+            s.0.clear();
+        } else if let Some(buffer) = s.0.get_mut("instance_translation") {
+            if transformations.iter().all(is_translation) {
+                buffer.update_partial(
+                    &transformations
+                        .iter()
+                        .map(|t| t.w.truncate())
+                        .collect::<Vec<_>>(),
+                    offset,
+                );
+            } else {
+                s.0.clear();
+            }
+        } else if !s.0.is_empty() {
+            for (row, name) in ["row1", "row2", "row3"].iter().enumerate() {
+                if let Some(buffer) = s.0.get_mut(*name) {
+                    buffer.update_partial(
+                        &transformations
+                            .iter()
+                            .map(|t| t.row(row))
+                            .collect::<Vec<_>>(),
+                        offset,
+                    );
+                }
+            }
+        }
+    }
+
+    ///
+    /// Updates the texture transformation of each instance (see [Instances::texture_transformations]) from an offset and scale per instance,
+    /// given as `[offset_x, offset_y, scale_x, scale_y]`. The uv coordinates of each instance are scaled and then offset before sampling,
+    /// which makes it possible to render instances using different regions of a texture atlas in a single draw call.
+    /// The other instance attributes are not changed.
+    /// If this mesh renders [SharedInstances], the shared instances are updated, which affects all meshes using them.
+    ///
+    /// # Panic
+    /// Will panic if the number of transforms does not match the number of instances.
+    ///
+    pub fn update_instance_uv_transforms(&mut self, transforms: &[Vec4]) {
+        let mut instances = if let Some(shared_instances) = &self.shared_instances {
+            shared_instances.instances()
+        } else {
+            self.instances.clone()
+        };
+        if transforms.len() != instances.transformations.len() {
+            panic!(
+                "the number of uv transforms ({}) must match the number of instances ({})",
+                transforms.len(),
+                instances.transformations.len()
+            );
+        }
+        instances.texture_transformations = Some(
+            transforms
+                .iter()
+                .map(|t| Mat3::new(t.z, 0.0, 0.0, 0.0, t.w, 0.0, t.x, t.y, 1.0))
+                .collect(),
+        );
+        if let Some(shared_instances) = &self.shared_instances {
+            shared_instances.update(&instances);
+            return;
+        }
+        self.instances = instances;
+        self.instance_buffers
+            .write()
+            .expect("failed acquiring write accesss")
+            .0
+            .clear();
+        self.culled_instance_buffers
+            .write()
+            .unwrap()
+            .indices
+            .clear();
     }
 
     fn update_aabb(&mut self) {
+        if self.shared_instances.is_some() {
+            // Computed when needed, since the shared instances can change without this mesh knowing
+            *self.shared_aabb.write().unwrap() = None;
+        } else {
+            self.aabb = self.instances_aabb(&self.instances.transformations);
+        }
+    }
+
+    fn instances_aabb(&self, transformations: &[Mat4]) -> AxisAlignedBoundingBox {
         let mut aabb = AxisAlignedBoundingBox::EMPTY;
-        for i in 0..self.instance_count as usize {
+        for transformation in transformations.iter().take(self.instance_count as usize) {
             let mut aabb2 = self.aabb_local;
-            aabb2.transform(&(self.instances.transformations[i] * self.transformation));
+            aabb2.transform(&(*transformation * self.transformation));
             aabb.expand_with_aabb(&aabb2);
         }
-        self.aabb = aabb;
+        aabb
+    }
+
+    ///
+    /// Calls the callback with the instance buffers to use for rendering and the number of instances to render, either the shared instance buffers,
+    /// the instances inside the view frustum if a bounding sphere is set or the instance buffers of this mesh, which are updated first if needed.
+    /// The callback is not called if all instances are culled.
+    ///
+    fn use_instance_buffers(
+        &self,
+        camera: &Camera,
+        depth_ordering_pose: Option<Vec3>,
+        margin: f32,
+        callback: impl FnOnce(&HashMap<String, InstanceBuffer>, u32),
+    ) {
+        if let Some(shared_instances) = &self.shared_instances {
+            callback(
+                &shared_instances.data.read().unwrap().buffers,
+                self.instance_count(),
+            );
+        } else if let Some((center, radius)) = self.bounding_sphere {
+            let mut indices = self.visible_instances(camera, center, radius, margin);
+            if indices.is_empty() {
+                return;
+            }
+            if let Some(position) = depth_ordering_pose {
+                let distances = indices
+                    .iter()
+                    .map(|i| {
+                        (self.transformation * self.instances.transformations[*i])
+                            .w
+                            .truncate()
+                            .distance2(position)
+                    })
+                    .collect::<Vec<_>>();
+                indices = Self::ordered_indices_back_to_front(indices.len(), &distances)
+                    .into_iter()
+                    .map(|i| indices[i])
+                    .collect();
+            }
+            let needs_update = self.culled_instance_buffers.read().unwrap().indices != indices;
+            if needs_update {
+                // The buffers are reused, so they are only reallocated when more instances are visible than ever before
+                let mut culled = self.culled_instance_buffers.write().unwrap();
+                fill_instance_buffers(
+                    &self.context,
+                    &mut culled.buffers,
+                    &self.instances,
+                    &indices,
+                );
+                culled.count = indices.len() as u32;
+                culled.indices = indices;
+            }
+            let culled = self.culled_instance_buffers.read().unwrap();
+            callback(&culled.buffers, culled.count);
+        } else {
+            self.update_instance_buffers(depth_ordering_pose);
+            callback(
+                &self
+                    .instance_buffers
+                    .read()
+                    .expect("failed to acquire read access")
+                    .0,
+                self.instance_count(),
+            );
+        }
+    }
+
+    ///
+    /// Returns the indices of the instances, up to the instance count, whose bounding sphere is not completely outside one of the planes of the view frustum.
+    /// The margin is added to the radius in world space, for example to account for displacement along the normals.
+    ///
+    fn visible_instances(
+        &self,
+        camera: &Camera,
+        center: Vec3,
+        radius: f32,
+        margin: f32,
+    ) -> Vec<usize> {
+        let planes = camera.frustum_planes();
+        self.instances
+            .transformations
+            .iter()
+            .take(self.instance_count as usize)
+            .enumerate()
+            .filter(|(_, transformation)| {
+                let transformation = self.current_transformation * **transformation;
+                let world_center = (transformation * center.extend(1.0)).truncate();
+                // The radius is scaled by the largest scale of the transformation to keep the test conservative
+                let world_radius = radius
+                    * transformation
+                        .x
+                        .truncate()
+                        .magnitude()
+                        .max(transformation.y.truncate().magnitude())
+                        .max(transformation.z.truncate().magnitude())
+                    + margin;
+                planes.iter().all(|plane| {
+                    plane.classify_sphere(world_center, world_radius) != PlaneSide::Back
+                })
+            })
+            .map(|(i, _)| i)
+            .collect()
     }
 
     /// Update the instance buffers, if depth_ordering_pose is populated depth ordering is performed
@@ -123,7 +494,7 @@ impl InstancedMesh {
 
             // Check if we need a reorder, this only applies to transparent materials.
             let reorder_needed = if let Some(ref ordering_pose) = depth_ordering_pose {
-                let camera_changed = *ordering_pose != s.1;
+                let camera_changed = Some(*ordering_pose) != s.1;
                 let instance_count_changed = if let Some(v) = s.0.values().next() {
                     v.instance_count() != self.instance_count
                 } else {
@@ -146,9 +517,7 @@ impl InstancedMesh {
                 .write()
                 .expect("failed acquiring mutable access");
             s.0 = self.create_instance_buffers(depth_ordering_pose);
-            if let Some(ordering_pose) = depth_ordering_pose {
-                s.1 = ordering_pose;
-            }
+            s.1 = depth_ordering_pose;
         }
     }
 
@@ -185,85 +554,7 @@ impl InstancedMesh {
             (0..self.instances.transformations.len()).collect::<Vec<usize>>()
         };
 
-        // Next, we can compute the instance buffers with that ordering.
-        let mut instance_buffers: HashMap<String, InstanceBuffer> = Default::default();
-
-        if indices
-            .iter()
-            .map(|i| self.instances.transformations[*i])
-            .all(|t| Mat3::from_cols(t.x.truncate(), t.y.truncate(), t.z.truncate()).is_identity())
-        {
-            instance_buffers.insert(
-                "instance_translation".to_string(),
-                InstanceBuffer::new_with_data(
-                    &self.context,
-                    &indices
-                        .iter()
-                        .map(|i| self.instances.transformations[*i])
-                        .map(|t| t.w.truncate())
-                        .collect::<Vec<_>>(),
-                ),
-            );
-        } else {
-            let mut row1 = Vec::new();
-            let mut row2 = Vec::new();
-            let mut row3 = Vec::new();
-            for transformation in indices.iter().map(|i| self.instances.transformations[*i]) {
-                row1.push(transformation.row(0));
-                row2.push(transformation.row(1));
-                row3.push(transformation.row(2));
-            }
-
-            instance_buffers.insert(
-                "row1".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &row1),
-            );
-            instance_buffers.insert(
-                "row2".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &row2),
-            );
-            instance_buffers.insert(
-                "row3".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &row3),
-            );
-        }
-
-        if let Some(texture_transforms) = &self.instances.texture_transformations {
-            let mut instance_tex_transform1 = Vec::new();
-            let mut instance_tex_transform2 = Vec::new();
-            for texture_transform in indices.iter().map(|i| texture_transforms[*i]) {
-                instance_tex_transform1.push(vec3(
-                    texture_transform.x.x,
-                    texture_transform.y.x,
-                    texture_transform.z.x,
-                ));
-                instance_tex_transform2.push(vec3(
-                    texture_transform.x.y,
-                    texture_transform.y.y,
-                    texture_transform.z.y,
-                ));
-            }
-            instance_buffers.insert(
-                "tex_transform_row1".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &instance_tex_transform1),
-            );
-            instance_buffers.insert(
-                "tex_transform_row2".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &instance_tex_transform2),
-            );
-        }
-        if let Some(instance_colors) = &self.instances.colors {
-            // Create the re-ordered color buffer by depth.
-            let ordered_instance_colors = indices
-                .iter()
-                .map(|i| instance_colors[*i])
-                .collect::<Vec<Color>>();
-            instance_buffers.insert(
-                "instance_color".to_string(),
-                InstanceBuffer::new_with_data(&self.context, &ordered_instance_colors),
-            );
-        }
-        instance_buffers
+        create_instance_buffers(&self.context, &self.instances, &indices)
     }
 
     fn draw(
@@ -273,6 +564,7 @@ impl InstancedMesh {
         camera: &Camera,
         attributes: FragmentAttributes,
         instance_buffers: &HashMap<String, InstanceBuffer>,
+        instance_count: u32,
     ) {
         if attributes.normal && instance_buffers.contains_key("instance_translation") {
             if let Some(inverse) = self.current_transformation.invert() {
@@ -293,6 +585,7 @@ impl InstancedMesh {
             "tex_transform_row1",
             "tex_transform_row2",
             "instance_color",
+            "instance_start_time",
         ] {
             if program.requires_attribute(attribute_name) {
                 program.use_instance_attribute(
@@ -302,22 +595,18 @@ impl InstancedMesh {
                 );
             }
         }
-        self.base_mesh.draw_instanced(
-            program,
-            render_states,
-            camera,
-            attributes,
-            self.instance_count,
-        );
+        self.base_mesh
+            .draw_instanced(program, render_states, camera, attributes, instance_count);
     }
 
     fn vertex_shader_source(
         &self,
         required_attributes: FragmentAttributes,
         instance_buffers: &HashMap<String, InstanceBuffer>,
+        displacement: bool,
     ) -> String {
         format!(
-            "{}{}{}{}{}{}{}{}",
+            "{}{}{}{}{}{}{}{}{}{}",
             if instance_buffers.contains_key("instance_translation") {
                 "#define USE_INSTANCE_TRANSLATIONS\n"
             } else {
@@ -338,6 +627,15 @@ impl InstancedMesh {
             } else {
                 ""
             },
+            if required_attributes.start_time
+                && instance_buffers.contains_key("instance_start_time")
+            {
+                "#define USE_START_TIME\n#define USE_INSTANCE_START_TIMES\n"
+            } else if required_attributes.start_time {
+                "#define USE_START_TIME\n"
+            } else {
+                ""
+            },
             if instance_buffers.contains_key("instance_color") && self.base_mesh.colors.is_some() {
                 "#define USE_VERTEX_COLORS\n#define USE_INSTANCE_COLORS\n"
             } else if instance_buffers.contains_key("instance_color") {
@@ -353,11 +651,204 @@ impl InstancedMesh {
                 ""
             },
             include_str!("../../core/shared.frag"),
+            if displacement {
+                concat!(
+                    "#define USE_DISPLACEMENT\n",
+                    include_str!("../material/shaders/displacement.frag")
+                )
+            } else {
+                ""
+            },
             include_str!("shaders/mesh.vert"),
         )
     }
 }
 
+fn create_instance_buffers(
+    context: &Context,
+    instances: &Instances,
+    indices: &[usize],
+) -> HashMap<String, InstanceBuffer> {
+    let mut instance_buffers = HashMap::new();
+    fill_instance_buffers(context, &mut instance_buffers, instances, indices);
+    instance_buffers.retain(|name, _| names.contains(name));
+}
+
+///
+/// Fills the instance buffers with the given instances in the order of the given indices.
+/// Existing buffers are reused, so the memory is only reallocated if a buffer does not have capacity for the data,
+/// and buffers for attributes which are no longer used are removed.
+///
+fn fill_instance_buffers(
+    context: &Context,
+    instance_buffers: &mut HashMap<String, InstanceBuffer>,
+    instances: &Instances,
+    indices: &[usize],
+) {
+    let mut fill = |name: &str, data: &dyn Fn(&mut InstanceBuffer)| {
+        data(
+            instance_buffers
+                .entry(name.to_string())
+                .or_insert_with(|| InstanceBuffer::new(context)),
+        )
+    };
+
+    let translation_only = indices
+        .iter()
+        .all(|i| is_translation(&instances.transformations[*i]));
+    if translation_only {
+        fill("instance_translation", &|buffer| {
+            buffer.fill(
+                &indices
+                    .iter()
+                    .map(|i| instances.transformations[*i].w.truncate())
+                    .collect::<Vec<_>>(),
+            )
+        });
+    } else {
+        let mut row1 = Vec::new();
+        let mut row2 = Vec::new();
+        let mut row3 = Vec::new();
+        for transformation in indices.iter().map(|i| instances.transformations[*i]) {
+            row1.push(transformation.row(0));
+            row2.push(transformation.row(1));
+            row3.push(transformation.row(2));
+        }
+        fill("row1", &|buffer| buffer.fill(&row1));
+        fill("row2", &|buffer| buffer.fill(&row2));
+        fill("row3", &|buffer| buffer.fill(&row3));
+    }
+
+    if let Some(texture_transforms) = &instances.texture_transformations {
+        let mut instance_tex_transform1 = Vec::new();
+        let mut instance_tex_transform2 = Vec::new();
+        for texture_transform in indices.iter().map(|i| texture_transforms[*i]) {
+            instance_tex_transform1.push(vec3(
+                texture_transform.x.x,
+                texture_transform.y.x,
+                texture_transform.z.x,
+            ));
+            instance_tex_transform2.push(vec3(
+                texture_transform.x.y,
+                texture_transform.y.y,
+                texture_transform.z.y,
+            ));
+        }
+        fill("tex_transform_row1", &|buffer| {
+            buffer.fill(&instance_tex_transform1)
+        });
+        fill("tex_transform_row2", &|buffer| {
+            buffer.fill(&instance_tex_transform2)
+        });
+    }
+    if let Some(instance_colors) = &instances.colors {
+        // Create the re-ordered color buffer by depth.
+        let ordered_instance_colors = indices
+            .iter()
+            .map(|i| instance_colors[*i])
+            .collect::<Vec<Color>>();
+        fill("instance_color", &|buffer| {
+            buffer.fill(&ordered_instance_colors)
+        });
+    }
+    if let Some(start_times) = &instances.start_times {
+        fill("instance_start_time", &|buffer| {
+            buffer.fill(&indices.iter().map(|i| start_times[*i]).collect::<Vec<_>>())
+        });
+    }
+
+    // The layout of the transformations can change and the optional attributes can be removed
+    instance_buffers.retain(|name, _| match name.as_str() {
+        "instance_translation" => translation_only,
+        "row1" | "row2" | "row3" => !translation_only,
+        "tex_transform_row1" | "tex_transform_row2" => instances.texture_transformations.is_some(),
+        "instance_color" => instances.colors.is_some(),
+        "instance_start_time" => instances.start_times.is_some(),
+        _ => false,
+    });
+}
+
+fn is_translation(transformation: &Mat4) -> bool {
+    Mat3::from_cols(
+        transformation.x.truncate(),
+        transformation.y.truncate(),
+        transformation.z.truncate(),
+    )
+    .is_identity()
+}
+
+///
+/// [Instances] which are transferred to the GPU once and can be rendered by several [InstancedMesh]es, see [InstancedMesh::new_with_shared_instances].
+/// This is useful when the same instances are rendered with different geometries, for example the trunk and the leaves of a tree
+/// or different levels of detail, since the instance buffers are only uploaded and stored once.
+/// Cloning creates another reference to the same instances and updating the instances affects all meshes using them.
+///
+/// The instances are not ordered by the distance to the camera before rendering, so transparent instances might not blend correctly.
+///
+#[derive(Clone)]
+pub struct SharedInstances {
+    context: Context,
+    data: Arc<RwLock<SharedInstancesData>>,
+}
+
+struct SharedInstancesData {
+    instances: Instances,
+    buffers: HashMap<String, InstanceBuffer>,
+    version: u64,
+}
+
+impl SharedInstances {
+    ///
+    /// Transfers the given instances to the GPU.
+    ///
+    pub fn new(context: &Context, instances: &Instances) -> Self {
+        #[cfg(debug_assertions)]
+        instances.validate().expect("invalid instances");
+        Self {
+            context: context.clone(),
+            data: Arc::new(RwLock::new(SharedInstancesData {
+                buffers: create_instance_buffers(
+                    context,
+                    instances,
+                    &(0..instances.transformations.len()).collect::<Vec<_>>(),
+                ),
+                instances: instances.clone(),
+                version: 0,
+            })),
+        }
+    }
+
+    ///
+    /// Replaces the instances and transfers them to the GPU, which affects all meshes rendering these instances.
+    ///
+    pub fn update(&self, instances: &Instances) {
+        #[cfg(debug_assertions)]
+        instances.validate().expect("invalid instances");
+        let mut data = self.data.write().unwrap();
+        data.buffers = create_instance_buffers(
+            &self.context,
+            instances,
+            &(0..instances.transformations.len()).collect::<Vec<_>>(),
+        );
+        data.instances = instances.clone();
+        data.version += 1;
+    }
+
+    ///
+    /// Returns a copy of the current instances.
+    ///
+    pub fn instances(&self) -> Instances {
+        self.data.read().unwrap().instances.clone()
+    }
+
+    ///
+    /// Returns the number of instances.
+    ///
+    pub fn count(&self) -> u32 {
+        self.data.read().unwrap().instances.count()
+    }
+}
+
 impl<'a> IntoIterator for &'a InstancedMesh {
     type Item = &'a dyn Geometry;
     type IntoIter = std::iter::Once<&'a dyn Geometry>;
@@ -369,7 +860,20 @@ impl<'a> IntoIterator for &'a InstancedMesh {
 
 impl Geometry for InstancedMesh {
     fn aabb(&self) -> AxisAlignedBoundingBox {
-        let mut aabb = self.aabb;
+        let mut aabb = if let Some(shared_instances) = &self.shared_instances {
+            let data = shared_instances.data.read().unwrap();
+            let mut shared_aabb = self.shared_aabb.write().unwrap();
+            match *shared_aabb {
+                Some((version, aabb)) if version == data.version => aabb,
+                _ => {
+                    let aabb = self.instances_aabb(&data.instances.transformations);
+                    *shared_aabb = Some((data.version, aabb));
+                    aabb
+                }
+            }
+        } else {
+            self.aabb
+        };
         aabb.transform(&self.current_transformation);
         aabb
     }
@@ -393,28 +897,37 @@ impl Geometry for InstancedMesh {
             None
         };
 
-        self.update_instance_buffers(update_pose);
-        let instance_buffers = &self
-            .instance_buffers
-            .read()
-            .expect("failed to acquire read access")
-            .0;
-
-        let fragment_shader = material.fragment_shader(lights);
-        let vertex_shader_source =
-            self.vertex_shader_source(fragment_shader.attributes, instance_buffers);
-        self.context
-            .program(vertex_shader_source, fragment_shader.source, |program| {
-                material.use_uniforms(program, camera, lights);
-                self.draw(
-                    program,
-                    material.render_states(),
-                    camera,
+        let margin = material.max_displacement().unwrap_or(0.0);
+        self.use_instance_buffers(
+            camera,
+            update_pose,
+            margin,
+            |instance_buffers, instance_count| {
+                let fragment_shader = material.fragment_shader(lights);
+                let vertex_shader_source = self.vertex_shader_source(
                     fragment_shader.attributes,
                     instance_buffers,
+                    material.max_displacement().is_some(),
                 );
-            })
-            .expect("Failed compiling shader");
+                let (vertex_shader_source, fragment_shader_source, clip_planes) =
+                    clip_shader_source(&self.context, vertex_shader_source, fragment_shader.source);
+                self.context
+                    .program(vertex_shader_source, fragment_shader_source, |program| {
+                        material.use_uniforms(program, camera, lights);
+                        draw_with_clip_planes(&self.context, program, &clip_planes, || {
+                            self.draw(
+                                program,
+                                material.render_states(),
+                                camera,
+                                fragment_shader.attributes,
+                                instance_buffers,
+                                instance_count,
+                            );
+                        });
+                    })
+                    .expect("Failed compiling shader");
+            },
+        );
     }
 
     fn render_with_post_material(
@@ -432,28 +945,40 @@ impl Geometry for InstancedMesh {
             None
         };
 
-        self.update_instance_buffers(update_pose);
-        let instance_buffers = &self
-            .instance_buffers
-            .read()
-            .expect("failed to acquire read access")
-            .0;
-
-        let fragment_shader = material.fragment_shader(lights, color_texture, depth_texture);
-        let vertex_shader_source =
-            self.vertex_shader_source(fragment_shader.attributes, instance_buffers);
-        self.context
-            .program(vertex_shader_source, fragment_shader.source, |program| {
-                material.use_uniforms(program, camera, lights, color_texture, depth_texture);
-                self.draw(
-                    program,
-                    material.render_states(),
-                    camera,
-                    fragment_shader.attributes,
-                    instance_buffers,
-                );
-            })
-            .expect("Failed compiling shader");
+        self.use_instance_buffers(
+            camera,
+            update_pose,
+            0.0,
+            |instance_buffers, instance_count| {
+                let fragment_shader =
+                    material.fragment_shader(lights, color_texture, depth_texture);
+                let vertex_shader_source =
+                    self.vertex_shader_source(fragment_shader.attributes, instance_buffers, false);
+                let (vertex_shader_source, fragment_shader_source, clip_planes) =
+                    clip_shader_source(&self.context, vertex_shader_source, fragment_shader.source);
+                self.context
+                    .program(vertex_shader_source, fragment_shader_source, |program| {
+                        material.use_uniforms(
+                            program,
+                            camera,
+                            lights,
+                            color_texture,
+                            depth_texture,
+                        );
+                        draw_with_clip_planes(&self.context, program, &clip_planes, || {
+                            self.draw(
+                                program,
+                                material.render_states(),
+                                camera,
+                                fragment_shader.attributes,
+                                instance_buffers,
+                                instance_count,
+                            );
+                        });
+                    })
+                    .expect("Failed compiling shader");
+            },
+        );
     }
 }
 
@@ -472,6 +997,8 @@ pub struct Instances {
     pub texture_transformations: Option<Vec<Mat3>>,
     /// Colors multiplied onto the base color of each instance.
     pub colors: Option<Vec<Color>>,
+    /// The start time of the animation of each instance in seconds, for example a [Flipbook] animation, such that the instances are not animated in sync.
+    pub start_times: Option<Vec<f32>>,
 }
 
 impl Instances {
@@ -499,6 +1026,7 @@ impl Instances {
         )?;
         buffer_check(Some(self.transformations.len()), "transformations")?;
         buffer_check(self.colors.as_ref().map(|b| b.len()), "colors")?;
+        buffer_check(self.start_times.as_ref().map(|b| b.len()), "start times")?;
 
         Ok(())
     }
@@ -533,4 +1061,220 @@ mod test {
         let res = InstancedMesh::ordered_indices_back_to_front(distances.len(), &distances);
         assert_eq!(res, vec![0, 2, 1, 3, 4]);
     }
+
+    #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_normals_of_non_uniformly_scaled_sphere() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let sphere = CpuMesh::sphere(64);
+        let scale = Mat4::from_nonuniform_scale(1.0, 3.0, 1.0);
+        let mut mesh = Gm::new(Mesh::new(&context, &sphere), NormalMaterial::default());
+        mesh.set_transformation(scale);
+        let instanced_mesh = Gm::new(
+            InstancedMesh::new(
+                &context,
+                &Instances {
+                    transformations: vec![scale],
+                    ..Default::default()
+                },
+                &sphere,
+            ),
+            NormalMaterial::default(),
+        );
+
+        // 32 pixels per unit, so the ellipsoid covers the entire viewport
+        let (width, height) = (64, 192);
+        let camera = Camera::new_orthographic(
+            Viewport::new_at_origo(width, height),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            6.0,
+            0.1,
+            10.0,
+        );
+        let mut texture = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            width,
+            height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &context,
+            width,
+            height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+
+        for object in [&mesh as &dyn Object, &instanced_mesh] {
+            let pixels = RenderTarget::new(
+                texture.as_color_target(None),
+                depth_texture.as_depth_target(),
+            )
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+            .render(&camera, std::iter::once(object), &[])
+            .read_color::<[u8; 4]>();
+
+            for (px, py) in [(48, 48), (16, 120), (40, 20), (32, 96)] {
+                // The rows are read from the top
+                let x = (px as f32 + 0.5) / 32.0 - 1.0;
+                let y = 3.0 - (py as f32 + 0.5) / 32.0;
+                let z = (1.0 - x * x - y * y / 9.0).sqrt();
+                // The gradient of x^2 + y^2 / 9 + z^2
+                let expected = vec3(x, y / 9.0, z).normalize();
+                let pixel = pixels[(py * width + px) as usize];
+                let normal = vec3(
+                    pixel[0] as f32 / 255.0 * 2.0 - 1.0,
+                    pixel[1] as f32 / 255.0 * 2.0 - 1.0,
+                    pixel[2] as f32 / 255.0 * 2.0 - 1.0,
+                );
+                assert!(
+                    normal.distance(expected) < 0.05,
+                    "{:?} != {:?} at ({}, {})",
+                    normal,
+                    expected,
+                    x,
+                    y
+                );
+            }
+        }
+    }
+
+    #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_shared_instances() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let shared_instances = SharedInstances::new(
+            &context,
+            &Instances {
+                transformations: vec![Mat4::from_translation(vec3(-2.0, 0.0, 0.0))],
+                ..Default::default()
+            },
+        );
+        let material = ColorMaterial {
+            color: Color::WHITE,
+            ..Default::default()
+        };
+        let cube = Gm::new(
+            InstancedMesh::new_with_shared_instances(&context, &shared_instances, &CpuMesh::cube()),
+            material.clone(),
+        );
+        let sphere = Gm::new(
+            InstancedMesh::new_with_shared_instances(
+                &context,
+                &shared_instances,
+                &CpuMesh::sphere(16),
+            ),
+            material,
+        );
+
+        // 8 pixels per unit, centered at the origin
+        let (width, height) = (64, 32);
+        let camera = Camera::new_orthographic(
+            Viewport::new_at_origo(width, height),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            4.0,
+            0.1,
+            10.0,
+        );
+        let mut texture = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            width,
+            height,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &context,
+            width,
+            height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut render = |object: &dyn Object| {
+            let pixels = RenderTarget::new(
+                texture.as_color_target(None),
+                depth_texture.as_depth_target(),
+            )
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+            .render(&camera, std::iter::once(object), &[])
+            .read_color::<[u8; 4]>();
+            // The pixels at x = -2 and x = 2 in the middle row
+            let row = (height / 2 * width) as usize;
+            (pixels[row + 16][0], pixels[row + 48][0])
+        };
+
+        for object in [&cube as &dyn Object, &sphere] {
+            assert_eq!(render(object), (255, 0));
+        }
+        shared_instances.update(&Instances {
+            transformations: vec![Mat4::from_translation(vec3(2.0, 0.0, 0.0))],
+            ..Default::default()
+        });
+        for object in [&cube as &dyn Object, &sphere] {
+            assert_eq!(render(object), (0, 255));
+            assert!(object.aabb().min().x > 0.0);
+        }
+    }
+
+    #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_bounding_sphere_culling() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let camera = Camera::new_perspective(
+            Viewport::new_at_origo(100, 100),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            degrees(90.0),
+            0.1,
+            100.0,
+        );
+        let mut mesh = InstancedMesh::new(
+            &context,
+            &Instances {
+                transformations: vec![
+                    Mat4::from_translation(vec3(0.0, 0.0, 0.0)),
+                    Mat4::from_translation(vec3(20.0, 0.0, 0.0)),
+                    Mat4::from_translation(vec3(0.0, 0.0, 10.0)),
+                    // Partly inside the view since it is scaled
+                    Mat4::from_translation(vec3(8.0, 0.0, 0.0)) * Mat4::from_scale(3.0),
+                ],
+                ..Default::default()
+            },
+            &CpuMesh::cube(),
+        );
+        assert_eq!(mesh.bounding_sphere(), None);
+        mesh.set_bounding_sphere(Vec3::zero(), 3.0f32.sqrt());
+        assert_eq!(
+            mesh.visible_instances(&camera, Vec3::zero(), 3.0f32.sqrt(), 0.0),
+            vec![0, 3]
+        );
+        mesh.use_instance_buffers(&camera, None, 0.0, |buffers, count| {
+            assert_eq!(count, 2);
+            assert_eq!(buffers["row1"].instance_count(), 2);
+            assert!(!buffers.contains_key("instance_translation"));
+        });
+        mesh.set_transformation(Mat4::from_translation(vec3(-20.0, 0.0, 0.0)));
+        assert_eq!(
+            mesh.visible_instances(&camera, Vec3::zero(), 3.0f32.sqrt(), 0.0),
+            vec![1]
+        );
+        // The buffers are refilled and only contain the translation since the visible instance is not scaled
+        mesh.use_instance_buffers(&camera, None, 0.0, |buffers, count| {
+            assert_eq!(count, 1);
+            assert_eq!(buffers["instance_translation"].instance_count(), 1);
+            assert!(!buffers.contains_key("row1"));
+        });
+    }
 }
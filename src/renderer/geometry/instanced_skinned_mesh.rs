@@ -0,0 +1,365 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::sync::{Arc, RwLock};
+
+use super::{clip_shader_source, draw_with_clip_planes, BaseMesh};
+
+///
+/// The skin of a [CpuMesh], ie. which joints of a [Skeleton] move each vertex and how much.
+///
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CpuSkin {
+    /// The indices of the up to four joints which move each vertex.
+    pub joints: Vec<[u16; 4]>,
+    /// The weight of each of the four joints for each vertex, which should sum to one. Unused joints must have a zero weight.
+    pub weights: Vec<Vec4>,
+}
+
+///
+/// Similar to [InstancedMesh], except that each instance is deformed by its own pose of a [Skeleton],
+/// so for example a crowd of animated characters can be rendered in a single draw call.
+/// The poses are sampled by a [CrowdAnimator] and uploaded to a texture using [InstancedSkinnedMesh::update_joints], where each instance fetches its joint matrices using its instance index.
+///
+/// The instances are not sorted by depth, so the mesh should be rendered with an opaque material.
+/// The bounding box is computed from the mesh in the bind pose, padded by half the size of it, so it also contains most animated poses.
+///
+pub struct InstancedSkinnedMesh {
+    context: Context,
+    base_mesh: BaseMesh,
+    joints: VertexBuffer,
+    weights: VertexBuffer,
+    joint_matrices: Arc<RwLock<Texture2D>>,
+    rows: [InstanceBuffer; 3],
+    instance_transformations: Vec<Mat4>,
+    joint_count: u32,
+    aabb_local: AxisAlignedBoundingBox,
+    aabb: AxisAlignedBoundingBox,
+    transformation: Mat4,
+}
+
+impl InstancedSkinnedMesh {
+    ///
+    /// Creates a new instanced skinned mesh from the given [CpuMesh] and [CpuSkin] with an instance for each of the given transformations,
+    /// which animates each instance using the poses of the corresponding instance in the given [CrowdAnimator].
+    ///
+    /// # Panic
+    /// Will panic if the skin does not have joints and weights for each vertex or if the number of transformations does not match the number of instances in the animator.
+    ///
+    pub fn new(
+        context: &Context,
+        cpu_mesh: &CpuMesh,
+        skin: &CpuSkin,
+        transformations: &[Mat4],
+        animator: &CrowdAnimator,
+    ) -> Self {
+        let joint_count = animator.skeleton().joint_count().max(1) as u32;
+        let instance_count = animator.instance_count().max(1) as u32;
+        let joint_matrices = Arc::new(RwLock::new(Texture2D::new_empty::<[f32; 4]>(
+            context,
+            4 * joint_count,
+            instance_count,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        )));
+        let mut mesh = Self::new_with_joint_matrices(
+            context,
+            cpu_mesh,
+            skin,
+            transformations,
+            joint_matrices,
+            joint_count,
+        );
+        mesh.update_joints(animator);
+        mesh
+    }
+
+    ///
+    /// Creates a new instanced skinned mesh from the given [CpuMesh] and [CpuSkin] which uses the same instances and poses as this mesh,
+    /// for example for another part of the same character with a different material.
+    /// Calling [InstancedSkinnedMesh::update_joints] on either mesh updates the poses of both.
+    ///
+    /// # Panic
+    /// Will panic if the skin does not have joints and weights for each vertex.
+    ///
+    pub fn new_sharing_joints(&self, cpu_mesh: &CpuMesh, skin: &CpuSkin) -> Self {
+        let mut mesh = Self::new_with_joint_matrices(
+            &self.context,
+            cpu_mesh,
+            skin,
+            &self.instance_transformations,
+            self.joint_matrices.clone(),
+            self.joint_count,
+        );
+        mesh.set_transformation(self.transformation);
+        mesh
+    }
+
+    fn new_with_joint_matrices(
+        context: &Context,
+        cpu_mesh: &CpuMesh,
+        skin: &CpuSkin,
+        transformations: &[Mat4],
+        joint_matrices: Arc<RwLock<Texture2D>>,
+        joint_count: u32,
+    ) -> Self {
+        let vertex_count = cpu_mesh.vertex_count();
+        if skin.joints.len() != vertex_count || skin.weights.len() != vertex_count {
+            panic!(
+                "the skin must have joints and weights for each of the {} vertices (got {} joints and {} weights)",
+                vertex_count,
+                skin.joints.len(),
+                skin.weights.len()
+            );
+        }
+        let mut mesh = Self {
+            context: context.clone(),
+            base_mesh: BaseMesh::new(context, cpu_mesh),
+            joints: VertexBuffer::new_with_data(
+                context,
+                &skin
+                    .joints
+                    .iter()
+                    .map(|j| vec4(j[0] as f32, j[1] as f32, j[2] as f32, j[3] as f32))
+                    .collect::<Vec<_>>(),
+            ),
+            weights: VertexBuffer::new_with_data(context, &skin.weights),
+            joint_matrices,
+            rows: [
+                InstanceBuffer::new(context),
+                InstanceBuffer::new(context),
+                InstanceBuffer::new(context),
+            ],
+            instance_transformations: Vec::new(),
+            joint_count,
+            aabb_local: cpu_mesh.compute_aabb(),
+            aabb: AxisAlignedBoundingBox::EMPTY,
+            transformation: Mat4::identity(),
+        };
+        mesh.set_instance_transformations(transformations);
+        mesh
+    }
+
+    ///
+    /// Returns the number of instances.
+    ///
+    pub fn instance_count(&self) -> u32 {
+        self.instance_transformations.len() as u32
+    }
+
+    ///
+    /// Returns the local to world transformation applied to all instances.
+    ///
+    pub fn transformation(&self) -> Mat4 {
+        self.transformation
+    }
+
+    ///
+    /// Set the local to world transformation applied to all instances.
+    /// This is applied before the transform for each instance.
+    ///
+    pub fn set_transformation(&mut self, transformation: Mat4) {
+        self.transformation = transformation;
+        self.update_aabb();
+    }
+
+    ///
+    /// Sets the transformation of each instance, for example the position and orientation of each character in a crowd.
+    /// The number of instances must be the same as the number of instances in the [CrowdAnimator] given to [InstancedSkinnedMesh::update_joints].
+    ///
+    pub fn set_instance_transformations(&mut self, transformations: &[Mat4]) {
+        for (i, row) in self.rows.iter_mut().enumerate() {
+            row.fill(&transformations.iter().map(|t| t.row(i)).collect::<Vec<_>>());
+        }
+        self.instance_transformations = transformations.to_vec();
+        self.update_aabb();
+    }
+
+    ///
+    /// Uploads the current poses of the instances in the given animator, which should be called each frame after [CrowdAnimator::update].
+    ///
+    /// # Panic
+    /// Will panic if the number of instances or joints in the animator is different from when this mesh was constructed.
+    ///
+    pub fn update_joints(&mut self, animator: &CrowdAnimator) {
+        if animator.instance_count() as u32 != self.instance_count()
+            || animator.skeleton().joint_count().max(1) as u32 != self.joint_count
+        {
+            panic!(
+                "the animator has {} instances and {} joints but the mesh was constructed for {} instances and {} joints",
+                animator.instance_count(),
+                animator.skeleton().joint_count(),
+                self.instance_count(),
+                self.joint_count
+            );
+        }
+        let data = animator
+            .joint_matrices()
+            .iter()
+            .flat_map(|m| [m.x.into(), m.y.into(), m.z.into(), m.w.into()])
+            .collect::<Vec<[f32; 4]>>();
+        if !data.is_empty() {
+            self.joint_matrices
+                .write()
+                .expect("failed acquiring write access")
+                .fill(&data);
+        }
+    }
+
+    fn update_aabb(&mut self) {
+        let padding = 0.5 * self.aabb_local.size();
+        let local = AxisAlignedBoundingBox::new_with_positions(&[
+            self.aabb_local.min() - padding,
+            self.aabb_local.max() + padding,
+        ]);
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        for transformation in self.instance_transformations.iter() {
+            let mut instance_aabb = local;
+            instance_aabb.transform(&(self.transformation * transformation));
+            aabb.expand_with_aabb(&instance_aabb);
+        }
+        self.aabb = aabb;
+    }
+
+    fn draw(
+        &self,
+        program: &Program,
+        render_states: RenderStates,
+        camera: &Camera,
+        attributes: FragmentAttributes,
+    ) {
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("modelMatrix", self.transformation);
+        for (name, row) in ["row1", "row2", "row3"].iter().zip(self.rows.iter()) {
+            program.use_instance_attribute(name, row);
+        }
+        program.use_vertex_attribute("joint_indices", &self.joints);
+        program.use_vertex_attribute("joint_weights", &self.weights);
+        program.use_texture(
+            "jointMatrices",
+            &self
+                .joint_matrices
+                .read()
+                .expect("failed acquiring read access"),
+        );
+        self.base_mesh.draw_instanced(
+            program,
+            render_states,
+            camera,
+            attributes,
+            self.instance_count(),
+        );
+    }
+
+    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+        format!(
+            "#define USE_INSTANCE_TRANSFORMS\n#define USE_SKINNING\n{}{}{}{}{}{}{}",
+            if required_attributes.normal {
+                "#define USE_NORMALS\n"
+            } else {
+                ""
+            },
+            if required_attributes.tangents {
+                "#define USE_TANGENTS\n"
+            } else {
+                ""
+            },
+            if required_attributes.uv {
+                "#define USE_UVS\n"
+            } else {
+                ""
+            },
+            if required_attributes.start_time {
+                "#define USE_START_TIME\n"
+            } else {
+                ""
+            },
+            if self.base_mesh.colors.is_some() {
+                "#define USE_VERTEX_COLORS\n"
+            } else {
+                ""
+            },
+            include_str!("../../core/shared.frag"),
+            include_str!("shaders/mesh.vert"),
+        )
+    }
+}
+
+impl<'a> IntoIterator for &'a InstancedSkinnedMesh {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for InstancedSkinnedMesh {
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.aabb
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        if self.instance_count() == 0 {
+            return;
+        }
+        let fragment_shader = material.fragment_shader(lights);
+        let (vertex_shader_source, fragment_shader_source, clip_planes) = clip_shader_source(
+            &self.context,
+            self.vertex_shader_source(fragment_shader.attributes),
+            fragment_shader.source,
+        );
+        self.context
+            .program(vertex_shader_source, fragment_shader_source, |program| {
+                material.use_uniforms(program, camera, lights);
+                draw_with_clip_planes(&self.context, program, &clip_planes, || {
+                    self.draw(
+                        program,
+                        material.render_states(),
+                        camera,
+                        fragment_shader.attributes,
+                    );
+                });
+            })
+            .expect("Failed compiling shader");
+    }
+
+    fn render_with_post_material(
+        &self,
+        material: &dyn PostMaterial,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        if self.instance_count() == 0 {
+            return;
+        }
+        let fragment_shader = material.fragment_shader(lights, color_texture, depth_texture);
+        let (vertex_shader_source, fragment_shader_source, clip_planes) = clip_shader_source(
+            &self.context,
+            self.vertex_shader_source(fragment_shader.attributes),
+            fragment_shader.source,
+        );
+        self.context
+            .program(vertex_shader_source, fragment_shader_source, |program| {
+                material.use_uniforms(program, camera, lights, color_texture, depth_texture);
+                draw_with_clip_planes(&self.context, program, &clip_planes, || {
+                    self.draw(
+                        program,
+                        material.render_states(),
+                        camera,
+                        fragment_shader.attributes,
+                    );
+                });
+            })
+            .expect("Failed compiling shader");
+    }
+}
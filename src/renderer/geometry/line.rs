@@ -64,6 +64,16 @@ impl Line {
         self.update();
     }
 
+    /// Get the number of vertices in the line.
+    pub fn vertex_count(&self) -> u32 {
+        self.mesh.vertex_count()
+    }
+
+    /// Get the number of triangles in the line.
+    pub fn triangle_count(&self) -> u32 {
+        self.mesh.triangle_count()
+    }
+
     fn update(&mut self) {
         let dx = self.pixel1.x - self.pixel0.x;
         let dy = self.pixel1.y - self.pixel0.y;
@@ -0,0 +1,250 @@
+use crate::renderer::*;
+use std::collections::HashMap;
+
+///
+/// The twelve edges of a cube given by the two corners they connect.
+/// The position of corner `i` is `(i & 1, (i >> 1) & 1, (i >> 2) & 1)`.
+///
+const EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (2, 3),
+    (4, 5),
+    (6, 7),
+    (0, 2),
+    (1, 3),
+    (4, 6),
+    (5, 7),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+///
+/// Extracts the isosurface where the given scalar field is equal to the given iso level using the marching cubes algorithm.
+/// The field is evaluated on a uniform grid inside the given bounds with the given number of cells along each axis.
+/// Values below the iso level are considered to be inside the surface, for example when the field is a signed distance function,
+/// so the triangles are counter clockwise and the normals, computed using central finite differences of the field, point towards higher values.
+///
+/// The resulting mesh is closed except where the surface is cut by the bounds, and vertices are shared between neighbouring triangles.
+///
+/// # Panic
+/// Will panic if the resolution is zero.
+///
+pub fn marching_cubes(
+    field: &dyn Fn(Vec3) -> f32,
+    bounds: AxisAlignedBoundingBox,
+    resolution: u32,
+    iso_level: f32,
+) -> CpuMesh {
+    if resolution == 0 {
+        panic!("the resolution of the marching cubes grid must be at least one");
+    }
+    let table = triangle_table();
+    let n = resolution as usize + 1;
+    let min = bounds.min();
+    let spacing = bounds.size() / resolution as f32;
+    let grid_position = |x: usize, y: usize, z: usize| {
+        min + vec3(
+            x as f32 * spacing.x,
+            y as f32 * spacing.y,
+            z as f32 * spacing.z,
+        )
+    };
+    let grid_index = |x: usize, y: usize, z: usize| x + n * (y + n * z);
+
+    let mut values = vec![0.0; n * n * n];
+    for z in 0..n {
+        for y in 0..n {
+            for x in 0..n {
+                values[grid_index(x, y, z)] = field(grid_position(x, y, z));
+            }
+        }
+    }
+
+    let mut positions = Vec::new();
+    let mut indices = Vec::new();
+    // The vertex on each grid edge, identified by the grid indices of the two end points
+    let mut vertices: HashMap<(usize, usize), u32> = HashMap::new();
+    let cells = resolution as usize;
+    for z in 0..cells {
+        for y in 0..cells {
+            for x in 0..cells {
+                let corners = [0, 1, 2, 3, 4, 5, 6, 7]
+                    .map(|i| grid_index(x + (i & 1), y + ((i >> 1) & 1), z + ((i >> 2) & 1)));
+                let case = corners
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| values[**c] < iso_level)
+                    .fold(0, |case, (i, _)| case | (1 << i));
+                for triangle in table[case].iter() {
+                    for edge in triangle {
+                        let (a, b) = EDGES[*edge as usize];
+                        let (a, b) = (corners[a], corners[b]);
+                        let index = *vertices.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                            let position =
+                                |i: usize| grid_position(i % n, (i / n) % n, i / (n * n));
+                            let t = (iso_level - values[a]) / (values[b] - values[a]);
+                            positions.push(position(a) + t * (position(b) - position(a)));
+                            positions.len() as u32 - 1
+                        });
+                        indices.push(index);
+                    }
+                }
+            }
+        }
+    }
+
+    let h = 0.5 * spacing.x.min(spacing.y).min(spacing.z);
+    let normals = positions
+        .iter()
+        .map(|p| {
+            let gradient = vec3(
+                field(p + vec3(h, 0.0, 0.0)) - field(p - vec3(h, 0.0, 0.0)),
+                field(p + vec3(0.0, h, 0.0)) - field(p - vec3(0.0, h, 0.0)),
+                field(p + vec3(0.0, 0.0, h)) - field(p - vec3(0.0, 0.0, h)),
+            );
+            if gradient.magnitude2() > 0.0 {
+                gradient.normalize()
+            } else {
+                vec3(0.0, 1.0, 0.0)
+            }
+        })
+        .collect();
+
+    CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        normals: Some(normals),
+        ..Default::default()
+    }
+}
+
+///
+/// Generates the lookup table which for each of the 256 combinations of inside and outside corners of a cube
+/// contains the triangles, given as the edges the vertices are placed on, that separates the inside corners from the outside corners.
+///
+/// On each side of the cube, the contour goes from each edge where the corners go from outside to inside to the next edge where they go from inside to outside,
+/// when going counter clockwise around the side seen from outside the cube. This separates diagonally opposite inside corners
+/// and, since neighbouring cubes make the same choice on a shared side, ensures that the resulting surface is closed.
+/// The contours on the sides are then connected into loops which are triangulated.
+///
+fn triangle_table() -> Vec<Vec<[u8; 3]>> {
+    let edge_id = |a: usize, b: usize| {
+        EDGES
+            .iter()
+            .position(|e| *e == (a.min(b), a.max(b)))
+            .unwrap()
+    };
+
+    // The four corners of each side of the cube in counter clockwise order seen from outside the cube
+    let mut sides = Vec::new();
+    for axis in 0..3 {
+        for side in 0..2 {
+            let (u, v) = if side == 1 {
+                ((axis + 1) % 3, (axis + 2) % 3)
+            } else {
+                ((axis + 2) % 3, (axis + 1) % 3)
+            };
+            sides.push(
+                [(0, 0), (1, 0), (1, 1), (0, 1)]
+                    .map(|(cu, cv)| (side << axis) | (cu << u) | (cv << v)),
+            );
+        }
+    }
+
+    (0..256)
+        .map(|case: usize| {
+            let inside = |corner: usize| case & (1 << corner) != 0;
+            // The next edge in the contour for each edge in the contour
+            let mut next = [None; 12];
+            for corners in sides.iter() {
+                let crossings = (0..4)
+                    .filter(|i| inside(corners[*i]) != inside(corners[(i + 1) % 4]))
+                    .collect::<Vec<_>>();
+                for (j, i) in crossings.iter().enumerate() {
+                    if !inside(corners[*i]) {
+                        let k = crossings[(j + 1) % crossings.len()];
+                        next[edge_id(corners[*i], corners[(i + 1) % 4])] =
+                            Some(edge_id(corners[k], corners[(k + 1) % 4]));
+                    }
+                }
+            }
+
+            let mut triangles = Vec::new();
+            let mut visited = [false; 12];
+            for start in 0..12 {
+                if visited[start] || next[start].is_none() {
+                    continue;
+                }
+                let mut contour = Vec::new();
+                let mut edge = start;
+                while !visited[edge] {
+                    visited[edge] = true;
+                    contour.push(edge as u8);
+                    edge = next[edge].unwrap();
+                }
+                for i in 1..contour.len() - 1 {
+                    triangles.push([contour[0], contour[i], contour[i + 1]]);
+                }
+            }
+            triangles
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_triangle_table() {
+        let table = triangle_table();
+        assert_eq!(table.len(), 256);
+        assert!(table[0].is_empty() && table[255].is_empty());
+        assert_eq!(table[1].len(), 1);
+        // Two diagonally opposite inside corners on the same side are separated
+        assert_eq!(table[0b1001].len(), 2);
+    }
+
+    #[test]
+    fn test_sphere() {
+        let radius = 0.8;
+        let mesh = marching_cubes(
+            &|p: Vec3| p.magnitude() - radius,
+            AxisAlignedBoundingBox::new_with_positions(&[
+                vec3(-1.0, -1.0, -1.0),
+                vec3(1.0, 1.0, 1.0),
+            ]),
+            16,
+            0.0,
+        );
+        let positions = mesh.positions.to_f32();
+        let normals = mesh.normals.as_ref().unwrap();
+        for (p, n) in positions.iter().zip(normals.iter()) {
+            assert!((p.magnitude() - radius).abs() < 0.02);
+            assert!(n.dot(p.normalize()) > 0.99);
+        }
+
+        let indices = match &mesh.indices {
+            Indices::U32(indices) => indices.clone(),
+            _ => unreachable!(),
+        };
+        let mut edges = HashMap::new();
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+            // Counter clockwise seen from the outside
+            assert!((b - a).cross(c - a).dot(a) > -1.0e-6);
+            for i in 0..3 {
+                *edges
+                    .entry((triangle[i], triangle[(i + 1) % 3]))
+                    .or_insert(0) += 1;
+            }
+        }
+        // Closed, ie. each directed edge appears once together with its opposite
+        for ((a, b), count) in edges.iter() {
+            assert_eq!(*count, 1);
+            assert_eq!(edges.get(&(*b, *a)), Some(&1));
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use crate::core::*;
 use crate::renderer::*;
 
-use super::BaseMesh;
+use super::{clip_shader_source, draw_with_clip_planes, BaseMesh};
 
 ///
 /// A triangle mesh [Geometry].
@@ -13,6 +13,8 @@ pub struct Mesh {
     transformation: Mat4,
     current_transformation: Mat4,
     animation: Option<Box<dyn Fn(f32) -> Mat4 + Send + Sync>>,
+    cpu_mesh: Option<CpuMesh>,
+    bvh: std::sync::RwLock<Option<BvhMesh>>,
 }
 
 impl Mesh {
@@ -29,9 +31,152 @@ impl Mesh {
             transformation: Mat4::identity(),
             current_transformation: Mat4::identity(),
             animation: None,
+            cpu_mesh: None,
+            bvh: std::sync::RwLock::new(None),
         }
     }
 
+    ///
+    /// Creates a new triangle mesh from the given [CpuMesh] like [Mesh::new], but also retains a copy of the CPU data
+    /// which can be queried later using for example [Mesh::positions] and [Mesh::indices], for example for snapping to vertices using [Mesh::closest_vertex].
+    /// The positions are stored as `f32` and the indices as `u32` regardless of the format of the given data.
+    ///
+    /// Note that the retained data is not updated if the GPU data is changed afterwards, for example using [Mesh::index_buffer_mut], use [Mesh::read_back] to get the current state.
+    ///
+    pub fn new_with_cpu_data(context: &Context, cpu_mesh: &CpuMesh) -> Self {
+        let mut mesh = Self::new(context, cpu_mesh);
+        mesh.cpu_mesh = Some(CpuMesh {
+            positions: Positions::F32(cpu_mesh.positions.to_f32()),
+            indices: match &cpu_mesh.indices {
+                Indices::U8(ind) => Indices::U32(ind.iter().map(|i| *i as u32).collect()),
+                Indices::U16(ind) => Indices::U32(ind.iter().map(|i| *i as u32).collect()),
+                Indices::U32(ind) => Indices::U32(ind.clone()),
+                Indices::None => Indices::None,
+            },
+            ..cpu_mesh.clone()
+        });
+        mesh
+    }
+
+    ///
+    /// Replaces the vertex data of this mesh with the given data, for example when the file it was loaded from has changed.
+    /// The buffers are reallocated, so the number of vertices and the attributes can change, while the transformation and animation are kept.
+    /// If the CPU data was retained (see [Mesh::new_with_cpu_data]), it is replaced by the new data.
+    ///
+    pub fn reload(&mut self, cpu_mesh: &CpuMesh) {
+        let mut mesh = if self.cpu_mesh.is_some() {
+            Self::new_with_cpu_data(&self.context, cpu_mesh)
+        } else {
+            Self::new(&self.context, cpu_mesh)
+        };
+        std::mem::swap(&mut self.base_mesh, &mut mesh.base_mesh);
+        std::mem::swap(&mut self.cpu_mesh, &mut mesh.cpu_mesh);
+        std::mem::swap(&mut self.bvh, &mut mesh.bvh);
+        self.aabb = mesh.aabb;
+    }
+
+    ///
+    /// Returns the CPU data retained when this mesh was constructed using [Mesh::new_with_cpu_data] or `None` if the data was not retained.
+    ///
+    pub fn cpu_mesh(&self) -> Option<&CpuMesh> {
+        self.cpu_mesh.as_ref()
+    }
+
+    ///
+    /// Returns the retained vertex positions in the local coordinate system of this mesh
+    /// or `None` if the CPU data was not retained, see [Mesh::new_with_cpu_data].
+    ///
+    pub fn positions(&self) -> Option<&[Vec3]> {
+        match &self.cpu_mesh.as_ref()?.positions {
+            Positions::F32(positions) => Some(positions),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns the retained indices, where each three consecutive indices define a triangle,
+    /// or `None` if the CPU data was not retained, see [Mesh::new_with_cpu_data], or if the mesh is not indexed.
+    ///
+    pub fn indices(&self) -> Option<&[u32]> {
+        match &self.cpu_mesh.as_ref()?.indices {
+            Indices::U32(indices) => Some(indices),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns the retained vertex normals in the local coordinate system of this mesh
+    /// or `None` if the CPU data was not retained, see [Mesh::new_with_cpu_data], or if the mesh has no normals.
+    ///
+    pub fn normals(&self) -> Option<&[Vec3]> {
+        self.cpu_mesh.as_ref()?.normals.as_deref()
+    }
+
+    ///
+    /// Returns the retained vertex position, transformed to world space using the current transformation of this mesh, which is closest to the given world space position.
+    /// Returns `None` if the CPU data was not retained, see [Mesh::new_with_cpu_data], or if the mesh has no vertices.
+    ///
+    pub fn closest_vertex(&self, position: Vec3) -> Option<Vec3> {
+        self.positions()?
+            .iter()
+            .map(|p| (self.current_transformation * p.extend(1.0)).truncate())
+            .min_by(|a, b| {
+                a.distance2(position)
+                    .partial_cmp(&b.distance2(position))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+    }
+
+    ///
+    /// Returns the closest intersection between the given world space ray and this mesh transformed by its current transformation,
+    /// or `None` if the ray does not hit the mesh or if the CPU data was not retained, see [Mesh::new_with_cpu_data].
+    /// The returned hit position is in world space and the distance is in units of the ray direction.
+    ///
+    /// The first call builds a [BvhMesh] from the retained CPU data which is cached and reused by subsequent calls,
+    /// so each call is fast even for meshes with many triangles.
+    ///
+    pub fn intersect_ray(&self, ray: &Ray) -> Option<RayHit> {
+        let positions = self.positions()?;
+        if self.bvh.read().unwrap().is_none() {
+            *self.bvh.write().unwrap() = Some(BvhMesh::new(positions, self.indices()));
+        }
+        let hit = self
+            .bvh
+            .read()
+            .unwrap()
+            .as_ref()
+            .unwrap()
+            .intersect_ray(&ray.transform(&self.current_transformation.invert()?))?;
+        Some(RayHit {
+            position: ray.at(hit.distance),
+            ..hit
+        })
+    }
+
+    ///
+    /// Downloads the current content of the GPU buffers of this mesh into a [CpuMesh], which for example is useful after the buffers have been updated.
+    /// The positions are in the local coordinate system of this mesh and the indices are always `u32`.
+    /// This waits for all previously issued work using the buffers to finish, so it should be used sparingly.
+    /// Returns an error if one of the buffers could not be mapped into client memory.
+    ///
+    pub fn read_back(&self) -> Result<CpuMesh, CoreError> {
+        self.base_mesh.read_back()
+    }
+
+    ///
+    /// Returns the number of vertices in this mesh.
+    ///
+    pub fn vertex_count(&self) -> u32 {
+        self.base_mesh.vertex_count()
+    }
+
+    ///
+    /// Returns the number of triangles in this mesh.
+    ///
+    pub fn triangle_count(&self) -> u32 {
+        self.base_mesh.triangle_count()
+    }
+
     pub(in crate::renderer) fn set_transformation_2d(&mut self, transformation: Mat3) {
         self.set_transformation(Mat4::new(
             transformation.x.x,
@@ -78,6 +223,30 @@ impl Mesh {
         self.animation = Some(Box::new(animation));
     }
 
+    ///
+    /// Returns the [IndexBuffer] which defines the triangles of this mesh
+    /// or `None` if each three consecutive vertices defines a triangle.
+    ///
+    pub fn index_buffer(&self) -> Option<&IndexBuffer> {
+        self.base_mesh.indices.as_ref()
+    }
+
+    ///
+    /// Returns a mutable reference to the [IndexBuffer] which defines the triangles of this mesh.
+    /// Use [IndexBuffer::update] to change the topology of the mesh without reallocating the buffer, for example for procedurally generated geometry.
+    ///
+    pub fn index_buffer_mut(&mut self) -> Option<&mut IndexBuffer> {
+        self.base_mesh.indices.as_mut()
+    }
+
+    ///
+    /// Replaces the [IndexBuffer] which defines the triangles of this mesh.
+    /// If `None`, each three consecutive vertices defines a triangle.
+    ///
+    pub fn set_index_buffer(&mut self, index_buffer: Option<IndexBuffer>) {
+        self.base_mesh.indices = index_buffer;
+    }
+
     fn draw(
         &self,
         program: &Program,
@@ -101,9 +270,63 @@ impl Mesh {
             .draw(program, render_states, camera, attributes);
     }
 
-    fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
+    ///
+    /// Draws this mesh with the given [Program] where the parameters of the draw call, for example the number of vertices and instances, are read from
+    /// the command at the given offset, in number of commands, in the given [DrawIndirectBuffer] instead of being specified on the CPU.
+    /// The command must be a [DrawElementsIndirectCommand] if this mesh has an [IndexBuffer] and otherwise a [DrawIndirectCommand].
+    /// Since the commands can be written by a [ComputeProgram], this makes it possible to for example decide on the GPU whether or not the mesh should be rendered.
+    ///
+    /// The vertex attributes used by the program are sent with the names `position`, `normal`, `tangent`, `uv_coordinates` and `color`
+    /// and the uniforms `viewProjection`, `modelMatrix` and `normalMatrix` are set if the program uses them.
+    ///
+    /// Must be called in the callback given as input to a [RenderTarget], [ColorTarget] or [DepthTarget] write method.
+    ///
+    /// # Panic
+    /// Will panic if the offset is not smaller than the capacity of the buffer or if the program uses an attribute which this mesh does not have.
+    /// Indirect draw calls are not supported on all platforms, see [Context::supports_draw_indirect].
+    ///
+    pub fn render_indirect(
+        &self,
+        program: &Program,
+        render_states: RenderStates,
+        viewport: Viewport,
+        camera: &Camera,
+        indirect: &DrawIndirectBuffer,
+        offset: usize,
+    ) {
+        if program.requires_uniform("normalMatrix") {
+            if let Some(inverse) = self.current_transformation.invert() {
+                program.use_uniform("normalMatrix", inverse.transpose());
+            } else {
+                // determinant is float zero
+                return;
+            }
+        }
+        program.use_uniform_if_required("viewProjection", camera.projection() * camera.view());
+        program.use_uniform_if_required("modelMatrix", self.current_transformation);
+        let attributes = FragmentAttributes {
+            normal: program.requires_attribute("normal"),
+            tangents: program.requires_attribute("tangent"),
+            uv: program.requires_attribute("uv_coordinates"),
+            ..FragmentAttributes::NONE
+        };
+        self.base_mesh.draw_indirect(
+            program,
+            render_states,
+            viewport,
+            attributes,
+            indirect,
+            offset,
+        );
+    }
+
+    fn vertex_shader_source(
+        &self,
+        required_attributes: FragmentAttributes,
+        displacement: bool,
+    ) -> String {
         format!(
-            "{}{}{}{}{}{}",
+            "{}{}{}{}{}{}{}{}",
             if required_attributes.normal {
                 "#define USE_NORMALS\n"
             } else {
@@ -119,12 +342,25 @@ impl Mesh {
             } else {
                 ""
             },
+            if required_attributes.start_time {
+                "#define USE_START_TIME\n"
+            } else {
+                ""
+            },
             if self.base_mesh.colors.is_some() {
                 "#define USE_VERTEX_COLORS\n"
             } else {
                 ""
             },
             include_str!("../../core/shared.frag"),
+            if displacement {
+                concat!(
+                    "#define USE_DISPLACEMENT\n",
+                    include_str!("../material/shaders/displacement.frag")
+                )
+            } else {
+                ""
+            },
             include_str!("shaders/mesh.vert"),
         )
     }
@@ -159,16 +395,23 @@ impl Geometry for Mesh {
         lights: &[&dyn Light],
     ) {
         let fragment_shader = material.fragment_shader(lights);
-        let vertex_shader_source = self.vertex_shader_source(fragment_shader.attributes);
+        let vertex_shader_source = self.vertex_shader_source(
+            fragment_shader.attributes,
+            material.max_displacement().is_some(),
+        );
+        let (vertex_shader_source, fragment_shader_source, clip_planes) =
+            clip_shader_source(&self.context, vertex_shader_source, fragment_shader.source);
         self.context
-            .program(vertex_shader_source, fragment_shader.source, |program| {
+            .program(vertex_shader_source, fragment_shader_source, |program| {
                 material.use_uniforms(program, camera, lights);
-                self.draw(
-                    program,
-                    material.render_states(),
-                    camera,
-                    fragment_shader.attributes,
-                );
+                draw_with_clip_planes(&self.context, program, &clip_planes, || {
+                    self.draw(
+                        program,
+                        material.render_states(),
+                        camera,
+                        fragment_shader.attributes,
+                    );
+                });
             })
             .expect("Failed compiling shader");
     }
@@ -182,17 +425,107 @@ impl Geometry for Mesh {
         depth_texture: Option<DepthTexture>,
     ) {
         let fragment_shader = material.fragment_shader(lights, color_texture, depth_texture);
-        let vertex_shader_source = self.vertex_shader_source(fragment_shader.attributes);
+        let vertex_shader_source = self.vertex_shader_source(fragment_shader.attributes, false);
+        let (vertex_shader_source, fragment_shader_source, clip_planes) =
+            clip_shader_source(&self.context, vertex_shader_source, fragment_shader.source);
         self.context
-            .program(vertex_shader_source, fragment_shader.source, |program| {
+            .program(vertex_shader_source, fragment_shader_source, |program| {
                 material.use_uniforms(program, camera, lights, color_texture, depth_texture);
-                self.draw(
-                    program,
-                    material.render_states(),
-                    camera,
-                    fragment_shader.attributes,
-                );
+                draw_with_clip_planes(&self.context, program, &clip_planes, || {
+                    self.draw(
+                        program,
+                        material.render_states(),
+                        camera,
+                        fragment_shader.attributes,
+                    );
+                });
             })
             .expect("Failed compiling shader");
     }
 }
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use crate::renderer::*;
+
+    #[test]
+    fn test_render_indirect() {
+        let context = crate::HeadlessContext::new().unwrap();
+        if !context.supports_draw_indirect() {
+            return;
+        }
+        let program = Program::from_source(
+            &context,
+            "uniform mat4 viewProjection;
+            uniform mat4 modelMatrix;
+            in vec3 position;
+            void main() {
+                gl_Position = viewProjection * modelMatrix * vec4(position, 1.0);
+            }",
+            "layout (location = 0) out vec4 color;
+            void main() {
+                color = vec4(1.0, 0.0, 0.0, 1.0);
+            }",
+        )
+        .unwrap();
+        // A square covering the right half of the viewport
+        let mut mesh = Mesh::new(&context, &CpuMesh::square());
+        mesh.set_transformation(
+            Mat4::from_translation(vec3(0.5, 0.0, 0.0))
+                * Mat4::from_nonuniform_scale(0.5, 1.0, 1.0),
+        );
+        let viewport = Viewport::new_at_origo(2, 1);
+        let camera = Camera::new_orthographic(
+            viewport,
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            1.0,
+            0.1,
+            10.0,
+        );
+        // The first command draws no instances and the second draws the whole square
+        let mut indirect = DrawIndirectBuffer::new(&context, 2);
+        indirect.fill_elements(&[
+            DrawElementsIndirectCommand {
+                count: 6,
+                instance_count: 0,
+                ..Default::default()
+            },
+            DrawElementsIndirectCommand {
+                count: 6,
+                instance_count: 1,
+                ..Default::default()
+            },
+        ]);
+        let render = |offset: usize| {
+            let mut texture = Texture2D::new_empty::<[u8; 4]>(
+                &context,
+                2,
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            texture
+                .as_color_target(None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+                .write(|| {
+                    mesh.render_indirect(
+                        &program,
+                        RenderStates::default(),
+                        viewport,
+                        &camera,
+                        &indirect,
+                        offset,
+                    );
+                })
+                .read::<[u8; 4]>()
+        };
+
+        assert_eq!(render(0), vec![[0, 0, 0, 255], [0, 0, 0, 255]]);
+        assert_eq!(render(1), vec![[0, 0, 0, 255], [255, 0, 0, 255]]);
+    }
+}
@@ -2,7 +2,7 @@ use crate::core::*;
 use crate::renderer::*;
 use std::collections::HashMap;
 
-use super::BaseMesh;
+use super::{clip_shader_source, draw_with_clip_planes, BaseMesh};
 
 ///
 /// Used for defining the attributes for each particle in a [ParticleSystem], for example its starting position and velocity.
@@ -19,6 +19,8 @@ pub struct Particles {
     pub texture_transforms: Option<Vec<Mat3>>,
     /// A custom color for each particle.
     pub colors: Option<Vec<Color>>,
+    /// The start time of the animation of each particle in seconds, for example a [Flipbook] animation, such that the particles are not animated in sync.
+    pub start_times: Option<Vec<f32>>,
 }
 
 impl Particles {
@@ -45,6 +47,7 @@ impl Particles {
             "texture transforms",
         )?;
         buffer_check(self.colors.as_ref().map(|b| b.len()), "colors")?;
+        buffer_check(self.start_times.as_ref().map(|b| b.len()), "start times")?;
         buffer_check(Some(self.start_positions.len()), "start_positions")?;
         buffer_check(Some(self.start_velocities.len()), "start_velocities")?;
 
@@ -121,6 +124,27 @@ impl ParticleSystem {
         self.transformation = transformation;
     }
 
+    ///
+    /// Returns the number of vertices in the geometry of a single particle.
+    ///
+    pub fn vertex_count(&self) -> u32 {
+        self.base_mesh.vertex_count()
+    }
+
+    ///
+    /// Returns the number of triangles in the geometry of a single particle.
+    ///
+    pub fn triangle_count(&self) -> u32 {
+        self.base_mesh.triangle_count()
+    }
+
+    ///
+    /// Returns the number of particles.
+    ///
+    pub fn instance_count(&self) -> u32 {
+        self.instance_count
+    }
+
     ///
     /// Set the particles attributes.
     ///
@@ -168,6 +192,12 @@ impl ParticleSystem {
                 InstanceBuffer::new_with_data(&self.context, instance_colors),
             );
         }
+        if let Some(start_times) = &particles.start_times {
+            self.instance_buffers.insert(
+                "instance_start_time".to_string(),
+                InstanceBuffer::new_with_data(&self.context, start_times),
+            );
+        }
     }
 
     fn draw(
@@ -198,6 +228,7 @@ impl ParticleSystem {
             "tex_transform_row1",
             "tex_transform_row2",
             "instance_color",
+            "instance_start_time",
         ] {
             if program.requires_attribute(attribute_name) {
                 program.use_instance_attribute(
@@ -218,7 +249,7 @@ impl ParticleSystem {
 
     fn vertex_shader_source(&self, required_attributes: FragmentAttributes) -> String {
         format!(
-            "#define PARTICLES\n{}{}{}{}{}{}{}",
+            "#define PARTICLES\n{}{}{}{}{}{}{}{}",
             if required_attributes.normal {
                 "#define USE_NORMALS\n"
             } else {
@@ -234,6 +265,15 @@ impl ParticleSystem {
             } else {
                 ""
             },
+            if required_attributes.start_time
+                && self.instance_buffers.contains_key("instance_start_time")
+            {
+                "#define USE_START_TIME\n#define USE_INSTANCE_START_TIMES\n"
+            } else if required_attributes.start_time {
+                "#define USE_START_TIME\n"
+            } else {
+                ""
+            },
             if self.instance_buffers.contains_key("instance_color")
                 && self.base_mesh.colors.is_some()
             {
@@ -278,15 +318,19 @@ impl Geometry for ParticleSystem {
     ) {
         let fragment_shader = material.fragment_shader(lights);
         let vertex_shader_source = self.vertex_shader_source(fragment_shader.attributes);
+        let (vertex_shader_source, fragment_shader_source, clip_planes) =
+            clip_shader_source(&self.context, vertex_shader_source, fragment_shader.source);
         self.context
-            .program(vertex_shader_source, fragment_shader.source, |program| {
+            .program(vertex_shader_source, fragment_shader_source, |program| {
                 material.use_uniforms(program, camera, lights);
-                self.draw(
-                    program,
-                    material.render_states(),
-                    camera,
-                    fragment_shader.attributes,
-                );
+                draw_with_clip_planes(&self.context, program, &clip_planes, || {
+                    self.draw(
+                        program,
+                        material.render_states(),
+                        camera,
+                        fragment_shader.attributes,
+                    );
+                });
             })
             .expect("Failed compiling shader");
     }
@@ -301,15 +345,19 @@ impl Geometry for ParticleSystem {
     ) {
         let fragment_shader = material.fragment_shader(lights, color_texture, depth_texture);
         let vertex_shader_source = self.vertex_shader_source(fragment_shader.attributes);
+        let (vertex_shader_source, fragment_shader_source, clip_planes) =
+            clip_shader_source(&self.context, vertex_shader_source, fragment_shader.source);
         self.context
-            .program(vertex_shader_source, fragment_shader.source, |program| {
+            .program(vertex_shader_source, fragment_shader_source, |program| {
                 material.use_uniforms(program, camera, lights, color_texture, depth_texture);
-                self.draw(
-                    program,
-                    material.render_states(),
-                    camera,
-                    fragment_shader.attributes,
-                );
+                draw_with_clip_planes(&self.context, program, &clip_planes, || {
+                    self.draw(
+                        program,
+                        material.render_states(),
+                        camera,
+                        fragment_shader.attributes,
+                    );
+                });
             })
             .expect("Failed compiling shader");
     }
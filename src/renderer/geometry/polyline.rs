@@ -0,0 +1,534 @@
+use crate::core::*;
+use crate::renderer::*;
+use std::f32::consts::PI;
+
+/// The maximum angle between the triangles of the round joins and caps.
+const ROUND_STEP: f32 = PI / 16.0;
+
+///
+/// How two segments of a [Polyline] are joined on the outer side of the turn.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineJoin {
+    /// The outer edges are extended until they meet, unless the distance from the point to the tip of the miter,
+    /// relative to half the thickness, exceeds the limit in which case the join is beveled.
+    Miter {
+        /// The maximum ratio between the length of the miter and half the thickness, 4 is a common choice.
+        limit: f32,
+    },
+    /// The outer corners are connected by a straight edge.
+    Bevel,
+    /// The outer corners are connected by a circular arc.
+    Round,
+}
+
+impl Default for LineJoin {
+    fn default() -> Self {
+        Self::Miter { limit: 4.0 }
+    }
+}
+
+///
+/// How the two ends of a [Polyline] are drawn.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LineCap {
+    /// The line ends exactly at the end points.
+    #[default]
+    Butt,
+    /// The line is extended by half the thickness beyond the end points.
+    Square,
+    /// The line ends with a half circle around the end points.
+    Round,
+}
+
+///
+/// A thick line strip through a list of points in the xy-plane, for example the graph of a function, with proper joins between the segments and caps at the ends.
+/// The points and the thickness are in the coordinate system of the camera, for example physical pixels when using the [camera2d] camera,
+/// so a line with a constant thickness on the screen while zooming is achieved by transforming the points to pixels and using [camera2d].
+///
+/// The line is expanded into triangles on the CPU whenever the points or the style change.
+/// The vertex buffers are only reallocated when the number of vertices grows beyond the capacity, so updating the points every frame is cheap.
+/// The texture coordinates are the distance along the line and the side of the line, where 0 is the left side, 1 is the right side and 0.5 is the center.
+/// A line with a transparent color can be drawn without overlapping itself using [Polyline::set_draw_once], which requires a stencil buffer on the render target.
+///
+pub struct Polyline {
+    context: Context,
+    positions: VertexBuffer,
+    uvs: VertexBuffer,
+    vertex_count: u32,
+    aabb: AxisAlignedBoundingBox,
+    points: Vec<Vec2>,
+    thickness: f32,
+    join: LineJoin,
+    cap: LineCap,
+    draw_once: bool,
+}
+
+impl Polyline {
+    ///
+    /// Constructs a new polyline through the given points with the given thickness, mitered joins and butt caps.
+    ///
+    pub fn new(context: &Context, points: &[Vec2], thickness: f32) -> Self {
+        let mut polyline = Self {
+            context: context.clone(),
+            positions: VertexBuffer::new(context),
+            uvs: VertexBuffer::new(context),
+            vertex_count: 0,
+            aabb: AxisAlignedBoundingBox::EMPTY,
+            points: points.to_vec(),
+            thickness,
+            join: LineJoin::default(),
+            cap: LineCap::default(),
+            draw_once: false,
+        };
+        polyline.update();
+        polyline
+    }
+
+    ///
+    /// Sets the points which the line goes through. Consecutive points at the same position are ignored.
+    ///
+    pub fn update_points(&mut self, points: &[Vec2]) {
+        self.points.clear();
+        self.points.extend_from_slice(points);
+        self.update();
+    }
+
+    ///
+    /// Returns the points which the line goes through.
+    ///
+    pub fn points(&self) -> &[Vec2] {
+        &self.points
+    }
+
+    ///
+    /// Sets the thickness of the line.
+    ///
+    pub fn set_thickness(&mut self, thickness: f32) {
+        self.thickness = thickness;
+        self.update();
+    }
+
+    ///
+    /// Returns the thickness of the line.
+    ///
+    pub fn thickness(&self) -> f32 {
+        self.thickness
+    }
+
+    ///
+    /// Sets how the segments are joined.
+    ///
+    pub fn set_join(&mut self, join: LineJoin) {
+        self.join = join;
+        self.update();
+    }
+
+    ///
+    /// Returns how the segments are joined.
+    ///
+    pub fn join(&self) -> LineJoin {
+        self.join
+    }
+
+    ///
+    /// Sets how the ends of the line are drawn.
+    ///
+    pub fn set_cap(&mut self, cap: LineCap) {
+        self.cap = cap;
+        self.update();
+    }
+
+    ///
+    /// Returns how the ends of the line are drawn.
+    ///
+    pub fn cap(&self) -> LineCap {
+        self.cap
+    }
+
+    ///
+    /// Sets whether each pixel is only drawn once, even where the triangles of the line overlap, for example at the joins and where the line crosses itself.
+    /// Otherwise a line with a transparent color is darker where it overlaps itself.
+    /// This uses the stencil buffer, so the render target must have a stencil buffer, for example using [SurfaceSettings::stencil_buffer](crate::SurfaceSettings::stencil_buffer),
+    /// where the stencil values are zero before rendering. The stencil values are reset to zero after the line is rendered.
+    /// Without a stencil buffer, the overlapping pixels are drawn multiple times.
+    ///
+    pub fn set_draw_once(&mut self, draw_once: bool) {
+        self.draw_once = draw_once;
+    }
+
+    ///
+    /// Returns whether each pixel is only drawn once, see [Polyline::set_draw_once].
+    ///
+    pub fn is_draw_once(&self) -> bool {
+        self.draw_once
+    }
+
+    /// Get the number of vertices in the triangles of the line.
+    pub fn vertex_count(&self) -> u32 {
+        self.vertex_count
+    }
+
+    /// Get the number of triangles in the line.
+    pub fn triangle_count(&self) -> u32 {
+        self.vertex_count / 3
+    }
+
+    fn update(&mut self) {
+        let (positions, uvs) = expand(&self.points, self.thickness, self.join, self.cap);
+        self.vertex_count = positions.len() as u32;
+        self.aabb = if positions.is_empty() {
+            AxisAlignedBoundingBox::EMPTY
+        } else {
+            AxisAlignedBoundingBox::new_with_positions(&positions)
+        };
+        if !positions.is_empty() {
+            // The memory of the buffers is reused when the data fits
+            self.positions.fill(&positions);
+            self.uvs.fill(&uvs);
+        }
+    }
+
+    fn draw(&self, program: &Program, render_states: RenderStates, camera: &Camera) {
+        let viewport = camera.viewport();
+        program.use_uniform("viewProjection", camera.view_projection());
+        let use_attributes = || {
+            program.use_vertex_attribute("position", &self.positions);
+            if program.requires_attribute("uv_coordinates") {
+                program.use_vertex_attribute("uv_coordinates", &self.uvs);
+            }
+        };
+        use_attributes();
+        if !self.draw_once {
+            program.draw_arrays(render_states, viewport, self.vertex_count);
+            return;
+        }
+
+        // Only draw where the stencil value is zero and increment it, then reset the stencil values by drawing the line again without writing color and depth
+        let stencil = |function, operation| StencilTest::Enabled {
+            function,
+            reference: 0,
+            read_mask: 0xFF,
+            write_mask: 0xFF,
+            fail: StencilOperation::Keep,
+            depth_fail: StencilOperation::Keep,
+            pass: operation,
+        };
+        self.context
+            .set_stencil_test(stencil(DepthTest::Equal, StencilOperation::Increment));
+        program.draw_arrays(render_states, viewport, self.vertex_count);
+        use_attributes();
+        self.context
+            .set_stencil_test(stencil(DepthTest::Always, StencilOperation::Zero));
+        program.draw_arrays(
+            RenderStates {
+                write_mask: WriteMask::NONE,
+                depth_test: DepthTest::Always,
+                blend: Blend::Disabled,
+                ..render_states
+            },
+            viewport,
+            self.vertex_count,
+        );
+        // Restore the default stencil function, operations and write mask
+        self.context.set_stencil_test(StencilTest::Disabled);
+    }
+
+    fn vertex_shader_source(&self) -> String {
+        include_str!("shaders/polyline.vert").to_owned()
+    }
+}
+
+impl<'a> IntoIterator for &'a Polyline {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Polyline {
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        self.context
+            .program(
+                self.vertex_shader_source(),
+                material.fragment_shader(lights).source,
+                |program| {
+                    material.use_uniforms(program, camera, lights);
+                    self.draw(program, material.render_states(), camera);
+                },
+            )
+            .expect("Failed compiling shader")
+    }
+
+    fn render_with_post_material(
+        &self,
+        material: &dyn PostMaterial,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        self.context
+            .program(
+                self.vertex_shader_source(),
+                material
+                    .fragment_shader(lights, color_texture, depth_texture)
+                    .source,
+                |program| {
+                    material.use_uniforms(program, camera, lights, color_texture, depth_texture);
+                    self.draw(program, material.render_states(), camera);
+                },
+            )
+            .expect("Failed compiling shader")
+    }
+
+    ///
+    /// Returns the [AxisAlignedBoundingBox] of the triangles of the line, including the joins and caps.
+    ///
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.aabb
+    }
+}
+
+///
+/// Expands the line through the given points into a list of triangles, returning the positions and texture coordinates of the vertices.
+///
+fn expand(points: &[Vec2], thickness: f32, join: LineJoin, cap: LineCap) -> (Vec<Vec3>, Vec<Vec2>) {
+    let mut triangles = Triangles::default();
+    let half_width = 0.5 * thickness;
+    let mut points = points.to_vec();
+    points.dedup_by(|a, b| a.distance2(*b) < 1.0e-12);
+    if points.len() < 2 || half_width <= 0.0 {
+        return (triangles.positions, triangles.uvs);
+    }
+
+    let directions: Vec<Vec2> = points
+        .windows(2)
+        .map(|p| (p[1] - p[0]).normalize())
+        .collect();
+    let mut distances = vec![0.0];
+    for p in points.windows(2) {
+        distances.push(distances.last().unwrap() + p[0].distance(p[1]));
+    }
+    let (first, last) = (0, points.len() - 1);
+    let (start_direction, end_direction) = (directions[0], directions[last - 1]);
+    if cap == LineCap::Square {
+        points[first] -= start_direction * half_width;
+        points[last] += end_direction * half_width;
+        distances[first] -= half_width;
+        distances[last] += half_width;
+    }
+
+    for (i, direction) in directions.iter().enumerate() {
+        let offset = normal(*direction) * half_width;
+        let (a, b) = (points[i], points[i + 1]);
+        let (ua, ub) = (distances[i], distances[i + 1]);
+        let (a_left, a_right) = ((a + offset, vec2(ua, 0.0)), (a - offset, vec2(ua, 1.0)));
+        let (b_left, b_right) = ((b + offset, vec2(ub, 0.0)), (b - offset, vec2(ub, 1.0)));
+        triangles.push([a_left, a_right, b_left]);
+        triangles.push([b_left, a_right, b_right]);
+    }
+
+    for i in 1..last {
+        let (incoming, outgoing) = (directions[i - 1], directions[i]);
+        let cross = incoming.perp_dot(outgoing);
+        let dot = incoming.dot(outgoing);
+        if cross.abs() < 1.0e-6 && dot > 0.0 {
+            // The segments continue in the same direction
+            continue;
+        }
+        // The outer side is to the right when turning left and vice versa
+        let side = if cross > 0.0 { -1.0 } else { 1.0 };
+        let (outer0, outer1) = (side * normal(incoming), side * normal(outgoing));
+        let center = (points[i], distances[i]);
+        let v = 0.5 - 0.5 * side;
+        let corner0 = (points[i] + outer0 * half_width, vec2(distances[i], v));
+        let corner1 = (points[i] + outer1 * half_width, vec2(distances[i], v));
+        match join {
+            LineJoin::Bevel => triangles.push([(center.0, vec2(center.1, 0.5)), corner0, corner1]),
+            LineJoin::Miter { limit } => {
+                let miter = (outer0 + outer1).normalize();
+                let cos_half_angle = miter.dot(outer0);
+                if cos_half_angle > 1.0e-6 && 1.0 / cos_half_angle <= limit {
+                    let tip = (
+                        points[i] + miter * (half_width / cos_half_angle),
+                        vec2(distances[i], v),
+                    );
+                    let center = (center.0, vec2(center.1, 0.5));
+                    triangles.push([center, corner0, tip]);
+                    triangles.push([center, tip, corner1]);
+                } else {
+                    triangles.push([(center.0, vec2(center.1, 0.5)), corner0, corner1]);
+                }
+            }
+            LineJoin::Round => {
+                // The arc turns the same way as the line, which is also well defined when the line turns around completely
+                let sweep = -side * cross.abs().atan2(dot);
+                triangles.fan(center, outer0, sweep, half_width, normal(incoming));
+            }
+        }
+    }
+
+    if cap == LineCap::Round {
+        let start_normal = normal(start_direction);
+        let end_normal = normal(end_direction);
+        triangles.fan(
+            (points[first], distances[first]),
+            start_normal,
+            PI,
+            half_width,
+            start_normal,
+        );
+        triangles.fan(
+            (points[last], distances[last]),
+            end_normal,
+            -PI,
+            half_width,
+            end_normal,
+        );
+    }
+    (triangles.positions, triangles.uvs)
+}
+
+/// The normal pointing to the left of the given direction.
+fn normal(direction: Vec2) -> Vec2 {
+    vec2(-direction.y, direction.x)
+}
+
+#[derive(Default)]
+struct Triangles {
+    positions: Vec<Vec3>,
+    uvs: Vec<Vec2>,
+}
+
+impl Triangles {
+    fn push(&mut self, vertices: [(Vec2, Vec2); 3]) {
+        for (position, uv) in vertices {
+            self.positions.push(position.extend(0.0));
+            self.uvs.push(uv);
+        }
+    }
+
+    ///
+    /// Adds a fan of triangles around the center, which is a position and a distance along the line,
+    /// from the given unit direction and through the given signed angle.
+    /// The side of the line of each vertex on the arc is given by the left normal of the line.
+    ///
+    fn fan(&mut self, center: (Vec2, f32), from: Vec2, sweep: f32, radius: f32, left: Vec2) {
+        // The small tolerance avoids an extra step due to rounding when the sweep is a multiple of the step
+        let steps = ((sweep.abs() / ROUND_STEP - 1.0e-3).ceil() as usize).max(1);
+        let vertex = |angle: f32| {
+            let (sin, cos) = angle.sin_cos();
+            let direction = vec2(from.x * cos - from.y * sin, from.x * sin + from.y * cos);
+            (
+                center.0 + direction * radius,
+                vec2(center.1, 0.5 - 0.5 * direction.dot(left)),
+            )
+        };
+        let center = (center.0, vec2(center.1, 0.5));
+        for step in 0..steps {
+            let angle0 = sweep * step as f32 / steps as f32;
+            let angle1 = sweep * (step + 1) as f32 / steps as f32;
+            self.push([center, vertex(angle0), vertex(angle1)]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bounds(positions: &[Vec3]) -> (Vec3, Vec3) {
+        let aabb = AxisAlignedBoundingBox::new_with_positions(positions);
+        (aabb.min(), aabb.max())
+    }
+
+    #[test]
+    fn test_segment() {
+        let points = [vec2(0.0, 0.0), vec2(10.0, 0.0)];
+        let (positions, uvs) = expand(&points, 2.0, LineJoin::default(), LineCap::Butt);
+        assert_eq!(positions.len(), 6);
+        assert_eq!(uvs.len(), 6);
+        assert_eq!(
+            bounds(&positions),
+            (vec3(0.0, -1.0, 0.0), vec3(10.0, 1.0, 0.0))
+        );
+
+        let (positions, uvs) = expand(&points, 2.0, LineJoin::default(), LineCap::Square);
+        assert_eq!(
+            bounds(&positions),
+            (vec3(-1.0, -1.0, 0.0), vec3(11.0, 1.0, 0.0))
+        );
+        assert!(uvs.iter().any(|uv| uv.x == -1.0) && uvs.iter().any(|uv| uv.x == 11.0));
+
+        let (positions, _) = expand(&points, 2.0, LineJoin::default(), LineCap::Round);
+        assert_eq!(positions.len(), 6 + 2 * 3 * 16);
+        let (min, max) = bounds(&positions);
+        assert!(min.distance(vec3(-1.0, -1.0, 0.0)) < 0.0001);
+        assert!(max.distance(vec3(11.0, 1.0, 0.0)) < 0.0001);
+
+        // Repeated points are ignored and a single point has no extent
+        let (positions, _) = expand(
+            &[points[0], points[0], points[1]],
+            2.0,
+            LineJoin::Bevel,
+            LineCap::Butt,
+        );
+        assert_eq!(positions.len(), 6);
+        assert!(expand(
+            &[points[0], points[0]],
+            2.0,
+            LineJoin::Bevel,
+            LineCap::Round
+        )
+        .0
+        .is_empty());
+    }
+
+    #[test]
+    fn test_joins() {
+        // A right angle turning left, so the outer corner is at the bottom right
+        let points = [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(10.0, 10.0)];
+        let (positions, _) = expand(&points, 2.0, LineJoin::Bevel, LineCap::Butt);
+        assert_eq!(positions.len(), 2 * 6 + 3);
+        assert_eq!(
+            bounds(&positions),
+            (vec3(0.0, -1.0, 0.0), vec3(11.0, 10.0, 0.0))
+        );
+
+        let (positions, _) = expand(&points, 2.0, LineJoin::default(), LineCap::Butt);
+        assert_eq!(positions.len(), 2 * 6 + 6);
+        assert!(positions
+            .iter()
+            .any(|p| p.distance(vec3(11.0, -1.0, 0.0)) < 0.0001));
+
+        // The miter of a sharp turn exceeds the limit, so it is beveled
+        let sharp = [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 1.0)];
+        let (positions, _) = expand(&sharp, 2.0, LineJoin::Miter { limit: 4.0 }, LineCap::Butt);
+        assert_eq!(positions.len(), 2 * 6 + 3);
+
+        let (positions, uvs) = expand(&points, 2.0, LineJoin::Round, LineCap::Butt);
+        assert_eq!(positions.len(), 2 * 6 + 3 * 8);
+        assert!(positions.iter().all(|p| p.x <= 11.0001 && p.y >= -1.0001));
+        assert!(uvs.iter().all(|uv| (0.0..=1.0).contains(&uv.y)));
+
+        // Turning around completely gives a half circle in front of the turn
+        let back = [vec2(0.0, 0.0), vec2(10.0, 0.0), vec2(0.0, 0.0)];
+        let (positions, _) = expand(&back, 2.0, LineJoin::Round, LineCap::Butt);
+        assert!((bounds(&positions).1.x - 11.0).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,420 @@
+use crate::renderer::*;
+use std::f32::consts::PI;
+
+///
+/// Rotates the (radius, height) profile around the y-axis, see [CpuMeshExt::lathe].
+///
+pub(super) fn lathe(profile: &[(f32, f32)], segments: u32, angle: Radians) -> CpuMesh {
+    if profile.len() < 2 {
+        panic!(
+            "a lathe profile needs at least two points, got {}",
+            profile.len()
+        );
+    }
+    if segments == 0 {
+        panic!("a lathe needs at least one segment");
+    }
+    let angle = angle.0.clamp(0.0, 2.0 * PI);
+    let full_revolution = angle >= 2.0 * PI - 1.0e-5;
+    let closed_profile = profile.len() > 2 && profile[0] == profile[profile.len() - 1];
+
+    let (min_height, max_height) = profile
+        .iter()
+        .fold((f32::MAX, f32::MIN), |(min, max), (_, h)| {
+            (min.min(*h), max.max(*h))
+        });
+    let height_range = max_height - min_height;
+
+    // The normal of the profile in the (radius, height) plane at each point, computed from the neighbouring points
+    let last = profile.len() - 1;
+    let profile_normals = (0..profile.len())
+        .map(|i| {
+            let previous = if i > 0 {
+                profile[i - 1]
+            } else if closed_profile {
+                profile[last - 1]
+            } else {
+                profile[i]
+            };
+            let next = if i < last {
+                profile[i + 1]
+            } else if closed_profile {
+                profile[1]
+            } else {
+                profile[i]
+            };
+            let normal = vec2(next.1 - previous.1, previous.0 - next.0);
+            if normal.magnitude2() > 0.0 {
+                normal.normalize()
+            } else {
+                vec2(1.0, 0.0)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let columns = segments as usize + 1;
+    let mut positions = Vec::with_capacity(profile.len() * columns);
+    let mut normals = Vec::with_capacity(profile.len() * columns);
+    let mut uvs = Vec::with_capacity(profile.len() * columns);
+    for (i, (radius, height)) in profile.iter().enumerate() {
+        for j in 0..columns {
+            let theta = angle * j as f32 / segments as f32;
+            // The seam of a full revolution is duplicated to get continuous uv coordinates, but with exactly the same position
+            let (sin, cos) = if full_revolution && j == segments as usize {
+                (0.0, 1.0)
+            } else {
+                theta.sin_cos()
+            };
+            positions.push(vec3(radius * sin, *height, radius * cos));
+            normals.push(vec3(
+                profile_normals[i].x * sin,
+                profile_normals[i].y,
+                profile_normals[i].x * cos,
+            ));
+            uvs.push(vec2(
+                theta / (2.0 * PI),
+                if height_range > 0.0 {
+                    (height - min_height) / height_range
+                } else {
+                    0.0
+                },
+            ));
+        }
+    }
+
+    let mut indices = Vec::new();
+    for i in 0..last {
+        for j in 0..segments as usize {
+            let a = (i * columns + j) as u32;
+            let b = a + 1;
+            let c = b + columns as u32;
+            let d = a + columns as u32;
+            // Skip the degenerate triangles where the profile touches the axis
+            if profile[i].0 != 0.0 {
+                indices.extend([a, b, c]);
+            }
+            if profile[i + 1].0 != 0.0 {
+                indices.extend([a, c, d]);
+            }
+        }
+    }
+
+    CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        normals: Some(normals),
+        uvs: Some(uvs),
+        ..Default::default()
+    }
+}
+
+///
+/// Extrudes the cross section along the path, see [CpuMeshExt::extrude_along_path].
+///
+pub(super) fn extrude_along_path(cross_section: &[Vec2], path: &[Vec3], closed: bool) -> CpuMesh {
+    if cross_section.len() < 3 {
+        panic!(
+            "a cross section needs at least three points, got {}",
+            cross_section.len()
+        );
+    }
+    if path.len() < 2 {
+        panic!("a path needs at least two points, got {}", path.len());
+    }
+
+    // Make sure the cross section is counter clockwise so the triangles are counter clockwise seen from the outside
+    let mut cross_section = cross_section.to_vec();
+    if signed_area(&cross_section) < 0.0 {
+        cross_section.reverse();
+    }
+    let n = cross_section.len();
+    let looped_path = path.len() > 2 && path[0] == path[path.len() - 1];
+
+    // The vertices around the cross section where the vertices at sharp corners are split to get flat shading of each side
+    let edge_normals = (0..n)
+        .map(|k| {
+            let edge = cross_section[(k + 1) % n] - cross_section[k];
+            vec2(edge.y, -edge.x).normalize()
+        })
+        .collect::<Vec<_>>();
+    let perimeter = (0..n)
+        .map(|k| cross_section[k].distance(cross_section[(k + 1) % n]))
+        .sum::<f32>();
+    let corner = |k: usize| {
+        let (incoming, outgoing) = (edge_normals[(k + n - 1) % n], edge_normals[k % n]);
+        if incoming.dot(outgoing) < 0.85 {
+            None
+        } else {
+            Some((incoming + outgoing).normalize())
+        }
+    };
+    let mut ring = vec![(cross_section[0], corner(0).unwrap_or(edge_normals[0]), 0.0)];
+    let mut edge_start = Vec::with_capacity(n);
+    let mut length = 0.0;
+    for k in 0..n {
+        edge_start.push(ring.len() - 1);
+        let end = cross_section[(k + 1) % n];
+        length += cross_section[k].distance(end);
+        let u = length / perimeter;
+        match corner(k + 1) {
+            Some(normal) => ring.push((end, normal, u)),
+            None => {
+                ring.push((end, edge_normals[k], u));
+                if k + 1 < n {
+                    ring.push((end, edge_normals[k + 1], u));
+                }
+            }
+        }
+    }
+
+    // The tangent, normal and binormal at each point on the path using parallel transport of the first normal
+    let last = path.len() - 1;
+    let tangents = (0..path.len())
+        .map(|i| {
+            let previous = if i > 0 {
+                path[i - 1]
+            } else if looped_path {
+                path[last - 1]
+            } else {
+                path[i]
+            };
+            let next = if i < last {
+                path[i + 1]
+            } else if looped_path {
+                path[1]
+            } else {
+                path[i]
+            };
+            (next - previous).normalize()
+        })
+        .collect::<Vec<_>>();
+    let mut normals = vec![if tangents[0].x.abs() > 0.9 {
+        vec3(0.0, 1.0, 0.0).cross(tangents[0]).normalize()
+    } else {
+        vec3(1.0, 0.0, 0.0).cross(tangents[0]).normalize()
+    }];
+    for i in 1..path.len() {
+        let rotation = Quat::from_arc(tangents[i - 1], tangents[i], None);
+        let normal = rotation * normals[i - 1];
+        normals.push((normal - tangents[i] * normal.dot(tangents[i])).normalize());
+    }
+    let mut path_lengths = vec![0.0];
+    for i in 1..path.len() {
+        path_lengths.push(path_lengths[i - 1] + path[i - 1].distance(path[i]));
+    }
+    let path_length = path_lengths[last];
+    if looped_path {
+        // Distribute the twist between the first and last frame along the path so the ends match
+        let twist = normals[last]
+            .cross(normals[0])
+            .dot(tangents[0])
+            .atan2(normals[last].dot(normals[0]));
+        for i in 1..path.len() {
+            normals[i] =
+                Quat::from_axis_angle(tangents[i], radians(twist * path_lengths[i] / path_length))
+                    * normals[i];
+        }
+    }
+
+    let mut positions = Vec::new();
+    let mut vertex_normals = Vec::new();
+    let mut uvs = Vec::new();
+    for i in 0..path.len() {
+        let binormal = tangents[i].cross(normals[i]);
+        let to_world = |p: Vec2| p.x * normals[i] + p.y * binormal;
+        for (p, normal, u) in ring.iter() {
+            positions.push(path[i] + to_world(*p));
+            vertex_normals.push(to_world(*normal));
+            uvs.push(vec2(*u, path_lengths[i] / path_length));
+        }
+    }
+    let ring_size = ring.len() as u32;
+    let mut indices = Vec::new();
+    for i in 0..last as u32 {
+        for k in edge_start.iter() {
+            let a = i * ring_size + *k as u32;
+            let b = a + 1;
+            let c = b + ring_size;
+            let d = a + ring_size;
+            indices.extend([a, b, c, a, c, d]);
+        }
+    }
+
+    if closed && !looped_path {
+        let triangles = triangulate(&cross_section);
+        let min = cross_section.iter().fold(vec2(f32::MAX, f32::MAX), |m, p| {
+            vec2(m.x.min(p.x), m.y.min(p.y))
+        });
+        let max = cross_section.iter().fold(vec2(f32::MIN, f32::MIN), |m, p| {
+            vec2(m.x.max(p.x), m.y.max(p.y))
+        });
+        for (i, sign) in [(0, -1.0), (last, 1.0)] {
+            let binormal = tangents[i].cross(normals[i]);
+            let offset = positions.len() as u32;
+            for p in cross_section.iter() {
+                positions.push(path[i] + p.x * normals[i] + p.y * binormal);
+                vertex_normals.push(sign * tangents[i]);
+                uvs.push(vec2(
+                    (p.x - min.x) / (max.x - min.x).max(f32::EPSILON),
+                    (p.y - min.y) / (max.y - min.y).max(f32::EPSILON),
+                ));
+            }
+            for [a, b, c] in triangles.iter() {
+                let (b, c) = if sign < 0.0 { (*c, *b) } else { (*b, *c) };
+                indices.extend([offset + *a as u32, offset + b as u32, offset + c as u32]);
+            }
+        }
+    }
+
+    CpuMesh {
+        positions: Positions::F32(positions),
+        indices: Indices::U32(indices),
+        normals: Some(vertex_normals),
+        uvs: Some(uvs),
+        ..Default::default()
+    }
+}
+
+fn signed_area(polygon: &[Vec2]) -> f32 {
+    0.5 * (0..polygon.len())
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            a.x * b.y - b.x * a.y
+        })
+        .sum::<f32>()
+}
+
+///
+/// Triangulates the simple counter clockwise polygon using ear clipping.
+///
+fn triangulate(polygon: &[Vec2]) -> Vec<[usize; 3]> {
+    let cross = |a: Vec2, b: Vec2, c: Vec2| (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x);
+    let mut remaining = (0..polygon.len()).collect::<Vec<_>>();
+    let mut triangles = Vec::new();
+    while remaining.len() > 3 {
+        let m = remaining.len();
+        let ear = (0..m).find(|j| {
+            let (a, b, c) = (
+                remaining[(j + m - 1) % m],
+                remaining[*j],
+                remaining[(j + 1) % m],
+            );
+            let (pa, pb, pc) = (polygon[a], polygon[b], polygon[c]);
+            cross(pa, pb, pc) > 0.0
+                && remaining.iter().all(|v| {
+                    let p = polygon[*v];
+                    *v == a
+                        || *v == b
+                        || *v == c
+                        || cross(pa, pb, p) < 0.0
+                        || cross(pb, pc, p) < 0.0
+                        || cross(pc, pa, p) < 0.0
+                })
+        });
+        // Fall back to clipping any vertex if the polygon is degenerate or self intersecting
+        let j = ear.unwrap_or(0);
+        triangles.push([
+            remaining[(j + m - 1) % m],
+            remaining[j],
+            remaining[(j + 1) % m],
+        ]);
+        remaining.remove(j);
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+    triangles
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn u32_indices(mesh: &CpuMesh) -> Vec<u32> {
+        match &mesh.indices {
+            Indices::U32(indices) => indices.clone(),
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn test_lathe() {
+        let cylinder = lathe(&[(1.0, 0.0), (1.0, 2.0)], 16, radians(2.0 * PI));
+        let positions = cylinder.positions.to_f32();
+        assert_eq!(positions.len(), 2 * 17);
+        // The seam is duplicated with the same position but different uv coordinates
+        assert_eq!(positions[0], positions[16]);
+        let uvs = cylinder.uvs.as_ref().unwrap();
+        assert_eq!(uvs[0], vec2(0.0, 0.0));
+        assert!((uvs[16].x - 1.0).abs() < 1.0e-6);
+        assert_eq!(uvs[17].y, 1.0);
+
+        let normals = cylinder.normals.as_ref().unwrap();
+        let indices = u32_indices(&cylinder);
+        assert_eq!(indices.len(), 2 * 16 * 3);
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+            let n = normals[triangle[0] as usize];
+            // Outwards normals and counter clockwise triangles seen from the outside
+            assert!(n.dot(vec3(a.x, 0.0, a.z)) > 0.99);
+            assert!((b - a).cross(c - a).dot(n) > 0.0);
+        }
+
+        // A cone touching the axis at the top only rotated a quarter of a revolution
+        let cone = lathe(&[(1.0, 0.0), (0.0, 1.0)], 4, radians(0.5 * PI));
+        assert_eq!(cone.positions.to_f32().len(), 2 * 5);
+        assert_eq!(u32_indices(&cone).len(), 4 * 3);
+        assert!((cone.uvs.as_ref().unwrap()[4].x - 0.25).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn test_extrude_along_path() {
+        let square = [
+            vec2(-0.5, -0.5),
+            vec2(0.5, -0.5),
+            vec2(0.5, 0.5),
+            vec2(-0.5, 0.5),
+        ];
+        let path = [
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+            vec3(1.0, 0.0, 2.0),
+            vec3(1.0, 0.0, 3.0),
+        ];
+        let mesh = extrude_along_path(&square, &path, true);
+        let positions = mesh.positions.to_f32();
+        let normals = mesh.normals.as_ref().unwrap();
+        // Four sides with split vertices at the sharp corners and two caps
+        assert_eq!(positions.len(), 4 * 8 + 2 * 4);
+        let indices = u32_indices(&mesh);
+        assert_eq!(indices.len(), (3 * 4 * 2 + 2 * 2) * 3);
+
+        let mut edges = std::collections::HashMap::new();
+        for triangle in indices.chunks(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| positions[triangle[i] as usize]);
+            let face_normal = (b - a).cross(c - a);
+            for i in 0..3 {
+                // Counter clockwise seen from the outside and flat sides
+                assert!(face_normal.dot(normals[triangle[i] as usize]) > 0.0);
+                let key = |v: u32| {
+                    let p = positions[v as usize] * 1000.0;
+                    (p.x.round() as i32, p.y.round() as i32, p.z.round() as i32)
+                };
+                *edges
+                    .entry((key(triangle[i]), key(triangle[(i + 1) % 3])))
+                    .or_insert(0) += 1;
+            }
+        }
+        // Closed, ie. each directed edge appears once together with its opposite
+        for ((a, b), count) in edges.iter() {
+            assert_eq!(*count, 1);
+            assert_eq!(edges.get(&(*b, *a)), Some(&1));
+        }
+
+        // The cross section is perpendicular to the path
+        for (i, p) in path.iter().enumerate() {
+            for v in 0..8 {
+                let d = positions[i * 8 + v] - p;
+                assert!((d.magnitude() - 0.5f32.sqrt()).abs() < 1.0e-5);
+            }
+        }
+    }
+}
@@ -2,6 +2,8 @@ use crate::renderer::*;
 
 ///
 /// A rectangle 2D geometry which can be rendered using the [camera2d] camera.
+/// It can be rendered with any material, for example in a [Gm], or directly as an [Object] using its own [ColorMaterial],
+/// which makes it easy to show an image using [Rectangle::set_texture].
 ///
 pub struct Rectangle {
     mesh: Mesh,
@@ -9,6 +11,7 @@ pub struct Rectangle {
     height: f32,
     center: PhysicalPoint,
     rotation: Radians,
+    material: ColorMaterial,
 }
 
 impl Rectangle {
@@ -30,6 +33,7 @@ impl Rectangle {
             height,
             center: center.into(),
             rotation: rotation.into(),
+            material: ColorMaterial::default(),
         };
         rectangle.update();
         rectangle
@@ -69,6 +73,68 @@ impl Rectangle {
         self.rotation
     }
 
+    ///
+    /// Sets the color of the material used when rendering the rectangle as an [Object], which is white by default.
+    /// This is only a default, rendering the rectangle with another material, for example in a [Gm], ignores this color.
+    /// The material is opaque, use [Rectangle::material_mut] to for example enable blending for a color with an alpha value below 255.
+    ///
+    pub fn set_color(&mut self, color: impl Into<Color>) {
+        self.material.color = color.into();
+    }
+
+    ///
+    /// Returns the color of the material used when rendering the rectangle as an [Object].
+    ///
+    pub fn color(&self) -> Color {
+        self.material.color
+    }
+
+    ///
+    /// Sets the texture of the material used when rendering the rectangle as an [Object], so it shows the texture multiplied by the color of the material.
+    /// The material is opaque, use [Rectangle::material_mut] to for example enable blending for a texture with transparent parts.
+    ///
+    pub fn set_texture(&mut self, texture: impl Into<Texture2DRef>) {
+        self.material.texture = Some(texture.into());
+    }
+
+    ///
+    /// Removes the texture of the material used when rendering the rectangle as an [Object], see [Rectangle::set_texture].
+    ///
+    pub fn remove_texture(&mut self) {
+        self.material.texture = None;
+    }
+
+    ///
+    /// Returns the texture of the material used when rendering the rectangle as an [Object], if any.
+    ///
+    pub fn texture(&self) -> Option<&Texture2DRef> {
+        self.material.texture.as_ref()
+    }
+
+    ///
+    /// Returns the material used when rendering the rectangle as an [Object].
+    ///
+    pub fn material(&self) -> &ColorMaterial {
+        &self.material
+    }
+
+    ///
+    /// Returns a mutable reference to the material used when rendering the rectangle as an [Object].
+    ///
+    pub fn material_mut(&mut self) -> &mut ColorMaterial {
+        &mut self.material
+    }
+
+    /// Get the number of vertices in the rectangle.
+    pub fn vertex_count(&self) -> u32 {
+        self.mesh.vertex_count()
+    }
+
+    /// Get the number of triangles in the rectangle.
+    pub fn triangle_count(&self) -> u32 {
+        self.mesh.triangle_count()
+    }
+
     fn update(&mut self) {
         self.mesh.set_transformation_2d(
             Mat3::from_translation(self.center.into())
@@ -112,6 +178,16 @@ impl Geometry for Rectangle {
     }
 }
 
+impl Object for Rectangle {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        self.render_with_material(&self.material, camera, lights)
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.material.material_type()
+    }
+}
+
 impl<'a> IntoIterator for &'a Rectangle {
     type Item = &'a dyn Geometry;
     type IntoIter = std::iter::Once<&'a dyn Geometry>;
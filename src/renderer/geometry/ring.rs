@@ -0,0 +1,165 @@
+use super::shape2d::Shape2D;
+use crate::renderer::*;
+
+///
+/// A ring 2D geometry, ie. a circle with a circular hole in the middle, which can be rendered using the [camera2d] camera.
+/// The ring is cut out of a quad in the fragment shader, so the edges are smooth and anti-aliased no matter the size of the ring.
+///
+pub struct Ring {
+    shape: Shape2D,
+    radius: f32,
+    inner_radius: f32,
+    center: PhysicalPoint,
+}
+
+impl Ring {
+    ///
+    /// Constructs a new ring geometry with the given outer and inner radius.
+    ///
+    pub fn new(
+        context: &Context,
+        center: impl Into<PhysicalPoint>,
+        radius: f32,
+        inner_radius: f32,
+    ) -> Self {
+        let mut ring = Self {
+            shape: Shape2D::new(context),
+            radius,
+            inner_radius,
+            center: center.into(),
+        };
+        ring.update();
+        ring
+    }
+
+    /// Set the outer radius of the ring.
+    pub fn set_radius(&mut self, radius: f32) {
+        self.radius = radius;
+        self.update();
+    }
+
+    /// Get the outer radius of the ring.
+    pub fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    /// Set the inner radius of the ring, ie. the radius of the hole.
+    pub fn set_inner_radius(&mut self, inner_radius: f32) {
+        self.inner_radius = inner_radius;
+    }
+
+    /// Get the inner radius of the ring, ie. the radius of the hole.
+    pub fn inner_radius(&self) -> f32 {
+        self.inner_radius
+    }
+
+    /// Set the center of the ring.
+    pub fn set_center(&mut self, center: impl Into<PhysicalPoint>) {
+        self.center = center.into();
+        self.update();
+    }
+
+    /// Get the center of the ring.
+    pub fn center(&self) -> PhysicalPoint {
+        self.center
+    }
+
+    ///
+    /// Set a border with the given width in physical pixels and color along the inside of both the outer and inner edge of the ring.
+    /// The inside of the border is colored by the material. A width of zero removes the border.
+    ///
+    pub fn set_border(&mut self, width: f32, color: Color) {
+        self.shape.set_border(width, color);
+    }
+
+    /// Get the width and color of the border if any.
+    pub fn border(&self) -> Option<(f32, Color)> {
+        self.shape.border()
+    }
+
+    /// Get the number of vertices in the ring.
+    pub fn vertex_count(&self) -> u32 {
+        self.shape.vertex_count()
+    }
+
+    /// Get the number of triangles in the ring.
+    pub fn triangle_count(&self) -> u32 {
+        self.shape.triangle_count()
+    }
+
+    fn update(&mut self) {
+        self.shape.update(
+            self.center.into(),
+            radians(0.0),
+            2.0 * self.radius,
+            2.0 * self.radius,
+        );
+    }
+
+    fn use_uniforms(&self, program: &Program) {
+        program.use_uniform("ringRadius", self.radius);
+        program.use_uniform("ringInnerRadius", self.inner_radius.min(self.radius));
+    }
+}
+
+const RING_DISTANCE: &str = "
+    uniform float ringRadius;
+    uniform float ringInnerRadius;
+    float shape_distance(vec2 p)
+    {
+        float half_thickness = 0.5 * (ringRadius - ringInnerRadius);
+        return abs(length(p) - ringInnerRadius - half_thickness) - half_thickness;
+    }";
+
+impl Geometry for Ring {
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        self.shape
+            .render_with_material(material, camera, lights, RING_DISTANCE, |program| {
+                self.use_uniforms(program)
+            })
+    }
+
+    fn render_with_post_material(
+        &self,
+        material: &dyn PostMaterial,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        self.shape.render_with_post_material(
+            material,
+            camera,
+            lights,
+            color_texture,
+            depth_texture,
+            RING_DISTANCE,
+            |program| self.use_uniforms(program),
+        )
+    }
+
+    ///
+    /// Returns the [AxisAlignedBoundingBox] for this geometry in the global coordinate system.
+    ///
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        let center: Vec2 = self.center.into();
+        AxisAlignedBoundingBox::new_with_positions(&[
+            (center - vec2(self.radius, self.radius)).extend(0.0),
+            (center + vec2(self.radius, self.radius)).extend(0.0),
+        ])
+    }
+}
+
+impl<'a> IntoIterator for &'a Ring {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
@@ -20,8 +20,36 @@ in vec4 row2;
 in vec4 row3;
 #endif
 
+#ifdef USE_SKINNING
+in vec4 joint_indices;
+in vec4 joint_weights;
+// The skinning matrices of all instances, one row of texels per instance and four texels (the columns) per joint
+uniform highp sampler2D jointMatrices;
+
+mat4 jointMatrix(float joint)
+{
+    int x = 4 * int(joint);
+    int y = textureSize(jointMatrices, 0).y - 1 - gl_InstanceID;
+    return mat4(
+        texelFetch(jointMatrices, ivec2(x, y), 0),
+        texelFetch(jointMatrices, ivec2(x + 1, y), 0),
+        texelFetch(jointMatrices, ivec2(x + 2, y), 0),
+        texelFetch(jointMatrices, ivec2(x + 3, y), 0)
+    );
+}
+#endif
+
 out vec3 pos;
 
+#ifdef CLIP_PLANE_COUNT
+#ifdef USE_CLIP_DISTANCE
+uniform vec4 clipPlanes[CLIP_PLANE_COUNT];
+out float gl_ClipDistance[CLIP_PLANE_COUNT];
+#else
+out vec3 clipPosition;
+#endif
+#endif
+
 #ifdef USE_NORMALS 
 uniform mat4 normalMatrix;
 in vec3 normal;
@@ -52,6 +80,13 @@ in vec4 color;
 in vec4 instance_color;
 #endif
 
+#ifdef USE_START_TIME
+#ifdef USE_INSTANCE_START_TIMES
+in float instance_start_time;
+#endif
+out float start_time;
+#endif
+
 out vec4 col;
 
 void main()
@@ -68,6 +103,13 @@ void main()
     local2World *= transform;
 #endif
 
+#ifdef USE_SKINNING
+    local2World *= joint_weights.x * jointMatrix(joint_indices.x)
+        + joint_weights.y * jointMatrix(joint_indices.y)
+        + joint_weights.z * jointMatrix(joint_indices.z)
+        + joint_weights.w * jointMatrix(joint_indices.w);
+#endif
+
     vec4 worldPosition = local2World * vec4(position, 1.);
     worldPosition /= worldPosition.w;
 #ifdef PARTICLES
@@ -76,21 +118,23 @@ void main()
 #ifdef USE_INSTANCE_TRANSLATIONS 
     worldPosition.xyz += instance_translation;
 #endif
-    gl_Position = viewProjection * worldPosition;
-
-    pos = worldPosition.xyz;
 
     // *** NORMAL ***
 #ifdef USE_NORMALS 
 #ifdef USE_INSTANCE_TRANSFORMS
-    mat3 normalMat = mat3(transpose(inverse(local2World)));
+    // The cofactor matrix is the inverse transpose scaled by the determinant, which is removed by the normalization except for the sign,
+    // and it is much cheaper to compute per vertex than the inverse
+    mat3 m = mat3(local2World);
+    mat3 normalMat = mat3(cross(m[1], m[2]), cross(m[2], m[0]), cross(m[0], m[1]));
+    normalMat *= sign(dot(m[0], normalMat[0]));
 #else
     mat3 normalMat = mat3(normalMatrix);
 #endif
     nor = normalize(normalMat * normal);
 
 #ifdef USE_TANGENTS 
-    tang = normalize(normalMat * tangent.xyz);
+    // Tangents lie in the surface, so unlike the normal they are transformed by the local to world transformation
+    tang = normalize(mat3(local2World) * tangent.xyz);
     bitang = normalize(cross(nor, tang) * tangent.w);
 #endif
 
@@ -107,6 +151,26 @@ void main()
 #else
     uvs = uv_coordinates;
 #endif
+#endif
+
+    // *** DISPLACEMENT ***
+#ifdef USE_DISPLACEMENT
+    worldPosition.xyz += displacement_height(uvs) * nor;
+#endif
+
+    gl_Position = viewProjection * worldPosition;
+
+    pos = worldPosition.xyz;
+
+    // *** CLIPPING ***
+#ifdef CLIP_PLANE_COUNT
+#ifdef USE_CLIP_DISTANCE
+    for (int i = 0; i < CLIP_PLANE_COUNT; i++) {
+        gl_ClipDistance[i] = dot(clipPlanes[i], vec4(worldPosition.xyz, 1.0));
+    }
+#else
+    clipPosition = worldPosition.xyz;
+#endif
 #endif
 
     // *** COLOR ***
@@ -116,5 +180,14 @@ void main()
 #endif
 #ifdef USE_INSTANCE_COLORS
     col *= instance_color;
+#endif
+
+    // *** START TIME ***
+#ifdef USE_START_TIME
+#ifdef USE_INSTANCE_START_TIMES
+    start_time = instance_start_time;
+#else
+    start_time = 0.0;
+#endif
 #endif
 }
\ No newline at end of file
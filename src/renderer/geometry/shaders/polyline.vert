@@ -0,0 +1,24 @@
+uniform mat4 viewProjection;
+
+in vec3 position;
+in vec2 uv_coordinates;
+
+out vec3 pos;
+out vec3 nor;
+out vec3 tang;
+out vec3 bitang;
+out vec2 uvs;
+out vec4 col;
+out float start_time;
+
+void main()
+{
+    start_time = 0.0;
+    pos = position;
+    nor = vec3(0.0, 0.0, 1.0);
+    tang = vec3(1.0, 0.0, 0.0);
+    bitang = vec3(0.0, 1.0, 0.0);
+    uvs = uv_coordinates;
+    col = vec4(1.0);
+    gl_Position = viewProjection * vec4(position, 1.0);
+}
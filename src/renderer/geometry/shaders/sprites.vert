@@ -4,6 +4,9 @@ uniform mat4 transformation;
 uniform vec3 direction;
 
 in vec3 center;
+#ifdef USE_INSTANCE_START_TIMES
+in float instance_start_time;
+#endif
 
 in vec3 position;
 in vec2 uv_coordinate;
@@ -11,11 +14,19 @@ in vec2 uv_coordinate;
 out vec2 uvs;
 out vec4 col;
 out vec3 pos;
+out vec3 sprite_center;
+out float start_time;
 
 void main()
 {
     uvs = uv_coordinate;
     col = vec4(1.0);
+    sprite_center = center;
+#ifdef USE_INSTANCE_START_TIMES
+    start_time = instance_start_time;
+#else
+    start_time = 0.0;
+#endif
 
     vec3 z = normalize(eye - center);
     vec3 y = direction;
@@ -0,0 +1,62 @@
+uniform mat4 viewProjection;
+uniform vec3 eye;
+uniform vec2 viewportSize;
+uniform float currentTime;
+uniform float lifetime;
+uniform vec2 widths;
+uniform vec4 startColor;
+uniform vec4 endColor;
+uniform int screenSpace;
+
+in vec3 position;
+in vec3 tangent;
+in vec2 time_side;
+
+out vec3 pos;
+out vec3 nor;
+out vec3 tang;
+out vec3 bitang;
+out vec2 uvs;
+out vec4 col;
+out float start_time;
+
+void main()
+{
+    start_time = 0.0;
+    float t = clamp((currentTime - time_side.x) / lifetime, 0.0, 1.0);
+    float side = time_side.y;
+    float width = mix(widths.x, widths.y, t);
+
+    // The direction of the trail is undefined for the very first sample, but then the sample is not part of any segment
+    vec3 direction = dot(tangent, tangent) > 0.0 ? normalize(tangent) : vec3(1.0, 0.0, 0.0);
+    vec3 view = normalize(eye - position);
+    vec3 offset = cross(direction, view);
+    if (dot(offset, offset) < 0.000001) {
+        // The trail points directly towards the camera, so any offset perpendicular to the trail is as good as any other
+        offset = cross(direction, abs(direction.y) < 0.99 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0));
+    }
+    offset = normalize(offset);
+
+    vec3 world_pos = position;
+    if (screenSpace == 0) {
+        world_pos += 0.5 * side * width * offset;
+    }
+    pos = world_pos;
+    nor = normalize(cross(offset, direction));
+    tang = direction;
+    bitang = offset;
+    uvs = vec2(t, 0.5 + 0.5 * side);
+    col = mix(startColor, endColor, t);
+    gl_Position = viewProjection * vec4(world_pos, 1.0);
+
+    if (screenSpace != 0) {
+        // Offset perpendicular to the direction of the trail on the screen, the width is in pixels
+        vec4 ahead = viewProjection * vec4(position + 0.01 * length(eye - position) * direction, 1.0);
+        vec2 screen_direction = (ahead.xy / ahead.w - gl_Position.xy / gl_Position.w) * viewportSize;
+        if (dot(screen_direction, screen_direction) < 0.000001) {
+            screen_direction = vec2(1.0, 0.0);
+        }
+        vec2 screen_offset = normalize(vec2(-screen_direction.y, screen_direction.x));
+        gl_Position.xy += side * width * screen_offset / viewportSize * gl_Position.w;
+    }
+}
@@ -0,0 +1,251 @@
+use crate::renderer::*;
+
+///
+/// The common functionality of the 2D shapes, like [Circle] and [CircularArc], which are rendered as a quad where the shape is cut out in the fragment shader
+/// using a signed distance function, which gives smooth edges independent of the size of the shape.
+///
+pub(super) struct Shape2D {
+    mesh: Mesh,
+    center: Vec2,
+    rotation: Radians,
+    border: Option<(f32, Color)>,
+}
+
+impl Shape2D {
+    pub fn new(context: &Context) -> Self {
+        let mut mesh = CpuMesh::square();
+        mesh.transform(&(Mat4::from_scale(0.5))).unwrap();
+        Self {
+            mesh: Mesh::new(context, &mesh),
+            center: vec2(0.0, 0.0),
+            rotation: radians(0.0),
+            border: None,
+        }
+    }
+
+    ///
+    /// Places the quad so it covers the shape with the given size plus a margin for the anti-aliasing.
+    ///
+    pub fn update(&mut self, center: Vec2, rotation: Radians, width: f32, height: f32) {
+        self.center = center;
+        self.rotation = rotation;
+        self.mesh.set_transformation_2d(
+            Mat3::from_translation(center)
+                * Mat3::from_angle_z(rotation)
+                * Mat3::from_nonuniform_scale(width + 4.0, height + 4.0),
+        );
+    }
+
+    pub fn set_border(&mut self, width: f32, color: Color) {
+        self.border = if width > 0.0 {
+            Some((width, color))
+        } else {
+            None
+        };
+    }
+
+    pub fn border(&self) -> Option<(f32, Color)> {
+        self.border
+    }
+
+    pub fn vertex_count(&self) -> u32 {
+        self.mesh.vertex_count()
+    }
+
+    pub fn triangle_count(&self) -> u32 {
+        self.mesh.triangle_count()
+    }
+
+    ///
+    /// Renders the quad with the given material where everything outside the shape, given by the `distance_function` source which must define
+    /// `float shape_distance(vec2 p)` returning the signed distance in physical pixels from the local position `p` to the edge of the shape, is cut away.
+    ///
+    pub fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        distance_function: &str,
+        use_uniforms: impl Fn(&Program),
+    ) {
+        self.mesh.render_with_material(
+            &ShapeMaterial {
+                shape: self,
+                material,
+                distance_function,
+                use_uniforms: &use_uniforms,
+            },
+            camera,
+            lights,
+        )
+    }
+
+    pub fn render_with_post_material(
+        &self,
+        material: &dyn PostMaterial,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+        distance_function: &str,
+        use_uniforms: impl Fn(&Program),
+    ) {
+        self.mesh.render_with_post_material(
+            &ShapeMaterial {
+                shape: self,
+                material,
+                distance_function,
+                use_uniforms: &use_uniforms,
+            },
+            camera,
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+
+    fn fragment_shader(
+        &self,
+        fragment_shader: FragmentShader,
+        distance_function: &str,
+    ) -> FragmentShader {
+        FragmentShader {
+            source: format!(
+                "{}
+                uniform vec2 shapeCenter;
+                uniform mat2 shapeRotation;
+                uniform vec4 shapeBorderColor;
+                uniform float shapeBorderWidth;
+                {}
+                void main()
+                {{
+                    shape_material_main();
+                    float d = shape_distance(shapeRotation * (gl_FragCoord.xy - shapeCenter));
+                    float w = max(fwidth(d), 0.0001);
+                    float coverage = clamp(0.5 - d / w, 0.0, 1.0);
+                    if (coverage <= 0.0) {{
+                        discard;
+                    }}
+                    if (shapeBorderWidth > 0.0) {{
+                        outColor = mix(outColor, shapeBorderColor, clamp(0.5 + (d + shapeBorderWidth) / w, 0.0, 1.0));
+                    }}
+                    outColor.a *= coverage;
+                }}",
+                fragment_shader
+                    .source
+                    .replacen("void main()", "void shape_material_main()", 1),
+                distance_function
+            ),
+            attributes: fragment_shader.attributes,
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera) {
+        // The shape is defined in physical pixels relative to the viewport, see camera2d
+        let viewport = camera.viewport();
+        program.use_uniform(
+            "shapeCenter",
+            self.center + vec2(viewport.x as f32, viewport.y as f32),
+        );
+        program.use_uniform("shapeRotation", Mat2::from_angle(-self.rotation));
+        let (width, color) = self.border.unwrap_or((0.0, Color::WHITE));
+        // The border color is converted to sRGB like the output of the materials
+        let srgb = |c: u8| {
+            let c = c as f32 / 255.0;
+            if c < 0.0031308 {
+                c * 12.92
+            } else {
+                1.055 * c.powf(1.0 / 2.4) - 0.055
+            }
+        };
+        program.use_uniform(
+            "shapeBorderColor",
+            vec4(
+                srgb(color.r),
+                srgb(color.g),
+                srgb(color.b),
+                color.a as f32 / 255.0,
+            ),
+        );
+        program.use_uniform("shapeBorderWidth", width);
+    }
+}
+
+///
+/// Wraps the material used for rendering a [Shape2D] to cut out the shape and blend the anti-aliased edges.
+///
+struct ShapeMaterial<'a, M: ?Sized> {
+    shape: &'a Shape2D,
+    material: &'a M,
+    distance_function: &'a str,
+    use_uniforms: &'a dyn Fn(&Program),
+}
+
+impl<'a, M: ?Sized> ShapeMaterial<'a, M> {
+    fn with_blending(&self, render_states: RenderStates) -> RenderStates {
+        RenderStates {
+            blend: Blend::TRANSPARENCY,
+            ..render_states
+        }
+    }
+}
+
+impl<'a> Material for ShapeMaterial<'a, dyn Material + 'a> {
+    fn fragment_shader(&self, lights: &[&dyn Light]) -> FragmentShader {
+        self.shape.fragment_shader(
+            self.material.fragment_shader(lights),
+            self.distance_function,
+        )
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        self.material.use_uniforms(program, camera, lights);
+        self.shape.use_uniforms(program, camera);
+        (self.use_uniforms)(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.with_blending(self.material.render_states())
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
+
+impl<'a> PostMaterial for ShapeMaterial<'a, dyn PostMaterial + 'a> {
+    fn fragment_shader(
+        &self,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) -> FragmentShader {
+        self.shape.fragment_shader(
+            self.material
+                .fragment_shader(lights, color_texture, depth_texture),
+            self.distance_function,
+        )
+    }
+
+    fn use_uniforms(
+        &self,
+        program: &Program,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        self.material
+            .use_uniforms(program, camera, lights, color_texture, depth_texture);
+        self.shape.use_uniforms(program, camera);
+        (self.use_uniforms)(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.with_blending(self.material.render_states())
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Transparent
+    }
+}
@@ -13,6 +13,7 @@ pub struct Sprites {
     position_buffer: VertexBuffer,
     uv_buffer: VertexBuffer,
     center_buffer: InstanceBuffer,
+    start_time_buffer: Option<InstanceBuffer>,
     transformation: Mat4,
     direction: Option<Vec3>,
 }
@@ -50,6 +51,7 @@ impl Sprites {
             position_buffer,
             uv_buffer,
             center_buffer: InstanceBuffer::new_with_data(context, centers),
+            start_time_buffer: None,
             transformation: Mat4::identity(),
             direction,
         }
@@ -83,6 +85,48 @@ impl Sprites {
         self.center_buffer.fill(centers);
     }
 
+    ///
+    /// Set the start time in seconds of the animation of each sprite, for example a [Flipbook] animation, such that the sprites are not animated in sync.
+    /// Use `None` to start the animation of all sprites at the same time.
+    ///
+    /// # Panic
+    /// Will panic if the number of start times is not the same as the number of sprites.
+    ///
+    pub fn set_start_times(&mut self, start_times: Option<&[f32]>) {
+        if let Some(start_times) = start_times {
+            if start_times.len() as u32 != self.instance_count() {
+                panic!(
+                    "the number of start times ({}) must be the same as the number of sprites ({})",
+                    start_times.len(),
+                    self.instance_count()
+                );
+            }
+        }
+        self.start_time_buffer = start_times
+            .map(|start_times| InstanceBuffer::new_with_data(&self.context, start_times));
+    }
+
+    ///
+    /// Returns the number of vertices in a single sprite.
+    ///
+    pub fn vertex_count(&self) -> u32 {
+        self.position_buffer.vertex_count()
+    }
+
+    ///
+    /// Returns the number of triangles in a single sprite.
+    ///
+    pub fn triangle_count(&self) -> u32 {
+        self.position_buffer.vertex_count() / 3
+    }
+
+    ///
+    /// Returns the number of sprites.
+    ///
+    pub fn instance_count(&self) -> u32 {
+        self.center_buffer.instance_count()
+    }
+
     fn draw(&self, program: &Program, render_states: RenderStates, camera: &Camera) {
         program.use_uniform("eye", camera.position());
         program.use_uniform("viewProjection", camera.projection() * camera.view());
@@ -90,6 +134,11 @@ impl Sprites {
         program.use_vertex_attribute("position", &self.position_buffer);
         program.use_vertex_attribute("uv_coordinate", &self.uv_buffer);
         program.use_instance_attribute("center", &self.center_buffer);
+        if let Some(start_time_buffer) = &self.start_time_buffer {
+            if program.requires_attribute("instance_start_time") {
+                program.use_instance_attribute("instance_start_time", start_time_buffer);
+            }
+        }
         program.use_uniform("direction", self.direction.unwrap_or(vec3(0.0, 0.0, 0.0)));
         program.draw_arrays_instanced(
             render_states,
@@ -98,6 +147,18 @@ impl Sprites {
             self.center_buffer.instance_count(),
         )
     }
+
+    fn vertex_shader_source(&self) -> String {
+        format!(
+            "{}{}",
+            if self.start_time_buffer.is_some() {
+                "#define USE_INSTANCE_START_TIMES\n"
+            } else {
+                ""
+            },
+            include_str!("shaders/sprites.vert")
+        )
+    }
 }
 
 impl<'a> IntoIterator for &'a Sprites {
@@ -125,7 +186,7 @@ impl Geometry for Sprites {
         }
         self.context
             .program(
-                include_str!("shaders/sprites.vert").to_owned(),
+                self.vertex_shader_source(),
                 fragment_shader.source,
                 |program| {
                     material.use_uniforms(program, camera, lights);
@@ -152,7 +213,7 @@ impl Geometry for Sprites {
         }
         self.context
             .program(
-                include_str!("shaders/sprites.vert").to_owned(),
+                self.vertex_shader_source(),
                 fragment_shader.source,
                 |program| {
                     material.use_uniforms(program, camera, lights, color_texture, depth_texture);
@@ -0,0 +1,507 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// A trail, ie. a ribbon which follows a moving point, for example a projectile, and fades out over time.
+/// Add the positions of the point using [Trail::push] and the samples older than the lifetime are removed automatically.
+///
+/// The ribbon always faces the camera and the width and color are interpolated from the start values at the newest sample to the end values at samples which are as old as the lifetime.
+/// The color is available as the vertex color in the material, so a [ColorMaterial] with additive blending is a good choice.
+/// The samples are stored in a ring buffer with a fixed capacity, so when the capacity is reached, the oldest sample is overwritten.
+/// Only the changed samples are uploaded to the GPU, so nothing is allocated after construction.
+///
+pub struct Trail {
+    context: Context,
+    positions: VertexBuffer,
+    tangents: VertexBuffer,
+    times: VertexBuffer,
+    elements: ElementBuffer,
+    samples: Vec<(Vec3, f64)>,
+    first: usize,
+    len: usize,
+    epoch: f64,
+    time: f64,
+    lifetime: f64,
+    widths: (f32, f32),
+    colors: (Color, Color),
+    screen_space: bool,
+}
+
+impl Trail {
+    ///
+    /// Constructs a new empty trail which can contain the given number of samples and where each sample is visible for the given lifetime.
+    /// The lifetime must be in the same unit as the time given to [Trail::push] and [Trail::set_time], for example milliseconds if using [FrameInput::accumulated_time](crate::FrameInput::accumulated_time).
+    /// The width is the width of the ribbon at the newest sample, which fades to zero at the end.
+    ///
+    /// # Panic
+    /// Will panic if the capacity is less than 2.
+    ///
+    pub fn new(context: &Context, capacity: usize, lifetime: f64, width: f32) -> Self {
+        assert!(
+            capacity >= 2,
+            "a trail needs a capacity of at least two samples"
+        );
+        let mut elements = Vec::with_capacity(capacity * 6);
+        for i in 0..capacity as u32 {
+            let a = 2 * i;
+            let b = 2 * ((i + 1) % capacity as u32);
+            elements.extend_from_slice(&[a, b, a + 1, a + 1, b, b + 1]);
+        }
+        Self {
+            context: context.clone(),
+            positions: VertexBuffer::new_with_data(context, &vec![Vec3::zero(); 2 * capacity]),
+            tangents: VertexBuffer::new_with_data(context, &vec![Vec3::zero(); 2 * capacity]),
+            times: VertexBuffer::new_with_data(context, &vec![Vec2::zero(); 2 * capacity]),
+            elements: ElementBuffer::new_with_data(context, &elements),
+            samples: vec![(Vec3::zero(), 0.0); capacity],
+            first: 0,
+            len: 0,
+            epoch: 0.0,
+            time: 0.0,
+            lifetime,
+            widths: (width, 0.0),
+            colors: (Color::WHITE, Color::new(255, 255, 255, 0)),
+            screen_space: false,
+        }
+    }
+
+    ///
+    /// Adds a sample at the given position to the trail and sets the current time to the given time, see [Trail::set_time].
+    /// If the position is the same as the position of the newest sample, for example when the point is stationary, the newest sample is moved forward in time instead.
+    ///
+    pub fn push(&mut self, position: Vec3, time: f64) {
+        if self.len == 0 {
+            self.epoch = time;
+        }
+        let capacity = self.samples.len();
+        let newest = self.newest();
+        if self.len > 0 && self.samples[newest].0.distance2(position) < 1.0e-10 {
+            self.samples[newest] = (position, time);
+        } else {
+            if self.len == capacity {
+                self.first = (self.first + 1) % capacity;
+                self.len -= 1;
+            }
+            self.len += 1;
+            self.samples[self.newest()] = (position, time);
+        }
+        self.set_time(time);
+
+        // The tangent of the previous sample depends on the new sample
+        if self.len > 1 {
+            self.upload(self.len - 2);
+        }
+        self.upload(self.len - 1);
+    }
+
+    ///
+    /// Sets the current time which is used to fade out the samples and remove the samples which are older than the lifetime.
+    /// Call this every frame, also when no samples are added, otherwise the trail is not fading out.
+    ///
+    pub fn set_time(&mut self, time: f64) {
+        self.time = time;
+        // The oldest sample is kept until the second oldest has expired, so the end of the trail doesn't jump
+        while self.len >= 2 && time - self.sample(1).1 >= self.lifetime {
+            self.first = (self.first + 1) % self.samples.len();
+            self.len -= 1;
+        }
+        if self.len == 1 && time - self.sample(0).1 >= self.lifetime {
+            self.len = 0;
+        }
+
+        // The times are stored relative to an epoch on the GPU, so move the epoch before the precision becomes a problem
+        if self.len > 0 && time - self.epoch > 1000.0 * self.lifetime.max(1.0) {
+            self.epoch = self.sample(0).1;
+            for i in 0..self.len {
+                self.upload(i);
+            }
+        }
+    }
+
+    ///
+    /// Removes all samples.
+    ///
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    ///
+    /// Returns the number of samples currently in the trail.
+    ///
+    pub fn sample_count(&self) -> usize {
+        self.len
+    }
+
+    ///
+    /// Returns the maximum number of samples in the trail.
+    ///
+    pub fn capacity(&self) -> usize {
+        self.samples.len()
+    }
+
+    ///
+    /// Sets the lifetime of the samples, see [Trail::new].
+    ///
+    pub fn set_lifetime(&mut self, lifetime: f64) {
+        self.lifetime = lifetime;
+    }
+
+    ///
+    /// Returns the lifetime of the samples.
+    ///
+    pub fn lifetime(&self) -> f64 {
+        self.lifetime
+    }
+
+    ///
+    /// Sets the width of the ribbon at the newest sample and at the samples which are as old as the lifetime.
+    /// The width is in world space units unless the trail is in screen space, see [Trail::set_screen_space].
+    ///
+    pub fn set_widths(&mut self, start_width: f32, end_width: f32) {
+        self.widths = (start_width, end_width);
+    }
+
+    ///
+    /// Returns the width of the ribbon at the newest sample and at the samples which are as old as the lifetime.
+    ///
+    pub fn widths(&self) -> (f32, f32) {
+        self.widths
+    }
+
+    ///
+    /// Sets the color of the ribbon at the newest sample and at the samples which are as old as the lifetime.
+    /// The default fades from opaque white to transparent white.
+    ///
+    pub fn set_colors(&mut self, start_color: Color, end_color: Color) {
+        self.colors = (start_color, end_color);
+    }
+
+    ///
+    /// Returns the color of the ribbon at the newest sample and at the samples which are as old as the lifetime.
+    ///
+    pub fn colors(&self) -> (Color, Color) {
+        self.colors
+    }
+
+    ///
+    /// Sets whether the width of the ribbon is in physical pixels on the screen instead of world space units.
+    /// This is useful for thin trails which should be visible no matter the distance to the camera.
+    ///
+    pub fn set_screen_space(&mut self, screen_space: bool) {
+        self.screen_space = screen_space;
+    }
+
+    ///
+    /// Returns whether the width of the ribbon is in physical pixels on the screen.
+    ///
+    pub fn is_screen_space(&self) -> bool {
+        self.screen_space
+    }
+
+    fn newest(&self) -> usize {
+        (self.first + self.len + self.samples.len() - 1) % self.samples.len()
+    }
+
+    fn sample(&self, i: usize) -> (Vec3, f64) {
+        self.samples[(self.first + i) % self.samples.len()]
+    }
+
+    ///
+    /// Uploads the i'th oldest sample with the tangent averaged from the segments on both sides,
+    /// which keeps the ribbon from flipping at sharp turns.
+    ///
+    fn upload(&mut self, i: usize) {
+        let (position, time) = self.sample(i);
+        let incoming = if i > 0 {
+            (position - self.sample(i - 1).0).normalize()
+        } else {
+            Vec3::zero()
+        };
+        let outgoing = if i + 1 < self.len {
+            (self.sample(i + 1).0 - position).normalize()
+        } else {
+            Vec3::zero()
+        };
+        let mut tangent = incoming + outgoing;
+        if tangent.magnitude2() < 0.0001 {
+            // The trail turns around completely, so use the direction towards the newest sample
+            tangent = if outgoing.magnitude2() > 0.0 {
+                outgoing
+            } else {
+                incoming
+            };
+        }
+        let time = (time - self.epoch) as f32;
+        let slot = (self.first + i) % self.samples.len();
+        self.positions
+            .update_partial(&[position, position], 2 * slot);
+        self.tangents.update_partial(&[tangent, tangent], 2 * slot);
+        self.times
+            .update_partial(&[vec2(time, -1.0), vec2(time, 1.0)], 2 * slot);
+    }
+
+    fn draw(&self, program: &Program, render_states: RenderStates, camera: &Camera) {
+        let viewport = camera.viewport();
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("eye", camera.position());
+        program.use_uniform(
+            "viewportSize",
+            vec2(viewport.width as f32, viewport.height as f32),
+        );
+        program.use_uniform("currentTime", (self.time - self.epoch) as f32);
+        program.use_uniform("lifetime", self.lifetime.max(f64::EPSILON) as f32);
+        program.use_uniform("widths", vec2(self.widths.0, self.widths.1));
+        program.use_uniform("startColor", self.colors.0);
+        program.use_uniform("endColor", self.colors.1);
+        program.use_uniform("screenSpace", i32::from(self.screen_space));
+        program.use_vertex_attribute("position", &self.positions);
+        program.use_vertex_attribute("tangent", &self.tangents);
+        program.use_vertex_attribute("time_side", &self.times);
+
+        // The segments wrap around the end of the ring buffer, in which case they are drawn in two parts
+        let capacity = self.samples.len();
+        let segments = self.len - 1;
+        let first_part = segments.min(capacity - self.first);
+        program.draw_subset_of_elements(
+            render_states,
+            viewport,
+            &self.elements,
+            6 * self.first as u32,
+            6 * first_part as u32,
+        );
+        if segments > first_part {
+            program.draw_subset_of_elements(
+                render_states,
+                viewport,
+                &self.elements,
+                0,
+                6 * (segments - first_part) as u32,
+            );
+        }
+    }
+
+    fn vertex_shader_source(&self) -> String {
+        include_str!("shaders/trail.vert").to_owned()
+    }
+}
+
+impl<'a> IntoIterator for &'a Trail {
+    type Item = &'a dyn Geometry;
+    type IntoIter = std::iter::Once<&'a dyn Geometry>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl Geometry for Trail {
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        if self.len < 2 {
+            return;
+        }
+        self.context
+            .program(
+                self.vertex_shader_source(),
+                material.fragment_shader(lights).source,
+                |program| {
+                    material.use_uniforms(program, camera, lights);
+                    self.draw(program, material.render_states(), camera);
+                },
+            )
+            .expect("Failed compiling shader")
+    }
+
+    fn render_with_post_material(
+        &self,
+        material: &dyn PostMaterial,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        if self.len < 2 {
+            return;
+        }
+        self.context
+            .program(
+                self.vertex_shader_source(),
+                material
+                    .fragment_shader(lights, color_texture, depth_texture)
+                    .source,
+                |program| {
+                    material.use_uniforms(program, camera, lights, color_texture, depth_texture);
+                    self.draw(program, material.render_states(), camera);
+                },
+            )
+            .expect("Failed compiling shader")
+    }
+
+    ///
+    /// Returns the [AxisAlignedBoundingBox] of the samples expanded by the width of the ribbon
+    /// or an infinite bounding box if the width is in screen space.
+    ///
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        if self.screen_space {
+            return AxisAlignedBoundingBox::INFINITE;
+        }
+        let mut aabb = AxisAlignedBoundingBox::EMPTY;
+        let margin = 0.5 * self.widths.0.abs().max(self.widths.1.abs());
+        for i in 0..self.len {
+            let position = self.sample(i).0;
+            aabb.expand_with_aabb(&AxisAlignedBoundingBox::new_with_positions(&[
+                position - vec3(margin, margin, margin),
+                position + vec3(margin, margin, margin),
+            ]));
+        }
+        aabb
+    }
+
+    ///
+    /// Sets the current time of the trail, see [Trail::set_time].
+    ///
+    fn animate(&mut self, time: f32) {
+        self.set_time(time as f64);
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use crate::renderer::*;
+
+    // Renders the trail, seen from the front, into 8x2 pixels covering x in [-4, 4] and returns the bottom row
+    fn render(context: &Context, trail: &Trail) -> Vec<[u8; 4]> {
+        let camera = Camera::new_orthographic(
+            Viewport::new_at_origo(8, 2),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            2.0,
+            0.1,
+            10.0,
+        );
+        let mut texture = Texture2D::new_empty::<[u8; 4]>(
+            context,
+            8,
+            2,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut pixels = texture
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+            .render(
+                &camera,
+                &Gm {
+                    geometry: trail,
+                    material: ColorMaterial {
+                        color: Color::WHITE,
+                        ..Default::default()
+                    },
+                },
+                &[],
+            )
+            .read::<[u8; 4]>();
+        pixels.truncate(8);
+        pixels
+    }
+
+    fn is_covered(pixel: [u8; 4]) -> bool {
+        pixel[0] > 128
+    }
+
+    #[test]
+    fn test_expiry() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut trail = Trail::new(&context, 8, 1.0, 1.0);
+        trail.push(vec3(0.0, 0.0, 0.0), 0.0);
+        trail.push(vec3(1.0, 0.0, 0.0), 0.5);
+        trail.push(vec3(2.0, 0.0, 0.0), 1.0);
+        assert_eq!(trail.sample_count(), 3);
+
+        // The oldest sample is kept until the second oldest has expired
+        trail.set_time(1.4);
+        assert_eq!(trail.sample_count(), 3);
+        trail.set_time(1.6);
+        assert_eq!(trail.sample_count(), 2);
+        trail.set_time(1.9);
+        assert_eq!(trail.sample_count(), 2);
+        // The newest sample expires together with the second oldest
+        trail.set_time(2.0);
+        assert_eq!(trail.sample_count(), 0);
+        assert!(render(&context, &trail).iter().all(|p| !is_covered(*p)));
+
+        // The trail starts over when a new sample is added
+        trail.push(vec3(0.0, 0.0, 0.0), 11.0);
+        assert_eq!(trail.sample_count(), 1);
+    }
+
+    #[test]
+    fn test_wrap_around() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut trail = Trail::new(&context, 4, 100.0, 2.0);
+        trail.set_widths(2.0, 2.0);
+        trail.set_colors(Color::WHITE, Color::WHITE);
+        // Six samples in a ring buffer with room for four, so the oldest two are overwritten and the segments wrap around the end of the buffer
+        for i in 0..6 {
+            trail.push(vec3(i as f32 - 3.0, 0.0, 0.0), i as f64);
+        }
+        assert_eq!(trail.sample_count(), 4);
+        assert_eq!(trail.aabb().min().x, -2.0);
+        assert_eq!(trail.aabb().max().x, 3.0);
+
+        // The ribbon covers x in [-1, 2], ie. the pixels with centers at -0.5, 0.5 and 1.5
+        let covered = render(&context, &trail)
+            .into_iter()
+            .map(is_covered)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            covered,
+            vec![false, false, false, true, true, true, false, false]
+        );
+    }
+
+    #[test]
+    fn test_degenerate_input() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut trail = Trail::new(&context, 4, 100.0, 2.0);
+        trail.set_widths(2.0, 2.0);
+        trail.set_colors(Color::WHITE, Color::WHITE);
+        assert_eq!(trail.sample_count(), 0);
+        assert!(trail.aabb().is_empty());
+        assert!(render(&context, &trail).iter().all(|p| !is_covered(*p)));
+
+        // Coincident samples move the newest sample forward in time instead of adding a segment of zero length
+        trail.push(vec3(0.0, 0.0, 0.0), 0.0);
+        trail.push(vec3(0.0, 0.0, 0.0), 1.0);
+        trail.push(vec3(0.0, 0.0, 0.0), 2.0);
+        assert_eq!(trail.sample_count(), 1);
+        assert!(render(&context, &trail).iter().all(|p| !is_covered(*p)));
+
+        // The sample is kept alive by the coincident samples
+        trail.set_time(100.5);
+        assert_eq!(trail.sample_count(), 1);
+
+        // A trail which turns around completely is still rendered
+        trail.push(vec3(1.0, 0.0, 0.0), 101.0);
+        trail.push(vec3(0.0, 0.0, 0.0), 102.0);
+        assert_eq!(trail.sample_count(), 3);
+        let pixels = render(&context, &trail);
+        assert!(is_covered(pixels[4]));
+        assert!(!is_covered(pixels[3]) && !is_covered(pixels[5]));
+    }
+
+    #[test]
+    #[should_panic(expected = "a trail needs a capacity of at least two samples")]
+    fn test_capacity_too_small() {
+        let context = crate::HeadlessContext::new().unwrap();
+        Trail::new(&context, 1, 1.0, 1.0);
+    }
+}
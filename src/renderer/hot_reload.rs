@@ -0,0 +1,172 @@
+//!
+//! Reloading of textures and models when the files they were loaded from change, which is useful while iterating on the assets.
+//! Requires the `asset-hot-reload` feature and is not available on web.
+//!
+
+use crate::renderer::*;
+use notify::Watcher;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+
+// Editors often write a file in several steps, so wait a bit after a change before loading the file
+const DEBOUNCE_TIME: std::time::Duration = std::time::Duration::from_millis(100);
+
+///
+/// An asset which has been reloaded by an [AssetWatcher].
+///
+pub enum ReloadedAsset {
+    /// A texture watched using [AssetWatcher::watch_texture].
+    /// Use for example [Texture2D::reload] or [Texture2DRef::reload] to update the texture.
+    Texture(CpuTexture),
+    /// A model watched using [AssetWatcher::watch_model].
+    /// Use for example [Model::reload] or [Mesh::reload] to update the model.
+    Model(CpuModel),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum AssetKind {
+    Texture,
+    Model,
+}
+
+///
+/// Watches asset files for changes and reloads the changed files on a background thread.
+/// Call [AssetWatcher::poll] each frame to get the reloaded assets and use them to update the existing textures and models on the thread
+/// owning the [Context], using for example [Texture2DRef::reload] and [Model::reload], so the objects using them do not have to be recreated.
+/// If a file fails to load, for example because it is only partly written, a warning is logged and the previous version is kept.
+///
+/// The files are loaded using [three_d_asset::io::load], so the features for the file formats must be enabled on the `three-d-asset` crate.
+/// Only changes to the watched file itself triggers a reload, not changes to its dependencies, for example the textures of a model.
+///
+pub struct AssetWatcher {
+    watcher: notify::RecommendedWatcher,
+    assets: Arc<Mutex<HashMap<PathBuf, (PathBuf, AssetKind)>>>,
+    receiver: Receiver<(PathBuf, ReloadedAsset)>,
+}
+
+impl AssetWatcher {
+    ///
+    /// Creates a new asset watcher which does not watch any files yet.
+    ///
+    pub fn new() -> Result<Self, RendererError> {
+        let assets = Arc::new(Mutex::new(HashMap::<PathBuf, (PathBuf, AssetKind)>::new()));
+        let (changed_sender, changed_receiver) = channel();
+        let (sender, receiver) = channel();
+        let watched = assets.clone();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                if matches!(
+                    event.kind,
+                    notify::EventKind::Create(_) | notify::EventKind::Modify(_)
+                ) {
+                    for path in event.paths {
+                        if watched.lock().unwrap().contains_key(&path) {
+                            changed_sender.send(path).ok();
+                        }
+                    }
+                }
+            }
+        })
+        .map_err(|e| RendererError::AssetWatcher(String::new(), e.to_string()))?;
+
+        // The thread stops when the watcher, which owns the sender of changed paths, is dropped
+        let loading = assets.clone();
+        std::thread::spawn(move || load_changed_assets(changed_receiver, loading, sender));
+        Ok(Self {
+            watcher,
+            assets,
+            receiver,
+        })
+    }
+
+    ///
+    /// Starts watching the texture at the given path, which is reloaded as a [ReloadedAsset::Texture] when the file changes.
+    ///
+    pub fn watch_texture(&mut self, path: impl AsRef<Path>) -> Result<(), RendererError> {
+        self.watch(path.as_ref(), AssetKind::Texture)
+    }
+
+    ///
+    /// Starts watching the model at the given path, which is reloaded as a [ReloadedAsset::Model] when the file changes.
+    ///
+    pub fn watch_model(&mut self, path: impl AsRef<Path>) -> Result<(), RendererError> {
+        self.watch(path.as_ref(), AssetKind::Model)
+    }
+
+    ///
+    /// Stops watching the file at the given path.
+    ///
+    pub fn unwatch(&mut self, path: impl AsRef<Path>) {
+        if let Ok(path) = path.as_ref().canonicalize() {
+            self.assets.lock().unwrap().remove(&path);
+        }
+    }
+
+    ///
+    /// Returns the assets which have been reloaded since the last call, together with the path they were watched with, without waiting.
+    /// Should be called each frame.
+    ///
+    pub fn poll(&mut self) -> Vec<(PathBuf, ReloadedAsset)> {
+        self.receiver.try_iter().collect()
+    }
+
+    fn watch(&mut self, path: &Path, kind: AssetKind) -> Result<(), RendererError> {
+        let error = |e: &dyn std::fmt::Display| {
+            RendererError::AssetWatcher(path.display().to_string(), e.to_string())
+        };
+        let canonical_path = path.canonicalize().map_err(|e| error(&e))?;
+        // Watch the directory, since many editors save a file by replacing it, which some platforms do not report for a watched file
+        let directory = canonical_path
+            .parent()
+            .ok_or_else(|| error(&"the path has no parent directory"))?;
+        self.watcher
+            .watch(directory, notify::RecursiveMode::NonRecursive)
+            .map_err(|e| error(&e))?;
+        self.assets
+            .lock()
+            .unwrap()
+            .insert(canonical_path, (path.to_path_buf(), kind));
+        Ok(())
+    }
+}
+
+fn load_changed_assets(
+    changed: Receiver<PathBuf>,
+    assets: Arc<Mutex<HashMap<PathBuf, (PathBuf, AssetKind)>>>,
+    sender: Sender<(PathBuf, ReloadedAsset)>,
+) {
+    while let Ok(path) = changed.recv() {
+        std::thread::sleep(DEBOUNCE_TIME);
+        let mut paths = vec![path];
+        paths.extend(changed.try_iter());
+        paths.sort();
+        paths.dedup();
+        for path in paths {
+            let asset = assets.lock().unwrap().get(&path).cloned();
+            if let Some((watched_path, kind)) = asset {
+                match load(&path, kind) {
+                    Ok(asset) => {
+                        if sender.send((watched_path, asset)).is_err() {
+                            return;
+                        }
+                    }
+                    Err(e) => log::warn!(
+                        "failed reloading {}, the previous version is kept: {}",
+                        watched_path.display(),
+                        e
+                    ),
+                }
+            }
+        }
+    }
+}
+
+fn load(path: &Path, kind: AssetKind) -> Result<ReloadedAsset, three_d_asset::Error> {
+    let mut raw_assets = three_d_asset::io::load(&[path])?;
+    Ok(match kind {
+        AssetKind::Texture => ReloadedAsset::Texture(raw_assets.deserialize(path)?),
+        AssetKind::Model => ReloadedAsset::Model(raw_assets.deserialize(path)?),
+    })
+}
@@ -25,6 +25,7 @@ mod environment;
 pub use environment::*;
 
 use crate::core::*;
+use crate::renderer::{DepthMaterial, Geometry};
 
 ///
 /// Specifies how the intensity of a light fades over distance.
@@ -120,6 +121,7 @@ pub fn lights_shader_source(lights: &[&dyn Light], lighting_model: LightingModel
     let mut shader_source = lighting_model_shader(lighting_model).to_string();
     shader_source.push_str(include_str!("../core/shared.frag"));
     shader_source.push_str(include_str!("light/shaders/light_shared.frag"));
+    shader_source.push_str(include_str!("light/shaders/shadow_filtering.frag"));
     let mut dir_fun = String::new();
     for (i, light) in lights.iter().enumerate() {
         shader_source.push_str(&light.shader_source(i as u32));
@@ -147,6 +149,338 @@ fn shadow_matrix(camera: &Camera) -> Mat4 {
     bias_matrix * camera.projection() * camera.view()
 }
 
+///
+/// Calls the `render` closure with clipping disabled if the clip planes should not be applied when generating shadow maps (see [ClipPlanes::clip_shadows]).
+///
+fn with_shadow_clip_planes(context: &Context, render: impl FnOnce()) {
+    let clip_planes = context.clip_planes();
+    if clip_planes.clip_shadows || clip_planes.is_empty() {
+        render();
+    } else {
+        context.set_clip_planes(ClipPlanes::default());
+        render();
+        context.set_clip_planes(clip_planes);
+    }
+}
+
+///
+/// The depth of the static geometries as seen from a light, which is reused until the light changes or the cache is invalidated.
+///
+struct StaticShadowCache {
+    texture: DepthTexture2D,
+    camera: Camera,
+    light_state: Vec<f32>,
+}
+
+fn new_shadow_texture(context: &Context, texture_size: u32) -> DepthTexture2D {
+    DepthTexture2D::new::<f32>(
+        context,
+        texture_size,
+        texture_size,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+///
+/// The type of shadow map used by a [DirectionalLight] or a [SpotLight], which determines how the shadow edges are filtered.
+/// The filtered types require rendering to and linear filtering of floating point textures, which is not supported everywhere on OpenGL ES and WebGL2,
+/// in which case a warning is logged and the [ShadowMapType::Standard] shadow map is used instead.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShadowMapType {
+    ///
+    /// The depth of a surface is compared to the depth in the shadow map at a few samples around the surface, which gives hard shadows with slightly smoothed edges.
+    /// The depth of the surface is offset by a small constant bias towards the light to avoid shadow acne.
+    ///
+    Standard,
+    ///
+    /// A variance shadow map, which stores the depth and the squared depth, blurs them and estimates the visibility of a surface using the Chebyshev upper bound.
+    /// This gives soft shadow edges, but light can bleed through where the shadows of several occluders overlap.
+    /// Instead of a depth bias, a small minimum variance is used to avoid shadow acne.
+    ///
+    Variance {
+        /// Visibility below this value in the range `[0, 1)` is considered to be fully in shadow, which removes light bleeding at the cost of slightly smaller shadows.
+        /// A value around `0.2` is usually a good compromise.
+        bleed_reduction: f32,
+    },
+    ///
+    /// An exponential shadow map, which stores the exponential of the depth, blurs it and estimates the visibility from the exponential of the difference between the depth of the surface and the occluder.
+    /// This gives soft shadow edges without light bleeding, but the exponent acts as the bias, so the shadow fades out close to the contact with the occluder.
+    ///
+    Exponential,
+}
+
+impl Default for ShadowMapType {
+    fn default() -> Self {
+        Self::Standard
+    }
+}
+
+///
+/// The blurred moments of a shadow map used for the [ShadowMapType::Variance] and [ShadowMapType::Exponential] shadow maps.
+///
+struct FilteredShadowMap {
+    texture: Texture2D,
+    scratch: Texture2D,
+    shadow_map_type: ShadowMapType,
+    depth_parameters: Vec3,
+}
+
+///
+/// Computes and blurs the moments of the depth in the given shadow map if the shadow map type requires it, reusing the textures from the previous filtered shadow map if possible.
+/// Returns `None` for the standard shadow map type, or if floating point textures are not supported in which case a warning is logged.
+///
+fn filter_shadow_map(
+    context: &Context,
+    shadow_map_type: ShadowMapType,
+    shadow_texture: &DepthTexture2D,
+    shadow_camera: &Camera,
+    previous: Option<FilteredShadowMap>,
+) -> Option<FilteredShadowMap> {
+    if shadow_map_type == ShadowMapType::Standard {
+        return None;
+    }
+    if !supports_filtered_shadow_maps(context) {
+        static WARNED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+        if !WARNED.swap(true, std::sync::atomic::Ordering::Relaxed) {
+            log::warn!(
+                "{:?} shadow maps require rendering to and filtering of floating point textures which is not supported, using standard shadow maps instead",
+                shadow_map_type
+            );
+        }
+        return None;
+    }
+    let size = shadow_texture.width();
+    let new_texture = || {
+        Texture2D::new_empty::<[f32; 2]>(
+            context,
+            size,
+            size,
+            Interpolation::Linear,
+            Interpolation::Linear,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        )
+    };
+    let (mut texture, mut scratch) = match previous {
+        Some(previous) if previous.texture.width() == size => (previous.texture, previous.scratch),
+        _ => (new_texture(), new_texture()),
+    };
+    let depth_parameters = vec3(
+        shadow_camera.z_near(),
+        shadow_camera.z_far(),
+        match shadow_camera.projection_type() {
+            ProjectionType::Perspective { .. } => 1.0,
+            _ => 0.0,
+        },
+    );
+    let render_states = RenderStates {
+        write_mask: WriteMask::COLOR,
+        depth_test: DepthTest::Always,
+        cull: Cull::Back,
+        ..Default::default()
+    };
+    let viewport = Viewport::new_at_origo(size, size);
+
+    texture
+        .as_color_target(None)
+        .clear(ClearState::default())
+        .write(|| {
+            apply_effect(
+                context,
+                &format!(
+                    "{}\n{}",
+                    include_str!("light/shaders/shadow_filtering.frag"),
+                    include_str!("light/shaders/shadow_moments.frag")
+                ),
+                render_states,
+                viewport,
+                |program| {
+                    program.use_depth_texture("depthMap", shadow_texture);
+                    program.use_uniform("shadowDepth", depth_parameters);
+                    program.use_uniform(
+                        "exponential",
+                        i32::from(shadow_map_type == ShadowMapType::Exponential),
+                    );
+                },
+            )
+        });
+
+    // A separable Gaussian blur, first horizontally into the scratch texture and then vertically back again
+    let blur = GaussianBlur::new_with_radius(2.0, 4);
+    scratch
+        .as_color_target(None)
+        .clear(ClearState::default())
+        .write(|| blur.apply_pass(context, &texture, vec2(1.0, 0.0), viewport));
+    texture
+        .as_color_target(None)
+        .clear(ClearState::default())
+        .write(|| blur.apply_pass(context, &scratch, vec2(0.0, 1.0), viewport));
+
+    Some(FilteredShadowMap {
+        texture,
+        scratch,
+        shadow_map_type,
+        depth_parameters,
+    })
+}
+
+///
+/// Floating point color targets and linear filtering of floating point textures are always supported on desktop OpenGL, but requires extensions on OpenGL ES and WebGL2.
+///
+fn supports_filtered_shadow_maps(context: &Context) -> bool {
+    if !context.version().is_embedded {
+        return true;
+    }
+    let extensions = context.supported_extensions();
+    let supported =
+        |name: &str| extensions.contains(name) || extensions.contains(&format!("GL_{}", name));
+    supported("EXT_color_buffer_float") && supported("OES_texture_float_linear")
+}
+
+///
+/// Returns the declarations of the uniforms and the expression for the visibility used by the lights with a shadow map.
+///
+fn shadow_shader_source(filtered: Option<&FilteredShadowMap>, i: u32) -> (String, String) {
+    let mut uniforms = format!(
+        "
+                    uniform sampler2D shadowMap{};
+                    uniform mat4 shadowMVP{};",
+        i, i
+    );
+    let visibility = match filtered.map(|f| f.shadow_map_type) {
+        Some(ShadowMapType::Variance { .. }) => {
+            uniforms.push_str(&format!(
+                "
+                    uniform vec3 shadowDepth{};
+                    uniform float bleedReduction{};",
+                i, i
+            ));
+            format!(
+                "calculate_variance_shadow(shadowMap{}, shadowMVP{}, shadowDepth{}, bleedReduction{}, position)",
+                i, i, i, i
+            )
+        }
+        Some(ShadowMapType::Exponential) => {
+            uniforms.push_str(&format!(
+                "
+                    uniform vec3 shadowDepth{};",
+                i
+            ));
+            format!(
+                "calculate_exponential_shadow(shadowMap{}, shadowMVP{}, shadowDepth{}, position)",
+                i, i, i
+            )
+        }
+        _ => format!("calculate_shadow(shadowMap{}, shadowMVP{}, position)", i, i),
+    };
+    (uniforms, visibility)
+}
+
+fn use_shadow_uniforms(
+    program: &Program,
+    i: u32,
+    shadow_texture: &DepthTexture2D,
+    shadow_matrix: Mat4,
+    filtered: Option<&FilteredShadowMap>,
+) {
+    program.use_uniform(&format!("shadowMVP{}", i), shadow_matrix);
+    if let Some(filtered) = filtered {
+        program.use_texture(&format!("shadowMap{}", i), &filtered.texture);
+        program.use_uniform(&format!("shadowDepth{}", i), filtered.depth_parameters);
+        if let ShadowMapType::Variance { bleed_reduction } = filtered.shadow_map_type {
+            program.use_uniform(
+                &format!("bleedReduction{}", i),
+                bleed_reduction.clamp(0.0, 0.99),
+            );
+        }
+    } else {
+        program.use_depth_texture(&format!("shadowMap{}", i), shadow_texture);
+    }
+}
+
+///
+/// Renders the depth of the given geometries as seen from the given shadow camera. Must be called in the callback of a depth target write method.
+///
+fn render_shadow_casters(
+    context: &Context,
+    shadow_camera: &Camera,
+    geometries: impl IntoIterator<Item = impl Geometry>,
+) {
+    let depth_material = DepthMaterial {
+        render_states: RenderStates {
+            write_mask: WriteMask::DEPTH,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    with_shadow_clip_planes(context, || {
+        for geometry in geometries
+            .into_iter()
+            .filter(|g| shadow_camera.in_frustum(&g.aabb()))
+        {
+            geometry.render_with_material(&depth_material, shadow_camera, &[]);
+        }
+    })
+}
+
+///
+/// Generates a shadow map from the static and dynamic geometries where the depth of the static geometries is cached.
+/// The cache is regenerated, including the shadow camera which is computed from the static geometries, if it is empty or if the light state has changed.
+/// Then the cached depth is copied to the shadow texture and the dynamic geometries are rendered on top.
+/// Returns the shadow camera or `None` if there is nothing to cast shadows.
+///
+fn generate_cached_shadow_map<G: Geometry>(
+    context: &Context,
+    texture_size: u32,
+    light_state: Vec<f32>,
+    static_cache: &mut Option<StaticShadowCache>,
+    shadow_texture: &mut Option<DepthTexture2D>,
+    shadow_camera: impl FnOnce() -> Option<Camera>,
+    static_geometries: impl IntoIterator<Item = G>,
+    dynamic_geometries: impl IntoIterator<Item = impl Geometry>,
+) -> Option<Camera> {
+    if static_cache
+        .as_ref()
+        .map(|cache| cache.light_state != light_state)
+        .unwrap_or(true)
+    {
+        *static_cache = None;
+        let camera = shadow_camera()?;
+        let mut texture = new_shadow_texture(context, texture_size);
+        texture
+            .as_depth_target()
+            .clear(ClearState::default())
+            .write(|| render_shadow_casters(context, &camera, static_geometries));
+        *static_cache = Some(StaticShadowCache {
+            texture,
+            camera,
+            light_state,
+        });
+    }
+    let cache = static_cache.as_ref().unwrap();
+
+    if shadow_texture
+        .as_ref()
+        .map(|texture| texture.width() != texture_size)
+        .unwrap_or(true)
+    {
+        *shadow_texture = Some(new_shadow_texture(context, texture_size));
+    }
+    shadow_texture
+        .as_mut()
+        .unwrap()
+        .as_depth_target()
+        .copy_from(
+            DepthTexture::Single(&cache.texture),
+            Viewport::new_at_origo(texture_size, texture_size),
+        )
+        .write(|| render_shadow_casters(context, &cache.camera, dynamic_geometries));
+    Some(cache.camera.clone())
+}
+
 fn compute_up_direction(direction: Vec3) -> Vec3 {
     if vec3(1.0, 0.0, 0.0).dot(direction).abs() > 0.9 {
         (vec3(0.0, 1.0, 0.0).cross(direction)).normalize()
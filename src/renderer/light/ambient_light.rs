@@ -43,17 +43,18 @@ impl Light for AmbientLight {
     fn shader_source(&self, i: u32) -> String {
         if self.environment.is_some() {
             format!(
-            "
-                uniform samplerCube irradianceMap;
-                uniform samplerCube prefilterMap;
-                uniform sampler2D brdfLUT;
+            "{}
                 uniform vec3 ambientColor;
     
                 vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                 {{
                     vec3 N = normal;
                     vec3 V = view_direction;
-                    vec3 R = reflect(-V, N); 
+                    #ifdef USE_ANISOTROPY
+                    vec3 R = reflect(-V, anisotropic_reflection_normal(V, N, roughness));
+                    #else
+                    vec3 R = reflect(-V, N);
+                    #endif
                     float NdV = max(0.001, dot(N, V));
                     
                     // calculate reflectance at normal incidence; if dia-electric (like plastic) use F0 
@@ -63,19 +64,16 @@ impl Light for AmbientLight {
                     vec3 diffuse_fresnel = 1.0 - specular_fresnel;
 
                     // Diffuse
-                    vec3 irradiance = texture(irradianceMap, N).rgb;
-                    vec3 diffuse = diffuse_fresnel * mix(surface_color, vec3(0.0), metallic) * irradiance;
+                    vec3 diffuse = diffuse_fresnel * mix(surface_color, vec3(0.0), metallic) * sample_irradiance(N);
                     
                     // sample both the pre-filter map and the BRDF lut and combine them together as per the Split-Sum approximation to get the IBL specular part.
-                    const float MAX_REFLECTION_LOD = 4.0;
-                    vec3 prefilteredColor = textureLod(prefilterMap, R,  roughness * MAX_REFLECTION_LOD).rgb;    
-                    vec2 brdf  = texture(brdfLUT, vec2(NdV, roughness)).rg;
-                    vec3 specular = prefilteredColor * (specular_fresnel * brdf.x + brdf.y);
+                    vec2 brdf = sample_brdf(NdV, roughness);
+                    vec3 specular = sample_prefiltered_specular(R, roughness) * (specular_fresnel * brdf.x + brdf.y);
     
                     return (diffuse + specular) * occlusion * ambientColor;
                 }}
             
-            ", i)
+            ", Environment::fragment_shader_source(), i)
         } else {
             format!(
                 "
@@ -90,9 +88,7 @@ impl Light for AmbientLight {
     }
     fn use_uniforms(&self, program: &Program, _i: u32) {
         if let Some(ref environment) = self.environment {
-            program.use_texture_cube("irradianceMap", &environment.irradiance_map);
-            program.use_texture_cube("prefilterMap", &environment.prefilter_map);
-            program.use_texture("brdfLUT", &environment.brdf_map);
+            program.use_environment(environment);
         }
         program.use_uniform("ambientColor", self.color.to_vec3() * self.intensity);
     }
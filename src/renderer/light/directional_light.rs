@@ -10,12 +10,16 @@ pub struct DirectionalLight {
     context: Context,
     shadow_texture: Option<DepthTexture2D>,
     shadow_matrix: Mat4,
+    static_shadow: Option<StaticShadowCache>,
+    filtered_shadow: Option<FilteredShadowMap>,
     /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
     pub intensity: f32,
     /// The base color of the light.
     pub color: Color,
     /// The direction the light shines.
     pub direction: Vec3,
+    /// The type of shadow map, which is applied the next time the shadow map is generated.
+    pub shadow_map_type: ShadowMapType,
 }
 
 impl DirectionalLight {
@@ -30,9 +34,12 @@ impl DirectionalLight {
             context: context.clone(),
             shadow_matrix: Mat4::identity(),
             shadow_texture: None,
+            static_shadow: None,
+            filtered_shadow: None,
             intensity,
             color,
             direction: *direction,
+            shadow_map_type: ShadowMapType::Standard,
         }
     }
 
@@ -42,6 +49,8 @@ impl DirectionalLight {
     ///
     pub fn clear_shadow_map(&mut self) {
         self.shadow_texture = None;
+        self.static_shadow = None;
+        self.filtered_shadow = None;
         self.shadow_matrix = Mat4::identity();
     }
 
@@ -50,28 +59,111 @@ impl DirectionalLight {
     /// It is recomended that the texture size is power of 2.
     /// If the shadows are too low resolution (the edges between shadow and non-shadow are pixelated) try to increase the texture size
     /// and/or split the scene by creating another light source with same parameters and let the two light sources shines on different parts of the scene.
+    /// All geometries cast shadows, also each of the instances of an [InstancedMesh] since the depth is rendered using the same vertex shader as when rendering the geometry.
     ///
     pub fn generate_shadow_map(
         &mut self,
         texture_size: u32,
         geometries: impl IntoIterator<Item = impl Geometry> + Clone,
     ) {
+        let shadow_camera = match self.shadow_camera(texture_size, geometries.clone()) {
+            Some(camera) => camera,
+            None => return,
+        };
+        let mut shadow_texture = new_shadow_texture(&self.context, texture_size);
+        shadow_texture
+            .as_depth_target()
+            .clear(ClearState::default())
+            .write(|| render_shadow_casters(&self.context, &shadow_camera, geometries));
+        self.filtered_shadow = filter_shadow_map(
+            &self.context,
+            self.shadow_map_type,
+            &shadow_texture,
+            &shadow_camera,
+            self.filtered_shadow.take(),
+        );
+        self.shadow_texture = Some(shadow_texture);
+        self.shadow_matrix = shadow_matrix(&shadow_camera);
+    }
+
+    ///
+    /// Generate a shadow map like [DirectionalLight::generate_shadow_map], but where the geometries are split into a static set, which does not change between frames,
+    /// and a dynamic set, for example moving characters. The depth of the static geometries is rendered once and cached, so each frame only the dynamic geometries are rendered
+    /// on top of a copy of the cached depth, which makes this much faster than [DirectionalLight::generate_shadow_map] when the static geometries are the majority of the scene.
+    ///
+    /// The cache is automatically regenerated when the direction of the light or the texture size changes, but not when the static geometries change,
+    /// so call [DirectionalLight::invalidate_static_shadows] if a static geometry is moved, added or removed.
+    /// The area covered by the shadow map is computed from the static geometries, so dynamic geometries outside the bounding box of the static geometries do not cast shadows.
+    ///
+    pub fn generate_shadow_map_with_static(
+        &mut self,
+        texture_size: u32,
+        static_geometries: impl IntoIterator<Item = impl Geometry> + Clone,
+        dynamic_geometries: impl IntoIterator<Item = impl Geometry>,
+    ) {
+        let light_state = vec![
+            texture_size as f32,
+            self.direction.x,
+            self.direction.y,
+            self.direction.z,
+        ];
+        let mut static_shadow = self.static_shadow.take();
+        let mut shadow_texture = self.shadow_texture.take();
+        let shadow_camera = generate_cached_shadow_map(
+            &self.context,
+            texture_size,
+            light_state,
+            &mut static_shadow,
+            &mut shadow_texture,
+            || self.shadow_camera(texture_size, static_geometries.clone()),
+            static_geometries.clone(),
+            dynamic_geometries,
+        );
+        self.static_shadow = static_shadow;
+        self.shadow_texture = shadow_texture;
+        if let Some(shadow_camera) = shadow_camera {
+            if let Some(shadow_texture) = &self.shadow_texture {
+                self.filtered_shadow = filter_shadow_map(
+                    &self.context,
+                    self.shadow_map_type,
+                    shadow_texture,
+                    &shadow_camera,
+                    self.filtered_shadow.take(),
+                );
+            }
+            self.shadow_matrix = shadow_matrix(&shadow_camera);
+        }
+    }
+
+    ///
+    /// Invalidates the cached depth of the static geometries given to [DirectionalLight::generate_shadow_map_with_static],
+    /// so that it is regenerated the next time that method is called. This must be called when any of the static geometries are changed.
+    ///
+    pub fn invalidate_static_shadows(&mut self) {
+        self.static_shadow = None;
+    }
+
+    fn shadow_camera(
+        &self,
+        texture_size: u32,
+        geometries: impl IntoIterator<Item = impl Geometry>,
+    ) -> Option<Camera> {
         let up = compute_up_direction(self.direction);
 
         let viewport = Viewport::new_at_origo(texture_size, texture_size);
         let mut aabb = AxisAlignedBoundingBox::EMPTY;
-        for geometry in geometries.clone() {
+        for geometry in geometries {
             aabb.expand_with_aabb(&geometry.aabb());
         }
         if aabb.is_empty() {
-            return;
+            return None;
         }
         let target = aabb.center();
         let position = target - aabb.max().distance(aabb.min()) * self.direction;
         let z_far = aabb.distance_max(&position);
         let z_near = aabb.distance(&position);
         let frustum_height = aabb.max().distance(aabb.min()); // TODO: more tight fit
-        let shadow_camera = Camera::new_orthographic(
+        Some(Camera::new_orthographic(
             viewport,
             position,
             target,
@@ -79,38 +171,14 @@ impl DirectionalLight {
             frustum_height,
             z_near,
             z_far,
-        );
-        let mut shadow_texture = DepthTexture2D::new::<f32>(
-            &self.context,
-            texture_size,
-            texture_size,
-            Wrapping::ClampToEdge,
-            Wrapping::ClampToEdge,
-        );
-        let depth_material = DepthMaterial {
-            render_states: RenderStates {
-                write_mask: WriteMask::DEPTH,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
-        shadow_texture
-            .as_depth_target()
-            .clear(ClearState::default())
-            .write(|| {
-                for geometry in geometries
-                    .into_iter()
-                    .filter(|g| shadow_camera.in_frustum(&g.aabb()))
-                {
-                    geometry.render_with_material(&depth_material, &shadow_camera, &[]);
-                }
-            });
-        self.shadow_texture = Some(shadow_texture);
-        self.shadow_matrix = shadow_matrix(&shadow_camera);
+        ))
     }
 
     ///
     /// Returns a reference to the shadow map if it has been generated.
+    /// This is the depth as seen from the light, also when the [ShadowMapType] is not [ShadowMapType::Standard].
+    /// The shadow map is a depth texture with 32 bit floating point precision, not a renderbuffer, so it can be sampled in a shader,
+    /// for example to visualize it for debugging or as input to an effect using [DepthTexture::Single].
     ///
     pub fn shadow_map(&self) -> Option<&DepthTexture2D> {
         self.shadow_texture.as_ref()
@@ -120,10 +188,11 @@ impl DirectionalLight {
 impl Light for DirectionalLight {
     fn shader_source(&self, i: u32) -> String {
         if self.shadow_texture.is_some() {
+            let (shadow_uniforms, visibility) =
+                shadow_shader_source(self.filtered_shadow.as_ref(), i);
             format!(
                 "
-                    uniform sampler2D shadowMap{};
-                    uniform mat4 shadowMVP{};
+                    {}
         
                     uniform vec3 color{};
                     uniform vec3 direction{};
@@ -131,10 +200,10 @@ impl Light for DirectionalLight {
                     vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
                     {{
                         return calculate_light(color{}, -direction{}, surface_color, view_direction, normal, metallic, roughness) 
-                            * calculate_shadow(shadowMap{}, shadowMVP{}, position);
+                            * {};
                     }}
                 
-                ", i, i, i, i, i, i, i, i, i)
+                ", shadow_uniforms, i, i, i, i, i, visibility)
         } else {
             format!(
                 "
@@ -151,8 +220,13 @@ impl Light for DirectionalLight {
     }
     fn use_uniforms(&self, program: &Program, i: u32) {
         if let Some(ref tex) = self.shadow_texture {
-            program.use_depth_texture(&format!("shadowMap{}", i), tex);
-            program.use_uniform(&format!("shadowMVP{}", i), self.shadow_matrix);
+            use_shadow_uniforms(
+                program,
+                i,
+                tex,
+                self.shadow_matrix,
+                self.filtered_shadow.as_ref(),
+            );
         }
         program.use_uniform(
             &format!("color{}", i),
@@ -161,3 +235,90 @@ impl Light for DirectionalLight {
         program.use_uniform(&format!("direction{}", i), self.direction.normalize());
     }
 }
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_instanced_mesh_casts_shadows() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut ground = Gm::new(
+            Mesh::new(&context, &CpuMesh::square()),
+            PhysicalMaterial::new_opaque(
+                &context,
+                &CpuMaterial {
+                    albedo: Color::WHITE,
+                    roughness: 1.0,
+                    metallic: 0.0,
+                    ..Default::default()
+                },
+            ),
+        );
+        ground.set_transformation(Mat4::from_scale(4.0) * Mat4::from_angle_x(degrees(-90.0)));
+        let mut cube = CpuMesh::cube();
+        cube.transform(&Mat4::from_scale(0.5)).unwrap();
+        let boxes = InstancedMesh::new(
+            &context,
+            &Instances {
+                transformations: vec![
+                    Mat4::from_translation(vec3(-2.0, 1.0, 0.0)),
+                    Mat4::from_translation(vec3(2.0, 1.0, 0.0)),
+                ],
+                ..Default::default()
+            },
+            &cube,
+        );
+
+        let mut light = DirectionalLight::new(&context, 1.0, Color::WHITE, &vec3(0.0, -1.0, 0.0));
+        light.generate_shadow_map(512, [&ground.geometry as &dyn Geometry, &boxes]);
+
+        // Looking straight down with 8 pixels per unit and the negative z-axis pointing up in the image
+        let size = 64;
+        let camera = Camera::new_orthographic(
+            Viewport::new_at_origo(size, size),
+            vec3(0.0, 5.0, 0.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 0.0, -1.0),
+            8.0,
+            0.1,
+            10.0,
+        );
+        let mut texture = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            size,
+            size,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &context,
+            size,
+            size,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let pixels = RenderTarget::new(
+            texture.as_color_target(None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+        .render(&camera, &ground, &[&light])
+        .read_color::<[u8; 4]>();
+        let brightness = |x: f32, z: f32| {
+            let column = ((x + 4.0) * 8.0) as u32;
+            let row = ((z + 4.0) * 8.0) as u32;
+            pixels[(row * size + column) as usize][0] as f32
+        };
+
+        let lit = brightness(0.0, 0.0);
+        assert!(lit > 20.0);
+        // Only the ground is rendered, but both boxes cast a shadow onto it
+        assert!(brightness(-2.0, 0.0) < 0.5 * lit);
+        assert!(brightness(2.0, 0.0) < 0.5 * lit);
+        assert!(brightness(0.0, 2.0) > 0.9 * lit);
+    }
+}
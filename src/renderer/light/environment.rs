@@ -1,6 +1,9 @@
 use crate::core::*;
 use crate::renderer::*;
 
+/// The number of mip levels in the [Environment::prefilter_map], each level is prefiltered for a roughness between 0 and 1.
+const PREFILTER_MIP_LEVELS: u32 = 5;
+
 ///
 /// Precalculations of light shining from an environment map (known as image based lighting - IBL).
 /// This allows for real-time rendering of ambient light from the environment (see [AmbientLight](crate::AmbientLight)).
@@ -9,10 +12,14 @@ pub struct Environment {
     /// A cube map used to calculate the diffuse contribution from the environment.
     pub irradiance_map: TextureCubeMap,
     /// A cube map used to calculate the specular contribution from the environment.
-    /// Each mip-map level contain the prefiltered color for a certain surface roughness.
+    /// Each mip-map level contain the prefiltered color for a certain surface roughness,
+    /// the roughness is mapped linearly to the mip-map levels, see [Environment::fragment_shader_source].
     pub prefilter_map: TextureCubeMap,
     /// A 2D texture that contain the BRDF lookup tables (LUT).
     pub brdf_map: Texture2D,
+    rotation: Radians,
+    intensity: f32,
+    sun_direction: Vec3,
 }
 
 impl Environment {
@@ -99,8 +106,7 @@ impl Environment {
                 include_str!("shaders/light_shared.frag"),
                 include_str!("shaders/prefilter.frag")
             );
-            let max_mip_levels = 5;
-            for mip in 0..max_mip_levels {
+            for mip in 0..PREFILTER_MIP_LEVELS {
                 for side in CubeMapSide::iter() {
                     let sides = [side];
                     let color_target = prefilter_map.as_color_target(&sides, Some(mip));
@@ -117,7 +123,7 @@ impl Environment {
                                 program.use_texture_cube("environmentMap", environment_map);
                                 program.use_uniform(
                                     "roughness",
-                                    mip as f32 / (max_mip_levels as f32 - 1.0),
+                                    mip as f32 / (PREFILTER_MIP_LEVELS as f32 - 1.0),
                                 );
                                 program.use_uniform("resolution", environment_map.width() as f32);
                             },
@@ -162,6 +168,310 @@ impl Environment {
             irradiance_map,
             prefilter_map,
             brdf_map,
+            rotation: radians(0.0),
+            intensity: 1.0,
+            sun_direction: brightest_direction(context, environment_map),
         }
     }
+
+    ///
+    /// Rotates the environment the given angle around the up direction (the y-axis).
+    /// The rotation is applied when sampling the precalculated maps, so it is cheap to change each frame.
+    /// Use [Skybox::set_rotation_and_intensity] to rotate the skybox and the environment together and keep the image based lighting aligned with the skybox.
+    ///
+    pub fn set_rotation(&mut self, rotation: impl Into<Radians>) {
+        self.rotation = rotation.into();
+    }
+
+    ///
+    /// Returns the rotation of the environment around the up direction (the y-axis).
+    ///
+    pub fn rotation(&self) -> Radians {
+        self.rotation
+    }
+
+    ///
+    /// Sets the intensity which the light from the environment is multiplied with, for example to dim the environment. The default is `1.0`.
+    ///
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity;
+    }
+
+    ///
+    /// Returns the intensity which the light from the environment is multiplied with.
+    ///
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    ///
+    /// Returns the direction towards the brightest point in the environment map, typically the sun, with the current rotation applied.
+    /// The brightest point is found once when the environment is constructed.
+    /// This can be used to align a [DirectionalLight] with the sun, in which case the direction of the light should be the negative of this direction.
+    ///
+    pub fn sample_sun_direction(&self) -> Vec3 {
+        Mat3::from_angle_y(self.rotation) * self.sun_direction
+    }
+
+    ///
+    /// Returns the fragment shader source defining the uniforms and the functions for sampling the precalculated maps in the same way as the [AmbientLight]:
+    /// - `vec3 sample_irradiance(vec3 normal)` returns the diffuse light arriving at a surface with the given normal.
+    /// - `vec3 sample_prefiltered_specular(vec3 direction, float roughness)` returns the light reflected in the given direction from a surface with the given roughness.
+    /// - `vec2 sample_brdf(float NdV, float roughness)` returns the scale and bias of the fresnel reflectance at normal incidence (the split sum approximation),
+    ///   where `NdV` is the cosine of the angle between the normal and the view direction.
+    ///
+    /// The directions are in world space and the rotation and intensity of the environment are applied.
+    /// The specular contribution of the environment is therefore `sample_prefiltered_specular(reflect(-V, N), roughness) * (F * brdf.x + brdf.y)`,
+    /// where `brdf = sample_brdf(NdV, roughness)` and `F` is the fresnel reflectance.
+    /// Send the uniforms to the program using [Program::use_environment].
+    ///
+    pub fn fragment_shader_source() -> &'static str {
+        include_str!("shaders/environment.frag")
+    }
+}
+
+impl Program {
+    ///
+    /// Sends the precalculated maps, the rotation, the intensity and the number of mip levels of the prefiltered map of the environment to this program,
+    /// which must contain the shader source returned by [Environment::fragment_shader_source].
+    /// The uniforms which are not used by the shader are skipped, so it is fine to for example only use the specular part of the environment.
+    ///
+    pub fn use_environment(&self, environment: &Environment) {
+        if self.requires_uniform("irradianceMap") {
+            self.use_texture_cube("irradianceMap", &environment.irradiance_map);
+        }
+        if self.requires_uniform("prefilterMap") {
+            self.use_texture_cube("prefilterMap", &environment.prefilter_map);
+            self.use_uniform_if_required("environmentMaxLod", (PREFILTER_MIP_LEVELS - 1) as f32);
+        }
+        if self.requires_uniform("brdfLUT") {
+            self.use_texture("brdfLUT", &environment.brdf_map);
+        }
+        self.use_uniform_if_required(
+            "environmentRotation",
+            Mat3::from_angle_y(-environment.rotation),
+        );
+        self.use_uniform_if_required("environmentIntensity", environment.intensity);
+    }
+}
+
+///
+/// Finds the direction towards the brightest point in the given environment map by rendering it to a low resolution equirectangular texture and reading it back.
+///
+fn brightest_direction(context: &Context, environment_map: &TextureCubeMap) -> Vec3 {
+    let (width, height) = (256, 128);
+    let mut texture = Texture2D::new_empty::<[f32; 4]>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let luminance = texture
+        .as_color_target(None)
+        .clear(ClearState::default())
+        .write(|| {
+            apply_effect(
+                context,
+                include_str!("shaders/brightest_direction.frag"),
+                RenderStates::default(),
+                Viewport::new_at_origo(width, height),
+                |program| {
+                    program.use_texture_cube("environmentMap", environment_map);
+                    program.use_uniform("resolution", vec2(width as f32, height as f32));
+                },
+            )
+        })
+        .read::<[f32; 4]>();
+    let index = luminance.iter().enumerate().fold(0, |brightest, (i, l)| {
+        if l[0] > luminance[brightest][0] {
+            i
+        } else {
+            brightest
+        }
+    });
+    let u = (((index as u32 % width) as f32) + 0.5) / width as f32;
+    let v = (((index as u32 / width) as f32) + 0.5) / height as f32;
+    let phi = (u - 0.5) * 2.0 * std::f32::consts::PI;
+    let theta = (v - 0.5) * std::f32::consts::PI;
+    vec3(
+        theta.cos() * phi.cos(),
+        theta.sin(),
+        theta.cos() * phi.sin(),
+    )
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    ///
+    /// A material which only shows the specular reflection of the environment using the public shader source.
+    ///
+    struct SpecularMaterial<'a> {
+        environment: &'a Environment,
+        roughness: f32,
+    }
+
+    impl Material for SpecularMaterial<'_> {
+        fn fragment_shader(&self, _lights: &[&dyn Light]) -> FragmentShader {
+            FragmentShader {
+                source: format!(
+                    "{}{}{}",
+                    include_str!("../../core/shared.frag"),
+                    Environment::fragment_shader_source(),
+                    "
+                    uniform vec3 cameraPosition;
+                    uniform float roughness;
+                    in vec3 pos;
+                    in vec3 nor;
+                    layout (location = 0) out vec4 outColor;
+                    void main()
+                    {
+                        vec3 N = normalize(gl_FrontFacing ? nor : -nor);
+                        vec3 V = normalize(cameraPosition - pos);
+                        vec2 brdf = sample_brdf(max(0.001, dot(N, V)), roughness);
+                        vec3 color = sample_prefiltered_specular(reflect(-V, N), roughness) * (brdf.x + brdf.y);
+                        outColor = vec4(srgb_from_rgb(reinhard_tone_mapping(color)), 1.0);
+                    }
+                    "
+                ),
+                attributes: FragmentAttributes {
+                    position: true,
+                    normal: true,
+                    ..FragmentAttributes::NONE
+                },
+            }
+        }
+
+        fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+            program.use_uniform("cameraPosition", camera.position());
+            program.use_uniform("roughness", self.roughness);
+            program.use_environment(self.environment);
+        }
+
+        fn render_states(&self) -> RenderStates {
+            RenderStates::default()
+        }
+
+        fn material_type(&self) -> MaterialType {
+            MaterialType::Opaque
+        }
+    }
+
+    fn face(color: [u8; 4]) -> CpuTexture {
+        CpuTexture {
+            data: TextureData::RgbaU8(vec![color; 16]),
+            width: 4,
+            height: 4,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_custom_material_matches_physical_material_specular() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let environment_map = TextureCubeMap::new(
+            &context,
+            &face([255, 0, 0, 255]),
+            &face([0, 255, 0, 255]),
+            &face([0, 0, 255, 255]),
+            &face([255, 255, 0, 255]),
+            &face([0, 255, 255, 255]),
+            &face([255, 0, 255, 255]),
+        );
+        let mut light =
+            AmbientLight::new_with_environment(&context, 1.0, Color::WHITE, &environment_map);
+        light
+            .environment
+            .as_mut()
+            .unwrap()
+            .set_rotation(degrees(30.0));
+        let sphere = Mesh::new(&context, &CpuMesh::sphere(32));
+        // A white metallic surface reflects all light, so there is no diffuse contribution and the fresnel reflectance is one
+        let roughness = 0.4;
+        let physical_material = PhysicalMaterial::new_opaque(
+            &context,
+            &CpuMaterial {
+                albedo: Color::WHITE,
+                metallic: 1.0,
+                roughness,
+                ..Default::default()
+            },
+        );
+        let specular_material = SpecularMaterial {
+            environment: light.environment.as_ref().unwrap(),
+            roughness,
+        };
+
+        let size = 32;
+        let camera = Camera::new_perspective(
+            Viewport::new_at_origo(size, size),
+            vec3(0.0, 1.0, 3.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            degrees(45.0),
+            0.1,
+            10.0,
+        );
+        let mut texture = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            size,
+            size,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            &context,
+            size,
+            size,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut render = |material: &dyn Material| {
+            RenderTarget::new(
+                texture.as_color_target(None),
+                depth_texture.as_depth_target(),
+            )
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+            .render_with_material(material, &camera, &sphere, &[&light])
+            .read_color::<[u8; 4]>()
+        };
+        let expected = render(&physical_material);
+        let actual = render(&specular_material);
+
+        assert!(expected.iter().any(|p| p[0] > 20 || p[1] > 20 || p[2] > 20));
+        for (e, a) in expected.iter().zip(actual.iter()) {
+            for c in 0..3 {
+                assert!((e[c] as i32 - a[c] as i32).abs() <= 1, "{:?} != {:?}", e, a);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rotate_skybox_and_environment() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let environment_map = std::sync::Arc::new(TextureCubeMap::new(
+            &context,
+            &face([255, 0, 0, 255]),
+            &face([0, 255, 0, 255]),
+            &face([0, 0, 255, 255]),
+            &face([255, 255, 0, 255]),
+            &face([0, 255, 255, 255]),
+            &face([255, 0, 255, 255]),
+        ));
+        let mut environment = Environment::new(&context, &environment_map);
+        let mut skybox = Skybox::new_with_texture(&context, environment_map);
+        skybox.set_rotation_and_intensity(&mut environment, degrees(90.0), 0.5);
+        assert_eq!(skybox.rotation(), environment.rotation());
+        assert_eq!(skybox.rotation(), degrees(90.0).into());
+        assert_eq!(skybox.intensity(), 0.5);
+        assert_eq!(environment.intensity(), 0.5);
+    }
 }
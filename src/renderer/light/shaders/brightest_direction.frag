@@ -0,0 +1,25 @@
+uniform samplerCube environmentMap;
+uniform vec2 resolution;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+vec3 direction_from_uv(vec2 uv) {
+    float phi = (uv.x - 0.5) * 6.2831853;
+    float theta = (uv.y - 0.5) * 3.1415927;
+    return vec3(cos(theta) * cos(phi), sin(theta), cos(theta) * sin(phi));
+}
+
+void main() {
+    // Use the brightest of a number of samples inside the texel to avoid missing small and bright areas like the sun
+    float brightest = 0.0;
+    for (int x = 0; x < 4; x++) {
+        for (int y = 0; y < 4; y++) {
+            vec2 uv = uvs + (vec2(float(x), float(y)) - 1.5) / (4.0 * resolution);
+            vec3 color = texture(environmentMap, direction_from_uv(uv)).rgb;
+            brightest = max(brightest, dot(color, vec3(0.2126, 0.7152, 0.0722)));
+        }
+    }
+    outColor = vec4(brightest, 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,25 @@
+uniform samplerCube irradianceMap;
+uniform samplerCube prefilterMap;
+uniform sampler2D brdfLUT;
+uniform mat3 environmentRotation;
+uniform float environmentIntensity;
+uniform float environmentMaxLod;
+
+// Returns the diffuse light arriving at a surface with the given normal in world space.
+vec3 sample_irradiance(vec3 normal)
+{
+    return environmentIntensity * texture(irradianceMap, environmentRotation * normal).rgb;
+}
+
+// Returns the light reflected in the given direction in world space, prefiltered for the given roughness.
+// Each mip level of the prefiltered map corresponds to a roughness, so the roughness is mapped linearly to the mip levels.
+vec3 sample_prefiltered_specular(vec3 direction, float roughness)
+{
+    return environmentIntensity * textureLod(prefilterMap, environmentRotation * direction, roughness * environmentMaxLod).rgb;
+}
+
+// Returns the scale (x) and bias (y) applied to the fresnel reflectance at normal incidence, also known as the split sum approximation.
+vec2 sample_brdf(float NdV, float roughness)
+{
+    return texture(brdfLUT, vec2(NdV, roughness)).rg;
+}
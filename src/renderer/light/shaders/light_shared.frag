@@ -99,6 +99,55 @@ vec3 cooktorrance_specular(in float NdL, in float NdV, in float NdH, in vec3 spe
     return specular_fresnel * G * D / (4.0 * NdV * NdL);
 }
 
+#ifdef USE_ANISOTROPY
+// Set by the material before calculating the lighting.
+// The tangent and bitangent span the direction of the anisotropy in world space and the strength is in the range [-1, 1],
+// where positive values stretch the highlights along the tangent and negative values along the bitangent.
+vec3 anisotropy_tangent;
+vec3 anisotropy_bitangent;
+float anisotropy_strength;
+
+// Anisotropic GGX normal distribution function and height correlated Smith visibility function, see for example
+// https://google.github.io/filament/Filament.html#materialsystem/anisotropicmodel
+float D_GGX_anisotropic(float at, float ab, float TdH, float BdH, float NdH)
+{
+    float a2 = at * ab;
+    vec3 d = vec3(ab * TdH, at * BdH, a2 * NdH);
+    float d2 = dot(d, d);
+    float b2 = a2 / d2;
+    return a2 * b2 * b2 / PI;
+}
+
+float V_smith_GGX_anisotropic(float at, float ab, float TdV, float BdV, float TdL, float BdL, float NdV, float NdL)
+{
+    float lambda_V = NdL * length(vec3(at * TdV, ab * BdV, NdV));
+    float lambda_L = NdV * length(vec3(at * TdL, ab * BdL, NdL));
+    return 0.5 / (lambda_V + lambda_L);
+}
+
+vec3 anisotropic_specular(in vec3 L, in vec3 V, in vec3 H, in float NdL, in float NdV, in float NdH, in vec3 specular_fresnel, in float roughness)
+{
+    vec3 T = anisotropy_strength < 0.0 ? anisotropy_bitangent : anisotropy_tangent;
+    vec3 B = anisotropy_strength < 0.0 ? anisotropy_tangent : anisotropy_bitangent;
+    float alpha = max(roughness * roughness, 0.001);
+    float at = mix(alpha, 1.0, anisotropy_strength * anisotropy_strength);
+    float ab = alpha;
+    float D = D_GGX_anisotropic(at, ab, dot(T, H), dot(B, H), NdH);
+    float Vis = V_smith_GGX_anisotropic(at, ab, dot(T, V), dot(B, V), dot(T, L), dot(B, L), NdV, NdL);
+    return specular_fresnel * D * Vis;
+}
+
+// Bends the normal used for looking up the reflection in an environment map towards the anisotropy direction to approximate stretched reflections
+vec3 anisotropic_reflection_normal(vec3 V, vec3 N, float roughness)
+{
+    vec3 direction = anisotropy_strength < 0.0 ? anisotropy_tangent : anisotropy_bitangent;
+    vec3 anisotropic_tangent = cross(direction, V);
+    vec3 anisotropic_normal = cross(anisotropic_tangent, direction);
+    float bend = abs(anisotropy_strength) * saturate(5.0 * roughness);
+    return normalize(mix(N, anisotropic_normal, bend));
+}
+#endif
+
 vec3 calculate_light(vec3 light_color, vec3 L, vec3 surface_color, vec3 V, vec3 N, float metallic, float roughness)
 {
     // compute material reflectance
@@ -126,8 +175,13 @@ vec3 calculate_light(vec3 light_color, vec3 L, vec3 surface_color, vec3 V, vec3
 #endif
 
 #ifdef COOK
+#ifdef USE_ANISOTROPY
+    // specular reflectance with anisotropic COOK-TORRANCE
+    vec3 specular = anisotropic_specular(L, V, H, NdL, NdV, NdH, specular_fresnel, roughness);
+#else
     // specular reflectance with COOK-TORRANCE
     vec3 specular = cooktorrance_specular(NdL, NdV, NdH, specular_fresnel, roughness);
+#endif
 #endif
 
     // diffuse is common for any model
@@ -178,6 +232,20 @@ float calculate_shadow(sampler2D shadowMap, mat4 shadowMVP, vec3 position)
     return visibility * 0.25;
 }
 
+vec3 calculate_cookie(sampler2D cookieMap, mat4 cookieMVP, mat3 cookieTransformation, vec3 position)
+{
+    vec4 cookie_coord = cookieMVP * vec4(position, 1.);
+    if (cookie_coord.w <= 0.0) {
+        return vec3(0.0);
+    }
+    vec2 uv = cookie_coord.xy / cookie_coord.w;
+    if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        // Clamp to black border, since that is not supported on all platforms
+        return vec3(0.0);
+    }
+    return texture(cookieMap, (cookieTransformation * vec3(uv, 1.0)).xy).rgb;
+}
+
 vec3 ImportanceSampleGGX(vec2 Xi, vec3 N, float roughness)
 {
 	float a = roughness*roughness;
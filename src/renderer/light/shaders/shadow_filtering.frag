@@ -0,0 +1,46 @@
+
+// The depth in the range [0, 1] which is linear in the distance to the light, given the window depth and the near plane, the far plane and whether the projection is perspective
+float shadow_linear_depth(float depth, vec3 shadowDepth)
+{
+    if (shadowDepth.z < 0.5) {
+        return depth;
+    }
+    float near = shadowDepth.x;
+    float far = shadowDepth.y;
+    float z = 2.0 * near * far / (far + near - (2.0 * depth - 1.0) * (far - near));
+    return clamp((z - near) / (far - near), 0.0, 1.0);
+}
+
+float calculate_variance_shadow(sampler2D shadowMap, mat4 shadowMVP, vec3 shadowDepth, float bleedReduction, vec3 position)
+{
+    vec4 shadow_coord = shadowMVP * vec4(position, 1.);
+    vec2 uv = shadow_coord.xy / shadow_coord.w;
+    if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        return 1.0;
+    }
+    float depth = shadow_linear_depth(shadow_coord.z / shadow_coord.w, shadowDepth);
+    vec2 moments = texture(shadowMap, uv).xy;
+    if (depth <= moments.x) {
+        return 1.0;
+    }
+    // The minimum variance replaces the depth bias used by the standard shadow map
+    float variance = max(moments.y - moments.x * moments.x, 0.00002);
+    float d = depth - moments.x;
+    float p_max = variance / (variance + d * d);
+    return clamp((p_max - bleedReduction) / (1.0 - bleedReduction), 0.0, 1.0);
+}
+
+// The exponent used for the exponential shadow map, a higher value gives sharper shadows but the precision of the floating point texture limits it
+const float SHADOW_EXPONENT = 80.0;
+
+float calculate_exponential_shadow(sampler2D shadowMap, mat4 shadowMVP, vec3 shadowDepth, vec3 position)
+{
+    vec4 shadow_coord = shadowMVP * vec4(position, 1.);
+    vec2 uv = shadow_coord.xy / shadow_coord.w;
+    if(uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+        return 1.0;
+    }
+    float depth = shadow_linear_depth(shadow_coord.z / shadow_coord.w, shadowDepth);
+    float occluder = texture(shadowMap, uv).x;
+    return clamp(occluder * exp(-SHADOW_EXPONENT * depth), 0.0, 1.0);
+}
@@ -0,0 +1,18 @@
+
+uniform sampler2D depthMap;
+uniform vec3 shadowDepth;
+uniform int exponential;
+
+in vec2 uvs;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    float depth = shadow_linear_depth(texture(depthMap, uvs).x, shadowDepth);
+    if (exponential == 1) {
+        outColor = vec4(exp(SHADOW_EXPONENT * depth), 0.0, 0.0, 1.0);
+    } else {
+        outColor = vec4(depth, depth * depth, 0.0, 1.0);
+    }
+}
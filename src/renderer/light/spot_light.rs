@@ -10,6 +10,8 @@ pub struct SpotLight {
     context: Context,
     shadow_texture: Option<DepthTexture2D>,
     shadow_matrix: Mat4,
+    static_shadow: Option<StaticShadowCache>,
+    filtered_shadow: Option<FilteredShadowMap>,
     /// The intensity of the light. This allows for higher intensity than 1 which can be used to simulate high intensity light sources like the sun.
     pub intensity: f32,
     /// The base color of the light.
@@ -20,8 +22,17 @@ pub struct SpotLight {
     pub direction: Vec3,
     /// The cutoff angle for the light.
     pub cutoff: Radians,
+    /// The angle from the light direction where the light starts to fade out smoothly until it reaches zero at the [SpotLight::cutoff] angle.
+    /// If `None`, which is the default, the inner cutoff is 75% of the current cutoff angle.
+    pub inner_cutoff: Option<Radians>,
     /// The [Attenuation] of the light.
     pub attenuation: Attenuation,
+    /// An optional texture, also called a cookie, which is projected from the light in the light direction, for example to simulate the shaped beam of a flashlight or a projector.
+    /// The light contribution is multiplied by the color of the texture, so black parts of the texture blocks the light completely.
+    /// The texture covers the cone of the light, nothing is projected outside the texture.
+    pub cookie: Option<Texture2DRef>,
+    /// The type of shadow map, which is applied the next time the shadow map is generated.
+    pub shadow_map_type: ShadowMapType,
 }
 
 impl SpotLight {
@@ -38,13 +49,18 @@ impl SpotLight {
         SpotLight {
             context: context.clone(),
             shadow_texture: None,
+            static_shadow: None,
+            filtered_shadow: None,
             intensity,
             color,
             position: *position,
             direction: *direction,
             cutoff: cutoff.into(),
+            inner_cutoff: None,
             attenuation,
             shadow_matrix: Mat4::identity(),
+            cookie: None,
+            shadow_map_type: ShadowMapType::Standard,
         }
     }
 
@@ -54,6 +70,8 @@ impl SpotLight {
     ///
     pub fn clear_shadow_map(&mut self) {
         self.shadow_texture = None;
+        self.static_shadow = None;
+        self.filtered_shadow = None;
         self.shadow_matrix = Mat4::identity();
     }
 
@@ -67,6 +85,95 @@ impl SpotLight {
         texture_size: u32,
         geometries: impl IntoIterator<Item = impl Geometry> + Clone,
     ) {
+        let shadow_camera = self.shadow_camera(texture_size, geometries.clone(), false);
+        self.shadow_matrix = shadow_matrix(&shadow_camera);
+
+        let mut shadow_texture = new_shadow_texture(&self.context, texture_size);
+        shadow_texture
+            .as_depth_target()
+            .clear(ClearState::default())
+            .write(|| render_shadow_casters(&self.context, &shadow_camera, geometries));
+        self.filtered_shadow = filter_shadow_map(
+            &self.context,
+            self.shadow_map_type,
+            &shadow_texture,
+            &shadow_camera,
+            self.filtered_shadow.take(),
+        );
+        self.shadow_texture = Some(shadow_texture);
+    }
+
+    ///
+    /// Generate a shadow map like [SpotLight::generate_shadow_map], but where the geometries are split into a static set, which does not change between frames,
+    /// and a dynamic set, for example moving characters. The depth of the static geometries is rendered once and cached, so each frame only the dynamic geometries are rendered
+    /// on top of a copy of the cached depth, which makes this much faster than [SpotLight::generate_shadow_map] when the static geometries are the majority of the scene.
+    ///
+    /// The cache is automatically regenerated when the position, direction or cutoff of the light or the texture size changes, but not when the static geometries change,
+    /// so call [SpotLight::invalidate_static_shadows] if a static geometry is moved, added or removed.
+    /// The far plane of the shadow is computed from the static geometries, so dynamic geometries further away from the light than all static geometries do not cast shadows.
+    ///
+    pub fn generate_shadow_map_with_static(
+        &mut self,
+        texture_size: u32,
+        static_geometries: impl IntoIterator<Item = impl Geometry> + Clone,
+        dynamic_geometries: impl IntoIterator<Item = impl Geometry>,
+    ) {
+        let light_state = vec![
+            texture_size as f32,
+            self.position.x,
+            self.position.y,
+            self.position.z,
+            self.direction.x,
+            self.direction.y,
+            self.direction.z,
+            self.cutoff.0,
+        ];
+        let mut static_shadow = self.static_shadow.take();
+        let mut shadow_texture = self.shadow_texture.take();
+        let shadow_camera = generate_cached_shadow_map(
+            &self.context,
+            texture_size,
+            light_state,
+            &mut static_shadow,
+            &mut shadow_texture,
+            || Some(self.shadow_camera(texture_size, static_geometries.clone(), true)),
+            static_geometries.clone(),
+            dynamic_geometries,
+        );
+        self.static_shadow = static_shadow;
+        self.shadow_texture = shadow_texture;
+        if let Some(shadow_camera) = shadow_camera {
+            if let Some(shadow_texture) = &self.shadow_texture {
+                self.filtered_shadow = filter_shadow_map(
+                    &self.context,
+                    self.shadow_map_type,
+                    shadow_texture,
+                    &shadow_camera,
+                    self.filtered_shadow.take(),
+                );
+            }
+            self.shadow_matrix = shadow_matrix(&shadow_camera);
+        }
+    }
+
+    ///
+    /// Invalidates the cached depth of the static geometries given to [SpotLight::generate_shadow_map_with_static],
+    /// so that it is regenerated the next time that method is called. This must be called when any of the static geometries are changed.
+    ///
+    pub fn invalidate_static_shadows(&mut self) {
+        self.static_shadow = None;
+    }
+
+    ///
+    /// Computes the camera used for rendering the shadow map which covers the given geometries.
+    /// If the shadow map is reused for other geometries, the near plane is moved close to the light since the other geometries might be closer to the light.
+    ///
+    fn shadow_camera(
+        &self,
+        texture_size: u32,
+        geometries: impl IntoIterator<Item = impl Geometry>,
+        reused: bool,
+    ) -> Camera {
         let position = self.position;
         let direction = self.direction;
         let up = compute_up_direction(self.direction);
@@ -75,15 +182,18 @@ impl SpotLight {
 
         let mut z_far = 0.0f32;
         let mut z_near = f32::MAX;
-        for geometry in geometries.clone() {
+        for geometry in geometries {
             let aabb = geometry.aabb();
             if !aabb.is_empty() {
                 z_far = z_far.max(aabb.distance_max(&self.position));
                 z_near = z_near.min(aabb.distance(&self.position));
             }
         }
+        if reused {
+            z_near = z_far * 0.001;
+        }
 
-        let shadow_camera = Camera::new_perspective(
+        Camera::new_perspective(
             viewport,
             position,
             position + direction,
@@ -91,111 +201,104 @@ impl SpotLight {
             self.cutoff,
             z_near.max(0.01),
             z_far,
-        );
-        self.shadow_matrix = shadow_matrix(&shadow_camera);
-
-        let mut shadow_texture = DepthTexture2D::new::<f32>(
-            &self.context,
-            texture_size,
-            texture_size,
-            Wrapping::ClampToEdge,
-            Wrapping::ClampToEdge,
-        );
-        let depth_material = DepthMaterial {
-            render_states: RenderStates {
-                write_mask: WriteMask::DEPTH,
-                ..Default::default()
-            },
-            ..Default::default()
-        };
-        shadow_texture
-            .as_depth_target()
-            .clear(ClearState::default())
-            .write(|| {
-                for geometry in geometries
-                    .into_iter()
-                    .filter(|g| shadow_camera.in_frustum(&g.aabb()))
-                {
-                    geometry.render_with_material(&depth_material, &shadow_camera, &[]);
-                }
-            });
-        self.shadow_texture = Some(shadow_texture);
+        )
     }
 
     ///
     /// Returns a reference to the shadow map if it has been generated.
+    /// This is the depth as seen from the light, also when the [ShadowMapType] is not [ShadowMapType::Standard].
+    /// The shadow map is a depth texture with 32 bit floating point precision, not a renderbuffer, so it can be sampled in a shader,
+    /// for example to visualize it for debugging or as input to an effect using [DepthTexture::Single].
     ///
     pub fn shadow_map(&self) -> Option<&DepthTexture2D> {
         self.shadow_texture.as_ref()
     }
+
+    ///
+    /// The matrix which projects a position into the texture coordinates of the cookie, covering the entire cone of the light.
+    /// Only the direction from the light matters, so the near and far planes are arbitrary.
+    ///
+    fn cookie_matrix(&self) -> Mat4 {
+        let camera = Camera::new_perspective(
+            Viewport::new_at_origo(1, 1),
+            self.position,
+            self.position + self.direction,
+            compute_up_direction(self.direction),
+            self.cutoff * 2.0,
+            0.01,
+            1.0,
+        );
+        shadow_matrix(&camera)
+    }
 }
 
 impl Light for SpotLight {
     fn shader_source(&self, i: u32) -> String {
+        let mut uniforms = String::new();
+        let mut factors = String::new();
         if self.shadow_texture.is_some() {
-            format!(
+            let (shadow_uniforms, visibility) =
+                shadow_shader_source(self.filtered_shadow.as_ref(), i);
+            uniforms.push_str(&shadow_uniforms);
+            factors.push_str(&format!("result *= {};", visibility));
+        }
+        if self.cookie.is_some() {
+            uniforms.push_str(&format!(
                 "
-                    uniform sampler2D shadowMap{};
-                    uniform mat4 shadowMVP{};
-        
-                    uniform vec3 color{};
-                    uniform vec3 attenuation{};
-                    uniform vec3 position{};
-                    uniform float cutoff{};
-                    uniform vec3 direction{};
-                    vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
-                    {{
-                        vec3 light_direction = position{} - position;
-                        float distance = length(light_direction);
-                        light_direction = light_direction / distance;
-        
-                        float angle = acos(dot(-light_direction, normalize(direction{})));
-                        float cutoff = cutoff{};
-                    
-                        vec3 result = vec3(0.0);
-                        if (angle < cutoff) {{
-                            vec3 light_color = attenuate(color{}, attenuation{}, distance);
-                            result = calculate_light(light_color, light_direction, surface_color, view_direction, normal, 
-                                metallic, roughness) * (1.0 - smoothstep(0.75 * cutoff, cutoff, angle));
-                            result *= calculate_shadow(shadowMap{}, shadowMVP{}, position);
-                        }}
-                        return result;
-                    }}
+                    uniform sampler2D cookieMap{};
+                    uniform mat4 cookieMVP{};
+                    uniform mat3 cookieTransformation{};",
+                i, i, i
+            ));
+            factors.push_str(&format!(
+                "result *= calculate_cookie(cookieMap{}, cookieMVP{}, cookieTransformation{}, position);",
+                i, i, i
+            ));
+        }
+        format!(
+            "
+                {}
+                uniform vec3 color{};
+                uniform vec3 attenuation{};
+                uniform vec3 position{};
+                uniform float cutoff{};
+                uniform float innerCutoff{};
+                uniform vec3 direction{};
+                vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
+                {{
+                    vec3 light_direction = position{} - position;
+                    float distance = length(light_direction);
+                    light_direction = light_direction / distance;
+    
+                    // The cosine of the angle between the light direction and the direction to the surface compared to the cosine of the cutoff angles
+                    float theta = dot(-light_direction, normalize(direction{}));
                 
-                ", i, i, i, i, i, i, i, i, i, i, i, i, i, i, i)
-        } else {
-            format!(
-                "
-                    uniform vec3 color{};
-                    uniform vec3 attenuation{};
-                    uniform vec3 position{};
-                    uniform float cutoff{};
-                    uniform vec3 direction{};
-                    vec3 calculate_lighting{}(vec3 surface_color, vec3 position, vec3 normal, vec3 view_direction, float metallic, float roughness, float occlusion)
-                    {{
-                        vec3 light_direction = position{} - position;
-                        float distance = length(light_direction);
-                        light_direction = light_direction / distance;
-        
-                        float angle = acos(dot(-light_direction, normalize(direction{})));
-                        float cutoff = cutoff{};
-                    
-                        vec3 result = vec3(0.0);
-                        if (angle < cutoff) {{
-                            vec3 light_color = attenuate(color{}, attenuation{}, distance);
-                            result = calculate_light(light_color, light_direction, surface_color, view_direction, normal, 
-                                metallic, roughness) * (1.0 - smoothstep(0.75 * cutoff, cutoff, angle));
-                        }}
-                        return result;
+                    vec3 result = vec3(0.0);
+                    if (theta > cutoff{}) {{
+                        vec3 light_color = attenuate(color{}, attenuation{}, distance);
+                        result = calculate_light(light_color, light_direction, surface_color, view_direction, normal, 
+                            metallic, roughness) * smoothstep(cutoff{}, max(innerCutoff{}, cutoff{} + 0.0001), theta);
+                        {}
                     }}
-                
-                ", i, i, i, i, i, i, i, i, i, i, i)
-        }
+                    return result;
+                }}
+            
+            ", uniforms, i, i, i, i, i, i, i, i, i, i, i, i, i, i, i, factors)
     }
     fn use_uniforms(&self, program: &Program, i: u32) {
         if let Some(ref tex) = self.shadow_texture {
-            program.use_depth_texture(&format!("shadowMap{}", i), tex);
-            program.use_uniform(&format!("shadowMVP{}", i), self.shadow_matrix);
+            use_shadow_uniforms(
+                program,
+                i,
+                tex,
+                self.shadow_matrix,
+                self.filtered_shadow.as_ref(),
+            );
+        }
+        if let Some(ref cookie) = self.cookie {
+            program.use_texture(&format!("cookieMap{}", i), cookie);
+            program.use_uniform(&format!("cookieMVP{}", i), self.cookie_matrix());
+            program.use_uniform(&format!("cookieTransformation{}", i), cookie.transformation);
         }
         program.use_uniform(
             &format!("color{}", i),
@@ -211,6 +314,14 @@ impl Light for SpotLight {
         );
         program.use_uniform(&format!("position{}", i), self.position);
         program.use_uniform(&format!("direction{}", i), self.direction.normalize());
-        program.use_uniform(&format!("cutoff{}", i), self.cutoff.0);
+        program.use_uniform(&format!("cutoff{}", i), self.cutoff.0.cos());
+        program.use_uniform(
+            &format!("innerCutoff{}", i),
+            self.inner_cutoff
+                .unwrap_or(self.cutoff * 0.75)
+                .0
+                .min(self.cutoff.0)
+                .cos(),
+        );
     }
 }
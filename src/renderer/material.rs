@@ -11,6 +11,8 @@ pub use three_d_asset::material::{
     GeometryFunction, LightingModel, NormalDistributionFunction, PbrMaterial as CpuMaterial,
 };
 
+use dyn_clone::DynClone;
+
 mod color_material;
 #[doc(inline)]
 pub use color_material::*;
@@ -31,6 +33,10 @@ mod position_material;
 #[doc(inline)]
 pub use position_material::*;
 
+mod velocity_material;
+#[doc(inline)]
+pub use velocity_material::*;
+
 mod uv_material;
 #[doc(inline)]
 pub use uv_material::*;
@@ -55,6 +61,10 @@ mod isosurface_material;
 #[doc(inline)]
 pub use isosurface_material::*;
 
+mod flipbook;
+#[doc(inline)]
+pub use flipbook::*;
+
 use std::sync::Arc;
 
 ///
@@ -69,6 +79,37 @@ pub struct Texture2DRef {
     pub transformation: Mat3,
 }
 
+impl Texture2DRef {
+    ///
+    /// Replaces the content of the texture with the given data, for example when the image file it was loaded from has changed.
+    /// If the new data has the same size and format as the texture, the texture is updated in place, so all materials sharing the texture see the new content.
+    /// Otherwise, if this is the only reference to the texture, it is reloaded in place (see [Texture2D::reload]),
+    /// and if not, this reference is changed to a new texture, since a shared texture cannot change size or format, and the other references keep the old texture.
+    ///
+    pub fn reload(&mut self, context: &Context, cpu_texture: &CpuTexture) {
+        if self.texture.refill(cpu_texture) {
+            return;
+        }
+        if let Some(texture) = Arc::get_mut(&mut self.texture) {
+            texture.reload(cpu_texture);
+        } else {
+            self.texture = Arc::new(Texture2D::new_with_color_space(
+                context,
+                cpu_texture,
+                self.texture.color_space(),
+            ));
+        }
+    }
+
+    ///
+    /// Sets the [Texture2DRef::transformation] from the given offset, scale and rotation of the uv coordinates.
+    /// The transformation is applied each time the texture is used, so it can be changed every frame, for example to scroll the texture.
+    ///
+    pub fn set_transform(&mut self, transform: TextureTransform) {
+        self.transformation = transform.matrix();
+    }
+}
+
 impl std::ops::Deref for Texture2DRef {
     type Target = Texture2D;
     fn deref(&self) -> &Self::Target {
@@ -85,6 +126,51 @@ impl std::convert::From<Arc<Texture2D>> for Texture2DRef {
     }
 }
 
+///
+/// A transformation of the uv coordinates given as an offset, a scale and a rotation, for example to repeat a texture many times across a large surface.
+/// The transformation is defined in the same way as in the glTF [KHR_texture_transform](https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_texture_transform) extension,
+/// ie. the uv coordinates are first scaled, then rotated and finally offset.
+/// Use [Texture2DRef::set_transform] to apply it to a single texture or for example [PhysicalMaterial::set_texture_transform] to apply it to all textures of a material.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextureTransform {
+    /// The offset added to the uv coordinates after scaling and rotating.
+    pub offset: Vec2,
+    /// The scale of the uv coordinates, a scale of `50.0` repeats a texture with [Wrapping::Repeat] 50 times.
+    pub scale: Vec2,
+    /// The rotation of the uv coordinates, which is counterclockwise in texture space where the v axis points down.
+    pub rotation: Radians,
+}
+
+impl TextureTransform {
+    ///
+    /// Creates a new texture transform with the given offset, scale and rotation.
+    ///
+    pub fn new(offset: Vec2, scale: Vec2, rotation: Radians) -> Self {
+        Self {
+            offset,
+            scale,
+            rotation,
+        }
+    }
+
+    ///
+    /// Returns the transformation matrix which is applied to the uv coordinates, see [Texture2DRef::transformation].
+    ///
+    pub fn matrix(&self) -> Mat3 {
+        let (sin, cos) = self.rotation.0.sin_cos();
+        Mat3::from_translation(self.offset)
+            * Mat3::new(cos, -sin, 0.0, sin, cos, 0.0, 0.0, 0.0, 1.0)
+            * Mat3::from_nonuniform_scale(self.scale.x, self.scale.y)
+    }
+}
+
+impl Default for TextureTransform {
+    fn default() -> Self {
+        Self::new(vec2(0.0, 0.0), vec2(1.0, 1.0), radians(0.0))
+    }
+}
+
 ///
 /// Defines the material type which is needed to render the objects in the correct order.
 /// For example, transparent objects need to be rendered back to front, whereas opaque objects need to be rendered front to back.
@@ -99,6 +185,56 @@ pub enum MaterialType {
     Deferred,
 }
 
+///
+/// Displacement of the vertices of a geometry along their normals by the values in a height map, see for example [PhysicalMaterial::displacement].
+/// The height map is sampled in the vertex shader, so the geometry needs enough vertices to show the details of the height map.
+/// The normals are adjusted in the fragment shader using the screen space derivatives of the height, so the lighting follows the displaced surface.
+/// The displacement is also applied when rendering shadows, see [Material::displacement].
+///
+#[derive(Clone)]
+pub struct Displacement {
+    /// The height map, the displacement is the value in the red channel multiplied by the [Self::scale].
+    pub texture: Texture2DRef,
+    /// The displacement in world units of a value of `1.0` in the height map.
+    pub scale: f32,
+    /// The uv coordinates are multiplied by this value before sampling the height map, which repeats the height map if it uses [Wrapping::Repeat].
+    pub uv_scale: Vec2,
+    /// This value is added to the uv coordinates after scaling, which for example can be changed each frame to scroll the height map for animated water.
+    pub uv_offset: Vec2,
+}
+
+impl Displacement {
+    ///
+    /// Creates a new displacement with the given height map and scale without scaling or offsetting the uv coordinates.
+    ///
+    pub fn new(texture: Texture2DRef, scale: f32) -> Self {
+        Self {
+            texture,
+            scale,
+            uv_scale: vec2(1.0, 1.0),
+            uv_offset: vec2(0.0, 0.0),
+        }
+    }
+
+    ///
+    /// Returns the fragment shader source defining the uniforms and the `float displacement_height(vec2 uv)` function, which is also used by the vertex shader.
+    ///
+    pub fn fragment_shader_source() -> &'static str {
+        include_str!("material/shaders/displacement.frag")
+    }
+
+    ///
+    /// Sends the uniform data needed for the displacement to the vertex and fragment shader.
+    ///
+    pub fn use_uniforms(&self, program: &Program) {
+        program.use_texture("displacementTexture", &self.texture);
+        program.use_uniform("displacementTexTransform", self.texture.transformation);
+        program.use_uniform("displacementScale", self.scale);
+        program.use_uniform("displacementUvScale", self.uv_scale);
+        program.use_uniform("displacementUvOffset", self.uv_offset);
+    }
+}
+
 ///
 /// Describes the set of attributes provided by a [geometry] and consumed by a [Material], ie. calculated in the vertex shader and then sent to the fragment shader.
 /// To use an attribute for a material, add the relevant shader code to the fragment shader source (documented for each attribute) and return this struct from [Material::fragment_shader] with the relevant attribute set to true.
@@ -115,6 +251,8 @@ pub struct FragmentAttributes {
     pub uv: bool,
     /// Color: `in vec4 col;`
     pub color: bool,
+    /// The start time of each instance, or zero if the geometry does not have per instance start times, for example used by a [Flipbook]: `in float start_time;`
+    pub start_time: bool,
 }
 
 impl FragmentAttributes {
@@ -125,6 +263,7 @@ impl FragmentAttributes {
         tangents: true,
         uv: true,
         color: true,
+        start_time: true,
     };
     /// No attributes
     pub const NONE: Self = Self {
@@ -133,6 +272,7 @@ impl FragmentAttributes {
         tangents: false,
         uv: false,
         color: false,
+        start_time: false,
     };
 }
 
@@ -171,6 +311,40 @@ pub trait Material {
     /// Returns the type of material.
     ///
     fn material_type(&self) -> MaterialType;
+
+    ///
+    /// Returns the [Displacement] that this material applies to the vertices of a geometry along their normals,
+    /// or `None`, which is the default, if this material does not displace the vertices.
+    /// The uniforms of the displacement must be sent in [Material::use_uniforms] using [Displacement::use_uniforms].
+    /// A geometry in a [Gm] with a displacing material is displaced in the same way when it is rendered with another material,
+    /// for example when rendering shadow maps, so the shadows match the displaced surface.
+    ///
+    fn displacement(&self) -> Option<Displacement> {
+        None
+    }
+
+    ///
+    /// Returns the maximum distance that this material displaces the vertices of a geometry along their normals,
+    /// which by default is the scale of the [Material::displacement].
+    /// Geometries that support displacement, like [Mesh] and [InstancedMesh], then apply the displacement in the vertex shader.
+    ///
+    fn max_displacement(&self) -> Option<f32> {
+        self.displacement().map(|displacement| displacement.scale)
+    }
+
+    ///
+    /// For updating the animation of this material, for example a [Flipbook]. The time parameter should be some continious time, for example the time since start.
+    /// The default implementation does nothing.
+    ///
+    fn animate(&mut self, _time: f32) {}
+
+    ///
+    /// Returns an id which identifies this instance of the material, so objects which share the same material, for example by reference, return the same id (see [SortInfo::material_id]).
+    /// The default implementation returns the memory address of the material, which is unique as long as the material is alive and not moved.
+    ///
+    fn material_id(&self) -> usize {
+        self as *const Self as *const () as usize
+    }
 }
 
 ///
@@ -181,6 +355,15 @@ pub trait FromCpuMaterial: std::marker::Sized {
     /// Creates a new material that can be used for rendering from a [CpuMaterial].
     ///
     fn from_cpu_material(context: &Context, cpu_material: &CpuMaterial) -> Self;
+
+    ///
+    /// Updates this material from a [CpuMaterial], for example when the file it was loaded from has changed, see [Model::reload].
+    /// The default implementation replaces this material with a new material created by [FromCpuMaterial::from_cpu_material],
+    /// implementations should instead update the textures in place (see [Texture2DRef::reload]), so other materials sharing the textures see the update.
+    ///
+    fn reload(&mut self, context: &Context, cpu_material: &CpuMaterial) {
+        *self = Self::from_cpu_material(context, cpu_material);
+    }
 }
 
 ///
@@ -193,6 +376,29 @@ pub trait FromCpuVoxelGrid: std::marker::Sized {
     fn from_cpu_voxel_grid(context: &Context, cpu_voxel_grid: &CpuVoxelGrid) -> Self;
 }
 
+///
+/// A [Material] that can be cloned as a trait object, for example a `Box<dyn CloneMaterial>`,
+/// which is useful when the type of the material is not known, for example when instancing or serializing a scene.
+/// This is implemented for all materials that implement [Clone], including all the built-in materials.
+/// A boxed material is cloned using [Clone::clone] and a material behind a reference is cloned into a new box using [CloneMaterial::clone_material].
+///
+/// Note that the GPU resources, for example textures, are shared between the clones and not copied.
+///
+pub trait CloneMaterial: Material + DynClone {
+    ///
+    /// Clones this material into a new box.
+    ///
+    fn clone_material(&self) -> Box<dyn CloneMaterial>;
+}
+
+impl<T: Material + Clone + 'static> CloneMaterial for T {
+    fn clone_material(&self) -> Box<dyn CloneMaterial> {
+        Box::new(self.clone())
+    }
+}
+
+dyn_clone::clone_trait_object!(CloneMaterial);
+
 impl<T: Material + ?Sized> Material for &T {
     fn fragment_shader(&self, lights: &[&dyn Light]) -> FragmentShader {
         (*self).fragment_shader(lights)
@@ -206,6 +412,15 @@ impl<T: Material + ?Sized> Material for &T {
     fn material_type(&self) -> MaterialType {
         (*self).material_type()
     }
+    fn max_displacement(&self) -> Option<f32> {
+        (*self).max_displacement()
+    }
+    fn displacement(&self) -> Option<Displacement> {
+        (*self).displacement()
+    }
+    fn material_id(&self) -> usize {
+        (*self).material_id()
+    }
 }
 
 impl<T: Material + ?Sized> Material for &mut T {
@@ -221,9 +436,21 @@ impl<T: Material + ?Sized> Material for &mut T {
     fn material_type(&self) -> MaterialType {
         (**self).material_type()
     }
+    fn max_displacement(&self) -> Option<f32> {
+        (**self).max_displacement()
+    }
+    fn displacement(&self) -> Option<Displacement> {
+        (**self).displacement()
+    }
+    fn animate(&mut self, time: f32) {
+        (**self).animate(time)
+    }
+    fn material_id(&self) -> usize {
+        (**self).material_id()
+    }
 }
 
-impl<T: Material> Material for Box<T> {
+impl<T: Material + ?Sized> Material for Box<T> {
     fn fragment_shader(&self, lights: &[&dyn Light]) -> FragmentShader {
         self.as_ref().fragment_shader(lights)
     }
@@ -236,6 +463,18 @@ impl<T: Material> Material for Box<T> {
     fn material_type(&self) -> MaterialType {
         self.as_ref().material_type()
     }
+    fn max_displacement(&self) -> Option<f32> {
+        self.as_ref().max_displacement()
+    }
+    fn displacement(&self) -> Option<Displacement> {
+        self.as_ref().displacement()
+    }
+    fn animate(&mut self, time: f32) {
+        self.as_mut().animate(time)
+    }
+    fn material_id(&self) -> usize {
+        self.as_ref().material_id()
+    }
 }
 
 impl<T: Material> Material for std::rc::Rc<T> {
@@ -251,6 +490,15 @@ impl<T: Material> Material for std::rc::Rc<T> {
     fn material_type(&self) -> MaterialType {
         self.as_ref().material_type()
     }
+    fn max_displacement(&self) -> Option<f32> {
+        self.as_ref().max_displacement()
+    }
+    fn displacement(&self) -> Option<Displacement> {
+        self.as_ref().displacement()
+    }
+    fn material_id(&self) -> usize {
+        self.as_ref().material_id()
+    }
 }
 
 impl<T: Material> Material for std::sync::Arc<T> {
@@ -266,6 +514,15 @@ impl<T: Material> Material for std::sync::Arc<T> {
     fn material_type(&self) -> MaterialType {
         self.as_ref().material_type()
     }
+    fn max_displacement(&self) -> Option<f32> {
+        self.as_ref().max_displacement()
+    }
+    fn displacement(&self) -> Option<Displacement> {
+        self.as_ref().displacement()
+    }
+    fn material_id(&self) -> usize {
+        self.as_ref().material_id()
+    }
 }
 
 impl<T: Material> Material for std::cell::RefCell<T> {
@@ -281,6 +538,18 @@ impl<T: Material> Material for std::cell::RefCell<T> {
     fn material_type(&self) -> MaterialType {
         self.borrow().material_type()
     }
+    fn max_displacement(&self) -> Option<f32> {
+        self.borrow().max_displacement()
+    }
+    fn displacement(&self) -> Option<Displacement> {
+        self.borrow().displacement()
+    }
+    fn animate(&mut self, time: f32) {
+        self.get_mut().animate(time)
+    }
+    fn material_id(&self) -> usize {
+        self.borrow().material_id()
+    }
 }
 
 impl<T: Material> Material for std::sync::RwLock<T> {
@@ -296,6 +565,18 @@ impl<T: Material> Material for std::sync::RwLock<T> {
     fn material_type(&self) -> MaterialType {
         self.read().unwrap().material_type()
     }
+    fn max_displacement(&self) -> Option<f32> {
+        self.read().unwrap().max_displacement()
+    }
+    fn displacement(&self) -> Option<Displacement> {
+        self.read().unwrap().displacement()
+    }
+    fn animate(&mut self, time: f32) {
+        self.get_mut().unwrap().animate(time)
+    }
+    fn material_id(&self) -> usize {
+        self.read().unwrap().material_id()
+    }
 }
 
 fn is_transparent(cpu_material: &CpuMaterial) -> bool {
@@ -671,3 +952,185 @@ impl<T: PostMaterial> PostMaterial for std::sync::RwLock<T> {
         self.read().unwrap().material_type()
     }
 }
+
+fn reload_texture(
+    context: &Context,
+    texture: &mut Option<Texture2DRef>,
+    cpu_texture: Option<&CpuTexture>,
+    color_space: ColorSpace,
+) {
+    match (texture.as_mut(), cpu_texture) {
+        (Some(texture), Some(cpu_texture)) => texture.reload(context, cpu_texture),
+        (None, Some(cpu_texture)) => {
+            *texture = Some(
+                Arc::new(Texture2D::new_with_color_space(
+                    context,
+                    cpu_texture,
+                    color_space,
+                ))
+                .into(),
+            )
+        }
+        (_, None) => *texture = None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_clone_material() {
+        let material: Box<dyn CloneMaterial> = Box::new(ColorMaterial {
+            color: Color::new_opaque(255, 0, 0),
+            ..Default::default()
+        });
+        let clones = [material.clone(), material.as_ref().clone_material()];
+        for clone in clones {
+            assert_eq!(clone.material_type(), MaterialType::Opaque);
+            assert_eq!(
+                clone.fragment_shader(&[]).source,
+                material.fragment_shader(&[]).source
+            );
+        }
+    }
+
+    #[test]
+    fn test_texture_transform() {
+        let uv = |transform: TextureTransform, u: f32, v: f32| {
+            let uv = transform.matrix() * vec3(u, v, 1.0);
+            vec2(uv.x, uv.y)
+        };
+        assert_eq!(TextureTransform::default().matrix(), Mat3::identity());
+
+        let transform = TextureTransform::new(vec2(0.5, 0.0), vec2(2.0, 3.0), radians(0.0));
+        assert_eq!(uv(transform, 1.0, 1.0), vec2(2.5, 3.0));
+
+        // Rotating by 90 degrees maps the u axis to the negative v axis and the v axis to the u axis
+        let transform = TextureTransform::new(
+            vec2(0.0, 1.0),
+            vec2(1.0, 1.0),
+            radians(0.5 * std::f32::consts::PI),
+        );
+        let result = uv(transform, 1.0, 0.0);
+        assert!(result.distance(vec2(0.0, 0.0)) < 0.0001);
+        let result = uv(transform, 0.0, 1.0);
+        assert!(result.distance(vec2(1.0, 1.0)) < 0.0001);
+    }
+
+    #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_texture_transform_render() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let texture = Texture2DRef::from(Arc::new(Texture2D::new(
+            &context,
+            &CpuTexture {
+                data: TextureData::RgbaU8(vec![[255, 0, 0, 255], [0, 255, 0, 255]]),
+                width: 2,
+                height: 1,
+                min_filter: Interpolation::Nearest,
+                mag_filter: Interpolation::Nearest,
+                mip_map_filter: None,
+                wrap_s: Wrapping::Repeat,
+                wrap_t: Wrapping::Repeat,
+                ..Default::default()
+            },
+        )));
+        let viewport = Viewport::new_at_origo(8, 1);
+        let render = |transform: TextureTransform| {
+            let mut texture = texture.clone();
+            texture.set_transform(transform);
+            let rectangle = Gm::new(
+                Rectangle::new(&context, vec2(4.0, 0.5), degrees(0.0), 8.0, 1.0),
+                ColorMaterial {
+                    texture: Some(texture),
+                    ..Default::default()
+                },
+            );
+            let mut target = Texture2D::new_empty::<[u8; 4]>(
+                &context,
+                8,
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            target
+                .as_color_target(None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+                .render(&camera2d(viewport), &rectangle, &[])
+                .read::<[u8; 4]>()
+        };
+        let untransformed = render(TextureTransform::default());
+        let scaled = render(TextureTransform {
+            scale: vec2(2.0, 1.0),
+            ..Default::default()
+        });
+        let offset = render(TextureTransform {
+            offset: vec2(0.5, 0.0),
+            ..Default::default()
+        });
+        for x in 0..8 {
+            assert_eq!(scaled[x], untransformed[(2 * x) % 8]);
+            assert_eq!(offset[x], untransformed[(x + 4) % 8]);
+        }
+        assert_ne!(untransformed[0], untransformed[7]);
+    }
+
+    #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_reload_shared_texture() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let cpu_texture = |color: [u8; 4], width: u32| CpuTexture {
+            data: TextureData::RgbaU8(vec![color; width as usize]),
+            width,
+            height: 1,
+            min_filter: Interpolation::Nearest,
+            mag_filter: Interpolation::Nearest,
+            mip_map_filter: None,
+            ..Default::default()
+        };
+        let mut texture = Texture2DRef::from(Arc::new(Texture2D::new(
+            &context,
+            &cpu_texture([255, 0, 0, 255], 2),
+        )));
+        let shared = texture.clone();
+        let render = |texture: &Texture2DRef| {
+            let rectangle = Gm::new(
+                Rectangle::new(&context, vec2(1.0, 0.5), degrees(0.0), 2.0, 1.0),
+                ColorMaterial {
+                    texture: Some(texture.clone()),
+                    ..Default::default()
+                },
+            );
+            let mut target = Texture2D::new_empty::<[u8; 4]>(
+                &context,
+                2,
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            target
+                .as_color_target(None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+                .render(&camera2d(Viewport::new_at_origo(2, 1)), &rectangle, &[])
+                .read::<[u8; 4]>()
+        };
+
+        // Same size and format, so the shared texture is updated in place
+        texture.reload(&context, &cpu_texture([0, 255, 0, 255], 2));
+        assert!(Arc::ptr_eq(&texture.texture, &shared.texture));
+        assert_eq!(render(&shared), vec![[0, 255, 0, 255]; 2]);
+
+        // A shared texture cannot change size, so only the reloaded reference changes
+        texture.reload(&context, &cpu_texture([0, 0, 255, 255], 4));
+        assert!(!Arc::ptr_eq(&texture.texture, &shared.texture));
+        assert_eq!(texture.width(), 4);
+        assert_eq!(render(&shared), vec![[0, 255, 0, 255]; 2]);
+    }
+}
@@ -6,6 +6,9 @@ use std::sync::Arc;
 /// A material that renders a [Geometry] in a color defined by multiplying a color with an optional texture and optional per vertex colors.
 /// This material is not affected by lights.
 ///
+/// The per vertex colors are for example the [CpuMesh::colors] of a [Mesh] or the colors of a point cloud,
+/// so this material with [ColorMaterial::color] acting as a tint is the material to use for visualizing painted meshes or scan data.
+///
 #[derive(Clone, Default)]
 pub struct ColorMaterial {
     /// Base surface color. Assumed to be in linear color space.
@@ -16,6 +19,10 @@ pub struct ColorMaterial {
     pub render_states: RenderStates,
     /// Whether this material should be treated as a transparent material (An object needs to be rendered differently depending on whether it is transparent or opaque).
     pub is_transparent: bool,
+    /// An optional flipbook animation which is played using the frames in the [ColorMaterial::texture]. Ignored if there is no texture.
+    pub flipbook: Option<Flipbook>,
+    /// The time in seconds used for playing the [ColorMaterial::flipbook] animation, which is set by [Material::animate].
+    pub time: f32,
 }
 
 impl ColorMaterial {
@@ -34,24 +41,33 @@ impl ColorMaterial {
 
     /// Constructs a new opaque color material from a [CpuMaterial].
     pub fn new_opaque(context: &Context, cpu_material: &CpuMaterial) -> Self {
-        let texture = cpu_material
-            .albedo_texture
-            .as_ref()
-            .map(|cpu_texture| Arc::new(Texture2D::new(context, cpu_texture)).into());
+        let texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Arc::new(Texture2D::new_with_color_space(
+                context,
+                cpu_texture,
+                ColorSpace::Srgb,
+            ))
+            .into()
+        });
         Self {
             color: cpu_material.albedo,
             texture,
             is_transparent: false,
             render_states: RenderStates::default(),
+            ..Default::default()
         }
     }
 
     /// Constructs a new transparent color material from a [CpuMaterial].
     pub fn new_transparent(context: &Context, cpu_material: &CpuMaterial) -> Self {
-        let texture = cpu_material
-            .albedo_texture
-            .as_ref()
-            .map(|cpu_texture| Arc::new(Texture2D::new(context, cpu_texture)).into());
+        let texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Arc::new(Texture2D::new_with_color_space(
+                context,
+                cpu_texture,
+                ColorSpace::Srgb,
+            ))
+            .into()
+        });
         Self {
             color: cpu_material.albedo,
             texture,
@@ -61,6 +77,7 @@ impl ColorMaterial {
                 blend: Blend::TRANSPARENCY,
                 ..Default::default()
             },
+            ..Default::default()
         }
     }
 
@@ -71,6 +88,7 @@ impl ColorMaterial {
             texture: physical_material.albedo_texture.clone(),
             render_states: physical_material.render_states,
             is_transparent: physical_material.is_transparent,
+            ..Default::default()
         }
     }
 }
@@ -88,9 +106,17 @@ impl Material for ColorMaterial {
             ..FragmentAttributes::NONE
         };
         let mut shader = String::new();
-        if self.texture.is_some() {
+        if let Some(ref texture) = self.texture {
             attributes.uv = true;
             shader.push_str("#define USE_TEXTURE\nin vec2 uvs;\n");
+            if texture.color_space() == ColorSpace::Srgb {
+                shader.push_str("#define TEXTURE_IN_SRGB_FORMAT\n");
+            }
+            if let Some(flipbook) = self.flipbook {
+                attributes.start_time = true;
+                shader.push_str(&flipbook.fragment_shader_source());
+                shader.push_str(include_str!("shaders/flipbook.frag"));
+            }
         }
         shader.push_str(include_str!("../../core/shared.frag"));
         shader.push_str(include_str!("shaders/color_material.frag"));
@@ -105,6 +131,9 @@ impl Material for ColorMaterial {
         if let Some(ref tex) = self.texture {
             program.use_uniform("textureTransformation", tex.transformation);
             program.use_texture("tex", tex);
+            if let Some(flipbook) = self.flipbook {
+                flipbook.use_uniforms(program, self.time);
+            }
         }
     }
     fn render_states(&self) -> RenderStates {
@@ -117,4 +146,125 @@ impl Material for ColorMaterial {
             MaterialType::Opaque
         }
     }
+    fn animate(&mut self, time: f32) {
+        self.time = time;
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_vertex_colors_multiplied_by_color() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut cpu_mesh = CpuMesh::square();
+        cpu_mesh.colors = Some(vec![Color::new_opaque(255, 0, 255); 4]);
+        let mesh = Mesh::new(&context, &cpu_mesh);
+        let camera = Camera::new_orthographic(
+            Viewport::new_at_origo(4, 4),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            1.0,
+            0.1,
+            10.0,
+        );
+        let mut texture = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            4,
+            4,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let material = ColorMaterial {
+            color: Color::new_opaque(255, 255, 0),
+            ..Default::default()
+        };
+        let pixels = texture
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+            .render_with_material(&material, &camera, &mesh, &[])
+            .read::<[u8; 4]>();
+        // Only the red channel is in both the vertex colors and the tint
+        assert!(pixels.iter().all(|p| *p == [255, 0, 0, 255]));
+    }
+
+    #[test]
+    fn test_flipbook_with_instance_start_times() {
+        let context = crate::HeadlessContext::new().unwrap();
+        // Two frames next to each other, the first is red and the second is green
+        let texture = Texture2DRef::from(Arc::new(Texture2D::new(
+            &context,
+            &CpuTexture {
+                data: TextureData::RgbaU8(vec![[255, 0, 0, 255], [0, 255, 0, 255]]),
+                width: 2,
+                height: 1,
+                min_filter: Interpolation::Nearest,
+                mag_filter: Interpolation::Nearest,
+                mip_map_filter: None,
+                ..Default::default()
+            },
+        )));
+        // The left instance starts at time zero and the right instance one second later
+        let mut quads = Gm::new(
+            InstancedMesh::new(
+                &context,
+                &Instances {
+                    transformations: vec![
+                        Mat4::from_translation(vec3(-0.5, 0.0, 0.0)) * Mat4::from_scale(0.5),
+                        Mat4::from_translation(vec3(0.5, 0.0, 0.0)) * Mat4::from_scale(0.5),
+                    ],
+                    start_times: Some(vec![0.0, 1.0]),
+                    ..Default::default()
+                },
+                &CpuMesh::square(),
+            ),
+            ColorMaterial {
+                texture: Some(texture),
+                flipbook: Some(Flipbook::new(2, 1, 1.0)),
+                ..Default::default()
+            },
+        );
+        let camera = Camera::new_orthographic(
+            Viewport::new_at_origo(4, 2),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            1.0,
+            0.1,
+            10.0,
+        );
+        let mut target = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            4,
+            2,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        quads.animate(1.5);
+        let pixels = target
+            .as_color_target(None)
+            .clear(ClearState::color(0.0, 0.0, 0.0, 1.0))
+            .render(&camera, &quads, &[])
+            .read::<[u8; 4]>();
+        // The left instance shows the second frame and the right instance the first frame
+        for row in pixels.chunks(4) {
+            assert_eq!(
+                row,
+                [
+                    [0, 255, 0, 255],
+                    [0, 255, 0, 255],
+                    [255, 0, 0, 255],
+                    [255, 0, 0, 255]
+                ]
+            );
+        }
+    }
 }
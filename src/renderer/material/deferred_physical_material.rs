@@ -55,10 +55,14 @@ impl DeferredPhysicalMaterial {
     /// [DeferredPhysicalMaterial::metallic_roughness_texture] and [DeferredPhysicalMaterial::occlusion_texture] while any [CpuMaterial::metallic_roughness_texture] or [CpuMaterial::occlusion_texture] are ignored.
     ///
     pub fn new(context: &Context, cpu_material: &CpuMaterial) -> Self {
-        let albedo_texture = cpu_material
-            .albedo_texture
-            .as_ref()
-            .map(|cpu_texture| Arc::new(Texture2D::new(context, cpu_texture)).into());
+        let albedo_texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Arc::new(Texture2D::new_with_color_space(
+                context,
+                cpu_texture,
+                ColorSpace::Srgb,
+            ))
+            .into()
+        });
         let metallic_roughness_texture =
             if let Some(ref cpu_texture) = cpu_material.occlusion_metallic_roughness_texture {
                 Some(Arc::new(Texture2D::new(context, cpu_texture)).into())
@@ -80,10 +84,14 @@ impl DeferredPhysicalMaterial {
             .normal_texture
             .as_ref()
             .map(|cpu_texture| Arc::new(Texture2D::new(context, cpu_texture)).into());
-        let emissive_texture = cpu_material
-            .emissive_texture
-            .as_ref()
-            .map(|cpu_texture| Arc::new(Texture2D::new(context, cpu_texture)).into());
+        let emissive_texture = cpu_material.emissive_texture.as_ref().map(|cpu_texture| {
+            Arc::new(Texture2D::new_with_color_space(
+                context,
+                cpu_texture,
+                ColorSpace::Srgb,
+            ))
+            .into()
+        });
         Self {
             name: cpu_material.name.clone(),
             albedo: cpu_material.albedo,
@@ -125,12 +133,31 @@ impl DeferredPhysicalMaterial {
             emissive: physical_material.emissive,
             emissive_texture: physical_material.emissive_texture.clone(),
             alpha_cutout: if physical_material.is_transparent {
-                Some(0.5)
+                physical_material.alpha_cutout.or(Some(0.5))
             } else {
-                None
+                physical_material.alpha_cutout
             },
         }
     }
+
+    ///
+    /// Sets the same [TextureTransform] on all textures of this material, ie. the albedo, metallic roughness, occlusion, normal and emissive textures.
+    ///
+    pub fn set_texture_transform(&mut self, transform: TextureTransform) {
+        for texture in [
+            &mut self.albedo_texture,
+            &mut self.metallic_roughness_texture,
+            &mut self.occlusion_texture,
+            &mut self.normal_texture,
+            &mut self.emissive_texture,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            texture.set_transform(transform);
+        }
+    }
+
     ///
     /// The second stage of a deferred render call.
     /// Use the [Object::render] method to render the objects with this material into a [RenderTarget] and then call this method with these textures to render to the screen.
@@ -167,7 +194,7 @@ impl DeferredPhysicalMaterial {
                 }
                 program.use_uniform_if_required(
                     "viewProjectionInverse",
-                    (camera.projection() * camera.view()).invert().unwrap(),
+                    camera.inverse_view_projection(),
                 );
                 program.use_uniform("debug_type", DebugType::None as i32);
             },
@@ -199,8 +226,11 @@ impl Material for DeferredPhysicalMaterial {
         {
             attributes.uv = true;
             output.push_str("in vec2 uvs;\n");
-            if self.albedo_texture.is_some() {
+            if let Some(ref albedo_texture) = self.albedo_texture {
                 output.push_str("#define USE_ALBEDO_TEXTURE;\n");
+                if albedo_texture.color_space() == ColorSpace::Srgb {
+                    output.push_str("#define ALBEDO_TEXTURE_IN_SRGB_FORMAT;\n");
+                }
             }
             if self.metallic_roughness_texture.is_some() {
                 output.push_str("#define USE_METALLIC_ROUGHNESS_TEXTURE;\n");
@@ -212,8 +242,11 @@ impl Material for DeferredPhysicalMaterial {
                 attributes.tangents = true;
                 output.push_str("#define USE_NORMAL_TEXTURE;\nin vec3 tang;\nin vec3 bitang;\n");
             }
-            if self.emissive_texture.is_some() {
+            if let Some(ref emissive_texture) = self.emissive_texture {
                 output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
+                if emissive_texture.color_space() == ColorSpace::Srgb {
+                    output.push_str("#define EMISSIVE_TEXTURE_IN_SRGB_FORMAT;\n");
+                }
             }
             if self.alpha_cutout.is_some() {
                 output.push_str(
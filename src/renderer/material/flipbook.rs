@@ -0,0 +1,127 @@
+use crate::core::*;
+
+///
+/// Defines how a [Flipbook] animation is played.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum FlipbookPlayMode {
+    /// Starts over from the first frame after the last frame.
+    #[default]
+    Loop,
+    /// Plays the frames once and then keeps showing the last frame.
+    Once,
+    /// Plays the frames forwards and then backwards, over and over again.
+    PingPong,
+}
+
+///
+/// A flipbook animation, ie. a texture which contains a grid of frames that are shown one after the other, for example an explosion or an animated icon.
+/// The frames are ordered from left to right starting in the top row, which is the usual layout of a sprite sheet.
+///
+/// Set a flipbook on a [ColorMaterial] to play the animation on any geometry with uv coordinates.
+/// The current frame is computed on the GPU from the time given to [Material::animate] (or [Object::animate](crate::renderer::Object::animate) of a [Gm])
+/// minus [Flipbook::start_time] and minus the start time of each instance, if the geometry has per instance start times,
+/// see [Instances::start_times], [Particles::start_times] and [Sprites::set_start_times].
+/// This makes it possible to render many instances which are on different frames in a single draw call.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Flipbook {
+    /// The number of frames in each row of the texture.
+    pub columns: u32,
+    /// The number of rows of frames in the texture.
+    pub rows: u32,
+    /// The number of frames in the animation, which can be less than `columns * rows` if the last row is not full.
+    pub frame_count: u32,
+    /// The number of frames shown each second.
+    pub frames_per_second: f32,
+    /// Defines what happens when the last frame is reached.
+    pub play_mode: FlipbookPlayMode,
+    /// The time in seconds where the animation starts, which is added to the start time of each instance.
+    pub start_time: f32,
+    /// Whether to blend the current frame with the next frame, which gives a smooth animation when playing only a few frames each second.
+    pub blend_frames: bool,
+}
+
+impl Flipbook {
+    ///
+    /// Creates a new looping flipbook animation where all the `columns * rows` frames in the texture are played at the given number of frames per second.
+    ///
+    pub fn new(columns: u32, rows: u32, frames_per_second: f32) -> Self {
+        Self {
+            columns,
+            rows,
+            frame_count: columns * rows,
+            frames_per_second,
+            play_mode: FlipbookPlayMode::Loop,
+            start_time: 0.0,
+            blend_frames: false,
+        }
+    }
+
+    ///
+    /// Returns the position in the animation at the given time in seconds since the start of the animation, measured in frames,
+    /// ie. the integer part is the index of the current frame and the fractional part is how far the animation has progressed towards the next frame.
+    /// This is the same as what is computed on the GPU.
+    ///
+    pub fn frame_at(&self, time: f32) -> f32 {
+        let last = self.frame_count.max(1) as f32 - 1.0;
+        let frame = time.max(0.0) * self.frames_per_second;
+        match self.play_mode {
+            FlipbookPlayMode::Loop => frame % (last + 1.0),
+            FlipbookPlayMode::Once => frame.min(last),
+            FlipbookPlayMode::PingPong => {
+                if last > 0.0 {
+                    let frame = frame % (2.0 * last);
+                    if frame > last {
+                        2.0 * last - frame
+                    } else {
+                        frame
+                    }
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+
+    pub(in crate::renderer) fn fragment_shader_source(&self) -> String {
+        format!(
+            "#define USE_FLIPBOOK\n{}",
+            if self.blend_frames {
+                "#define FLIPBOOK_BLEND_FRAMES\n"
+            } else {
+                ""
+            }
+        )
+    }
+
+    pub(in crate::renderer) fn use_uniforms(&self, program: &Program, time: f32) {
+        program.use_uniform("flipbookGrid", vec2(self.columns as f32, self.rows as f32));
+        program.use_uniform("flipbookFrameCount", self.frame_count.max(1) as f32);
+        program.use_uniform("flipbookFramesPerSecond", self.frames_per_second);
+        program.use_uniform("flipbookPlayMode", self.play_mode as i32);
+        program.use_uniform("flipbookTime", time - self.start_time);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_frame_at() {
+        let mut flipbook = Flipbook::new(4, 2, 10.0);
+        assert_eq!(flipbook.frame_at(-1.0), 0.0);
+        assert!((flipbook.frame_at(0.25) - 2.5).abs() < 0.0001);
+        assert!((flipbook.frame_at(0.95) - 1.5).abs() < 0.0001);
+
+        flipbook.play_mode = FlipbookPlayMode::Once;
+        assert_eq!(flipbook.frame_at(0.95), 7.0);
+
+        // Forwards through the frames 0 to 7 and then backwards again
+        flipbook.play_mode = FlipbookPlayMode::PingPong;
+        assert!((flipbook.frame_at(0.65) - 6.5).abs() < 0.0001);
+        assert!((flipbook.frame_at(0.95) - 4.5).abs() < 0.0001);
+        assert!((flipbook.frame_at(1.45) - 0.5).abs() < 0.0001);
+    }
+}
@@ -40,6 +40,27 @@ pub struct PhysicalMaterial {
     pub emissive_texture: Option<Texture2DRef>,
     /// The lighting model used when rendering this material
     pub lighting_model: LightingModel,
+    /// The strength of the anisotropy in the range `[-1..1]`, used for example for brushed metal and hair.
+    /// At 0, which is the default, the highlights are round, at positive values they are stretched along the anisotropy direction (see [Self::anisotropy_rotation])
+    /// and at negative values they are stretched orthogonal to the anisotropy direction.
+    /// Anisotropy is only applied to the specular highlights using the [LightingModel::Cook] lighting model, where the anisotropic GGX distribution is used,
+    /// and to the reflections of an environment map, see [AmbientLight::new_with_environment].
+    /// Anisotropy requires the geometry to have tangents, like a [Self::normal_texture].
+    pub anisotropy: f32,
+    /// The rotation of the anisotropy direction counter clockwise around the normal, starting from the tangent direction.
+    pub anisotropy_rotation: Radians,
+    /// Texture with the anisotropy direction in tangent space mapped to the range `[0..1]` in the red and green channels and a strength, which is multiplied with the [Self::anisotropy] value, in the blue channel.
+    /// The direction is rotated by the [Self::anisotropy_rotation]. This is the same layout as the `KHR_materials_anisotropy` glTF extension.
+    pub anisotropy_texture: Option<Texture2DRef>,
+    /// A threshold on the alpha value of the color as a workaround for transparency, for example for foliage.
+    /// If the alpha value of a pixel touched by an object with this material is less than the threshold, then that object is not contributing to the color of that pixel.
+    /// On the other hand, if the alpha value is more than the threshold, then it is contributing fully to that pixel and thereby blocks out everything behind.
+    /// Contrary to transparent materials, objects with an alpha cutout write to the depth buffer and therefore do not need to be sorted,
+    /// so the material should be opaque, which is the case when constructed from a [CpuMaterial] with an [CpuMaterial::alpha_cutout].
+    pub alpha_cutout: Option<f32>,
+    /// Displaces the vertices of the geometry along their normals by the values in a height map, for example for terrain or water.
+    /// Only supported by [Mesh] and [InstancedMesh] geometries.
+    pub displacement: Option<Displacement>,
 }
 
 impl PhysicalMaterial {
@@ -49,6 +70,7 @@ impl PhysicalMaterial {
     /// [PhysicalMaterial::metallic_roughness_texture] and [PhysicalMaterial::occlusion_texture] while any [CpuMaterial::metallic_roughness_texture] or [CpuMaterial::occlusion_texture] are ignored.
     /// Tries to infer whether this material is transparent or opaque from the alpha value of the albedo color and the alpha values in the albedo texture.
     /// Since this is not always correct, it is preferred to use [PhysicalMaterial::new_opaque] or [PhysicalMaterial::new_transparent].
+    /// The material is always opaque if the input has an [CpuMaterial::alpha_cutout].
     ///
     pub fn new(context: &Context, cpu_material: &CpuMaterial) -> Self {
         Self::new_internal(context, cpu_material, super::is_transparent(cpu_material))
@@ -64,15 +86,21 @@ impl PhysicalMaterial {
     /// Constructs a new transparent physical material from a [CpuMaterial].
     /// If the input contains an [CpuMaterial::occlusion_metallic_roughness_texture], this texture is used for both
     /// [PhysicalMaterial::metallic_roughness_texture] and [PhysicalMaterial::occlusion_texture] while any [CpuMaterial::metallic_roughness_texture] or [CpuMaterial::occlusion_texture] are ignored.
+    /// The material is opaque anyway if the input has an [CpuMaterial::alpha_cutout], since the cutout replaces blending.
     pub fn new_transparent(context: &Context, cpu_material: &CpuMaterial) -> Self {
         Self::new_internal(context, cpu_material, true)
     }
 
     fn new_internal(context: &Context, cpu_material: &CpuMaterial, is_transparent: bool) -> Self {
-        let albedo_texture = cpu_material
-            .albedo_texture
-            .as_ref()
-            .map(|cpu_texture| Arc::new(Texture2D::new(context, cpu_texture)).into());
+        let is_transparent = is_transparent && cpu_material.alpha_cutout.is_none();
+        let albedo_texture = cpu_material.albedo_texture.as_ref().map(|cpu_texture| {
+            Arc::new(Texture2D::new_with_color_space(
+                context,
+                cpu_texture,
+                ColorSpace::Srgb,
+            ))
+            .into()
+        });
         let metallic_roughness_texture =
             if let Some(ref cpu_texture) = cpu_material.occlusion_metallic_roughness_texture {
                 Some(Arc::new(Texture2D::new(context, cpu_texture)).into())
@@ -94,10 +122,14 @@ impl PhysicalMaterial {
             .normal_texture
             .as_ref()
             .map(|cpu_texture| Arc::new(Texture2D::new(context, cpu_texture)).into());
-        let emissive_texture = cpu_material
-            .emissive_texture
-            .as_ref()
-            .map(|cpu_texture| Arc::new(Texture2D::new(context, cpu_texture)).into());
+        let emissive_texture = cpu_material.emissive_texture.as_ref().map(|cpu_texture| {
+            Arc::new(Texture2D::new_with_color_space(
+                context,
+                cpu_texture,
+                ColorSpace::Srgb,
+            ))
+            .into()
+        });
         Self {
             name: cpu_material.name.clone(),
             albedo: cpu_material.albedo,
@@ -122,6 +154,31 @@ impl PhysicalMaterial {
             emissive: cpu_material.emissive,
             emissive_texture,
             lighting_model: cpu_material.lighting_model,
+            alpha_cutout: cpu_material.alpha_cutout,
+            anisotropy: 0.0,
+            anisotropy_rotation: radians(0.0),
+            anisotropy_texture: None,
+            displacement: None,
+        }
+    }
+
+    ///
+    /// Sets the same [TextureTransform] on all textures of this material, ie. the albedo, metallic roughness, occlusion, normal, emissive and anisotropy textures,
+    /// for example to repeat all textures of a ground material many times. The [PhysicalMaterial::displacement] is not affected.
+    ///
+    pub fn set_texture_transform(&mut self, transform: TextureTransform) {
+        for texture in [
+            &mut self.albedo_texture,
+            &mut self.metallic_roughness_texture,
+            &mut self.occlusion_texture,
+            &mut self.normal_texture,
+            &mut self.emissive_texture,
+            &mut self.anisotropy_texture,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            texture.set_transform(transform);
         }
     }
 }
@@ -130,6 +187,80 @@ impl FromCpuMaterial for PhysicalMaterial {
     fn from_cpu_material(context: &Context, cpu_material: &CpuMaterial) -> Self {
         Self::new(context, cpu_material)
     }
+
+    fn reload(&mut self, context: &Context, cpu_material: &CpuMaterial) {
+        let is_transparent =
+            super::is_transparent(cpu_material) && cpu_material.alpha_cutout.is_none();
+        super::reload_texture(
+            context,
+            &mut self.albedo_texture,
+            cpu_material.albedo_texture.as_ref(),
+            ColorSpace::Srgb,
+        );
+        if let Some(ref cpu_texture) = cpu_material.occlusion_metallic_roughness_texture {
+            super::reload_texture(
+                context,
+                &mut self.metallic_roughness_texture,
+                Some(cpu_texture),
+                ColorSpace::Linear,
+            );
+            self.occlusion_texture = self.metallic_roughness_texture.clone();
+        } else {
+            // The occlusion texture must not be updated in place if it was the combined occlusion metallic roughness texture
+            if let (Some(metallic_roughness), Some(occlusion)) =
+                (&self.metallic_roughness_texture, &self.occlusion_texture)
+            {
+                if Arc::ptr_eq(&metallic_roughness.texture, &occlusion.texture) {
+                    self.occlusion_texture = None;
+                }
+            }
+            super::reload_texture(
+                context,
+                &mut self.metallic_roughness_texture,
+                cpu_material.metallic_roughness_texture.as_ref(),
+                ColorSpace::Linear,
+            );
+            super::reload_texture(
+                context,
+                &mut self.occlusion_texture,
+                cpu_material.occlusion_texture.as_ref(),
+                ColorSpace::Linear,
+            );
+        }
+        super::reload_texture(
+            context,
+            &mut self.normal_texture,
+            cpu_material.normal_texture.as_ref(),
+            ColorSpace::Linear,
+        );
+        super::reload_texture(
+            context,
+            &mut self.emissive_texture,
+            cpu_material.emissive_texture.as_ref(),
+            ColorSpace::Srgb,
+        );
+        self.name = cpu_material.name.clone();
+        self.albedo = cpu_material.albedo;
+        self.metallic = cpu_material.metallic;
+        self.roughness = cpu_material.roughness;
+        self.normal_scale = cpu_material.normal_scale;
+        self.occlusion_strength = cpu_material.occlusion_strength;
+        self.emissive = cpu_material.emissive;
+        self.lighting_model = cpu_material.lighting_model;
+        self.alpha_cutout = cpu_material.alpha_cutout;
+        if is_transparent != self.is_transparent {
+            self.is_transparent = is_transparent;
+            self.render_states = if is_transparent {
+                RenderStates {
+                    write_mask: WriteMask::COLOR,
+                    blend: Blend::TRANSPARENCY,
+                    ..Default::default()
+                }
+            } else {
+                RenderStates::default()
+            };
+        }
+    }
 }
 
 impl Material for PhysicalMaterial {
@@ -140,17 +271,37 @@ impl Material for PhysicalMaterial {
             color: true,
             ..FragmentAttributes::NONE
         };
-        let mut output = lights_shader_source(lights, self.lighting_model);
+        // The anisotropy must be defined before the lighting functions which use it
+        let uses_anisotropy = self.anisotropy != 0.0 || self.anisotropy_texture.is_some();
+        let mut output = if uses_anisotropy {
+            "#define USE_ANISOTROPY\n".to_string()
+        } else {
+            String::new()
+        };
+        output.push_str(&lights_shader_source(lights, self.lighting_model));
+        if uses_anisotropy || self.normal_texture.is_some() {
+            attributes.tangents = true;
+            output.push_str("in vec3 tang;\nin vec3 bitang;\n");
+        }
         if self.albedo_texture.is_some()
             || self.metallic_roughness_texture.is_some()
             || self.normal_texture.is_some()
             || self.occlusion_texture.is_some()
             || self.emissive_texture.is_some()
+            || self.anisotropy_texture.is_some()
+            || self.displacement.is_some()
         {
             attributes.uv = true;
             output.push_str("in vec2 uvs;\n");
-            if self.albedo_texture.is_some() {
+            if self.displacement.is_some() {
+                output.push_str("#define USE_DISPLACEMENT\n");
+                output.push_str(Displacement::fragment_shader_source());
+            }
+            if let Some(ref albedo_texture) = self.albedo_texture {
                 output.push_str("#define USE_ALBEDO_TEXTURE;\n");
+                if albedo_texture.color_space() == ColorSpace::Srgb {
+                    output.push_str("#define ALBEDO_TEXTURE_IN_SRGB_FORMAT;\n");
+                }
             }
             if self.metallic_roughness_texture.is_some() {
                 output.push_str("#define USE_METALLIC_ROUGHNESS_TEXTURE;\n");
@@ -159,13 +310,24 @@ impl Material for PhysicalMaterial {
                 output.push_str("#define USE_OCCLUSION_TEXTURE;\n");
             }
             if self.normal_texture.is_some() {
-                attributes.tangents = true;
-                output.push_str("#define USE_NORMAL_TEXTURE;\nin vec3 tang;\nin vec3 bitang;\n");
+                output.push_str("#define USE_NORMAL_TEXTURE;\n");
+            }
+            if self.anisotropy_texture.is_some() {
+                output.push_str("#define USE_ANISOTROPY_TEXTURE;\n");
             }
-            if self.emissive_texture.is_some() {
+            if let Some(ref emissive_texture) = self.emissive_texture {
                 output.push_str("#define USE_EMISSIVE_TEXTURE;\n");
+                if emissive_texture.color_space() == ColorSpace::Srgb {
+                    output.push_str("#define EMISSIVE_TEXTURE_IN_SRGB_FORMAT;\n");
+                }
             }
         }
+        if let Some(alpha_cutout) = self.alpha_cutout {
+            output.push_str(&format!(
+                "#define ALPHACUT;\nfloat acut = {:?};\n",
+                alpha_cutout
+            ));
+        }
         output.push_str(include_str!("shaders/physical_material.frag"));
         FragmentShader {
             source: output,
@@ -207,6 +369,25 @@ impl Material for PhysicalMaterial {
                     program.use_texture("normalTexture", texture);
                 }
             }
+            if program.requires_uniform("anisotropy") {
+                program.use_uniform("anisotropy", self.anisotropy);
+                program.use_uniform(
+                    "anisotropyDirection",
+                    vec2(
+                        self.anisotropy_rotation.0.cos(),
+                        self.anisotropy_rotation.0.sin(),
+                    ),
+                );
+            }
+            if program.requires_uniform("anisotropyTexture") {
+                if let Some(ref texture) = self.anisotropy_texture {
+                    program.use_uniform("anisotropyTexTransform", texture.transformation);
+                    program.use_texture("anisotropyTexture", texture);
+                }
+            }
+        }
+        if let Some(ref displacement) = self.displacement {
+            displacement.use_uniforms(program);
         }
         program.use_uniform("albedo", self.albedo);
         program.use_uniform("emissive", self.emissive);
@@ -228,6 +409,9 @@ impl Material for PhysicalMaterial {
             MaterialType::Opaque
         }
     }
+    fn displacement(&self) -> Option<Displacement> {
+        self.displacement.clone()
+    }
 }
 
 impl Default for PhysicalMaterial {
@@ -248,6 +432,11 @@ impl Default for PhysicalMaterial {
             emissive: Color::BLACK,
             emissive_texture: None,
             lighting_model: LightingModel::Blinn,
+            alpha_cutout: None,
+            anisotropy: 0.0,
+            anisotropy_rotation: radians(0.0),
+            anisotropy_texture: None,
+            displacement: None,
         }
     }
 }
@@ -14,9 +14,17 @@ void main()
     outColor = surfaceColor * col;
     
     #ifdef USE_TEXTURE
+    #ifdef USE_FLIPBOOK
+    vec4 tex_color = sample_flipbook(tex, textureTransformation, uvs);
+    #else
     vec4 tex_color = texture(tex, (textureTransformation * vec3(uvs, 1.0)).xy);
+    #endif
+    #ifdef TEXTURE_IN_SRGB_FORMAT
+    outColor *= tex_color;
+    #else
     outColor *= vec4(rgb_from_srgb(tex_color.rgb), tex_color.a);
     #endif
+    #endif
 
     outColor.rgb = srgb_from_rgb(outColor.rgb);
 }
\ No newline at end of file
@@ -47,7 +47,11 @@ void main()
     #ifdef ALPHACUT
         if (c.a < acut) discard;
     #endif
-    surface_color *= vec4(rgb_from_srgb(c.rgb), c.a);
+    #ifdef ALBEDO_TEXTURE_IN_SRGB_FORMAT
+        surface_color *= c;
+    #else
+        surface_color *= vec4(rgb_from_srgb(c.rgb), c.a);
+    #endif
 #endif
 
     float metallic_factor = metallic;
@@ -74,7 +78,11 @@ void main()
     vec3 total_emissive = emissive.rgb;
 #ifdef USE_EMISSIVE_TEXTURE
     vec4 e = texture(emissiveTexture, (emissiveTexTransform * vec3(uvs, 1.0)).xy);
-    total_emissive *= rgb_from_srgb(e.rgb);
+    #ifdef EMISSIVE_TEXTURE_IN_SRGB_FORMAT
+        total_emissive *= e.rgb;
+    #else
+        total_emissive *= rgb_from_srgb(e.rgb);
+    #endif
 #endif
 
     outColor = vec4(surface_color.rgb, metallic_factor);
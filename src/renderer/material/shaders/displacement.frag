@@ -0,0 +1,12 @@
+
+uniform sampler2D displacementTexture;
+uniform mat3 displacementTexTransform;
+uniform float displacementScale;
+uniform vec2 displacementUvScale;
+uniform vec2 displacementUvOffset;
+
+// The distance along the normal that the surface is displaced at the given uv coordinates
+float displacement_height(vec2 uv) {
+    vec2 displacementUv = (displacementTexTransform * vec3(uv * displacementUvScale + displacementUvOffset, 1.0)).xy;
+    return displacementScale * textureLod(displacementTexture, displacementUv, 0.0).r;
+}
@@ -0,0 +1,44 @@
+uniform vec2 flipbookGrid;
+uniform float flipbookFrameCount;
+uniform float flipbookFramesPerSecond;
+uniform int flipbookPlayMode;
+uniform float flipbookTime;
+
+in float start_time;
+
+// The position in the animation measured in frames, which is the same as Flipbook::frame_at
+float flipbook_frame()
+{
+    float last = flipbookFrameCount - 1.0;
+    float frame = max(flipbookTime - start_time, 0.0) * flipbookFramesPerSecond;
+    if (flipbookPlayMode == 0) {
+        return mod(frame, last + 1.0);
+    } else if (flipbookPlayMode == 1) {
+        return min(frame, last);
+    } else if (last > 0.0) {
+        frame = mod(frame, 2.0 * last);
+        return frame > last ? 2.0 * last - frame : frame;
+    }
+    return 0.0;
+}
+
+// Maps the uv coordinates of the geometry to the given frame, where the frames are ordered from left to right starting in the top row
+vec2 flipbook_uvs(vec2 uvs, float frame)
+{
+    float row = floor((frame + 0.5) / flipbookGrid.x);
+    float column = frame - row * flipbookGrid.x;
+    return (vec2(column, flipbookGrid.y - 1.0 - row) + clamp(uvs, 0.0, 1.0)) / flipbookGrid;
+}
+
+vec4 sample_flipbook(sampler2D tex, mat3 textureTransformation, vec2 uvs)
+{
+    float frame = flipbook_frame();
+    float current = floor(frame);
+    vec4 color = texture(tex, (textureTransformation * vec3(flipbook_uvs(uvs, current), 1.0)).xy);
+#ifdef FLIPBOOK_BLEND_FRAMES
+    float next = flipbookPlayMode == 0 ? mod(current + 1.0, flipbookFrameCount) : min(current + 1.0, flipbookFrameCount - 1.0);
+    vec4 next_color = texture(tex, (textureTransformation * vec3(flipbook_uvs(uvs, next), 1.0)).xy);
+    color = mix(color, next_color, frame - current);
+#endif
+    return color;
+}
@@ -32,10 +32,37 @@ uniform mat3 normalTexTransform;
 uniform float normalScale;
 #endif
 
+#ifdef USE_ANISOTROPY
+uniform float anisotropy;
+uniform vec2 anisotropyDirection;
+#endif
+
+#ifdef USE_ANISOTROPY_TEXTURE
+uniform sampler2D anisotropyTexture;
+uniform mat3 anisotropyTexTransform;
+#endif
+
 in vec3 pos;
 in vec3 nor;
 in vec4 col;
 
+#ifdef USE_DISPLACEMENT
+// Perturbs the normal by the screen space gradient of the displacement height, since the vertex normals are not displaced
+vec3 displaced_normal(vec3 n, vec3 p, vec2 uv)
+{
+    vec3 dpdx = dFdx(p);
+    vec3 dpdy = dFdy(p);
+    float h = displacement_height(uv);
+    float dhdx = dFdx(h);
+    float dhdy = dFdy(h);
+    vec3 r1 = cross(dpdy, n);
+    vec3 r2 = cross(n, dpdx);
+    float det = dot(dpdx, r1);
+    vec3 grad = sign(det) * (dhdx * r1 + dhdy * r2);
+    return normalize(abs(det) * n - grad);
+}
+#endif
+
 layout (location = 0) out vec4 outColor;
 
 void main()
@@ -46,7 +73,14 @@ void main()
     #ifdef ALPHACUT
         if (c.a < acut) discard;
     #endif
-    surface_color *= vec4(rgb_from_srgb(c.rgb), c.a);
+    #ifdef ALBEDO_TEXTURE_IN_SRGB_FORMAT
+        surface_color *= c;
+    #else
+        surface_color *= vec4(rgb_from_srgb(c.rgb), c.a);
+    #endif
+#endif
+#ifdef ALPHACUT
+    if (surface_color.a < acut) discard;
 #endif
 
     float metallic_factor = metallic;
@@ -63,6 +97,9 @@ void main()
 #endif
 
     vec3 normal = normalize(gl_FrontFacing ? nor : -nor);
+#ifdef USE_DISPLACEMENT
+    normal = displaced_normal(normal, pos, uvs);
+#endif
 #ifdef USE_NORMAL_TEXTURE
     vec3 tangent = normalize(gl_FrontFacing ? tang : -tang);
     vec3 bitangent = normalize(gl_FrontFacing ? bitang : -bitang);
@@ -70,10 +107,31 @@ void main()
     normal = tbn * ((2.0 * texture(normalTexture, (normalTexTransform * vec3(uvs, 1.0)).xy).xyz - 1.0) * vec3(normalScale, normalScale, 1.0));
 #endif
 
+#ifdef USE_ANISOTROPY
+    vec2 anisotropy_direction = anisotropyDirection;
+    float strength = anisotropy;
+#ifdef USE_ANISOTROPY_TEXTURE
+    vec3 a = texture(anisotropyTexture, (anisotropyTexTransform * vec3(uvs, 1.0)).xy).rgb;
+    anisotropy_direction = mat2(anisotropyDirection.x, anisotropyDirection.y, -anisotropyDirection.y, anisotropyDirection.x) * normalize(2.0 * a.rg - 1.0);
+    strength *= a.b;
+#endif
+    // The direction is given in the tangent frame and is made orthogonal to the possibly normal mapped normal
+    vec3 t = normalize(gl_FrontFacing ? tang : -tang);
+    vec3 b = normalize(gl_FrontFacing ? bitang : -bitang);
+    vec3 direction = anisotropy_direction.x * t + anisotropy_direction.y * b;
+    anisotropy_tangent = normalize(direction - dot(direction, normal) * normal);
+    anisotropy_bitangent = cross(normal, anisotropy_tangent);
+    anisotropy_strength = clamp(strength, -1.0, 1.0);
+#endif
+
     vec3 total_emissive = emissive.rgb;
 #ifdef USE_EMISSIVE_TEXTURE
     vec4 e = texture(emissiveTexture, (emissiveTexTransform * vec3(uvs, 1.0)).xy);
-    total_emissive *= rgb_from_srgb(e.rgb);
+    #ifdef EMISSIVE_TEXTURE_IN_SRGB_FORMAT
+        total_emissive *= e.rgb;
+    #else
+        total_emissive *= rgb_from_srgb(e.rgb);
+    #endif
 #endif
 
     outColor.rgb = total_emissive + calculate_lighting(cameraPosition, surface_color.rgb, pos, normal, metallic_factor, roughness_factor, occlusion);
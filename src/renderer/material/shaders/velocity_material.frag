@@ -0,0 +1,16 @@
+uniform mat4 viewProjection;
+uniform mat4 previousViewProjection;
+uniform mat4 motion;
+
+in vec3 pos;
+
+layout (location = 0) out vec4 outColor;
+
+void main()
+{
+    // The difference between the current and the previous position in texture coordinates, which is half the difference in normalized device coordinates
+    vec4 current = viewProjection * vec4(pos, 1.0);
+    vec4 previous = previousViewProjection * motion * vec4(pos, 1.0);
+    vec2 velocity = 0.5 * (current.xy / current.w - previous.xy / previous.w);
+    outColor = vec4(velocity, 0.0, 1.0);
+}
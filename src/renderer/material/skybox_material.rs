@@ -2,8 +2,11 @@ use crate::core::*;
 use crate::renderer::*;
 use std::sync::Arc;
 
+#[derive(Clone)]
 pub struct SkyboxMaterial {
     pub texture: Arc<TextureCubeMap>,
+    pub rotation: Radians,
+    pub intensity: f32,
 }
 
 impl Material for SkyboxMaterial {
@@ -21,6 +24,8 @@ impl Material for SkyboxMaterial {
     fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
         program.use_uniform("isHDR", i32::from(self.texture.is_hdr()));
         program.use_texture_cube("texture0", &self.texture);
+        program.use_uniform("rotation", Mat3::from_angle_y(-self.rotation));
+        program.use_uniform("intensity", self.intensity);
     }
 
     fn render_states(&self) -> RenderStates {
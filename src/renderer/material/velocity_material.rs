@@ -0,0 +1,142 @@
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// Renders the screen space velocity of each pixel in texture coordinates per frame to the red and green channels, which is the input to the [MotionBlurEffect].
+/// The velocity is the difference between the current position of the surface on the screen and the position in the previous frame,
+/// which is calculated using the view-projection matrix of the camera in the previous frame (see [CameraHistory])
+/// and, for moving objects, the transformation of the object in the previous frame (see [VelocityMaterial::set_object_transformations]).
+/// Render to a texture with a signed float format, for example [FrameTextureFormat::Rg16F], cleared to zero, or use [VelocityMaterial::frame_pass].
+///
+#[derive(Clone)]
+pub struct VelocityMaterial {
+    /// The view-projection matrix of the camera in the previous frame, see [CameraHistory::previous_view_projection].
+    pub previous_view_projection: Mat4,
+    /// The transformation from the current world space position of the objects rendered with this material to their position in the previous frame.
+    /// This is the identity for objects that do not move, see [VelocityMaterial::set_object_transformations].
+    pub motion: Mat4,
+    /// Render states.
+    pub render_states: RenderStates,
+}
+
+impl VelocityMaterial {
+    ///
+    /// Creates a new velocity material for objects that do not move, so the velocity is only caused by the movement of the camera given by the camera history.
+    ///
+    pub fn new(camera_history: &CameraHistory) -> Self {
+        Self {
+            previous_view_projection: camera_history.previous_view_projection(),
+            motion: Mat4::identity(),
+            render_states: RenderStates::default(),
+        }
+    }
+
+    ///
+    /// Sets the [VelocityMaterial::motion] from the transformation of the objects rendered with this material in the previous frame and in the current frame.
+    ///
+    pub fn set_object_transformations(&mut self, previous: Mat4, current: Mat4) {
+        self.motion = previous * current.invert().unwrap_or(Mat4::identity());
+    }
+
+    ///
+    /// Returns a [FramePass] which renders the velocities of the given geometries with this material to a color texture with the given name
+    /// and the [FrameTextureFormat::Rg16F] format, using a depth texture with the given name for depth testing.
+    /// The textures have the size of the viewport and the velocity is zero where there is no geometry.
+    ///
+    pub fn frame_pass<'a, G: Geometry + 'a>(
+        &'a self,
+        camera: &'a Camera,
+        geometries: impl IntoIterator<Item = G> + 'a,
+        velocity: &str,
+        depth: &str,
+    ) -> FramePass<'a> {
+        let velocity_name = velocity.to_string();
+        let depth_name = depth.to_string();
+        FramePass::new("velocity", move |resources| {
+            resources
+                .render_target(&velocity_name, &depth_name)
+                .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+                .render_with_material(self, camera, geometries, &[]);
+        })
+        .write(
+            velocity,
+            FrameTextureFormat::Rg16F,
+            FrameTextureSize::viewport(),
+        )
+        .write(
+            depth,
+            FrameTextureFormat::Depth32F,
+            FrameTextureSize::viewport(),
+        )
+    }
+}
+
+impl Material for VelocityMaterial {
+    fn fragment_shader(&self, _lights: &[&dyn Light]) -> FragmentShader {
+        FragmentShader {
+            source: include_str!("shaders/velocity_material.frag").to_string(),
+            attributes: FragmentAttributes {
+                position: true,
+                ..FragmentAttributes::NONE
+            },
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform("viewProjection", camera.projection() * camera.view());
+        program.use_uniform("previousViewProjection", self.previous_view_projection);
+        program.use_uniform("motion", self.motion);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.render_states
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_velocity() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let viewport = Viewport::new_at_origo(4, 1);
+        let camera = camera2d(viewport);
+        let rectangle = Rectangle::new(&context, vec2(2.0, 0.5), degrees(0.0), 4.0, 1.0);
+        let render = |material: &VelocityMaterial| {
+            let mut target = Texture2D::new_empty::<[f32; 4]>(
+                &context,
+                4,
+                1,
+                Interpolation::Nearest,
+                Interpolation::Nearest,
+                None,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            );
+            target
+                .as_color_target(None)
+                .clear(ClearState::color(0.0, 0.0, 0.0, 0.0))
+                .render_with_material(material, &camera, &rectangle, &[])
+                .read::<[f32; 4]>()
+        };
+
+        let mut material = VelocityMaterial::new(&CameraHistory::new(&camera));
+        for pixel in render(&material) {
+            assert!(pixel[0].abs() < 0.0001 && pixel[1].abs() < 0.0001);
+        }
+
+        // The object was one pixel further to the left in the previous frame, so it moved a quarter of the width of the viewport to the right
+        material.set_object_transformations(
+            Mat4::from_translation(vec3(-1.0, 0.0, 0.0)),
+            Mat4::identity(),
+        );
+        for pixel in render(&material) {
+            assert!((pixel[0] - 0.25).abs() < 0.0001 && pixel[1].abs() < 0.0001);
+        }
+    }
+}
@@ -90,11 +90,8 @@ impl PostMaterial for WaterMaterial {
         for (i, light) in lights.iter().enumerate() {
             light.use_uniforms(program, i as u32);
         }
-        program.use_uniform("viewProjection", camera.projection() * camera.view());
-        program.use_uniform(
-            "viewProjectionInverse",
-            (camera.projection() * camera.view()).invert().unwrap(),
-        );
+        program.use_uniform("viewProjection", camera.view_projection());
+        program.use_uniform("viewProjectionInverse", camera.inverse_view_projection());
         program.use_uniform("cameraPosition", camera.position());
         program.use_uniform(
             "screenSize",
@@ -9,6 +9,10 @@ mod gm;
 #[doc(inline)]
 pub use gm::*;
 
+mod gm_with_options;
+#[doc(inline)]
+pub use gm_with_options::*;
+
 mod model;
 #[doc(inline)]
 pub use model::*;
@@ -17,6 +21,14 @@ mod instanced_model;
 #[doc(inline)]
 pub use instanced_model::*;
 
+mod instanced_skinned_model;
+#[doc(inline)]
+pub use instanced_skinned_model::*;
+
+mod optimized_model;
+#[doc(inline)]
+pub use optimized_model::*;
+
 mod voxel_grid;
 #[doc(inline)]
 pub use voxel_grid::*;
@@ -59,6 +71,31 @@ pub trait Object: Geometry {
     /// Returns the type of material applied to this object.
     ///
     fn material_type(&self) -> MaterialType;
+
+    ///
+    /// Returns the render order of this object. Objects with a higher render order are rendered after objects with a lower render order,
+    /// regardless of material type and distance to the camera (see [cmp_render_order]). The default render order is 0.
+    ///
+    fn render_order(&self) -> i32 {
+        0
+    }
+
+    ///
+    /// Returns the id of this object which is written to the object id texture when rendering into a render target with object ids (see [RenderTarget::new_with_object_ids] and [ObjectIds]).
+    /// If `None`, which is the default, the object is given the id `index + 1` where `index` is the index of the object in the list of objects given to the render call.
+    /// The id `0` means that no object is rendered in a pixel.
+    ///
+    fn object_id(&self) -> Option<u32> {
+        None
+    }
+
+    ///
+    /// Returns an id which identifies the instance of the material applied to this object (see [Material::material_id] and [SortInfo::material_id]).
+    /// The default implementation returns the memory address of the object.
+    ///
+    fn material_id(&self) -> usize {
+        self as *const Self as *const () as usize
+    }
 }
 
 impl<T: Object + ?Sized> Object for &T {
@@ -69,6 +106,18 @@ impl<T: Object + ?Sized> Object for &T {
     fn material_type(&self) -> MaterialType {
         (*self).material_type()
     }
+
+    fn render_order(&self) -> i32 {
+        (*self).render_order()
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        (*self).object_id()
+    }
+
+    fn material_id(&self) -> usize {
+        (*self).material_id()
+    }
 }
 
 impl<T: Object + ?Sized> Object for &mut T {
@@ -79,6 +128,18 @@ impl<T: Object + ?Sized> Object for &mut T {
     fn material_type(&self) -> MaterialType {
         (**self).material_type()
     }
+
+    fn render_order(&self) -> i32 {
+        (**self).render_order()
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        (**self).object_id()
+    }
+
+    fn material_id(&self) -> usize {
+        (**self).material_id()
+    }
 }
 
 impl<T: Object> Object for Box<T> {
@@ -89,6 +150,18 @@ impl<T: Object> Object for Box<T> {
     fn material_type(&self) -> MaterialType {
         self.as_ref().material_type()
     }
+
+    fn render_order(&self) -> i32 {
+        self.as_ref().render_order()
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        self.as_ref().object_id()
+    }
+
+    fn material_id(&self) -> usize {
+        self.as_ref().material_id()
+    }
 }
 
 impl<T: Object> Object for std::rc::Rc<T> {
@@ -99,6 +172,18 @@ impl<T: Object> Object for std::rc::Rc<T> {
     fn material_type(&self) -> MaterialType {
         self.as_ref().material_type()
     }
+
+    fn render_order(&self) -> i32 {
+        self.as_ref().render_order()
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        self.as_ref().object_id()
+    }
+
+    fn material_id(&self) -> usize {
+        self.as_ref().material_id()
+    }
 }
 
 impl<T: Object> Object for std::sync::Arc<T> {
@@ -109,6 +194,18 @@ impl<T: Object> Object for std::sync::Arc<T> {
     fn material_type(&self) -> MaterialType {
         self.as_ref().material_type()
     }
+
+    fn render_order(&self) -> i32 {
+        self.as_ref().render_order()
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        self.as_ref().object_id()
+    }
+
+    fn material_id(&self) -> usize {
+        self.as_ref().material_id()
+    }
 }
 
 impl<T: Object> Object for std::cell::RefCell<T> {
@@ -119,6 +216,18 @@ impl<T: Object> Object for std::cell::RefCell<T> {
     fn material_type(&self) -> MaterialType {
         self.borrow().material_type()
     }
+
+    fn render_order(&self) -> i32 {
+        self.borrow().render_order()
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        self.borrow().object_id()
+    }
+
+    fn material_id(&self) -> usize {
+        self.borrow().material_id()
+    }
 }
 
 impl<T: Object> Object for std::sync::RwLock<T> {
@@ -129,4 +238,16 @@ impl<T: Object> Object for std::sync::RwLock<T> {
     fn material_type(&self) -> MaterialType {
         self.read().unwrap().material_type()
     }
+
+    fn render_order(&self) -> i32 {
+        self.read().unwrap().render_order()
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        self.read().unwrap().object_id()
+    }
+
+    fn material_id(&self) -> usize {
+        self.read().unwrap().material_id()
+    }
 }
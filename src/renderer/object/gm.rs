@@ -32,11 +32,23 @@ impl<'a, G: Geometry, M: Material> IntoIterator for &'a Gm<G, M> {
 
 impl<G: Geometry, M: Material> Geometry for Gm<G, M> {
     fn aabb(&self) -> AxisAlignedBoundingBox {
-        self.geometry.aabb()
+        let aabb = self.geometry.aabb();
+        match self.material.max_displacement() {
+            // The vertices can be displaced in any direction along their normals
+            Some(displacement) if !aabb.is_empty() => {
+                let offset = vec3(1.0, 1.0, 1.0) * displacement.abs();
+                AxisAlignedBoundingBox::new_with_positions(&[
+                    aabb.min() - offset,
+                    aabb.max() + offset,
+                ])
+            }
+            _ => aabb,
+        }
     }
 
     fn animate(&mut self, time: f32) {
-        self.geometry.animate(time)
+        self.geometry.animate(time);
+        self.material.animate(time);
     }
 
     fn render_with_material(
@@ -45,7 +57,20 @@ impl<G: Geometry, M: Material> Geometry for Gm<G, M> {
         camera: &Camera,
         lights: &[&dyn Light],
     ) {
-        self.geometry.render_with_material(material, camera, lights)
+        // Another material, for example when rendering shadow maps, must displace the vertices in the same way as the material of this object
+        match self.material.displacement() {
+            Some(displacement) if material.displacement().is_none() => {
+                self.geometry.render_with_material(
+                    &DisplacementOverride {
+                        material,
+                        displacement,
+                    },
+                    camera,
+                    lights,
+                )
+            }
+            _ => self.geometry.render_with_material(material, camera, lights),
+        }
     }
 
     fn render_with_post_material(
@@ -74,6 +99,10 @@ impl<G: Geometry, M: Material> Object for Gm<G, M> {
     fn material_type(&self) -> MaterialType {
         self.material.material_type()
     }
+
+    fn material_id(&self) -> usize {
+        self.material.material_id()
+    }
 }
 
 impl<G: Geometry + Clone, M: Material + Clone> Clone for Gm<G, M> {
@@ -97,3 +126,38 @@ impl<G: Geometry, M: Material> std::ops::DerefMut for Gm<G, M> {
         &mut self.geometry
     }
 }
+
+///
+/// A material which is identical to the wrapped material except that it displaces the vertices.
+///
+struct DisplacementOverride<'a> {
+    material: &'a dyn Material,
+    displacement: Displacement,
+}
+
+impl Material for DisplacementOverride<'_> {
+    fn fragment_shader(&self, lights: &[&dyn Light]) -> FragmentShader {
+        let mut fragment_shader = self.material.fragment_shader(lights);
+        // The displacement is applied along the normals and sampled at the uv coordinates in the vertex shader
+        fragment_shader.attributes.normal = true;
+        fragment_shader.attributes.uv = true;
+        fragment_shader
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        self.material.use_uniforms(program, camera, lights);
+        self.displacement.use_uniforms(program);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        self.material.render_states()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.material.material_type()
+    }
+
+    fn displacement(&self) -> Option<Displacement> {
+        Some(self.displacement.clone())
+    }
+}
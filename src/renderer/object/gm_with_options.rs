@@ -0,0 +1,312 @@
+use crate::renderer::*;
+
+///
+/// A [Gm] with a few per object render options which are useful for editor helpers like gizmos and light icons,
+/// namely a constant size on screen, rendering on top of everything else, the render order and the object id.
+/// The options are specified using the builder methods, for example
+/// `GmWithOptions::new(gm).with_screen_space_scale(100.0).with_depth_test(DepthTest::Always).with_render_order(1)`.
+///
+pub struct GmWithOptions<G: Geometry, M: Material> {
+    gm: Gm<G, M>,
+    screen_space_scale: Option<f32>,
+    screen_space_origin: Vec3,
+    depth_test_override: Option<DepthTest>,
+    render_order: i32,
+    object_id: Option<u32>,
+}
+
+impl<G: Geometry, M: Material> GmWithOptions<G, M> {
+    ///
+    /// Creates a new [GmWithOptions] from the given [Gm] with the default options, ie. the object is rendered exactly like the [Gm].
+    ///
+    pub fn new(gm: Gm<G, M>) -> Self {
+        Self {
+            gm,
+            screen_space_scale: None,
+            screen_space_origin: vec3(0.0, 0.0, 0.0),
+            depth_test_override: None,
+            render_order: 0,
+            object_id: None,
+        }
+    }
+
+    ///
+    /// Scales the object around the screen space origin (see [Self::set_screen_space_origin]) each time it is rendered, so that one unit in the local coordinate system of the object
+    /// is always the given number of physical pixels on screen, regardless of the distance to the camera.
+    /// This applies to all render calls including [pick], but not [ray_intersect] since it does not know the camera.
+    /// Note that the frustum culling and render order is based on the bounding box of the unscaled object.
+    ///
+    pub fn with_screen_space_scale(mut self, pixels: f32) -> Self {
+        self.set_screen_space_scale(Some(pixels));
+        self
+    }
+
+    ///
+    /// Sets the screen space scale, see [Self::with_screen_space_scale]. Use `None` to render the object with its actual size.
+    ///
+    pub fn set_screen_space_scale(&mut self, pixels: Option<f32>) {
+        self.screen_space_scale = pixels;
+    }
+
+    ///
+    /// Returns the screen space scale, see [Self::with_screen_space_scale].
+    ///
+    pub fn screen_space_scale(&self) -> Option<f32> {
+        self.screen_space_scale
+    }
+
+    ///
+    /// Sets the point in world space which stays fixed when the object is scaled because of the screen space scale, typically the position of the object.
+    ///
+    pub fn set_screen_space_origin(&mut self, origin: Vec3) {
+        self.screen_space_origin = origin;
+    }
+
+    ///
+    /// Returns the point in world space which stays fixed when the object is scaled because of the screen space scale.
+    ///
+    pub fn screen_space_origin(&self) -> Vec3 {
+        self.screen_space_origin
+    }
+
+    ///
+    /// Overrides the depth test of the material when rendering the object with its own material, for example [DepthTest::Always] to render the object on top of everything rendered before it.
+    /// Combine with a render order higher than the other objects (see [Self::with_render_order]), to render the object after the other objects.
+    ///
+    pub fn with_depth_test(mut self, depth_test: DepthTest) -> Self {
+        self.set_depth_test_override(Some(depth_test));
+        self
+    }
+
+    ///
+    /// Sets the depth test override, see [Self::with_depth_test]. Use `None` to use the depth test of the material.
+    ///
+    pub fn set_depth_test_override(&mut self, depth_test: Option<DepthTest>) {
+        self.depth_test_override = depth_test;
+    }
+
+    ///
+    /// Returns the depth test override, see [Self::with_depth_test].
+    ///
+    pub fn depth_test_override(&self) -> Option<DepthTest> {
+        self.depth_test_override
+    }
+
+    ///
+    /// Sets the render order of the object (see [Object::render_order]). Defaults to 0.
+    ///
+    pub fn with_render_order(mut self, render_order: i32) -> Self {
+        self.set_render_order(render_order);
+        self
+    }
+
+    ///
+    /// Sets the render order of the object (see [Object::render_order]).
+    ///
+    pub fn set_render_order(&mut self, render_order: i32) {
+        self.render_order = render_order;
+    }
+
+    ///
+    /// Sets the id of the object written to the object id texture (see [Object::object_id]).
+    /// By default, the id is assigned from the order of the objects in the render call.
+    ///
+    pub fn with_object_id(mut self, object_id: u32) -> Self {
+        self.set_object_id(Some(object_id));
+        self
+    }
+
+    ///
+    /// Sets the id of the object written to the object id texture, see [Self::with_object_id].
+    /// Use `None` to assign the id from the order of the objects in the render call.
+    ///
+    pub fn set_object_id(&mut self, object_id: Option<u32>) {
+        self.object_id = object_id;
+    }
+
+    ///
+    /// Returns the camera which should be used for rendering the unscaled geometry in order to get the result of rendering the geometry
+    /// scaled around the screen space origin using the given camera, see [Self::with_screen_space_scale].
+    /// Scaling the object uniformly around a point is the same as moving the camera towards that point and scaling the near and far planes accordingly.
+    ///
+    fn screen_space_camera(&self, camera: &Camera) -> Option<Camera> {
+        let pixels = self.screen_space_scale?;
+        let origin = self.screen_space_origin;
+        let viewport_height = camera.viewport().height.max(1) as f32;
+        let scale = match camera.projection_type() {
+            ProjectionType::Perspective { field_of_view_y } => {
+                pixels * camera.position().distance(origin) * 2.0 * (0.5 * field_of_view_y.0).tan()
+                    / viewport_height
+            }
+            ProjectionType::Orthographic { height } => pixels * height / viewport_height,
+        };
+        if scale <= 0.0 || !scale.is_finite() {
+            return None;
+        }
+        let mut screen_space_camera = camera.clone();
+        let position = origin + (camera.position() - origin) / scale;
+        screen_space_camera.set_view(position, position + camera.view_direction(), *camera.up());
+        match camera.projection_type() {
+            ProjectionType::Perspective { field_of_view_y } => screen_space_camera
+                .set_perspective_projection(
+                    *field_of_view_y,
+                    camera.z_near() / scale,
+                    camera.z_far() / scale,
+                ),
+            ProjectionType::Orthographic { height } => screen_space_camera
+                .set_orthographic_projection(
+                    height / scale,
+                    camera.z_near() / scale,
+                    camera.z_far() / scale,
+                ),
+        }
+        Some(screen_space_camera)
+    }
+}
+
+impl<'a, G: Geometry, M: Material> IntoIterator for &'a GmWithOptions<G, M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::iter::Once<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        std::iter::once(self)
+    }
+}
+
+impl<G: Geometry, M: Material> Geometry for GmWithOptions<G, M> {
+    fn aabb(&self) -> AxisAlignedBoundingBox {
+        self.gm.aabb()
+    }
+
+    fn animate(&mut self, time: f32) {
+        self.gm.animate(time)
+    }
+
+    fn render_with_material(
+        &self,
+        material: &dyn Material,
+        camera: &Camera,
+        lights: &[&dyn Light],
+    ) {
+        let screen_space_camera = self.screen_space_camera(camera);
+        self.gm.render_with_material(
+            material,
+            screen_space_camera.as_ref().unwrap_or(camera),
+            lights,
+        )
+    }
+
+    fn render_with_post_material(
+        &self,
+        material: &dyn PostMaterial,
+        camera: &Camera,
+        lights: &[&dyn Light],
+        color_texture: Option<ColorTexture>,
+        depth_texture: Option<DepthTexture>,
+    ) {
+        let screen_space_camera = self.screen_space_camera(camera);
+        self.gm.render_with_post_material(
+            material,
+            screen_space_camera.as_ref().unwrap_or(camera),
+            lights,
+            color_texture,
+            depth_texture,
+        )
+    }
+}
+
+impl<G: Geometry, M: Material> Object for GmWithOptions<G, M> {
+    fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
+        if let Some(depth_test) = self.depth_test_override {
+            self.render_with_material(
+                &DepthTestOverride {
+                    material: &self.gm.material,
+                    depth_test,
+                },
+                camera,
+                lights,
+            )
+        } else {
+            self.render_with_material(&self.gm.material, camera, lights)
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.gm.material_type()
+    }
+
+    fn render_order(&self) -> i32 {
+        self.render_order
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        self.object_id
+    }
+
+    fn material_id(&self) -> usize {
+        self.gm.material_id()
+    }
+}
+
+impl<G: Geometry + Clone, M: Material + Clone> Clone for GmWithOptions<G, M> {
+    fn clone(&self) -> Self {
+        Self {
+            gm: self.gm.clone(),
+            screen_space_scale: self.screen_space_scale,
+            screen_space_origin: self.screen_space_origin,
+            depth_test_override: self.depth_test_override,
+            render_order: self.render_order,
+            object_id: self.object_id,
+        }
+    }
+}
+
+impl<G: Geometry, M: Material> std::ops::Deref for GmWithOptions<G, M> {
+    type Target = Gm<G, M>;
+    fn deref(&self) -> &Self::Target {
+        &self.gm
+    }
+}
+
+impl<G: Geometry, M: Material> std::ops::DerefMut for GmWithOptions<G, M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.gm
+    }
+}
+
+///
+/// A material which is identical to the wrapped material except for the depth test.
+///
+struct DepthTestOverride<'a, M: Material> {
+    material: &'a M,
+    depth_test: DepthTest,
+}
+
+impl<'a, M: Material> Material for DepthTestOverride<'a, M> {
+    fn fragment_shader(&self, lights: &[&dyn Light]) -> FragmentShader {
+        self.material.fragment_shader(lights)
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        self.material.use_uniforms(program, camera, lights)
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            depth_test: self.depth_test,
+            ..self.material.render_states()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        self.material.material_type()
+    }
+
+    fn displacement(&self) -> Option<Displacement> {
+        self.material.displacement()
+    }
+
+    fn max_displacement(&self) -> Option<f32> {
+        self.material.max_displacement()
+    }
+}
@@ -4,15 +4,133 @@ use std::f32::consts::PI;
 
 const NO_VIEW_ANGLES: u32 = 8;
 
+///
+/// The layout of the views in the atlas textures of lit [Imposters], see [Imposters::new_lit].
+/// The atlas is a grid with a column for each azimuth angle and a row for each elevation angle, where each view is a square of `view_size` x `view_size` pixels.
+/// The view in column `i` and row `j` is seen from the direction with azimuth angle [ImposterAtlasLayout::azimuth] and elevation angle [ImposterAtlasLayout::elevation],
+/// see [ImposterAtlasLayout::view_direction].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ImposterAtlasLayout {
+    /// The number of views around the up direction.
+    pub azimuth_count: u32,
+    /// The number of views from the horizon and up towards, but not including, straight above.
+    /// If it is 1, the imposters are cylindrical, ie. they only rotate around the up direction, otherwise they are spherical and always face the camera.
+    pub elevation_count: u32,
+    /// The width and height of each view in pixels.
+    pub view_size: u32,
+}
+
+impl ImposterAtlasLayout {
+    ///
+    /// Returns the width of the atlas in pixels.
+    ///
+    pub fn width(&self) -> u32 {
+        self.azimuth_count * self.view_size
+    }
+
+    ///
+    /// Returns the height of the atlas in pixels.
+    ///
+    pub fn height(&self) -> u32 {
+        self.elevation_count * self.view_size
+    }
+
+    ///
+    /// Returns the part of the atlas containing the view with the given azimuth and elevation index.
+    ///
+    /// # Panic
+    /// Will panic if the azimuth index is not less than the azimuth count or the elevation index is not less than the elevation count.
+    ///
+    pub fn view_viewport(&self, azimuth_index: u32, elevation_index: u32) -> Viewport {
+        self.check_indices(azimuth_index, elevation_index);
+        Viewport {
+            x: (azimuth_index * self.view_size) as i32,
+            y: (elevation_index * self.view_size) as i32,
+            width: self.view_size,
+            height: self.view_size,
+        }
+    }
+
+    ///
+    /// Returns the azimuth angle of the views with the given azimuth index, measured from the positive x-axis towards the positive z-axis.
+    ///
+    pub fn azimuth(&self, azimuth_index: u32) -> Radians {
+        radians(azimuth_index as f32 * 2.0 * PI / self.azimuth_count as f32)
+    }
+
+    ///
+    /// Returns the elevation angle of the views with the given elevation index, measured from the horizon.
+    ///
+    pub fn elevation(&self, elevation_index: u32) -> Radians {
+        radians(elevation_index as f32 * 0.5 * PI / self.elevation_count as f32)
+    }
+
+    ///
+    /// Returns the direction from the imposter towards the camera which the view with the given azimuth and elevation index is seen from.
+    ///
+    /// # Panic
+    /// Will panic if the azimuth index is not less than the azimuth count or the elevation index is not less than the elevation count.
+    ///
+    pub fn view_direction(&self, azimuth_index: u32, elevation_index: u32) -> Vec3 {
+        self.check_indices(azimuth_index, elevation_index);
+        let azimuth = self.azimuth(azimuth_index).0;
+        let elevation = self.elevation(elevation_index).0;
+        vec3(
+            elevation.cos() * azimuth.cos(),
+            elevation.sin(),
+            elevation.cos() * azimuth.sin(),
+        )
+    }
+
+    fn check_indices(&self, azimuth_index: u32, elevation_index: u32) {
+        if azimuth_index >= self.azimuth_count || elevation_index >= self.elevation_count {
+            panic!(
+                "the view ({}, {}) is outside the imposter atlas of {} x {} views",
+                azimuth_index, elevation_index, self.azimuth_count, self.elevation_count
+            );
+        }
+    }
+}
+
+impl Default for ImposterAtlasLayout {
+    fn default() -> Self {
+        Self {
+            azimuth_count: 8,
+            elevation_count: 1,
+            view_size: 256,
+        }
+    }
+}
+
 ///
 /// A level-of-detail technique to replace rendering of high-poly meshes.
 /// Should only be used where details cannot be seen, for example when the objects are far away.
 /// A set of objects are rendered from different angles into a set of textures and the textures are then
 /// rendered continuously instead of the expensive objects.
 ///
+/// The imposters are either baked with the lights given at construction using [Imposters::new], in which case they have to be baked again when the lights change,
+/// or lit by the lights in the scene using [Imposters::new_lit].
+/// Use an [ImposterLod] to switch between rendering a model and imposters based on the distance to the camera.
+///
 pub struct Imposters {
+    context: Context,
     sprites: Sprites,
-    material: ImpostersMaterial,
+    positions: Vec<Vec3>,
+    views: ImposterViews,
+}
+
+enum ImposterViews {
+    Unlit(ImpostersMaterial),
+    Lit(ImposterAtlas),
+}
+
+struct ImposterAtlas {
+    layout: ImposterAtlasLayout,
+    color_texture: Texture2D,
+    normal_depth_texture: Texture2D,
+    center: Vec3,
+    radius: f32,
 }
 
 impl Imposters {
@@ -27,29 +145,64 @@ impl Imposters {
         lights: &[&dyn Light],
         max_texture_size: u32,
     ) -> Self {
-        let mut aabb = AxisAlignedBoundingBox::EMPTY;
-        objects
-            .clone()
-            .into_iter()
-            .for_each(|o| aabb.expand_with_aabb(&o.aabb()));
-        let mut sprites = Sprites::new(context, positions, Some(vec3(0.0, 1.0, 0.0)));
-        sprites.set_transformation(get_sprite_transform(aabb));
-        Imposters {
-            sprites,
-            material: ImpostersMaterial::new(context, aabb, objects, lights, max_texture_size),
-        }
+        let mut imposters = Self {
+            context: context.clone(),
+            sprites: Sprites::new(context, &[], Some(vec3(0.0, 1.0, 0.0))),
+            positions: positions.to_vec(),
+            views: ImposterViews::Unlit(ImpostersMaterial::new(context)),
+        };
+        imposters.update_texture(objects, lights, max_texture_size);
+        imposters
+    }
+
+    ///
+    /// Constructs new imposters which are lit by the lights in the scene and bakes the atlas textures from the given objects using the given layout.
+    /// An imposter is placed at each of the given positions and shows the objects as if they were translated by that position.
+    ///
+    /// The objects are baked from a number of directions, given by the [ImposterAtlasLayout], into a color atlas and an atlas with the normal and depth.
+    /// At runtime, each imposter blends the four views nearest to the direction it is seen from and lights the result using the baked normals,
+    /// so the imposters do not have to be baked again when the lights change.
+    /// The objects are baked with a white [AmbientLight] with intensity 1 to capture the surface color, so the lighting of the imposters is always diffuse,
+    /// and the normals are the geometric normals of the objects, ie. normal textures are not included.
+    ///
+    /// # Panic
+    /// Will panic if the azimuth count, elevation count or view size of the layout is zero.
+    ///
+    pub fn new_lit(
+        context: &Context,
+        positions: &[Vec3],
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        layout: ImposterAtlasLayout,
+    ) -> Self {
+        let mut imposters = Self {
+            context: context.clone(),
+            sprites: Sprites::new(context, &[], Some(vec3(0.0, 1.0, 0.0))),
+            positions: positions.to_vec(),
+            views: ImposterViews::Lit(ImposterAtlas::new(context, objects, layout)),
+        };
+        imposters.update_sprites();
+        imposters
     }
 
     ///
     /// Set the positions of the imposters.
     ///
     pub fn set_positions(&mut self, positions: &[Vec3]) {
-        self.sprites.set_centers(positions);
+        self.positions = positions.to_vec();
+        self.update_sprites();
+    }
+
+    ///
+    /// Returns the positions of the imposters.
+    ///
+    pub fn positions(&self) -> &[Vec3] {
+        &self.positions
     }
 
     ///
     /// Render the imposter texture from the given objects with the given lights.
     /// Use this if you want to update the look of the imposters.
+    /// The imposters are not lit by the lights in the scene afterwards, even if they were constructed using [Imposters::new_lit].
     ///
     pub fn update_texture(
         &mut self,
@@ -57,15 +210,102 @@ impl Imposters {
         lights: &[&dyn Light],
         max_texture_size: u32,
     ) {
-        let mut aabb = AxisAlignedBoundingBox::EMPTY;
-        objects
-            .clone()
-            .into_iter()
-            .for_each(|o| aabb.expand_with_aabb(&o.aabb()));
+        let aabb = objects_aabb(objects.clone());
+        if !matches!(self.views, ImposterViews::Unlit(_)) {
+            self.views = ImposterViews::Unlit(ImpostersMaterial::new(&self.context));
+        }
+        if let ImposterViews::Unlit(material) = &mut self.views {
+            material.update(aabb, objects, lights, max_texture_size);
+        }
+        self.sprites.set_direction(Some(vec3(0.0, 1.0, 0.0)));
         self.sprites.set_transformation(get_sprite_transform(aabb));
-        self.material
-            .update(aabb, objects, lights, max_texture_size);
+        self.update_sprites();
+    }
+
+    ///
+    /// Bakes the atlas textures of lit imposters (see [Imposters::new_lit]) from the given objects using the given layout,
+    /// for example when the objects have changed or to change the resolution at runtime.
+    ///
+    /// # Panic
+    /// Will panic if the azimuth count, elevation count or view size of the layout is zero.
+    ///
+    pub fn bake(
+        &mut self,
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        layout: ImposterAtlasLayout,
+    ) {
+        self.views = ImposterViews::Lit(ImposterAtlas::new(&self.context, objects, layout));
+        self.update_sprites();
+    }
+
+    ///
+    /// Returns the layout of the views in the atlas textures or `None` if the imposters are not lit, see [Imposters::new_lit].
+    ///
+    pub fn layout(&self) -> Option<ImposterAtlasLayout> {
+        self.atlas().map(|atlas| atlas.layout)
+    }
+
+    ///
+    /// Returns the atlas with the surface color in sRGB and the coverage in the alpha channel, mainly for debugging the baking,
+    /// or `None` if the imposters are not lit, see [Imposters::new_lit].
+    ///
+    pub fn color_texture(&self) -> Option<&Texture2D> {
+        self.atlas().map(|atlas| &atlas.color_texture)
+    }
+
+    ///
+    /// Returns the atlas with the world space normal mapped to the range `[0, 1]` in the red, green and blue channels and the depth in the alpha channel,
+    /// where 0 is the radius of the bounding sphere in front of the center of the objects and 1 is the radius behind it, mainly for debugging the baking,
+    /// or `None` if the imposters are not lit, see [Imposters::new_lit].
+    ///
+    pub fn normal_depth_texture(&self) -> Option<&Texture2D> {
+        self.atlas().map(|atlas| &atlas.normal_depth_texture)
+    }
+
+    fn atlas(&self) -> Option<&ImposterAtlas> {
+        if let ImposterViews::Lit(atlas) = &self.views {
+            Some(atlas)
+        } else {
+            None
+        }
     }
+
+    fn update_sprites(&mut self) {
+        if let ImposterViews::Lit(atlas) = &self.views {
+            self.sprites
+                .set_direction(if atlas.layout.elevation_count == 1 {
+                    Some(vec3(0.0, 1.0, 0.0))
+                } else {
+                    None
+                });
+            // The sprites are centered at the center of the objects, so the lighting can use the direction from the center to the camera
+            let centers = self
+                .positions
+                .iter()
+                .map(|p| p + atlas.center)
+                .collect::<Vec<_>>();
+            self.sprites.set_centers(&centers);
+            self.sprites.set_transformation(Mat4::from_nonuniform_scale(
+                atlas.radius,
+                atlas.radius,
+                0.0,
+            ));
+        } else {
+            self.sprites.set_centers(&self.positions);
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.positions.is_empty() || self.atlas().map_or(false, |atlas| atlas.radius <= 0.0)
+    }
+}
+
+fn objects_aabb(objects: impl IntoIterator<Item = impl Object>) -> AxisAlignedBoundingBox {
+    let mut aabb = AxisAlignedBoundingBox::EMPTY;
+    objects
+        .into_iter()
+        .for_each(|o| aabb.expand_with_aabb(&o.aabb()));
+    aabb
 }
 
 fn get_sprite_transform(aabb: AxisAlignedBoundingBox) -> Mat4 {
@@ -96,7 +336,9 @@ impl Geometry for Imposters {
         camera: &Camera,
         lights: &[&dyn Light],
     ) {
-        self.sprites.render_with_material(material, camera, lights)
+        if !self.is_empty() {
+            self.sprites.render_with_material(material, camera, lights)
+        }
     }
 
     fn render_with_post_material(
@@ -107,44 +349,169 @@ impl Geometry for Imposters {
         color_texture: Option<ColorTexture>,
         depth_texture: Option<DepthTexture>,
     ) {
-        self.sprites.render_with_post_material(
-            material,
-            camera,
-            lights,
-            color_texture,
-            depth_texture,
-        )
+        if !self.is_empty() {
+            self.sprites.render_with_post_material(
+                material,
+                camera,
+                lights,
+                color_texture,
+                depth_texture,
+            )
+        }
     }
 
     fn aabb(&self) -> AxisAlignedBoundingBox {
-        self.sprites.aabb()
+        if let Some(atlas) = self.atlas() {
+            AxisAlignedBoundingBox::from_points(&self.positions)
+                .transformed(&Mat4::from_translation(atlas.center))
+                .expanded_by(atlas.radius)
+        } else {
+            self.sprites.aabb()
+        }
     }
 }
 
 impl Object for Imposters {
     fn render(&self, camera: &Camera, lights: &[&dyn Light]) {
-        self.render_with_material(&self.material, camera, lights)
+        match &self.views {
+            ImposterViews::Unlit(material) => self.render_with_material(material, camera, lights),
+            ImposterViews::Lit(atlas) => {
+                self.render_with_material(&LitImpostersMaterial { atlas }, camera, lights)
+            }
+        }
     }
 
     fn material_type(&self) -> MaterialType {
-        self.material.material_type()
+        match &self.views {
+            ImposterViews::Unlit(material) => material.material_type(),
+            ImposterViews::Lit(_) => MaterialType::Opaque,
+        }
     }
 }
 
+impl ImposterAtlas {
+    fn new(
+        context: &Context,
+        objects: impl IntoIterator<Item = impl Object> + Clone,
+        layout: ImposterAtlasLayout,
+    ) -> Self {
+        if layout.azimuth_count == 0 || layout.elevation_count == 0 || layout.view_size == 0 {
+            panic!(
+                "the imposter atlas layout must contain at least one view of at least one pixel"
+            );
+        }
+        let aabb = objects_aabb(objects.clone());
+        if aabb.is_empty() {
+            return Self {
+                layout,
+                color_texture: new_atlas_texture::<[u8; 4]>(context, 1, 1),
+                normal_depth_texture: new_atlas_texture::<[f16; 4]>(context, 1, 1),
+                center: vec3(0.0, 0.0, 0.0),
+                radius: 0.0,
+            };
+        }
+        let (min, max) = (aabb.min(), aabb.max());
+        let center = 0.5 * (min + max);
+        let radius = 0.5 * (max - min).magnitude();
+
+        let (width, height) = (layout.width(), layout.height());
+        let mut color_texture = new_atlas_texture::<[u8; 4]>(context, width, height);
+        let mut normal_depth_texture = new_atlas_texture::<[f16; 4]>(context, width, height);
+        let mut depth_texture = DepthTexture2D::new::<f32>(
+            context,
+            width,
+            height,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut camera = Camera::new_orthographic(
+            layout.view_viewport(0, 0),
+            center + 2.0 * radius * vec3(1.0, 0.0, 0.0),
+            center,
+            vec3(0.0, 1.0, 0.0),
+            2.0 * radius,
+            0.0,
+            4.0 * radius,
+        );
+        let view = |camera: &mut Camera, azimuth_index: u32, elevation_index: u32| {
+            camera.set_viewport(layout.view_viewport(azimuth_index, elevation_index));
+            camera.set_view(
+                center + 2.0 * radius * layout.view_direction(azimuth_index, elevation_index),
+                center,
+                vec3(0.0, 1.0, 0.0),
+            );
+        };
+
+        // The surface color is captured by rendering the objects with their own materials using only a white ambient light
+        let ambient = AmbientLight::new(context, 1.0, Color::WHITE);
+        {
+            let render_target = RenderTarget::new(
+                color_texture.as_color_target(None),
+                depth_texture.as_depth_target(),
+            );
+            render_target.clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0));
+            for j in 0..layout.elevation_count {
+                for i in 0..layout.azimuth_count {
+                    view(&mut camera, i, j);
+                    render_target.render(&camera, objects.clone(), &[&ambient]);
+                }
+            }
+        }
+
+        // The depth test against the depth from the color pass makes sure the normals belong to the visible surfaces
+        {
+            let render_target = RenderTarget::new(
+                normal_depth_texture.as_color_target(None),
+                depth_texture.as_depth_target(),
+            );
+            render_target.clear(ClearState::color(0.5, 0.5, 1.0, 0.5));
+            for j in 0..layout.elevation_count {
+                for i in 0..layout.azimuth_count {
+                    view(&mut camera, i, j);
+                    let material = ImposterBakeMaterial {
+                        center,
+                        view_direction: layout.view_direction(i, j),
+                        radius,
+                    };
+                    render_target.write(|| {
+                        for object in objects.clone() {
+                            object.render_with_material(&material, &camera, &[]);
+                        }
+                    });
+                }
+            }
+        }
+        Self {
+            layout,
+            color_texture,
+            normal_depth_texture,
+            center,
+            radius,
+        }
+    }
+}
+
+fn new_atlas_texture<T: TextureDataType>(context: &Context, width: u32, height: u32) -> Texture2D {
+    Texture2D::new_empty::<T>(
+        context,
+        width,
+        height,
+        Interpolation::Linear,
+        Interpolation::Linear,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
 struct ImpostersMaterial {
     context: Context,
     texture: Texture2DArray,
 }
 
 impl ImpostersMaterial {
-    pub fn new(
-        context: &Context,
-        aabb: AxisAlignedBoundingBox,
-        objects: impl IntoIterator<Item = impl Object> + Clone,
-        lights: &[&dyn Light],
-        max_texture_size: u32,
-    ) -> Self {
-        let mut m = Self {
+    pub fn new(context: &Context) -> Self {
+        Self {
             context: context.clone(),
             texture: Texture2DArray::new_empty::<[u8; 4]>(
                 context,
@@ -157,10 +524,9 @@ impl ImpostersMaterial {
                 Wrapping::ClampToEdge,
                 Wrapping::ClampToEdge,
             ),
-        };
-        m.update(aabb, objects, lights, max_texture_size);
-        m
+        }
     }
+
     pub fn update(
         &mut self,
         aabb: AxisAlignedBoundingBox,
@@ -254,3 +620,240 @@ impl Material for ImpostersMaterial {
         MaterialType::Transparent
     }
 }
+
+struct LitImpostersMaterial<'a> {
+    atlas: &'a ImposterAtlas,
+}
+
+impl Material for LitImpostersMaterial<'_> {
+    fn fragment_shader(&self, lights: &[&dyn Light]) -> FragmentShader {
+        FragmentShader {
+            source: format!(
+                "{}{}",
+                lights_shader_source(lights, LightingModel::Blinn),
+                include_str!("shaders/imposter_lit.frag")
+            ),
+            attributes: FragmentAttributes {
+                position: true,
+                uv: true,
+                ..FragmentAttributes::NONE
+            },
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, camera: &Camera, lights: &[&dyn Light]) {
+        for (i, light) in lights.iter().enumerate() {
+            light.use_uniforms(program, i as u32);
+        }
+        let layout = self.atlas.layout;
+        program.use_uniform("cameraPosition", camera.position());
+        program.use_uniform("azimuthCount", layout.azimuth_count as i32);
+        program.use_uniform("elevationCount", layout.elevation_count as i32);
+        program.use_uniform("viewSize", layout.view_size as f32);
+        program.use_uniform("radius", self.atlas.radius);
+        program.use_texture("colorTexture", &self.atlas.color_texture);
+        program.use_texture("normalDepthTexture", &self.atlas.normal_depth_texture);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
+
+struct ImposterBakeMaterial {
+    center: Vec3,
+    view_direction: Vec3,
+    radius: f32,
+}
+
+impl Material for ImposterBakeMaterial {
+    fn fragment_shader(&self, _lights: &[&dyn Light]) -> FragmentShader {
+        FragmentShader {
+            source: include_str!("shaders/imposter_bake.frag").to_string(),
+            attributes: FragmentAttributes {
+                position: true,
+                normal: true,
+                ..FragmentAttributes::NONE
+            },
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform("center", self.center);
+        program.use_uniform("viewDirection", self.view_direction);
+        program.use_uniform("radius", self.radius);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates {
+            write_mask: WriteMask::COLOR,
+            depth_test: DepthTest::LessOrEqual,
+            cull: Cull::None,
+            ..Default::default()
+        }
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
+
+///
+/// Renders the instances of a model which are closer to the camera than a given distance as an [InstancedModel] and the rest as [Imposters].
+/// Call [ImposterLod::update] each frame, or whenever the camera has moved, to move the instances between the two.
+///
+/// The imposters only use the translation of the instance transformations, so rotated or scaled instances look the same as the baked model when they are far away.
+///
+pub struct ImposterLod<M: Material> {
+    model: InstancedModel<M>,
+    imposters: Imposters,
+    transformations: Vec<Mat4>,
+    distance: f32,
+}
+
+impl<M: Material + FromCpuMaterial + Clone + Default> ImposterLod<M> {
+    ///
+    /// Constructs a new [ImposterLod] with an instance of the given model for each of the given transformations.
+    /// Instances further away from the camera than the given distance are rendered as imposters baked from the model using the given layout.
+    /// All instances are rendered as imposters until [ImposterLod::update] is called.
+    ///
+    pub fn new(
+        context: &Context,
+        cpu_model: &CpuModel,
+        transformations: &[Mat4],
+        distance: f32,
+        layout: ImposterAtlasLayout,
+    ) -> Result<Self, RendererError> {
+        let imposters =
+            Imposters::new_lit(context, &[], &Model::<M>::new(context, cpu_model)?, layout);
+        let model = InstancedModel::new(context, &Instances::default(), cpu_model)?;
+        let mut lod = Self {
+            model,
+            imposters,
+            transformations: transformations.to_vec(),
+            distance,
+        };
+        lod.update_instances(|_| false);
+        Ok(lod)
+    }
+}
+
+impl<M: Material> ImposterLod<M> {
+    ///
+    /// Renders the instances closer to the given camera position than the switch distance as the model and the rest as imposters.
+    ///
+    pub fn update(&mut self, camera_position: Vec3) {
+        let distance2 = self.distance * self.distance;
+        self.update_instances(|position| position.distance2(camera_position) < distance2);
+    }
+
+    ///
+    /// Set the transformations of the instances. Call [ImposterLod::update] afterwards to decide which instances are rendered as imposters.
+    ///
+    pub fn set_transformations(&mut self, transformations: &[Mat4]) {
+        self.transformations = transformations.to_vec();
+        self.update_instances(|_| false);
+    }
+
+    ///
+    /// Set the distance from the camera at which the instances switch between the model and imposters.
+    ///
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance;
+    }
+
+    ///
+    /// Returns the distance from the camera at which the instances switch between the model and imposters.
+    ///
+    pub fn distance(&self) -> f32 {
+        self.distance
+    }
+
+    ///
+    /// Returns the model used for the instances close to the camera.
+    ///
+    pub fn model(&self) -> &InstancedModel<M> {
+        &self.model
+    }
+
+    ///
+    /// Returns the imposters used for the instances far from the camera, for example to bake the imposters again using [Imposters::bake].
+    ///
+    pub fn imposters_mut(&mut self) -> &mut Imposters {
+        &mut self.imposters
+    }
+
+    ///
+    /// Returns the imposters used for the instances far from the camera.
+    ///
+    pub fn imposters(&self) -> &Imposters {
+        &self.imposters
+    }
+
+    fn update_instances(&mut self, is_near: impl Fn(Vec3) -> bool) {
+        let mut near = Vec::new();
+        let mut far = Vec::new();
+        for transformation in self.transformations.iter() {
+            let position = transformation.w.truncate();
+            if is_near(position) {
+                near.push(*transformation);
+            } else {
+                far.push(position);
+            }
+        }
+        let instances = Instances {
+            transformations: near,
+            ..Default::default()
+        };
+        for part in self.model.iter_mut() {
+            part.geometry.set_instances(&instances);
+        }
+        self.imposters.set_positions(&far);
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a ImposterLod<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.model
+            .iter()
+            .map(|m| m as &dyn Object)
+            .chain(std::iter::once(&self.imposters as &dyn Object))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_imposter_atlas_layout() {
+        let layout = ImposterAtlasLayout {
+            azimuth_count: 4,
+            elevation_count: 2,
+            view_size: 64,
+        };
+        assert_eq!((layout.width(), layout.height()), (256, 128));
+        assert_eq!(
+            layout.view_viewport(3, 1),
+            Viewport {
+                x: 192,
+                y: 64,
+                width: 64,
+                height: 64
+            }
+        );
+        assert!((layout.view_direction(0, 0) - vec3(1.0, 0.0, 0.0)).magnitude() < 1.0e-5);
+        assert!((layout.view_direction(1, 0) - vec3(0.0, 0.0, 1.0)).magnitude() < 1.0e-5);
+        let up = layout.view_direction(2, 1);
+        assert!((up - vec3(-0.5f32.sqrt(), 0.5f32.sqrt(), 0.0)).magnitude() < 1.0e-5);
+    }
+}
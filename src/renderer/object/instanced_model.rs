@@ -6,9 +6,35 @@ use crate::renderer::*;
 pub struct InstancedModelPart<M: Material> {
     gm: Gm<InstancedMesh, M>,
     animations: Vec<KeyFrameAnimation>,
+    render_order: i32,
+    object_id: Option<u32>,
 }
 
 impl<M: Material> InstancedModelPart<M> {
+    pub(super) fn new(gm: Gm<InstancedMesh, M>, animations: Vec<KeyFrameAnimation>) -> Self {
+        Self {
+            gm,
+            animations,
+            render_order: 0,
+            object_id: None,
+        }
+    }
+
+    ///
+    /// Sets the render order of this part (see [Object::render_order]). Defaults to 0.
+    ///
+    pub fn set_render_order(&mut self, render_order: i32) {
+        self.render_order = render_order;
+    }
+
+    ///
+    /// Sets the id of this part written to the object id texture (see [Object::object_id]).
+    /// Use `None`, which is the default, to assign the id from the order of the objects in the render call.
+    ///
+    pub fn set_object_id(&mut self, object_id: Option<u32>) {
+        self.object_id = object_id;
+    }
+
     ///
     /// Returns a list of unique names for the animations for this model part. Use these names as input to [Self::choose_animation].
     ///
@@ -85,6 +111,18 @@ impl<M: Material> Object for InstancedModelPart<M> {
     fn material_type(&self) -> MaterialType {
         self.gm.material_type()
     }
+
+    fn render_order(&self) -> i32 {
+        self.render_order
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        self.object_id
+    }
+
+    fn material_id(&self) -> usize {
+        self.gm.material_id()
+    }
 }
 
 impl<'a, M: Material> IntoIterator for &'a InstancedModelPart<M> {
@@ -122,6 +160,30 @@ impl<M: Material + FromCpuMaterial + Clone + Default> InstancedModel<M> {
         context: &Context,
         instances: &Instances,
         cpu_model: &CpuModel,
+    ) -> Result<Self, RendererError> {
+        Self::new_with_meshes(context, cpu_model, |geometry| {
+            InstancedMesh::new(context, instances, geometry)
+        })
+    }
+
+    ///
+    /// Constructs an [InstancedModel] from a [CpuModel] like [InstancedModel::new], except that all the parts of the model render the given [SharedInstances],
+    /// so the instance attributes are only stored once on the GPU, see [InstancedMesh::new_with_shared_instances].
+    ///
+    pub fn new_with_shared_instances(
+        context: &Context,
+        shared_instances: &SharedInstances,
+        cpu_model: &CpuModel,
+    ) -> Result<Self, RendererError> {
+        Self::new_with_meshes(context, cpu_model, |geometry| {
+            InstancedMesh::new_with_shared_instances(context, shared_instances, geometry)
+        })
+    }
+
+    fn new_with_meshes(
+        context: &Context,
+        cpu_model: &CpuModel,
+        new_mesh: impl Fn(&CpuMesh) -> InstancedMesh,
     ) -> Result<Self, RendererError> {
         let materials = cpu_model
             .materials
@@ -145,7 +207,7 @@ impl<M: Material + FromCpuMaterial + Clone + Default> InstancedModel<M> {
                     M::default()
                 };
                 let mut gm = Gm {
-                    geometry: InstancedMesh::new(context, instances, geometry),
+                    geometry: new_mesh(geometry),
                     material,
                 };
                 gm.set_transformation(primitive.transformation);
@@ -190,6 +252,12 @@ impl<M: Material + FromCpuMaterial + Clone + Default> InstancedModel<M> {
     }
 }
 
+impl<M: Material> InstancedModel<M> {
+    pub(super) fn from_parts(parts: Vec<InstancedModelPart<M>>) -> Self {
+        Self(parts)
+    }
+}
+
 impl<M: Material> std::ops::Deref for InstancedModel<M> {
     type Target = Vec<InstancedModelPart<M>>;
     fn deref(&self) -> &Self::Target {
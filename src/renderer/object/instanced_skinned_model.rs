@@ -0,0 +1,78 @@
+use crate::renderer::*;
+
+///
+/// Many instances of a skinned model, for example a crowd of animated characters, where each part of the model is an [InstancedSkinnedMesh] with some type of [material].
+/// All parts share the same instances and poses, so each part is rendered in a single draw call for all instances.
+///
+pub struct InstancedSkinnedModel<M: Material>(Vec<Gm<InstancedSkinnedMesh, M>>);
+
+impl<M: Material> InstancedSkinnedModel<M> {
+    ///
+    /// Constructs a new instanced skinned model from the given parts, each consisting of a [CpuMesh], the [CpuSkin] of the mesh and a material,
+    /// with an instance for each of the given transformations which is animated by the corresponding instance in the given [CrowdAnimator].
+    ///
+    /// # Panic
+    /// Will panic if a skin does not have joints and weights for each vertex of the mesh or if the number of transformations does not match the number of instances in the animator.
+    ///
+    pub fn new(
+        context: &Context,
+        parts: Vec<(&CpuMesh, &CpuSkin, M)>,
+        transformations: &[Mat4],
+        animator: &CrowdAnimator,
+    ) -> Self {
+        let mut gms: Vec<Gm<InstancedSkinnedMesh, M>> = Vec::new();
+        for (cpu_mesh, skin, material) in parts {
+            let mesh = if let Some(first) = gms.first() {
+                first.geometry.new_sharing_joints(cpu_mesh, skin)
+            } else {
+                InstancedSkinnedMesh::new(context, cpu_mesh, skin, transformations, animator)
+            };
+            gms.push(Gm::new(mesh, material));
+        }
+        Self(gms)
+    }
+
+    ///
+    /// Uploads the current poses of the instances in the given animator, which should be called each frame after [CrowdAnimator::update].
+    /// See [InstancedSkinnedMesh::update_joints].
+    ///
+    pub fn update_joints(&mut self, animator: &CrowdAnimator) {
+        if let Some(first) = self.0.first_mut() {
+            first.geometry.update_joints(animator);
+        }
+    }
+
+    ///
+    /// Sets the transformation of each instance, see [InstancedSkinnedMesh::set_instance_transformations].
+    ///
+    pub fn set_instance_transformations(&mut self, transformations: &[Mat4]) {
+        for part in self.0.iter_mut() {
+            part.geometry.set_instance_transformations(transformations);
+        }
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a InstancedSkinnedModel<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+            .map(|m| m as &dyn Object)
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+impl<M: Material> std::ops::Deref for InstancedSkinnedModel<M> {
+    type Target = Vec<Gm<InstancedSkinnedMesh, M>>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<M: Material> std::ops::DerefMut for InstancedSkinnedModel<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
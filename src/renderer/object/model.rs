@@ -7,9 +7,35 @@ pub use three_d_asset::Model as CpuModel;
 pub struct ModelPart<M: Material> {
     gm: Gm<Mesh, M>,
     animations: Vec<KeyFrameAnimation>,
+    render_order: i32,
+    object_id: Option<u32>,
 }
 
 impl<M: Material> ModelPart<M> {
+    pub(super) fn new(gm: Gm<Mesh, M>, animations: Vec<KeyFrameAnimation>) -> Self {
+        Self {
+            gm,
+            animations,
+            render_order: 0,
+            object_id: None,
+        }
+    }
+
+    ///
+    /// Sets the render order of this part (see [Object::render_order]). Defaults to 0.
+    ///
+    pub fn set_render_order(&mut self, render_order: i32) {
+        self.render_order = render_order;
+    }
+
+    ///
+    /// Sets the id of this part written to the object id texture (see [Object::object_id]).
+    /// Use `None`, which is the default, to assign the id from the order of the objects in the render call.
+    ///
+    pub fn set_object_id(&mut self, object_id: Option<u32>) {
+        self.object_id = object_id;
+    }
+
     ///
     /// Returns a list of unique names for the animations for this model part. Use these names as input to [Self::choose_animation].
     ///
@@ -85,6 +111,18 @@ impl<M: Material> Object for ModelPart<M> {
     fn material_type(&self) -> MaterialType {
         self.gm.material_type()
     }
+
+    fn render_order(&self) -> i32 {
+        self.render_order
+    }
+
+    fn object_id(&self) -> Option<u32> {
+        self.object_id
+    }
+
+    fn material_id(&self) -> usize {
+        self.gm.material_id()
+    }
 }
 
 impl<'a, M: Material> IntoIterator for &'a ModelPart<M> {
@@ -125,31 +163,16 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
             .map(|m| M::from_cpu_material(context, m))
             .collect::<Vec<_>>();
         let mut gms = Vec::new();
-        for primitive in cpu_model.geometries.iter() {
-            if let CpuGeometry::Triangles(geometry) = &primitive.geometry {
-                let material = if let Some(material_index) = primitive.material_index {
-                    materials
-                        .get(material_index)
-                        .ok_or_else(|| {
-                            RendererError::MissingMaterial(
-                                material_index.to_string(),
-                                primitive.name.clone(),
-                            )
-                        })?
-                        .clone()
-                } else {
-                    M::default()
-                };
-                let mut gm = Gm {
-                    geometry: Mesh::new(context, geometry),
-                    material,
-                };
-                gm.set_transformation(primitive.transformation);
-                gms.push(ModelPart {
-                    gm,
-                    animations: primitive.animations.clone(),
-                });
-            }
+        for (geometry, material_index, transformation, animations) in parts(cpu_model)? {
+            let material = material_index
+                .map(|i| materials[i].clone())
+                .unwrap_or_default();
+            let mut gm = Gm {
+                geometry: Mesh::new(context, geometry),
+                material,
+            };
+            gm.set_transformation(transformation);
+            gms.push(ModelPart { gm, animations });
         }
         let mut model = Self(gms);
         if let Some(animation_name) = model.animations().first().cloned() {
@@ -158,6 +181,58 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
         Ok(model)
     }
 
+    ///
+    /// Replaces the content of this model with the given [CpuModel], for example when the file it was loaded from has changed.
+    /// The meshes and materials of the existing parts are reloaded in place (see [Mesh::reload] and [FromCpuMaterial::reload]),
+    /// so textures shared with other materials are updated as well, and the transformations and animations are replaced,
+    /// while parts are added or removed if the number of parts has changed.
+    /// If an error is returned, the model is unchanged.
+    ///
+    pub fn reload(&mut self, context: &Context, cpu_model: &CpuModel) -> Result<(), RendererError> {
+        let parts = parts(cpu_model)?;
+        self.0.truncate(parts.len());
+        // Each material is reloaded once and then shared by all the parts using it, like when the model is constructed
+        let mut materials: Vec<Option<M>> = vec![None; cpu_model.materials.len()];
+        for (i, (geometry, material_index, transformation, animations)) in
+            parts.into_iter().enumerate()
+        {
+            let reloaded = material_index.and_then(|i| materials[i].clone());
+            if let Some(part) = self.0.get_mut(i) {
+                part.gm.geometry.reload(geometry);
+                if let Some(material) = reloaded {
+                    part.gm.material = material;
+                } else if let Some(material_index) = material_index {
+                    part.gm
+                        .material
+                        .reload(context, &cpu_model.materials[material_index]);
+                    materials[material_index] = Some(part.gm.material.clone());
+                } else {
+                    part.gm.material = M::default();
+                }
+                part.gm.set_transformation(transformation);
+                part.animations = animations;
+            } else {
+                let material = reloaded.unwrap_or_else(|| {
+                    material_index
+                        .map(|material_index| {
+                            let material =
+                                M::from_cpu_material(context, &cpu_model.materials[material_index]);
+                            materials[material_index] = Some(material.clone());
+                            material
+                        })
+                        .unwrap_or_default()
+                });
+                let mut gm = Gm::new(Mesh::new(context, geometry), material);
+                gm.set_transformation(transformation);
+                self.0.push(ModelPart { gm, animations });
+            }
+        }
+        if let Some(animation_name) = self.animations().first().cloned() {
+            self.choose_animation(animation_name.as_deref());
+        }
+        Ok(())
+    }
+
     ///
     /// Returns a list of unique names for the animations in this model. Use these names as input to [Self::choose_animation].
     ///
@@ -186,6 +261,40 @@ impl<M: Material + FromCpuMaterial + Clone + Default> Model<M> {
     }
 }
 
+///
+/// Returns the mesh, material, transformation and animations of each triangle mesh part of the given model.
+///
+fn parts(
+    cpu_model: &CpuModel,
+) -> Result<Vec<(&CpuMesh, Option<usize>, Mat4, Vec<KeyFrameAnimation>)>, RendererError> {
+    let mut parts = Vec::new();
+    for primitive in cpu_model.geometries.iter() {
+        if let CpuGeometry::Triangles(geometry) = &primitive.geometry {
+            if let Some(material_index) = primitive.material_index {
+                if material_index >= cpu_model.materials.len() {
+                    return Err(RendererError::MissingMaterial(
+                        material_index.to_string(),
+                        primitive.name.clone(),
+                    ));
+                }
+            }
+            parts.push((
+                geometry,
+                primitive.material_index,
+                primitive.transformation,
+                primitive.animations.clone(),
+            ));
+        }
+    }
+    Ok(parts)
+}
+
+impl<M: Material> Model<M> {
+    pub(super) fn from_parts(parts: Vec<ModelPart<M>>) -> Self {
+        Self(parts)
+    }
+}
+
 impl<M: Material> std::ops::Deref for Model<M> {
     type Target = Vec<ModelPart<M>>;
     fn deref(&self) -> &Self::Target {
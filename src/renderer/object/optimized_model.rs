@@ -0,0 +1,487 @@
+use crate::renderer::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+///
+/// Statistics for the optimization performed when constructing an [OptimizedModel].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InstancingStats {
+    /// The number of draw calls needed to render the model without the optimization, ie. the number of triangle meshes in the [CpuModel].
+    pub draw_calls_before: usize,
+    /// The number of draw calls needed to render the optimized model.
+    pub draw_calls_after: usize,
+}
+
+///
+/// Where a primitive (node) from the original [CpuModel] ended up in an [OptimizedModel].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PrimitiveLocation {
+    /// The primitive is rendered as the model part with the given index in [OptimizedModel::model].
+    Model(usize),
+    /// The primitive is rendered as the given instance of the model part with the given index in [OptimizedModel::instanced_model].
+    Instance {
+        /// The index of the model part.
+        part: usize,
+        /// The index of the instance.
+        instance: usize,
+    },
+}
+
+///
+/// A model constructed from a [CpuModel] where all primitives (nodes) that share the same mesh and material are collapsed into one [InstancedMesh] with the transformation of each primitive as an instance.
+/// This can reduce the number of draw calls considerably for large scenes, for example glTF scenes with many nodes referencing the same mesh.
+/// Primitives with animations and meshes that are used with different materials are not collapsed.
+///
+/// Use [OptimizedModel::location] or [OptimizedModel::locations_by_name] to find a specific primitive from the original [CpuModel]
+/// and [OptimizedModel::set_transformation] and [OptimizedModel::set_visible] to change it.
+///
+pub struct OptimizedModel<M: Material> {
+    model: Model<M>,
+    instanced_model: InstancedModel<M>,
+    instance_transformations: Vec<Vec<Mat4>>,
+    hidden_instances: Vec<Vec<bool>>,
+    hidden_parts: Vec<bool>,
+    locations: Vec<Option<PrimitiveLocation>>,
+    names: Vec<String>,
+    stats: InstancingStats,
+}
+
+impl<M: Material + FromCpuMaterial + Clone + Default> OptimizedModel<M> {
+    ///
+    /// Constructs an [OptimizedModel] from the given [CpuModel], see [OptimizedModel] for more information.
+    ///
+    pub fn new(context: &Context, cpu_model: &CpuModel) -> Result<Self, RendererError> {
+        let materials = cpu_model
+            .materials
+            .iter()
+            .map(|m| M::from_cpu_material(context, m))
+            .collect::<Vec<_>>();
+        let material = |index: usize| -> Result<M, RendererError> {
+            let primitive = &cpu_model.geometries[index];
+            if let Some(material_index) = primitive.material_index {
+                Ok(materials
+                    .get(material_index)
+                    .ok_or_else(|| {
+                        RendererError::MissingMaterial(
+                            material_index.to_string(),
+                            primitive.name.clone(),
+                        )
+                    })?
+                    .clone())
+            } else {
+                Ok(M::default())
+            }
+        };
+
+        let mut locations = vec![None; cpu_model.geometries.len()];
+        let mut parts = Vec::new();
+        let mut instanced_parts = Vec::new();
+        let mut instance_transformations = Vec::new();
+        for group in instancing_groups(cpu_model) {
+            if let CpuGeometry::Triangles(geometry) = &cpu_model.geometries[group[0]].geometry {
+                if group.len() == 1 {
+                    let primitive = &cpu_model.geometries[group[0]];
+                    let mut gm = Gm {
+                        geometry: Mesh::new(context, geometry),
+                        material: material(group[0])?,
+                    };
+                    gm.set_transformation(primitive.transformation);
+                    locations[group[0]] = Some(PrimitiveLocation::Model(parts.len()));
+                    parts.push(ModelPart::new(gm, primitive.animations.clone()));
+                } else {
+                    let transformations = group
+                        .iter()
+                        .map(|i| cpu_model.geometries[*i].transformation)
+                        .collect::<Vec<_>>();
+                    let gm = Gm {
+                        geometry: InstancedMesh::new(
+                            context,
+                            &Instances {
+                                transformations: transformations.clone(),
+                                ..Default::default()
+                            },
+                            geometry,
+                        ),
+                        material: material(group[0])?,
+                    };
+                    for (instance, i) in group.iter().enumerate() {
+                        locations[*i] = Some(PrimitiveLocation::Instance {
+                            part: instanced_parts.len(),
+                            instance,
+                        });
+                    }
+                    instanced_parts.push(InstancedModelPart::new(gm, Vec::new()));
+                    instance_transformations.push(transformations);
+                }
+            }
+        }
+
+        let mut model = Model::from_parts(parts);
+        if let Some(animation_name) = model.animations().first().cloned() {
+            model.choose_animation(animation_name.as_deref());
+        }
+        let stats = InstancingStats {
+            draw_calls_before: locations.iter().filter(|l| l.is_some()).count(),
+            draw_calls_after: model.len() + instanced_parts.len(),
+        };
+        Ok(Self {
+            hidden_parts: vec![false; model.len()],
+            hidden_instances: instance_transformations
+                .iter()
+                .map(|t| vec![false; t.len()])
+                .collect(),
+            model,
+            instanced_model: InstancedModel::from_parts(instanced_parts),
+            instance_transformations,
+            locations,
+            names: cpu_model
+                .geometries
+                .iter()
+                .map(|p| p.name.clone())
+                .collect(),
+            stats,
+        })
+    }
+}
+
+///
+/// Additional functionality for the raw assets loaded using [three_d_asset::io::load] or [three_d_asset::io::load_async].
+///
+pub trait RawAssetsExt {
+    ///
+    /// Deserializes the model at the given path and constructs an [OptimizedModel] from it, which collapses the primitives that share the same mesh and material into instanced draws.
+    /// The optimization is opt-in, deserialize a [CpuModel] and use [Model::new] to construct a model with a draw call per primitive instead.
+    /// Use [OptimizedModel::stats] to see the number of draw calls before and after the optimization.
+    ///
+    fn optimize_instancing<M: Material + FromCpuMaterial + Clone + Default>(
+        &mut self,
+        context: &Context,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<OptimizedModel<M>, RendererError>;
+}
+
+impl RawAssetsExt for three_d_asset::io::RawAssets {
+    fn optimize_instancing<M: Material + FromCpuMaterial + Clone + Default>(
+        &mut self,
+        context: &Context,
+        path: impl AsRef<std::path::Path>,
+    ) -> Result<OptimizedModel<M>, RendererError> {
+        let path = path.as_ref();
+        let cpu_model: CpuModel = self
+            .deserialize(path)
+            .map_err(|e| RendererError::ModelLoading(path.display().to_string(), e.to_string()))?;
+        OptimizedModel::new(context, &cpu_model)
+    }
+}
+
+impl<M: Material> OptimizedModel<M> {
+    ///
+    /// Returns the number of draw calls before and after the optimization.
+    ///
+    pub fn stats(&self) -> InstancingStats {
+        self.stats
+    }
+
+    ///
+    /// The primitives that are not collapsed into instances.
+    ///
+    pub fn model(&self) -> &Model<M> {
+        &self.model
+    }
+
+    ///
+    /// The primitives that are collapsed into instances.
+    ///
+    pub fn instanced_model(&self) -> &InstancedModel<M> {
+        &self.instanced_model
+    }
+
+    ///
+    /// Returns where the primitive with the given index in [CpuModel::geometries] ended up or `None` if it is not a triangle mesh.
+    ///
+    pub fn location(&self, primitive_index: usize) -> Option<PrimitiveLocation> {
+        self.locations.get(primitive_index).copied().flatten()
+    }
+
+    ///
+    /// Returns where all primitives with the given name ended up.
+    ///
+    pub fn locations_by_name(&self, name: &str) -> Vec<PrimitiveLocation> {
+        self.names
+            .iter()
+            .zip(self.locations.iter())
+            .filter(|(n, _)| n.as_str() == name)
+            .filter_map(|(_, l)| *l)
+            .collect()
+    }
+
+    ///
+    /// Sets the local to world transformation of the primitive at the given location.
+    ///
+    pub fn set_transformation(&mut self, location: PrimitiveLocation, transformation: Mat4) {
+        match location {
+            PrimitiveLocation::Model(part) => self.model[part].set_transformation(transformation),
+            PrimitiveLocation::Instance { part, instance } => {
+                self.instance_transformations[part][instance] = transformation;
+                self.update_instance(part, instance);
+            }
+        }
+    }
+
+    ///
+    /// Shows or hides the primitive at the given location.
+    /// A hidden instance is still part of the instanced draw call, but it is scaled to zero so nothing is visible.
+    ///
+    pub fn set_visible(&mut self, location: PrimitiveLocation, visible: bool) {
+        match location {
+            PrimitiveLocation::Model(part) => self.hidden_parts[part] = !visible,
+            PrimitiveLocation::Instance { part, instance } => {
+                self.hidden_instances[part][instance] = !visible;
+                self.update_instance(part, instance);
+            }
+        }
+    }
+
+    fn update_instance(&mut self, part: usize, instance: usize) {
+        let transformation = if self.hidden_instances[part][instance] {
+            Mat4::from_scale(0.0)
+        } else {
+            self.instance_transformations[part][instance]
+        };
+        self.instanced_model[part].update_instance_transformations(instance, &[transformation]);
+    }
+
+    ///
+    /// For updating the animation. The time parameter should be some continious time, for example the time since start.
+    ///
+    pub fn animate(&mut self, time: f32) {
+        self.model.iter_mut().for_each(|m| m.animate(time));
+    }
+}
+
+impl<'a, M: Material> IntoIterator for &'a OptimizedModel<M> {
+    type Item = &'a dyn Object;
+    type IntoIter = std::vec::IntoIter<&'a dyn Object>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.model
+            .iter()
+            .zip(self.hidden_parts.iter())
+            .filter(|(_, hidden)| !**hidden)
+            .map(|(m, _)| m as &dyn Object)
+            .chain(self.instanced_model.iter().map(|m| m as &dyn Object))
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+}
+
+///
+/// Groups the triangle primitives in the model that can be rendered as instances of the same [InstancedMesh].
+/// Each group contains the indices of the primitives in the group and the primitives that cannot be instanced are returned as groups of size one.
+///
+fn instancing_groups(cpu_model: &CpuModel) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut buckets: HashMap<u64, Vec<usize>> = HashMap::new();
+    let mut material_indices: HashMap<usize, Option<usize>> = HashMap::new();
+    let mut overridden = Vec::new();
+    for (index, primitive) in cpu_model.geometries.iter().enumerate() {
+        if let CpuGeometry::Triangles(mesh) = &primitive.geometry {
+            if !primitive.animations.is_empty() {
+                groups.push(vec![index]);
+                continue;
+            }
+            let bucket = buckets.entry(mesh_hash(mesh)).or_default();
+            let group_index = bucket.iter().copied().find(|g| {
+                if let CpuGeometry::Triangles(other) = &cpu_model.geometries[groups[*g][0]].geometry
+                {
+                    mesh_eq(mesh, other)
+                } else {
+                    false
+                }
+            });
+            if let Some(group_index) = group_index {
+                if material_indices[&group_index] != primitive.material_index {
+                    overridden.push(group_index);
+                }
+                groups[group_index].push(index);
+            } else {
+                bucket.push(groups.len());
+                material_indices.insert(groups.len(), primitive.material_index);
+                groups.push(vec![index]);
+            }
+        }
+    }
+
+    // Split the groups where the mesh is used with different materials
+    let mut result = Vec::new();
+    for (group_index, group) in groups.into_iter().enumerate() {
+        if overridden.contains(&group_index) {
+            result.extend(group.into_iter().map(|i| vec![i]));
+        } else {
+            result.push(group);
+        }
+    }
+    result
+}
+
+fn mesh_hash(mesh: &CpuMesh) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for p in mesh.positions.to_f32() {
+        p.x.to_bits().hash(&mut hasher);
+        p.y.to_bits().hash(&mut hasher);
+        p.z.to_bits().hash(&mut hasher);
+    }
+    indices_u32(&mesh.indices).hash(&mut hasher);
+    hasher.finish()
+}
+
+fn mesh_eq(a: &CpuMesh, b: &CpuMesh) -> bool {
+    a.positions.to_f32() == b.positions.to_f32()
+        && indices_u32(&a.indices) == indices_u32(&b.indices)
+        && a.normals == b.normals
+        && a.tangents == b.tangents
+        && a.uvs == b.uvs
+        && a.colors == b.colors
+}
+
+fn indices_u32(indices: &Indices) -> Option<Vec<u32>> {
+    match indices {
+        Indices::U8(ind) => Some(ind.iter().map(|i| *i as u32).collect()),
+        Indices::U16(ind) => Some(ind.iter().map(|i| *i as u32).collect()),
+        Indices::U32(ind) => Some(ind.clone()),
+        Indices::None => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use three_d_asset::Primitive;
+
+    fn cube_copies(count: usize, material_index: impl Fn(usize) -> Option<usize>) -> CpuModel {
+        CpuModel {
+            name: "cubes".to_owned(),
+            geometries: (0..count)
+                .map(|i| Primitive {
+                    name: format!("cube{}", i),
+                    transformation: Mat4::from_translation(vec3(i as f32, 0.0, 0.0)),
+                    animations: Vec::new(),
+                    geometry: CpuGeometry::Triangles(CpuMesh::cube()),
+                    material_index: material_index(i),
+                })
+                .collect(),
+            materials: vec![CpuMaterial::default(), CpuMaterial::default()],
+        }
+    }
+
+    #[test]
+    fn test_instancing_groups_collapses_copies() {
+        let groups = instancing_groups(&cube_copies(100, |_| Some(0)));
+        assert_eq!(groups, vec![(0..100).collect::<Vec<_>>()]);
+    }
+
+    #[test]
+    fn test_instancing_groups_skips_material_overrides() {
+        let groups = instancing_groups(&cube_copies(3, |i| if i == 1 { Some(1) } else { Some(0) }));
+        assert_eq!(groups, vec![vec![0], vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn test_instancing_groups_different_meshes() {
+        let mut cpu_model = cube_copies(2, |_| None);
+        cpu_model.geometries[1].geometry = CpuGeometry::Triangles(CpuMesh::sphere(4));
+        let groups = instancing_groups(&cpu_model);
+        assert_eq!(groups, vec![vec![0], vec![1]]);
+    }
+
+    ///
+    /// Creates a binary glTF file with a single cube mesh which is referenced by the given number of nodes.
+    ///
+    #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+    fn cube_copies_glb(count: usize) -> Vec<u8> {
+        let positions: [[f32; 3]; 8] = [
+            [-1.0, -1.0, -1.0],
+            [1.0, -1.0, -1.0],
+            [1.0, 1.0, -1.0],
+            [-1.0, 1.0, -1.0],
+            [-1.0, -1.0, 1.0],
+            [1.0, -1.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [-1.0, 1.0, 1.0],
+        ];
+        let indices: [u16; 36] = [
+            0, 2, 1, 0, 3, 2, 4, 5, 6, 4, 6, 7, 0, 1, 5, 0, 5, 4, 3, 7, 6, 3, 6, 2, 0, 4, 7, 0, 7,
+            3, 1, 2, 6, 1, 6, 5,
+        ];
+        let mut bin = positions
+            .iter()
+            .flatten()
+            .flat_map(|v| v.to_le_bytes())
+            .collect::<Vec<_>>();
+        bin.extend(indices.iter().flat_map(|i| i.to_le_bytes()));
+        let nodes = (0..count)
+            .map(|i| format!(r#"{{"mesh":0,"translation":[{},0,0]}}"#, 3 * i))
+            .collect::<Vec<_>>();
+        let mut json = format!(
+            r#"{{"asset":{{"version":"2.0"}},"scene":0,"scenes":[{{"nodes":[{}]}}],"nodes":[{}],"meshes":[{{"primitives":[{{"attributes":{{"POSITION":0}},"indices":1}}]}}],"accessors":[{{"bufferView":0,"componentType":5126,"count":8,"type":"VEC3","min":[-1,-1,-1],"max":[1,1,1]}},{{"bufferView":1,"componentType":5123,"count":36,"type":"SCALAR"}}],"bufferViews":[{{"buffer":0,"byteOffset":0,"byteLength":96,"target":34962}},{{"buffer":0,"byteOffset":96,"byteLength":72,"target":34963}}],"buffers":[{{"byteLength":{}}}]}}"#,
+            (0..count).map(|i| i.to_string()).collect::<Vec<_>>().join(","),
+            nodes.join(","),
+            bin.len()
+        )
+        .into_bytes();
+        // Both chunks must be aligned to 4 bytes
+        json.resize((json.len() + 3) / 4 * 4, b' ');
+        bin.resize((bin.len() + 3) / 4 * 4, 0);
+
+        let mut glb = Vec::new();
+        glb.extend(b"glTF");
+        glb.extend(2u32.to_le_bytes());
+        glb.extend(((12 + 8 + json.len() + 8 + bin.len()) as u32).to_le_bytes());
+        glb.extend((json.len() as u32).to_le_bytes());
+        glb.extend(b"JSON");
+        glb.extend(json);
+        glb.extend((bin.len() as u32).to_le_bytes());
+        glb.extend(b"BIN\0");
+        glb.extend(bin);
+        glb
+    }
+
+    #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_gltf_cube_copies_end_up_as_one_instanced_draw() {
+        let mut raw_assets = three_d_asset::io::RawAssets::new();
+        raw_assets.insert("cubes.glb", cube_copies_glb(100));
+
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut model = raw_assets
+            .optimize_instancing::<ColorMaterial>(&context, "cubes.glb")
+            .unwrap();
+        assert_eq!(
+            model.stats(),
+            InstancingStats {
+                draw_calls_before: 100,
+                draw_calls_after: 1,
+            }
+        );
+        assert!(model.model().is_empty());
+        assert_eq!(model.instanced_model().len(), 1);
+        assert_eq!(model.instanced_model()[0].instance_count(), 100);
+        assert_eq!(
+            model.location(42),
+            Some(PrimitiveLocation::Instance {
+                part: 0,
+                instance: 42
+            })
+        );
+
+        // Moving a single instance updates the instances in place
+        let location = model.location(42).unwrap();
+        model.set_transformation(location, Mat4::from_translation(vec3(1000.0, 0.0, 0.0)));
+        assert_eq!(model.instanced_model()[0].aabb().max().x, 1001.0);
+        model.set_visible(location, false);
+        assert_eq!(model.instanced_model()[0].aabb().max().x, 3.0 * 99.0 + 1.0);
+    }
+}
@@ -0,0 +1,67 @@
+uniform sampler2D colorTexture;
+uniform sampler2D normalDepthTexture;
+uniform vec3 cameraPosition;
+uniform int azimuthCount;
+uniform int elevationCount;
+uniform float viewSize;
+uniform float radius;
+
+in vec2 uvs;
+in vec3 pos;
+in vec3 sprite_center;
+
+layout (location = 0) out vec4 outColor;
+
+vec4 color_sum = vec4(0.0);
+vec4 normal_depth_sum = vec4(0.0);
+
+void accumulate_view(int azimuth_index, int elevation_index, float weight)
+{
+    // Stay half a pixel away from the edges of the view to avoid bleeding from the neighbouring views
+    vec2 view_uv = clamp(uvs, vec2(0.5 / viewSize), vec2(1.0 - 0.5 / viewSize));
+    vec2 uv = (vec2(float(azimuth_index), float(elevation_index)) + view_uv) / vec2(float(azimuthCount), float(elevationCount));
+    vec4 color = texture(colorTexture, uv);
+    float w = weight * color.a;
+    color_sum += vec4(w * color.rgb, w);
+    normal_depth_sum += w * texture(normalDepthTexture, uv);
+}
+
+void main()
+{
+    vec3 dir = normalize(cameraPosition - sprite_center);
+
+    float azimuth = atan(dir.z, dir.x);
+    if (azimuth < 0.0) {
+        azimuth += 2.0 * PI;
+    }
+    azimuth *= float(azimuthCount) / (2.0 * PI);
+    float elevation = clamp(asin(clamp(dir.y, -1.0, 1.0)), 0.0, 0.5 * PI) * float(elevationCount) / (0.5 * PI);
+    elevation = min(elevation, float(elevationCount - 1));
+
+    int azimuth0 = int(floor(azimuth)) % azimuthCount;
+    int azimuth1 = (azimuth0 + 1) % azimuthCount;
+    int elevation0 = int(floor(elevation));
+    int elevation1 = min(elevation0 + 1, elevationCount - 1);
+    vec2 f = vec2(fract(azimuth), elevation - floor(elevation));
+
+    accumulate_view(azimuth0, elevation0, (1.0 - f.x) * (1.0 - f.y));
+    accumulate_view(azimuth1, elevation0, f.x * (1.0 - f.y));
+    accumulate_view(azimuth0, elevation1, (1.0 - f.x) * f.y);
+    accumulate_view(azimuth1, elevation1, f.x * f.y);
+
+    if (color_sum.a < 0.5) {
+        discard;
+    }
+    vec3 surface_color = inverse_reinhard_tone_mapping(rgb_from_srgb(color_sum.rgb / color_sum.a));
+    vec4 normal_depth = normal_depth_sum / color_sum.a;
+    vec3 normal = normalize(2.0 * normal_depth.xyz - 1.0);
+
+    // The depth is baked along the view direction, which is horizontal for cylindrical imposters
+    vec3 depth_direction = elevationCount == 1 ? normalize(vec3(dir.x, 0.0, dir.z)) : dir;
+    vec3 position = pos + depth_direction * radius * (2.0 * normal_depth.w - 1.0);
+
+    outColor.rgb = calculate_lighting(cameraPosition, surface_color, position, normal, 0.0, 1.0, 1.0);
+    outColor.rgb = reinhard_tone_mapping(outColor.rgb);
+    outColor.rgb = srgb_from_rgb(outColor.rgb);
+    outColor.a = 1.0;
+}
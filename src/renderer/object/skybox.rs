@@ -106,7 +106,11 @@ impl Skybox {
         Skybox {
             context: context.clone(),
             vertex_buffer,
-            material: SkyboxMaterial { texture },
+            material: SkyboxMaterial {
+                texture,
+                rotation: radians(0.0),
+                intensity: 1.0,
+            },
         }
     }
 
@@ -116,6 +120,54 @@ impl Skybox {
     pub fn texture(&self) -> &Arc<TextureCubeMap> {
         &self.material.texture
     }
+
+    ///
+    /// Rotates the skybox the given angle around the up direction (the y-axis).
+    /// The rotation is applied when sampling the texture, so it is cheap to change each frame.
+    /// Use [Skybox::set_rotation_and_intensity] to also rotate the [Environment] and keep the image based lighting aligned with the skybox.
+    ///
+    pub fn set_rotation(&mut self, rotation: impl Into<Radians>) {
+        self.material.rotation = rotation.into();
+    }
+
+    ///
+    /// Returns the rotation of the skybox around the up direction (the y-axis).
+    ///
+    pub fn rotation(&self) -> Radians {
+        self.material.rotation
+    }
+
+    ///
+    /// Sets the intensity which the colors of the skybox are multiplied with, for example to dim the skybox. The default is `1.0`.
+    ///
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.material.intensity = intensity;
+    }
+
+    ///
+    /// Returns the intensity which the colors of the skybox are multiplied with.
+    ///
+    pub fn intensity(&self) -> f32 {
+        self.material.intensity
+    }
+
+    ///
+    /// Rotates the skybox and the given [Environment] the given angle around the up direction (the y-axis) and multiplies both with the given intensity,
+    /// so the image based lighting stays aligned with and as bright as the skybox.
+    /// The environment is usually the [AmbientLight::environment] calculated from the same environment map as the skybox.
+    ///
+    pub fn set_rotation_and_intensity(
+        &mut self,
+        environment: &mut Environment,
+        rotation: impl Into<Radians>,
+        intensity: f32,
+    ) {
+        let rotation = rotation.into();
+        self.set_rotation(rotation);
+        self.set_intensity(intensity);
+        environment.set_rotation(rotation);
+        environment.set_intensity(intensity);
+    }
 }
 
 impl<'a> IntoIterator for &'a Skybox {
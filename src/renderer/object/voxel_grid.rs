@@ -85,4 +85,8 @@ impl<M: Material> Object for VoxelGrid<M> {
     fn material_type(&self) -> MaterialType {
         self.0.material_type()
     }
+
+    fn material_id(&self) -> usize {
+        self.0.material_id()
+    }
 }
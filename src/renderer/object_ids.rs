@@ -0,0 +1,432 @@
+//!
+//! Functionality for finding the object in each pixel, either without rendering the scene again (see [ObjectIds])
+//! or by rendering the ids in a separate pass (see [PickingRenderer]).
+//!
+
+use crate::core::*;
+use crate::renderer::*;
+
+///
+/// The id of an object (see [Object::object_id]) and the index of the instance of the object in a pixel, as decoded by [ObjectIds::decode].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ObjectId {
+    /// The id of the object, see [Object::object_id].
+    pub object: u32,
+    /// The index of the instance, for example of an [InstancedMesh], or zero if the object is not instanced or no bits are used for the instance index.
+    pub instance: u32,
+}
+
+///
+/// A texture which contains the id of the object (see [Object::object_id]) rendered in each pixel.
+/// The ids are written in the same render pass as the color by rendering into the render target returned by [ObjectIds::render_target],
+/// which makes it cheap to find the object under the cursor every frame, for example for highlighting it, compared to a separate pick pass (see [pick]).
+/// Since the screen does not have an object id texture, the scene must be rendered into a color texture which is then copied to the screen.
+///
+/// Deferred objects (see [MaterialType::Deferred]) are not written to the object id texture.
+///
+pub struct ObjectIds {
+    context: Context,
+    texture: Texture2D,
+    instance_bits: u32,
+    readback: Option<GpuReadback<u32>>,
+    hovered: Option<ObjectId>,
+}
+
+impl ObjectIds {
+    ///
+    /// Constructs a new object id texture with the given size, which should be the same size as the color texture it is rendered together with.
+    /// The highest `instance_bits` bits of each id contains the index of the instance and the remaining bits the object id,
+    /// so for example `8` allows for 256 instances per object and object ids up to 2^24 - 1. Use `0` if there is no need to tell instances apart.
+    ///
+    /// # Panic
+    /// Will panic if `instance_bits` is larger than 31.
+    ///
+    pub fn new(context: &Context, width: u32, height: u32, instance_bits: u32) -> Self {
+        if instance_bits > 31 {
+            panic!(
+                "cannot use more than 31 bits for the instance index (got {})",
+                instance_bits
+            );
+        }
+        Self {
+            context: context.clone(),
+            texture: new_object_id_texture(context, width, height),
+            instance_bits,
+            readback: None,
+            hovered: None,
+        }
+    }
+
+    ///
+    /// Resizes the object id texture if the size is different from the current size, for example when the window is resized.
+    ///
+    pub fn set_size(&mut self, width: u32, height: u32) {
+        if self.texture.width() != width || self.texture.height() != height {
+            self.texture = new_object_id_texture(&self.context, width, height);
+            self.readback = None;
+        }
+    }
+
+    ///
+    /// Returns the texture containing the object ids.
+    ///
+    pub fn texture(&self) -> &Texture2D {
+        &self.texture
+    }
+
+    ///
+    /// Returns the number of bits used for the instance index, see [ObjectIds::new].
+    ///
+    pub fn instance_bits(&self) -> u32 {
+        self.instance_bits
+    }
+
+    ///
+    /// Returns a render target which renders into the given color and depth targets and writes the object ids into this texture,
+    /// see [RenderTarget::new_with_object_ids].
+    ///
+    pub fn render_target<'a>(
+        &'a self,
+        color: ColorTarget<'a>,
+        depth: DepthTarget<'a>,
+    ) -> RenderTarget<'a> {
+        RenderTarget::new_with_object_ids(color, &self.texture, self.instance_bits, depth)
+    }
+
+    ///
+    /// Decodes a value read from the object id texture into the object id and instance index.
+    /// Returns `None` if the value is zero, ie. no object was rendered in that pixel.
+    ///
+    pub fn decode(&self, value: u32) -> Option<ObjectId> {
+        if value == 0 {
+            return None;
+        }
+        if self.instance_bits == 0 {
+            return Some(ObjectId {
+                object: value,
+                instance: 0,
+            });
+        }
+        let object_bits = 32 - self.instance_bits;
+        Some(ObjectId {
+            object: value & ((1 << object_bits) - 1),
+            instance: value >> object_bits,
+        })
+    }
+
+    ///
+    /// Returns the object in the given pixel, for example the cursor position, without waiting for the GPU.
+    /// Call this each frame after rendering, then the returned object is the object in the pixel given a frame or two earlier,
+    /// since the read of the pixel is started by this call and only finished when the GPU has finished rendering.
+    /// The pixel coordinate must be in physical pixels, where (0, 0) indicate the bottom left corner of the texture.
+    ///
+    pub fn hovered_id(&mut self, pixel: impl Into<PhysicalPoint>) -> Option<ObjectId> {
+        if let Some(pixels) = self.readback.as_ref().and_then(|r| r.pixels()) {
+            self.hovered = self.decode(pixels[0]);
+            self.readback = None;
+        }
+        if self.readback.is_none() {
+            let pixel = pixel.into();
+            let (x, y) = (pixel.x.floor(), pixel.y.floor());
+            if x < 0.0
+                || y < 0.0
+                || x >= self.texture.width() as f32
+                || y >= self.texture.height() as f32
+            {
+                self.hovered = None;
+            } else {
+                self.readback = Some(self.texture.as_color_target(None).read_partially_async(
+                    ScissorBox {
+                        x: x as i32,
+                        y: y as i32,
+                        width: 1,
+                        height: 1,
+                    },
+                ));
+            }
+        }
+        self.hovered
+    }
+}
+
+fn new_object_id_texture(context: &Context, width: u32, height: u32) -> Texture2D {
+    Texture2D::new_empty::<u32>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+///
+/// Finds the object in a pixel by rendering each object with a unique id into an offscreen texture and then reading the id in the pixel.
+/// Compared to [pick], which returns the position of the closest intersection, this returns which object was hit,
+/// and compared to [ObjectIds] the ids are rendered in a separate pass, but the result is available immediately.
+///
+pub struct PickingRenderer {
+    context: Context,
+    texture: Option<Texture2D>,
+    depth_texture: Option<DepthTexture2D>,
+    viewport: Viewport,
+}
+
+impl PickingRenderer {
+    ///
+    /// Constructs a new picking renderer. Nothing can be picked until [PickingRenderer::render] has been called.
+    ///
+    pub fn new(context: &Context) -> Self {
+        Self {
+            context: context.clone(),
+            texture: None,
+            depth_texture: None,
+            viewport: Viewport::new_at_origo(1, 1),
+        }
+    }
+
+    ///
+    /// Renders the given geometries as seen from the given camera into the given viewport of the offscreen texture,
+    /// where the id of each geometry is written to an unsigned integer texture like the one used by [ObjectIds].
+    /// The viewport is typically the same as the viewport of the camera.
+    ///
+    /// # Panic
+    /// Will panic if any of the ids is zero, since zero indicates that no object was rendered in a pixel.
+    ///
+    pub fn render<I: Into<u32> + Copy>(
+        &mut self,
+        objects: &[(I, &dyn Geometry)],
+        camera: &Camera,
+        viewport: Viewport,
+    ) {
+        if self.viewport.width != viewport.width
+            || self.viewport.height != viewport.height
+            || self.texture.is_none()
+        {
+            self.texture = Some(new_object_id_texture(
+                &self.context,
+                viewport.width,
+                viewport.height,
+            ));
+            self.depth_texture = Some(DepthTexture2D::new::<f32>(
+                &self.context,
+                viewport.width,
+                viewport.height,
+                Wrapping::ClampToEdge,
+                Wrapping::ClampToEdge,
+            ));
+        }
+        self.viewport = viewport;
+
+        // The texture only covers the viewport, so render with a camera which has the viewport at the origin
+        let mut camera = camera.clone();
+        camera.set_viewport(Viewport::new_at_origo(viewport.width, viewport.height));
+        RenderTarget::new(
+            self.texture.as_mut().unwrap().as_color_target(None),
+            self.depth_texture.as_mut().unwrap().as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+        .write(|| {
+            for (id, geometry) in objects {
+                let id = (*id).into();
+                if id == 0 {
+                    panic!("the id zero is reserved for pixels where no object is rendered");
+                }
+                geometry.render_with_material(&IdMaterial { id }, &camera, &[]);
+            }
+        });
+    }
+
+    ///
+    /// Returns the id of the object rendered in the given pixel in the last call to [PickingRenderer::render] or `None` if no object was rendered in that pixel.
+    /// The pixel coordinate must be in physical pixels, where (viewport.x, viewport.y) indicate the bottom left corner of the viewport given to [PickingRenderer::render].
+    ///
+    pub fn pick(&mut self, x: u32, y: u32) -> Option<u32> {
+        let texture = self.texture.as_mut()?;
+        let x = x as i32 - self.viewport.x;
+        let y = y as i32 - self.viewport.y;
+        if x < 0 || y < 0 || x >= texture.width() as i32 || y >= texture.height() as i32 {
+            return None;
+        }
+        let id = texture
+            .as_color_target(None)
+            .read_partially::<u32>(ScissorBox {
+                x,
+                y,
+                width: 1,
+                height: 1,
+            })[0];
+        if id == 0 {
+            None
+        } else {
+            Some(id)
+        }
+    }
+}
+
+///
+/// Writes the id to an unsigned integer texture.
+///
+struct IdMaterial {
+    id: u32,
+}
+
+impl Material for IdMaterial {
+    fn fragment_shader(&self, _lights: &[&dyn Light]) -> FragmentShader {
+        FragmentShader {
+            source: "
+                uniform uint pickingId;
+                layout (location = 0) out uint outId;
+                void main()
+                {
+                    outId = pickingId;
+                }
+            "
+            .to_owned(),
+            attributes: FragmentAttributes::NONE,
+        }
+    }
+
+    fn use_uniforms(&self, program: &Program, _camera: &Camera, _lights: &[&dyn Light]) {
+        program.use_uniform("pickingId", self.id);
+    }
+
+    fn render_states(&self) -> RenderStates {
+        RenderStates::default()
+    }
+
+    fn material_type(&self) -> MaterialType {
+        MaterialType::Opaque
+    }
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_object_and_instance_ids() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let size = 16;
+        // Two squares side by side, the left is a mesh with a user defined id and the right is two instances on top of each other
+        let mut left = GmWithOptions::new(Gm::new(
+            Mesh::new(&context, &CpuMesh::square()),
+            ColorMaterial::default(),
+        ))
+        .with_object_id(7);
+        left.set_transformation(Mat4::from_translation(vec3(-1.0, 0.0, 0.0)));
+        let right = Gm::new(
+            InstancedMesh::new(
+                &context,
+                &Instances {
+                    transformations: vec![
+                        Mat4::from_translation(vec3(1.0, 1.0, 0.0)) * Mat4::from_scale(0.5),
+                        Mat4::from_translation(vec3(1.0, -1.0, 0.0)) * Mat4::from_scale(0.5),
+                    ],
+                    ..Default::default()
+                },
+                &CpuMesh::square(),
+            ),
+            ColorMaterial::default(),
+        );
+        let camera = Camera::new_orthographic(
+            Viewport::new_at_origo(size, size),
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            4.0,
+            0.1,
+            10.0,
+        );
+
+        let mut color = Texture2D::new_empty::<[u8; 4]>(
+            &context,
+            size,
+            size,
+            Interpolation::Nearest,
+            Interpolation::Nearest,
+            None,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut depth = DepthTexture2D::new::<f32>(
+            &context,
+            size,
+            size,
+            Wrapping::ClampToEdge,
+            Wrapping::ClampToEdge,
+        );
+        let mut object_ids = ObjectIds::new(&context, size, size, 8);
+        object_ids
+            .render_target(color.as_color_target(None), depth.as_depth_target())
+            .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 1.0, 1.0))
+            .render(&camera, left.into_iter().chain(&right), &[]);
+
+        // The rows are read from the top
+        let ids = object_ids.texture.as_color_target(None).read::<u32>();
+        let id_at = |x: u32, y: u32| object_ids.decode(ids[(y * size + x) as usize]);
+        assert_eq!(
+            id_at(4, 8),
+            Some(ObjectId {
+                object: 7,
+                instance: 0
+            })
+        );
+        assert_eq!(
+            id_at(12, 4),
+            Some(ObjectId {
+                object: 2,
+                instance: 0
+            })
+        );
+        assert_eq!(
+            id_at(12, 12),
+            Some(ObjectId {
+                object: 2,
+                instance: 1
+            })
+        );
+        assert_eq!(id_at(0, 0), None);
+    }
+
+    #[test]
+    fn test_picking_renderer() {
+        let context = crate::HeadlessContext::new().unwrap();
+        let mut left = Mesh::new(&context, &CpuMesh::square());
+        left.set_transformation(Mat4::from_translation(vec3(-1.0, 0.0, 0.0)));
+        let mut right = Mesh::new(&context, &CpuMesh::square());
+        right.set_transformation(Mat4::from_translation(vec3(1.0, 0.0, 0.0)));
+        let viewport = Viewport {
+            x: 10,
+            y: 20,
+            width: 16,
+            height: 8,
+        };
+        let camera = Camera::new_orthographic(
+            viewport,
+            vec3(0.0, 0.0, 5.0),
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 1.0, 0.0),
+            2.0,
+            0.1,
+            10.0,
+        );
+
+        let mut picking = PickingRenderer::new(&context);
+        assert_eq!(picking.pick(14, 24), None);
+        picking.render(
+            &[
+                (3u32, &left as &dyn Geometry),
+                (u32::MAX, &right as &dyn Geometry),
+            ],
+            &camera,
+            viewport,
+        );
+        assert_eq!(picking.pick(14, 24), Some(3));
+        assert_eq!(picking.pick(22, 24), Some(u32::MAX));
+        assert_eq!(picking.pick(5, 24), None);
+    }
+}
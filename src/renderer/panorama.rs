@@ -0,0 +1,248 @@
+//!
+//! Functionality for capturing a 360 degrees panorama of a scene.
+//!
+
+use crate::renderer::*;
+
+///
+/// Options for [capture_panorama].
+///
+#[derive(Clone, Copy)]
+pub struct PanoramaOptions<'a> {
+    /// A skybox which is rendered behind the objects or `None` to leave the background transparent black.
+    pub skybox: Option<&'a Skybox>,
+    /// Whether to return the panorama as 16 bit floating point data ([TextureData::RgbaF16]) instead of 8 bit data ([TextureData::RgbaU8]).
+    pub hdr: bool,
+    /// The colors are multiplied by the exposure before they are clamped to the range `[0..1]` and converted to 8 bit data. Not used if `hdr` is true.
+    pub exposure: f32,
+    /// The distance to the near plane of the cameras used for capturing the scene.
+    pub z_near: f32,
+    /// The distance to the far plane of the cameras used for capturing the scene.
+    pub z_far: f32,
+}
+
+impl Default for PanoramaOptions<'_> {
+    fn default() -> Self {
+        Self {
+            skybox: None,
+            hdr: false,
+            exposure: 1.0,
+            z_near: 0.01,
+            z_far: 1000.0,
+        }
+    }
+}
+
+///
+/// Captures a 360 degrees panorama of the objects as seen from the given position and returns it as an equirectangular image with the given width and half the height,
+/// for example for sharing or for baking an environment map, which can be loaded again using [TextureCubeMap::new_from_equirectangular].
+///
+/// The scene is first rendered into the six sides of a cube map, each side with a quarter of the width of the panorama, which is then converted to the equirectangular projection.
+/// The first row of the image is straight up and the first and last columns are both in the negative x direction, so the image wraps around horizontally.
+///
+/// # Panic
+/// Will panic if the width is less than two.
+///
+pub fn capture_panorama(
+    context: &Context,
+    position: Vec3,
+    width: u32,
+    objects: impl IntoIterator<Item = impl Object> + Clone,
+    lights: &[&dyn Light],
+    options: PanoramaOptions,
+) -> CpuTexture {
+    if width < 2 {
+        panic!(
+            "the width of a panorama must be at least two pixels, got {}",
+            width
+        );
+    }
+    let height = width / 2;
+    let side_size = (width / 4).max(1);
+    let mut cube_map = TextureCubeMap::new_empty::<[f16; 4]>(
+        context,
+        side_size,
+        side_size,
+        Interpolation::Linear,
+        Interpolation::Linear,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    let mut depth_texture = DepthTexture2D::new::<f32>(
+        context,
+        side_size,
+        side_size,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    );
+    for side in CubeMapSide::iter() {
+        let camera = Camera::new_perspective(
+            Viewport::new_at_origo(side_size, side_size),
+            position,
+            position + side.direction(),
+            side.up(),
+            degrees(90.0),
+            options.z_near,
+            options.z_far,
+        );
+        RenderTarget::new(
+            cube_map.as_color_target(&[side], None),
+            depth_texture.as_depth_target(),
+        )
+        .clear(ClearState::color_and_depth(0.0, 0.0, 0.0, 0.0, 1.0))
+        .render(&camera, options.skybox, &[])
+        .render(&camera, objects.clone(), lights);
+    }
+
+    // The direction is computed per pixel and the cube map is sampled without mip maps,
+    // so there is no seam at the column where the image wraps around or distortion at the poles
+    let fragment_shader_source = format!(
+        "
+        uniform samplerCube cubeMap;
+        uniform float exposure;
+        in vec2 uvs;
+        layout (location = 0) out vec4 outColor;
+
+        void main()
+        {{
+            float phi = (uvs.x - 0.5) * 6.2831853;
+            float theta = (uvs.y - 0.5) * 3.1415927;
+            vec3 direction = vec3(cos(theta) * cos(phi), sin(theta), cos(theta) * sin(phi));
+            vec4 color = textureLod(cubeMap, direction, 0.0);
+            {}
+        }}",
+        if options.hdr {
+            "outColor = color;"
+        } else {
+            "outColor = vec4(clamp(exposure * color.rgb, 0.0, 1.0), clamp(color.a, 0.0, 1.0));"
+        }
+    );
+    let render = |target: &ColorTarget| {
+        target.clear(ClearState::default()).write(|| {
+            apply_effect(
+                context,
+                &fragment_shader_source,
+                RenderStates {
+                    depth_test: DepthTest::Always,
+                    write_mask: WriteMask::COLOR,
+                    ..Default::default()
+                },
+                Viewport::new_at_origo(width, height),
+                |program| {
+                    program.use_texture_cube("cubeMap", &cube_map);
+                    program.use_uniform_if_required("exposure", options.exposure);
+                },
+            )
+        });
+    };
+    let data = if options.hdr {
+        let mut texture = new_panorama_texture::<[f16; 4]>(context, width, height);
+        let target = texture.as_color_target(None);
+        render(&target);
+        TextureData::RgbaF16(target.read())
+    } else {
+        let mut texture = new_panorama_texture::<[u8; 4]>(context, width, height);
+        let target = texture.as_color_target(None);
+        render(&target);
+        TextureData::RgbaU8(target.read())
+    };
+    CpuTexture {
+        name: "panorama".to_owned(),
+        data,
+        width,
+        height,
+        ..Default::default()
+    }
+}
+
+fn new_panorama_texture<T: TextureDataType>(
+    context: &Context,
+    width: u32,
+    height: u32,
+) -> Texture2D {
+    Texture2D::new_empty::<T>(
+        context,
+        width,
+        height,
+        Interpolation::Nearest,
+        Interpolation::Nearest,
+        None,
+        Wrapping::ClampToEdge,
+        Wrapping::ClampToEdge,
+    )
+}
+
+#[cfg(all(test, feature = "headless", not(target_arch = "wasm32")))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_panorama_continuity() {
+        let context = crate::HeadlessContext::new().unwrap();
+        // A colored marker in each of the six directions
+        let objects = [
+            (vec3(-1.0, 0.0, 0.0), Color::RED),
+            (vec3(1.0, 0.0, 0.0), Color::GREEN),
+            (vec3(0.0, 1.0, 0.0), Color::BLUE),
+            (vec3(0.0, -1.0, 0.0), Color::WHITE),
+            (vec3(0.0, 0.0, 1.0), Color::new_opaque(255, 255, 0)),
+            (vec3(0.0, 0.0, -1.0), Color::new_opaque(0, 255, 255)),
+        ]
+        .map(|(direction, color)| {
+            let mut marker = Gm::new(
+                Mesh::new(&context, &CpuMesh::sphere(16)),
+                ColorMaterial {
+                    color,
+                    ..Default::default()
+                },
+            );
+            marker.set_transformation(
+                Mat4::from_translation(5.0 * direction) * Mat4::from_scale(2.0),
+            );
+            marker
+        });
+        let panorama = capture_panorama(
+            &context,
+            vec3(0.0, 0.0, 0.0),
+            256,
+            &objects,
+            &[],
+            PanoramaOptions::default(),
+        );
+        assert_eq!((panorama.width, panorama.height), (256, 128));
+        let pixels = match panorama.data {
+            TextureData::RgbaU8(pixels) => pixels,
+            _ => unreachable!(),
+        };
+        let pixel = |x: u32, y: u32| pixels[(y * panorama.width + x) as usize];
+        let difference = |a: [u8; 4], b: [u8; 4]| {
+            (0..4)
+                .map(|i| (a[i] as i32 - b[i] as i32).abs())
+                .max()
+                .unwrap()
+        };
+
+        // The first and last column are neighbours when the panorama wraps around
+        for y in 0..panorama.height {
+            assert!(difference(pixel(0, y), pixel(panorama.width - 1, y)) < 32);
+        }
+        // All pixels in the first and last row are close to the same direction, straight up or straight down
+        for x in 0..panorama.width {
+            assert!(difference(pixel(x, 0), pixel(0, 0)) < 32);
+            assert!(difference(pixel(x, panorama.height - 1), pixel(0, panorama.height - 1)) < 32);
+        }
+        // The markers are in the expected directions
+        let equator = panorama.height / 2;
+        assert_eq!(pixel(0, equator), [255, 0, 0, 255]);
+        assert_eq!(pixel(panorama.width / 2, equator), [0, 255, 0, 255]);
+        assert_eq!(pixel(panorama.width / 2, 0), [0, 0, 255, 255]);
+        assert_eq!(
+            pixel(panorama.width / 2, panorama.height - 1),
+            [255, 255, 255, 255]
+        );
+        assert_eq!(pixel(3 * panorama.width / 4, equator), [255, 255, 0, 255]);
+        assert_eq!(pixel(panorama.width / 4, equator), [0, 255, 255, 255]);
+    }
+}
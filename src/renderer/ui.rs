@@ -0,0 +1,261 @@
+//!
+//! A minimal set of user interface widgets, for example a [Button] and a [TextField], rendered with the 2D geometries for applications which cannot use the egui based GUI.
+//! The widgets are positioned in the coordinate system of the [camera2d] camera, either manually or anchored to the viewport using a [HudElement] or a [HudStack],
+//! and the events are routed to the widgets by a [UiLayer].
+//!
+
+mod text_edit;
+#[doc(inline)]
+pub use text_edit::*;
+
+mod button;
+#[doc(inline)]
+pub use button::*;
+
+mod text_field;
+#[doc(inline)]
+pub use text_field::*;
+
+mod layout;
+#[doc(inline)]
+pub use layout::*;
+
+use crate::renderer::*;
+
+///
+/// Measures and renders text for the widgets, since the widgets do not contain any font rendering.
+/// An implementation could for example render each character using a [Rectangle] and a [ColorMaterial] with a glyph texture.
+///
+pub trait UiFont {
+    ///
+    /// Returns the width in physical pixels of the given text when rendered.
+    ///
+    fn text_width(&self, text: &str) -> f32;
+
+    ///
+    /// Renders the given text in a single line into the currently bound render target using the given [camera2d] camera,
+    /// where `position` is the left end of the vertical center of the line.
+    ///
+    fn render_text(&self, text: &str, position: PhysicalPoint, color: Color, camera: &Camera);
+}
+
+///
+/// The input to [Widget::handle_event] in addition to the event.
+///
+pub struct WidgetInput<'a> {
+    /// Whether the event has a position and this widget is the topmost widget at that position.
+    pub hit: bool,
+    /// The position of the event, if any, in the coordinate system of the [camera2d] camera, ie. in physical pixels relative to the bottom left corner of the viewport.
+    pub position: Option<PhysicalPoint>,
+    /// Whether this widget has the keyboard focus.
+    pub focused: bool,
+    /// The font used for measuring text.
+    pub font: &'a dyn UiFont,
+    /// The current time, see [UiLayer::handle_events].
+    pub time: f64,
+}
+
+///
+/// A user interface widget which can be used in a [UiLayer].
+///
+pub trait Widget {
+    ///
+    /// Returns whether the given position, in the coordinate system of the [camera2d] camera, is inside this widget.
+    ///
+    fn contains(&self, position: PhysicalPoint) -> bool;
+
+    ///
+    /// Returns whether this widget can receive the keyboard focus, which happens when it is clicked.
+    ///
+    fn is_focusable(&self) -> bool {
+        false
+    }
+
+    ///
+    /// Called when this widget receives or loses the keyboard focus.
+    ///
+    fn set_focused(&mut self, _focused: bool, _time: f64) {}
+
+    ///
+    /// Handles the given event. All widgets receive the mouse events while only the focused widget receive the keyboard and text events.
+    /// Set the `handled` flag of the event if the event is consumed by this widget.
+    ///
+    fn handle_event(&mut self, event: &mut Event, input: &WidgetInput);
+
+    ///
+    /// Renders this widget using the given [camera2d] camera into the currently bound render target.
+    ///
+    fn render(&mut self, camera: &Camera, font: &dyn UiFont, focused: bool, time: f64);
+}
+
+///
+/// Routes events to a list of widgets and renders them in order, so the last widget is on top.
+/// The widgets are owned by the application and given to both [UiLayer::handle_events] and [UiLayer::render],
+/// which must be the same widgets in the same order every frame since the focus is stored as an index into the list.
+///
+pub struct UiLayer {
+    font: Box<dyn UiFont>,
+    focused: Option<usize>,
+    viewport: Viewport,
+    time: f64,
+}
+
+impl UiLayer {
+    ///
+    /// Constructs a new layer which uses the given font for measuring and rendering text.
+    ///
+    pub fn new(font: impl UiFont + 'static) -> Self {
+        Self {
+            font: Box::new(font),
+            focused: None,
+            viewport: Viewport::new_at_origo(1, 1),
+            time: 0.0,
+        }
+    }
+
+    ///
+    /// Returns the index of the widget which has the keyboard focus, if any.
+    ///
+    pub fn focused(&self) -> Option<usize> {
+        self.focused
+    }
+
+    ///
+    /// Removes the keyboard focus from the focused widget, if any.
+    ///
+    pub fn clear_focus(&mut self, widgets: &mut [&mut dyn Widget]) {
+        self.set_focus(None, widgets);
+    }
+
+    ///
+    /// Sends the events to the widgets. Mouse events are hit tested against the widgets in the given viewport, where the last widget is the topmost,
+    /// and clicking a focusable widget gives it the keyboard focus. Keyboard and text events are only sent to the focused widget.
+    /// Events which are already handled, for example by a GUI on top of this layer, do not hit any widget,
+    /// and events consumed by the widgets are marked as handled, so they are ignored by for example camera controls that handle the events afterwards.
+    /// The time is used for animations, for example the blinking caret, and must be in milliseconds, for example [FrameInput::accumulated_time](crate::FrameInput::accumulated_time).
+    ///
+    pub fn handle_events(
+        &mut self,
+        events: &mut [Event],
+        viewport: Viewport,
+        time: f64,
+        widgets: &mut [&mut dyn Widget],
+    ) {
+        self.viewport = viewport;
+        self.time = time;
+        if self.focused.map(|i| i >= widgets.len()).unwrap_or(false) {
+            self.focused = None;
+        }
+        for event in events.iter_mut() {
+            let (position, handled) = match event {
+                Event::MousePress {
+                    position, handled, ..
+                }
+                | Event::MouseRelease {
+                    position, handled, ..
+                }
+                | Event::MouseMotion {
+                    position, handled, ..
+                } => {
+                    let position = PhysicalPoint::from(*position);
+                    (
+                        Some(PhysicalPoint {
+                            x: position.x - viewport.x as f32,
+                            y: position.y - viewport.y as f32,
+                        }),
+                        *handled,
+                    )
+                }
+                Event::MouseLeave => (None, false),
+                Event::KeyPress { .. } | Event::KeyRelease { .. } | Event::Text(_) => {
+                    if let Some(i) = self.focused {
+                        widgets[i].handle_event(
+                            event,
+                            &WidgetInput {
+                                hit: false,
+                                position: None,
+                                focused: true,
+                                font: self.font.as_ref(),
+                                time,
+                            },
+                        );
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            let hit = position
+                .filter(|_| !handled)
+                .and_then(|position| widgets.iter().rposition(|w| w.contains(position)));
+            if matches!(event, Event::MousePress { handled: false, .. }) {
+                let focus = hit.filter(|i| widgets[*i].is_focusable());
+                self.set_focus(focus, widgets);
+            }
+            for (i, widget) in widgets.iter_mut().enumerate() {
+                widget.handle_event(
+                    event,
+                    &WidgetInput {
+                        hit: hit == Some(i),
+                        position,
+                        focused: self.focused == Some(i),
+                        font: self.font.as_ref(),
+                        time,
+                    },
+                );
+            }
+        }
+    }
+
+    ///
+    /// Renders the widgets in order into the currently bound render target, so it must be called in the callback of [RenderTarget::write],
+    /// using the viewport given to the last call to [UiLayer::handle_events].
+    ///
+    pub fn render(&self, widgets: &mut [&mut dyn Widget]) {
+        let camera = camera2d(self.viewport);
+        for (i, widget) in widgets.iter_mut().enumerate() {
+            widget.render(
+                &camera,
+                self.font.as_ref(),
+                self.focused == Some(i),
+                self.time,
+            );
+        }
+    }
+
+    fn set_focus(&mut self, focus: Option<usize>, widgets: &mut [&mut dyn Widget]) {
+        if focus != self.focused {
+            if let Some(i) = self.focused {
+                widgets[i].set_focused(false, self.time);
+            }
+            if let Some(i) = focus {
+                widgets[i].set_focused(true, self.time);
+            }
+            self.focused = focus;
+        }
+    }
+}
+
+///
+/// The material used for rendering the widgets on top of everything else in the order they are rendered.
+///
+fn ui_material(color: Color) -> ColorMaterial {
+    ColorMaterial {
+        color,
+        render_states: RenderStates {
+            depth_test: DepthTest::Always,
+            write_mask: WriteMask::COLOR,
+            blend: Blend::TRANSPARENCY,
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+///
+/// Returns whether the position is inside the axis aligned rectangle.
+///
+fn rectangle_contains(rectangle: &Rectangle, position: PhysicalPoint) -> bool {
+    let center = rectangle.center();
+    let (width, height) = rectangle.size();
+    (position.x - center.x).abs() <= 0.5 * width && (position.y - center.y).abs() <= 0.5 * height
+}
@@ -0,0 +1,173 @@
+use super::*;
+
+///
+/// The visual state of a [Button].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ButtonState {
+    /// The mouse is not over the button.
+    Normal,
+    /// The mouse is over the button.
+    Hovered,
+    /// The button is pressed, ie. the left mouse button was pressed over the button and is not yet released.
+    Pressed,
+}
+
+///
+/// A rectangular button with a text label which changes color when hovered and pressed.
+/// A click is a press and release of the left mouse button over the button, which is polled using [Button::clicked].
+///
+pub struct Button {
+    rectangle: Rectangle,
+    label: String,
+    colors: [Color; 3],
+    text_color: Color,
+    hovered: bool,
+    pressed: bool,
+    clicked: bool,
+}
+
+impl Button {
+    ///
+    /// Constructs a new button with the given label, center and size in the coordinate system of the [camera2d] camera.
+    ///
+    pub fn new(
+        context: &Context,
+        label: impl Into<String>,
+        center: impl Into<PhysicalPoint>,
+        width: f32,
+        height: f32,
+    ) -> Self {
+        Self {
+            rectangle: Rectangle::new(context, center, degrees(0.0), width, height),
+            label: label.into(),
+            colors: [
+                Color::new_opaque(200, 200, 200),
+                Color::new_opaque(225, 225, 225),
+                Color::new_opaque(160, 160, 160),
+            ],
+            text_color: Color::BLACK,
+            hovered: false,
+            pressed: false,
+            clicked: false,
+        }
+    }
+
+    ///
+    /// Returns true if the button has been clicked since the last call to this function.
+    ///
+    pub fn clicked(&mut self) -> bool {
+        std::mem::replace(&mut self.clicked, false)
+    }
+
+    ///
+    /// Returns the visual state of the button.
+    ///
+    pub fn state(&self) -> ButtonState {
+        if self.pressed {
+            ButtonState::Pressed
+        } else if self.hovered {
+            ButtonState::Hovered
+        } else {
+            ButtonState::Normal
+        }
+    }
+
+    ///
+    /// Sets the label.
+    ///
+    pub fn set_label(&mut self, label: impl Into<String>) {
+        self.label = label.into();
+    }
+
+    ///
+    /// Returns the label.
+    ///
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    ///
+    /// Sets the background color in the normal, hovered and pressed state and the color of the label.
+    ///
+    pub fn set_colors(&mut self, normal: Color, hovered: Color, pressed: Color, text: Color) {
+        self.colors = [normal, hovered, pressed];
+        self.text_color = text;
+    }
+
+    ///
+    /// Sets the center of the button.
+    ///
+    pub fn set_center(&mut self, center: impl Into<PhysicalPoint>) {
+        self.rectangle.set_center(center);
+    }
+
+    ///
+    /// Sets the size of the button.
+    ///
+    pub fn set_size(&mut self, width: f32, height: f32) {
+        self.rectangle.set_size(width, height);
+    }
+}
+
+impl Widget for Button {
+    fn contains(&self, position: PhysicalPoint) -> bool {
+        rectangle_contains(&self.rectangle, position)
+    }
+
+    fn handle_event(&mut self, event: &mut Event, input: &WidgetInput) {
+        match event {
+            Event::MousePress {
+                button: MouseButton::Left,
+                handled,
+                ..
+            } => {
+                if input.hit && !*handled {
+                    self.pressed = true;
+                    *handled = true;
+                }
+            }
+            Event::MouseRelease {
+                button: MouseButton::Left,
+                handled,
+                ..
+            } => {
+                if self.pressed {
+                    self.pressed = false;
+                    self.clicked |= input.hit;
+                    *handled = true;
+                }
+            }
+            Event::MouseMotion { handled, .. } => {
+                self.hovered = input.hit;
+                if self.pressed {
+                    *handled = true;
+                }
+            }
+            Event::MouseLeave => {
+                self.hovered = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn render(&mut self, camera: &Camera, font: &dyn UiFont, _focused: bool, _time: f64) {
+        let color = match self.state() {
+            ButtonState::Normal => self.colors[0],
+            ButtonState::Hovered => self.colors[1],
+            ButtonState::Pressed => self.colors[2],
+        };
+        self.rectangle
+            .render_with_material(&ui_material(color), camera, &[]);
+        let center = self.rectangle.center();
+        font.render_text(
+            &self.label,
+            PhysicalPoint {
+                x: center.x - 0.5 * font.text_width(&self.label),
+                y: center.y,
+            },
+            self.text_color,
+            camera,
+        );
+    }
+}
@@ -0,0 +1,419 @@
+use super::*;
+
+///
+/// One of the nine positions in a viewport that a HUD element can be anchored to, see [Anchor].
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnchorPosition {
+    /// The top left corner.
+    TopLeft,
+    /// The center of the top edge.
+    Top,
+    /// The top right corner.
+    TopRight,
+    /// The center of the left edge.
+    Left,
+    /// The center of the viewport.
+    #[default]
+    Center,
+    /// The center of the right edge.
+    Right,
+    /// The bottom left corner.
+    BottomLeft,
+    /// The center of the bottom edge.
+    Bottom,
+    /// The bottom right corner.
+    BottomRight,
+}
+
+impl AnchorPosition {
+    /// The position as fractions of the width and height, measured from the top left corner.
+    fn fractions(&self) -> (f32, f32) {
+        match self {
+            Self::TopLeft => (0.0, 0.0),
+            Self::Top => (0.5, 0.0),
+            Self::TopRight => (1.0, 0.0),
+            Self::Left => (0.0, 0.5),
+            Self::Center => (0.5, 0.5),
+            Self::Right => (1.0, 0.5),
+            Self::BottomLeft => (0.0, 1.0),
+            Self::Bottom => (0.5, 1.0),
+            Self::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+///
+/// Places a 2D element relative to a viewport, for example in the top right corner with a margin of 16 logical pixels.
+/// The element is aligned such that the same point of the element is at the anchor position, for example the top right corner of the element
+/// is at the top right corner of the viewport, and then moved by the offset.
+///
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Anchor {
+    /// The position in the viewport.
+    pub position: AnchorPosition,
+    /// The horizontal offset in logical pixels, where positive is to the right.
+    pub offset_x: f32,
+    /// The vertical offset in logical pixels, where positive is down, like [LogicalPoint].
+    pub offset_y: f32,
+}
+
+impl Anchor {
+    ///
+    /// Constructs a new anchor at the given position with the given offset in logical pixels, where positive is to the right and down.
+    ///
+    pub fn new(position: AnchorPosition, offset_x: f32, offset_y: f32) -> Self {
+        Self {
+            position,
+            offset_x,
+            offset_y,
+        }
+    }
+
+    ///
+    /// Constructs a new anchor at the given position which keeps the given margin in logical pixels to the edges of the viewport,
+    /// ie. the offset points away from the edges that the position is on.
+    ///
+    pub fn with_margin(position: AnchorPosition, margin: f32) -> Self {
+        let (fx, fy) = position.fractions();
+        let inwards = |fraction: f32| match fraction {
+            f if f == 0.0 => margin,
+            f if f == 1.0 => -margin,
+            _ => 0.0,
+        };
+        Self::new(position, inwards(fx), inwards(fy))
+    }
+
+    ///
+    /// Returns the center and the size in physical pixels of an element with the given size in logical pixels, when anchored in the given viewport.
+    /// The center is in the coordinate system of the [camera2d] camera, ie. relative to the bottom left corner of the viewport,
+    /// which is the same coordinate system that the [UiLayer] uses for hit testing.
+    /// The edges of the element are rounded to whole physical pixels.
+    ///
+    pub fn place(
+        &self,
+        viewport: Viewport,
+        device_pixel_ratio: f32,
+        width: f32,
+        height: f32,
+    ) -> (PhysicalPoint, f32, f32) {
+        let (fx, fy) = self.position.fractions();
+        let width = (width * device_pixel_ratio).round();
+        let height = (height * device_pixel_ratio).round();
+        let left =
+            (fx * (viewport.width as f32 - width) + self.offset_x * device_pixel_ratio).round();
+        let top =
+            (fy * (viewport.height as f32 - height) + self.offset_y * device_pixel_ratio).round();
+        let center = PhysicalPoint {
+            x: left + 0.5 * width,
+            y: viewport.height as f32 - top - 0.5 * height,
+        };
+        (center, width, height)
+    }
+}
+
+///
+/// A 2D object which can be positioned by a [HudElement] or a [HudStack].
+///
+pub trait HudObject {
+    ///
+    /// Sets the center and the size of this object in physical pixels in the coordinate system of the [camera2d] camera.
+    ///
+    fn set_placement(&mut self, center: PhysicalPoint, width: f32, height: f32);
+}
+
+impl HudObject for Rectangle {
+    fn set_placement(&mut self, center: PhysicalPoint, width: f32, height: f32) {
+        self.set_center(center);
+        self.set_size(width, height);
+    }
+}
+
+impl HudObject for Circle {
+    ///
+    /// Sets the center and the radius such that the circle fits inside the given size.
+    ///
+    fn set_placement(&mut self, center: PhysicalPoint, width: f32, height: f32) {
+        self.set_center(center);
+        self.set_radius(0.5 * width.min(height));
+    }
+}
+
+impl HudObject for Button {
+    fn set_placement(&mut self, center: PhysicalPoint, width: f32, height: f32) {
+        self.set_center(center);
+        self.set_size(width, height);
+    }
+}
+
+impl HudObject for TextField {
+    fn set_placement(&mut self, center: PhysicalPoint, width: f32, height: f32) {
+        self.set_center(center);
+        self.set_size(width, height);
+    }
+}
+
+impl<G: Geometry + HudObject, M: Material> HudObject for Gm<G, M> {
+    fn set_placement(&mut self, center: PhysicalPoint, width: f32, height: f32) {
+        self.geometry.set_placement(center, width, height);
+    }
+}
+
+///
+/// Owns a 2D object, for example a [Rectangle] or a [Button], and positions it in the viewport using an [Anchor].
+/// Call [HudElement::update] each frame before handling the events and rendering, then the element follows the viewport when the window is resized
+/// or moved to a screen with another device pixel ratio.
+/// The element dereferences to the object, so it can for example be rendered as the object, and it is a [Widget] if the object is.
+///
+pub struct HudElement<T: HudObject> {
+    /// The object.
+    pub object: T,
+    /// The anchor which places the object in the viewport.
+    pub anchor: Anchor,
+    /// The width in logical pixels.
+    pub width: f32,
+    /// The height in logical pixels.
+    pub height: f32,
+}
+
+impl<T: HudObject> HudElement<T> {
+    ///
+    /// Constructs a new element which places the given object using the given anchor and size in logical pixels.
+    ///
+    pub fn new(object: T, anchor: Anchor, width: f32, height: f32) -> Self {
+        Self {
+            object,
+            anchor,
+            width,
+            height,
+        }
+    }
+
+    ///
+    /// Places the object in the given viewport, for example [FrameInput::viewport](crate::FrameInput::viewport)
+    /// with the device pixel ratio [FrameInput::device_pixel_ratio](crate::FrameInput::device_pixel_ratio).
+    ///
+    pub fn update(&mut self, viewport: Viewport, device_pixel_ratio: f32) {
+        let (center, width, height) =
+            self.anchor
+                .place(viewport, device_pixel_ratio, self.width, self.height);
+        self.object.set_placement(center, width, height);
+    }
+}
+
+impl<T: HudObject> std::ops::Deref for HudElement<T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.object
+    }
+}
+
+impl<T: HudObject> std::ops::DerefMut for HudElement<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.object
+    }
+}
+
+impl<T: HudObject + Widget> Widget for HudElement<T> {
+    fn contains(&self, position: PhysicalPoint) -> bool {
+        self.object.contains(position)
+    }
+
+    fn is_focusable(&self) -> bool {
+        self.object.is_focusable()
+    }
+
+    fn set_focused(&mut self, focused: bool, time: f64) {
+        self.object.set_focused(focused, time)
+    }
+
+    fn handle_event(&mut self, event: &mut Event, input: &WidgetInput) {
+        self.object.handle_event(event, input)
+    }
+
+    fn render(&mut self, camera: &Camera, font: &dyn UiFont, focused: bool, time: f64) {
+        self.object.render(camera, font, focused, time)
+    }
+}
+
+///
+/// The direction in which a [HudStack] places its objects.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StackDirection {
+    /// From left to right.
+    Row,
+    /// From top to bottom.
+    Column,
+}
+
+///
+/// Places a number of 2D objects of the same size next to each other in a row or a column with a spacing between them,
+/// for example a bar of [Button]s, where the bounding box of all the objects is placed in the viewport using an [Anchor].
+/// Call [HudStack::update] each frame before handling the events and rendering, see [HudElement].
+///
+pub struct HudStack<T: HudObject> {
+    /// The objects in the order they are placed.
+    pub objects: Vec<T>,
+    /// The anchor which places the bounding box of all the objects in the viewport.
+    pub anchor: Anchor,
+    /// The direction in which the objects are placed.
+    pub direction: StackDirection,
+    /// The width of each object in logical pixels.
+    pub width: f32,
+    /// The height of each object in logical pixels.
+    pub height: f32,
+    /// The spacing between the objects in logical pixels.
+    pub spacing: f32,
+}
+
+impl<T: HudObject> HudStack<T> {
+    ///
+    /// Constructs a new stack of the given objects, which each have the given size in logical pixels, with no spacing between them.
+    ///
+    pub fn new(
+        objects: Vec<T>,
+        anchor: Anchor,
+        direction: StackDirection,
+        width: f32,
+        height: f32,
+    ) -> Self {
+        Self {
+            objects,
+            anchor,
+            direction,
+            width,
+            height,
+            spacing: 0.0,
+        }
+    }
+
+    ///
+    /// Places the objects in the given viewport, see [HudElement::update].
+    ///
+    pub fn update(&mut self, viewport: Viewport, device_pixel_ratio: f32) {
+        let count = self.objects.len() as f32;
+        let gaps = (count - 1.0).max(0.0) * self.spacing;
+        let (total_width, total_height) = match self.direction {
+            StackDirection::Row => (count * self.width + gaps, self.height),
+            StackDirection::Column => (self.width, count * self.height + gaps),
+        };
+        let (center, _, _) =
+            self.anchor
+                .place(viewport, device_pixel_ratio, total_width, total_height);
+        // The bounding box is placed using the anchor, and each object is placed relative to the top left corner of the bounding box
+        let left = center.x - 0.5 * (total_width * device_pixel_ratio).round();
+        let top = center.y + 0.5 * (total_height * device_pixel_ratio).round();
+        for (i, object) in self.objects.iter_mut().enumerate() {
+            let (x, y) = match self.direction {
+                StackDirection::Row => (i as f32 * (self.width + self.spacing), 0.0),
+                StackDirection::Column => (0.0, i as f32 * (self.height + self.spacing)),
+            };
+            let item_left = (left + x * device_pixel_ratio).round();
+            let item_top = (top - y * device_pixel_ratio).round();
+            let width = (self.width * device_pixel_ratio).round();
+            let height = (self.height * device_pixel_ratio).round();
+            object.set_placement(
+                PhysicalPoint {
+                    x: item_left + 0.5 * width,
+                    y: item_top - 0.5 * height,
+                },
+                width,
+                height,
+            );
+        }
+    }
+
+    ///
+    /// Returns the objects as widgets, for example for [UiLayer::handle_events] and [UiLayer::render].
+    ///
+    pub fn widgets(&mut self) -> Vec<&mut dyn Widget>
+    where
+        T: Widget,
+    {
+        self.objects
+            .iter_mut()
+            .map(|object| object as &mut dyn Widget)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    struct Placement(PhysicalPoint, f32, f32);
+
+    impl Placement {
+        fn new() -> Self {
+            Placement(PhysicalPoint { x: 0.0, y: 0.0 }, 0.0, 0.0)
+        }
+    }
+
+    impl HudObject for Placement {
+        fn set_placement(&mut self, center: PhysicalPoint, width: f32, height: f32) {
+            *self = Placement(center, width, height);
+        }
+    }
+
+    #[test]
+    fn test_anchor() {
+        let viewport = Viewport::new_at_origo(800, 600);
+        let anchor = Anchor::with_margin(AnchorPosition::TopRight, 16.0);
+        assert_eq!((anchor.offset_x, anchor.offset_y), (-16.0, 16.0));
+        let (center, width, height) = anchor.place(viewport, 1.0, 100.0, 50.0);
+        assert_eq!(center, PhysicalPoint { x: 734.0, y: 559.0 });
+        assert_eq!((width, height), (100.0, 50.0));
+
+        // The margin and size are in logical pixels
+        let (center, width, height) = anchor.place(viewport, 2.0, 100.0, 50.0);
+        assert_eq!(center, PhysicalPoint { x: 668.0, y: 518.0 });
+        assert_eq!((width, height), (200.0, 100.0));
+
+        let anchor = Anchor::with_margin(AnchorPosition::Center, 16.0);
+        assert_eq!((anchor.offset_x, anchor.offset_y), (0.0, 0.0));
+        let (center, _, _) = anchor.place(viewport, 1.5, 20.0, 20.0);
+        assert_eq!(center, PhysicalPoint { x: 400.0, y: 300.0 });
+
+        let anchor = Anchor::with_margin(AnchorPosition::BottomLeft, 10.0);
+        let (center, _, _) = anchor.place(viewport, 1.0, 20.0, 40.0);
+        assert_eq!(center, PhysicalPoint { x: 20.0, y: 30.0 });
+    }
+
+    #[test]
+    fn test_stack() {
+        let mut stack = HudStack {
+            spacing: 10.0,
+            ..HudStack::new(
+                (0..3).map(|_| Placement::new()).collect(),
+                Anchor::with_margin(AnchorPosition::Bottom, 20.0),
+                StackDirection::Row,
+                100.0,
+                40.0,
+            )
+        };
+        stack.update(Viewport::new_at_origo(800, 600), 1.0);
+        let centers: Vec<_> = stack.objects.iter().map(|p| p.0).collect();
+        assert_eq!(
+            centers,
+            vec![
+                PhysicalPoint { x: 290.0, y: 40.0 },
+                PhysicalPoint { x: 400.0, y: 40.0 },
+                PhysicalPoint { x: 510.0, y: 40.0 },
+            ]
+        );
+
+        stack.direction = StackDirection::Column;
+        stack.update(Viewport::new_at_origo(800, 600), 2.0);
+        let centers: Vec<_> = stack.objects.iter().map(|p| p.0).collect();
+        assert_eq!(
+            centers,
+            vec![
+                PhysicalPoint { x: 400.0, y: 280.0 },
+                PhysicalPoint { x: 400.0, y: 180.0 },
+                PhysicalPoint { x: 400.0, y: 80.0 },
+            ]
+        );
+        assert!(stack.objects.iter().all(|p| (p.1, p.2) == (200.0, 80.0)));
+    }
+}
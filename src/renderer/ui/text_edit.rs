@@ -0,0 +1,309 @@
+use crate::renderer::*;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+///
+/// The editing state of a single line of text, ie. the text, the caret position and the selection, used by [TextField].
+/// All positions are byte indices into the text which are always on a grapheme cluster boundary,
+/// so for example a letter followed by a combining accent or an emoji consisting of several code points is moved over and deleted as one character.
+///
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TextEditState {
+    text: String,
+    caret: usize,
+    anchor: Option<usize>,
+}
+
+impl TextEditState {
+    ///
+    /// Constructs a new editing state with the given text and the caret at the end of the text.
+    ///
+    pub fn new(text: impl Into<String>) -> Self {
+        let text = text.into();
+        Self {
+            caret: text.len(),
+            text,
+            anchor: None,
+        }
+    }
+
+    ///
+    /// Returns the text.
+    ///
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    ///
+    /// Replaces the text, moves the caret to the end of the text and removes the selection.
+    ///
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        *self = Self::new(text);
+    }
+
+    ///
+    /// Returns the position of the caret as a byte index into the text.
+    ///
+    pub fn caret(&self) -> usize {
+        self.caret
+    }
+
+    ///
+    /// Returns the selected range of the text as byte indices or `None` if nothing is selected.
+    ///
+    pub fn selection(&self) -> Option<Range<usize>> {
+        self.anchor
+            .filter(|anchor| *anchor != self.caret)
+            .map(|anchor| anchor.min(self.caret)..anchor.max(self.caret))
+    }
+
+    ///
+    /// Returns the selected text, which is empty if nothing is selected.
+    ///
+    pub fn selected_text(&self) -> &str {
+        self.selection()
+            .map(|range| &self.text[range])
+            .unwrap_or("")
+    }
+
+    ///
+    /// Selects the entire text.
+    ///
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.caret = self.text.len();
+    }
+
+    ///
+    /// Inserts the given text at the caret, replacing the selection if any.
+    /// Control characters, for example new lines, are ignored since the text is a single line.
+    ///
+    pub fn insert(&mut self, text: &str) {
+        self.delete_selection();
+        let text = text.chars().filter(|c| !c.is_control()).collect::<String>();
+        self.text.insert_str(self.caret, &text);
+        self.caret += text.len();
+    }
+
+    ///
+    /// Deletes the selection or, if nothing is selected, the character before the caret, ie. the behaviour of the backspace key.
+    ///
+    pub fn delete_backward(&mut self) {
+        if !self.delete_selection() {
+            let start = self.previous_boundary();
+            self.text.replace_range(start..self.caret, "");
+            self.caret = start;
+        }
+    }
+
+    ///
+    /// Deletes the selection or, if nothing is selected, the character after the caret, ie. the behaviour of the delete key.
+    ///
+    pub fn delete_forward(&mut self) {
+        if !self.delete_selection() {
+            let end = self.next_boundary();
+            self.text.replace_range(self.caret..end, "");
+        }
+    }
+
+    ///
+    /// Moves the caret one character to the left. If `select` is true, the selection is extended to the new caret position,
+    /// otherwise an existing selection is removed and the caret is placed at the start of it.
+    ///
+    pub fn move_left(&mut self, select: bool) {
+        let position = match self.selection() {
+            Some(range) if !select => range.start,
+            _ => self.previous_boundary(),
+        };
+        self.move_to(position, select);
+    }
+
+    ///
+    /// Moves the caret one character to the right. If `select` is true, the selection is extended to the new caret position,
+    /// otherwise an existing selection is removed and the caret is placed at the end of it.
+    ///
+    pub fn move_right(&mut self, select: bool) {
+        let position = match self.selection() {
+            Some(range) if !select => range.end,
+            _ => self.next_boundary(),
+        };
+        self.move_to(position, select);
+    }
+
+    ///
+    /// Moves the caret to the start of the text. If `select` is true, the selection is extended to the new caret position.
+    ///
+    pub fn move_home(&mut self, select: bool) {
+        self.move_to(0, select);
+    }
+
+    ///
+    /// Moves the caret to the end of the text. If `select` is true, the selection is extended to the new caret position.
+    ///
+    pub fn move_end(&mut self, select: bool) {
+        self.move_to(self.text.len(), select);
+    }
+
+    ///
+    /// Moves the caret to the grapheme cluster boundary closest to the given byte index. If `select` is true, the selection is extended to the new caret position.
+    ///
+    pub fn move_to(&mut self, position: usize, select: bool) {
+        if select {
+            self.anchor.get_or_insert(self.caret);
+        } else {
+            self.anchor = None;
+        }
+        self.caret = self
+            .boundaries()
+            .min_by_key(|boundary| boundary.abs_diff(position))
+            .unwrap_or(0);
+    }
+
+    ///
+    /// Returns the byte indices of all positions where the caret can be placed, ie. the grapheme cluster boundaries including the start and end of the text.
+    ///
+    pub fn boundaries(&self) -> impl Iterator<Item = usize> + '_ {
+        self.text
+            .grapheme_indices(true)
+            .map(|(i, _)| i)
+            .chain(std::iter::once(self.text.len()))
+    }
+
+    ///
+    /// Applies the editing action of the given key, if any, and returns whether the key was used.
+    /// The supported keys are the arrow keys, home and end, where holding shift extends the selection,
+    /// backspace, delete and command/ctrl + A for selecting all text.
+    ///
+    pub fn handle_key(&mut self, key: Key, modifiers: Modifiers) -> bool {
+        let select = modifiers.shift;
+        match key {
+            Key::ArrowLeft => self.move_left(select),
+            Key::ArrowRight => self.move_right(select),
+            Key::Home | Key::ArrowUp => self.move_home(select),
+            Key::End | Key::ArrowDown => self.move_end(select),
+            Key::Backspace => self.delete_backward(),
+            Key::Delete => self.delete_forward(),
+            Key::A if modifiers.command || modifiers.ctrl => self.select_all(),
+            _ => return false,
+        }
+        true
+    }
+
+    fn delete_selection(&mut self) -> bool {
+        if let Some(range) = self.selection() {
+            self.text.replace_range(range.clone(), "");
+            self.caret = range.start;
+            self.anchor = None;
+            true
+        } else {
+            self.anchor = None;
+            false
+        }
+    }
+
+    fn previous_boundary(&self) -> usize {
+        self.text[..self.caret]
+            .graphemes(true)
+            .next_back()
+            .map(|grapheme| self.caret - grapheme.len())
+            .unwrap_or(0)
+    }
+
+    fn next_boundary(&self) -> usize {
+        self.text[self.caret..]
+            .graphemes(true)
+            .next()
+            .map(|grapheme| self.caret + grapheme.len())
+            .unwrap_or(self.caret)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SHIFT: Modifiers = Modifiers {
+        alt: false,
+        ctrl: false,
+        shift: true,
+        command: false,
+    };
+
+    #[test]
+    fn test_insert_and_delete() {
+        let mut state = TextEditState::new("helo");
+        state.move_left(false);
+        state.insert("l");
+        assert_eq!(state.text(), "hello");
+        assert_eq!(state.caret(), 4);
+        state.delete_forward();
+        assert_eq!(state.text(), "hell");
+        state.delete_backward();
+        state.delete_backward();
+        assert_eq!(state.text(), "he");
+        state.move_home(false);
+        state.delete_backward();
+        assert_eq!(state.text(), "he");
+        state.insert("a\nb");
+        assert_eq!(state.text(), "abhe");
+    }
+
+    #[test]
+    fn test_grapheme_aware_editing() {
+        // An e with a combining accent and a family emoji consisting of several code points joined by zero width joiners
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let mut state = TextEditState::new(format!("e\u{301}{}x", family));
+        state.move_left(false);
+        state.delete_backward();
+        assert_eq!(state.text(), "e\u{301}x");
+        state.delete_backward();
+        assert_eq!(state.text(), "x");
+        assert_eq!(state.caret(), 0);
+
+        state.set_text(format!("{}e\u{301}", family));
+        state.move_home(false);
+        state.move_right(false);
+        assert_eq!(state.caret(), family.len());
+        state.delete_forward();
+        assert_eq!(state.text(), family);
+        state.move_to(3, false);
+        assert_eq!(state.caret(), 0);
+        assert_eq!(
+            state.boundaries().collect::<Vec<_>>(),
+            vec![0, family.len()]
+        );
+    }
+
+    #[test]
+    fn test_selection() {
+        let mut state = TextEditState::new("hello world");
+        assert!(state.handle_key(Key::ArrowLeft, SHIFT));
+        assert!(state.handle_key(Key::ArrowLeft, SHIFT));
+        assert_eq!(state.selected_text(), "ld");
+        assert!(state.handle_key(Key::ArrowLeft, Modifiers::default()));
+        assert_eq!(state.caret(), 9);
+        assert!(state.handle_key(Key::Home, SHIFT));
+        assert_eq!(state.selected_text(), "hello wor");
+        state.insert("bye");
+        assert_eq!(state.text(), "byeld");
+        assert_eq!(state.selection(), None);
+
+        state.move_home(false);
+        state.handle_key(Key::End, SHIFT);
+        state.handle_key(Key::ArrowLeft, Modifiers::default());
+        assert_eq!(state.selection(), None);
+        assert_eq!(state.caret(), 0);
+
+        state.handle_key(
+            Key::A,
+            Modifiers {
+                ctrl: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(state.selected_text(), "byeld");
+        state.handle_key(Key::Backspace, Modifiers::default());
+        assert_eq!(state.text(), "");
+        assert!(!state.handle_key(Key::Escape, Modifiers::default()));
+    }
+}
@@ -0,0 +1,241 @@
+use super::*;
+
+///
+/// A single line text input field. The field receives the keyboard focus when clicked,
+/// after which text can be written and edited as described in [TextEditState::handle_key].
+/// The caret is placed by clicking and a selection is made by dragging or by holding shift.
+/// Text which is wider than the field is not clipped.
+///
+pub struct TextField {
+    state: TextEditState,
+    border: Rectangle,
+    background: Rectangle,
+    selection: Rectangle,
+    caret: Rectangle,
+    background_color: Color,
+    text_color: Color,
+    last_activity: f64,
+    selecting: bool,
+}
+
+impl TextField {
+    ///
+    /// Constructs a new empty text field with the given center and size in the coordinate system of the [camera2d] camera.
+    ///
+    pub fn new(
+        context: &Context,
+        center: impl Into<PhysicalPoint>,
+        width: f32,
+        height: f32,
+    ) -> Self {
+        let center = center.into();
+        Self {
+            state: TextEditState::default(),
+            border: Rectangle::new(context, center, degrees(0.0), width + 2.0, height + 2.0),
+            background: Rectangle::new(context, center, degrees(0.0), width, height),
+            selection: Rectangle::new(context, center, degrees(0.0), 0.0, 0.0),
+            caret: Rectangle::new(context, center, degrees(0.0), 0.0, 0.0),
+            background_color: Color::WHITE,
+            text_color: Color::BLACK,
+            last_activity: 0.0,
+            selecting: false,
+        }
+    }
+
+    ///
+    /// Returns the text.
+    ///
+    pub fn text(&self) -> &str {
+        self.state.text()
+    }
+
+    ///
+    /// Replaces the text and moves the caret to the end of the text.
+    ///
+    pub fn set_text(&mut self, text: impl Into<String>) {
+        self.state.set_text(text);
+    }
+
+    ///
+    /// Returns the editing state, ie. the text, caret and selection.
+    ///
+    pub fn edit_state(&self) -> &TextEditState {
+        &self.state
+    }
+
+    ///
+    /// Returns the editing state mutably, for example for moving the caret.
+    ///
+    pub fn edit_state_mut(&mut self) -> &mut TextEditState {
+        &mut self.state
+    }
+
+    ///
+    /// Sets the background and text color.
+    ///
+    pub fn set_colors(&mut self, background: Color, text: Color) {
+        self.background_color = background;
+        self.text_color = text;
+    }
+
+    ///
+    /// Sets the center of the text field.
+    ///
+    pub fn set_center(&mut self, center: impl Into<PhysicalPoint>) {
+        let center = center.into();
+        self.border.set_center(center);
+        self.background.set_center(center);
+    }
+
+    ///
+    /// Sets the size of the text field.
+    ///
+    pub fn set_size(&mut self, width: f32, height: f32) {
+        self.border.set_size(width + 2.0, height + 2.0);
+        self.background.set_size(width, height);
+    }
+
+    fn padding(&self) -> f32 {
+        (0.2 * self.background.size().1).min(6.0)
+    }
+
+    fn text_start(&self) -> f32 {
+        self.background.center().x - 0.5 * self.background.size().0 + self.padding()
+    }
+
+    fn place_caret(&mut self, x: f32, select: bool, font: &dyn UiFont) {
+        let start = self.text_start();
+        let text = self.state.text();
+        let position = self
+            .state
+            .boundaries()
+            .map(|i| (i, (start + font.text_width(&text[..i]) - x).abs()))
+            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+            .map(|(i, _)| i)
+            .unwrap_or(0);
+        self.state.move_to(position, select);
+    }
+}
+
+impl Widget for TextField {
+    fn contains(&self, position: PhysicalPoint) -> bool {
+        rectangle_contains(&self.background, position)
+    }
+
+    fn is_focusable(&self) -> bool {
+        true
+    }
+
+    fn set_focused(&mut self, focused: bool, time: f64) {
+        self.last_activity = time;
+        if !focused {
+            self.selecting = false;
+        }
+    }
+
+    fn handle_event(&mut self, event: &mut Event, input: &WidgetInput) {
+        match event {
+            Event::MousePress {
+                button: MouseButton::Left,
+                modifiers,
+                handled,
+                ..
+            } => {
+                if input.hit && !*handled {
+                    if let Some(position) = input.position {
+                        self.place_caret(position.x, modifiers.shift, input.font);
+                    }
+                    self.selecting = true;
+                    self.last_activity = input.time;
+                    *handled = true;
+                }
+            }
+            Event::MouseMotion { handled, .. } => {
+                if self.selecting {
+                    if let Some(position) = input.position {
+                        self.place_caret(position.x, true, input.font);
+                    }
+                    self.last_activity = input.time;
+                    *handled = true;
+                }
+            }
+            Event::MouseRelease {
+                button: MouseButton::Left,
+                handled,
+                ..
+            } => {
+                if self.selecting {
+                    self.selecting = false;
+                    *handled = true;
+                }
+            }
+            Event::KeyPress {
+                kind,
+                modifiers,
+                handled,
+            } => {
+                if input.focused && !*handled && self.state.handle_key(*kind, *modifiers) {
+                    self.last_activity = input.time;
+                    *handled = true;
+                }
+            }
+            Event::Text(text) => {
+                if input.focused {
+                    self.state.insert(text);
+                    self.last_activity = input.time;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn render(&mut self, camera: &Camera, font: &dyn UiFont, focused: bool, time: f64) {
+        let border_color = if focused {
+            Color::new_opaque(60, 120, 220)
+        } else {
+            Color::new_opaque(120, 120, 120)
+        };
+        self.border
+            .render_with_material(&ui_material(border_color), camera, &[]);
+        self.background
+            .render_with_material(&ui_material(self.background_color), camera, &[]);
+
+        let start = self.text_start();
+        let center_y = self.background.center().y;
+        let line_height = self.background.size().1 - 2.0 * self.padding();
+        let text = self.state.text();
+        if let Some(range) = self.state.selection().filter(|_| focused) {
+            let x0 = start + font.text_width(&text[..range.start]);
+            let x1 = start + font.text_width(&text[..range.end]);
+            self.selection.set_center(PhysicalPoint {
+                x: 0.5 * (x0 + x1),
+                y: center_y,
+            });
+            self.selection.set_size(x1 - x0, line_height);
+            self.selection.render_with_material(
+                &ui_material(Color::new_opaque(150, 190, 255)),
+                camera,
+                &[],
+            );
+        }
+        font.render_text(
+            text,
+            PhysicalPoint {
+                x: start,
+                y: center_y,
+            },
+            self.text_color,
+            camera,
+        );
+
+        // The caret blinks with a period of one second, but is always visible right after editing
+        let blink_on = ((time - self.last_activity) / 500.0).floor() as i64 % 2 == 0;
+        if focused && blink_on {
+            let x = start + font.text_width(&text[..self.state.caret()]);
+            self.caret.set_center(PhysicalPoint { x, y: center_y });
+            self.caret.set_size(1.5, line_height);
+            self.caret
+                .render_with_material(&ui_material(self.text_color), camera, &[]);
+        }
+    }
+}
@@ -4,15 +4,28 @@
 //! from a [glow](https://crates.io/crates/glow) OpenGL/WebGL context.
 //! * If full control over the window and event handling, but not the context creation, is desired, use a [WindowedContext] or [HeadlessContext].
 //! * Finally, for an easy setup, use [Window::new] or [Window::from_winit_window], the latter will provide full control over the creation of the window.
+//! * On web, if the canvas and the event listeners are managed by another framework, use [Context::from_webgl2_context](crate::core::Context::from_webgl2_context)
+//! and [WebFrameInputGenerator] which do not depend on [winit](https://crates.io/crates/winit) (requires the `web-input` feature).
 //!
 //!
 
+#[cfg(any(feature = "window", feature = "web-input"))]
+mod frame_io;
+#[cfg(any(feature = "window", feature = "web-input"))]
+pub use frame_io::*;
+
 #[cfg(feature = "window")]
 #[cfg_attr(docsrs, doc(feature = "window"))]
 mod winit_window;
 #[cfg(feature = "window")]
 pub use winit_window::*;
 
+#[cfg(all(feature = "web-input", target_arch = "wasm32"))]
+#[cfg_attr(docsrs, doc(feature = "web-input"))]
+mod web_input;
+#[cfg(all(feature = "web-input", target_arch = "wasm32"))]
+pub use web_input::*;
+
 #[cfg(all(feature = "headless", not(target_arch = "wasm32")))]
 #[cfg_attr(docsrs, doc(feature = "headless"))]
 mod headless;
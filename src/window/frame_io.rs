@@ -0,0 +1,184 @@
+use crate::control::Event;
+use crate::core::{Context, RenderTarget, Viewport, ViewportExt};
+
+///
+/// Input for rendering (and whatever else needs it) each frame.
+/// It includes events that can be used as input to [controls](crate::renderer::control).
+/// The data should only be used for one frame.
+///
+/// Note:
+/// [FrameInput] is automatically generated if using the default [Window](crate::window::Window).
+/// Use [FrameInputGenerator](crate::window::FrameInputGenerator) to generate it with a custom [winit](https://crates.io/crates/winit) window.
+///
+#[derive(Clone, Debug)]
+pub struct FrameInput {
+    /// A list of [events](crate::Event) which has occurred since last frame.
+    pub events: Vec<Event>,
+
+    /// Milliseconds since last frame, which is zero for the first frame.
+    pub elapsed_time: f64,
+
+    /// Milliseconds accumulated time since start.
+    pub accumulated_time: f64,
+
+    /// Viewport of the window in physical pixels (the size of the screen [RenderTarget] which is returned from [FrameInput::screen]).
+    pub viewport: Viewport,
+
+    /// Width of the window in logical pixels.
+    pub window_width: u32,
+
+    /// Height of the window in logical pixels.
+    pub window_height: u32,
+
+    /// Number of physical pixels for each logical pixel.
+    pub device_pixel_ratio: f32,
+
+    /// Whether or not this is the first frame.
+    pub first_frame: bool,
+
+    /// The graphics context for the window.
+    pub context: Context,
+}
+
+impl FrameInput {
+    ///
+    /// Returns the screen render target, which is used for drawing to the screen, for this window.
+    /// Same as
+    ///
+    /// ```notrust
+    /// RenderTarget::screen(&frame_input.context, frame_input.viewport.width, frame_input.viewport.height)
+    /// ```
+    ///
+    pub fn screen(&self) -> RenderTarget {
+        debug_assert!(
+            (self.window_width as f32 * self.device_pixel_ratio - self.viewport.width as f32).abs()
+                <= self.device_pixel_ratio.max(1.0)
+                && (self.window_height as f32 * self.device_pixel_ratio
+                    - self.viewport.height as f32)
+                    .abs()
+                    <= self.device_pixel_ratio.max(1.0),
+            "the viewport ({}x{}) is not the window size ({}x{}) in physical pixels, make sure the viewport is in physical and the window size in logical pixels",
+            self.viewport.width,
+            self.viewport.height,
+            self.window_width,
+            self.window_height
+        );
+        RenderTarget::screen(&self.context, self.viewport.width, self.viewport.height)
+    }
+
+    ///
+    /// Returns the size of the window in physical pixels, ie. the size of the screen render target, which is the size to use for render targets and textures
+    /// which are rendered to the screen, for example by an effect, otherwise they are scaled and look blurry.
+    ///
+    pub fn physical_size(&self) -> (u32, u32) {
+        (self.viewport.width, self.viewport.height)
+    }
+
+    ///
+    /// Returns the size of the window in logical pixels, which is the coordinate system of the positions in the [events](crate::Event).
+    ///
+    pub fn logical_size(&self) -> (u32, u32) {
+        (self.window_width, self.window_height)
+    }
+
+    ///
+    /// Returns the viewport of the window in logical pixels, for example for laying out 2D content in logical pixels
+    /// before converting it to physical pixels using [ViewportExt::from_logical].
+    ///
+    pub fn screen_viewport_logical(&self) -> Viewport {
+        self.viewport.to_logical(self.device_pixel_ratio)
+    }
+
+    ///
+    /// Returns true if the window is minimized or for some other reason has zero width or height.
+    /// Nothing should be rendered in that case, since it is not possible to create render targets and textures with a size of zero.
+    /// The default [Window](crate::window::Window) does not invoke the render loop callback while the window is minimized,
+    /// so this only needs to be checked when generating the frame input using a [FrameInputGenerator](crate::window::FrameInputGenerator).
+    ///
+    pub fn is_minimized(&self) -> bool {
+        self.viewport.width == 0 || self.viewport.height == 0
+    }
+}
+
+///
+/// Output from the rendering to the default [Window](crate::window::Window) each frame.
+///
+#[derive(Clone, Debug)]
+pub struct FrameOutput {
+    ///
+    /// If this is true:
+    /// - On desktop, the window is closed and the renderloop is stopped.
+    /// - On web, the render loop is stopped, the event handlers are removed and the `Window` dropped. Note that the canvas is not removed.
+    ///
+    pub exit: bool,
+
+    ///
+    /// Swaps the back and front buffer if this is true.
+    /// Set this to true if something have been rendered this frame and you want to display it.
+    /// Set it to false if nothing have been rendered this frame, for example if nothing has changed,
+    /// and you want to reuse the image from an old frame.
+    /// Currently ignored on web, since it does not use double buffering.
+    ///
+    pub swap_buffers: bool,
+
+    ///
+    /// Whether to stop the render loop until next event.
+    ///
+    pub wait_next_event: bool,
+
+    ///
+    /// Whether or not the window should receive mouse events.
+    /// If this is false, the mouse events pass through the window to whatever is behind it, which is useful for overlay applications,
+    /// see [WindowSettings::transparent](crate::WindowSettings::transparent).
+    /// Not supported on web, Wayland, iOS and Android.
+    ///
+    pub cursor_hittest: bool,
+
+    ///
+    /// If this is true, the next frame starts with the content of the screen from this frame instead of undefined content,
+    /// which makes it possible to only redraw the part of the screen that has changed, for example using [RenderTarget::clear_partially]
+    /// and [RenderTarget::write_partially] with a [ScissorBox](crate::core::ScissorBox) around the changed part.
+    /// Since the content of the back buffer is not preserved when swapping buffers on most platforms, the default [Window](crate::window::Window)
+    /// copies the color of the screen into an accumulation texture at the end of the frame and back to the screen at the start of the next frame.
+    /// Only the color is preserved, so the depth should be cleared in the part of the screen that is redrawn.
+    /// The content is discarded when the window is resized.
+    ///
+    pub preserve_screen: bool,
+}
+
+impl Default for FrameOutput {
+    fn default() -> Self {
+        Self {
+            exit: false,
+            swap_buffers: true,
+            wait_next_event: false,
+            cursor_hittest: true,
+            preserve_screen: false,
+        }
+    }
+}
+
+///
+/// Returns the given duration since the last frame in milliseconds or zero for the first frame,
+/// since the time before the first frame includes initialization, like compiling shaders and loading assets, which should not be part of any animation.
+///
+pub(super) fn elapsed_time(duration: std::time::Duration, first_frame: bool) -> f64 {
+    if first_frame {
+        0.0
+    } else {
+        duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 * 1e-6
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_elapsed_time() {
+        assert_eq!(elapsed_time(Duration::from_secs(3), true), 0.0);
+        assert_eq!(elapsed_time(Duration::ZERO, false), 0.0);
+        assert!((elapsed_time(Duration::from_micros(1_016_500), false) - 1016.5).abs() < 1e-9);
+    }
+}
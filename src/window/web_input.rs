@@ -0,0 +1,558 @@
+use super::FrameInput;
+use crate::control::*;
+use crate::core::*;
+use instant::Instant;
+use std::sync::Arc;
+use web_sys::{
+    HtmlCanvasElement, KeyboardEvent, MouseEvent, PointerEvent, TouchEvent, WebGl2RenderingContext,
+    WheelEvent,
+};
+
+impl Context {
+    ///
+    /// Creates a new context from a [WebGL2 context](web_sys::WebGl2RenderingContext), for example obtained from a canvas which is managed by another framework.
+    /// Also enables the `EXT_color_buffer_float`, `OES_texture_float_linear` and `OES_texture_half_float_linear` extensions which are needed for rendering to and filtering floating point textures.
+    /// Use a [WebFrameInputGenerator] to generate the [FrameInput] for each frame from the DOM events of the canvas.
+    ///
+    pub fn from_webgl2_context(webgl_context: WebGl2RenderingContext) -> Result<Self, CoreError> {
+        for extension in [
+            "EXT_color_buffer_float",
+            "OES_texture_float_linear",
+            "OES_texture_half_float_linear",
+        ] {
+            webgl_context
+                .get_extension(extension)
+                .map_err(|e| {
+                    CoreError::ContextCreation(format!(
+                        "unable to get the {} extension: {:?}",
+                        extension, e
+                    ))
+                })?
+                .ok_or_else(|| {
+                    CoreError::ContextCreation(format!(
+                        "the {} extension is not supported",
+                        extension
+                    ))
+                })?;
+        }
+        Self::from_gl_context(Arc::new(crate::context::Context::from_webgl2_context(
+            webgl_context,
+        )))
+    }
+}
+
+///
+/// Use this to generate [FrameInput] for a new frame from DOM events on web, without using [winit](https://crates.io/crates/winit).
+/// This is useful when the canvas and the event listeners are managed by another framework.
+/// Forward the events from your event listeners to the `handle_*` methods and call [WebFrameInputGenerator::generate] each frame.
+///
+/// All positions are in logical pixels relative to the top left corner of the canvas.
+/// The size of the canvas drawing buffer is updated to the displayed size of the canvas times the device pixel ratio each time [WebFrameInputGenerator::generate] is called.
+///
+pub struct WebFrameInputGenerator {
+    canvas: HtmlCanvasElement,
+    last_time: Instant,
+    first_frame: bool,
+    events: Vec<Event>,
+    accumulated_time: f64,
+    viewport: Viewport,
+    window_width: u32,
+    window_height: u32,
+    device_pixel_ratio: f64,
+    cursor_pos: Option<LogicalPoint>,
+    finger_id: Option<i32>,
+    secondary_cursor_pos: Option<LogicalPoint>,
+    secondary_finger_id: Option<i32>,
+    modifiers: Modifiers,
+    mouse_pressed: Option<MouseButton>,
+}
+
+impl WebFrameInputGenerator {
+    ///
+    /// Creates a new frame input generator for the given canvas.
+    ///
+    pub fn new(canvas: &HtmlCanvasElement) -> Self {
+        let mut generator = Self {
+            canvas: canvas.clone(),
+            last_time: Instant::now(),
+            first_frame: true,
+            events: Vec::new(),
+            accumulated_time: 0.0,
+            viewport: Viewport::new_at_origo(1, 1),
+            window_width: 1,
+            window_height: 1,
+            device_pixel_ratio: 1.0,
+            cursor_pos: None,
+            finger_id: None,
+            secondary_cursor_pos: None,
+            secondary_finger_id: None,
+            modifiers: Modifiers::default(),
+            mouse_pressed: None,
+        };
+        generator.update_size();
+        generator
+    }
+
+    ///
+    /// Resets the accumulated time to zero, for example when restarting a simulation without recreating the window.
+    /// The accumulated time of the next frame is then the elapsed time of that frame.
+    ///
+    pub fn reset(&mut self) {
+        self.set_accumulated_time(0.0);
+    }
+
+    ///
+    /// Sets the accumulated time in milliseconds, for example to replay a recorded sequence from a timestamp other than zero.
+    /// The accumulated time of the next frame is the given time plus the elapsed time of that frame.
+    ///
+    pub fn set_accumulated_time(&mut self, accumulated_time: f64) {
+        self.accumulated_time = accumulated_time;
+    }
+
+    ///
+    /// Generates [FrameInput] for a new frame. This should be called each frame and the generated data should only be used for one frame.
+    ///
+    pub fn generate(&mut self, context: &Context) -> FrameInput {
+        self.update_size();
+        let now = Instant::now();
+        let elapsed_time =
+            super::frame_io::elapsed_time(now.duration_since(self.last_time), self.first_frame);
+        self.accumulated_time += elapsed_time;
+        self.last_time = now;
+
+        let frame_input = FrameInput {
+            events: self.events.drain(..).collect(),
+            elapsed_time,
+            accumulated_time: self.accumulated_time,
+            viewport: self.viewport,
+            window_width: self.window_width,
+            window_height: self.window_height,
+            device_pixel_ratio: self.device_pixel_ratio as f32,
+            first_frame: self.first_frame,
+            context: context.clone(),
+        };
+        self.first_frame = false;
+        frame_input
+    }
+
+    ///
+    /// Handles a [MouseEvent] of the type `mousedown`, `mouseup`, `mousemove`, `mouseenter`/`mouseover` or `mouseleave`/`mouseout`.
+    /// Events of other types are ignored.
+    ///
+    /// Consider calling `prevent_default` on `mousedown` events to avoid selecting text on the page while dragging
+    /// and listen for `contextmenu` events and call `prevent_default` to be able to use the right mouse button.
+    ///
+    pub fn handle_mouse_event(&mut self, event: &MouseEvent) {
+        self.update_modifiers(
+            event.shift_key(),
+            event.ctrl_key(),
+            event.alt_key(),
+            event.meta_key(),
+        );
+        let position = self.position(event.client_x() as f32, event.client_y() as f32);
+        match event.type_().as_str() {
+            "mousedown" | "pointerdown" => {
+                if let Some(button) = mouse_button(event.button()) {
+                    self.mouse_pressed = Some(button);
+                    self.events.push(Event::MousePress {
+                        button,
+                        position,
+                        modifiers: self.modifiers,
+                        handled: false,
+                    });
+                }
+                self.cursor_pos = Some(position);
+            }
+            "mouseup" | "pointerup" => {
+                if let Some(button) = mouse_button(event.button()) {
+                    self.mouse_pressed = None;
+                    self.events.push(Event::MouseRelease {
+                        button,
+                        position,
+                        modifiers: self.modifiers,
+                        handled: false,
+                    });
+                }
+                self.cursor_pos = Some(position);
+            }
+            "mousemove" | "pointermove" => {
+                let delta = if let Some(last_pos) = self.cursor_pos {
+                    (position.x - last_pos.x, position.y - last_pos.y)
+                } else {
+                    (0.0, 0.0)
+                };
+                self.events.push(Event::MouseMotion {
+                    button: self.mouse_pressed,
+                    delta,
+                    position,
+                    modifiers: self.modifiers,
+                    handled: false,
+                });
+                self.cursor_pos = Some(position);
+            }
+            "mouseenter" | "mouseover" | "pointerenter" | "pointerover" => {
+                self.events.push(Event::MouseEnter);
+            }
+            "mouseleave" | "mouseout" | "pointerleave" | "pointerout" => {
+                self.mouse_pressed = None;
+                self.cursor_pos = None;
+                self.events.push(Event::MouseLeave);
+            }
+            _ => {}
+        }
+    }
+
+    ///
+    /// Handles a [PointerEvent] of the type `pointerdown`, `pointerup`, `pointercancel`, `pointermove`, `pointerenter`/`pointerover` or `pointerleave`/`pointerout`.
+    /// Mouse and pen input is handled like mouse events (see [WebFrameInputGenerator::handle_mouse_event]) and touch input like touch events (see [WebFrameInputGenerator::handle_touch_event]).
+    /// Pen input also generates [Event::PointerPressure] events with the pressure and tilt reported by the browser.
+    /// Events of other types are ignored.
+    ///
+    /// Set the `touch-action` CSS property of the canvas to `none`, otherwise the browser will cancel the pointer events when it starts scrolling or zooming the page.
+    /// Consider calling `set_pointer_capture` on `pointerdown` events to keep receiving events while dragging outside the canvas.
+    ///
+    pub fn handle_pointer_event(&mut self, event: &PointerEvent) {
+        if event.pointer_type() == "touch" {
+            self.update_modifiers(
+                event.shift_key(),
+                event.ctrl_key(),
+                event.alt_key(),
+                event.meta_key(),
+            );
+            let position = self.position(event.client_x() as f32, event.client_y() as f32);
+            match event.type_().as_str() {
+                "pointerdown" => self.touch_start(event.pointer_id(), position),
+                "pointerup" | "pointercancel" => self.touch_end(event.pointer_id(), position),
+                "pointermove" => self.touch_move(event.pointer_id(), position),
+                _ => {}
+            }
+        } else {
+            if event.pointer_type() == "pen"
+                && matches!(
+                    event.type_().as_str(),
+                    "pointerdown" | "pointerup" | "pointermove"
+                )
+            {
+                self.events.push(Event::PointerPressure {
+                    position: self.position(event.client_x() as f32, event.client_y() as f32),
+                    pressure: Some(event.pressure()),
+                    tilt: Some((event.tilt_x() as f32, event.tilt_y() as f32)),
+                    handled: false,
+                });
+            }
+            self.handle_mouse_event(event);
+        }
+    }
+
+    ///
+    /// Handles a [WheelEvent] of the type `wheel`. Events of other types are ignored.
+    ///
+    /// Call `prevent_default` on the event to avoid scrolling the page while scrolling on the canvas.
+    /// Note that the event listener must be registered as non-passive for this to work.
+    ///
+    pub fn handle_wheel_event(&mut self, event: &WheelEvent) {
+        if event.type_() != "wheel" {
+            return;
+        }
+        self.update_modifiers(
+            event.shift_key(),
+            event.ctrl_key(),
+            event.alt_key(),
+            event.meta_key(),
+        );
+        let position = self.position(event.client_x() as f32, event.client_y() as f32);
+        let scale = match event.delta_mode() {
+            WheelEvent::DOM_DELTA_LINE => 24.0,
+            WheelEvent::DOM_DELTA_PAGE => self.window_height as f32,
+            _ => 1.0,
+        };
+        // Positive delta in the DOM means scrolling down while it means scrolling up in the events
+        self.events.push(Event::MouseWheel {
+            delta: (
+                -event.delta_x() as f32 * scale,
+                -event.delta_y() as f32 * scale,
+            ),
+            position,
+            modifiers: self.modifiers,
+            handled: false,
+        });
+    }
+
+    ///
+    /// Handles a [KeyboardEvent] of the type `keydown` or `keyup`. Events of other types are ignored.
+    /// Printable characters also generate [Event::Text] events when pressed.
+    ///
+    /// The canvas only receives keyboard events when it has focus, so either set the `tabindex` attribute on the canvas or listen on the document instead.
+    /// Consider calling `prevent_default` on `keydown` events for keys that have a default action in the browser,
+    /// for example the arrow keys and space which scroll the page and tab which moves the focus.
+    ///
+    pub fn handle_keyboard_event(&mut self, event: &KeyboardEvent) {
+        self.update_modifiers(
+            event.shift_key(),
+            event.ctrl_key(),
+            event.alt_key(),
+            event.meta_key(),
+        );
+        let key = event.key();
+        match event.type_().as_str() {
+            "keydown" => {
+                if let Some(kind) = translate_key(&key) {
+                    self.events.push(Event::KeyPress {
+                        kind,
+                        modifiers: self.modifiers,
+                        handled: false,
+                    });
+                }
+                // Dead keys and keys pressed while composing text with an input method have names like "Dead" and "Process" instead of a single character,
+                // so only the composed character generates text. AltGr is reported as control and alt on Windows but is not a shortcut.
+                let mut chars = key.chars();
+                if let (Some(ch), None) = (chars.next(), chars.next()) {
+                    if !ch.is_control()
+                        && !event.is_composing()
+                        && (!self.modifiers.command || event.get_modifier_state("AltGraph"))
+                    {
+                        self.events.push(Event::Text(ch.to_string()));
+                    }
+                }
+            }
+            "keyup" => {
+                if let Some(kind) = translate_key(&key) {
+                    self.events.push(Event::KeyRelease {
+                        kind,
+                        modifiers: self.modifiers,
+                        handled: false,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    ///
+    /// Handles a [TouchEvent] of the type `touchstart`, `touchend`, `touchcancel` or `touchmove`. Events of other types are ignored.
+    /// The first finger acts as the left mouse button and moving two fingers closer or further apart generates [Event::MouseWheel] events.
+    ///
+    /// Call `prevent_default` on the events to avoid scrolling and zooming the page
+    /// and to avoid that the browser generates mouse events from the touch events.
+    /// Note that the event listeners must be registered as non-passive for this to work.
+    ///
+    pub fn handle_touch_event(&mut self, event: &TouchEvent) {
+        self.update_modifiers(
+            event.shift_key(),
+            event.ctrl_key(),
+            event.alt_key(),
+            event.meta_key(),
+        );
+        let touches = event.changed_touches();
+        let event_type = event.type_();
+        for i in 0..touches.length() {
+            if let Some(touch) = touches.get(i) {
+                let position = self.position(touch.client_x() as f32, touch.client_y() as f32);
+                match event_type.as_str() {
+                    "touchstart" => self.touch_start(touch.identifier(), position),
+                    "touchend" | "touchcancel" => self.touch_end(touch.identifier(), position),
+                    "touchmove" => self.touch_move(touch.identifier(), position),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    fn touch_start(&mut self, id: i32, position: LogicalPoint) {
+        if self.finger_id.is_none() {
+            self.events.push(Event::MousePress {
+                button: MouseButton::Left,
+                position,
+                modifiers: self.modifiers,
+                handled: false,
+            });
+            self.cursor_pos = Some(position);
+            self.finger_id = Some(id);
+        } else if self.secondary_finger_id.is_none() {
+            self.secondary_cursor_pos = Some(position);
+            self.secondary_finger_id = Some(id);
+        }
+    }
+
+    fn touch_end(&mut self, id: i32, position: LogicalPoint) {
+        if self.finger_id == Some(id) {
+            self.events.push(Event::MouseRelease {
+                button: MouseButton::Left,
+                position,
+                modifiers: self.modifiers,
+                handled: false,
+            });
+            self.cursor_pos = None;
+            self.finger_id = None;
+        } else if self.secondary_finger_id == Some(id) {
+            self.secondary_cursor_pos = None;
+            self.secondary_finger_id = None;
+        }
+    }
+
+    fn touch_move(&mut self, id: i32, position: LogicalPoint) {
+        if self.finger_id == Some(id) {
+            let last_pos = self.cursor_pos.unwrap();
+            if let Some(p) = self.secondary_cursor_pos {
+                self.events.push(Event::MouseWheel {
+                    position,
+                    modifiers: self.modifiers,
+                    handled: false,
+                    delta: (
+                        (position.x - p.x).abs() - (last_pos.x - p.x).abs(),
+                        (position.y - p.y).abs() - (last_pos.y - p.y).abs(),
+                    ),
+                });
+            } else {
+                self.events.push(Event::MouseMotion {
+                    button: Some(MouseButton::Left),
+                    position,
+                    modifiers: self.modifiers,
+                    handled: false,
+                    delta: (position.x - last_pos.x, position.y - last_pos.y),
+                });
+            }
+            self.cursor_pos = Some(position);
+        } else if self.secondary_finger_id == Some(id) {
+            let last_pos = self.secondary_cursor_pos.unwrap();
+            if let Some(p) = self.cursor_pos {
+                self.events.push(Event::MouseWheel {
+                    position: p,
+                    modifiers: self.modifiers,
+                    handled: false,
+                    delta: (
+                        (position.x - p.x).abs() - (last_pos.x - p.x).abs(),
+                        (position.y - p.y).abs() - (last_pos.y - p.y).abs(),
+                    ),
+                });
+            }
+            self.secondary_cursor_pos = Some(position);
+        }
+    }
+
+    fn update_size(&mut self) {
+        self.device_pixel_ratio = web_sys::window()
+            .map(|w| w.device_pixel_ratio())
+            .unwrap_or(1.0);
+        self.window_width = self.canvas.client_width().max(1) as u32;
+        self.window_height = self.canvas.client_height().max(1) as u32;
+        let width = (self.window_width as f64 * self.device_pixel_ratio).round() as u32;
+        let height = (self.window_height as f64 * self.device_pixel_ratio).round() as u32;
+        if self.canvas.width() != width {
+            self.canvas.set_width(width);
+        }
+        if self.canvas.height() != height {
+            self.canvas.set_height(height);
+        }
+        self.viewport = Viewport::new_at_origo(width, height);
+    }
+
+    fn position(&self, client_x: f32, client_y: f32) -> LogicalPoint {
+        let rect = self.canvas.get_bounding_client_rect();
+        LogicalPoint {
+            x: client_x - rect.left() as f32,
+            y: client_y - rect.top() as f32,
+            device_pixel_ratio: self.device_pixel_ratio as f32,
+            height: self.viewport.height as f32,
+        }
+    }
+
+    fn update_modifiers(&mut self, shift: bool, ctrl: bool, alt: bool, meta: bool) {
+        let modifiers = Modifiers {
+            alt,
+            ctrl,
+            shift,
+            command: ctrl || meta,
+        };
+        if modifiers != self.modifiers {
+            self.modifiers = modifiers;
+            self.events.push(Event::ModifiersChange { modifiers });
+        }
+    }
+}
+
+fn mouse_button(button: i16) -> Option<MouseButton> {
+    match button {
+        0 => Some(MouseButton::Left),
+        1 => Some(MouseButton::Middle),
+        2 => Some(MouseButton::Right),
+        _ => None,
+    }
+}
+
+fn translate_key(key: &str) -> Option<Key> {
+    Some(match key {
+        "ArrowDown" | "Down" => Key::ArrowDown,
+        "ArrowLeft" | "Left" => Key::ArrowLeft,
+        "ArrowRight" | "Right" => Key::ArrowRight,
+        "ArrowUp" | "Up" => Key::ArrowUp,
+
+        "Escape" | "Esc" => Key::Escape,
+        "Tab" => Key::Tab,
+        "Backspace" => Key::Backspace,
+        "Enter" => Key::Enter,
+        " " | "Spacebar" => Key::Space,
+
+        "Insert" => Key::Insert,
+        "Delete" | "Del" => Key::Delete,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+
+        "0" => Key::Num0,
+        "1" => Key::Num1,
+        "2" => Key::Num2,
+        "3" => Key::Num3,
+        "4" => Key::Num4,
+        "5" => Key::Num5,
+        "6" => Key::Num6,
+        "7" => Key::Num7,
+        "8" => Key::Num8,
+        "9" => Key::Num9,
+
+        "a" | "A" => Key::A,
+        "b" | "B" => Key::B,
+        "c" | "C" => Key::C,
+        "d" | "D" => Key::D,
+        "e" | "E" => Key::E,
+        "f" | "F" => Key::F,
+        "g" | "G" => Key::G,
+        "h" | "H" => Key::H,
+        "i" | "I" => Key::I,
+        "j" | "J" => Key::J,
+        "k" | "K" => Key::K,
+        "l" | "L" => Key::L,
+        "m" | "M" => Key::M,
+        "n" | "N" => Key::N,
+        "o" | "O" => Key::O,
+        "p" | "P" => Key::P,
+        "q" | "Q" => Key::Q,
+        "r" | "R" => Key::R,
+        "s" | "S" => Key::S,
+        "t" | "T" => Key::T,
+        "u" | "U" => Key::U,
+        "v" | "V" => Key::V,
+        "w" | "W" => Key::W,
+        "x" | "X" => Key::X,
+        "y" | "Y" => Key::Y,
+        "z" | "Z" => Key::Z,
+
+        "F1" => Key::F1,
+        "F2" => Key::F2,
+        "F3" => Key::F3,
+        "F4" => Key::F4,
+        "F5" => Key::F5,
+        "F6" => Key::F6,
+        "F7" => Key::F7,
+        "F8" => Key::F8,
+        "F9" => Key::F9,
+        "F10" => Key::F10,
+        "F11" => Key::F11,
+        "F12" => Key::F12,
+
+        _ => {
+            return None;
+        }
+    })
+}
@@ -3,13 +3,19 @@ use crate::core::{Context, CoreError, Viewport};
 use winit::event::{Event, WindowEvent};
 use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::WindowBuilder;
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::future::Future;
+#[cfg(not(target_arch = "wasm32"))]
+use std::pin::Pin;
+#[cfg(not(target_arch = "wasm32"))]
+use std::task::{Context as TaskContext, Poll};
 use winit::*;
 
 mod settings;
 pub use settings::*;
 
-mod frame_io;
-pub use frame_io::*;
+use super::{FrameInput, FrameOutput};
 
 mod frame_input_generator;
 pub use frame_input_generator::*;
@@ -17,6 +23,14 @@ pub use frame_input_generator::*;
 mod windowed_context;
 pub use windowed_context::*;
 
+#[cfg(not(target_arch = "wasm32"))]
+mod redraw_signal;
+#[cfg(not(target_arch = "wasm32"))]
+pub use redraw_signal::*;
+
+mod preserved_screen;
+use preserved_screen::PreservedScreen;
+
 use thiserror::Error;
 ///
 /// Error associated with a window.
@@ -75,6 +89,8 @@ pub struct Window {
     #[cfg(target_arch = "wasm32")]
     closure: wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>,
     gl: WindowedContext,
+    #[cfg(not(target_arch = "wasm32"))]
+    redraw_signal: RedrawSignal,
     #[allow(dead_code)]
     maximized: bool,
 }
@@ -97,13 +113,21 @@ impl Window {
     ) -> Result<Self, WindowError> {
         #[cfg(not(target_arch = "wasm32"))]
         let window_builder = {
+            #[allow(deprecated)]
+            let decorations = window_settings.decorations && !window_settings.borderless;
             let window_builder = WindowBuilder::new()
                 .with_title(&window_settings.title)
                 .with_min_inner_size(dpi::LogicalSize::new(
                     window_settings.min_size.0,
                     window_settings.min_size.1,
                 ))
-                .with_decorations(!window_settings.borderless);
+                .with_decorations(decorations)
+                .with_transparent(window_settings.transparent)
+                .with_window_level(if window_settings.always_on_top {
+                    window::WindowLevel::AlwaysOnTop
+                } else {
+                    window::WindowLevel::Normal
+                });
 
             if let Some((width, height)) = window_settings.max_size {
                 window_builder
@@ -157,10 +181,12 @@ impl Window {
         };
 
         let winit_window = window_builder.build(&event_loop)?;
+        let mut surface_settings = window_settings.surface_settings;
+        surface_settings.transparent |= window_settings.transparent;
         Self::from_winit_window(
             winit_window,
             event_loop,
-            window_settings.surface_settings,
+            surface_settings,
             window_settings.max_size.is_none(),
         )
     }
@@ -199,6 +225,8 @@ impl Window {
 
         Ok(Self {
             window: winit_window,
+            #[cfg(not(target_arch = "wasm32"))]
+            redraw_signal: RedrawSignal::new(event_loop.create_proxy()),
             event_loop,
             gl: gl?,
             #[cfg(target_arch = "wasm32")]
@@ -212,77 +240,132 @@ impl Window {
     ///
     pub fn render_loop<F: 'static + FnMut(FrameInput) -> FrameOutput>(self, mut callback: F) {
         let mut frame_input_generator = FrameInputGenerator::from_winit_window(&self.window);
+        let mut cursor_hittest = true;
+        let mut preserved_screen = PreservedScreen::new();
         self.event_loop
             .run(move |event, _, control_flow| match event {
                 Event::LoopDestroyed => {
                     #[cfg(target_arch = "wasm32")]
-                    {
-                        use wasm_bindgen::JsCast;
-                        use winit::platform::web::WindowExtWebSys;
-                        self.window
-                            .canvas()
-                            .remove_event_listener_with_callback(
-                                "contextmenu",
-                                self.closure.as_ref().unchecked_ref(),
-                            )
-                            .unwrap();
-                    }
+                    remove_context_menu_listener(&self.window, &self.closure);
                 }
-                Event::MainEventsCleared => {
+                Event::MainEventsCleared | Event::UserEvent(()) => {
                     self.window.request_redraw();
                 }
                 Event::RedrawRequested(_) => {
                     #[cfg(target_arch = "wasm32")]
                     if self.maximized || option_env!("THREE_D_SCREENSHOT").is_some() {
-                        use winit::platform::web::WindowExtWebSys;
-
-                        let html_canvas = self.window.canvas();
-                        let browser_window = html_canvas
-                            .owner_document()
-                            .and_then(|doc| doc.default_view())
-                            .or_else(web_sys::window)
-                            .unwrap();
-
-                        self.window.set_inner_size(dpi::LogicalSize {
-                            width: browser_window.inner_width().unwrap().as_f64().unwrap(),
-                            height: browser_window.inner_height().unwrap().as_f64().unwrap(),
-                        });
+                        fit_canvas_to_browser_window(&self.window);
                     }
 
+                    if frame_input_generator.is_minimized() {
+                        // Nothing can be rendered to a window with zero size, so wait until it is restored
+                        *control_flow = ControlFlow::Wait;
+                        return;
+                    }
                     let frame_input = frame_input_generator.generate(&self.gl);
+                    preserved_screen.begin_frame(&self.gl, frame_input.viewport);
                     let frame_output = callback(frame_input);
-                    if frame_output.exit {
-                        *control_flow = ControlFlow::Exit;
-                    } else {
-                        if frame_output.swap_buffers && option_env!("THREE_D_SCREENSHOT").is_none()
-                        {
-                            self.gl.swap_buffers().unwrap();
-                        }
-                        if frame_output.wait_next_event {
+                    handle_frame_output(
+                        &self.window,
+                        &self.gl,
+                        frame_output,
+                        &mut preserved_screen,
+                        &mut cursor_hittest,
+                        control_flow,
+                    );
+                }
+                Event::WindowEvent { ref event, .. } => {
+                    handle_window_event(&self.gl, &mut frame_input_generator, event, control_flow);
+                }
+                _ => (),
+            });
+    }
+
+    ///
+    /// Start the main render loop which calls the `callback` closure each frame and runs the returned future to completion before the frame is finished.
+    /// The future is polled on the main thread between the events from the window, so the window stays responsive while the future waits,
+    /// for example for data received over the network. The events that arrive in the meantime are given to the next frame.
+    /// The future is polled again when it is woken, so it can await any future that wakes its waker when it is ready,
+    /// for example a channel receiver from an async runtime like [tokio](https://crates.io/crates/tokio), which runs on other threads.
+    /// Not available on web, where futures are spawned on the browser event loop, for example using [wasm-bindgen-futures](https://crates.io/crates/wasm-bindgen-futures).
+    ///
+    /// The future is not required to be [Send], since it is always polled on the main thread, which means that it can own graphics resources.
+    /// Use a [RedrawSignal], see [Window::redraw_signal], to render a new frame when something happens while the render loop waits for the next event.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render_loop_async<F, Fut>(self, mut callback: F)
+    where
+        F: 'static + FnMut(FrameInput) -> Fut,
+        Fut: 'static + Future<Output = FrameOutput>,
+    {
+        let mut frame_input_generator = FrameInputGenerator::from_winit_window(&self.window);
+        let mut cursor_hittest = true;
+        let mut preserved_screen = PreservedScreen::new();
+        let waker = self.redraw_signal.waker();
+        let mut frame: Option<Pin<Box<dyn Future<Output = FrameOutput>>>> = None;
+        self.event_loop
+            .run(move |event, _, control_flow| match event {
+                Event::MainEventsCleared => {
+                    // While a frame is in progress, the frame is polled when it is woken instead
+                    if frame.is_none() {
+                        self.window.request_redraw();
+                    }
+                }
+                Event::UserEvent(()) => {
+                    self.window.request_redraw();
+                }
+                Event::RedrawRequested(_) => {
+                    if frame.is_none() {
+                        if frame_input_generator.is_minimized() {
+                            // Nothing can be rendered to a window with zero size, so wait until it is restored
                             *control_flow = ControlFlow::Wait;
-                        } else {
-                            *control_flow = ControlFlow::Poll;
-                            self.window.request_redraw();
+                            return;
                         }
+                        let frame_input = frame_input_generator.generate(&self.gl);
+                        preserved_screen.begin_frame(&self.gl, frame_input.viewport);
+                        frame = Some(Box::pin(callback(frame_input)));
                     }
-                }
-                Event::WindowEvent { ref event, .. } => {
-                    frame_input_generator.handle_winit_window_event(event);
-                    match event {
-                        WindowEvent::Resized(physical_size) => {
-                            self.gl.resize(*physical_size);
+
+                    let poll = frame
+                        .as_mut()
+                        .unwrap()
+                        .as_mut()
+                        .poll(&mut TaskContext::from_waker(&waker));
+                    match poll {
+                        Poll::Ready(frame_output) => {
+                            frame = None;
+                            handle_frame_output(
+                                &self.window,
+                                &self.gl,
+                                frame_output,
+                                &mut preserved_screen,
+                                &mut cursor_hittest,
+                                control_flow,
+                            );
                         }
-                        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
-                            self.gl.resize(**new_inner_size);
+                        Poll::Pending => {
+                            // Handle events until the future wakes the event loop
+                            *control_flow = ControlFlow::Wait;
                         }
-                        WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-                        _ => (),
                     }
                 }
+                Event::WindowEvent { ref event, .. } => {
+                    handle_window_event(&self.gl, &mut frame_input_generator, event, control_flow);
+                }
                 _ => (),
             });
     }
 
+    ///
+    /// Returns a [RedrawSignal] which makes the render loop render a new frame, also when the last [FrameOutput::wait_next_event] was true.
+    /// The signal can be sent from any thread, for example from an async task which receives data over the network.
+    /// Not available on web, which is single threaded.
+    ///
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn redraw_signal(&self) -> RedrawSignal {
+        self.redraw_signal.clone()
+    }
+
     ///
     /// Return the current logical size of the window.
     ///
@@ -315,3 +398,85 @@ impl Window {
         (*self.gl).clone()
     }
 }
+
+fn handle_frame_output(
+    window: &window::Window,
+    gl: &WindowedContext,
+    frame_output: FrameOutput,
+    preserved_screen: &mut PreservedScreen,
+    cursor_hittest: &mut bool,
+    control_flow: &mut ControlFlow,
+) {
+    if frame_output.exit {
+        *control_flow = ControlFlow::Exit;
+    } else {
+        if frame_output.cursor_hittest != *cursor_hittest {
+            // Not supported on all platforms, in which case the window keeps receiving mouse events
+            if window
+                .set_cursor_hittest(frame_output.cursor_hittest)
+                .is_ok()
+            {
+                *cursor_hittest = frame_output.cursor_hittest;
+            }
+        }
+        preserved_screen.end_frame(gl, frame_output.preserve_screen);
+        if frame_output.swap_buffers && option_env!("THREE_D_SCREENSHOT").is_none() {
+            gl.swap_buffers().unwrap();
+        }
+        if frame_output.wait_next_event {
+            *control_flow = ControlFlow::Wait;
+        } else {
+            *control_flow = ControlFlow::Poll;
+            window.request_redraw();
+        }
+    }
+}
+
+fn handle_window_event(
+    gl: &WindowedContext,
+    frame_input_generator: &mut FrameInputGenerator,
+    event: &WindowEvent,
+    control_flow: &mut ControlFlow,
+) {
+    frame_input_generator.handle_winit_window_event(event);
+    match event {
+        WindowEvent::Resized(physical_size) => {
+            gl.resize(*physical_size);
+        }
+        WindowEvent::ScaleFactorChanged { new_inner_size, .. } => {
+            gl.resize(**new_inner_size);
+        }
+        WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+        _ => (),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn fit_canvas_to_browser_window(window: &window::Window) {
+    use winit::platform::web::WindowExtWebSys;
+
+    let html_canvas = window.canvas();
+    let browser_window = html_canvas
+        .owner_document()
+        .and_then(|doc| doc.default_view())
+        .or_else(web_sys::window)
+        .unwrap();
+
+    window.set_inner_size(dpi::LogicalSize {
+        width: browser_window.inner_width().unwrap().as_f64().unwrap(),
+        height: browser_window.inner_height().unwrap().as_f64().unwrap(),
+    });
+}
+
+#[cfg(target_arch = "wasm32")]
+fn remove_context_menu_listener(
+    window: &window::Window,
+    closure: &wasm_bindgen::closure::Closure<dyn FnMut(web_sys::Event)>,
+) {
+    use wasm_bindgen::JsCast;
+    use winit::platform::web::WindowExtWebSys;
+    window
+        .canvas()
+        .remove_event_listener_with_callback("contextmenu", closure.as_ref().unchecked_ref())
+        .unwrap();
+}
@@ -5,10 +5,113 @@ use crate::core::*;
 use instant::Instant;
 #[cfg(not(target_arch = "wasm32"))]
 use std::time::Instant;
-use winit::dpi::PhysicalSize;
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::event::Ime;
 use winit::event::TouchPhase;
 use winit::event::WindowEvent;
 
+///
+/// Configuration of how a [FrameInputGenerator] translates the window events into [Event]s.
+/// More options might be added in the future, so start from [FrameInputConfig::default] and change the fields that should differ.
+///
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct FrameInputConfig {
+    /// The number of logical pixels scrolled per line when the platform reports the mouse wheel movement in lines instead of pixels.
+    /// The default is 24.
+    pub line_scroll_px: f32,
+    /// Whether to generate an [Event::PointerPressure] event for every movement of a pen or finger.
+    /// Otherwise only the latest pressure of the movements since the last press or release is kept each frame.
+    /// The events at a press or release are always generated.
+    /// The default is false.
+    pub high_frequency_pointer: bool,
+    /// Translates the mouse input into touch input when set, which makes it possible to test touch interactions on a desktop, see [TouchEmulation].
+    /// The default is `None`.
+    pub touch_emulation: Option<TouchEmulation>,
+}
+
+impl Default for FrameInputConfig {
+    fn default() -> Self {
+        Self {
+            line_scroll_px: 24.0,
+            high_frequency_pointer: false,
+            touch_emulation: None,
+        }
+    }
+}
+
+///
+/// The modifier key which adds a second finger when emulating touch input, see [TouchEmulation].
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum TouchEmulationModifier {
+    /// The alt key.
+    #[default]
+    Alt,
+    /// The shift key.
+    Shift,
+    /// The control key.
+    Ctrl,
+}
+
+impl TouchEmulationModifier {
+    fn matches(&self, keycode: winit::event::VirtualKeyCode) -> bool {
+        use winit::event::VirtualKeyCode;
+        match self {
+            Self::Alt => keycode == VirtualKeyCode::LAlt || keycode == VirtualKeyCode::RAlt,
+            Self::Shift => keycode == VirtualKeyCode::LShift || keycode == VirtualKeyCode::RShift,
+            Self::Ctrl => {
+                keycode == VirtualKeyCode::LControl || keycode == VirtualKeyCode::RControl
+            }
+        }
+    }
+}
+
+///
+/// Configuration of the touch emulation of a [FrameInputGenerator], see [FrameInputConfig::touch_emulation].
+///
+/// When enabled, the mouse input is translated into touch input before it is translated into [Event]s,
+/// so the events are the same as the events generated by a touch screen.
+/// Dragging with the left mouse button moves one finger.
+/// Holding down the [TouchEmulation::modifier] places an anchor at the cursor position and adds a second finger at the position mirrored around the anchor,
+/// so dragging away from or towards the anchor pinches and dragging around the anchor twists.
+/// The other mouse buttons and the mouse movement when no button is pressed are ignored, since a touch screen has no equivalent,
+/// while the mouse wheel and the keyboard still work as usual.
+/// Use a [TouchOverlay](crate::renderer::TouchOverlay) to show the emulated fingers.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct TouchEmulation {
+    /// The modifier key which adds the second finger while it is held down.
+    /// The default is [TouchEmulationModifier::Alt].
+    pub modifier: TouchEmulationModifier,
+}
+
+/// The id of the first and second emulated finger.
+const EMULATED_FINGER_IDS: [u64; 2] = [0, 1];
+
+///
+/// The state of the touch emulation, where the positions are in physical pixels like the positions of real touch events.
+///
+#[derive(Default)]
+struct EmulatedTouchState {
+    cursor: Option<PhysicalPosition<f64>>,
+    anchor: Option<PhysicalPosition<f64>>,
+    fingers: [bool; 2],
+}
+
+impl EmulatedTouchState {
+    fn finger_position(&self, finger: usize) -> Option<PhysicalPosition<f64>> {
+        let cursor = self.cursor?;
+        if finger == 0 {
+            Some(cursor)
+        } else {
+            self.anchor.map(|anchor| {
+                PhysicalPosition::new(2.0 * anchor.x - cursor.x, 2.0 * anchor.y - cursor.y)
+            })
+        }
+    }
+}
+
 ///
 /// Use this to generate [FrameInput] for a new frame with a custom [winit](https://crates.io/crates/winit) window.
 /// [FrameInput] is automatically generated if using the default [Window](crate::window::Window).
@@ -28,13 +131,19 @@ pub struct FrameInputGenerator {
     secondary_finger_id: Option<u64>,
     modifiers: Modifiers,
     mouse_pressed: Option<MouseButton>,
+    pending_pressure: Option<usize>,
+    config: FrameInputConfig,
+    is_macos: bool,
+    ime_composing: bool,
+    ime_commit: String,
+    emulated_touch: EmulatedTouchState,
 }
 
 impl FrameInputGenerator {
     ///
     /// Creates a new frame input generator.
     ///
-    fn new(size: PhysicalSize<u32>, device_pixel_ratio: f64) -> Self {
+    fn new(size: PhysicalSize<u32>, device_pixel_ratio: f64, config: FrameInputConfig) -> Self {
         let (window_width, window_height): (u32, u32) =
             size.to_logical::<f32>(device_pixel_ratio).into();
         Self {
@@ -52,6 +161,12 @@ impl FrameInputGenerator {
             secondary_finger_id: None,
             modifiers: Modifiers::default(),
             mouse_pressed: None,
+            pending_pressure: None,
+            config,
+            is_macos: cfg!(target_os = "macos"),
+            ime_composing: false,
+            ime_commit: String::new(),
+            emulated_touch: EmulatedTouchState::default(),
         }
     }
 
@@ -59,7 +174,47 @@ impl FrameInputGenerator {
     /// Creates a new frame input generator from a [winit](https://crates.io/crates/winit) window.
     ///
     pub fn from_winit_window(window: &winit::window::Window) -> Self {
-        Self::new(window.inner_size(), window.scale_factor())
+        Self::from_winit_window_with_config(window, FrameInputConfig::default())
+    }
+
+    ///
+    /// Creates a new frame input generator from a [winit](https://crates.io/crates/winit) window with the given configuration.
+    ///
+    pub fn from_winit_window_with_config(
+        window: &winit::window::Window,
+        config: FrameInputConfig,
+    ) -> Self {
+        Self::new(window.inner_size(), window.scale_factor(), config)
+    }
+
+    ///
+    /// Returns the configuration used for translating the window events.
+    ///
+    pub fn config(&self) -> &FrameInputConfig {
+        &self.config
+    }
+
+    ///
+    /// Sets the configuration used for translating the window events from now on.
+    ///
+    pub fn set_config(&mut self, config: FrameInputConfig) {
+        self.config = config;
+    }
+
+    ///
+    /// Resets the accumulated time to zero, for example when restarting a simulation without recreating the window.
+    /// The accumulated time of the next frame is then the elapsed time of that frame.
+    ///
+    pub fn reset(&mut self) {
+        self.set_accumulated_time(0.0);
+    }
+
+    ///
+    /// Sets the accumulated time in milliseconds, for example to replay a recorded sequence from a timestamp other than zero.
+    /// The accumulated time of the next frame is the given time plus the elapsed time of that frame.
+    ///
+    pub fn set_accumulated_time(&mut self, accumulated_time: f64) {
+        self.accumulated_time = accumulated_time;
     }
 
     ///
@@ -67,12 +222,15 @@ impl FrameInputGenerator {
     ///
     pub fn generate(&mut self, context: &Context) -> FrameInput {
         let now = Instant::now();
-        let duration = now.duration_since(self.last_time);
-        let elapsed_time =
-            duration.as_secs() as f64 * 1000.0 + duration.subsec_nanos() as f64 * 1e-6;
+        let elapsed_time = crate::window::frame_io::elapsed_time(
+            now.duration_since(self.last_time),
+            self.first_frame,
+        );
         self.accumulated_time += elapsed_time;
         self.last_time = now;
 
+        self.pending_pressure = None;
+        self.ime_commit.clear();
         let frame_input = FrameInput {
             events: self.events.drain(..).collect(),
             elapsed_time,
@@ -92,18 +250,16 @@ impl FrameInputGenerator {
                 #[cfg(feature = "image")]
                 if let Some(path) = option_env!("THREE_D_SCREENSHOT") {
                     let pixels = frame_input.screen().read_color::<[u8; 4]>();
-                    let img = image::DynamicImage::ImageRgba8(
-                        image::ImageBuffer::from_raw(
-                            frame_input.viewport.width,
-                            frame_input.viewport.height,
-                            pixels.into_iter().flatten().collect::<Vec<_>>(),
-                        )
-                        .unwrap(),
-                    );
-                    img.resize(
+                    let img = image::RgbaImage::from_raw(
+                        frame_input.viewport.width,
+                        frame_input.viewport.height,
+                        pixels.into_iter().flatten().collect::<Vec<_>>(),
+                    )
+                    .unwrap();
+                    resize_linear_correct(
+                        &img,
                         frame_input.window_width,
                         frame_input.window_height,
-                        image::imageops::FilterType::Triangle,
                     )
                     .save(path)
                     .unwrap();
@@ -114,10 +270,26 @@ impl FrameInputGenerator {
         frame_input
     }
 
+    ///
+    /// Returns true if the window is minimized or for some other reason has zero width or height, see [FrameInput::is_minimized].
+    ///
+    pub fn is_minimized(&self) -> bool {
+        self.viewport.width == 0 || self.viewport.height == 0
+    }
+
     ///
     /// Handle the [WindowEvent] generated by a [winit](https://crates.io/crates/winit) event loop.
     ///
+    /// Typed characters, including characters composed using dead keys or typed with AltGr, generate one [Event::Text] each.
+    /// If the input method is allowed on the window (see `Window::set_ime_allowed` in winit), the committed text generates one [Event::Text]
+    /// and the characters some platforms also send for the same text are ignored.
+    ///
     pub fn handle_winit_window_event(&mut self, event: &WindowEvent) {
+        if let Some(emulation) = self.config.touch_emulation {
+            if self.emulate_touch(emulation, event) {
+                return;
+            }
+        }
         match event {
             WindowEvent::Resized(physical_size) => {
                 self.viewport = Viewport::new_at_origo(physical_size.width, physical_size.height);
@@ -157,7 +329,7 @@ impl FrameInputGenerator {
                         || keycode == VirtualKeyCode::RControl
                     {
                         self.modifiers.ctrl = state;
-                        if !cfg!(target_os = "macos") {
+                        if !self.is_macos {
                             self.modifiers.command = state;
                         }
                         self.events.push(crate::Event::ModifiersChange {
@@ -175,7 +347,7 @@ impl FrameInputGenerator {
                             modifiers: self.modifiers,
                         });
                     } else if (keycode == VirtualKeyCode::LWin || keycode == VirtualKeyCode::RWin)
-                        && cfg!(target_os = "macos")
+                        && self.is_macos
                     {
                         self.modifiers.command = state;
                         self.events.push(crate::Event::ModifiersChange {
@@ -185,57 +357,55 @@ impl FrameInputGenerator {
                 }
             }
             WindowEvent::MouseWheel { delta, .. } => {
-                if let Some(position) = self.cursor_pos {
-                    match delta {
-                        winit::event::MouseScrollDelta::LineDelta(x, y) => {
-                            let line_height = 24.0; // TODO
-                            self.events.push(crate::Event::MouseWheel {
-                                delta: (*x * line_height, *y * line_height),
+                let position = self.cursor_position();
+                match delta {
+                    winit::event::MouseScrollDelta::LineDelta(x, y) => {
+                        let line_height = self.config.line_scroll_px;
+                        self.events.push(crate::Event::MouseWheel {
+                            delta: (*x * line_height, *y * line_height),
+                            position,
+                            modifiers: self.modifiers,
+                            handled: false,
+                        });
+                    }
+                    winit::event::MouseScrollDelta::PixelDelta(delta) => {
+                        let d = delta.to_logical(self.device_pixel_ratio);
+                        self.events.push(crate::Event::MouseWheel {
+                            delta: (d.x, d.y),
+                            position,
+                            modifiers: self.modifiers,
+                            handled: false,
+                        });
+                    }
+                }
+            }
+            WindowEvent::MouseInput { state, button, .. } => {
+                let position = self.cursor_position();
+                let button = match button {
+                    winit::event::MouseButton::Left => Some(crate::MouseButton::Left),
+                    winit::event::MouseButton::Middle => Some(crate::MouseButton::Middle),
+                    winit::event::MouseButton::Right => Some(crate::MouseButton::Right),
+                    _ => None,
+                };
+                if let Some(b) = button {
+                    self.events
+                        .push(if *state == winit::event::ElementState::Pressed {
+                            self.mouse_pressed = Some(b);
+                            crate::Event::MousePress {
+                                button: b,
                                 position,
                                 modifiers: self.modifiers,
                                 handled: false,
-                            });
-                        }
-                        winit::event::MouseScrollDelta::PixelDelta(delta) => {
-                            let d = delta.to_logical(self.device_pixel_ratio);
-                            self.events.push(crate::Event::MouseWheel {
-                                delta: (d.x, d.y),
+                            }
+                        } else {
+                            self.mouse_pressed = None;
+                            crate::Event::MouseRelease {
+                                button: b,
                                 position,
                                 modifiers: self.modifiers,
                                 handled: false,
-                            });
-                        }
-                    }
-                }
-            }
-            WindowEvent::MouseInput { state, button, .. } => {
-                if let Some(position) = self.cursor_pos {
-                    let button = match button {
-                        winit::event::MouseButton::Left => Some(crate::MouseButton::Left),
-                        winit::event::MouseButton::Middle => Some(crate::MouseButton::Middle),
-                        winit::event::MouseButton::Right => Some(crate::MouseButton::Right),
-                        _ => None,
-                    };
-                    if let Some(b) = button {
-                        self.events
-                            .push(if *state == winit::event::ElementState::Pressed {
-                                self.mouse_pressed = Some(b);
-                                crate::Event::MousePress {
-                                    button: b,
-                                    position,
-                                    modifiers: self.modifiers,
-                                    handled: false,
-                                }
-                            } else {
-                                self.mouse_pressed = None;
-                                crate::Event::MouseRelease {
-                                    button: b,
-                                    position,
-                                    modifiers: self.modifiers,
-                                    handled: false,
-                                }
-                            });
-                    }
+                            }
+                        });
                 }
             }
             WindowEvent::CursorMoved { position, .. } => {
@@ -261,10 +431,30 @@ impl FrameInputGenerator {
                 self.cursor_pos = Some(position);
             }
             WindowEvent::ReceivedCharacter(ch) => {
-                if is_printable_char(*ch) && !self.modifiers.ctrl && !self.modifiers.command {
+                if self.ime_commit.starts_with(*ch) {
+                    // Some platforms also send the text committed by the input method as characters
+                    self.ime_commit.drain(..ch.len_utf8());
+                } else if !self.ime_composing && is_printable_char(*ch) && !self.is_shortcut() {
+                    self.ime_commit.clear();
                     self.events.push(crate::Event::Text(ch.to_string()));
                 }
             }
+            WindowEvent::Ime(ime) => match ime {
+                Ime::Preedit(text, _) => {
+                    self.ime_composing = !text.is_empty();
+                }
+                Ime::Commit(text) => {
+                    self.ime_composing = false;
+                    if !text.is_empty() {
+                        self.events.push(crate::Event::Text(text.clone()));
+                        self.ime_commit = text.clone();
+                    }
+                }
+                Ime::Enabled | Ime::Disabled => {
+                    self.ime_composing = false;
+                    self.ime_commit.clear();
+                }
+            },
             WindowEvent::CursorEntered { .. } => {
                 self.events.push(crate::Event::MouseEnter);
             }
@@ -272,97 +462,281 @@ impl FrameInputGenerator {
                 self.mouse_pressed = None;
                 self.events.push(crate::Event::MouseLeave);
             }
-            WindowEvent::Touch(touch) => {
-                let position = touch.location.to_logical::<f32>(self.device_pixel_ratio);
-                let position = LogicalPoint {
-                    x: position.x,
-                    y: position.y,
-                    device_pixel_ratio: self.device_pixel_ratio as f32,
-                    height: self.viewport.height as f32,
-                };
-                match touch.phase {
-                    TouchPhase::Started => {
-                        if self.finger_id.is_none() {
-                            self.events.push(crate::Event::MousePress {
-                                button: MouseButton::Left,
-                                position,
-                                modifiers: self.modifiers,
-                                handled: false,
-                            });
-                            self.cursor_pos = Some(position);
-                            self.finger_id = Some(touch.id);
-                        } else if self.secondary_finger_id.is_none() {
-                            self.secondary_cursor_pos = Some(position);
-                            self.secondary_finger_id = Some(touch.id);
-                        }
+            WindowEvent::Touch(touch) => self.handle_touch(touch),
+            _ => (),
+        }
+    }
+
+    ///
+    /// Translates the mouse input into touch input, see [TouchEmulation]. Returns true if the event is consumed.
+    ///
+    fn emulate_touch(&mut self, emulation: TouchEmulation, event: &WindowEvent) -> bool {
+        match event {
+            WindowEvent::CursorMoved {
+                device_id,
+                position,
+                ..
+            } => {
+                self.emulated_touch.cursor = Some(*position);
+                for finger in 0..2 {
+                    if self.emulated_touch.fingers[finger] {
+                        self.emit_emulated_touch(*device_id, finger, TouchPhase::Moved);
                     }
-                    TouchPhase::Ended | TouchPhase::Cancelled => {
-                        if self.finger_id.map(|id| id == touch.id).unwrap_or(false) {
-                            self.events.push(crate::Event::MouseRelease {
-                                button: MouseButton::Left,
-                                position,
-                                modifiers: self.modifiers,
-                                handled: false,
-                            });
-                            self.cursor_pos = None;
-                            self.finger_id = None;
-                        } else if self
-                            .secondary_finger_id
-                            .map(|id| id == touch.id)
-                            .unwrap_or(false)
-                        {
-                            self.secondary_cursor_pos = None;
-                            self.secondary_finger_id = None;
+                }
+                true
+            }
+            WindowEvent::MouseInput {
+                device_id,
+                state,
+                button,
+                ..
+            } => {
+                if *button == winit::event::MouseButton::Left {
+                    if *state == winit::event::ElementState::Pressed {
+                        if self.emulated_touch.cursor.is_none() {
+                            self.emulated_touch.cursor = Some(self.window_center());
                         }
-                    }
-                    TouchPhase::Moved => {
-                        if self.finger_id.map(|id| id == touch.id).unwrap_or(false) {
-                            let last_pos = self.cursor_pos.unwrap();
-                            if let Some(p) = self.secondary_cursor_pos {
-                                self.events.push(crate::Event::MouseWheel {
-                                    position,
-                                    modifiers: self.modifiers,
-                                    handled: false,
-                                    delta: (
-                                        (position.x - p.x).abs() - (last_pos.x - p.x).abs(),
-                                        (position.y - p.y).abs() - (last_pos.y - p.y).abs(),
-                                    ),
-                                });
-                            } else {
-                                self.events.push(crate::Event::MouseMotion {
-                                    button: Some(MouseButton::Left),
-                                    position,
-                                    modifiers: self.modifiers,
-                                    handled: false,
-                                    delta: (position.x - last_pos.x, position.y - last_pos.y),
-                                });
+                        self.emit_emulated_touch(*device_id, 0, TouchPhase::Started);
+                        if self.emulated_touch.anchor.is_some() {
+                            self.emit_emulated_touch(*device_id, 1, TouchPhase::Started);
+                        }
+                    } else {
+                        for finger in [1, 0] {
+                            if self.emulated_touch.fingers[finger] {
+                                self.emit_emulated_touch(*device_id, finger, TouchPhase::Ended);
                             }
-                            self.cursor_pos = Some(position);
-                        } else if self
-                            .secondary_finger_id
-                            .map(|id| id == touch.id)
-                            .unwrap_or(false)
-                        {
-                            let last_pos = self.secondary_cursor_pos.unwrap();
-                            if let Some(p) = self.cursor_pos {
-                                self.events.push(crate::Event::MouseWheel {
-                                    position: p,
-                                    modifiers: self.modifiers,
-                                    handled: false,
-                                    delta: (
-                                        (position.x - p.x).abs() - (last_pos.x - p.x).abs(),
-                                        (position.y - p.y).abs() - (last_pos.y - p.y).abs(),
-                                    ),
-                                });
+                        }
+                    }
+                }
+                true
+            }
+            WindowEvent::KeyboardInput {
+                device_id, input, ..
+            } => {
+                if input
+                    .virtual_keycode
+                    .map(|keycode| emulation.modifier.matches(keycode))
+                    .unwrap_or(false)
+                {
+                    if input.state == winit::event::ElementState::Pressed {
+                        if self.emulated_touch.anchor.is_none() {
+                            self.emulated_touch.anchor =
+                                Some(self.emulated_touch.cursor.unwrap_or(self.window_center()));
+                            if self.emulated_touch.fingers[0] {
+                                self.emit_emulated_touch(*device_id, 1, TouchPhase::Started);
                             }
-                            self.secondary_cursor_pos = Some(position);
                         }
+                    } else {
+                        if self.emulated_touch.fingers[1] {
+                            self.emit_emulated_touch(*device_id, 1, TouchPhase::Ended);
+                        }
+                        self.emulated_touch.anchor = None;
                     }
                 }
+                // The modifiers are still updated
+                false
             }
-            _ => (),
+            WindowEvent::CursorEntered { .. } | WindowEvent::CursorLeft { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn emit_emulated_touch(
+        &mut self,
+        device_id: winit::event::DeviceId,
+        finger: usize,
+        phase: TouchPhase,
+    ) {
+        if let Some(location) = self.emulated_touch.finger_position(finger) {
+            self.emulated_touch.fingers[finger] =
+                phase == TouchPhase::Started || phase == TouchPhase::Moved;
+            self.handle_touch(&winit::event::Touch {
+                device_id,
+                phase,
+                location,
+                force: None,
+                id: EMULATED_FINGER_IDS[finger],
+            });
         }
     }
+
+    ///
+    /// Returns the positions of the emulated fingers which currently touch the screen, see [FrameInputConfig::touch_emulation].
+    /// The first position is the position of the first finger, which follows the cursor.
+    ///
+    pub fn emulated_touches(&self) -> Vec<LogicalPoint> {
+        (0..2)
+            .filter(|finger| self.emulated_touch.fingers[*finger])
+            .filter_map(|finger| self.emulated_touch.finger_position(finger))
+            .map(|position| self.logical_point(position))
+            .collect()
+    }
+
+    ///
+    /// Returns the position of the anchor which the second emulated finger is mirrored around while the [TouchEmulation::modifier] is held down,
+    /// or `None` if the modifier is not held down, see [FrameInputConfig::touch_emulation].
+    ///
+    pub fn emulated_touch_anchor(&self) -> Option<LogicalPoint> {
+        self.emulated_touch
+            .anchor
+            .map(|anchor| self.logical_point(anchor))
+    }
+
+    fn window_center(&self) -> PhysicalPosition<f64> {
+        PhysicalPosition::new(
+            0.5 * self.viewport.width as f64,
+            0.5 * self.viewport.height as f64,
+        )
+    }
+
+    fn logical_point(&self, position: PhysicalPosition<f64>) -> LogicalPoint {
+        let position = position.to_logical::<f32>(self.device_pixel_ratio);
+        LogicalPoint {
+            x: position.x,
+            y: position.y,
+            device_pixel_ratio: self.device_pixel_ratio as f32,
+            height: self.viewport.height as f32,
+        }
+    }
+
+    ///
+    /// Handles a touch event, either from the window or emulated using the mouse (see [FrameInputConfig::touch_emulation]).
+    /// The first finger generates mouse events with the left button and moving a second finger generates mouse wheel events.
+    ///
+    fn handle_touch(&mut self, touch: &winit::event::Touch) {
+        let position = self.logical_point(touch.location);
+        let pressure = touch.force.map(|force| force.normalized() as f32);
+        match touch.phase {
+            TouchPhase::Started => {
+                if self.finger_id.is_none() {
+                    self.push_pressure(position, pressure, false);
+                    self.events.push(crate::Event::MousePress {
+                        button: MouseButton::Left,
+                        position,
+                        modifiers: self.modifiers,
+                        handled: false,
+                    });
+                    self.cursor_pos = Some(position);
+                    self.finger_id = Some(touch.id);
+                } else if self.secondary_finger_id.is_none() {
+                    self.secondary_cursor_pos = Some(position);
+                    self.secondary_finger_id = Some(touch.id);
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                if self.finger_id.map(|id| id == touch.id).unwrap_or(false) {
+                    self.push_pressure(position, pressure, false);
+                    self.events.push(crate::Event::MouseRelease {
+                        button: MouseButton::Left,
+                        position,
+                        modifiers: self.modifiers,
+                        handled: false,
+                    });
+                    self.cursor_pos = None;
+                    self.finger_id = None;
+                } else if self
+                    .secondary_finger_id
+                    .map(|id| id == touch.id)
+                    .unwrap_or(false)
+                {
+                    self.secondary_cursor_pos = None;
+                    self.secondary_finger_id = None;
+                }
+            }
+            TouchPhase::Moved => {
+                if self.finger_id.map(|id| id == touch.id).unwrap_or(false) {
+                    let last_pos = self.cursor_pos.unwrap();
+                    if let Some(p) = self.secondary_cursor_pos {
+                        self.events.push(crate::Event::MouseWheel {
+                            position,
+                            modifiers: self.modifiers,
+                            handled: false,
+                            delta: (
+                                (position.x - p.x).abs() - (last_pos.x - p.x).abs(),
+                                (position.y - p.y).abs() - (last_pos.y - p.y).abs(),
+                            ),
+                        });
+                    } else {
+                        self.push_pressure(position, pressure, true);
+                        self.events.push(crate::Event::MouseMotion {
+                            button: Some(MouseButton::Left),
+                            position,
+                            modifiers: self.modifiers,
+                            handled: false,
+                            delta: (position.x - last_pos.x, position.y - last_pos.y),
+                        });
+                    }
+                    self.cursor_pos = Some(position);
+                } else if self
+                    .secondary_finger_id
+                    .map(|id| id == touch.id)
+                    .unwrap_or(false)
+                {
+                    let last_pos = self.secondary_cursor_pos.unwrap();
+                    if let Some(p) = self.cursor_pos {
+                        self.events.push(crate::Event::MouseWheel {
+                            position: p,
+                            modifiers: self.modifiers,
+                            handled: false,
+                            delta: (
+                                (position.x - p.x).abs() - (last_pos.x - p.x).abs(),
+                                (position.y - p.y).abs() - (last_pos.y - p.y).abs(),
+                            ),
+                        });
+                    }
+                    self.secondary_cursor_pos = Some(position);
+                }
+            }
+        }
+    }
+
+    ///
+    /// Returns the last known cursor position, which is kept when the cursor leaves the window, or the center of the window if the position is not known yet,
+    /// for example when the mouse wheel or a trackpad tap is the first input after the window is created or after the last finger is lifted.
+    ///
+    fn cursor_position(&self) -> LogicalPoint {
+        self.cursor_pos.unwrap_or(LogicalPoint {
+            x: 0.5 * self.window_width as f32,
+            y: 0.5 * self.window_height as f32,
+            device_pixel_ratio: self.device_pixel_ratio as f32,
+            height: self.viewport.height as f32,
+        })
+    }
+
+    ///
+    /// Returns true if the modifiers that are down turn a key press into a shortcut instead of text.
+    /// On Windows, the AltGr key is reported as both control and alt, so characters typed with control and alt down are text,
+    /// which is also the case for control alt combinations on Linux since they do not produce printable characters.
+    ///
+    fn is_shortcut(&self) -> bool {
+        if self.is_macos {
+            self.modifiers.ctrl || self.modifiers.command
+        } else {
+            self.modifiers.ctrl && !self.modifiers.alt
+        }
+    }
+
+    ///
+    /// Pushes an [Event::PointerPressure] event. The event of a movement replaces the previous one from a movement since the last press or release
+    /// unless [FrameInputConfig::high_frequency_pointer] is enabled, so the events are still ordered correctly relative to the press and release.
+    /// Winit doesn't report the tilt, only the altitude angle of the pen on some platforms.
+    ///
+    fn push_pressure(&mut self, position: LogicalPoint, pressure: Option<f32>, is_motion: bool) {
+        if is_motion && !self.config.high_frequency_pointer {
+            if let Some(index) = self.pending_pressure {
+                self.events.remove(index);
+            }
+            self.pending_pressure = Some(self.events.len());
+        } else {
+            self.pending_pressure = None;
+        }
+        self.events.push(crate::Event::PointerPressure {
+            position,
+            pressure,
+            tilt: None,
+            handled: false,
+        });
+    }
 }
 
 fn is_printable_char(chr: char) -> bool {
@@ -433,8 +807,386 @@ fn translate_virtual_key_code(key: winit::event::VirtualKeyCode) -> Option<crate
         Y => Key::Y,
         Z => Key::Z,
 
+        F1 => Key::F1,
+        F2 => Key::F2,
+        F3 => Key::F3,
+        F4 => Key::F4,
+        F5 => Key::F5,
+        F6 => Key::F6,
+        F7 => Key::F7,
+        F8 => Key::F8,
+        F9 => Key::F9,
+        F10 => Key::F10,
+        F11 => Key::F11,
+        F12 => Key::F12,
+
         _ => {
             return None;
         }
     })
 }
+
+///
+/// Resizes the sRGB encoded image to the given size like [image::imageops::resize] with a triangle filter,
+/// except that the filtering is done on linear colors, since averaging the sRGB encoded colors directly darkens the mid-tones.
+///
+#[cfg(all(feature = "image", not(target_arch = "wasm32")))]
+fn resize_linear_correct(img: &image::RgbaImage, width: u32, height: u32) -> image::RgbaImage {
+    let linear = image::Rgba32FImage::from_fn(img.width(), img.height(), |x, y| {
+        let p = img.get_pixel(x, y).0;
+        image::Rgba([
+            linear_from_srgb(p[0]),
+            linear_from_srgb(p[1]),
+            linear_from_srgb(p[2]),
+            p[3] as f32 / 255.0,
+        ])
+    });
+    let resized = image::imageops::resize(
+        &linear,
+        width,
+        height,
+        image::imageops::FilterType::Triangle,
+    );
+    image::RgbaImage::from_fn(width, height, |x, y| {
+        let p = resized.get_pixel(x, y).0;
+        image::Rgba([
+            srgb_from_linear(p[0]),
+            srgb_from_linear(p[1]),
+            srgb_from_linear(p[2]),
+            (p[3].clamp(0.0, 1.0) * 255.0).round() as u8,
+        ])
+    })
+}
+
+#[cfg(all(feature = "image", not(target_arch = "wasm32")))]
+fn linear_from_srgb(value: u8) -> f32 {
+    let value = value as f32 / 255.0;
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+#[cfg(all(feature = "image", not(target_arch = "wasm32")))]
+fn srgb_from_linear(value: f32) -> u8 {
+    let value = value.clamp(0.0, 1.0);
+    let srgb = if value <= 0.0031308 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    };
+    (srgb * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_minimize_and_restore() {
+        let mut generator = FrameInputGenerator::new(
+            PhysicalSize::new(800, 600),
+            2.0,
+            FrameInputConfig::default(),
+        );
+        assert!(!generator.is_minimized());
+
+        generator.handle_winit_window_event(&WindowEvent::Resized(PhysicalSize::new(0, 0)));
+        assert!(generator.is_minimized());
+        assert_eq!(generator.window_width, 0);
+        assert_eq!(generator.window_height, 0);
+
+        generator.handle_winit_window_event(&WindowEvent::Resized(PhysicalSize::new(800, 600)));
+        assert!(!generator.is_minimized());
+        assert_eq!(generator.viewport.width, 800);
+        assert_eq!(generator.viewport.height, 600);
+        assert_eq!(generator.window_width, 400);
+        assert_eq!(generator.window_height, 300);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_mouse_input_before_cursor_moved() {
+        let mut generator = FrameInputGenerator::new(
+            PhysicalSize::new(800, 600),
+            2.0,
+            FrameInputConfig::default(),
+        );
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+        generator.handle_winit_window_event(&WindowEvent::MouseWheel {
+            device_id,
+            delta: winit::event::MouseScrollDelta::LineDelta(0.0, 1.0),
+            phase: TouchPhase::Moved,
+            modifiers: Default::default(),
+        });
+        generator.handle_winit_window_event(&WindowEvent::MouseInput {
+            device_id,
+            state: winit::event::ElementState::Pressed,
+            button: winit::event::MouseButton::Left,
+            modifiers: Default::default(),
+        });
+        assert_eq!(generator.events.len(), 2);
+        match &generator.events[0] {
+            Event::MouseWheel {
+                delta, position, ..
+            } => {
+                assert_eq!(*delta, (0.0, 24.0));
+                // The center of the window
+                assert_eq!((position.x, position.y), (200.0, 150.0));
+            }
+            _ => panic!("expected a mouse wheel event"),
+        }
+        assert!(matches!(
+            generator.events[1],
+            Event::MousePress {
+                button: MouseButton::Left,
+                ..
+            }
+        ));
+
+        // The last known position is used after the cursor leaves the window
+        generator.handle_winit_window_event(&WindowEvent::CursorMoved {
+            device_id,
+            position: winit::dpi::PhysicalPosition::new(100.0, 50.0),
+            modifiers: Default::default(),
+        });
+        generator.handle_winit_window_event(&WindowEvent::CursorLeft { device_id });
+        generator.handle_winit_window_event(&WindowEvent::MouseWheel {
+            device_id,
+            delta: winit::event::MouseScrollDelta::LineDelta(0.0, -1.0),
+            phase: TouchPhase::Moved,
+            modifiers: Default::default(),
+        });
+        match generator.events.last() {
+            Some(Event::MouseWheel { position, .. }) => {
+                assert_eq!((position.x, position.y), (50.0, 25.0))
+            }
+            _ => panic!("expected a mouse wheel event"),
+        }
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_touch_emulation() {
+        use winit::event::{ElementState, KeyboardInput, VirtualKeyCode};
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+        let generator = |touch_emulation| {
+            FrameInputGenerator::new(
+                PhysicalSize::new(800, 600),
+                2.0,
+                FrameInputConfig {
+                    touch_emulation,
+                    ..Default::default()
+                },
+            )
+        };
+        let cursor = |x, y| WindowEvent::CursorMoved {
+            device_id,
+            position: PhysicalPosition::new(x, y),
+            modifiers: Default::default(),
+        };
+        let button = |state| WindowEvent::MouseInput {
+            device_id,
+            state,
+            button: winit::event::MouseButton::Left,
+            modifiers: Default::default(),
+        };
+        let alt = |state| WindowEvent::KeyboardInput {
+            device_id,
+            input: KeyboardInput {
+                scancode: 0,
+                state,
+                virtual_keycode: Some(VirtualKeyCode::LAlt),
+                modifiers: Default::default(),
+            },
+            is_synthetic: false,
+        };
+        let touch = |id, phase, x, y| {
+            WindowEvent::Touch(winit::event::Touch {
+                device_id,
+                phase,
+                location: PhysicalPosition::new(x, y),
+                force: None,
+                id,
+            })
+        };
+
+        // Hold alt at (400, 300), then pinch and twist with the cursor starting at (500, 300)
+        let mut emulated = generator(Some(TouchEmulation::default()));
+        for event in [
+            cursor(400.0, 300.0),
+            alt(ElementState::Pressed),
+            cursor(500.0, 300.0),
+            button(ElementState::Pressed),
+            cursor(550.0, 300.0),
+            cursor(500.0, 350.0),
+        ] {
+            emulated.handle_winit_window_event(&event);
+        }
+        assert_eq!(emulated.emulated_touches().len(), 2);
+        assert_eq!(
+            (
+                emulated.emulated_touches()[1].x,
+                emulated.emulated_touches()[1].y
+            ),
+            (150.0, 125.0)
+        );
+        for event in [
+            alt(ElementState::Released),
+            cursor(510.0, 350.0),
+            button(ElementState::Released),
+        ] {
+            emulated.handle_winit_window_event(&event);
+        }
+        assert!(emulated.emulated_touches().is_empty());
+
+        let mut real = generator(None);
+        for event in [
+            alt(ElementState::Pressed),
+            touch(0, TouchPhase::Started, 500.0, 300.0),
+            touch(1, TouchPhase::Started, 300.0, 300.0),
+            touch(0, TouchPhase::Moved, 550.0, 300.0),
+            touch(1, TouchPhase::Moved, 250.0, 300.0),
+            touch(0, TouchPhase::Moved, 500.0, 350.0),
+            touch(1, TouchPhase::Moved, 300.0, 250.0),
+            touch(1, TouchPhase::Ended, 300.0, 250.0),
+            alt(ElementState::Released),
+            touch(0, TouchPhase::Moved, 510.0, 350.0),
+            touch(0, TouchPhase::Ended, 510.0, 350.0),
+        ] {
+            real.handle_winit_window_event(&event);
+        }
+        assert_eq!(
+            format!("{:?}", emulated.events),
+            format!("{:?}", real.events)
+        );
+        assert!(emulated
+            .events
+            .iter()
+            .any(|event| matches!(event, Event::MouseWheel { .. })));
+    }
+
+    ///
+    /// Replays the text input cases in the given fixture through a generator and returns the name, expected and actual text events of each case.
+    /// The fixtures are written by hand and not recorded from real devices, so passing tests only show that the generator handles the documented event sequences.
+    /// Recordings from real Windows, Linux and macOS devices are still needed, see the header of each fixture.
+    ///
+    #[allow(deprecated)]
+    fn replay_text_input(fixture: &str, is_macos: bool) -> Vec<(String, Vec<String>, Vec<String>)> {
+        use winit::event::{ElementState, KeyboardInput, VirtualKeyCode};
+        let device_id = unsafe { winit::event::DeviceId::dummy() };
+        let key = |state, name: &str| {
+            let virtual_keycode = match name {
+                "dead" => None,
+                "LControl" => Some(VirtualKeyCode::LControl),
+                "LAlt" => Some(VirtualKeyCode::LAlt),
+                "RAlt" => Some(VirtualKeyCode::RAlt),
+                "LWin" => Some(VirtualKeyCode::LWin),
+                "Space" => Some(VirtualKeyCode::Space),
+                "Return" => Some(VirtualKeyCode::Return),
+                "Escape" => Some(VirtualKeyCode::Escape),
+                "Key2" => Some(VirtualKeyCode::Key2),
+                "A" => Some(VirtualKeyCode::A),
+                "C" => Some(VirtualKeyCode::C),
+                "E" => Some(VirtualKeyCode::E),
+                "I" => Some(VirtualKeyCode::I),
+                "K" => Some(VirtualKeyCode::K),
+                "N" => Some(VirtualKeyCode::N),
+                "Q" => Some(VirtualKeyCode::Q),
+                "V" => Some(VirtualKeyCode::V),
+                "X" => Some(VirtualKeyCode::X),
+                _ => panic!("unknown key {}", name),
+            };
+            WindowEvent::KeyboardInput {
+                device_id,
+                input: KeyboardInput {
+                    scancode: 0,
+                    state,
+                    virtual_keycode,
+                    modifiers: Default::default(),
+                },
+                is_synthetic: false,
+            }
+        };
+
+        let mut cases = Vec::new();
+        for case in fixture.split("\n\n").skip(1) {
+            let mut generator = FrameInputGenerator::new(
+                PhysicalSize::new(800, 600),
+                1.0,
+                FrameInputConfig::default(),
+            );
+            generator.is_macos = is_macos;
+            let mut lines = case.lines();
+            let name = lines.next().unwrap().trim_start_matches("# ").to_string();
+            let mut expected = Vec::new();
+            for line in lines {
+                let (command, argument) = line.split_once(' ').unwrap_or((line, ""));
+                let event = match command {
+                    "press" => key(ElementState::Pressed, argument),
+                    "release" => key(ElementState::Released, argument),
+                    "char" => WindowEvent::ReceivedCharacter(
+                        match argument.strip_prefix("U+") {
+                            Some(code) => char::from_u32(u32::from_str_radix(code, 16).unwrap()),
+                            None => argument.chars().next(),
+                        }
+                        .unwrap(),
+                    ),
+                    "preedit" => WindowEvent::Ime(Ime::Preedit(argument.to_string(), None)),
+                    "commit" => WindowEvent::Ime(Ime::Commit(argument.to_string())),
+                    "ime" => WindowEvent::Ime(Ime::Enabled),
+                    "text" => {
+                        expected.push(argument.to_string());
+                        continue;
+                    }
+                    _ => panic!("unknown command {} in case {}", command, name),
+                };
+                generator.handle_winit_window_event(&event);
+            }
+            let actual = generator
+                .events
+                .iter()
+                .filter_map(|event| match event {
+                    Event::Text(text) => Some(text.clone()),
+                    _ => None,
+                })
+                .collect();
+            cases.push((name, expected, actual));
+        }
+        cases
+    }
+
+    #[test]
+    fn test_text_input() {
+        for (fixture, is_macos) in [
+            (include_str!("fixtures/windows.txt"), false),
+            (include_str!("fixtures/linux.txt"), false),
+            (include_str!("fixtures/macos.txt"), true),
+        ] {
+            let cases = replay_text_input(fixture, is_macos);
+            assert!(!cases.is_empty());
+            for (name, expected, actual) in cases {
+                assert_eq!(expected, actual, "{}", name);
+            }
+        }
+    }
+
+    #[cfg(all(feature = "image", not(target_arch = "wasm32")))]
+    #[test]
+    fn test_resize_linear_correct() {
+        let img = image::RgbaImage::from_fn(2, 1, |x, _| {
+            if x == 0 {
+                image::Rgba([0, 0, 0, 255])
+            } else {
+                image::Rgba([255, 255, 255, 255])
+            }
+        });
+        let resized = resize_linear_correct(&img, 1, 1);
+        // Half of the light intensity, which is not the same as the average of the sRGB values (128)
+        assert_eq!(resized.get_pixel(0, 0).0, [188, 188, 188, 255]);
+        for value in 0..=255 {
+            assert_eq!(srgb_from_linear(linear_from_srgb(value)), value);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use crate::core::*;
+
+///
+/// Keeps the content of the screen from one frame to the next when [FrameOutput::preserve_screen](crate::FrameOutput::preserve_screen) is true.
+/// The content of the back buffer is undefined after the buffers are swapped on most platforms (and cleared by the browser on web),
+/// so the color of the screen is copied into an accumulation texture before the buffers are swapped and copied back to the screen before the next frame is rendered.
+///
+pub(super) struct PreservedScreen {
+    viewport: Viewport,
+    color: Option<Texture2D>,
+}
+
+impl PreservedScreen {
+    pub fn new() -> Self {
+        Self {
+            viewport: Viewport::new_at_origo(1, 1),
+            color: None,
+        }
+    }
+
+    ///
+    /// Copies the preserved content, if any, to the screen before rendering a frame with the given viewport.
+    /// The preserved content is discarded if the size of the screen has changed since it was preserved.
+    ///
+    pub fn begin_frame(&mut self, context: &Context, viewport: Viewport) {
+        self.viewport = viewport;
+        if self.color.as_ref().map_or(false, |color| {
+            color.width() != viewport.width || color.height() != viewport.height
+        }) {
+            self.color = None;
+        }
+        if let Some(color) = &self.color {
+            RenderTarget::screen(context, viewport.width, viewport.height).copy_from_color(
+                ColorTexture::Single(color),
+                viewport,
+                WriteMask::COLOR,
+            );
+        }
+    }
+
+    ///
+    /// Copies the content of the screen into the accumulation texture if `preserve` is true, otherwise the accumulation texture is released.
+    /// Must be called before the buffers are swapped.
+    ///
+    pub fn end_frame(&mut self, context: &Context, preserve: bool) {
+        if !preserve {
+            self.color = None;
+            return;
+        }
+        let viewport = self.viewport;
+        self.color
+            .get_or_insert_with(|| {
+                Texture2D::new_empty::<[u8; 4]>(
+                    context,
+                    viewport.width,
+                    viewport.height,
+                    Interpolation::Nearest,
+                    Interpolation::Nearest,
+                    None,
+                    Wrapping::ClampToEdge,
+                    Wrapping::ClampToEdge,
+                )
+            })
+            .as_color_target(None)
+            .blit_from_screen();
+    }
+}
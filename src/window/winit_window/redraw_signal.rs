@@ -0,0 +1,62 @@
+use std::sync::{Arc, Mutex};
+use std::task::{Wake, Waker};
+use winit::event_loop::EventLoopProxy;
+
+///
+/// A handle which wakes up the render loop of a [Window](super::Window) and makes it render a new frame, see [Window::redraw_signal](super::Window::redraw_signal).
+///
+/// The handle can be cloned and sent to other threads or async tasks, for example a task which receives data over the network,
+/// such that the render loop can wait for new events using [FrameOutput::wait_next_event](crate::FrameOutput::wait_next_event)
+/// and still render as soon as new data is available, without polling.
+///
+/// Not available on web, since the event loop proxy of [winit](https://crates.io/crates/winit) cannot be sent between threads there.
+///
+#[derive(Clone)]
+pub struct RedrawSignal {
+    proxy: Arc<Mutex<EventLoopProxy<()>>>,
+}
+
+impl RedrawSignal {
+    pub(super) fn new(proxy: EventLoopProxy<()>) -> Self {
+        Self {
+            proxy: Arc::new(Mutex::new(proxy)),
+        }
+    }
+
+    ///
+    /// Requests a new frame. Does nothing if the render loop has stopped.
+    ///
+    pub fn request_redraw(&self) {
+        // Fails only if the event loop no longer exists, in which case there is nothing to redraw
+        let _ = self.proxy.lock().unwrap().send_event(());
+    }
+
+    ///
+    /// Returns a [Waker] which requests a new frame when woken, which is used to poll the frame futures in [Window::render_loop_async](super::Window::render_loop_async).
+    ///
+    pub(super) fn waker(&self) -> Waker {
+        Waker::from(Arc::new(self.clone()))
+    }
+}
+
+impl Wake for RedrawSignal {
+    fn wake(self: Arc<Self>) {
+        self.request_redraw();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.request_redraw();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_redraw_signal_is_send_and_sync() {
+        // Required for sending the signal to other threads and for building a waker from it
+        fn assert_send_sync<T: Send + Sync + 'static>() {}
+        assert_send_sync::<RedrawSignal>();
+    }
+}
@@ -40,6 +40,10 @@ pub struct SurfaceSettings {
     /// Specify whether or not hardware acceleration is preferred, required, or
     /// off. The default is [HardwareAcceleration::Preferred].
     pub hardware_acceleration: HardwareAcceleration,
+    /// Request an alpha channel in the surface which is used by the window compositor to blend the rendered image with whatever is behind the window.
+    /// The compositor expects the colors to be premultiplied with alpha, see [WindowSettings::transparent].
+    /// The default is false.
+    pub transparent: bool,
 }
 
 impl Default for SurfaceSettings {
@@ -50,6 +54,7 @@ impl Default for SurfaceSettings {
             stencil_buffer: 0,
             multisamples: 4,
             hardware_acceleration: HardwareAcceleration::Preferred,
+            transparent: false,
         }
     }
 }
@@ -74,10 +79,34 @@ pub struct WindowSettings {
     /// If `None` is specified, the canvas will be resized to the same size as
     /// the owner `Window`'s inner width and height.
     pub max_size: Option<(u32, u32)>,
-    /// Borderless mode.
+    /// Whether or not the window should have decorations like a border and a title bar.
+    /// The default is true.
+    ///
+    /// On web this has no effect.
+    pub decorations: bool,
+    /// Borderless mode, which removes the decorations of the window if true.
     ///
     /// On web this has no effect.
+    #[deprecated(note = "use `decorations: false` instead")]
     pub borderless: bool,
+    /// Whether or not the window should be placed above all other windows.
+    /// The default is false.
+    ///
+    /// On web this has no effect.
+    pub always_on_top: bool,
+    /// Makes the window background transparent such that the desktop (or other windows) behind it are visible
+    /// wherever the alpha value of the rendered image is less than one, for example by clearing with an alpha value of 0.
+    /// This also requests an alpha channel in the surface, see [SurfaceSettings::transparent].
+    /// The default is false.
+    ///
+    /// The window compositor (and the browser) expects the colors in the window to be premultiplied with alpha,
+    /// ie. a half transparent red should be written as `(0.5, 0.0, 0.0, 0.5)` and not `(1.0, 0.0, 0.0, 0.5)`.
+    /// Therefore the clear color should be premultiplied (see [ClearState](crate::ClearState)) and transparent objects should be rendered
+    /// using [Blend::PREMULTIPLIED_TRANSPARENCY](crate::Blend::PREMULTIPLIED_TRANSPARENCY) which also writes the alpha value needed by the compositor.
+    /// Opaque objects need no special handling.
+    ///
+    /// Transparency is supported on Windows, MacOS, Wayland and X11 with a compositing window manager and on web.
+    pub transparent: bool,
     /// An optional [canvas element][web_sys::HtmlCanvasElement] for using as winit window.
     /// If this is `None`, the DOM (`index.html`) must contain a canvas element
     #[cfg(target_arch = "wasm32")]
@@ -87,12 +116,16 @@ pub struct WindowSettings {
     pub surface_settings: SurfaceSettings,
 }
 impl Default for WindowSettings {
+    #[allow(deprecated)]
     fn default() -> Self {
         Self {
             title: "".to_string(),
             min_size: (2, 2),
             max_size: None,
+            decorations: true,
             borderless: false,
+            always_on_top: false,
+            transparent: false,
             #[cfg(target_arch = "wasm32")]
             canvas: None,
             surface_settings: SurfaceSettings::default(),
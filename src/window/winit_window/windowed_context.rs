@@ -20,6 +20,7 @@ mod inner {
         pub stencil: bool,
         pub willReadFrequently: bool,
         pub alpha: bool,
+        pub premultipliedAlpha: bool,
     }
 
     /// A context used for rendering
@@ -48,7 +49,8 @@ mod inner {
                             HardwareAcceleration::Preferred => false,
                             HardwareAcceleration::Off => true,
                         },
-                        alpha: false,
+                        alpha: settings.transparent,
+                        premultipliedAlpha: true,
                     })
                     .unwrap(),
                 )
@@ -160,6 +162,12 @@ mod inner {
             } else {
                 config_template
             };
+            // the compositor needs an alpha channel to be able to blend the window with the desktop behind it
+            let config_template = if settings.transparent {
+                config_template.with_alpha_size(8).with_transparency(true)
+            } else {
+                config_template
+            };
             let config_template = config_template
                 .with_stencil_size(settings.stencil_buffer)
                 .compatible_with_native_window(raw_window_handle)